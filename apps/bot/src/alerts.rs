@@ -0,0 +1,168 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serenity::all::{CreateMessage, Http, UserId};
+use stock::{PriceSource, StoredAlert, SymbolStore, alert::Alert, calendar};
+
+use tracing::{debug, info, instrument, warn};
+
+use crate::i18n::{self, Locale, MessageKey};
+
+/// Check every user's price alerts against the latest trade and DM the owner
+/// of each one that fires. An alert's new state is saved before its DM goes
+/// out, so a user with closed DMs isn't retried every run, and only while
+/// the alert still reads as the run found it: one its owner changed or
+/// deleted meanwhile is left alone and not sent. Move alerts fire once and
+/// are then removed. Outside regular trading hours this does nothing; the
+/// schedule also covers the hours either side of the session.
+#[instrument(name = "run_alerts", skip(http, price_client, symbol_store))]
+pub async fn run_alerts(
+    http: Arc<Http>,
+    price_client: Arc<dyn PriceSource>,
+    symbol_store: Arc<SymbolStore>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if !calendar::is_regular_hours(now) {
+        debug!("outside regular trading hours, skipping");
+        return Ok(());
+    }
+
+    let mut prices: HashMap<String, Option<f64>> = HashMap::new();
+    let mut checked: usize = 0;
+    let mut fired: usize = 0;
+
+    for user_id in symbol_store.list_alert_users().await? {
+        let alerts = match symbol_store.list_stored_alerts(user_id).await {
+            Ok(alerts) => alerts,
+            Err(e) => {
+                warn!(user_id, error = ?e, "failed to load alerts");
+                continue;
+            }
+        };
+        let mut locale = None;
+
+        for stored in alerts {
+            let mut alert = stored.alert.clone();
+            if alert.is_dormant() {
+                continue;
+            }
+
+            if !prices.contains_key(&alert.symbol) {
+                let price = match price_client.fetch_snapshot(&alert.symbol).await {
+                    Ok(snapshot) => snapshot.and_then(|s| s.price()),
+                    Err(e) => {
+                        warn!(symbol = %alert.symbol, error = ?e, "snapshot fetch failed");
+                        None
+                    }
+                };
+                prices.insert(alert.symbol.clone(), price);
+            }
+            let Some(price) = prices[&alert.symbol] else {
+                debug!(symbol = %alert.symbol, "no price, skipping");
+                continue;
+            };
+
+            checked += 1;
+            if !alert.evaluate(price, now) {
+                if alert != stored.alert {
+                    settle(&symbol_store, user_id, &stored, Some(&alert)).await;
+                }
+                continue;
+            }
+
+            fired += 1;
+            info!(user_id, alert_id = %alert.id, %alert, price, "alert fired");
+            let next = if alert.move_pct.is_some() && alert.is_dormant() {
+                None
+            } else {
+                Some(&alert)
+            };
+            // settled first, so an alert deleted meanwhile doesn't DM
+            if !settle(&symbol_store, user_id, &stored, next).await {
+                continue;
+            }
+
+            let locale = match locale {
+                Some(locale) => locale,
+                None => *locale.insert(user_locale(&symbol_store, user_id).await),
+            };
+            if let Err(e) = UserId::new(user_id)
+                .direct_message(
+                    &*http,
+                    CreateMessage::new().content(fired_message(locale, &alert, price)),
+                )
+                .await
+            {
+                warn!(user_id, error = ?e, "failed to DM alert");
+            }
+        }
+    }
+
+    info!(checked, fired, symbols = prices.len(), "alert run complete");
+    Ok(())
+}
+
+/// The DM for `alert` firing at `price`.
+pub fn fired_message(locale: Locale, alert: &Alert, price: f64) -> String {
+    match alert.move_pct {
+        Some(_) => i18n::tr(
+            locale,
+            MessageKey::AlertMoveFired,
+            &[
+                &alert.symbol,
+                &format!("{:+.2}%", (price - alert.price) / alert.price * 100.0),
+                &format!("{:.2}", alert.price),
+                &format!("{price:.2}"),
+            ],
+        ),
+        None => i18n::tr(
+            locale,
+            MessageKey::AlertFired,
+            &[
+                &alert.symbol,
+                &alert.direction.as_str(),
+                &format!("{:.2}", alert.price),
+                &format!("{price:.2}"),
+            ],
+        ),
+    }
+}
+
+/// Language of `user_id`'s alert DMs: the one they last set an alert in,
+/// else English.
+async fn user_locale(symbol_store: &SymbolStore, user_id: u64) -> Locale {
+    match symbol_store.get_user_pref(user_id).await {
+        Ok(prefs) => prefs
+            .locale
+            .as_deref()
+            .and_then(Locale::from_tag)
+            .unwrap_or_default(),
+        Err(e) => {
+            warn!(user_id, error = ?e, "failed to load alert locale");
+            Locale::default()
+        }
+    }
+}
+
+/// Write back an alert's new state, `None` removing it. False when it
+/// wasn't written: the owner changed or deleted the alert during the run,
+/// or the store failed.
+async fn settle(
+    symbol_store: &SymbolStore,
+    user_id: u64,
+    stored: &StoredAlert,
+    next: Option<&Alert>,
+) -> bool {
+    match symbol_store.settle_alert(user_id, stored, next).await {
+        Ok(true) => true,
+        Ok(false) => {
+            debug!(user_id, alert_id = %stored.alert.id, "alert changed during the run, left as is");
+            false
+        }
+        Err(e) => {
+            warn!(user_id, alert_id = %stored.alert.id, error = ?e, "failed to save alert state");
+            false
+        }
+    }
+}
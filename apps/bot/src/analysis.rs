@@ -0,0 +1,302 @@
+//! The `/stock analyze` report: every indicator for one symbol, assembled
+//! from daily bars so it can be built and checked without Discord.
+
+use chrono::{Duration, NaiveDate};
+use stock::{
+    Bar,
+    earnings::EarningsSource,
+    indicators::{
+        atr,
+        cdc::{self, Signal},
+        macd::{self, MacdState},
+        rsi,
+    },
+};
+
+use tracing::warn;
+
+use crate::{
+    fmt,
+    i18n::{Locale, MessageKey, tr},
+};
+
+/// Sessions in the trailing 52-week range.
+pub const RANGE_BARS: usize = 252;
+/// Fewest bars the 52-week range is shown for.
+pub const MIN_RANGE_BARS: usize = 20;
+/// Sessions averaged for the volume comparison, not counting the latest.
+pub const VOLUME_AVERAGE_BARS: usize = 20;
+/// ATRs between the close and the suggested stop.
+pub const STOP_ATR_MULTIPLE: f64 = 2.0;
+/// Days ahead the earnings calendar is searched for the next report.
+pub const EARNINGS_LOOKAHEAD_DAYS: i64 = 120;
+
+/// Everything `/stock analyze` shows for a symbol. Readings the history is
+/// too short for are None and left out of the reply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisReport {
+    pub symbol: String,
+    pub close: f64,
+    /// Change from the previous close, in percent.
+    pub change_pct: Option<f64>,
+    pub signal: Option<Signal>,
+    pub rsi: Option<f64>,
+    pub macd: Option<MacdReading>,
+    pub stop: Option<StopSuggestion>,
+    pub range: Option<RangePosition>,
+    pub volume: Option<VolumeReading>,
+    /// Date of the next earnings report, when a calendar knows it. Not in
+    /// the bars, so [`build`] leaves it None; see [`next_earnings`].
+    pub next_earnings: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdReading {
+    pub state: MacdState,
+    pub histogram: f64,
+}
+
+/// A stop [`STOP_ATR_MULTIPLE`] ATRs from the close: below it in a bullish
+/// zone, above it in a bearish one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StopSuggestion {
+    pub price: f64,
+    pub atr: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangePosition {
+    pub low: f64,
+    pub high: f64,
+    /// Where the close sits, 0 at the low and 1 at the high.
+    pub position: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeReading {
+    pub latest: f64,
+    pub average: f64,
+}
+
+impl VolumeReading {
+    pub fn ratio(&self) -> f64 {
+        self.latest / self.average
+    }
+}
+
+/// Build the report for `symbol` from its daily `bars`, oldest first. None
+/// when there are no bars at all.
+pub fn build(symbol: &str, bars: &[Bar], band_pct: f64) -> Option<AnalysisReport> {
+    let last = bars.last()?;
+    let n = bars.len();
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+    let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+
+    let change_pct = n
+        .checked_sub(2)
+        .map(|i| closes[i])
+        .filter(|prev| *prev != 0.0)
+        .map(|prev| (last.close / prev - 1.0) * 100.0);
+
+    let signal = (n >= macd::SLOW_PERIOD).then(|| cdc::calculate(&closes, band_pct).0);
+
+    let rsi = latest(&rsi::calculate(&closes, rsi::DEFAULT_PERIOD));
+
+    let (line, signal_line, histogram) = macd::calculate(
+        &closes,
+        macd::FAST_PERIOD,
+        macd::SLOW_PERIOD,
+        macd::SIGNAL_PERIOD,
+    );
+    let macd = macd::state(&line, &signal_line)
+        .zip(latest(&histogram))
+        .map(|(state, histogram)| MacdReading { state, histogram });
+
+    let stop = latest(&atr::calculate(&highs, &lows, &closes, atr::DEFAULT_PERIOD)).map(|atr| {
+        let offset = STOP_ATR_MULTIPLE * atr;
+        let price = match signal {
+            Some(Signal::Sell | Signal::BearishZone) => last.close + offset,
+            _ => last.close - offset,
+        };
+        StopSuggestion { price, atr }
+    });
+
+    let range = (n >= MIN_RANGE_BARS)
+        .then(|| {
+            let window = &bars[n.saturating_sub(RANGE_BARS)..];
+            let low = window.iter().map(|b| b.low).fold(f64::INFINITY, f64::min);
+            let high = window
+                .iter()
+                .map(|b| b.high)
+                .fold(f64::NEG_INFINITY, f64::max);
+            (high > low).then(|| RangePosition {
+                low,
+                high,
+                position: ((last.close - low) / (high - low)).clamp(0.0, 1.0),
+            })
+        })
+        .flatten();
+
+    let volume = (n > VOLUME_AVERAGE_BARS)
+        .then(|| {
+            let window = &bars[n - 1 - VOLUME_AVERAGE_BARS..n - 1];
+            let average = window.iter().map(|b| b.volume).sum::<f64>() / VOLUME_AVERAGE_BARS as f64;
+            (average > 0.0).then_some(VolumeReading {
+                latest: last.volume,
+                average,
+            })
+        })
+        .flatten();
+
+    Some(AnalysisReport {
+        symbol: symbol.to_uppercase(),
+        close: last.close,
+        change_pct,
+        signal,
+        rsi,
+        macd,
+        stop,
+        range,
+        volume,
+        next_earnings: None,
+    })
+}
+
+/// The first report of `symbol` from `today` on within
+/// [`EARNINGS_LOOKAHEAD_DAYS`]. None when there isn't one or the calendar
+/// can't be reached, and the field is left out.
+pub async fn next_earnings(
+    source: &dyn EarningsSource,
+    symbol: &str,
+    today: NaiveDate,
+) -> Option<NaiveDate> {
+    let symbols = [symbol.to_string()];
+    let end = today + Duration::days(EARNINGS_LOOKAHEAD_DAYS);
+    match source.upcoming(&symbols, today, end).await {
+        Ok(dates) => dates
+            .into_iter()
+            .filter(|e| e.symbol.eq_ignore_ascii_case(symbol) && e.date >= today)
+            .map(|e| e.date)
+            .min(),
+        Err(e) => {
+            warn!(%symbol, error = ?e, "failed to load earnings calendar");
+            None
+        }
+    }
+}
+
+/// The latest value of `series`, if it has warmed up.
+fn latest(series: &[f64]) -> Option<f64> {
+    series.last().copied().filter(|v| v.is_finite())
+}
+
+impl AnalysisReport {
+    /// `$182.52 (+1.2%)`
+    pub fn headline(&self) -> String {
+        match self.change_pct {
+            Some(change) => format!("{} ({})", fmt::price(self.close), fmt::signed_pct(change)),
+            None => fmt::price(self.close),
+        }
+    }
+
+    /// Embed fields as `(name, value)`, in display order, skipping readings
+    /// the history was too short for.
+    pub fn fields(&self, locale: Locale) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+
+        if let Some(signal) = self.signal {
+            fields.push((
+                tr(locale, MessageKey::AnalyzeZone, &[]),
+                tr(locale, MessageKey::for_signal(signal), &[]),
+            ));
+        }
+
+        if let Some(rsi) = self.rsi {
+            let mut value = format!("{rsi:.1}");
+            let zone = if rsi >= rsi::OVERBOUGHT {
+                Some(MessageKey::RsiOverbought)
+            } else if rsi <= rsi::OVERSOLD {
+                Some(MessageKey::RsiOversold)
+            } else {
+                None
+            };
+            if let Some(zone) = zone {
+                value.push_str(" · ");
+                value.push_str(&tr(locale, zone, &[]));
+            }
+            fields.push((
+                tr(locale, MessageKey::AnalyzeRsi, &[&rsi::DEFAULT_PERIOD]),
+                value,
+            ));
+        }
+
+        if let Some(macd) = self.macd {
+            let state = match macd.state {
+                MacdState::BullishCross => MessageKey::MacdBullishCross,
+                MacdState::BearishCross => MessageKey::MacdBearishCross,
+                MacdState::Bullish => MessageKey::MacdBullish,
+                MacdState::Bearish => MessageKey::MacdBearish,
+            };
+            fields.push((
+                tr(locale, MessageKey::AnalyzeMacd, &[]),
+                format!("{} ({:+.2})", tr(locale, state, &[]), macd.histogram),
+            ));
+        }
+
+        if let Some(stop) = self.stop {
+            let distance = (stop.price / self.close - 1.0) * 100.0;
+            fields.push((
+                tr(locale, MessageKey::AnalyzeStop, &[&STOP_ATR_MULTIPLE]),
+                tr(
+                    locale,
+                    MessageKey::AnalyzeStopValue,
+                    &[
+                        &fmt::price(stop.price),
+                        &fmt::signed_pct(distance),
+                        &fmt::price(stop.atr),
+                    ],
+                ),
+            ));
+        }
+
+        if let Some(range) = self.range {
+            fields.push((
+                tr(locale, MessageKey::AnalyzeRange, &[]),
+                tr(
+                    locale,
+                    MessageKey::AnalyzeRangeValue,
+                    &[
+                        &fmt::price(range.low),
+                        &fmt::price(range.high),
+                        &format!("{:.0}", range.position * 100.0),
+                    ],
+                ),
+            ));
+        }
+
+        if let Some(volume) = self.volume {
+            fields.push((
+                tr(locale, MessageKey::AnalyzeVolume, &[&VOLUME_AVERAGE_BARS]),
+                tr(
+                    locale,
+                    MessageKey::AnalyzeVolumeValue,
+                    &[
+                        &fmt::volume(volume.latest),
+                        &fmt::volume(volume.average),
+                        &format!("{:.1}", volume.ratio()),
+                    ],
+                ),
+            ));
+        }
+
+        if let Some(date) = self.next_earnings {
+            fields.push((
+                tr(locale, MessageKey::AnalyzeEarnings, &[]),
+                date.format("%Y-%m-%d").to_string(),
+            ));
+        }
+
+        fields
+    }
+}
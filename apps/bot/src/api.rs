@@ -0,0 +1,299 @@
+//! Read-only JSON API for dashboards, served when `API_TOKEN` is set.
+//!
+//! Every route but `/api/health` wants `Authorization: Bearer <API_TOKEN>`
+//! and a `scope` query parameter naming the watchlist (`guild:<id>` or
+//! `user:<id>`).
+//! Bars come from the price cache only, so polling the API never spends
+//! Alpaca requests.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::{Path, Query, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::{Duration, Utc};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use stock::{
+    Bar, PriceSource, Scope, StoreCircuits, SymbolMeta, SymbolStore, Timeframe,
+    circuit::{CircuitState, StoreUnavailable},
+    scan::ScanReading,
+    usage::ApiUsage,
+    validate_symbol,
+};
+use tracing::{debug, info, instrument, warn};
+
+/// Days of bars `/bars` returns when `days` isn't given.
+pub const DEFAULT_DAYS: i64 = 90;
+/// Most days of bars one request can ask for.
+pub const MAX_DAYS: i64 = 3650;
+
+/// A watchlist symbol with its metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchlistEntry {
+    pub symbol: String,
+    #[serde(flatten)]
+    pub meta: SymbolMeta,
+}
+
+/// A watchlist symbol with the reading its last scan recorded, None until
+/// it has been scanned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatestSignal {
+    pub symbol: String,
+    pub reading: Option<ScanReading>,
+}
+
+/// Where the API reads watchlists from. [`SymbolStore`] in the bot; tests
+/// stand in their own so the routes can be checked without Redis.
+pub trait WatchlistSource: Send + Sync {
+    /// Every symbol in `scope`, sorted, with its metadata.
+    fn watchlist(&self, scope: Scope) -> BoxFuture<'_, Result<Vec<WatchlistEntry>>>;
+
+    /// Every symbol in `scope`, sorted, with its last scan reading.
+    fn latest_signals(&self, scope: Scope) -> BoxFuture<'_, Result<Vec<LatestSignal>>>;
+
+    /// Where the backend's circuits are at, for `/api/health`. None for a
+    /// source without any.
+    fn circuits(&self) -> Option<StoreCircuits> {
+        None
+    }
+}
+
+impl WatchlistSource for SymbolStore {
+    fn watchlist(&self, scope: Scope) -> BoxFuture<'_, Result<Vec<WatchlistEntry>>> {
+        Box::pin(async move {
+            let mut symbols = self.list(scope).await?;
+            symbols.sort();
+            let mut meta = self.list_meta(scope).await?;
+            Ok(symbols
+                .into_iter()
+                .map(|symbol| WatchlistEntry {
+                    meta: meta.remove(&symbol).unwrap_or_default(),
+                    symbol,
+                })
+                .collect())
+        })
+    }
+
+    fn latest_signals(&self, scope: Scope) -> BoxFuture<'_, Result<Vec<LatestSignal>>> {
+        Box::pin(async move {
+            Ok(self
+                .list_last_readings(scope)
+                .await?
+                .into_iter()
+                .map(|(symbol, reading)| LatestSignal { symbol, reading })
+                .collect())
+        })
+    }
+
+    fn circuits(&self) -> Option<StoreCircuits> {
+        Some(self.circuit_state())
+    }
+}
+
+/// Body of `/api/health`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Health {
+    /// `ok`, or `degraded` while any store circuit isn't closed.
+    pub status: String,
+    pub store: Option<StoreCircuits>,
+    /// Market-data request budget, None for a source without one.
+    pub usage: Option<ApiUsage>,
+}
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub watchlist: Arc<dyn WatchlistSource>,
+    pub prices: Arc<dyn PriceSource>,
+    /// Bearer token every request must carry.
+    pub token: Arc<str>,
+}
+
+/// The API's routes, all but the health check behind the bearer token.
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/api/watchlist", get(watchlist))
+        .route("/api/signals/latest", get(latest_signals))
+        .route("/api/symbols/{symbol}/bars", get(bars))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .route("/api/health", get(health))
+        .with_state(state)
+}
+
+/// Serve [`router`] on `addr` until the process exits.
+#[instrument(name = "api_serve", skip(state))]
+pub async fn serve(addr: &str, state: ApiState) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(addr = %listener.local_addr()?, "api listening");
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// 503 when the store turned the call away, `fallback` otherwise.
+fn store_error(e: &anyhow::Error, fallback: &str) -> Response {
+    if e.downcast_ref::<StoreUnavailable>().is_some() {
+        return error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "watchlist backend temporarily unavailable",
+        );
+    }
+    error(StatusCode::INTERNAL_SERVER_ERROR, fallback)
+}
+
+/// Equal-time comparison, so response timing doesn't leak how much of a
+/// guessed token was right.
+fn token_matches(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match given {
+        Some(token) if token_matches(token.as_bytes(), state.token.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => {
+            warn!(path = %request.uri().path(), "rejected api request without a valid token");
+            error(StatusCode::UNAUTHORIZED, "missing or invalid bearer token")
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ScopeQuery {
+    scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BarsQuery {
+    days: Option<i64>,
+}
+
+/// The watchlist `query` names, or why it doesn't name one.
+fn parse_scope(query: &ScopeQuery) -> Result<Scope, String> {
+    let raw = query.scope.as_deref().ok_or("scope is required")?;
+    raw.parse().map_err(|e: anyhow::Error| e.to_string())
+}
+
+#[instrument(name = "api_watchlist", skip_all, fields(scope = ?query.scope))]
+async fn watchlist(State(state): State<ApiState>, Query(query): Query<ScopeQuery>) -> Response {
+    let scope = match parse_scope(&query) {
+        Ok(scope) => scope,
+        Err(message) => return error(StatusCode::BAD_REQUEST, message),
+    };
+    match state.watchlist.watchlist(scope).await {
+        Ok(entries) => {
+            debug!(count = entries.len(), "served watchlist");
+            Json(entries).into_response()
+        }
+        Err(e) => {
+            warn!(error = ?e, "failed to load watchlist");
+            store_error(&e, "failed to load watchlist")
+        }
+    }
+}
+
+#[instrument(name = "api_latest_signals", skip_all, fields(scope = ?query.scope))]
+async fn latest_signals(
+    State(state): State<ApiState>,
+    Query(query): Query<ScopeQuery>,
+) -> Response {
+    let scope = match parse_scope(&query) {
+        Ok(scope) => scope,
+        Err(message) => return error(StatusCode::BAD_REQUEST, message),
+    };
+    match state.watchlist.latest_signals(scope).await {
+        Ok(signals) => {
+            debug!(count = signals.len(), "served latest signals");
+            Json(signals).into_response()
+        }
+        Err(e) => {
+            warn!(error = ?e, "failed to load latest signals");
+            store_error(&e, "failed to load signals")
+        }
+    }
+}
+
+/// Store circuit states and the request budget, without a token so load
+/// balancers can poll it. 503 while any circuit is open.
+#[instrument(name = "api_health", skip_all)]
+async fn health(State(state): State<ApiState>) -> Response {
+    let store = state.watchlist.circuits();
+    let states = store.map_or(vec![], |c| vec![c.read, c.write]);
+    let (status, code) = if states.contains(&CircuitState::Open) {
+        ("degraded", StatusCode::SERVICE_UNAVAILABLE)
+    } else if states.iter().any(|s| *s != CircuitState::Closed) {
+        ("degraded", StatusCode::OK)
+    } else {
+        ("ok", StatusCode::OK)
+    };
+    debug!(status, "served health");
+    (
+        code,
+        Json(Health {
+            status: status.into(),
+            store,
+            usage: state.prices.usage(),
+        }),
+    )
+        .into_response()
+}
+
+/// Daily bars for the last `days`, from the cache only. Symbols nothing has
+/// fetched lately are 404 rather than a live Alpaca call.
+#[instrument(name = "api_bars", skip_all, fields(%symbol, days = ?query.days))]
+async fn bars(
+    State(state): State<ApiState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<BarsQuery>,
+) -> Response {
+    let symbol = symbol.trim().to_uppercase();
+    if validate_symbol(&symbol).is_err() {
+        return error(StatusCode::NOT_FOUND, format!("unknown symbol {symbol}"));
+    }
+    let days = query.days.unwrap_or(DEFAULT_DAYS);
+    if !(1..=MAX_DAYS).contains(&days) {
+        return error(
+            StatusCode::BAD_REQUEST,
+            format!("days must be between 1 and {MAX_DAYS}"),
+        );
+    }
+
+    let Some(cached) = state.prices.cached_bars(&symbol, Timeframe::Day1) else {
+        debug!("no cached bars");
+        return error(
+            StatusCode::NOT_FOUND,
+            format!("no cached bars for {symbol}"),
+        );
+    };
+
+    let since = Utc::now() - Duration::days(days);
+    let bars: Vec<Bar> = cached
+        .into_iter()
+        .filter(|b| b.timestamp >= since)
+        .collect();
+    debug!(count = bars.len(), "served cached bars");
+    Json(bars).into_response()
+}
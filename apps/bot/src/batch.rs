@@ -0,0 +1,912 @@
+use std::{
+    collections::HashSet,
+    mem::take,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serenity::all::{
+    ChannelId, CreateActionRow, CreateAllowedMentions, CreateAttachment, CreateEmbed,
+    CreateMessage, Error as SerenityError, Http, HttpError, Mentionable, MessageId, StatusCode,
+    UserId,
+};
+use stock::SymbolStore;
+use tracing::{debug, info, warn};
+
+use crate::{
+    Context, Error,
+    discord_text::{check_embed, without_image},
+    i18n::{self, MessageKey, tr},
+    notify::SignalEvent,
+    t,
+};
+
+/// Discord allows at most 10 embeds per message.
+pub const MAX_EMBEDS: usize = 10;
+/// Discord allows at most 10 attachments per message.
+pub const MAX_ATTACHMENTS: usize = 10;
+/// Default cumulative upload ceiling per message. Servers without boosts
+/// reject uploads over 10 MB; staying at 8 MiB leaves headroom for the JSON
+/// payload and multipart framing.
+pub const DEFAULT_MAX_BYTES: usize = 8 * 1024 * 1024;
+/// Tries at one batch before [`IdempotentSink`] gives up on it.
+pub const SEND_ATTEMPTS: u32 = 3;
+/// Wait before the first retry of a batch; doubles after each.
+pub const RETRY_BASE: Duration = Duration::from_secs(2);
+/// How long Discord accepts follow-ups on an interaction's token.
+pub const INTERACTION_LIFETIME: Duration = Duration::from_secs(15 * 60);
+/// How close to the token's expiry sends move to the channel: a send
+/// started just before it would otherwise land just after.
+pub const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// One outgoing message worth of embeds and files.
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    pub embeds: Vec<CreateEmbed>,
+    pub attachments: Vec<CreateAttachment>,
+    /// Structured hits for non-Discord sinks; not rendered in the message.
+    pub events: Vec<SignalEvent>,
+    /// Buttons under the message.
+    pub components: Vec<CreateActionRow>,
+    /// Symbol of the hit each embed shows, in embed order; None for embeds
+    /// that aren't one. Embeds past its end, like a run summary, aren't.
+    pub symbols: Vec<Option<String>>,
+    /// What each queued embed brought along, in embed order, so a split
+    /// keeps files and events with their embed. Embeds past its end brought
+    /// nothing.
+    owns: Vec<Owns>,
+}
+
+/// Whether a queued embed came with a file and an event.
+#[derive(Debug, Clone, Copy)]
+struct Owns {
+    file: bool,
+    event: bool,
+}
+
+impl Batch {
+    pub fn is_empty(&self) -> bool {
+        self.embeds.is_empty() && self.attachments.is_empty()
+    }
+
+    /// Total size of the attached files in bytes.
+    pub fn bytes(&self) -> usize {
+        self.attachments.iter().map(|a| a.data.len()).sum()
+    }
+
+    /// The same embeds with their images dropped and no files, for when
+    /// Discord won't take the upload.
+    pub fn without_attachments(self) -> Batch {
+        Batch {
+            embeds: self.embeds.into_iter().map(without_image).collect(),
+            attachments: Vec::new(),
+            events: self.events,
+            components: self.components,
+            symbols: self.symbols,
+            owns: self
+                .owns
+                .into_iter()
+                .map(|owns| Owns {
+                    file: false,
+                    ..owns
+                })
+                .collect(),
+        }
+    }
+
+    /// The batch without the hits `keep` turns down, each taking its file
+    /// and event along. Embeds that aren't a hit stay.
+    pub fn retain_hits(self, mut keep: impl FnMut(&str) -> bool) -> Batch {
+        let mut kept = Batch {
+            components: self.components,
+            ..Batch::default()
+        };
+        let mut attachments = self.attachments.into_iter();
+        let mut events = self.events.into_iter();
+        for (i, embed) in self.embeds.into_iter().enumerate() {
+            let owns = self.owns.get(i).copied();
+            let file = owns.filter(|o| o.file).and_then(|_| attachments.next());
+            let event = owns.filter(|o| o.event).and_then(|_| events.next());
+            let symbol = self.symbols.get(i).cloned().flatten();
+            if symbol.as_deref().is_some_and(|symbol| !keep(symbol)) {
+                continue;
+            }
+            kept.embeds.push(embed);
+            kept.attachments.extend(file);
+            kept.events.extend(event);
+            kept.owns.extend(owns);
+            if i < self.symbols.len() {
+                kept.symbols.push(symbol);
+            }
+        }
+        kept.attachments.extend(attachments);
+        kept.events.extend(events);
+        kept
+    }
+
+    /// Split into two halves, keeping each embed's file and event with it:
+    /// not every embed has them, so they're cut after the ones the first
+    /// half's embeds brought. The buttons stay under the second.
+    fn split(mut self) -> (Batch, Batch) {
+        let mid = self.embeds.len() / 2;
+        let owns = self.owns.split_off(mid.min(self.owns.len()));
+        let files = self.owns.iter().filter(|o| o.file).count();
+        let events = self.owns.iter().filter(|o| o.event).count();
+
+        let embeds = self.embeds.split_off(mid);
+        let attachments = self
+            .attachments
+            .split_off(files.min(self.attachments.len()));
+        let events = self.events.split_off(events.min(self.events.len()));
+        let components = take(&mut self.components);
+        let symbols = self.symbols.split_off(mid.min(self.symbols.len()));
+        (
+            self,
+            Batch {
+                embeds,
+                attachments,
+                events,
+                components,
+                symbols,
+                owns,
+            },
+        )
+    }
+}
+
+/// Destination for batched scan output.
+pub trait BatchSink {
+    fn send_batch(&self, batch: Batch) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Post a plain text message, e.g. when a scan found nothing.
+    fn send_notice(&self, content: String) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Close out a scan without posting anything visible. Interactive sinks
+    /// still have to answer the deferred interaction so it doesn't hang.
+    fn acknowledge(&self) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+}
+
+/// A hit that went out, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostedHit {
+    pub symbol: String,
+    pub channel: ChannelId,
+    pub message: MessageId,
+    /// Position of the hit's embed in the message.
+    pub embed_index: usize,
+}
+
+/// Posts batches to a channel, used by the scheduled jobs.
+pub struct ChannelSink {
+    pub http: Arc<Http>,
+    pub channel: ChannelId,
+    /// Jump link to the first batch posted, for pointing back at the run.
+    pub first_link: Arc<OnceLock<String>>,
+    /// Every hit posted, for editing its message later.
+    pub posted: Arc<Mutex<Vec<PostedHit>>>,
+    /// Text posted with every message, e.g. what the messages continue.
+    pub header: Option<String>,
+    /// User the header pings, on the first message only.
+    pub ping: Option<UserId>,
+    pinged: Arc<AtomicBool>,
+}
+
+impl ChannelSink {
+    pub fn new(http: Arc<Http>, channel: ChannelId) -> Self {
+        Self {
+            http,
+            channel,
+            first_link: Arc::default(),
+            posted: Arc::default(),
+            header: None,
+            ping: None,
+            pinged: Arc::default(),
+        }
+    }
+
+    pub fn with_header(mut self, header: impl Into<String>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Let the header ping `user` the first time it goes out. Later
+    /// messages mention them without a ping.
+    pub fn pinging(mut self, user: UserId) -> Self {
+        self.ping = Some(user);
+        self
+    }
+
+    /// Who the next header may ping: [`Self::ping`] the first time, nobody
+    /// after that.
+    fn allowed_mentions(&self) -> CreateAllowedMentions {
+        let ping = self
+            .ping
+            .filter(|_| !self.pinged.swap(true, Ordering::Relaxed));
+        CreateAllowedMentions::new().users(ping)
+    }
+}
+
+impl BatchSink for ChannelSink {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        let mut msg = CreateMessage::new()
+            .embeds(batch.embeds)
+            .add_files(batch.attachments)
+            .components(batch.components);
+        if let Some(header) = &self.header {
+            msg = msg
+                .content(header)
+                .allowed_mentions(self.allowed_mentions());
+        }
+        let posted = self.channel.send_message(&self.http, msg).await?;
+        let _ = self.first_link.set(posted.link());
+        let hits = batch
+            .symbols
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, symbol)| {
+                Some(PostedHit {
+                    symbol: symbol?,
+                    channel: self.channel,
+                    message: posted.id,
+                    embed_index: i,
+                })
+            });
+        self.posted.lock().unwrap().extend(hits);
+        Ok(())
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        let msg = match &self.header {
+            Some(header) => CreateMessage::new()
+                .content(format!("{header}\n{content}"))
+                .allowed_mentions(self.allowed_mentions()),
+            None => CreateMessage::new().content(content),
+        };
+        self.channel.send_message(&self.http, msg).await?;
+        Ok(())
+    }
+}
+
+/// Replies to the invoking command through its interaction token.
+pub struct ReplySink<'a> {
+    pub ctx: Context<'a>,
+    /// Whether the command was deferred ephemerally; follow-ups match it.
+    pub ephemeral: bool,
+}
+
+impl BatchSink for ReplySink<'_> {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        self.ctx
+            .send(poise::CreateReply {
+                embeds: batch.embeds,
+                attachments: batch.attachments,
+                components: Some(batch.components).filter(|c| !c.is_empty()),
+                ephemeral: Some(self.ephemeral),
+                ..Default::default()
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        self.ctx
+            .send(
+                poise::CreateReply::default()
+                    .content(content)
+                    .ephemeral(self.ephemeral),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn acknowledge(&self) -> Result<(), Error> {
+        self.ctx
+            .send(
+                poise::CreateReply::default()
+                    .content(t!(self.ctx, MessageKey::ScanComplete))
+                    .ephemeral(true),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Current time, swappable so tests can move it.
+pub type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// Whether an interaction created at `created` is within [`EXPIRY_MARGIN`]
+/// of its token expiring, or past it.
+pub fn token_expiring(created: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    let usable = chrono::Duration::from_std(INTERACTION_LIFETIME - EXPIRY_MARGIN)
+        .expect("interaction lifetime fits a chrono duration");
+    now >= created + usable
+}
+
+/// Replies through the interaction while its token lasts, then posts the
+/// rest to a channel. A long scan can outlive the token's 15 minutes, and
+/// every reply after that fails.
+pub struct InteractionSink<R, C> {
+    reply: R,
+    channel: C,
+    created: DateTime<Utc>,
+    clock: Clock,
+}
+
+impl<R, C> InteractionSink<R, C> {
+    pub fn new(reply: R, channel: C, created: DateTime<Utc>) -> Self {
+        Self {
+            reply,
+            channel,
+            created,
+            clock: Arc::new(Utc::now),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn expiring(&self) -> bool {
+        let expiring = token_expiring(self.created, (self.clock)());
+        if expiring {
+            debug!(created = %self.created, "interaction token expiring, posting to the channel");
+        }
+        expiring
+    }
+}
+
+impl<R: BatchSink + Sync, C: BatchSink + Sync> BatchSink for InteractionSink<R, C> {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        if self.expiring() {
+            self.channel.send_batch(batch).await
+        } else {
+            self.reply.send_batch(batch).await
+        }
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        if self.expiring() {
+            self.channel.send_notice(content).await
+        } else {
+            self.reply.send_notice(content).await
+        }
+    }
+
+    async fn acknowledge(&self) -> Result<(), Error> {
+        // an expired interaction can't be answered any more
+        if self.expiring() {
+            self.channel.acknowledge().await
+        } else {
+            self.reply.acknowledge().await
+        }
+    }
+}
+
+/// `ctx`'s replies, moving to a channel before the token expires. Those
+/// messages say which command and whose results they continue, pinging the
+/// user once. `ephemeral` is how the command was deferred: its replies stay
+/// private, and what's left goes to the user's DMs rather than the channel.
+/// Build it once per command; it reads the locale as it's made.
+pub async fn interaction_sink(
+    ctx: Context<'_>,
+    ephemeral: bool,
+) -> Result<InteractionSink<ReplySink<'_>, ChannelSink>, Error> {
+    let locale = i18n::locale(ctx).await;
+    let header = tr(
+        locale,
+        MessageKey::ResultsContinued,
+        &[&ctx.author().mention(), &ctx.command().qualified_name],
+    );
+    let http = ctx.serenity_context().http.clone();
+    let channel = if ephemeral {
+        ChannelSink::new(
+            http,
+            ctx.author()
+                .create_dm_channel(ctx.serenity_context())
+                .await?
+                .id,
+        )
+    } else {
+        ChannelSink::new(http, ctx.channel_id()).pinging(ctx.author().id)
+    };
+    let created =
+        DateTime::from_timestamp(ctx.created_at().unix_timestamp(), 0).unwrap_or_else(Utc::now);
+    Ok(InteractionSink::new(
+        ReplySink { ctx, ephemeral },
+        channel.with_header(header),
+        created,
+    ))
+}
+
+/// Whether sending again might work: Discord answered with a 5xx, or the
+/// request timed out or couldn't connect. Rejected payloads fail the same
+/// way every time.
+pub fn is_transient(err: &Error) -> bool {
+    match err.downcast_ref::<SerenityError>() {
+        Some(SerenityError::Http(HttpError::UnsuccessfulRequest(res))) => {
+            res.status_code.is_server_error()
+        }
+        Some(SerenityError::Http(HttpError::Request(e))) => e.is_timeout() || e.is_connect(),
+        _ => false,
+    }
+}
+
+/// Whether Discord turned a message down over its files: 413 Payload Too
+/// Large, or error 40005 (request entity too large). The same message
+/// without them would go through.
+pub fn is_upload_rejected(err: &Error) -> bool {
+    match err.downcast_ref::<SerenityError>() {
+        Some(SerenityError::Http(HttpError::UnsuccessfulRequest(res))) => {
+            res.status_code == StatusCode::PAYLOAD_TOO_LARGE || res.error.code == 40005
+        }
+        _ => false,
+    }
+}
+
+/// Whether Discord refused for lack of access to the channel: 403, error
+/// 50001 (Missing Access) or 50013 (Missing Permissions).
+pub fn is_forbidden(err: &Error) -> bool {
+    match err.downcast_ref::<SerenityError>() {
+        Some(SerenityError::Http(HttpError::UnsuccessfulRequest(res))) => {
+            res.status_code == StatusCode::FORBIDDEN || matches!(res.error.code, 50001 | 50013)
+        }
+        _ => false,
+    }
+}
+
+/// Whether Discord doesn't know the channel: 404, or error 10003 (Unknown
+/// Channel), as when it was deleted after being set up.
+pub fn is_unknown_channel(err: &Error) -> bool {
+    match err.downcast_ref::<SerenityError>() {
+        Some(SerenityError::Http(HttpError::UnsuccessfulRequest(res))) => {
+            res.status_code == StatusCode::NOT_FOUND || res.error.code == 10003
+        }
+        _ => false,
+    }
+}
+
+/// Posts to `primary`, and to `fallback` whatever `primary` won't take for
+/// lack of access or because the channel is gone. The first time that
+/// happens `note` goes to `fallback` ahead of the rest, saying why the
+/// messages are there.
+pub struct FallbackSink<P, F> {
+    primary: P,
+    fallback: F,
+    note: String,
+    noted: AtomicBool,
+    fallen_back: Arc<Mutex<HashSet<String>>>,
+}
+
+impl<P, F> FallbackSink<P, F> {
+    pub fn new(primary: P, fallback: F, note: impl Into<String>) -> Self {
+        Self {
+            primary,
+            fallback,
+            note: note.into(),
+            noted: AtomicBool::new(false),
+            fallen_back: Arc::default(),
+        }
+    }
+
+    /// Share the hits already posted to `fallback` with other sinks falling
+    /// back to it, so a hit routed to several unreachable channels shows up
+    /// there once.
+    pub fn sharing(mut self, fallen_back: Arc<Mutex<HashSet<String>>>) -> Self {
+        self.fallen_back = fallen_back;
+        self
+    }
+}
+
+impl<P: BatchSink + Sync, F: BatchSink + Sync> FallbackSink<P, F> {
+    /// Post the note to `fallback` if this is the first refusal.
+    async fn falling_back(&self, err: &Error) -> Result<(), Error> {
+        warn!(error = ?err, "can't post to the channel, posting to the fallback");
+        if !self.noted.swap(true, Ordering::Relaxed) {
+            self.fallback.send_notice(self.note.clone()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `err` sends a message to the fallback.
+fn unreachable(err: &Error) -> bool {
+    is_forbidden(err) || is_unknown_channel(err)
+}
+
+impl<P: BatchSink + Sync, F: BatchSink + Sync> BatchSink for FallbackSink<P, F> {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        match self.primary.send_batch(batch.clone()).await {
+            Err(e) if unreachable(&e) => {
+                self.falling_back(&e).await?;
+                let batch = {
+                    let fallen_back = self.fallen_back.lock().unwrap();
+                    batch.retain_hits(|symbol| !fallen_back.contains(symbol))
+                };
+                if batch.is_empty() {
+                    debug!("every hit already fell back, nothing left to post");
+                    return Ok(());
+                }
+                let symbols: Vec<String> = batch.symbols.iter().flatten().cloned().collect();
+                self.fallback.send_batch(batch).await?;
+                // only once sent, so a retried batch isn't emptied
+                self.fallen_back.lock().unwrap().extend(symbols);
+                Ok(())
+            }
+            sent => sent,
+        }
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        match self.primary.send_notice(content.clone()).await {
+            Err(e) if unreachable(&e) => {
+                self.falling_back(&e).await?;
+                self.fallback.send_notice(content).await
+            }
+            sent => sent,
+        }
+    }
+
+    async fn acknowledge(&self) -> Result<(), Error> {
+        self.primary.acknowledge().await
+    }
+}
+
+/// Remembers which batches of a run were posted, so a retried run can skip
+/// them.
+pub trait BatchLedger {
+    fn is_posted(&self, key: &str) -> impl Future<Output = Result<bool, Error>> + Send;
+
+    fn mark_posted(&self, key: &str) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+impl BatchLedger for Arc<SymbolStore> {
+    async fn is_posted(&self, key: &str) -> Result<bool, Error> {
+        self.is_batch_posted(key).await
+    }
+
+    async fn mark_posted(&self, key: &str) -> Result<(), Error> {
+        self.mark_batch_posted(key).await
+    }
+}
+
+/// Wraps another sink so each hit of a run is posted at most once. Hits are
+/// keyed `{run}:{symbol}` and recorded in the ledger once their batch is
+/// posted; running the same hits again skips the recorded ones, however
+/// they're batched. Embeds that aren't a hit, like a run summary, aren't
+/// recorded. Transient failures are retried with exponential backoff, and
+/// a batch that still fails after [`SEND_ATTEMPTS`] is listed in `failed`
+/// as `{run}:{symbols}`, its symbols sorted.
+pub struct IdempotentSink<S, L> {
+    inner: S,
+    ledger: L,
+    run: String,
+    retry_base: Duration,
+    /// Keys of the batches given up on, for the run summary.
+    pub failed: Arc<Mutex<Vec<String>>>,
+}
+
+impl<S, L> IdempotentSink<S, L> {
+    pub fn new(inner: S, ledger: L, run: impl Into<String>) -> Self {
+        Self {
+            inner,
+            ledger,
+            run: run.into(),
+            retry_base: RETRY_BASE,
+            failed: Arc::default(),
+        }
+    }
+
+    pub fn with_retry_base(mut self, retry_base: Duration) -> Self {
+        self.retry_base = retry_base;
+        self
+    }
+
+    fn hit_key(&self, symbol: &str) -> String {
+        format!("{}:{symbol}", self.run)
+    }
+}
+
+impl<S, L: BatchLedger> IdempotentSink<S, L> {
+    /// The hits of `batch` the ledger says already went out.
+    async fn already_posted(&self, batch: &Batch) -> HashSet<String> {
+        let mut posted = HashSet::new();
+        for symbol in batch.symbols.iter().flatten() {
+            let key = self.hit_key(symbol);
+            match self.ledger.is_posted(&key).await {
+                Ok(true) => {
+                    posted.insert(symbol.clone());
+                }
+                Ok(false) => {}
+                // posting twice beats not posting at all
+                Err(e) => warn!(%key, error = ?e, "failed to check batch ledger"),
+            }
+        }
+        posted
+    }
+}
+
+impl<S: BatchSink + Sync, L: BatchLedger + Sync> BatchSink for IdempotentSink<S, L> {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        let posted = self.already_posted(&batch).await;
+        let batch = if posted.is_empty() {
+            batch
+        } else {
+            info!(run = %self.run, skipped = posted.len(), "hits already posted, skipping them");
+            batch.retain_hits(|symbol| !posted.contains(symbol))
+        };
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let mut symbols: Vec<String> = batch.symbols.iter().flatten().cloned().collect();
+        symbols.sort();
+        let key = self.hit_key(&symbols.join(","));
+
+        let mut delay = self.retry_base;
+        for attempt in 1..=SEND_ATTEMPTS {
+            match self.inner.send_batch(batch.clone()).await {
+                Ok(()) => break,
+                Err(e) if !is_transient(&e) => return Err(e),
+                Err(e) if attempt == SEND_ATTEMPTS => {
+                    self.failed.lock().unwrap().push(key.clone());
+                    return Err(e.context(format!("batch {key} failed {attempt} times")));
+                }
+                Err(e) => {
+                    warn!(%key, attempt, ?delay, error = ?e, "batch send failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+
+        for symbol in &symbols {
+            let key = self.hit_key(symbol);
+            if let Err(e) = self.ledger.mark_posted(&key).await {
+                warn!(%key, error = ?e, "failed to record posted hit");
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        self.inner.send_notice(content).await
+    }
+
+    async fn acknowledge(&self) -> Result<(), Error> {
+        self.inner.acknowledge().await
+    }
+}
+
+/// Accumulates embed/attachment pairs and flushes them to a [`BatchSink`]
+/// before any of Discord's per-message limits (embed count, attachment count,
+/// cumulative upload size) would be exceeded.
+pub struct MessageBatcher<S> {
+    sink: S,
+    pending: Batch,
+    max_bytes: usize,
+    queued: usize,
+    degraded: usize,
+    closing: Vec<CreateActionRow>,
+}
+
+impl<S: BatchSink> MessageBatcher<S> {
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            pending: Batch::default(),
+            max_bytes: DEFAULT_MAX_BYTES,
+            queued: 0,
+            degraded: 0,
+            closing: Vec::new(),
+        }
+    }
+
+    /// Embeds sent without their file so far, because Discord wouldn't take
+    /// the upload.
+    pub fn degraded(&self) -> usize {
+        self.degraded
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Buttons for the last message [`Self::finish`] sends, the one closing
+    /// out the scan. They need embeds to sit under: a scan ending on a plain
+    /// notice, or on a batch that already went out full, goes without.
+    pub fn with_closing_components(mut self, components: Vec<CreateActionRow>) -> Self {
+        self.closing = components;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Queue a hit, with or without an attachment. Flushes the pending batch
+    /// first if the new attachment would push it over the byte ceiling, and
+    /// after adding once the batch is full. An attachment that alone exceeds
+    /// the ceiling can never be sent, so the embed goes out without it. An
+    /// embed over Discord's text limits is rejected here, naming the part
+    /// that's too long, rather than failing the whole batch on send.
+    pub async fn push(
+        &mut self,
+        embed: CreateEmbed,
+        attachment: impl Into<Option<CreateAttachment>>,
+        event: Option<SignalEvent>,
+    ) -> Result<(), Error> {
+        self.queue(None, embed, attachment.into(), event).await
+    }
+
+    /// [`Self::push`] a hit on `symbol`, so the sink can tell where it went.
+    pub async fn push_hit(
+        &mut self,
+        symbol: &str,
+        embed: CreateEmbed,
+        attachment: impl Into<Option<CreateAttachment>>,
+        event: Option<SignalEvent>,
+    ) -> Result<(), Error> {
+        self.queue(Some(symbol.to_string()), embed, attachment.into(), event)
+            .await
+    }
+
+    async fn queue(
+        &mut self,
+        symbol: Option<String>,
+        embed: CreateEmbed,
+        attachment: Option<CreateAttachment>,
+        event: Option<SignalEvent>,
+    ) -> Result<(), Error> {
+        check_embed(&embed)?;
+
+        let attachment = attachment.filter(|attachment| {
+            let size = attachment.data.len();
+            if size > self.max_bytes {
+                warn!(
+                    filename = %attachment.filename,
+                    bytes = size,
+                    max = self.max_bytes,
+                    "attachment over the byte ceiling, sending embed without it"
+                );
+            }
+            size <= self.max_bytes
+        });
+        let size = attachment.as_ref().map_or(0, |a| a.data.len());
+
+        if !self.pending.is_empty() && self.pending.bytes() + size > self.max_bytes {
+            debug!(
+                pending = self.pending.bytes(),
+                incoming = size,
+                max = self.max_bytes,
+                "byte ceiling reached, flushing early"
+            );
+            self.flush().await?;
+        }
+
+        self.pending.owns.push(Owns {
+            file: attachment.is_some(),
+            event: event.is_some(),
+        });
+        self.pending.embeds.push(embed);
+        self.pending.attachments.extend(attachment);
+        self.pending.events.extend(event);
+        self.pending.symbols.push(symbol);
+        self.queued += 1;
+
+        if self.pending.embeds.len() >= MAX_EMBEDS
+            || self.pending.attachments.len() >= MAX_ATTACHMENTS
+        {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Send whatever is pending. A batch whose upload is rejected is sent
+    /// again without its files, the embeds losing their images. Any other
+    /// rejected send is retried once as two halves; a transient failure
+    /// isn't, as smaller messages won't help.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        let batch = take(&mut self.pending);
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        info!(
+            embeds = batch.embeds.len(),
+            bytes = batch.bytes(),
+            "sending batch"
+        );
+
+        let err = match self.sink.send_batch(batch.clone()).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+
+        if is_upload_rejected(&err) && !batch.attachments.is_empty() {
+            warn!(
+                error = ?err,
+                attachments = batch.attachments.len(),
+                "upload rejected, sending batch without attachments"
+            );
+            self.degraded += batch.attachments.len();
+            return self.sink.send_batch(batch.without_attachments()).await;
+        }
+        if batch.embeds.len() < 2 || is_transient(&err) {
+            return Err(err);
+        }
+
+        warn!(error = ?err, "send batch failed, retrying as two halves");
+        let (first, second) = batch.split();
+        for half in [first, second] {
+            self.sink.send_batch(half).await?;
+        }
+        Ok(())
+    }
+
+    /// Flush the remainder and close out the scan. If nothing was ever queued,
+    /// either post `notice` or, when `announce_empty` is off, acknowledge quietly.
+    ///
+    /// `incomplete` is the summary for a scan that couldn't fetch every
+    /// symbol. It always goes out, riding in the last batch when there is one.
+    pub async fn finish(
+        &mut self,
+        notice: String,
+        announce_empty: bool,
+        incomplete: Option<CreateEmbed>,
+    ) -> Result<(), Error> {
+        if self.queued > 0 {
+            // push flushes full batches, so there is always room for one more
+            self.pending.embeds.extend(incomplete);
+            self.pending.components = take(&mut self.closing);
+            return self.flush().await;
+        }
+
+        if announce_empty {
+            info!("no actionable signals found");
+            self.sink.send_notice(notice).await?;
+        } else if incomplete.is_none() {
+            info!("no actionable signals found, notice suppressed");
+            return self.sink.acknowledge().await;
+        }
+
+        if let Some(summary) = incomplete {
+            warn!("scan incomplete, posting summary");
+            self.pending.embeds.push(summary);
+            self.pending.components = take(&mut self.closing);
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// [`Self::finish`] with a summary that needs to know how the sends
+    /// went: `summary` is built from [`Self::degraded`] once the last batch
+    /// of hits is out, and follows it as a message of its own.
+    pub async fn finish_with(
+        &mut self,
+        notice: String,
+        announce_empty: bool,
+        summary: impl FnOnce(usize) -> Option<CreateEmbed>,
+    ) -> Result<(), Error> {
+        if self.queued == 0 {
+            return self.finish(notice, announce_empty, summary(0)).await;
+        }
+
+        if !self.pending.is_empty() {
+            self.pending.components = take(&mut self.closing);
+            self.flush().await?;
+        }
+        if let Some(summary) = summary(self.degraded) {
+            self.pending.embeds.push(summary);
+            self.pending.components = take(&mut self.closing);
+            self.flush().await?;
+        }
+        Ok(())
+    }
+}
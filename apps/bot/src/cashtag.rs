@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use regex::Regex;
+use serenity::all::{Context as SerenityContext, Message};
+use stock::scan::{SignalReading, latest_signal};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Data, Error,
+    i18n::{self, MessageKey, tr},
+    style::SignalStyle,
+};
+
+/// Cashtags acted on per message, so a pasted list can't fan out into a
+/// burst of Alpaca requests.
+pub const MAX_PER_MESSAGE: usize = 2;
+
+/// How long a cached reading is reused before Alpaca is asked again.
+pub const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+static CASHTAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\w$])\$([A-Za-z]{1,5}(?:\.[A-Za-z])?)\b").unwrap());
+
+/// Extract up to [`MAX_PER_MESSAGE`] distinct `$TICKER` tokens, uppercased,
+/// in order of appearance. Dollar amounts like `$100` are not tickers.
+pub fn extract(content: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for caps in CASHTAG.captures_iter(content) {
+        let symbol = caps[1].to_uppercase();
+        if !out.contains(&symbol) {
+            out.push(symbol);
+        }
+        if out.len() == MAX_PER_MESSAGE {
+            break;
+        }
+    }
+    out
+}
+
+/// Per-symbol signal cache so chat activity doesn't hammer Alpaca.
+pub struct SignalCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, SignalReading)>>,
+}
+
+impl Default for SignalCache {
+    fn default() -> Self {
+        Self::new(CACHE_TTL)
+    }
+}
+
+impl SignalCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached reading for `symbol`, if it is younger than the TTL at `now`.
+    pub fn get(&self, symbol: &str, now: Instant) -> Option<SignalReading> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(symbol)
+            .filter(|(at, _)| now.duration_since(*at) < self.ttl)
+            .map(|(_, reading)| *reading)
+    }
+
+    /// Drop every reading. Returns how many there were.
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let evicted = entries.len();
+        entries.clear();
+        evicted
+    }
+
+    pub fn insert(&self, symbol: &str, reading: SignalReading, now: Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (at, _)| now.duration_since(*at) < self.ttl);
+        entries.insert(symbol.to_string(), (now, reading));
+    }
+}
+
+async fn reading(data: &Data, symbol: &str) -> Result<Option<SignalReading>, Error> {
+    if let Some(cached) = data.signal_cache.get(symbol, Instant::now()) {
+        debug!(%symbol, "signal cache hit");
+        return Ok(Some(cached));
+    }
+
+    let reading = latest_signal(
+        data.price_client.as_ref(),
+        symbol,
+        data.config.signal_band_pct,
+    )
+    .await?;
+    if let Some(reading) = reading {
+        data.signal_cache.insert(symbol, reading, Instant::now());
+    }
+    Ok(reading)
+}
+
+/// React to `$TICKER` mentions with the current zone, and optionally reply
+/// with the last close. Only active in guilds that opted in; when the guild
+/// has a daily channel, only there.
+#[instrument(
+    name = "cashtag_message",
+    skip(ctx, data, msg),
+    fields(message_id = %msg.id, channel_id = %msg.channel_id)
+)]
+pub async fn handle_message(
+    ctx: &SerenityContext,
+    data: &Data,
+    msg: &Message,
+) -> Result<(), Error> {
+    if msg.author.bot {
+        return Ok(());
+    }
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    let symbols = extract(&msg.content);
+    if symbols.is_empty() {
+        return Ok(());
+    }
+
+    let settings = data.symbol_store.get_guild_settings(guild_id.get()).await?;
+    if !settings.cashtag_reactions {
+        return Ok(());
+    }
+    if let Some(channel) = settings.daily_channel
+        && channel != msg.channel_id.get()
+    {
+        debug!("not the signals channel");
+        return Ok(());
+    }
+
+    info!(symbols = %symbols.join(", "), "cashtags found");
+
+    let style = SignalStyle::resolve(&data.config.signal_colors, &settings);
+
+    let mut lines = Vec::new();
+    for symbol in symbols {
+        let reading = match reading(data, &symbol).await {
+            Ok(Some(reading)) => reading,
+            Ok(None) => {
+                debug!(%symbol, "no history for cashtag");
+                continue;
+            }
+            Err(e) => {
+                warn!(%symbol, error = ?e, "failed to load cashtag signal");
+                continue;
+            }
+        };
+
+        if let Err(e) = msg.react(ctx, style.reaction(reading.signal)).await {
+            warn!(%symbol, error = ?e, "failed to react");
+        }
+
+        if settings.cashtag_replies {
+            lines.push((symbol, reading));
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let locale = i18n::resolve(&data.symbol_store, Some(guild_id), None).await;
+    let content = lines
+        .into_iter()
+        .map(|(symbol, reading)| {
+            let signal = tr(locale, MessageKey::for_signal(reading.signal), &[]);
+            tr(
+                locale,
+                MessageKey::CashtagLine,
+                &[
+                    &style.emoji(reading.signal),
+                    &symbol,
+                    &format!("{:.2}", reading.close),
+                    &signal,
+                ],
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if let Err(e) = msg.reply(ctx, content).await {
+        warn!(error = ?e, "failed to reply to cashtag");
+    }
+    Ok(())
+}
@@ -1 +1,64 @@
+use ::stock::{InvalidSymbol, RenderTimeout, RequestFailed, circuit::StoreUnavailable, failover};
+use poise::{CreateReply, FrameworkError};
+use tracing::{error, warn};
+
+use crate::{
+    Data, Error,
+    i18n::{self, Locale, MessageKey, tr},
+};
+
 pub mod stock;
+
+/// What a member is told when a command fails with `e`. Only the kind of
+/// failure shows, never the error's own text: Redis and Alpaca errors name
+/// hosts, keys and request details that are for the logs.
+pub fn error_reply(locale: Locale, e: &Error) -> String {
+    let causes = || e.chain();
+    if causes().any(|c| c.is::<StoreUnavailable>()) {
+        return tr(locale, MessageKey::StoreUnavailable, &[]);
+    }
+    if let Some(invalid) = causes().find_map(|c| c.downcast_ref::<InvalidSymbol>()) {
+        return tr(locale, MessageKey::CommandInvalidSymbol, &[&invalid.0]);
+    }
+    if causes().any(|c| c.is::<RenderTimeout>() || c.is::<tokio::time::error::Elapsed>()) {
+        return tr(locale, MessageKey::CommandTimedOut, &[]);
+    }
+    if failover::is_retryable(e) {
+        return tr(locale, MessageKey::CommandDataUnavailable, &[]);
+    }
+    if causes().any(|c| c.is::<RequestFailed>() || c.is::<reqwest::Error>()) {
+        return tr(locale, MessageKey::CommandDataFailed, &[]);
+    }
+    tr(locale, MessageKey::CommandFailed, &[])
+}
+
+/// Central handler for errors a command returned. The full error is
+/// logged and the member gets a short ephemeral reply from
+/// [`error_reply`]. Everything else, like bad arguments or missing
+/// permissions, goes to poise's default handling.
+pub async fn on_error(error: FrameworkError<'_, Data, Error>) {
+    if let FrameworkError::Command { error: e, ctx, .. } = &error {
+        let command = &ctx.command().qualified_name;
+        match e.chain().find_map(|c| c.downcast_ref::<StoreUnavailable>()) {
+            Some(unavailable) => warn!(
+                %command,
+                circuit = unavailable.circuit,
+                retry_in_secs = unavailable.retry_in.as_secs(),
+                "command failed fast, store unavailable"
+            ),
+            None => error!(%command, error = ?e, "command failed"),
+        }
+        let locale = i18n::locale(*ctx).await;
+        let reply = CreateReply::default()
+            .content(error_reply(locale, e))
+            .ephemeral(true);
+        if let Err(e) = ctx.send(reply).await {
+            warn!(error = ?e, "failed to send command error reply");
+        }
+        return;
+    }
+
+    if let Err(e) = poise::builtins::on_error(error).await {
+        error!(error = ?e, "error while handling error");
+    }
+}
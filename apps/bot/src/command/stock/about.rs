@@ -0,0 +1,97 @@
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use stock::calendar::DEFAULT_TIMEZONE;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::MessageKey,
+    invocation, t,
+};
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_about", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn about(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+
+    let watchlist = match data.symbol_store.len(invocation::scope(ctx)).await {
+        Ok(n) => n.to_string(),
+        Err(e) => {
+            warn!(error = ?e, "failed to count watchlist");
+            "?".to_string()
+        }
+    };
+
+    let last_run = match data.symbol_store.get_last_daily_run().await {
+        Ok(Some(at)) => format!(
+            "{} ({})",
+            fmt::time(at, DEFAULT_TIMEZONE, TimeStyle::Full),
+            fmt::time(at, DEFAULT_TIMEZONE, TimeStyle::Relative)
+        ),
+        Ok(None) => t!(ctx, MessageKey::Never),
+        Err(e) => {
+            warn!(error = ?e, "failed to load last daily run");
+            "?".to_string()
+        }
+    };
+
+    let api_usage = match data.price_client.usage() {
+        // counted against what Alpaca reports left, so other processes
+        // sharing the key show up too
+        Some(usage) => format!("{} / {}", usage.limit - usage.remaining, usage.limit),
+        None => "?".to_string(),
+    };
+
+    let uptime = fmt::uptime(data.started_at.elapsed());
+    debug!(%uptime, %watchlist, %api_usage, "collected about info");
+
+    // the whole store's footprint is for whoever runs the bot, not everyone
+    let is_manager = ctx
+        .author_member()
+        .await
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.manage_guild());
+    let store = if is_manager {
+        Some(match data.symbol_store.stats().await {
+            Ok(stats) => {
+                debug!(?stats, "collected store stats");
+                t!(
+                    ctx,
+                    MessageKey::AboutStoreValue,
+                    stats.keys,
+                    stats.memory_bytes.map_or("?".to_string(), fmt::bytes),
+                    stats.symbols,
+                    stats.watchlists,
+                    stats.alerts,
+                    stats.pending_sessions
+                )
+            }
+            Err(e) => {
+                warn!(error = ?e, "failed to collect store stats");
+                "?".to_string()
+            }
+        })
+    } else {
+        None
+    };
+
+    let mut embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::AboutTitle))
+        .field(
+            t!(ctx, MessageKey::AboutVersion),
+            &data.config.version,
+            true,
+        )
+        .field(t!(ctx, MessageKey::AboutUptime), uptime, true)
+        .field(t!(ctx, MessageKey::AboutWatchlist), watchlist, true)
+        .field(t!(ctx, MessageKey::AboutApiUsage), api_usage, true)
+        .field(t!(ctx, MessageKey::AboutLastDailyRun), last_run, false);
+    if let Some(store) = store {
+        embed = embed.field(t!(ctx, MessageKey::AboutStore), store, false);
+    }
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! The "Add symbol" button under the daily summary. It opens a modal for a
+//! ticker and an optional note, so members can add to the watchlist without
+//! looking up the slash command. The ticker goes through the same checks as
+//! `/stock watch`, and the reply says exactly why one was turned down.
+
+use std::{collections::HashMap, sync::Arc};
+
+use poise::serenity_prelude as serenity;
+use serenity::{
+    ActionRowComponent, CreateActionRow, CreateButton, CreateInputText, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateModal, EditInteractionResponse, InputTextStyle,
+};
+use stock::{MAX_NOTE_LEN, Scope, normalize_note};
+use tracing::{debug, info, instrument, warn};
+
+use super::watch::{Rejected, record_added, validate_one};
+use crate::{
+    Data, Error,
+    i18n::{self, Locale, MessageKey, tr},
+    t,
+};
+
+/// Shared by the button, the modal and its inputs.
+pub const COMPONENT_PREFIX: &str = "add_symbol_";
+pub const OPEN_ID: &str = "add_symbol_open";
+pub const MODAL_ID: &str = "add_symbol_modal";
+pub const TICKER_ID: &str = "add_symbol_ticker";
+pub const NOTE_ID: &str = "add_symbol_note";
+
+/// Longest ticker or alias the modal takes.
+const TICKER_LIMIT: u16 = 32;
+
+/// The row holding the "Add symbol" button.
+pub fn button_row(locale: Locale) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(OPEN_ID)
+            .label(tr(locale, MessageKey::AddSymbolButton, &[]))
+            .style(serenity::ButtonStyle::Secondary),
+    ])
+}
+
+fn modal(locale: Locale) -> CreateModal {
+    let ticker = CreateInputText::new(
+        InputTextStyle::Short,
+        tr(locale, MessageKey::AddSymbolTicker, &[]),
+        TICKER_ID,
+    )
+    .placeholder("AAPL")
+    .max_length(TICKER_LIMIT)
+    .required(true);
+    let note = CreateInputText::new(
+        InputTextStyle::Paragraph,
+        tr(locale, MessageKey::AddSymbolNote, &[]),
+        NOTE_ID,
+    )
+    .max_length(MAX_NOTE_LEN as u16)
+    .required(false);
+    CreateModal::new(MODAL_ID, tr(locale, MessageKey::AddSymbolTitle, &[])).components(vec![
+        CreateActionRow::InputText(ticker),
+        CreateActionRow::InputText(note),
+    ])
+}
+
+/// The submitted inputs by custom id. Inputs left blank are missing.
+pub fn submitted(rows: &[serenity::ActionRow]) -> HashMap<&str, &str> {
+    rows.iter()
+        .flat_map(|row| &row.components)
+        .filter_map(|component| match component {
+            ActionRowComponent::InputText(input) => {
+                Some((input.custom_id.as_str(), input.value.as_deref()?))
+            }
+            _ => None,
+        })
+        .filter(|(_, value)| !value.trim().is_empty())
+        .collect()
+}
+
+/// What the reply says about `rejected`.
+pub fn rejection_line(locale: Locale, rejected: &Rejected) -> String {
+    match rejected {
+        Rejected::Empty => tr(locale, MessageKey::NoValidSymbols, &[]),
+        Rejected::Invalid(symbol) => tr(locale, MessageKey::AddSymbolInvalid, &[symbol]),
+        Rejected::Unlisted(symbol) => tr(locale, MessageKey::AddSymbolUnlisted, &[symbol]),
+    }
+}
+
+fn scope_of(guild_id: Option<serenity::GuildId>, user_id: serenity::UserId) -> Scope {
+    match guild_id {
+        Some(guild_id) => Scope::Guild(guild_id.get()),
+        None => Scope::User(user_id.get()),
+    }
+}
+
+/// Open the modal for the "Add symbol" button.
+#[instrument(
+    name = "component_add_symbol",
+    skip(ctx, data, interaction),
+    fields(custom_id = %interaction.data.custom_id, user_id = %interaction.user.id)
+)]
+pub async fn handle_component(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ComponentInteraction,
+) -> Result<(), Error> {
+    if interaction.data.custom_id != OPEN_ID {
+        debug!("ignored unrelated component interaction");
+        return Ok(());
+    }
+    let locale = i18n::resolve(
+        &data.symbol_store,
+        interaction.guild_id,
+        Some(&interaction.locale),
+    )
+    .await;
+
+    interaction
+        .create_response(ctx, CreateInteractionResponse::Modal(modal(locale)))
+        .await?;
+    debug!("opened add symbol modal");
+    Ok(())
+}
+
+/// Add the symbol a submitted modal names, with its note, and say how it
+/// went to the submitter alone.
+#[instrument(
+    name = "modal_add_symbol",
+    skip(ctx, data, interaction),
+    fields(custom_id = %interaction.data.custom_id, user_id = %interaction.user.id)
+)]
+pub async fn handle_modal(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ModalInteraction,
+) -> Result<(), Error> {
+    if interaction.data.custom_id != MODAL_ID {
+        debug!("ignored unrelated modal submission");
+        return Ok(());
+    }
+    // the asset list can take longer than Discord waits for a response
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let locale = i18n::resolve(
+        &data.symbol_store,
+        interaction.guild_id,
+        Some(&interaction.locale),
+    )
+    .await;
+    let inputs = submitted(&interaction.data.components);
+    let raw = inputs.get(TICKER_ID).copied().unwrap_or_default();
+    let note = inputs.get(NOTE_ID).and_then(|n| normalize_note(n));
+
+    let aliases = match interaction.guild_id {
+        Some(guild_id) => data
+            .symbol_store
+            .list_aliases(guild_id.get())
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = ?e, "failed to load guild aliases");
+                HashMap::new()
+            }),
+        None => HashMap::new(),
+    };
+    // an unreachable asset list isn't a reason to refuse the watch
+    let listed = data.price_client.list_assets().await.unwrap_or_else(|e| {
+        warn!(error = ?e, "failed to load asset list, skipping listing check");
+        Arc::from(Vec::new())
+    });
+
+    let resolved = match validate_one(raw, &aliases, &listed) {
+        Ok(resolved) => resolved,
+        Err(rejected) => {
+            warn!(raw, ?rejected, "rejected symbol");
+            interaction
+                .edit_response(
+                    ctx,
+                    EditInteractionResponse::new().content(rejection_line(locale, &rejected)),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+    let symbol = resolved.symbol;
+
+    let scope = scope_of(interaction.guild_id, interaction.user.id);
+    let store = &data.symbol_store;
+    let added = store.add(scope, &symbol).await?;
+    if note.is_some() {
+        store.set_note(scope, &symbol, note.clone()).await?;
+    }
+    info!(%symbol, added, noted = note.is_some(), "added symbol from modal");
+
+    let mut lines = Vec::new();
+    if let Some(name) = &resolved.via_alias {
+        lines.push(t!(locale, MessageKey::AliasInterpreted, name, symbol));
+    }
+    lines.push(if added {
+        t!(locale, MessageKey::NowWatching, symbol)
+    } else {
+        t!(locale, MessageKey::AlreadyWatching, symbol)
+    });
+    if let Some(note) = &note {
+        lines.push(t!(locale, MessageKey::AddSymbolNoteSaved, note));
+    }
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new().content(lines.join("\n")),
+        )
+        .await?;
+
+    // after responding, so a slow price fetch can't time out the interaction
+    if added {
+        record_added(data, scope, std::slice::from_ref(&symbol)).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    Context, Error, discord_text, fmt,
+    i18n::MessageKey,
+    registration::{self, SyncOutcome},
+    t,
+};
+
+/// Tools for the bot's owners
+#[poise::command(
+    slash_command,
+    owners_only,
+    subcommands("resync", "cache_flush", "store_stats", "loglevel")
+)]
+pub async fn admin(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Re-register the slash commands with Discord now
+///
+/// Uses `COMMAND_SCOPE` like startup does, but registers globally even if
+/// nothing looks different. With `guild`, registers in that server alone
+/// instead, where changes show up at once.
+#[poise::command(slash_command, owners_only)]
+#[instrument(name = "cmd_admin_resync", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn resync(
+    ctx: Context<'_>,
+    #[description = "Only register in this server id, where changes show up at once"] guild: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    if let Some(raw) = guild {
+        let Some(guild_id) = raw.trim().parse::<u64>().ok().filter(|&id| id != 0) else {
+            discord_text::send_split(
+                ctx,
+                &t!(ctx, MessageKey::ResyncInvalidGuild, raw),
+                true,
+                vec![],
+            )
+            .await?;
+            return Ok(());
+        };
+        registration::register_guild(ctx.http(), &ctx.framework().options().commands, guild_id)
+            .await?;
+        info!(guild_id, "resynced commands in guild");
+        discord_text::send_split(
+            ctx,
+            &t!(ctx, MessageKey::ResyncGuild, guild_id),
+            true,
+            vec![],
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let outcome = registration::sync(
+        ctx.http(),
+        &ctx.framework().options().commands,
+        &ctx.data().config.command_scope,
+        true,
+    )
+    .await?;
+    info!(?outcome, "resynced commands");
+
+    let content = match outcome {
+        SyncOutcome::Unchanged => t!(ctx, MessageKey::ResyncUnchanged),
+        SyncOutcome::Registered(changes) if changes.is_empty() => {
+            t!(ctx, MessageKey::ResyncForced)
+        }
+        SyncOutcome::Registered(changes) => {
+            let listed: Vec<String> = changes.iter().map(|c| format!("`{c}`")).collect();
+            t!(
+                ctx,
+                MessageKey::ResyncRegistered,
+                changes.len(),
+                listed.join(", ")
+            )
+        }
+        SyncOutcome::Guilds {
+            guilds,
+            cleared,
+            dropped,
+        } => {
+            let mut content = t!(ctx, MessageKey::ResyncGuilds, guilds.len());
+            if !cleared.is_empty() {
+                content.push('\n');
+                content.push_str(&t!(ctx, MessageKey::ResyncCleared, cleared.join(", ")));
+            }
+            if !dropped.is_empty() {
+                let dropped: Vec<String> = dropped.iter().map(u64::to_string).collect();
+                content.push('\n');
+                content.push_str(&t!(ctx, MessageKey::ResyncDropped, dropped.join(", ")));
+            }
+            content
+        }
+    };
+
+    discord_text::send_split(ctx, &content, true, vec![]).await?;
+    Ok(())
+}
+
+/// Empty the bar, symbol-list and cashtag caches
+///
+/// The next requests go to Alpaca, e.g. after a data correction.
+#[poise::command(slash_command, owners_only, rename = "cache-flush")]
+#[instrument(name = "cmd_admin_cache_flush", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn cache_flush(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let flushed = data.price_client.flush_cache().await;
+    let signals = data.signal_cache.clear();
+    info!(
+        bars = flushed.bars,
+        assets = flushed.assets,
+        signals,
+        "flushed caches"
+    );
+
+    let assets = if flushed.assets {
+        t!(ctx, MessageKey::AdminCacheDropped)
+    } else {
+        t!(ctx, MessageKey::AdminCacheNotHeld)
+    };
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::AdminCacheTitle))
+        .field(
+            t!(ctx, MessageKey::AdminCacheBars),
+            flushed.bars.to_string(),
+            true,
+        )
+        .field(t!(ctx, MessageKey::AdminCacheSymbolList), assets, true)
+        .field(
+            t!(ctx, MessageKey::AdminCacheSignals),
+            signals.to_string(),
+            true,
+        );
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Key counts, memory use and circuit state of the Redis store
+#[poise::command(slash_command, owners_only, rename = "store-stats")]
+#[instrument(name = "cmd_admin_store_stats", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn store_stats(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let store = &ctx.data().symbol_store;
+    // the circuits are read first: they're what's wanted when SCAN fails
+    let circuits = store.circuit_state();
+    let circuits = t!(
+        ctx,
+        MessageKey::AdminStoreCircuitsValue,
+        circuits.read.as_str(),
+        circuits.write.as_str()
+    );
+    let stats = match store.key_stats().await {
+        Ok(stats) => Some(stats),
+        Err(e) => {
+            warn!(error = ?e, "failed to collect key stats");
+            None
+        }
+    };
+
+    let unavailable = t!(ctx, MessageKey::Unavailable);
+    let (keys, categories, memory) = match &stats {
+        Some(stats) => {
+            let categories: Vec<String> = stats
+                .categories
+                .iter()
+                .map(|(category, count)| format!("`{category}` {count}"))
+                .collect();
+            (
+                stats.keys().to_string(),
+                discord_text::truncate_field(
+                    &categories.join("\n"),
+                    discord_text::FIELD_VALUE_LIMIT,
+                ),
+                stats.used_memory.map_or(unavailable.clone(), fmt::bytes),
+            )
+        }
+        None => ("?".to_string(), unavailable.clone(), unavailable.clone()),
+    };
+    let categories = if categories.is_empty() {
+        "-".to_string()
+    } else {
+        categories
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::AdminStoreTitle))
+        .field(t!(ctx, MessageKey::AdminStoreKeys, keys), categories, false)
+        .field(t!(ctx, MessageKey::AdminStoreMemory), memory, true)
+        .field(t!(ctx, MessageKey::AdminStoreCircuits), circuits, true);
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Change what gets logged, without a restart
+///
+/// Takes `RUST_LOG` syntax, e.g. `info,stock=debug`. An invalid filter changes nothing.
+#[poise::command(slash_command, owners_only)]
+#[instrument(name = "cmd_admin_loglevel", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn loglevel(
+    ctx: Context<'_>,
+    #[description = "Filter in RUST_LOG syntax, e.g. info,stock=debug"] filter: String,
+) -> Result<(), Error> {
+    let log_filter = &ctx.data().log_filter;
+    let content = match log_filter.set(&filter) {
+        Ok(previous) => t!(
+            ctx,
+            MessageKey::AdminLogLevelChanged,
+            log_filter.current(),
+            previous
+        ),
+        Err(e) => {
+            warn!(error = %e, "rejected log filter");
+            t!(
+                ctx,
+                MessageKey::AdminLogLevelInvalid,
+                e,
+                log_filter.current()
+            )
+        }
+    };
+    discord_text::send_split(ctx, &content, true, vec![]).await?;
+    Ok(())
+}
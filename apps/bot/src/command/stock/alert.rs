@@ -0,0 +1,414 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ::serenity::all::{
+    CreateActionRow, CreateEmbed, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use chrono::Utc;
+use poise::{CreateReply, serenity_prelude as serenity};
+use stock::{
+    alert::{Alert, Direction, Mode},
+    calendar::DEFAULT_TIMEZONE,
+};
+use tracing::{debug, info, instrument, warn};
+
+use super::delete::expires_line;
+use crate::{
+    Context, Data, Error, discord_text,
+    fmt::{self, TimeStyle},
+    i18n::{self, MessageKey},
+    invocation, t,
+};
+
+/// Custom ids of every alert component start with this, so the dispatcher
+/// can route them here.
+pub const COMPONENT_PREFIX: &str = "alert_";
+
+const SELECT_ID: &str = "alert_select_delete";
+const CONFIRM_PREFIX: &str = "alert_confirm_del_";
+const CANCEL_ID: &str = "alert_cancel_del";
+
+/// Select menus hold at most 25 options.
+const MAX_OPTIONS: usize = 25;
+
+/// Cooldown for recurring alerts when none is given.
+const DEFAULT_COOLDOWN_MINS: u32 = 60;
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum AlertDirection {
+    #[name = "above"]
+    Above,
+    #[name = "below"]
+    Below,
+}
+
+impl From<AlertDirection> for Direction {
+    fn from(d: AlertDirection) -> Self {
+        match d {
+            AlertDirection::Above => Direction::Above,
+            AlertDirection::Below => Direction::Below,
+        }
+    }
+}
+
+#[poise::command(slash_command, subcommands("add", "move_", "list"))]
+pub async fn alert(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_alert_add", skip(ctx), fields(user_id = %ctx.author().id, symbol = %symbol))]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "Ticker symbol (e.g., TSLA)"] symbol: String,
+    #[description = "Alert when the price goes above or below the level"] direction: AlertDirection,
+    #[description = "Price level"] price: f64,
+    #[description = "Fire every time the price crosses the level again"] recurring: Option<bool>,
+    #[description = "Minutes between repeats of a recurring alert (default 60)"]
+    #[min = 1]
+    cooldown: Option<u32>,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    if !(price.is_finite() && price > 0.0) {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::InvalidPrice))
+                .ephemeral(ephemeral),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mode = if recurring.unwrap_or(false) {
+        let mins = cooldown.unwrap_or(DEFAULT_COOLDOWN_MINS).max(1);
+        Mode::Recurring {
+            cooldown: Duration::from_secs(u64::from(mins) * 60),
+        }
+    } else {
+        Mode::OneShot
+    };
+
+    let alert = Alert::new(&symbol, direction.into(), price, Utc::now()).with_mode(mode);
+    ctx.data()
+        .symbol_store
+        .save_alert(ctx.author().id.get(), &alert)
+        .await?;
+    info!(alert_id = %alert.id, %alert, ?mode, "alert added");
+    remember_locale(ctx).await;
+
+    ctx.send(
+        CreateReply::default()
+            .content(t!(ctx, MessageKey::AlertSet, alert))
+            .ephemeral(ephemeral),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Alert when the price moves a percentage either way from now
+#[poise::command(slash_command, rename = "move")]
+#[instrument(name = "cmd_alert_move", skip(ctx), fields(user_id = %ctx.author().id, symbol = %symbol))]
+pub async fn move_(
+    ctx: Context<'_>,
+    #[description = "Ticker symbol (e.g., TSLA)"] symbol: String,
+    #[description = "Percent move up or down from the current price"]
+    #[min = 0.1]
+    #[max = 100]
+    percent: f64,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let symbol = symbol.trim().to_uppercase();
+    let reference = match ctx.data().price_client.fetch_snapshot(&symbol).await {
+        Ok(snapshot) => snapshot.and_then(|s| s.price()),
+        Err(e) => {
+            warn!(error = ?e, "snapshot fetch failed");
+            None
+        }
+    };
+    let Some(reference) = reference else {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::AlertNoReference, symbol))
+                .ephemeral(ephemeral),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    // setting a move alert again on the symbol re-bases the existing one
+    let user_id = ctx.author().id.get();
+    let now = Utc::now();
+    let existing = ctx
+        .data()
+        .symbol_store
+        .list_alerts(user_id)
+        .await?
+        .into_iter()
+        .find(|a| a.symbol == symbol && a.move_pct.is_some());
+    let alert = match existing {
+        Some(mut alert) => {
+            alert.move_pct = Some(percent);
+            alert.rebase(reference, now);
+            alert
+        }
+        None => Alert::on_move(&symbol, reference, percent, now),
+    };
+    ctx.data().symbol_store.save_alert(user_id, &alert).await?;
+    info!(alert_id = %alert.id, %alert, "move alert set");
+    remember_locale(ctx).await;
+
+    ctx.send(
+        CreateReply::default()
+            .content(t!(ctx, MessageKey::AlertSet, alert))
+            .ephemeral(ephemeral),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Keep the member's Discord language in their prefs, so the DMs their
+/// alerts send read in it.
+async fn remember_locale(ctx: Context<'_>) {
+    let Some(locale) = ctx.locale() else {
+        return;
+    };
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+    let res = async {
+        let mut prefs = store.get_user_pref(user_id).await?;
+        if prefs.locale.as_deref() != Some(locale) {
+            prefs.locale = Some(locale.to_string());
+            store.set_user_pref(user_id, &prefs).await?;
+        }
+        Ok::<_, Error>(())
+    }
+    .await;
+    if let Err(e) = res {
+        warn!(error = ?e, "failed to remember alert locale");
+    }
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_alert_list", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let alerts = ctx
+        .data()
+        .symbol_store
+        .list_alerts(ctx.author().id.get())
+        .await?;
+
+    if alerts.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::AlertsEmpty))
+                .ephemeral(ephemeral),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let locale = i18n::locale(ctx).await;
+    let lines: Vec<String> = alerts.iter().map(|a| line(locale, a)).collect();
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::AlertsTitle, alerts.len()))
+        .description(discord_text::truncate_field(
+            &lines.join("\n"),
+            discord_text::DESCRIPTION_LIMIT,
+        ));
+
+    let limit = alerts.len().min(MAX_OPTIONS);
+    let opts: Vec<CreateSelectMenuOption> = alerts
+        .iter()
+        .take(limit)
+        .map(|a| CreateSelectMenuOption::new(a.to_string(), a.id.clone()))
+        .collect();
+    let menu = CreateSelectMenu::new(SELECT_ID, CreateSelectMenuKind::String { options: opts })
+        .placeholder(t!(ctx, MessageKey::AlertSelectPlaceholder))
+        .min_values(1)
+        .max_values(limit as u8);
+
+    debug!(count = alerts.len(), limit, "listing alerts");
+    ctx.send(
+        CreateReply::default()
+            .embed(embed)
+            .components(vec![CreateActionRow::SelectMenu(menu)])
+            .ephemeral(ephemeral),
+    )
+    .await?;
+    Ok(())
+}
+
+/// One list entry: the alert, its mode and when it last fired.
+fn line(locale: i18n::Locale, alert: &Alert) -> String {
+    let mode = match alert.mode {
+        Mode::OneShot => i18n::tr(locale, MessageKey::AlertModeOneShot, &[]),
+        Mode::Recurring { cooldown } => i18n::tr(
+            locale,
+            MessageKey::AlertModeRecurring,
+            &[&fmt::uptime(cooldown)],
+        ),
+    };
+    let fired = match alert.last_fired_at {
+        Some(at) => i18n::tr(
+            locale,
+            MessageKey::AlertLastFired,
+            &[&fmt::time(at, DEFAULT_TIMEZONE, TimeStyle::Relative)],
+        ),
+        None => i18n::tr(locale, MessageKey::AlertNeverFired, &[]),
+    };
+    format!("**{alert}** · {mode} · {fired}")
+}
+
+fn update(content: String) -> serenity::CreateInteractionResponse {
+    serenity::CreateInteractionResponse::UpdateMessage(
+        serenity::CreateInteractionResponseMessage::new()
+            .content(content)
+            .embeds(vec![])
+            .components(vec![]),
+    )
+}
+
+fn ephemeral(content: String) -> serenity::CreateInteractionResponse {
+    serenity::CreateInteractionResponse::Message(
+        serenity::CreateInteractionResponseMessage::new()
+            .content(content)
+            .ephemeral(true),
+    )
+}
+
+#[instrument(
+    name = "component_alert",
+    skip(ctx, data, interaction),
+    fields(custom_id = %interaction.data.custom_id, user_id = %interaction.user.id)
+)]
+pub async fn handle_component(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ComponentInteraction,
+) -> Result<(), Error> {
+    let id = interaction.data.custom_id.as_str();
+    let user_id = interaction.user.id.get();
+    let locale = i18n::resolve(
+        &data.symbol_store,
+        interaction.guild_id,
+        Some(&interaction.locale),
+    )
+    .await;
+
+    if id == SELECT_ID {
+        let values = match &interaction.data.kind {
+            serenity::ComponentInteractionDataKind::StringSelect { values } => values.clone(),
+            _ => vec![],
+        };
+        if values.is_empty() {
+            debug!("empty selection submitted");
+            return Ok(());
+        }
+
+        let alerts = data.symbol_store.list_alerts(user_id).await?;
+        let (selected, _) = stock::alert::split_selected(alerts, &values);
+        let labels: Vec<String> = selected.iter().map(|a| a.to_string()).collect();
+
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let req_id = format!("{user_id}-{ts}-alerts");
+        data.symbol_store
+            .set_pending_delete(req_id.clone(), values)
+            .await?;
+        info!(req_id = %req_id, count = selected.len(), "initiated alert delete confirmation");
+
+        let prompt = format!(
+            "{}\n{}",
+            t!(
+                locale,
+                MessageKey::AlertDeleteConfirmPrompt,
+                labels.len(),
+                labels.join("\n> ")
+            ),
+            expires_line(
+                locale,
+                Utc::now(),
+                data.symbol_store.pending_delete_window()
+            )
+        );
+        let row = serenity::CreateActionRow::Buttons(vec![
+            serenity::CreateButton::new(format!("{CONFIRM_PREFIX}{req_id}"))
+                .label(t!(locale, MessageKey::ButtonConfirm))
+                .style(serenity::ButtonStyle::Danger),
+            serenity::CreateButton::new(CANCEL_ID)
+                .label(t!(locale, MessageKey::ButtonCancel))
+                .style(serenity::ButtonStyle::Secondary),
+        ]);
+
+        interaction
+            .create_response(
+                ctx,
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .content(prompt)
+                        .embeds(vec![])
+                        .components(vec![row]),
+                ),
+            )
+            .await?;
+        // count the confirmation window from when the prompt is on screen,
+        // not from the selection
+        if let Err(e) = data
+            .symbol_store
+            .refresh_pending_delete(req_id.clone())
+            .await
+        {
+            warn!(req_id = %req_id, error = ?e, "failed to refresh pending delete");
+        }
+        return Ok(());
+    }
+
+    if id == CANCEL_ID {
+        info!("cancelled alert delete");
+        interaction
+            .create_response(ctx, update(t!(locale, MessageKey::Cancelled)))
+            .await?;
+        return Ok(());
+    }
+
+    if let Some(req_id) = id.strip_prefix(CONFIRM_PREFIX) {
+        if let Some(owner) = req_id.split('-').next()
+            && owner != user_id.to_string()
+        {
+            warn!(owner = %owner, req_id = %req_id, "attempted to confirm request");
+            interaction
+                .create_response(ctx, ephemeral(t!(locale, MessageKey::DeleteNotOwner)))
+                .await?;
+            return Ok(());
+        }
+
+        let Some(ids) = data
+            .symbol_store
+            .take_pending_delete(req_id.to_string())
+            .await?
+        else {
+            warn!(req_id = %req_id, "session expired or not found");
+            interaction
+                .create_response(ctx, ephemeral(t!(locale, MessageKey::AlertSessionExpired)))
+                .await?;
+            return Ok(());
+        };
+
+        let removed = data.symbol_store.remove_alerts(user_id, &ids).await?;
+        let labels: Vec<String> = removed.iter().map(|a| a.to_string()).collect();
+        info!(req_id = %req_id, count = removed.len(), "confirmed alert deletion");
+
+        interaction
+            .create_response(
+                ctx,
+                update(t!(locale, MessageKey::AlertsDeleted, labels.join(", "))),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    debug!("ignored unrelated component interaction");
+    Ok(())
+}
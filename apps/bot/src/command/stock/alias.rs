@@ -0,0 +1,150 @@
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use stock::{
+    Scope,
+    alias::{self, AliasError, MAX_ALIAS_LEN, MAX_GUILD_ALIASES},
+};
+use tracing::{info, instrument, warn};
+
+use crate::{Context, Error, discord_text, i18n::MessageKey, t};
+
+#[poise::command(slash_command, guild_only, subcommands("add", "list", "remove"))]
+pub async fn alias(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Let members type a name in place of a ticker in this server
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_alias_add", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "Name members will type, e.g. apple"] name: String,
+    #[description = "Ticker it stands for, e.g. AAPL"] symbol: String,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+    let store = &ctx.data().symbol_store;
+
+    let (name, symbol) = match alias::validate(&name, &symbol) {
+        Ok(pair) => pair,
+        Err(e) => return reject(ctx, e).await,
+    };
+    // validate only knows the built-in tickers; a name can't hide one this
+    // server watches either
+    if store
+        .contains(Scope::Guild(guild_id.get()), &name.to_uppercase())
+        .await?
+    {
+        return reject(ctx, AliasError::ShadowsTicker(name.to_uppercase())).await;
+    }
+
+    let aliases = store.list_aliases(guild_id.get()).await?;
+    let previous = aliases.get(&name).cloned();
+    if previous.is_none() && aliases.len() >= MAX_GUILD_ALIASES {
+        warn!(count = aliases.len(), "alias limit reached");
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::AliasLimit, MAX_GUILD_ALIASES))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    store.set_alias(guild_id.get(), &name, &symbol).await?;
+    info!(%guild_id, %name, %symbol, ?previous, "alias set");
+
+    let reply = match previous {
+        Some(previous) if previous != symbol => {
+            t!(ctx, MessageKey::AliasReplaced, name, symbol, previous)
+        }
+        _ => t!(ctx, MessageKey::AliasAdded, name, symbol),
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Show the names this server has set up for tickers
+#[poise::command(slash_command, guild_only)]
+#[instrument(name = "cmd_alias_list", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let aliases = ctx.data().symbol_store.list_aliases(guild_id.get()).await?;
+    if aliases.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::AliasListEmpty))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut aliases: Vec<(String, String)> = aliases.into_iter().collect();
+    aliases.sort();
+    let lines: Vec<String> = aliases
+        .iter()
+        .map(|(name, symbol)| format!("`{name}` → **{symbol}**"))
+        .collect();
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::AliasListTitle))
+        .description(discord_text::truncate_field(
+            &lines.join("\n"),
+            discord_text::DESCRIPTION_LIMIT,
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Stop a name standing for a ticker in this server
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_alias_remove", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "Alias to remove"] name: String,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let name = alias::normalize(&name);
+    let removed = ctx
+        .data()
+        .symbol_store
+        .remove_alias(guild_id.get(), &name)
+        .await?;
+    info!(%guild_id, %name, removed, "alias remove");
+
+    let reply = if removed {
+        t!(ctx, MessageKey::AliasRemoved, name)
+    } else {
+        t!(ctx, MessageKey::AliasNotFound, name)
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Tell the invoker why the alias wasn't added.
+async fn reject(ctx: Context<'_>, e: AliasError) -> Result<(), Error> {
+    warn!(error = %e, "rejected alias");
+    let reply = match e {
+        AliasError::Empty => t!(ctx, MessageKey::AliasEmpty),
+        AliasError::TooLong => t!(ctx, MessageKey::AliasTooLong, MAX_ALIAS_LEN),
+        AliasError::ShadowsTicker(ticker) => t!(ctx, MessageKey::AliasShadowsTicker, ticker),
+        AliasError::InvalidSymbol(symbol) => t!(ctx, MessageKey::InvalidSymbols, symbol),
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use chrono::{Duration, Utc};
+use poise::CreateReply;
+use serenity::all::{CreateAttachment, CreateEmbed};
+use stock::indicators::cdc::{ChartOptions, Signal, calculate, enough_to_chart};
+use stock::{ChartJob, Session, Timeframe, calendar};
+use tracing::{debug, info, instrument};
+
+use super::graph::chart_events;
+use crate::{
+    Context, Error, analysis,
+    fmt::{self, TimeStyle},
+    i18n::MessageKey,
+    invocation, style, t,
+};
+
+/// Enough calendar days for a full 52-week range of sessions.
+const LOOKBACK_DAYS: i64 = 380;
+const FETCH_LIMIT: usize = 400;
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_analyze", skip(ctx), fields(symbol = %symbol))]
+pub async fn analyze(
+    ctx: Context<'_>,
+    #[description = "Symbol of stock to analyze"] symbol: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    debug!("deferred reply");
+
+    let data = ctx.data();
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let bars = data
+        .price_client
+        .fetch_price(
+            &symbol,
+            Duration::days(LOOKBACK_DAYS),
+            Timeframe::Day1,
+            FETCH_LIMIT,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    info!(bars = bars.len(), "fetched price bars");
+
+    let Some(mut report) = analysis::build(&symbol, &bars, data.config.signal_band_pct) else {
+        ctx.say(t!(ctx, MessageKey::AnalyzeNoData, symbol)).await?;
+        return Ok(());
+    };
+    if let Some(earnings) = &data.earnings {
+        let today = calendar::session_date(Utc::now());
+        report.next_earnings = analysis::next_earnings(earnings.as_ref(), &symbol, today).await;
+    }
+    debug!(?report, "built analysis");
+
+    let locale = crate::i18n::locale(ctx).await;
+    let fields = report
+        .fields(locale)
+        .into_iter()
+        .map(|(name, value)| (name, value, true));
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::AnalysisTitle, symbol))
+        .description(report.headline())
+        .fields(fields);
+    let mut embed = style::for_invocation(ctx)
+        .await
+        .apply(embed, report.signal.unwrap_or(Signal::None));
+
+    let mut lines: Vec<String> = note.into_iter().collect();
+    let mut reply = CreateReply::default();
+    if enough_to_chart(bars.len(), data.config.min_chart_bars) {
+        let tz = invocation::timezone(ctx).await;
+        let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+        let dates: Vec<String> = bars
+            .iter()
+            .map(|b| fmt::time(b.timestamp, tz, TimeStyle::Axis(Timeframe::Day1)))
+            .collect();
+        let (_, ema12, ema26) = calculate(&closes, data.config.signal_band_pct);
+        let (annotations, bar_dates) =
+            chart_events(data.price_client.as_ref(), &symbol, &bars).await;
+
+        let chart = data
+            .renderer
+            .render(ChartJob {
+                symbol: symbol.clone(),
+                closes: closes.into(),
+                ema12: ema12.into(),
+                ema26: ema26.into(),
+                dates: dates.into(),
+                options: ChartOptions {
+                    annotations,
+                    bar_dates,
+                    ..Default::default()
+                },
+            })
+            .await?;
+        info!(bytes = chart.len(), "chart generated");
+
+        let filename = format!("{symbol}_analysis.png");
+        embed = embed.image(format!("attachment://{filename}"));
+        reply = reply.attachment(CreateAttachment::bytes(chart, filename));
+    } else {
+        // the readings are still worth showing, with a caveat
+        info!(bars = bars.len(), "short history, skipping chart");
+        lines.push(t!(ctx, MessageKey::AnalyzeShortHistory, bars.len()));
+    }
+
+    reply = reply.embed(embed);
+    if !lines.is_empty() {
+        reply = reply.content(lines.join("\n"));
+    }
+    ctx.send(reply).await?;
+    info!("sent analysis");
+
+    Ok(())
+}
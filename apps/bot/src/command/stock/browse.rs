@@ -0,0 +1,454 @@
+use poise::{CreateReply, serenity_prelude as serenity};
+use serenity::all::{
+    CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse,
+};
+use stock::indicators::cdc::ChartOptions;
+use stock::{BrowseState, ChartJob, Scope, Session, Timeframe, calendar::DEFAULT_TIMEZONE};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Context, Data, Error,
+    fmt::{self, TimeStyle},
+    i18n::{self, Locale, MessageKey, tr},
+    invocation, report,
+    style::SignalStyle,
+    t,
+};
+
+/// Custom ids of every browser component start with this, so the
+/// dispatcher can route them here.
+pub const COMPONENT_PREFIX: &str = "browse_";
+
+const SELECT_ID: &str = "browse_select";
+const PREV_ID: &str = "browse_prev";
+const NEXT_ID: &str = "browse_next";
+const BACK_ID: &str = "browse_back";
+const REMOVE_ID: &str = "browse_remove";
+
+/// Symbols per page: as many as a select menu holds.
+pub const PAGE_SIZE: usize = 25;
+
+/// Most recent daily bars drawn, as in `/stock graph`.
+const CHART_BARS: usize = 365;
+
+/// Pages needed for `len` symbols; an empty list still has one.
+pub fn page_count(len: usize) -> usize {
+    len.div_ceil(PAGE_SIZE).max(1)
+}
+
+/// The symbols on `page`, which is clamped to the last page so a list that
+/// shrank since it was paged still shows something. Returns the page shown.
+pub fn page(symbols: &[String], page: usize) -> (usize, &[String]) {
+    let page = page.min(page_count(symbols.len()) - 1);
+    let start = page * PAGE_SIZE;
+    let end = (start + PAGE_SIZE).min(symbols.len());
+    (page, &symbols[start.min(end)..end])
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_browse", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn browse(ctx: Context<'_>) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let store = &ctx.data().symbol_store;
+
+    let mut symbols = store.list(invocation::scope(ctx)).await?;
+    if symbols.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::WatchlistEmpty))
+                .ephemeral(ephemeral),
+        )
+        .await?;
+        return Ok(());
+    }
+    symbols.sort();
+
+    let locale = i18n::locale(ctx).await;
+    let (embed, components) = list_view(locale, &symbols, 0);
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .embed(embed)
+                .components(components)
+                .ephemeral(ephemeral),
+        )
+        .await?;
+
+    let message_id = handle.message().await?.id.get();
+    if let Err(e) = store
+        .set_browse_state(message_id, &BrowseState::default())
+        .await
+    {
+        warn!(error = ?e, "failed to save browse state");
+    }
+    info!(
+        message_id,
+        count = symbols.len(),
+        "opened watchlist browser"
+    );
+    Ok(())
+}
+
+/// One page of the watchlist: a select menu of its symbols, with buttons to
+/// the neighbouring pages.
+fn list_view(
+    locale: Locale,
+    symbols: &[String],
+    page_index: usize,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let pages = page_count(symbols.len());
+    let (page_index, shown) = page(symbols, page_index);
+
+    let embed = CreateEmbed::default()
+        .title(tr(locale, MessageKey::WatchlistTitle, &[&symbols.len()]))
+        .description(shown.join(", "))
+        .footer(serenity::CreateEmbedFooter::new(tr(
+            locale,
+            MessageKey::BrowsePage,
+            &[&(page_index + 1), &pages],
+        )));
+
+    let options: Vec<CreateSelectMenuOption> = shown
+        .iter()
+        .map(|s| CreateSelectMenuOption::new(s.clone(), s.clone()))
+        .collect();
+    let menu = CreateSelectMenu::new(SELECT_ID, CreateSelectMenuKind::String { options })
+        .placeholder(tr(locale, MessageKey::BrowsePlaceholder, &[]));
+    let buttons = vec![
+        CreateButton::new(PREV_ID)
+            .label(tr(locale, MessageKey::BrowsePrev, &[]))
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(page_index == 0),
+        CreateButton::new(NEXT_ID)
+            .label(tr(locale, MessageKey::BrowseNext, &[]))
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(page_index + 1 >= pages),
+    ];
+
+    (
+        embed,
+        vec![
+            CreateActionRow::SelectMenu(menu),
+            CreateActionRow::Buttons(buttons),
+        ],
+    )
+}
+
+/// One symbol's daily chart and key stats, with buttons back to the list
+/// and to remove it. `None` when there is no price history to show.
+async fn detail_view(
+    data: &Data,
+    guild_id: Option<serenity::GuildId>,
+    user_id: u64,
+    locale: Locale,
+    symbol: &str,
+) -> Result<Option<(CreateEmbed, CreateAttachment, Vec<CreateActionRow>)>, Error> {
+    let Some((embed, chart)) = chart_view(data, guild_id, user_id, locale, symbol).await? else {
+        return Ok(None);
+    };
+    let buttons = vec![
+        CreateButton::new(BACK_ID)
+            .label(t!(locale, MessageKey::BrowseBack))
+            .style(serenity::ButtonStyle::Secondary),
+        CreateButton::new(REMOVE_ID)
+            .label(t!(locale, MessageKey::BrowseRemove))
+            .style(serenity::ButtonStyle::Danger),
+    ];
+    Ok(Some((
+        embed,
+        chart,
+        vec![CreateActionRow::Buttons(buttons)],
+    )))
+}
+
+/// One symbol's daily chart and key stats, drawn with the guild's strategy
+/// and the user's chart preferences. `None` when there is no price history
+/// to show.
+async fn chart_view(
+    data: &Data,
+    guild_id: Option<serenity::GuildId>,
+    user_id: u64,
+    locale: Locale,
+    symbol: &str,
+) -> Result<Option<(CreateEmbed, CreateAttachment)>, Error> {
+    let mut bars = data
+        .price_client
+        .fetch_price(
+            symbol,
+            Timeframe::Day1.lookback(),
+            Timeframe::Day1,
+            CHART_BARS,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    bars.drain(..bars.len().saturating_sub(CHART_BARS));
+    let Some(last) = bars.last() else {
+        return Ok(None);
+    };
+    let close = last.close;
+    let change = bars
+        .len()
+        .checked_sub(2)
+        .map(|i| bars[i].close)
+        .filter(|prev| *prev > 0.0)
+        .map(|prev| (close - prev) / prev * 100.0);
+    let high = bars.iter().map(|b| b.high).fold(f64::MIN, f64::max);
+    let low = bars.iter().map(|b| b.low).fold(f64::MAX, f64::min);
+
+    let store = &data.symbol_store;
+    let (tz, strategy) = match guild_id {
+        Some(guild_id) => {
+            let tz = match store.get_guild_settings(guild_id.get()).await {
+                Ok(settings) => settings.timezone(),
+                Err(e) => {
+                    warn!(error = ?e, "failed to load guild timezone");
+                    DEFAULT_TIMEZONE
+                }
+            };
+            let strategy = store
+                .get_strategy(guild_id.get())
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(error = ?e, "failed to load guild strategy");
+                    Default::default()
+                });
+            (tz, strategy)
+        }
+        None => (DEFAULT_TIMEZONE, Default::default()),
+    };
+    let (theme, scale) = match store.get_user_pref(user_id).await {
+        Ok(prefs) => (prefs.chart_theme, prefs.chart_scale),
+        Err(e) => {
+            warn!(error = ?e, "failed to load user prefs");
+            Default::default()
+        }
+    };
+
+    let (signal, ema12, ema26) = strategy.evaluate(&bars, data.config.signal_band_pct);
+    let chart = data
+        .renderer
+        .render(ChartJob {
+            symbol: symbol.to_string(),
+            closes: bars.iter().map(|b| b.close).collect(),
+            ema12: ema12.into(),
+            ema26: ema26.into(),
+            dates: bars
+                .iter()
+                .map(|b| fmt::time(b.timestamp, tz, TimeStyle::Axis(Timeframe::Day1)))
+                .collect(),
+            options: ChartOptions {
+                average_names: strategy.line_names(),
+                theme,
+                scale,
+                ..Default::default()
+            },
+        })
+        .await?;
+    debug!(bytes = chart.len(), "chart rendered");
+
+    let filename = report::chart_filename(symbol, signal);
+    let embed = CreateEmbed::default()
+        .title(t!(locale, MessageKey::AnalysisTitle, symbol))
+        .description(t!(
+            locale,
+            MessageKey::CurrentSignal,
+            t!(locale, MessageKey::for_signal(signal))
+        ))
+        .field(t!(locale, MessageKey::BrowseClose), fmt::price(close), true)
+        .field(
+            t!(locale, MessageKey::BrowseChange),
+            change.map_or("-".to_string(), fmt::signed_pct),
+            true,
+        )
+        .field(
+            t!(locale, MessageKey::BrowseRange),
+            format!("{} – {}", fmt::price(low), fmt::price(high)),
+            true,
+        )
+        .image(format!("attachment://{filename}"));
+    let style = SignalStyle::for_guild(store, &data.config.signal_colors, guild_id).await;
+    let embed = style.apply(embed, signal);
+
+    Ok(Some((embed, CreateAttachment::bytes(chart, filename))))
+}
+
+/// Show page `page_index` of `symbols`, or the empty-watchlist note once
+/// nothing is left. Returns the page shown.
+fn list_response(
+    locale: Locale,
+    symbols: &[String],
+    page_index: usize,
+    content: String,
+) -> (usize, EditInteractionResponse) {
+    if symbols.is_empty() {
+        let content = [content, tr(locale, MessageKey::WatchlistEmpty, &[])]
+            .join("\n")
+            .trim()
+            .to_string();
+        let edit = EditInteractionResponse::new()
+            .content(content)
+            .embeds(vec![])
+            .components(vec![])
+            .clear_attachments();
+        return (0, edit);
+    }
+
+    let (page_index, _) = page(symbols, page_index);
+    let (embed, components) = list_view(locale, symbols, page_index);
+    let edit = EditInteractionResponse::new()
+        .content(content)
+        .embed(embed)
+        .components(components)
+        .clear_attachments();
+    (page_index, edit)
+}
+
+#[instrument(
+    name = "component_browse",
+    skip(ctx, data, interaction),
+    fields(custom_id = %interaction.data.custom_id, user_id = %interaction.user.id)
+)]
+pub async fn handle_component(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ComponentInteraction,
+) -> Result<(), Error> {
+    let id = interaction.data.custom_id.as_str();
+    let message_id = interaction.message.id.get();
+    let user_id = interaction.user.id.get();
+    let scope = match interaction.guild_id {
+        Some(guild_id) => Scope::Guild(guild_id.get()),
+        None => Scope::User(user_id),
+    };
+    let locale = i18n::resolve(
+        &data.symbol_store,
+        interaction.guild_id,
+        Some(&interaction.locale),
+    )
+    .await;
+
+    // expired or unreadable state starts the browser over from the first page
+    let saved = match data.symbol_store.get_browse_state(message_id).await {
+        Ok(saved) => saved,
+        Err(e) => {
+            warn!(error = ?e, "failed to load browse state");
+            None
+        }
+    };
+    let expired = saved.is_none();
+    let mut state = saved.unwrap_or_default();
+    if expired {
+        debug!("browse state expired, rebuilding from page 0");
+    }
+
+    if id == REMOVE_ID && !expired {
+        let allowed = match interaction.guild_id {
+            Some(_) => interaction
+                .member
+                .as_ref()
+                .and_then(|m| m.permissions)
+                .is_some_and(|p| p.manage_guild()),
+            None => true,
+        };
+        if !allowed {
+            warn!("member without Manage Server pressed remove");
+            interaction
+                .create_response(
+                    ctx,
+                    serenity::CreateInteractionResponse::Message(
+                        serenity::CreateInteractionResponseMessage::new()
+                            .content(t!(locale, MessageKey::BrowseRemoveNeedsManageGuild))
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    // charts can take longer to render than Discord waits for a response
+    interaction
+        .create_response(ctx, serenity::CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let mut symbols = data.symbol_store.list(scope).await?;
+    symbols.sort();
+    let mut content = String::new();
+
+    if expired {
+        state = BrowseState::default();
+    } else if id == SELECT_ID {
+        let chosen = match &interaction.data.kind {
+            serenity::ComponentInteractionDataKind::StringSelect { values } => {
+                values.first().cloned()
+            }
+            _ => None,
+        };
+        state.symbol = chosen.filter(|s| symbols.contains(s));
+    } else if id == PREV_ID {
+        state.page = state.page.saturating_sub(1);
+        state.symbol = None;
+    } else if id == NEXT_ID {
+        state.page += 1;
+        state.symbol = None;
+    } else if id == BACK_ID {
+        state.symbol = None;
+    } else if id == REMOVE_ID {
+        if let Some(symbol) = state.symbol.take() {
+            // with its metadata, readings and alerts, as /stock delete does
+            let impact = data
+                .symbol_store
+                .impact_report(scope, std::slice::from_ref(&symbol))
+                .await?;
+            let outcome = data.symbol_store.remove_cascade(scope, &impact).await;
+            if let Some((_, error)) = outcome.failed.first() {
+                return Err(Error::msg(format!("failed to remove {symbol}: {error}")));
+            }
+            symbols.retain(|s| *s != symbol);
+            info!(%symbol, "removed symbol from browser");
+            content = t!(locale, MessageKey::BrowseRemoved, symbol);
+        }
+    } else {
+        debug!("ignored unrelated component interaction");
+        return Ok(());
+    }
+
+    let detail = match &state.symbol {
+        Some(symbol) => detail_view(data, interaction.guild_id, user_id, locale, symbol)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(%symbol, error = ?e, "failed to load symbol detail");
+                None
+            }),
+        None => None,
+    };
+    // a symbol without history falls back to its page with a note
+    if detail.is_none()
+        && let Some(symbol) = state.symbol.take()
+    {
+        content = t!(locale, MessageKey::AnalyzeNoData, symbol);
+    }
+
+    let edit = match detail {
+        Some((embed, chart, components)) => EditInteractionResponse::new()
+            .content(content)
+            .embed(embed)
+            .components(components)
+            .clear_attachments()
+            .new_attachment(chart),
+        None => {
+            let (page_index, edit) = list_response(locale, &symbols, state.page, content);
+            state.page = page_index;
+            edit
+        }
+    };
+
+    interaction.edit_response(ctx, edit).await?;
+    if let Err(e) = data.symbol_store.set_browse_state(message_id, &state).await {
+        warn!(error = ?e, "failed to save browse state");
+    }
+    debug!(page = state.page, symbol = ?state.symbol, "browser updated");
+    Ok(())
+}
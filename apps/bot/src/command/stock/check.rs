@@ -0,0 +1,150 @@
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use stock::{
+    calendar,
+    indicators::cdc::Signal,
+    scan::{Decision, Verdict, check_symbol},
+};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::{self, Locale, MessageKey, tr},
+    invocation, report, style, t,
+};
+
+fn signal_name(locale: Locale, signal: Signal) -> String {
+    tr(locale, MessageKey::for_signal(signal), &[])
+}
+
+/// Why the daily scan would or wouldn't post `decision`: the verdict first,
+/// then what each filter saw.
+pub fn explain(locale: Locale, decision: &Decision, weekly_confirm: bool) -> Vec<String> {
+    let raw = signal_name(locale, decision.raw);
+    let verdict = match decision.verdict() {
+        Verdict::Posts => tr(
+            locale,
+            MessageKey::CheckPosts,
+            &[&signal_name(locale, decision.signal)],
+        ),
+        Verdict::NoCrossover => tr(locale, MessageKey::CheckNoCrossover, &[&raw]),
+        Verdict::WeakTrend(trend) => tr(
+            locale,
+            MessageKey::CheckWeakTrend,
+            &[&raw, &format!("{:.1}", trend.strength), &trend.min],
+        ),
+        Verdict::WeeklyDisagrees(Signal::None) => {
+            tr(locale, MessageKey::CheckWeeklyUnknown, &[&raw])
+        }
+        Verdict::WeeklyDisagrees(weekly) => tr(
+            locale,
+            MessageKey::CheckWeeklyDisagrees,
+            &[&raw, &signal_name(locale, weekly)],
+        ),
+    };
+
+    let adx = match decision.trend {
+        Some(trend) => tr(
+            locale,
+            MessageKey::CheckAdx,
+            &[&format!("{:.1}", trend.strength), &trend.min],
+        ),
+        None => tr(locale, MessageKey::CheckAdxOff, &[]),
+    };
+    let weekly = match (weekly_confirm, decision.weekly) {
+        (false, _) => tr(locale, MessageKey::CheckWeeklyOff, &[]),
+        (true, Some(weekly)) => tr(
+            locale,
+            MessageKey::CheckWeekly,
+            &[&signal_name(locale, weekly)],
+        ),
+        (true, None) => tr(locale, MessageKey::CheckWeeklyNotNeeded, &[]),
+    };
+    vec![verdict, format!("• {adx}"), format!("• {weekly}")]
+}
+
+/// Explain whether the daily scan would post a symbol today
+///
+/// Runs the scan's own filters on the latest daily bar with this server's
+/// strategy and says which one, if any, stops the signal.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_check", skip(ctx), fields(symbol = %symbol))]
+pub async fn check(
+    ctx: Context<'_>,
+    #[description = "Symbol to check"] symbol: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    debug!("deferred reply");
+
+    let data = ctx.data();
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let strategy = invocation::strategy(ctx).await;
+    let weekly_confirm = data.config.weekly_confirmation;
+    let Some(checked) = check_symbol(
+        data.price_client.as_ref(),
+        &symbol,
+        strategy,
+        data.config.signal_band_pct,
+        weekly_confirm,
+    )
+    .await?
+    else {
+        ctx.say(t!(ctx, MessageKey::CheckNoData, symbol)).await?;
+        return Ok(());
+    };
+    info!(verdict = ?checked.decision.verdict(), "checked symbol");
+
+    let locale = i18n::locale(ctx).await;
+    let mut lines = explain(locale, &checked.decision, weekly_confirm);
+
+    // mutes and quiet periods are the scan's last say
+    let scope = invocation::scope(ctx);
+    let store = &data.symbol_store;
+    match store.list_muted(scope).await {
+        Ok(muted) if muted.contains_key(&symbol.to_uppercase()) => {
+            lines.push(tr(locale, MessageKey::CheckLeftOut, &[]));
+        }
+        Ok(_) => {}
+        Err(e) => warn!(error = ?e, "failed to load muted symbols"),
+    }
+    match store.list_meta(scope).await {
+        Ok(meta) => {
+            if let Some(until) = report::quiet_until(&meta, &symbol) {
+                let tz = invocation::timezone(ctx).await;
+                let until = fmt::time(until, tz, TimeStyle::Date);
+                lines.push(tr(locale, MessageKey::CheckQuiet, &[&until]));
+            }
+        }
+        Err(e) => warn!(error = ?e, "failed to load symbol metadata"),
+    }
+
+    let mut footer = format!(
+        "{strategy} · {} · {}",
+        fmt::price(checked.close),
+        calendar::session_date(checked.timestamp)
+    );
+    if let Some(source) = &checked.fallback {
+        footer.push_str(" · ");
+        footer.push_str(&tr(locale, MessageKey::SourceFallback, &[source]));
+    }
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::CheckTitle, symbol))
+        .description(lines.join("\n"))
+        .footer(CreateEmbedFooter::new(footer));
+    let embed = style::for_invocation(ctx)
+        .await
+        .apply(embed, checked.decision.signal);
+
+    let mut reply = CreateReply::default().embed(embed);
+    if let Some(note) = note {
+        reply = reply.content(note);
+    }
+    ctx.send(reply).await?;
+    info!("sent check");
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use poise::CreateReply;
+use tracing::{info, instrument};
+
+use crate::{Context, Error, i18n::MessageKey, invocation, t};
+
+/// Merge watchlist entries that only differ by case or spacing
+///
+/// Entries stored before symbols were normalized can sit next to their
+/// normalized form; this rewrites them in place.
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_clean", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn clean(ctx: Context<'_>) -> Result<(), Error> {
+    let scope = invocation::scope(ctx);
+    let cleanup = ctx.data().symbol_store.clean(scope).await?;
+    info!(
+        renamed = cleanup.renamed.len(),
+        removed = cleanup.removed.len(),
+        symbols = cleanup.symbols.len(),
+        "cleaned watchlist"
+    );
+
+    let content = if cleanup.is_clean() {
+        t!(ctx, MessageKey::CleanAlready, cleanup.symbols.len())
+    } else {
+        t!(
+            ctx,
+            MessageKey::CleanDone,
+            cleanup.renamed.len(),
+            cleanup.removed.len(),
+            cleanup.symbols.len()
+        )
+    };
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use chrono::{Duration, Utc};
+use poise::CreateReply;
+use stock::calendar::DEFAULT_TIMEZONE;
+use tracing::{info, instrument};
+
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::MessageKey,
+    t,
+};
+
+/// Pause length when none is given.
+const DEFAULT_DAYS: u32 = 14;
+/// Longest pause accepted, roughly one quarter.
+const MAX_DAYS: u32 = 90;
+
+#[poise::command(slash_command, guild_only, subcommands("pause", "resume"))]
+pub async fn daily(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_daily_pause", skip(ctx), fields(user_id = %ctx.author().id, days))]
+pub async fn pause(
+    ctx: Context<'_>,
+    #[description = "Days to pause the daily signals for (default 14)"]
+    #[min = 1]
+    #[max = 90]
+    days: Option<u32>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let days = days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+    let until = Utc::now() + Duration::days(days.into());
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.daily_paused_until = Some(until);
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    info!(%guild_id, %until, "daily signals paused");
+
+    let reply = t!(
+        ctx,
+        MessageKey::DailyPaused,
+        fmt::time(until, DEFAULT_TIMEZONE, TimeStyle::Full)
+    );
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_daily_resume", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn resume(ctx: Context<'_>) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+
+    let reply = if settings.daily_paused_at(Utc::now()).is_some() {
+        settings.daily_paused_until = None;
+        store.set_guild_settings(guild_id.get(), &settings).await?;
+        info!(%guild_id, "daily signals resumed");
+        t!(ctx, MessageKey::DailyResumed)
+    } else {
+        t!(ctx, MessageKey::DailyNotPaused)
+    };
+
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use poise::CreateReply;
+use serenity::all::{CreateAttachment, CreateEmbed};
+use stock::{Session, Timeframe};
+use tracing::{debug, info, instrument, warn};
+
+use super::graph::TimeframeChoice;
+use crate::{
+    Context, Error,
+    i18n::MessageKey,
+    invocation,
+    ohlcv::{self, DEFAULT_ROWS, MAX_CSV_ROWS, MAX_TABLE_ROWS},
+    t,
+};
+
+/// Bars requested; the last `count` of them are kept. Intraday lookbacks
+/// can return more than that, oldest first.
+const FETCH_LIMIT: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum DataFormat {
+    #[default]
+    #[name = "table"]
+    Table,
+    #[name = "csv"]
+    Csv,
+}
+
+/// Show a symbol's last bars as raw open, high, low, close and volume
+///
+/// No indicators, just the bars as fetched. A table fits up to 40 rows;
+/// pick CSV for more, sent as a file.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_data", skip(ctx), fields(symbol = %symbol))]
+pub async fn data(
+    ctx: Context<'_>,
+    #[description = "Symbol to show bars for"] symbol: String,
+    #[description = "Most recent bars to show (default 20)"]
+    #[min = 1]
+    #[max = 1000]
+    count: Option<u32>,
+    #[description = "Bar size (default 1Day)"] timeframe: Option<TimeframeChoice>,
+    #[description = "Table in the reply, or a CSV file (default table)"] format: Option<DataFormat>,
+) -> Result<(), Error> {
+    let format = format.unwrap_or_default();
+    let timeframe: Timeframe = timeframe.map_or(Timeframe::Day1, Into::into);
+    let cap = match format {
+        DataFormat::Table => MAX_TABLE_ROWS,
+        DataFormat::Csv => MAX_CSV_ROWS,
+    };
+    let asked = count.map_or(DEFAULT_ROWS, |c| c as usize);
+    let count = asked.clamp(1, cap);
+
+    ctx.defer().await?;
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+    if stock::validate_symbol(&symbol).is_err() {
+        warn!("invalid symbol");
+        ctx.say(t!(ctx, MessageKey::InvalidSymbols, symbol)).await?;
+        return Ok(());
+    }
+
+    let mut bars = ctx
+        .data()
+        .price_client
+        .fetch_price(
+            &symbol,
+            timeframe.lookback(),
+            timeframe,
+            FETCH_LIMIT,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    bars.drain(..bars.len().saturating_sub(count));
+    if bars.is_empty() {
+        ctx.say(t!(ctx, MessageKey::AnalyzeNoData, symbol)).await?;
+        return Ok(());
+    }
+    info!(bars = bars.len(), "fetched price bars");
+
+    let mut lines: Vec<String> = note.into_iter().collect();
+    if asked > count {
+        debug!(asked, count, "capped row count");
+        lines.push(t!(ctx, MessageKey::DataCapped, count, MAX_CSV_ROWS));
+    }
+
+    let reply = match format {
+        DataFormat::Table => {
+            let tz = invocation::timezone(ctx).await;
+            let table = ohlcv::table(&bars, timeframe, tz);
+            let embed = CreateEmbed::default()
+                .title(t!(
+                    ctx,
+                    MessageKey::DataTitle,
+                    symbol,
+                    bars.len(),
+                    timeframe.as_str()
+                ))
+                .description(format!("```\n{table}\n```"));
+            CreateReply::default().embed(embed)
+        }
+        DataFormat::Csv => {
+            let csv = ohlcv::csv(&bars);
+            debug!(bytes = csv.len(), "built csv");
+            lines.push(t!(
+                ctx,
+                MessageKey::DataTitle,
+                symbol,
+                bars.len(),
+                timeframe.as_str()
+            ));
+            let filename = format!("{}_{}.csv", symbol.replace('/', "-"), timeframe.as_str());
+            CreateReply::default().attachment(CreateAttachment::bytes(csv, filename))
+        }
+    };
+    let reply = if lines.is_empty() {
+        reply
+    } else {
+        reply.content(lines.join("\n"))
+    };
+    ctx.send(reply).await?;
+    Ok(())
+}
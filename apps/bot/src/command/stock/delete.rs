@@ -2,31 +2,130 @@ use ::serenity::all::{
     CreateActionRow, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
 };
 use anyhow::bail;
+use chrono::{DateTime, Utc};
 use poise::serenity_prelude as serenity;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::{debug, error, info, instrument, warn};
-
-use crate::{Context, Data, Error};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use stock::{DeleteOutcome, Scope, SymbolImpact, calendar::DEFAULT_TIMEZONE, fuzzy};
+use tracing::{debug, info, instrument, warn};
+
+use super::watch::autocomplete_watched;
+use crate::{
+    Context, Data, Error, discord_text,
+    fmt::{self, TimeStyle},
+    i18n::{self, Locale, MessageKey, tr},
+    invocation, t,
+};
 
 const SELECT_DELETE_ID: &str = "select_delete";
 const CONFIRM_PREFIX: &str = "confirm_del_";
 const CANCEL_ID: &str = "cancel_del";
+/// Options a select menu holds.
+const SELECT_LIMIT: usize = 25;
+
+/// Closing line of a confirmation prompt shown at `now`, saying when a
+/// request waiting `window` for its confirmation expires.
+pub fn expires_line(locale: Locale, now: DateTime<Utc>, window: Duration) -> String {
+    let expires = now + chrono::Duration::from_std(window).unwrap_or_default();
+    tr(
+        locale,
+        MessageKey::ConfirmExpires,
+        &[&fmt::time(expires, DEFAULT_TIMEZONE, TimeStyle::Relative)],
+    )
+}
+
+/// One line of the confirmation prompt: what deleting the symbol takes
+/// with it, e.g. `TSLA — 2 alerts, added 84 days ago`.
+pub fn impact_line(locale: Locale, impact: &SymbolImpact, now: DateTime<Utc>) -> String {
+    let mut parts = Vec::new();
+    if !impact.alert_ids.is_empty() {
+        parts.push(tr(
+            locale,
+            MessageKey::DeleteImpactAlerts,
+            &[&impact.alert_ids.len()],
+        ));
+    }
+    if let Some(added_at) = impact.meta.as_ref().and_then(|m| m.added_at) {
+        let days = (now - added_at).num_days().max(0);
+        parts.push(tr(locale, MessageKey::DeleteImpactAdded, &[&days]));
+    }
+    if parts.is_empty() {
+        parts.push(tr(locale, MessageKey::DeleteImpactNothing, &[]));
+    }
+    format!("{} — {}", impact.symbol, parts.join(", "))
+}
+
+/// The message shown once a delete is done: what went, how many related
+/// records went with it, and anything that couldn't be deleted.
+pub fn outcome_message(locale: Locale, outcome: &DeleteOutcome) -> String {
+    let mut lines = Vec::new();
+    if !outcome.removed.is_empty() {
+        lines.push(tr(
+            locale,
+            MessageKey::Deleted,
+            &[&outcome.removed.join(", ")],
+        ));
+    }
+    if outcome.cleaned > 0 {
+        lines.push(tr(locale, MessageKey::DeleteCleaned, &[&outcome.cleaned]));
+    }
+    if !outcome.failed.is_empty() {
+        let failed: Vec<&str> = outcome.failed.iter().map(|(s, _)| s.as_str()).collect();
+        lines.push(tr(locale, MessageKey::DeleteFailed, &[&failed.join(", ")]));
+    }
+    lines.join("\n")
+}
+
+fn interaction_scope(interaction: &serenity::ComponentInteraction) -> Scope {
+    match interaction.guild_id {
+        Some(guild_id) => Scope::Guild(guild_id.get()),
+        None => Scope::User(interaction.user.id.get()),
+    }
+}
 
 #[poise::command(slash_command)]
-#[instrument(name = "cmd_delete", skip(ctx), fields(user_id = %ctx.author().id))]
-pub async fn delete(ctx: Context<'_>) -> Result<(), Error> {
-    ctx.defer().await?;
-    debug!("deferred reply");
+#[instrument(name = "cmd_delete", skip(ctx), fields(user_id = %ctx.author().id, ?search))]
+pub async fn delete(
+    ctx: Context<'_>,
+    #[description = "Only list watched symbols matching this, typos allowed (e.g. GOOG)"]
+    #[autocomplete = "autocomplete_watched"]
+    search: Option<String>,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    if ephemeral {
+        ctx.defer_ephemeral().await?;
+    } else {
+        ctx.defer().await?;
+    }
+    debug!(ephemeral, "deferred reply");
 
     let symbol_store = ctx.data().symbol_store.clone();
 
-    let symbols: Vec<String> = symbol_store.list().await?;
+    let scope = invocation::scope(ctx);
+    let symbols: Vec<String> = symbol_store.list(scope).await?;
     if symbols.is_empty() {
         info!("attempted delete from empty watchlist");
-        bail!("Watchlist is empty.");
+        bail!(t!(ctx, MessageKey::WatchlistEmpty));
     }
+    // the menu holds 25, so a long watchlist is narrowed down first
+    let symbols = match search.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        Some(query) => {
+            let found = fuzzy::search(&symbols, query, SELECT_LIMIT);
+            if found.is_empty() {
+                info!(%query, "no watched symbol matches");
+                ctx.send(
+                    poise::CreateReply::default()
+                        .content(t!(ctx, MessageKey::DeleteNoMatch, query))
+                        .ephemeral(ephemeral),
+                )
+                .await?;
+                return Ok(());
+            }
+            found
+        }
+        None => symbols,
+    };
 
-    let limit = symbols.len().min(25);
+    let limit = symbols.len().min(SELECT_LIMIT);
 
     let opts: Vec<CreateSelectMenuOption> = symbols
         .into_iter()
@@ -38,7 +137,7 @@ pub async fn delete(ctx: Context<'_>) -> Result<(), Error> {
         SELECT_DELETE_ID,
         CreateSelectMenuKind::String { options: opts },
     )
-    .placeholder("Choose symbols...")
+    .placeholder(t!(ctx, MessageKey::DeleteSelectPlaceholder))
     .min_values(1)
     .max_values(limit as u8);
 
@@ -48,8 +147,9 @@ pub async fn delete(ctx: Context<'_>) -> Result<(), Error> {
 
     ctx.send(
         poise::CreateReply::default()
-            .content("Select symbols to delete (you can pick multiple):")
-            .components(components),
+            .content(t!(ctx, MessageKey::DeleteSelectPrompt))
+            .components(components)
+            .ephemeral(ephemeral),
     )
     .await?;
 
@@ -68,6 +168,13 @@ pub async fn handle_component(
     interaction: &serenity::ComponentInteraction,
 ) -> Result<(), Error> {
     let id = interaction.data.custom_id.as_str();
+    let locale = || {
+        i18n::resolve(
+            &data.symbol_store,
+            interaction.guild_id,
+            Some(&interaction.locale),
+        )
+    };
 
     if id == SELECT_DELETE_ID {
         let values = match &interaction.data.kind {
@@ -98,18 +205,47 @@ pub async fn handle_component(
             "initiated delete confirmation"
         );
 
-        let msg = format!(
-            "Are you sure you want to delete **{}** symbols?\n> {}",
-            values.len(),
-            values.join(", ")
+        let locale = locale().await;
+        let details = match data
+            .symbol_store
+            .impact_report(interaction_scope(interaction), &values)
+            .await
+        {
+            Ok(impact) => {
+                let now = Utc::now();
+                impact
+                    .iter()
+                    .map(|i| impact_line(locale, i, now))
+                    .collect::<Vec<_>>()
+                    .join("\n> ")
+            }
+            Err(e) => {
+                warn!(req_id = %req_id, error = ?e, "failed to gather delete impact");
+                values.join(", ")
+            }
+        };
+        let expires = expires_line(
+            locale,
+            Utc::now(),
+            data.symbol_store.pending_delete_window(),
+        );
+        let prompt = discord_text::truncate_field(
+            &t!(
+                locale,
+                MessageKey::DeleteConfirmPrompt,
+                values.len(),
+                details
+            ),
+            discord_text::CONTENT_LIMIT - expires.chars().count() - 1,
         );
+        let msg = format!("{prompt}\n{expires}");
 
         let row = serenity::CreateActionRow::Buttons(vec![
             serenity::CreateButton::new(format!("{CONFIRM_PREFIX}{req_id}"))
-                .label("Confirm")
+                .label(t!(locale, MessageKey::ButtonConfirm))
                 .style(serenity::ButtonStyle::Danger),
             serenity::CreateButton::new(CANCEL_ID)
-                .label("Cancel")
+                .label(t!(locale, MessageKey::ButtonCancel))
                 .style(serenity::ButtonStyle::Secondary),
         ]);
 
@@ -124,19 +260,29 @@ pub async fn handle_component(
             )
             .await?;
 
+        // count the confirmation window from when the prompt is on screen,
+        // not from the selection
+        if let Err(e) = data
+            .symbol_store
+            .refresh_pending_delete(req_id.clone())
+            .await
+        {
+            warn!(req_id = %req_id, error = ?e, "failed to refresh pending delete");
+        }
         debug!(req_id = %req_id, "updated message to confirmation UI");
         return Ok(());
     }
 
     if id == CANCEL_ID {
         info!("cancelled delete operation");
+        let locale = locale().await;
 
         interaction
             .create_response(
                 ctx,
                 serenity::CreateInteractionResponse::UpdateMessage(
                     serenity::CreateInteractionResponseMessage::new()
-                        .content("Cancelled.")
+                        .content(t!(locale, MessageKey::Cancelled))
                         .components(vec![]),
                 ),
             )
@@ -146,6 +292,8 @@ pub async fn handle_component(
     }
 
     if let Some(req_id) = id.strip_prefix(CONFIRM_PREFIX) {
+        let locale = locale().await;
+
         if let Some(owner) = req_id.split('-').next()
             && owner != interaction.user.id.get().to_string()
         {
@@ -156,7 +304,7 @@ pub async fn handle_component(
                     ctx,
                     serenity::CreateInteractionResponse::Message(
                         serenity::CreateInteractionResponseMessage::new()
-                            .content("❌ You can’t confirm someone else’s delete.")
+                            .content(t!(locale, MessageKey::DeleteNotOwner))
                             .ephemeral(true),
                     ),
                 )
@@ -166,7 +314,7 @@ pub async fn handle_component(
 
         let symbols: Vec<String> = match data
             .symbol_store
-            .get_pending_delete(req_id.to_string())
+            .take_pending_delete(req_id.to_string())
             .await?
         {
             Some(s) => s,
@@ -178,7 +326,7 @@ pub async fn handle_component(
                         ctx,
                         serenity::CreateInteractionResponse::Message(
                             serenity::CreateInteractionResponseMessage::new()
-                                .content("❌ Session expired. Run /delete again.")
+                                .content(t!(locale, MessageKey::DeleteSessionExpired))
                                 .ephemeral(true),
                         ),
                     )
@@ -194,20 +342,20 @@ pub async fn handle_component(
             "confirmed deletion"
         );
 
-        // delete each symbol
-        for sym in &symbols {
-            match data.symbol_store.remove(sym).await {
-                Ok(_) => info!(symbol = %sym, "deleted symbol"),
-                Err(e) => error!(symbol = %sym, error = ?e, "failed to delete symbol"),
-            }
-        }
+        // gathered again: alerts may have changed while the prompt was up
+        let scope = interaction_scope(interaction);
+        let impact = data.symbol_store.impact_report(scope, &symbols).await?;
+        let outcome = data.symbol_store.remove_cascade(scope, &impact).await;
 
         interaction
             .create_response(
                 ctx,
                 serenity::CreateInteractionResponse::UpdateMessage(
                     serenity::CreateInteractionResponseMessage::new()
-                        .content(format!("{} was deleted.", symbols.join(", ")))
+                        .content(discord_text::truncate_field(
+                            &outcome_message(locale, &outcome),
+                            discord_text::CONTENT_LIMIT,
+                        ))
                         .components(vec![]),
                 ),
             )
@@ -0,0 +1,39 @@
+use poise::CreateReply;
+use tracing::{info, instrument};
+
+use super::prefs::Toggle;
+use crate::{Context, Error, i18n::MessageKey, t};
+
+/// Get a DM after each daily scan with signals on your own watchlist
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_digest", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn digest(
+    ctx: Context<'_>,
+    #[description = "Send the daily digest DM"] mode: Toggle,
+) -> Result<(), Error> {
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+
+    let mut prefs = store.get_user_pref(user_id).await?;
+    prefs.daily_digest = mode.enabled();
+    if prefs.daily_digest {
+        // outside a server there's nowhere to say the DM failed
+        prefs.digest_guild = ctx.guild_id().map(|g| g.get());
+    }
+    store.set_user_pref(user_id, &prefs).await?;
+
+    info!(
+        daily_digest = prefs.daily_digest,
+        guild = ?prefs.digest_guild,
+        "updated digest pref"
+    );
+
+    let key = if prefs.daily_digest {
+        MessageKey::DigestOn
+    } else {
+        MessageKey::DigestOff
+    };
+    ctx.send(CreateReply::default().content(t!(ctx, key)).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use chrono::{Duration, NaiveDate, Utc};
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use stock::{
+    calendar::session_date,
+    corporate_actions::{CorporateActions, Dividend, Split},
+};
+use tracing::{debug, info, instrument};
+
+use crate::{
+    Context, Error,
+    discord_text::{FIELD_VALUE_LIMIT, truncate_field},
+    i18n::{self, Locale, MessageKey, tr},
+    invocation, t,
+};
+
+/// How far back past actions are listed.
+const LOOKBACK_DAYS: i64 = 365;
+/// How far ahead announced actions are looked for.
+const LOOKAHEAD_DAYS: i64 = 90;
+
+/// A split as `new-for-old`: `4-for-1`, or `1-for-10` when reverse.
+pub fn split_ratio(split: &Split) -> String {
+    format!("{}-for-{}", split.new_rate, split.old_rate)
+}
+
+/// Cash per share in dollars, with cents and any finer digits Alpaca gives:
+/// `$0.26`, `$0.0125`.
+pub fn dividend_rate(rate: f64) -> String {
+    let cents = format!("{rate:.2}");
+    let exact = format!("{rate:.6}");
+    let exact = exact.trim_end_matches('0');
+    if exact.len() > cents.len() {
+        format!("${exact}")
+    } else {
+        format!("${cents}")
+    }
+}
+
+fn dividend_line(locale: Locale, dividend: &Dividend) -> String {
+    let key = if dividend.special {
+        MessageKey::DividendsSpecialDividend
+    } else {
+        MessageKey::DividendsDividend
+    };
+    let mut line = tr(
+        locale,
+        key,
+        &[&dividend.ex_date, &dividend_rate(dividend.rate)],
+    );
+    if let Some(paid) = dividend.payable_date {
+        line.push_str(&tr(locale, MessageKey::DividendsPayable, &[&paid]));
+    }
+    line
+}
+
+fn split_line(locale: Locale, split: &Split) -> String {
+    let key = if split.is_reverse() {
+        MessageKey::DividendsReverseSplit
+    } else {
+        MessageKey::DividendsSplit
+    };
+    tr(locale, key, &[&split.ex_date, &split_ratio(split)])
+}
+
+/// Dividends and splits as lines, split at `today`: upcoming ones (ex-date
+/// today or later) soonest first, then past ones most recent first.
+pub fn action_lines(
+    locale: Locale,
+    actions: &CorporateActions,
+    today: NaiveDate,
+) -> (Vec<String>, Vec<String>) {
+    let mut dated: Vec<(NaiveDate, String)> = actions
+        .dividends
+        .iter()
+        .map(|d| (d.ex_date, dividend_line(locale, d)))
+        .chain(
+            actions
+                .splits
+                .iter()
+                .map(|s| (s.ex_date, split_line(locale, s))),
+        )
+        .collect();
+    dated.sort_by_key(|(date, _)| *date);
+
+    let (upcoming, mut recent): (Vec<_>, Vec<_>) =
+        dated.into_iter().partition(|(date, _)| *date >= today);
+    recent.reverse();
+    let lines = |dated: Vec<(NaiveDate, String)>| dated.into_iter().map(|(_, l)| l).collect();
+    (lines(upcoming), lines(recent))
+}
+
+/// Recent and announced dividends and splits for a symbol
+///
+/// Covers the past year and anything announced for the next 90 days.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_dividends", skip(ctx), fields(symbol = %symbol))]
+pub async fn dividends(
+    ctx: Context<'_>,
+    #[description = "Symbol to look up"] symbol: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    debug!("deferred reply");
+
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let today = session_date(Utc::now());
+    let actions = ctx
+        .data()
+        .price_client
+        .fetch_corporate_actions(
+            &symbol,
+            today - Duration::days(LOOKBACK_DAYS),
+            today + Duration::days(LOOKAHEAD_DAYS),
+        )
+        .await?;
+    info!(
+        dividends = actions.dividends.len(),
+        splits = actions.splits.len(),
+        "fetched corporate actions"
+    );
+
+    let mut lines: Vec<String> = note.into_iter().collect();
+    if actions.is_empty() {
+        lines.push(t!(ctx, MessageKey::DividendsNone, symbol));
+        ctx.say(lines.join("\n")).await?;
+        return Ok(());
+    }
+
+    let locale = i18n::locale(ctx).await;
+    let (upcoming, recent) = action_lines(locale, &actions, today);
+    let mut embed = CreateEmbed::default().title(t!(ctx, MessageKey::DividendsTitle, symbol));
+    for (key, section) in [
+        (MessageKey::DividendsUpcoming, upcoming),
+        (MessageKey::DividendsRecent, recent),
+    ] {
+        if !section.is_empty() {
+            embed = embed.field(
+                tr(locale, key, &[]),
+                truncate_field(&section.join("\n"), FIELD_VALUE_LIMIT),
+                false,
+            );
+        }
+    }
+
+    let mut reply = CreateReply::default().embed(embed);
+    if !lines.is_empty() {
+        reply = reply.content(lines.join("\n"));
+    }
+    ctx.send(reply).await?;
+    info!("sent corporate actions");
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use poise::CreateReply;
+use stock::{Scope, position};
+use tracing::{info, instrument};
+
+use crate::{Context, Error, fmt, i18n::MessageKey, invocation, t};
+
+/// Record the rough price you bought a symbol at
+///
+/// Scan signals and charts for the symbol then show the unrealized P/L
+/// against it. Leave the price out to clear it. In a server the entry is
+/// the server's, shown on its scan posts, so only members who can manage
+/// it may set one; in DMs it's your own.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_entry", skip(ctx), fields(user_id = %ctx.author().id, symbol = %symbol, ?price))]
+pub async fn entry(
+    ctx: Context<'_>,
+    #[description = "Ticker symbol (e.g., TSLA)"] symbol: String,
+    #[description = "Price you bought at, empty to clear it"] price: Option<f64>,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let store = &ctx.data().symbol_store;
+    let scope = invocation::scope(ctx);
+
+    let is_manager = match scope {
+        Scope::User(_) => true,
+        Scope::Guild(_) => ctx
+            .author_member()
+            .await
+            .and_then(|m| m.permissions)
+            .is_some_and(|p| p.manage_guild()),
+    };
+    if !is_manager {
+        info!("entry refused, not a manager");
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::EntryManagersOnly))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let content = if !store.contains(scope, &symbol).await? {
+        info!("symbol not on watchlist");
+        t!(ctx, MessageKey::NotWatching, symbol)
+    } else {
+        match price {
+            Some(price) if !position::valid_entry(price) => t!(ctx, MessageKey::EntryInvalid),
+            Some(price) => {
+                store.set_entry(scope, &symbol, price).await?;
+                info!("entry price set");
+                t!(ctx, MessageKey::EntrySet, symbol, fmt::price(price))
+            }
+            None => {
+                if store.clear_entry(scope, &symbol).await? {
+                    info!("entry price cleared");
+                    t!(ctx, MessageKey::EntryCleared, symbol)
+                } else {
+                    t!(ctx, MessageKey::EntryNone, symbol)
+                }
+            }
+        }
+    };
+    let content = match note {
+        Some(note) => format!("{note}\n{content}"),
+        None => content,
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(ephemeral))
+        .await?;
+    Ok(())
+}
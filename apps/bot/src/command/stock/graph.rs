@@ -1,31 +1,361 @@
-use chrono::Duration;
-use poise::CreateReply;
-use serenity::all::{CreateAttachment, CreateEmbed};
-use stock::indicators::cdc::{Signal, calculate, generate_chart};
-use tracing::{debug, error, info, instrument};
+use std::time::Duration;
 
-use crate::{Context, Error};
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use poise::{CreateReply, serenity_prelude as serenity};
+use serenity::all::{
+    CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use stock::indicators::annotation::AnnotationKind;
+use stock::indicators::cdc::{Benchmark, ChartOptions, IndicatorSet, Signal, enough_to_chart};
+use stock::indicators::donchian::{self, Breakout};
+use stock::indicators::{relative, vwap};
+use stock::{Bar, ChartJob, PriceSource, Session, Timeframe, assets, calendar, fuzzy};
+use tracing::{debug, error, info, instrument, warn};
 
+use super::watch::{autocomplete_watched, is_watchable};
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::{self, Locale, MessageKey, tr},
+    invocation, report, style, t,
+};
+
+/// Custom ids of the "did you mean" button start with this, followed by
+/// the invocation's id. The click is answered by the invocation itself.
+pub const COMPONENT_PREFIX: &str = "graph_suggest_";
+
+/// How long a mistyped symbol waits on the listed-symbols lookup before
+/// it's charted as typed. The lookup carries on past it, so the next one
+/// finds the list cached.
+const LISTED_WAIT: Duration = Duration::from_secs(5);
+/// How long a "did you mean" button stays live.
+const SUGGESTION_WAIT: Duration = Duration::from_secs(120);
+
+const DONCHIAN_PERIOD: usize = 20;
+/// Bars requested per chart; intraday lookbacks can return more than fit.
+const FETCH_LIMIT: usize = 10_000;
+/// Most recent bars actually drawn.
+const CHART_BARS: usize = 365;
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum TimeframeChoice {
+    #[name = "1Min"]
+    Minute1,
+    #[name = "5Min"]
+    Minute5,
+    #[name = "15Min"]
+    Minute15,
+    #[name = "30Min"]
+    Minute30,
+    #[name = "1Hour"]
+    Hour1,
+    #[name = "1Day"]
+    Day1,
+    #[name = "1Week"]
+    Week1,
+    #[name = "1Month"]
+    Month1,
+}
+
+impl From<TimeframeChoice> for Timeframe {
+    fn from(choice: TimeframeChoice) -> Self {
+        match choice {
+            TimeframeChoice::Minute1 => Timeframe::Minute1,
+            TimeframeChoice::Minute5 => Timeframe::Minute5,
+            TimeframeChoice::Minute15 => Timeframe::Minute15,
+            TimeframeChoice::Minute30 => Timeframe::Minute30,
+            TimeframeChoice::Hour1 => Timeframe::Hour1,
+            TimeframeChoice::Day1 => Timeframe::Day1,
+            TimeframeChoice::Week1 => Timeframe::Week1,
+            TimeframeChoice::Month1 => Timeframe::Month1,
+        }
+    }
+}
+
+/// Timeframe for `symbol` when none was given: the one the user last used
+/// for it, then their default, then daily. Lookup failures fall through.
+async fn remembered_timeframe(ctx: Context<'_>, symbol: &str) -> Timeframe {
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+
+    let remembered = store
+        .get_graph_timeframe(user_id, symbol)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "failed to load remembered timeframe");
+            None
+        });
+    let preferred = match remembered {
+        Some(_) => None,
+        None => store
+            .get_user_pref(user_id)
+            .await
+            .map(|p| p.default_timeframe)
+            .unwrap_or_else(|e| {
+                warn!(error = ?e, "failed to load user prefs");
+                None
+            }),
+    };
+
+    Timeframe::resolve(None, remembered, preferred)
+}
+
+/// Earnings and ex-dividend dates within `bars`, and each bar's session
+/// date to place them by. A failed lookup leaves the chart without them.
+pub async fn chart_events(
+    prices: &dyn PriceSource,
+    symbol: &str,
+    bars: &[Bar],
+) -> (Vec<(NaiveDate, AnnotationKind)>, Option<Vec<NaiveDate>>) {
+    let dates: Vec<NaiveDate> = bars
+        .iter()
+        .map(|b| calendar::session_date(b.timestamp))
+        .collect();
+    let (Some(&start), Some(&end)) = (dates.first(), dates.last()) else {
+        return (Vec::new(), None);
+    };
+    match prices.fetch_events(symbol, start, end).await {
+        Ok(events) => {
+            debug!(count = events.len(), "fetched chart events");
+            (events, Some(dates))
+        }
+        Err(e) => {
+            warn!(error = ?e, "failed to fetch chart events");
+            (Vec::new(), None)
+        }
+    }
+}
+
+/// The watched symbol `symbol` was likely meant to be, when it is neither
+/// watched nor a listed ticker. None whenever that can't be told in time:
+/// charting what was typed is the safe fallback.
+async fn watched_suggestion(ctx: Context<'_>, symbol: &str) -> Option<String> {
+    let watched = match ctx.data().symbol_store.list(invocation::scope(ctx)).await {
+        Ok(watched) => watched,
+        Err(e) => {
+            warn!(error = ?e, "failed to load watchlist for suggestions");
+            return None;
+        }
+    };
+    if watched.iter().any(|w| w == symbol) {
+        return None;
+    }
+    let suggested = fuzzy::suggestion(&watched, symbol)?;
+    if !is_watchable(symbol) {
+        return Some(suggested);
+    }
+
+    // fetched apart from this reply, so a cold list still lands in the
+    // cache when the wait runs out
+    let price_client = ctx.data().price_client.clone();
+    let listing = tokio::spawn(async move { price_client.list_assets().await });
+    match tokio::time::timeout(LISTED_WAIT, listing).await {
+        Ok(Ok(Ok(listed))) if !assets::is_listed(&listed, symbol) => Some(suggested),
+        Ok(Ok(Ok(_))) => None,
+        Ok(Ok(Err(e))) => {
+            warn!(error = ?e, "failed to list symbols for suggestions");
+            None
+        }
+        Ok(Err(e)) => {
+            warn!(error = ?e, "symbol list task failed");
+            None
+        }
+        Err(_) => {
+            debug!("symbol list too slow, charting as typed");
+            None
+        }
+    }
+}
+
+/// Offer `suggested` in place of `typed` and wait for the author to take
+/// it. The command's options stay with this invocation, so the chart drawn
+/// after the click is the one asked for. None when the button isn't pressed
+/// in time; the prompt then loses it.
+async fn take_suggestion(
+    ctx: Context<'_>,
+    typed: &str,
+    suggested: String,
+) -> Result<Option<String>, Error> {
+    info!(%typed, %suggested, "suggesting a watched symbol");
+    let button = CreateButton::new(format!("{COMPONENT_PREFIX}{}", ctx.id()))
+        .label(t!(ctx, MessageKey::GraphChartSuggested, suggested))
+        .style(serenity::ButtonStyle::Primary);
+    let prompt = ctx
+        .send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::GraphDidYouMean, typed, suggested))
+                .components(vec![CreateActionRow::Buttons(vec![button])]),
+        )
+        .await?;
+    let message = prompt.message().await?;
+
+    let Some(click) = serenity::ComponentInteractionCollector::new(ctx.serenity_context())
+        .message_id(message.id)
+        .author_id(ctx.author().id)
+        .timeout(SUGGESTION_WAIT)
+        .await
+    else {
+        debug!("suggestion not taken");
+        prompt
+            .edit(ctx, CreateReply::default().components(vec![]))
+            .await?;
+        return Ok(None);
+    };
+    click
+        .create_response(
+            ctx.serenity_context(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(t!(ctx, MessageKey::GraphCharting, suggested))
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+    info!(%suggested, "suggestion taken");
+    Ok(Some(suggested))
+}
+
+/// The `/stock graph` embed for `symbol` on `signal`, and the chart to
+/// attach when one rendered. Without a chart the embed still gives the
+/// signal, its footer saying the chart is unavailable.
+pub fn graph_message(
+    locale: Locale,
+    symbol: &str,
+    signal: Signal,
+    breakout: Breakout,
+    chart: Option<Vec<u8>>,
+    tz: Tz,
+) -> (CreateEmbed, Option<CreateAttachment>) {
+    let mut description = tr(
+        locale,
+        MessageKey::CurrentSignal,
+        &[&tr(locale, MessageKey::for_signal(signal), &[])],
+    );
+    let breakout_key = match breakout {
+        Breakout::Bullish => Some(MessageKey::DonchianBreakoutBullish),
+        Breakout::Bearish => Some(MessageKey::DonchianBreakoutBearish),
+        Breakout::None => None,
+    };
+    if let Some(key) = breakout_key {
+        description.push('\n');
+        description.push_str(&tr(locale, key, &[&DONCHIAN_PERIOD]));
+    }
+
+    let mut footer = report::generated_at(locale, Utc::now(), tz);
+    if chart.is_none() {
+        footer.push_str(" · ");
+        footer.push_str(&tr(locale, MessageKey::ChartUnavailable, &[]));
+    }
+    let embed = CreateEmbed::default()
+        .title(tr(
+            locale,
+            MessageKey::AnalysisTitle,
+            &[&symbol.to_uppercase()],
+        ))
+        .description(description)
+        .footer(CreateEmbedFooter::new(footer));
+    let Some(chart) = chart else {
+        return (embed, None);
+    };
+
+    let filename = report::chart_filename(symbol, signal);
+    let embed = embed.image(format!("attachment://{filename}"));
+    (embed, Some(CreateAttachment::bytes(chart, filename)))
+}
+
+// each slash command option is an argument
+#[allow(clippy::too_many_arguments)]
 #[poise::command(slash_command)]
-#[instrument(name = "cmd_graph", skip(ctx), fields(symbol = %symbol))]
+#[instrument(name = "cmd_graph", skip(ctx), fields(symbol = %symbol, ?timeframe))]
 pub async fn graph(
     ctx: Context<'_>,
-    #[description = "Symbol of stock to generate"] symbol: String,
+    #[description = "Symbol of stock to generate"]
+    #[autocomplete = "autocomplete_watched"]
+    symbol: String,
+    #[description = "Bar size (defaults to the last one you used for this symbol)"]
+    timeframe: Option<TimeframeChoice>,
+    #[description = "Overlay the 20-day Donchian channel"] donchian: Option<bool>,
+    #[description = "Overlay the VWAP line"] vwap: Option<bool>,
+    #[description = "Indicators to draw, comma-separated: ema, bollinger, volume, rsi, macd, spread (default ema)"]
+    indicators: Option<String>,
+    #[description = "Compare against a benchmark, both rebased to 100 (e.g. SPY)"]
+    benchmark: Option<String>,
+    #[description = "Skip the cache and fetch live prices"] fresh: Option<bool>,
+    #[description = "Label the last crossover with its date (default: when one just fired)"]
+    crossover: Option<bool>,
 ) -> Result<(), Error> {
     info!("starting");
 
+    let benchmark = benchmark
+        .map(|b| b.trim().to_uppercase())
+        .filter(|b| !b.is_empty());
+    if let Some(benchmark) = &benchmark
+        && stock::validate_symbol(benchmark).is_err()
+    {
+        warn!(%benchmark, "invalid benchmark");
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::InvalidSymbols, benchmark))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let indicators = match indicators.as_deref().map(str::parse::<IndicatorSet>) {
+        None => IndicatorSet::default(),
+        Some(Ok(set)) => set,
+        Some(Err(e)) => {
+            warn!(error = %e, "invalid indicator list");
+            ctx.send(
+                CreateReply::default()
+                    .content(t!(
+                        ctx,
+                        MessageKey::InvalidIndicators,
+                        indicators.unwrap_or_default(),
+                        IndicatorSet::NAMES.join(", ")
+                    ))
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    debug!(?indicators, "resolved indicators");
+
     ctx.defer().await?;
     debug!("deferred reply");
 
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let (symbol, note) = match watched_suggestion(ctx, &resolved.symbol).await {
+        Some(suggested) => match take_suggestion(ctx, &resolved.symbol, suggested).await? {
+            Some(suggested) => (suggested, None),
+            None => return Ok(()),
+        },
+        None => {
+            let note = invocation::alias_note(ctx, &resolved).await;
+            (resolved.symbol, note)
+        }
+    };
+
     let price_client = &ctx.data().price_client;
+    let timeframe = match timeframe {
+        Some(choice) => choice.into(),
+        None => remembered_timeframe(ctx, &symbol).await,
+    };
+    debug!(timeframe = timeframe.as_str(), "resolved timeframe");
 
     debug!("fetching price bars");
-    let bars = match price_client
+    let mut bars = match price_client
         .fetch_price(
             symbol.as_str(),
-            Duration::days(300),
-            stock::Timeframe::Day1,
-            365,
+            timeframe.lookback(),
+            timeframe,
+            FETCH_LIMIT,
+            fresh.unwrap_or(false),
+            Session::Extended,
         )
         .await
     {
@@ -35,14 +365,77 @@ pub async fn graph(
         }
         Err(e) => {
             error!(error = ?e, "fetch_price failed");
-            return Err(e.into());
+            return Err(e);
+        }
+    };
+
+    bars.drain(..bars.len().saturating_sub(CHART_BARS));
+    if !enough_to_chart(bars.len(), ctx.data().config.min_chart_bars) {
+        warn!(bars = bars.len(), "too few bars to chart");
+        ctx.say(t!(
+            ctx,
+            MessageKey::ChartInsufficientHistory,
+            symbol,
+            bars.len()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let benchmark = match benchmark {
+        Some(benchmark) => {
+            let benchmark_bars = price_client
+                .fetch_price(
+                    &benchmark,
+                    timeframe.lookback(),
+                    timeframe,
+                    FETCH_LIMIT,
+                    fresh.unwrap_or(false),
+                    Session::Extended,
+                )
+                .await?;
+            if benchmark_bars.is_empty() {
+                warn!(%benchmark, "no benchmark history");
+                ctx.say(t!(ctx, MessageKey::BenchmarkNoData, benchmark))
+                    .await?;
+                return Ok(());
+            }
+
+            // compared bar for bar, on the timestamps both histories have
+            let pairs = relative::align(&bars, &benchmark_bars);
+            if pairs.is_empty() {
+                warn!(%benchmark, "no bars in common with the benchmark");
+                ctx.say(t!(ctx, MessageKey::BenchmarkNoData, benchmark))
+                    .await?;
+                return Ok(());
+            }
+            let closes = pairs.iter().map(|(_, b)| b.close).collect();
+            let kept: Vec<Bar> = pairs.iter().map(|(bar, _)| (*bar).clone()).collect();
+            let dropped = bars.len() - kept.len();
+            bars = kept;
+            info!(%benchmark, bars = bars.len(), dropped, "aligned benchmark");
+            Some(Benchmark {
+                symbol: benchmark,
+                closes,
+            })
         }
+        None => None,
     };
 
+    if let Err(e) = ctx
+        .data()
+        .symbol_store
+        .set_graph_timeframe(ctx.author().id.get(), &symbol, timeframe)
+        .await
+    {
+        warn!(error = ?e, "failed to remember graph timeframe");
+    }
+
+    let tz = invocation::timezone(ctx).await;
     let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
     let dates: Vec<String> = bars
         .iter()
-        .map(|b| b.timestamp.format("%Y-%m-%d").to_string())
+        .map(|b| fmt::time(b.timestamp, tz, TimeStyle::Axis(timeframe)))
         .collect();
 
     debug!(
@@ -51,38 +444,104 @@ pub async fn graph(
         "prepared series"
     );
 
-    let (sig, ema12, ema26) = calculate(&closes);
-    info!(signal = ?sig, "calculated indicators");
+    let strategy = invocation::strategy(ctx).await;
+    let (sig, ema12, ema26) = strategy.evaluate(&bars, ctx.data().config.signal_band_pct);
+    info!(signal = ?sig, %strategy, "calculated indicators");
+
+    let added_price = match ctx
+        .data()
+        .symbol_store
+        .get_meta(invocation::scope(ctx), &symbol)
+        .await
+    {
+        Ok(meta) => meta.added_price,
+        Err(e) => {
+            warn!(error = ?e, "failed to load symbol meta");
+            None
+        }
+    };
+    let entry = ctx
+        .data()
+        .symbol_store
+        .get_entry(invocation::scope(ctx), &symbol)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "failed to load entry price");
+            None
+        });
+    let last_close = closes.last().copied();
+
+    let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+    let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+    let (upper, lower, _) = donchian::calculate(&highs, &lows, DONCHIAN_PERIOD);
+    let breakout = donchian::breakout(&closes, &upper, &lower);
+    info!(breakout = ?breakout, "calculated donchian channel");
+
+    let prefs = invocation::user_prefs(ctx).await;
+    let (annotations, bar_dates) =
+        chart_events(ctx.data().price_client.as_ref(), &symbol, &bars).await;
+    let options = ChartOptions {
+        donchian: donchian.unwrap_or(false).then_some((upper, lower)),
+        vwap: vwap
+            .unwrap_or(false)
+            .then(|| vwap::for_bars(&bars, timeframe)),
+        added_price,
+        added_name: t!(ctx, MessageKey::ChartAddedLine),
+        indicators,
+        volumes: indicators
+            .volume
+            .then(|| bars.iter().map(|b| b.volume).collect()),
+        benchmark,
+        average_names: strategy.line_names(),
+        theme: prefs.chart_theme,
+        scale: prefs.chart_scale,
+        annotate_crossover: crossover.unwrap_or(matches!(sig, Signal::Buy | Signal::Sell)),
+        band_pct: ctx.data().config.signal_band_pct,
+        annotations,
+        bar_dates,
+        ..Default::default()
+    };
 
     debug!("generating chart");
-    let image_bytes = match generate_chart(symbol.as_str(), &closes, &ema12, &ema26, &dates) {
+    let job = ChartJob {
+        symbol: symbol.clone(),
+        closes: closes.into(),
+        ema12: ema12.into(),
+        ema26: ema26.into(),
+        dates: dates.into(),
+        options,
+    };
+    // a broken render costs the reply its chart, not the verdict
+    let chart = match ctx.data().renderer.render(job).await {
         Ok(bytes) => {
             info!(bytes = bytes.len(), "chart generated");
-            bytes
+            Some(bytes)
         }
         Err(e) => {
-            error!(error = ?e, "generate_chart failed");
-            return Err(e.into());
+            warn!(error = ?e, "chart render failed, replying without it");
+            None
         }
     };
 
-    let filename = format!("{}_chart.png", symbol);
-    let attachment = CreateAttachment::bytes(image_bytes, filename.clone());
-
-    let mut embed = CreateEmbed::default()
-        .title(format!("{} Analysis", symbol.to_uppercase()))
-        .description(format!("Current Signal: {:?}", sig))
-        .image(format!("attachment://{}", filename));
-
-    embed = match sig {
-        Signal::Buy | Signal::BullishZone => embed.color(0x00ff00),
-        Signal::Sell | Signal::BearishZone => embed.color(0xff0000),
-        Signal::None => embed.color(0xffffff),
-    };
+    let locale = i18n::locale(ctx).await;
+    let (mut embed, attachment) = graph_message(locale, &symbol, sig, breakout, chart, tz);
+    if let Some((name, value, inline)) = entry
+        .zip(last_close)
+        .and_then(|(entry, close)| report::position_field(locale, entry, close))
+    {
+        embed = embed.field(name, value, inline);
+    }
+    let embed = style::for_invocation(ctx).await.apply(embed, sig);
 
     debug!("sending response");
-    ctx.send(CreateReply::default().embed(embed).attachment(attachment))
-        .await?;
+    let mut reply = CreateReply::default().embed(embed);
+    if let Some(attachment) = attachment {
+        reply = reply.attachment(attachment);
+    }
+    if let Some(note) = note {
+        reply = reply.content(note);
+    }
+    ctx.send(reply).await?;
     info!("sent response");
 
     Ok(())
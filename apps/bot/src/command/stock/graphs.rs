@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use serenity::futures::StreamExt;
+use stock::{
+    Timeframe,
+    scan::{ScanFrame, ScanHit, ScanOutcome, scan_frame},
+};
+use tracing::{debug, info, instrument, warn};
+
+use super::{graph::TimeframeChoice, watch::parse_symbols};
+use crate::{
+    Context, Error,
+    batch::{self, MessageBatcher},
+    discord_text,
+    i18n::{self, MessageKey},
+    invocation, report, style, t,
+};
+
+/// Most symbols one `/stock graphs` charts: two messages of charts.
+pub const MAX_GRAPHS: usize = 20;
+
+/// What a `/stock graphs` list asks for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphsRequest {
+    /// Symbols to chart, uppercased and in input order.
+    pub symbols: Vec<String>,
+    /// Valid symbols past the first [`MAX_GRAPHS`], left out.
+    pub over_cap: Vec<String>,
+    /// Tokens that aren't symbols.
+    pub invalid: Vec<String>,
+}
+
+impl GraphsRequest {
+    /// Split a comma list the way `/stock watch` does, keeping the first
+    /// [`MAX_GRAPHS`] symbols.
+    pub fn parse(raw: &str) -> Self {
+        let (mut symbols, invalid) = parse_symbols(raw);
+        let over_cap = symbols.split_off(symbols.len().min(MAX_GRAPHS));
+        Self {
+            symbols,
+            over_cap,
+            invalid,
+        }
+    }
+}
+
+/// `hits` in the order their symbols were asked for.
+pub fn in_request_order(symbols: &[String], mut hits: Vec<ScanHit>) -> Vec<ScanHit> {
+    let position: HashMap<&str, usize> = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.as_str(), i))
+        .collect();
+    hits.sort_by_key(|hit| position.get(hit.symbol.as_str()).copied());
+    hits
+}
+
+/// Chart several symbols at once, whatever their signal
+///
+/// Takes a comma list, up to 20 symbols. Charts go out ten to a message.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_graphs", skip(ctx), fields(user_id = %ctx.author().id, raw = %symbols))]
+pub async fn graphs(
+    ctx: Context<'_>,
+    #[description = "Symbols to chart, comma-separated (e.g. AAPL,MSFT,NVDA), up to 20"]
+    symbols: String,
+    #[description = "Bar size (default 1Day)"] timeframe: Option<TimeframeChoice>,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    if ephemeral {
+        ctx.defer_ephemeral().await?;
+    } else {
+        ctx.defer().await?;
+    }
+    debug!(ephemeral, "deferred reply");
+
+    let tokens: Vec<&str> = symbols.split(',').collect();
+    let mut lines = Vec::new();
+    let mut resolved = Vec::new();
+    for r in invocation::resolve_symbols(ctx, &tokens).await {
+        lines.extend(invocation::alias_note(ctx, &r).await);
+        resolved.push(r.symbol);
+    }
+    let request = GraphsRequest::parse(&resolved.join(","));
+    info!(
+        count = request.symbols.len(),
+        over_cap = request.over_cap.len(),
+        "parsed symbols"
+    );
+
+    if !request.invalid.is_empty() {
+        warn!(invalid = %request.invalid.join(", "), "rejected invalid symbols");
+        lines.push(t!(
+            ctx,
+            MessageKey::IgnoredInvalidSymbols,
+            request.invalid.join(", ")
+        ));
+    }
+    if !request.over_cap.is_empty() {
+        lines.push(t!(
+            ctx,
+            MessageKey::GraphsCapped,
+            MAX_GRAPHS,
+            request.over_cap.join(", ")
+        ));
+    }
+    if request.symbols.is_empty() {
+        lines.push(t!(ctx, MessageKey::NoValidSymbols));
+        ctx.send(
+            poise::CreateReply::default()
+                .content(lines.join("\n"))
+                .ephemeral(ephemeral),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let timeframe: Timeframe = timeframe.map_or(Timeframe::Day1, Into::into);
+    let locale = i18n::locale(ctx).await;
+    let style = style::for_invocation(ctx).await;
+    let tz = invocation::timezone(ctx).await;
+    let mut results = scan_frame(
+        ctx.data().price_client.clone(),
+        ctx.data().renderer.clone(),
+        request.symbols.clone(),
+        ScanFrame::at(timeframe)
+            .every_symbol()
+            .min_chart_bars(ctx.data().config.min_chart_bars),
+        invocation::strategy(ctx).await,
+        ctx.data().config.signal_band_pct,
+    );
+
+    let mut processed: usize = 0;
+    let mut hits = Vec::with_capacity(request.symbols.len());
+    let mut no_data = Vec::new();
+    let mut failed = Vec::new();
+    while let Some((symbol, res)) = results.next().await {
+        processed += 1;
+        match res {
+            Ok(ScanOutcome { hit: Some(hit), .. }) => hits.push(hit),
+            Ok(_) => no_data.push(symbol),
+            Err(e) => {
+                warn!(%symbol, error = ?e, processed, "chart failed");
+                failed.push(symbol);
+            }
+        }
+    }
+    info!(
+        processed,
+        charts = hits.len(),
+        no_data = no_data.len(),
+        failures = failed.len(),
+        "charted symbols"
+    );
+
+    if !no_data.is_empty() {
+        no_data.sort();
+        lines.push(t!(ctx, MessageKey::GraphsNoData, no_data.join(", ")));
+    }
+    if !lines.is_empty() {
+        ctx.send(
+            poise::CreateReply::default()
+                .content(lines.join("\n"))
+                .ephemeral(ephemeral),
+        )
+        .await?;
+    }
+
+    let chartless = hits.iter().filter(|hit| hit.chart_failed).count();
+    let fallback = hits.iter().filter(|hit| hit.fallback.is_some()).count();
+    let sink = batch::interaction_sink(ctx, ephemeral).await?;
+    let mut batcher = MessageBatcher::new(sink).with_max_bytes(ctx.data().config.max_message_bytes);
+    for hit in in_request_order(&request.symbols, hits) {
+        let symbol = hit.symbol.clone();
+        let (embed, attachment) = report::hit_message(locale, hit, None, &style, tz);
+        // one hit too big to post costs its own post, not the rest
+        if let Err(overflow) = discord_text::check_embed(&embed) {
+            warn!(%symbol, %overflow, ?embed, "hit embed over Discord's limits, skipping it");
+            continue;
+        }
+        batcher.push(embed, attachment, None).await?;
+    }
+    batcher
+        .finish_with(t!(ctx, MessageKey::NoValidSymbols), false, |rejected| {
+            report::incomplete_summary(
+                locale,
+                processed,
+                &failed,
+                report::Chartless {
+                    undrawn: chartless,
+                    rejected,
+                },
+                fallback,
+            )
+        })
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use chrono::Duration;
+use poise::CreateReply;
+use serenity::{
+    all::{CreateAttachment, CreateEmbed},
+    futures::{StreamExt, stream},
+};
+use stock::{
+    QuoteSource, Session, Timeframe,
+    calendar::DEFAULT_TIMEZONE,
+    indicators::cdc::{SPARKLINE_SIZE, generate_sparkline, sparkline_unicode},
+    scan::concurrency_for,
+};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    Context, Error, discord_text,
+    fmt::{self, TimeStyle},
+    i18n::{self, MessageKey, tr},
+    invocation, report, t,
+};
+
+/// Daily closes a sparkline covers, about a month.
+const SPARKLINE_BARS: usize = 20;
+/// Rows that get an image sparkline when asked for.
+const SPARKLINE_ROWS: usize = 10;
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_list", skip(ctx), fields(user_id = %ctx.author().id, ?sparklines))]
+pub async fn list(
+    ctx: Context<'_>,
+    #[description = "Attach a month's trend for the first 10 symbols"] sparklines: Option<bool>,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    // quotes and sparklines can outlast the interaction's three seconds
+    if ephemeral {
+        ctx.defer_ephemeral().await?;
+    } else {
+        ctx.defer().await?;
+    }
+    debug!(ephemeral, "deferred reply");
+    let store = &ctx.data().symbol_store;
+    let scope = invocation::scope(ctx);
+
+    let mut symbols = store.list(scope).await?;
+    if symbols.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::WatchlistEmpty))
+                .ephemeral(ephemeral),
+        )
+        .await?;
+        return Ok(());
+    }
+    symbols.sort();
+
+    let meta = store.list_meta(scope).await?;
+    let quotes = match ctx.data().price_client.fetch_quotes(&symbols).await {
+        Ok(quotes) => quotes,
+        Err(e) => {
+            warn!(error = ?e, "failed to fetch prices for list");
+            Default::default()
+        }
+    };
+
+    // only what the cache already holds, so listing costs no bar requests
+    let price_client = &ctx.data().price_client;
+    let recent = |symbol: &str| -> Option<Vec<f64>> {
+        let bars = price_client.cached_bars(symbol, Timeframe::Day1)?;
+        let start = bars.len().saturating_sub(SPARKLINE_BARS);
+        Some(bars[start..].iter().map(|b| b.close).collect())
+    };
+
+    let locale = i18n::locale(ctx).await;
+    let lines: Vec<String> = symbols
+        .iter()
+        .map(|symbol| {
+            let mut parts = vec![match report::quiet_until(&meta, symbol) {
+                Some(until) => format!(
+                    "🔇 **{symbol}** · {}",
+                    fmt::time(until, DEFAULT_TIMEZONE, TimeStyle::Date)
+                ),
+                None => format!("**{symbol}**"),
+            }];
+            if let Some(quote) = quotes.get(symbol) {
+                let price = quote.price;
+                parts.push(match quote.source {
+                    QuoteSource::LastClose => tr(
+                        locale,
+                        MessageKey::PriceLastClose,
+                        &[&format!("${price:.2}")],
+                    ),
+                    _ => format!("${price:.2}"),
+                });
+                if let Some(since) = meta
+                    .get(symbol)
+                    .and_then(|m| report::since_added(locale, m, price))
+                {
+                    parts.push(since);
+                }
+            }
+            if let Some(closes) = recent(symbol).filter(|c| c.len() > 1) {
+                parts.push(format!("`{}`", sparkline_unicode(&closes)));
+            }
+            if let Some(note) = meta.get(symbol).and_then(|m| m.note.as_deref()) {
+                parts.push(format!("📝 {note}"));
+            }
+            parts.join(" · ")
+        })
+        .collect();
+    debug!(count = lines.len(), "listing watchlist");
+
+    // long watchlists continue in untitled embeds, one per message to stay
+    // under the per-message embed text limit
+    let title = t!(ctx, MessageKey::WatchlistTitle, symbols.len());
+    let pages = discord_text::split_content(&lines.join("\n"), discord_text::DESCRIPTION_LIMIT);
+    let mut attachments = if sparklines.unwrap_or(false) {
+        sparkline_images(ctx, &symbols[..symbols.len().min(SPARKLINE_ROWS)]).await
+    } else {
+        Vec::new()
+    };
+    for (i, page) in pages.into_iter().enumerate() {
+        let mut embed = CreateEmbed::default().description(page);
+        let mut reply = CreateReply::default();
+        if i == 0 {
+            embed = embed.title(&title);
+            reply.attachments = std::mem::take(&mut attachments);
+        }
+        ctx.send(reply.embed(embed).ephemeral(ephemeral)).await?;
+    }
+    Ok(())
+}
+
+/// A sparkline PNG for each of `symbols` whose daily bars can be had, in
+/// their order. Symbols that fail to fetch or draw are left out.
+async fn sparkline_images(ctx: Context<'_>, symbols: &[String]) -> Vec<CreateAttachment> {
+    let price_client = &ctx.data().price_client;
+    let (width, height) = SPARKLINE_SIZE;
+
+    let mut drawn: Vec<(usize, CreateAttachment)> = stream::iter(symbols.iter().enumerate())
+        .map(|(i, symbol)| async move {
+            let bars = match price_client
+                .fetch_price(
+                    symbol,
+                    Duration::days(SPARKLINE_BARS as i64 * 2),
+                    Timeframe::Day1,
+                    SPARKLINE_BARS,
+                    false,
+                    Session::Regular,
+                )
+                .await
+            {
+                Ok(bars) => bars,
+                Err(e) => {
+                    warn!(%symbol, error = ?e, "failed to fetch bars for sparkline");
+                    return None;
+                }
+            };
+            let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+            match generate_sparkline(&closes, width, height) {
+                Ok(png) => {
+                    let filename = format!("{}.png", symbol.replace('/', "-"));
+                    Some((i, CreateAttachment::bytes(png, filename)))
+                }
+                Err(e) => {
+                    debug!(%symbol, error = %e, "no sparkline");
+                    None
+                }
+            }
+        })
+        .buffer_unordered(concurrency_for(price_client.usage()))
+        .filter_map(|drawn| async move { drawn })
+        .collect()
+        .await;
+    drawn.sort_by_key(|(i, _)| *i);
+    debug!(count = drawn.len(), "drew sparklines");
+    drawn.into_iter().map(|(_, png)| png).collect()
+}
@@ -1,20 +1,178 @@
-mod delete;
-mod graph;
+mod about;
+pub mod add_symbol;
+mod admin;
+mod alert;
+mod alias;
+mod analyze;
+mod benchmark;
+pub mod browse;
+pub mod check;
+mod clean;
+mod daily;
+mod data;
+pub mod delete;
+mod digest;
+pub mod dividends;
+mod entry;
+pub mod graph;
+pub mod graphs;
+mod list;
+mod mute;
+mod prefs;
+mod quiet;
+mod report;
+mod runs;
+mod settings;
+mod share;
+pub mod snapshot;
+mod spread;
+mod stats;
+mod strategy;
+pub mod tag;
 mod trigger;
-mod watch;
+pub mod watch;
 
-use crate::{Context, Error};
+use poise::serenity_prelude as serenity;
+use tracing::debug;
+
+use crate::{Context, Data, Error, onboarding};
+use about::about;
+use admin::admin;
+use alert::alert;
+use alias::alias;
+use analyze::analyze;
+use benchmark::benchmark;
+use browse::browse;
+use check::check;
+use clean::clean;
+use daily::daily;
+use data::data;
 use delete::delete;
+use digest::digest;
+use dividends::dividends;
+use entry::entry;
 use graph::graph;
+use graphs::graphs;
+use list::list;
+use mute::{mute, unmute};
+use prefs::prefs;
+use quiet::quiet;
+use report::report;
+use runs::runs;
+use settings::settings;
+use share::share;
+use snapshot::snapshot;
+use spread::spread;
+use stats::stats;
+use strategy::strategy;
+use tag::tag;
 use trigger::trigger;
 use watch::watch;
 
-pub use delete::handle_component;
+/// Route a component interaction to the flow that owns its custom id.
+pub async fn handle_component(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ComponentInteraction,
+) -> Result<(), Error> {
+    if interaction
+        .data
+        .custom_id
+        .starts_with(alert::COMPONENT_PREFIX)
+    {
+        alert::handle_component(ctx, data, interaction).await
+    } else if interaction
+        .data
+        .custom_id
+        .starts_with(add_symbol::COMPONENT_PREFIX)
+    {
+        add_symbol::handle_component(ctx, data, interaction).await
+    } else if interaction
+        .data
+        .custom_id
+        .starts_with(watch::COMPONENT_PREFIX)
+    {
+        watch::handle_component(ctx, data, interaction).await
+    } else if interaction
+        .data
+        .custom_id
+        .starts_with(browse::COMPONENT_PREFIX)
+    {
+        browse::handle_component(ctx, data, interaction).await
+    } else if interaction
+        .data
+        .custom_id
+        .starts_with(graph::COMPONENT_PREFIX)
+    {
+        // answered by the `/stock graph` invocation that posted it
+        Ok(())
+    } else if interaction
+        .data
+        .custom_id
+        .starts_with(onboarding::COMPONENT_PREFIX)
+    {
+        onboarding::handle_component(ctx, data, interaction).await
+    } else {
+        delete::handle_component(ctx, data, interaction).await
+    }
+}
+
+/// Route a submitted modal to the flow that owns its custom id.
+pub async fn handle_modal(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ModalInteraction,
+) -> Result<(), Error> {
+    if interaction
+        .data
+        .custom_id
+        .starts_with(add_symbol::COMPONENT_PREFIX)
+    {
+        add_symbol::handle_modal(ctx, data, interaction).await
+    } else {
+        debug!(custom_id = %interaction.data.custom_id, "ignored unknown modal");
+        Ok(())
+    }
+}
 
 #[poise::command(
     slash_command,
     rename = "stock",
-    subcommands("delete", "watch", "graph", "trigger")
+    subcommands(
+        "delete",
+        "watch",
+        "graph",
+        "graphs",
+        "trigger",
+        "settings",
+        "prefs",
+        "about",
+        "quiet",
+        "mute",
+        "unmute",
+        "tag",
+        "list",
+        "alert",
+        "daily",
+        "runs",
+        "analyze",
+        "dividends",
+        "check",
+        "benchmark",
+        "digest",
+        "stats",
+        "clean",
+        "admin",
+        "strategy",
+        "share",
+        "browse",
+        "alias",
+        "data",
+        "report",
+        "snapshot",
+        "entry",
+        "spread"
+    )
 )]
 pub async fn stock_command(_: Context<'_>) -> Result<(), Error> {
     Ok(())
@@ -0,0 +1,100 @@
+use chrono::{Duration, Utc};
+use poise::CreateReply;
+use stock::calendar::DEFAULT_TIMEZONE;
+use tracing::{info, instrument};
+
+use super::watch::autocomplete_watched;
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::MessageKey,
+    invocation, t,
+};
+
+/// Longest mute with an end date, roughly a year.
+const MAX_DAYS: u32 = 365;
+
+/// Leave a watched symbol out of the scheduled scans for a while
+///
+/// Unlike `/stock quiet`, a muted symbol isn't scanned at all. Without `days`,
+/// it stays muted until `/stock unmute`.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_mute", skip(ctx), fields(user_id = %ctx.author().id, symbol = %symbol, days))]
+pub async fn mute(
+    ctx: Context<'_>,
+    #[description = "Ticker symbol (e.g., TSLA)"]
+    #[autocomplete = "autocomplete_watched"]
+    symbol: String,
+    #[description = "Days to mute for (default: until unmuted)"]
+    #[min = 1]
+    #[max = 365]
+    days: Option<u32>,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let store = &ctx.data().symbol_store;
+    let scope = invocation::scope(ctx);
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let content = if !store.contains(scope, &symbol).await? {
+        info!("symbol not on watchlist");
+        t!(ctx, MessageKey::NotWatching, symbol)
+    } else {
+        let until = days.map(|days| Utc::now() + Duration::days(days.clamp(1, MAX_DAYS).into()));
+        store.mute(scope, &symbol, until).await?;
+        info!(?until, "symbol muted");
+        match until {
+            Some(until) => t!(
+                ctx,
+                MessageKey::MuteSet,
+                symbol,
+                fmt::time(until, DEFAULT_TIMEZONE, TimeStyle::Full)
+            ),
+            None => t!(ctx, MessageKey::MuteSetIndefinite, symbol),
+        }
+    };
+    let content = match note {
+        Some(note) => format!("{note}\n{content}"),
+        None => content,
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(ephemeral))
+        .await?;
+    Ok(())
+}
+
+/// Put a muted symbol back in the scheduled scans
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_unmute", skip(ctx), fields(user_id = %ctx.author().id, symbol = %symbol))]
+pub async fn unmute(
+    ctx: Context<'_>,
+    #[description = "Ticker symbol (e.g., TSLA)"]
+    #[autocomplete = "autocomplete_watched"]
+    symbol: String,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let content = if ctx
+        .data()
+        .symbol_store
+        .unmute(invocation::scope(ctx), &symbol)
+        .await?
+    {
+        info!("symbol unmuted");
+        t!(ctx, MessageKey::MuteCleared, symbol)
+    } else {
+        t!(ctx, MessageKey::MuteNotMuted, symbol)
+    };
+    let content = match note {
+        Some(note) => format!("{note}\n{content}"),
+        None => content,
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(ephemeral))
+        .await?;
+    Ok(())
+}
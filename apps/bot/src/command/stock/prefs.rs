@@ -0,0 +1,238 @@
+use poise::CreateReply;
+use serenity::all::{CreateAttachment, CreateEmbed};
+use stock::{
+    ChartJob, Timeframe,
+    indicators::cdc::{ChartScale, ChartTheme},
+};
+use tracing::{info, instrument};
+
+use super::graph::TimeframeChoice;
+use crate::{Context, Error, i18n::MessageKey, t};
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum Toggle {
+    #[name = "on"]
+    On,
+    #[name = "off"]
+    Off,
+}
+
+impl Toggle {
+    pub fn enabled(self) -> bool {
+        matches!(self, Toggle::On)
+    }
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ThemeChoice {
+    #[name = "dark"]
+    Dark,
+    #[name = "light"]
+    Light,
+    #[name = "colorblind"]
+    Colorblind,
+}
+
+impl From<ThemeChoice> for ChartTheme {
+    fn from(choice: ThemeChoice) -> Self {
+        match choice {
+            ThemeChoice::Dark => ChartTheme::Dark,
+            ThemeChoice::Light => ChartTheme::Light,
+            ThemeChoice::Colorblind => ChartTheme::Colorblind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ScaleChoice {
+    #[name = "1x"]
+    X1,
+    #[name = "2x (sharper on high-DPI screens)"]
+    X2,
+}
+
+impl From<ScaleChoice> for ChartScale {
+    fn from(choice: ScaleChoice) -> Self {
+        match choice {
+            ScaleChoice::X1 => ChartScale::X1,
+            ScaleChoice::X2 => ChartScale::X2,
+        }
+    }
+}
+
+#[poise::command(
+    slash_command,
+    subcommands(
+        "ephemeral",
+        "timeframe",
+        "leaderboard",
+        "theme",
+        "theme_preview",
+        "scale"
+    )
+)]
+pub async fn prefs(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_prefs_ephemeral", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn ephemeral(
+    ctx: Context<'_>,
+    #[description = "Only show watchlist replies to you"] mode: Toggle,
+) -> Result<(), Error> {
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+
+    let mut prefs = store.get_user_pref(user_id).await?;
+    prefs.ephemeral_replies = mode.enabled();
+    store.set_user_pref(user_id, &prefs).await?;
+
+    info!(ephemeral = prefs.ephemeral_replies, "updated user prefs");
+
+    let key = if prefs.ephemeral_replies {
+        MessageKey::PrefsEphemeralOn
+    } else {
+        MessageKey::PrefsEphemeralOff
+    };
+    ctx.send(CreateReply::default().content(t!(ctx, key)).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_prefs_timeframe", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn timeframe(
+    ctx: Context<'_>,
+    #[description = "Chart bar size for symbols you haven't graphed yet (empty for daily)"]
+    timeframe: Option<TimeframeChoice>,
+) -> Result<(), Error> {
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+
+    let mut prefs = store.get_user_pref(user_id).await?;
+    prefs.default_timeframe = timeframe.map(Into::into);
+    store.set_user_pref(user_id, &prefs).await?;
+
+    info!(timeframe = ?prefs.default_timeframe, "updated user prefs");
+
+    let shown = prefs.default_timeframe.unwrap_or(Timeframe::Day1);
+    ctx.send(
+        CreateReply::default()
+            .content(t!(ctx, MessageKey::PrefsTimeframeSet, shown.as_str()))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_prefs_leaderboard", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn leaderboard(
+    ctx: Context<'_>,
+    #[description = "List you among the top users in /stock stats"] mode: Toggle,
+) -> Result<(), Error> {
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+
+    let mut prefs = store.get_user_pref(user_id).await?;
+    prefs.show_in_stats = mode.enabled();
+    store.set_user_pref(user_id, &prefs).await?;
+
+    info!(show_in_stats = prefs.show_in_stats, "updated user prefs");
+
+    let key = if prefs.show_in_stats {
+        MessageKey::PrefsLeaderboardOn
+    } else {
+        MessageKey::PrefsLeaderboardOff
+    };
+    ctx.send(CreateReply::default().content(t!(ctx, key)).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_prefs_theme", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn theme(
+    ctx: Context<'_>,
+    #[description = "Colors your charts are drawn in"] theme: ThemeChoice,
+) -> Result<(), Error> {
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+
+    let mut prefs = store.get_user_pref(user_id).await?;
+    prefs.chart_theme = theme.into();
+    store.set_user_pref(user_id, &prefs).await?;
+
+    info!(theme = ?prefs.chart_theme, "updated user prefs");
+
+    ctx.send(
+        CreateReply::default()
+            .content(t!(
+                ctx,
+                MessageKey::PrefsThemeSet,
+                prefs.chart_theme.as_str()
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// See a sample chart in a theme without switching to it
+///
+/// The chart is a made-up series, so nothing is fetched, and the reply is
+/// only shown to you.
+#[poise::command(slash_command, rename = "theme-preview")]
+#[instrument(name = "cmd_prefs_theme_preview", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn theme_preview(
+    ctx: Context<'_>,
+    #[description = "Theme to preview"] theme: ThemeChoice,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let theme: ChartTheme = theme.into();
+    let image = ctx.data().renderer.render(ChartJob::sample(theme)).await?;
+    info!(?theme, bytes = image.len(), "rendered theme preview");
+
+    let filename = format!("theme_{}.png", theme.as_str());
+    let embed = CreateEmbed::default()
+        .description(t!(ctx, MessageKey::PrefsThemePreview, theme.as_str()))
+        .image(format!("attachment://{filename}"));
+    ctx.send(
+        CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(image, filename))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_prefs_scale", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn scale(
+    ctx: Context<'_>,
+    #[description = "Pixel density your charts are drawn at"] scale: ScaleChoice,
+) -> Result<(), Error> {
+    let store = &ctx.data().symbol_store;
+    let user_id = ctx.author().id.get();
+
+    let mut prefs = store.get_user_pref(user_id).await?;
+    prefs.chart_scale = scale.into();
+    store.set_user_pref(user_id, &prefs).await?;
+
+    info!(scale = ?prefs.chart_scale, "updated user prefs");
+
+    ctx.send(
+        CreateReply::default()
+            .content(t!(
+                ctx,
+                MessageKey::PrefsScaleSet,
+                prefs.chart_scale.as_str()
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
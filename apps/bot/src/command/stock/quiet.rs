@@ -0,0 +1,52 @@
+use chrono::{Duration, Utc};
+use poise::CreateReply;
+use stock::calendar::DEFAULT_TIMEZONE;
+use tracing::{info, instrument};
+
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::MessageKey,
+    invocation, t,
+};
+
+/// Longest quiet period accepted, roughly one quarter.
+const MAX_DAYS: u32 = 90;
+
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_quiet", skip(ctx), fields(user_id = %ctx.author().id, symbol = %symbol, days))]
+pub async fn quiet(
+    ctx: Context<'_>,
+    #[description = "Ticker symbol (e.g., TSLA)"] symbol: String,
+    #[description = "Days to mute signals for, 0 to unmute"]
+    #[max = 90]
+    days: u32,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let store = &ctx.data().symbol_store;
+    let scope = invocation::scope(ctx);
+    let symbol = symbol.trim().to_uppercase();
+
+    let content = if !store.contains(scope, &symbol).await? {
+        info!("symbol not on watchlist");
+        t!(ctx, MessageKey::NotWatching, symbol)
+    } else if days == 0 {
+        store.clear_quiet(scope, &symbol).await?;
+        info!("quiet period cleared");
+        t!(ctx, MessageKey::QuietCleared, symbol)
+    } else {
+        let until = Utc::now() + Duration::days(days.min(MAX_DAYS).into());
+        store.set_quiet(scope, &symbol, until).await?;
+        info!(%until, "quiet period set");
+        t!(
+            ctx,
+            MessageKey::QuietSet,
+            symbol,
+            fmt::time(until, DEFAULT_TIMEZONE, TimeStyle::Full)
+        )
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(ephemeral))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use chrono::{Duration, Utc};
+use poise::CreateReply;
+use serenity::{all::CreateAttachment, futures::StreamExt};
+use stock::{
+    Session, Timeframe,
+    scan::{CONCURRENCY, concurrency_for},
+};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Context, Error,
+    i18n::MessageKey,
+    invocation,
+    report::{CsvRow, build_csv, csv_filename},
+    t,
+};
+
+/// A year of sessions for the 52-week high, with room to spare.
+const LOOKBACK_DAYS: i64 = 380;
+const FETCH_LIMIT: usize = 400;
+
+/// Export indicators for every watched symbol as a CSV file
+///
+/// One row per symbol; symbols that couldn't be fetched keep a row with the error.
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_report", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn report(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer().await?;
+    debug!("deferred reply");
+
+    let data = ctx.data();
+    let symbols = data.symbol_store.list(invocation::scope(ctx)).await?;
+    if symbols.is_empty() {
+        ctx.say(t!(ctx, MessageKey::WatchlistEmpty)).await?;
+        return Ok(());
+    }
+
+    let concurrency = concurrency_for(data.price_client.usage());
+    if concurrency < CONCURRENCY {
+        warn!(
+            concurrency,
+            "request budget low, fetching with reduced concurrency"
+        );
+    }
+    let band_pct = data.config.signal_band_pct;
+    let mut results: Vec<(String, Result<CsvRow, String>)> =
+        serenity::futures::stream::iter(symbols)
+            .map(|symbol| async move {
+                let bars = data
+                    .price_client
+                    .fetch_price(
+                        &symbol,
+                        Duration::days(LOOKBACK_DAYS),
+                        Timeframe::Day1,
+                        FETCH_LIMIT,
+                        false,
+                        Session::Regular,
+                    )
+                    .await;
+                let row = match bars {
+                    Ok(bars) => {
+                        CsvRow::from_bars(&bars, band_pct).ok_or_else(|| "no price history".into())
+                    }
+                    Err(e) => {
+                        warn!(%symbol, error = ?e, "fetch failed");
+                        Err(format!("{e:#}"))
+                    }
+                };
+                (symbol, row)
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+    let csv = build_csv(&results);
+    info!(
+        symbols = results.len(),
+        failed,
+        bytes = csv.len(),
+        "built report"
+    );
+
+    let filename = csv_filename(Utc::now(), invocation::timezone(ctx).await);
+    ctx.send(
+        CreateReply::default()
+            .content(t!(ctx, MessageKey::ReportCsvReady, results.len(), failed))
+            .attachment(CreateAttachment::bytes(csv, filename)),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use chrono::NaiveDate;
+use poise::CreateReply;
+use serenity::all::CreateAttachment;
+use stock::report::RunArchive;
+use tracing::{debug, info, instrument};
+
+use crate::{Context, Error, i18n::MessageKey, t};
+
+/// Dates listed when no date is given.
+const RECENT_RUNS: usize = 10;
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_runs", skip(ctx), fields(user_id = %ctx.author().id, date))]
+pub async fn runs(
+    ctx: Context<'_>,
+    #[description = "Session date, YYYY-MM-DD (empty to list recent runs)"] date: Option<String>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let archive = &ctx.data().run_archive;
+    if !archive.is_enabled() {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::RunsDisabled))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(date) = date else {
+        let dates = archive.list(RECENT_RUNS).await?;
+        debug!(count = dates.len(), "listed recent runs");
+        let content = if dates.is_empty() {
+            t!(ctx, MessageKey::RunsEmpty)
+        } else {
+            let dates: Vec<String> = dates.iter().map(|d| format!("`{d}`")).collect();
+            t!(ctx, MessageKey::RunsRecent, dates.join(", "))
+        };
+        ctx.send(CreateReply::default().content(content).ephemeral(true))
+            .await?;
+        return Ok(());
+    };
+
+    let Ok(date) = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") else {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::RunsInvalidDate, date))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    // the archive spans every guild; only hand back this one's results
+    let run = archive.read(date).await?.and_then(|mut run| {
+        let guild = run.guild(guild_id.get())?.clone();
+        run.guilds = vec![guild];
+        Some(run)
+    });
+    let Some(run) = run else {
+        info!(%date, "no archived run");
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::RunsNotFound, date))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let symbols = run.guilds[0].symbols.len();
+    let json = serde_json::to_vec_pretty(&run)?;
+    info!(%date, symbols, bytes = json.len(), "attaching archived run");
+
+    let attachment = CreateAttachment::bytes(json, RunArchive::file_name(date));
+    ctx.send(
+        CreateReply::default()
+            .content(t!(ctx, MessageKey::RunsAttached, date, symbols))
+            .attachment(attachment)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
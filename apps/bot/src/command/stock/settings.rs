@@ -0,0 +1,599 @@
+use chrono::{Duration, Utc};
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, GuildChannel, Mentionable};
+use tracing::{debug, info, instrument};
+
+use stock::{
+    IntradaySettings, MAX_TAG_LEN, Timeframe,
+    calendar::{DEFAULT_TIMEZONE, parse_timezone},
+    indicators::cdc::{parse_hex_color, sanitize_watermark},
+    normalize_tag,
+};
+
+use super::prefs::Toggle;
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::{Locale, MessageKey},
+    invocation,
+    style::{self, StylePreset},
+    t,
+};
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum Language {
+    #[name = "auto"]
+    Auto,
+    #[name = "en"]
+    En,
+    #[name = "th"]
+    Th,
+}
+
+impl Language {
+    fn locale(self) -> Option<Locale> {
+        match self {
+            Language::Auto => None,
+            Language::En => Some(Locale::En),
+            Language::Th => Some(Locale::Th),
+        }
+    }
+}
+
+/// Bar sizes the intraday scan offers.
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum IntradayTimeframe {
+    #[name = "15Min"]
+    Minute15,
+    #[name = "30Min"]
+    Minute30,
+    #[name = "1Hour"]
+    Hour1,
+}
+
+impl From<IntradayTimeframe> for Timeframe {
+    fn from(choice: IntradayTimeframe) -> Self {
+        match choice {
+            IntradayTimeframe::Minute15 => Timeframe::Minute15,
+            IntradayTimeframe::Minute30 => Timeframe::Minute30,
+            IntradayTimeframe::Hour1 => Timeframe::Hour1,
+        }
+    }
+}
+
+/// `3d 4h`, or `5h 20m` under a day.
+fn remaining(left: Duration) -> String {
+    if left.num_days() > 0 {
+        format!("{}d {}h", left.num_days(), left.num_hours() % 24)
+    } else {
+        format!("{}h {}m", left.num_hours(), left.num_minutes() % 60)
+    }
+}
+
+fn on_off(enabled: bool) -> MessageKey {
+    if enabled {
+        MessageKey::On
+    } else {
+        MessageKey::Off
+    }
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands(
+        "show",
+        "language",
+        "channel",
+        "cashtags",
+        "style",
+        "timezone",
+        "watermark",
+        "intraday",
+        "route",
+        "volume"
+    )
+)]
+pub async fn settings(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+#[instrument(name = "cmd_settings_show", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn show(ctx: Context<'_>) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let settings = ctx
+        .data()
+        .symbol_store
+        .get_guild_settings(guild_id.get())
+        .await?;
+    debug!(?settings, "loaded guild settings");
+
+    let paused_until = settings.daily_paused_at(Utc::now());
+    let language = match settings.locale.clone() {
+        Some(code) => code,
+        None => t!(ctx, MessageKey::LocaleAuto),
+    };
+
+    let daily_channel = match settings.daily_channel {
+        Some(id) => format!("<#{id}>"),
+        None => t!(ctx, MessageKey::NotSet),
+    };
+
+    let watermark = match settings.share_watermark.clone() {
+        Some(text) => text,
+        None => t!(ctx, MessageKey::NotSet),
+    };
+
+    let intraday = match settings.intraday.target() {
+        Some(channel) => intraday_summary(ctx, &settings.intraday, channel).await,
+        None => t!(ctx, MessageKey::Off),
+    };
+
+    let routes = if settings.tag_routes.is_empty() {
+        t!(ctx, MessageKey::NotSet)
+    } else {
+        settings
+            .tag_routes
+            .iter()
+            .map(|(tag, channel)| format!("`{tag}` → <#{channel}>"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let volume_spikes = match settings.volume_spikes() {
+        Some(threshold) => format!("{} ({threshold}×)", t!(ctx, MessageKey::On)),
+        None => t!(ctx, MessageKey::Off),
+    };
+
+    let description = [
+        t!(ctx, MessageKey::SettingsLocale, language),
+        t!(ctx, MessageKey::SettingsDailyChannel, daily_channel),
+        t!(ctx, MessageKey::SettingsIntraday, intraday),
+        t!(ctx, MessageKey::SettingsRoutes, routes),
+        t!(
+            ctx,
+            MessageKey::SettingsCashtagReactions,
+            t!(ctx, on_off(settings.cashtag_reactions))
+        ),
+        t!(
+            ctx,
+            MessageKey::SettingsCashtagReplies,
+            t!(ctx, on_off(settings.cashtag_replies))
+        ),
+        t!(ctx, MessageKey::SettingsVolumeSpikes, volume_spikes),
+        t!(ctx, MessageKey::SettingsStyle, style::describe(&settings)),
+        t!(
+            ctx,
+            MessageKey::SettingsTimezone,
+            settings.timezone().name()
+        ),
+        t!(
+            ctx,
+            MessageKey::SettingsStrategy,
+            invocation::strategy(ctx).await
+        ),
+        t!(ctx, MessageKey::SettingsWatermark, watermark),
+        match paused_until {
+            Some(until) => t!(
+                ctx,
+                MessageKey::SettingsDailyPaused,
+                fmt::time(until, DEFAULT_TIMEZONE, TimeStyle::Full),
+                remaining(until - Utc::now())
+            ),
+            None => t!(ctx, MessageKey::SettingsDailyRunning),
+        },
+    ]
+    .join("\n");
+
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::SettingsTitle))
+        .description(description);
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_language", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn language(
+    ctx: Context<'_>,
+    #[description = "Reply language for this server (auto follows each member)"] language: Language,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.locale = language.locale().map(|l| l.code().to_string());
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    info!(%guild_id, locale = ?settings.locale, "updated guild locale");
+
+    let reply = match language.locale() {
+        Some(locale) => t!(locale, MessageKey::LocaleSet, locale.code()),
+        None => t!(ctx, MessageKey::LocaleCleared),
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_channel", skip(ctx, channel), fields(user_id = %ctx.author().id))]
+pub async fn channel(
+    ctx: Context<'_>,
+    #[description = "Channel for daily signals (leave empty to turn them off)"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.daily_channel = channel.as_ref().map(|c| c.id.get());
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    info!(%guild_id, daily_channel = ?settings.daily_channel, "updated daily channel");
+
+    let reply = match &channel {
+        Some(channel) => t!(ctx, MessageKey::DailyChannelSet, channel.mention()),
+        None => t!(ctx, MessageKey::DailyChannelCleared),
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_cashtags", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn cashtags(
+    ctx: Context<'_>,
+    #[description = "React to $TICKER mentions with the current zone"] reactions: Toggle,
+    #[description = "Also reply with a one-line price"] replies: Option<Toggle>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.cashtag_reactions = reactions.enabled();
+    settings.cashtag_replies = replies.is_some_and(Toggle::enabled);
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    info!(
+        %guild_id,
+        reactions = settings.cashtag_reactions,
+        replies = settings.cashtag_replies,
+        "updated cashtag settings"
+    );
+
+    let mut reply = t!(
+        ctx,
+        MessageKey::CashtagsUpdated,
+        t!(ctx, on_off(settings.cashtag_reactions)),
+        t!(ctx, on_off(settings.cashtag_replies))
+    );
+    if settings.cashtag_reactions && !ctx.data().config.message_content_intent {
+        reply.push('\n');
+        reply.push_str(&t!(ctx, MessageKey::CashtagsIntentDisabled));
+    }
+
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_style", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn style(
+    ctx: Context<'_>,
+    #[description = "Built-in colors and emoji (colorblind uses blue and orange)"]
+    preset: StylePreset,
+    #[description = "Buy color over the preset's, like #0072B2"] buy_color: Option<String>,
+    #[description = "Sell color over the preset's, like #E69F00"] sell_color: Option<String>,
+    #[description = "Buy emoji over the preset's"] buy_emoji: Option<String>,
+    #[description = "Sell emoji over the preset's"] sell_emoji: Option<String>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    // check everything before saving anything
+    let parsed = (|| -> Result<_, Error> {
+        Ok((
+            buy_color.as_deref().map(parse_hex_color).transpose()?,
+            sell_color.as_deref().map(parse_hex_color).transpose()?,
+            buy_emoji.as_deref().map(style::parse_emoji).transpose()?,
+            sell_emoji.as_deref().map(style::parse_emoji).transpose()?,
+        ))
+    })();
+    let (buy_color, sell_color, buy_emoji, sell_emoji) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            info!(error = %e, "rejected style");
+            ctx.send(
+                CreateReply::default()
+                    .content(t!(ctx, MessageKey::StyleInvalid, e))
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    // a new style replaces the old one whole; leaving an override out clears it
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.style_preset = Some(preset.as_str().to_string());
+    settings.buy_color = buy_color;
+    settings.sell_color = sell_color;
+    settings.buy_emoji = buy_emoji;
+    settings.sell_emoji = sell_emoji;
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    let described = style::describe(&settings);
+    info!(%guild_id, style = %described, "updated signal style");
+
+    ctx.send(
+        CreateReply::default()
+            .content(t!(ctx, MessageKey::StyleSet, described))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_timezone", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn timezone(
+    ctx: Context<'_>,
+    #[description = "IANA zone for chart times, like Asia/Bangkok (empty for New York)"]
+    zone: Option<String>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let tz = match zone.as_deref().map(parse_timezone).transpose() {
+        Ok(tz) => tz,
+        Err(e) => {
+            info!(error = %e, "rejected timezone");
+            ctx.send(
+                CreateReply::default()
+                    .content(t!(ctx, MessageKey::TimezoneInvalid, e))
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.display_timezone = tz.map(|tz| tz.name().to_string());
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    let tz = settings.timezone();
+    info!(%guild_id, %tz, "updated display timezone");
+
+    ctx.send(
+        CreateReply::default()
+            .content(t!(
+                ctx,
+                MessageKey::TimezoneSet,
+                tz.name(),
+                fmt::time(Utc::now(), tz, TimeStyle::Plain)
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_watermark", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn watermark(
+    ctx: Context<'_>,
+    #[description = "Text on /stock share images (empty to clear)"]
+    #[max_length = 40]
+    text: Option<String>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.share_watermark = text.as_deref().and_then(sanitize_watermark);
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+    info!(%guild_id, watermark = ?settings.share_watermark, "updated share watermark");
+
+    let content = match &settings.share_watermark {
+        Some(text) => t!(ctx, MessageKey::WatermarkSet, text),
+        None => t!(ctx, MessageKey::WatermarkCleared),
+    };
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+fn intraday_charts(intraday: &IntradaySettings) -> MessageKey {
+    if intraday.charts {
+        MessageKey::IntradayCharts
+    } else {
+        MessageKey::IntradayTextOnly
+    }
+}
+
+/// `1Hour, <#channel>, text only`.
+async fn intraday_summary(ctx: Context<'_>, intraday: &IntradaySettings, channel: u64) -> String {
+    format!(
+        "{}, <#{channel}>, {}",
+        intraday.timeframe().as_str(),
+        t!(ctx, intraday_charts(intraday))
+    )
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_intraday", skip(ctx, channel), fields(user_id = %ctx.author().id))]
+pub async fn intraday(
+    ctx: Context<'_>,
+    #[description = "Post crossovers on intraday bars as they close in market hours"]
+    enabled: Toggle,
+    #[description = "Channel for intraday signals, apart from the daily one"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+    #[description = "Bar size (default 1Hour)"] timeframe: Option<IntradayTimeframe>,
+    #[description = "Attach a chart to each signal (default off, text only)"] charts: Option<
+        Toggle,
+    >,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    let intraday = &mut settings.intraday;
+    intraday.enabled = enabled.enabled();
+    if let Some(channel) = &channel {
+        intraday.channel = Some(channel.id.get());
+    }
+    if let Some(timeframe) = timeframe {
+        intraday.timeframe = Some(timeframe.into());
+    }
+    if let Some(charts) = charts {
+        intraday.charts = charts.enabled();
+    }
+    if intraday.enabled && intraday.channel.is_none() {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::IntradayNeedsChannel))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    let intraday = &settings.intraday;
+    info!(
+        %guild_id,
+        enabled = intraday.enabled,
+        channel = ?intraday.channel,
+        timeframe = intraday.timeframe().as_str(),
+        charts = intraday.charts,
+        "updated intraday settings"
+    );
+
+    let reply = match intraday.target() {
+        Some(channel) => t!(
+            ctx,
+            MessageKey::IntradaySet,
+            intraday.timeframe().as_str(),
+            format!("<#{channel}>"),
+            t!(ctx, intraday_charts(intraday))
+        ),
+        None => t!(ctx, MessageKey::IntradayOff),
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_route", skip(ctx, channel), fields(user_id = %ctx.author().id, tag = %tag))]
+pub async fn route(
+    ctx: Context<'_>,
+    #[description = "Symbol tag, as set with /stock tag"] tag: String,
+    #[description = "Channel for the tag's scan signals (leave empty to remove the route)"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+    let Some(tag) = normalize_tag(&tag) else {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::TagInvalid, tag.trim(), MAX_TAG_LEN))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    match &channel {
+        Some(channel) => settings.tag_routes.insert(tag.clone(), channel.id.get()),
+        None => settings.tag_routes.remove(&tag),
+    };
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+
+    info!(%guild_id, channel = ?channel.as_ref().map(|c| c.id), "updated tag route");
+
+    let reply = match &channel {
+        Some(channel) => t!(ctx, MessageKey::RouteSet, tag, channel.mention()),
+        None => t!(ctx, MessageKey::RouteCleared, tag),
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_settings_volume", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn volume(
+    ctx: Context<'_>,
+    #[description = "Post symbols trading far above their average volume, crossover or not"]
+    enabled: Toggle,
+    #[description = "Multiple of the 20-bar average volume that counts as a spike (default 3)"]
+    #[min = 1.5]
+    #[max = 20.0]
+    threshold: Option<f64>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+
+    let store = &ctx.data().symbol_store;
+    let mut settings = store.get_guild_settings(guild_id.get()).await?;
+    settings.volume_spike_alerts = enabled.enabled();
+    if let Some(threshold) = threshold {
+        settings.volume_spike_threshold = Some(threshold);
+    }
+    store.set_guild_settings(guild_id.get(), &settings).await?;
+    info!(
+        %guild_id,
+        threshold = ?settings.volume_spikes(),
+        "updated volume spike settings"
+    );
+
+    let reply = match settings.volume_spikes() {
+        Some(threshold) => t!(ctx, MessageKey::VolumeSpikesOn, threshold),
+        None => t!(ctx, MessageKey::VolumeSpikesOff),
+    };
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+    Ok(())
+}
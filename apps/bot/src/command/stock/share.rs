@@ -0,0 +1,109 @@
+use poise::CreateReply;
+use serenity::all::CreateAttachment;
+use stock::indicators::cdc::{BrandOptions, ChartOptions, sanitize_watermark};
+use stock::{ChartJob, Session, Timeframe};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::MessageKey,
+    invocation, t,
+};
+
+/// Most recent daily bars drawn, as in `/stock graph`.
+const CHART_BARS: usize = 365;
+
+/// Post a symbol's chart as a branded image that's easy to save
+///
+/// The daily chart under the server's strategy, with a strip along the
+/// bottom naming the bot, the date and the signal, plus the server's
+/// watermark. Sent as a bare image rather than an embed.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_share", skip(ctx), fields(symbol = %symbol))]
+pub async fn share(
+    ctx: Context<'_>,
+    #[description = "Symbol to share the chart of"] symbol: String,
+) -> Result<(), Error> {
+    let symbol = symbol.trim().to_uppercase();
+    if stock::validate_symbol(&symbol).is_err() {
+        warn!("invalid symbol");
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::InvalidSymbols, symbol))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+    let data = ctx.data();
+
+    let mut bars = data
+        .price_client
+        .fetch_price(
+            &symbol,
+            Timeframe::Day1.lookback(),
+            Timeframe::Day1,
+            CHART_BARS,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    bars.drain(..bars.len().saturating_sub(CHART_BARS));
+    let Some(last) = bars.last() else {
+        ctx.say(t!(ctx, MessageKey::AnalyzeNoData, symbol)).await?;
+        return Ok(());
+    };
+    info!(bars = bars.len(), "fetched price bars");
+
+    let tz = invocation::timezone(ctx).await;
+    let strategy = invocation::strategy(ctx).await;
+    let (signal, ema12, ema26) = strategy.evaluate(&bars, data.config.signal_band_pct);
+    let date = fmt::time(last.timestamp, tz, TimeStyle::Axis(Timeframe::Day1));
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let dates: Vec<String> = bars
+        .iter()
+        .map(|b| fmt::time(b.timestamp, tz, TimeStyle::Axis(Timeframe::Day1)))
+        .collect();
+
+    let watermark = match ctx.guild_id() {
+        Some(guild_id) => match data.symbol_store.get_guild_settings(guild_id.get()).await {
+            Ok(settings) => settings.share_watermark,
+            Err(e) => {
+                warn!(error = ?e, "failed to load guild settings");
+                None
+            }
+        },
+        None => None,
+    };
+    let brand = BrandOptions {
+        bot_name: ctx.cache().current_user().name.clone(),
+        date,
+        signal,
+        // stored watermarks are already clean; this covers older values
+        watermark: watermark.as_deref().and_then(sanitize_watermark),
+    };
+    let image = data
+        .renderer
+        .render(ChartJob {
+            symbol: symbol.clone(),
+            closes: closes.into(),
+            ema12: ema12.into(),
+            ema26: ema26.into(),
+            dates: dates.into(),
+            options: ChartOptions {
+                average_names: strategy.line_names(),
+                brand: Some(brand),
+                ..Default::default()
+            },
+        })
+        .await?;
+    info!(?signal, bytes = image.len(), "branded chart");
+
+    let filename = format!("{}_share.png", symbol.replace('/', "-"));
+    ctx.send(CreateReply::default().attachment(CreateAttachment::bytes(image, filename)))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use chrono_tz::Tz;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use stock::{Session, Snapshot, Timeframe, indicators::cdc::Signal};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::{self, Locale, MessageKey, tr},
+    invocation,
+    style::{self, SignalStyle},
+    t,
+};
+
+/// Daily bars fetched for the signal, as the daily scan reads them.
+const FETCH_LIMIT: usize = 365;
+
+/// The last price, the change since the previous close, the book, the
+/// session's bar and `signal` for `symbol` in one embed. `signal` is None
+/// when there wasn't enough history for one.
+pub fn snapshot_message(
+    locale: Locale,
+    symbol: &str,
+    snapshot: &Snapshot,
+    signal: Option<Signal>,
+    style: &SignalStyle,
+    tz: Tz,
+) -> CreateEmbed {
+    let mut embed = CreateEmbed::default().title(tr(
+        locale,
+        MessageKey::SnapshotTitle,
+        &[&symbol.to_uppercase()],
+    ));
+
+    if let Some(price) = snapshot.price() {
+        let mut last = fmt::price(price);
+        if let Some(prev) = snapshot.prev_daily_bar.as_ref().filter(|b| b.close > 0.0) {
+            let change = (price - prev.close) / prev.close * 100.0;
+            last = format!("{last} ({})", fmt::signed_pct(change));
+        }
+        embed = embed.field(tr(locale, MessageKey::SnapshotLast, &[]), last, true);
+    }
+    if let Some(quote) = snapshot
+        .latest_quote
+        .as_ref()
+        .filter(|q| q.spread().is_some())
+    {
+        let book = tr(
+            locale,
+            MessageKey::SnapshotBookValue,
+            &[
+                &fmt::price(quote.bid_price),
+                &fmt::volume(quote.bid_size),
+                &fmt::price(quote.ask_price),
+                &fmt::volume(quote.ask_size),
+            ],
+        );
+        embed = embed.field(tr(locale, MessageKey::SnapshotBook, &[]), book, true);
+    }
+    if let Some(prev) = &snapshot.prev_daily_bar {
+        embed = embed.field(
+            tr(locale, MessageKey::SnapshotPrevClose, &[]),
+            fmt::price(prev.close),
+            true,
+        );
+    }
+    if let Some(bar) = &snapshot.daily_bar {
+        let day = tr(
+            locale,
+            MessageKey::SnapshotDayValue,
+            &[
+                &fmt::price(bar.open),
+                &fmt::price(bar.high),
+                &fmt::price(bar.low),
+                &fmt::price(bar.close),
+                &fmt::volume(bar.volume),
+            ],
+        );
+        embed = embed.field(tr(locale, MessageKey::SnapshotDay, &[]), day, false);
+    }
+    let zone = match signal {
+        Some(signal) => tr(locale, MessageKey::for_signal(signal), &[]),
+        None => tr(locale, MessageKey::SnapshotNoSignal, &[]),
+    };
+    embed = embed.field(tr(locale, MessageKey::SnapshotSignal, &[]), zone, true);
+
+    let as_of = snapshot
+        .latest_trade
+        .as_ref()
+        .map(|t| t.timestamp)
+        .or_else(|| snapshot.daily_bar.as_ref().map(|b| b.timestamp));
+    if let Some(as_of) = as_of {
+        embed = embed.footer(CreateEmbedFooter::new(tr(
+            locale,
+            MessageKey::SnapshotAsOf,
+            &[&fmt::time(as_of, tz, TimeStyle::Plain)],
+        )));
+    }
+    style.apply(embed, signal.unwrap_or(Signal::None))
+}
+
+/// Quote, session bar and signal for a symbol in one embed
+///
+/// The quote, trade and bars come from a single snapshot request; the
+/// signal is worked out from daily bars with this server's strategy.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_snapshot", skip(ctx), fields(symbol = %symbol))]
+pub async fn snapshot(
+    ctx: Context<'_>,
+    #[description = "Symbol to look up"] symbol: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    debug!("deferred reply");
+
+    let data = ctx.data();
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let (snapshot, bars) = tokio::join!(
+        data.price_client.fetch_snapshot(&symbol),
+        data.price_client.fetch_price(
+            &symbol,
+            Timeframe::Day1.lookback(),
+            Timeframe::Day1,
+            FETCH_LIMIT,
+            false,
+            Session::Regular,
+        ),
+    );
+    let Some(snapshot) = snapshot? else {
+        ctx.say(t!(ctx, MessageKey::SnapshotNoData, symbol)).await?;
+        return Ok(());
+    };
+
+    // the snapshot is still worth showing without a signal
+    let signal = match bars {
+        Ok(bars) if !bars.is_empty() => {
+            let strategy = invocation::strategy(ctx).await;
+            let (signal, _, _) = strategy.evaluate(&bars, data.config.signal_band_pct);
+            info!(bars = bars.len(), ?signal, %strategy, "calculated signal");
+            Some(signal).filter(|s| *s != Signal::None)
+        }
+        Ok(_) => None,
+        Err(e) => {
+            warn!(error = ?e, "failed to fetch bars for the signal");
+            None
+        }
+    };
+
+    let locale = i18n::locale(ctx).await;
+    let tz = invocation::timezone(ctx).await;
+    let style = style::for_invocation(ctx).await;
+    let embed = snapshot_message(locale, &symbol, &snapshot, signal, &style, tz);
+
+    let mut reply = CreateReply::default().embed(embed);
+    if let Some(note) = note {
+        reply = reply.content(note);
+    }
+    ctx.send(reply).await?;
+    info!("sent snapshot");
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use poise::CreateReply;
+use serenity::all::{CreateAttachment, CreateEmbed};
+use stock::indicators::cdc::{CHART_LOOKBACK, ChartOptions, IndicatorSet, Signal, enough_to_chart};
+use stock::indicators::spread;
+use stock::{ChartJob, Session, Timeframe};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Context, Error,
+    fmt::{self, TimeStyle},
+    i18n::{self, Locale, MessageKey, tr},
+    invocation, style, t,
+};
+
+/// Daily bars fetched, as the daily scan reads them.
+const FETCH_LIMIT: usize = 365;
+
+/// The latest spread, how much of the window it spent above zero and the
+/// crossovers in it. `spread`, `slow` and `dates` are the charted window,
+/// `crossings` the ones in it, indexed into it.
+fn spread_fields(
+    locale: Locale,
+    spread: &[f64],
+    slow: &[f64],
+    dates: &[String],
+    crossings: &[(usize, Signal)],
+) -> Vec<(String, String, bool)> {
+    let bars = spread.len();
+    let mut fields = Vec::new();
+
+    if let (Some(&now), Some(&slow)) = (spread.last(), slow.last()) {
+        let value = match slow {
+            s if s > 0.0 => tr(
+                locale,
+                MessageKey::SpreadNowValue,
+                &[&fmt::price(now), &fmt::signed_pct(now / s * 100.0)],
+            ),
+            _ => fmt::price(now),
+        };
+        fields.push((tr(locale, MessageKey::SpreadNow, &[]), value, true));
+    }
+
+    let above = spread.iter().filter(|v| **v > 0.0).count();
+    fields.push((
+        tr(locale, MessageKey::SpreadAbove, &[]),
+        tr(locale, MessageKey::SpreadAboveValue, &[&above, &bars]),
+        true,
+    ));
+
+    let value = match crossings.last() {
+        Some(&(index, signal)) => tr(
+            locale,
+            MessageKey::SpreadCrossoversValue,
+            &[
+                &crossings.len(),
+                &bars,
+                &tr(locale, MessageKey::for_signal(signal), &[]),
+                &dates[index],
+            ],
+        ),
+        None => tr(locale, MessageKey::SpreadNoCrossovers, &[&bars]),
+    };
+    fields.push((tr(locale, MessageKey::SpreadCrossovers, &[]), value, false));
+    fields
+}
+
+/// Chart the gap between the fast and slow averages
+///
+/// The spread is drawn around a zero line in the signal's colors, so each
+/// crossover is where it crosses zero and its height is how far apart the
+/// averages have pulled.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_spread", skip(ctx), fields(symbol = %symbol))]
+pub async fn spread(
+    ctx: Context<'_>,
+    #[description = "Symbol to chart"] symbol: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    debug!("deferred reply");
+
+    let data = ctx.data();
+    let resolved = invocation::resolve_symbol(ctx, &symbol).await;
+    let note = invocation::alias_note(ctx, &resolved).await;
+    let symbol = resolved.symbol;
+
+    let bars = data
+        .price_client
+        .fetch_price(
+            &symbol,
+            Timeframe::Day1.lookback(),
+            Timeframe::Day1,
+            FETCH_LIMIT,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    info!(bars = bars.len(), "fetched price bars");
+
+    if !enough_to_chart(bars.len(), data.config.min_chart_bars) {
+        warn!(bars = bars.len(), "too few bars to chart");
+        ctx.say(t!(
+            ctx,
+            MessageKey::ChartInsufficientHistory,
+            symbol,
+            bars.len()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let strategy = invocation::strategy(ctx).await;
+    let (sig, ema12, ema26) = strategy.evaluate(&bars, data.config.signal_band_pct);
+    info!(signal = ?sig, %strategy, "calculated indicators");
+
+    let tz = invocation::timezone(ctx).await;
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let dates: Vec<String> = bars
+        .iter()
+        .map(|b| fmt::time(b.timestamp, tz, TimeStyle::Axis(Timeframe::Day1)))
+        .collect();
+
+    // the figures cover the bars the chart draws
+    let start = bars.len().saturating_sub(CHART_LOOKBACK);
+    let values = spread::calculate(&ema12[start..], &ema26[start..]);
+    // the band's state carries in from before the window
+    let crossings: Vec<(usize, Signal)> =
+        spread::crossings(&ema12, &ema26, data.config.signal_band_pct)
+            .into_iter()
+            .filter(|&(index, _)| index >= start)
+            .map(|(index, signal)| (index - start, signal))
+            .collect();
+    let locale = i18n::locale(ctx).await;
+    let fields = spread_fields(
+        locale,
+        &values,
+        &ema26[start..],
+        &dates[start..],
+        &crossings,
+    );
+
+    let prefs = invocation::user_prefs(ctx).await;
+    let (fast_name, slow_name) = strategy.line_names();
+    let job = ChartJob {
+        symbol: symbol.clone(),
+        closes: closes.into(),
+        ema12: ema12.into(),
+        ema26: ema26.into(),
+        dates: dates.into(),
+        options: ChartOptions {
+            indicators: IndicatorSet {
+                spread: true,
+                ..IndicatorSet::default()
+            },
+            average_names: strategy.line_names(),
+            theme: prefs.chart_theme,
+            scale: prefs.chart_scale,
+            band_pct: data.config.signal_band_pct,
+            ..Default::default()
+        },
+    };
+
+    let mut embed = CreateEmbed::default()
+        .title(t!(
+            ctx,
+            MessageKey::SpreadTitle,
+            symbol,
+            fast_name,
+            slow_name
+        ))
+        .fields(fields);
+    let mut reply = CreateReply::default();
+    // the figures are still worth sending without the chart
+    match data.renderer.render(job).await {
+        Ok(chart) => {
+            info!(bytes = chart.len(), "chart generated");
+            let filename = format!("{symbol}_spread.png");
+            embed = embed.image(format!("attachment://{filename}"));
+            reply = reply.attachment(CreateAttachment::bytes(chart, filename));
+        }
+        Err(e) => warn!(error = ?e, "chart render failed, replying without it"),
+    }
+
+    let embed = style::for_invocation(ctx).await.apply(embed, sig);
+    reply = reply.embed(embed);
+    if let Some(note) = note {
+        reply = reply.content(note);
+    }
+    ctx.send(reply).await?;
+    info!("sent spread");
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use chrono::Utc;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use stock::stats::{self, RETENTION_DAYS};
+use tracing::{info, instrument};
+
+use crate::{
+    Context, Error,
+    discord_text::{self, FIELD_VALUE_LIMIT},
+    i18n::MessageKey,
+    invocation, t,
+};
+
+/// Days summed when none are given.
+const DEFAULT_DAYS: u32 = 7;
+
+/// Show which commands get used, over the last few days
+///
+/// Counts cover this server only. Members only appear among the top users
+/// while opted in with `/stock prefs leaderboard`.
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_stats", skip(ctx), fields(user_id = %ctx.author().id, days))]
+pub async fn stats(
+    ctx: Context<'_>,
+    #[description = "Days to include, today counting as one (default 7)"]
+    #[min = 1]
+    #[max = 90]
+    days: Option<u32>,
+) -> Result<(), Error> {
+    let days = days.unwrap_or(DEFAULT_DAYS).clamp(1, RETENTION_DAYS as u32);
+    ctx.defer_ephemeral().await?;
+
+    let dates = stats::window(Utc::now().date_naive(), days);
+    let usage = ctx
+        .data()
+        .symbol_store
+        .usage_for(invocation::scope(ctx), &dates)
+        .await?;
+    let summary = stats::summarize(&usage);
+    info!(
+        days,
+        total = summary.total,
+        commands = summary.commands.len(),
+        "summarized usage"
+    );
+
+    let Some((busiest, busiest_count)) = summary.busiest_day else {
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::UsageStatsEmpty, days))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let mut commands = Vec::with_capacity(summary.commands.len());
+    for (command, count) in &summary.commands {
+        commands.push(t!(ctx, MessageKey::UsageStatsCommandLine, command, count));
+    }
+    let users = if summary.top_users.is_empty() {
+        t!(ctx, MessageKey::UsageStatsNoUsers)
+    } else {
+        let mut lines = Vec::with_capacity(summary.top_users.len());
+        for (user, count) in &summary.top_users {
+            lines.push(t!(ctx, MessageKey::UsageStatsUserLine, user, count));
+        }
+        lines.join("\n")
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(ctx, MessageKey::UsageStatsTitle, days))
+        .field(
+            t!(ctx, MessageKey::UsageStatsTotal),
+            summary.total.to_string(),
+            true,
+        )
+        .field(
+            t!(ctx, MessageKey::UsageStatsBusiest),
+            t!(
+                ctx,
+                MessageKey::UsageStatsBusiestValue,
+                busiest.format("%Y-%m-%d"),
+                busiest_count
+            ),
+            true,
+        )
+        .field(
+            t!(ctx, MessageKey::UsageStatsCommands),
+            discord_text::truncate_field(&commands.join("\n"), FIELD_VALUE_LIMIT),
+            false,
+        )
+        .field(
+            t!(ctx, MessageKey::UsageStatsTopUsers),
+            discord_text::truncate_field(&users, FIELD_VALUE_LIMIT),
+            false,
+        );
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use poise::CreateReply;
+use stock::{
+    scan::ScanFrame,
+    strategy::{AdxFilter, MaKind, Strategy},
+};
+use tracing::{info, instrument};
+
+use crate::{Context, Error, i18n::MessageKey, invocation, t};
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum MaChoice {
+    #[name = "EMA"]
+    Ema,
+    #[name = "SMA"]
+    Sma,
+}
+
+impl From<MaChoice> for MaKind {
+    fn from(choice: MaChoice) -> Self {
+        match choice {
+            MaChoice::Ema => MaKind::Ema,
+            MaChoice::Sma => MaKind::Sma,
+        }
+    }
+}
+
+/// Pick how the server's signals are computed
+///
+/// Graphs, `/stock trigger` and the daily scan all use it. Without options,
+/// shows the current strategy; options left out keep their current value.
+// each slash command option is an argument
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+#[instrument(name = "cmd_strategy", skip(ctx), fields(user_id = %ctx.author().id))]
+pub async fn strategy(
+    ctx: Context<'_>,
+    #[description = "Kind of moving average that crosses"] ma: Option<MaChoice>,
+    #[description = "Fast average period (default 12)"]
+    #[min = 1]
+    #[max = 199]
+    fast: Option<u32>,
+    #[description = "Slow average period (default 26)"]
+    #[min = 2]
+    #[max = 200]
+    slow: Option<u32>,
+    #[description = "Minimum ADX for a crossover to count (0 turns the filter off)"]
+    #[min = 0]
+    #[max = 100]
+    adx: Option<f64>,
+    #[description = "ADX period (default 14)"]
+    #[min = 2]
+    #[max = 200]
+    adx_period: Option<u32>,
+    #[description = "Go back to EMA 12/26 without filters"] reset: Option<bool>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t!(ctx, MessageKey::GuildOnly)).await?;
+        return Ok(());
+    };
+    let store = &ctx.data().symbol_store;
+
+    if reset.unwrap_or(false) {
+        store.clear_strategy(guild_id.get()).await?;
+        info!(%guild_id, "reset strategy");
+        let content = t!(ctx, MessageKey::StrategyReset, Strategy::default());
+        ctx.send(CreateReply::default().content(content).ephemeral(true))
+            .await?;
+        return Ok(());
+    }
+
+    let current = invocation::strategy(ctx).await;
+    let unchanged =
+        ma.is_none() && fast.is_none() && slow.is_none() && adx.is_none() && adx_period.is_none();
+    if unchanged {
+        let content = t!(ctx, MessageKey::StrategyCurrent, current);
+        ctx.send(CreateReply::default().content(content).ephemeral(true))
+            .await?;
+        return Ok(());
+    }
+
+    let mut strategy = current;
+    if let Some(ma) = ma {
+        strategy.ma = ma.into();
+    }
+    if let Some(fast) = fast {
+        strategy.fast = fast as usize;
+    }
+    if let Some(slow) = slow {
+        strategy.slow = slow as usize;
+    }
+    strategy.adx = match adx {
+        Some(min) if min <= 0.0 => None,
+        Some(min) => Some(AdxFilter {
+            min,
+            ..current.adx.unwrap_or_default()
+        }),
+        None => current.adx,
+    };
+    if let (Some(period), Some(filter)) = (adx_period, strategy.adx.as_mut()) {
+        filter.period = period as usize;
+    }
+
+    // periods have to warm up on the fewest bars any of the scans reads
+    let settings = store.get_guild_settings(guild_id.get()).await?;
+    let mut bars = ScanFrame::daily().expected_bars();
+    if settings.intraday.enabled {
+        let intraday = ScanFrame::intraday(settings.intraday.timeframe(), false);
+        bars = bars.min(intraday.expected_bars());
+    }
+    if let Err(e) = strategy.validate(bars) {
+        info!(error = %e, %strategy, "rejected strategy");
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::StrategyInvalid, e))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    store.set_strategy(guild_id.get(), &strategy).await?;
+    info!(%guild_id, %strategy, "updated strategy");
+
+    let mut content = t!(ctx, MessageKey::StrategySet, strategy);
+    if adx_period.is_some() && strategy.adx.is_none() {
+        content.push('\n');
+        content.push_str(&t!(ctx, MessageKey::StrategyAdxPeriodUnused));
+    }
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+    Ok(())
+}
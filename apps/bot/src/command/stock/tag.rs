@@ -0,0 +1,62 @@
+use poise::CreateReply;
+use stock::{MAX_TAG_LEN, MAX_TAGS, normalize_tag};
+use tracing::{info, instrument};
+
+use super::watch::autocomplete_watched;
+use crate::{Context, Error, i18n::MessageKey, invocation, t};
+
+/// Tags from a comma list, normalized and deduplicated in input order, or
+/// the first token that isn't a tag.
+pub fn parse_tags(raw: &str) -> Result<Vec<String>, String> {
+    let mut tags = Vec::new();
+    for token in raw.split(',').filter(|t| !t.trim().is_empty()) {
+        let tag = normalize_tag(token).ok_or_else(|| token.trim().to_string())?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// Label a watched symbol, e.g. by sector
+///
+/// Tags route the symbol's scan signals with `/stock settings route`.
+/// Leave `tags` empty to clear them.
+#[poise::command(slash_command)]
+#[instrument(name = "cmd_tag", skip(ctx), fields(user_id = %ctx.author().id, symbol = %symbol))]
+pub async fn tag(
+    ctx: Context<'_>,
+    #[description = "Ticker symbol (e.g., TSLA)"]
+    #[autocomplete = "autocomplete_watched"]
+    symbol: String,
+    #[description = "Tags, comma-separated (e.g. tech,ai), replacing the current ones"]
+    tags: Option<String>,
+) -> Result<(), Error> {
+    let ephemeral = invocation::ephemeral(ctx).await;
+    let store = &ctx.data().symbol_store;
+    let scope = invocation::scope(ctx);
+    let symbol = symbol.trim().to_uppercase();
+
+    let content = match parse_tags(tags.as_deref().unwrap_or_default()) {
+        Err(invalid) => t!(ctx, MessageKey::TagInvalid, invalid, MAX_TAG_LEN),
+        Ok(tags) if tags.len() > MAX_TAGS => t!(ctx, MessageKey::TagsTooMany, MAX_TAGS),
+        Ok(_) if !store.contains(scope, &symbol).await? => {
+            info!("symbol not on watchlist");
+            t!(ctx, MessageKey::NotWatching, symbol)
+        }
+        Ok(tags) => {
+            let listed = tags.join(", ");
+            store.set_tags(scope, &symbol, tags).await?;
+            info!(tags = %listed, "tags set");
+            if listed.is_empty() {
+                t!(ctx, MessageKey::TagsCleared, symbol)
+            } else {
+                t!(ctx, MessageKey::TagsSet, symbol, listed)
+            }
+        }
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(ephemeral))
+        .await?;
+    Ok(())
+}
@@ -1,191 +1,161 @@
-use std::mem::take;
 use std::time::Duration as StdDuration;
 
-use chrono::Duration;
-use serenity::all::{CreateAttachment, CreateEmbed};
-use serenity::futures::{StreamExt, stream};
-use stock::Timeframe;
-use stock::indicators::cdc::{Signal, calculate, generate_chart};
+use serenity::futures::StreamExt;
+use stock::scan::{ScanOutcome, scan};
 use tokio::time::timeout;
 
-use crate::{Context, Error};
+use crate::{
+    Context, Error,
+    batch::{self, MessageBatcher},
+    discord_text,
+    i18n::MessageKey,
+    invocation,
+    notify::{SignalEvent, WebhookSink},
+    report, style, t,
+};
 
-use tracing::{debug, error, info, instrument, warn};
-use tracing_futures::Instrument;
-
-struct Hit {
-    embed: CreateEmbed,
-    attachment: CreateAttachment,
-}
+use tracing::{debug, info, instrument, warn};
 
 #[poise::command(slash_command)]
 #[instrument(name = "cmd_trigger", skip(ctx), fields(user_id = %ctx.author().id))]
 pub async fn trigger(ctx: Context<'_>) -> Result<(), Error> {
+    let cooldown = ctx.data().config.trigger_cooldown.as_secs();
+    if cooldown > 0
+        && let Some(remaining) = ctx
+            .data()
+            .symbol_store
+            .try_cooldown("trigger", ctx.author().id.get(), cooldown)
+            .await?
+    {
+        info!(remaining, "trigger on cooldown");
+        ctx.send(
+            poise::CreateReply::default()
+                .content(t!(ctx, MessageKey::TriggerCooldown, remaining))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
     ctx.defer().await?;
     debug!("deferred reply");
 
+    let locale = crate::i18n::locale(ctx).await;
+    let style = style::for_invocation(ctx).await;
+    let tz = invocation::timezone(ctx).await;
     let price_client = ctx.data().price_client.clone();
+    let renderer = ctx.data().renderer.clone();
     let symbol_store = ctx.data().symbol_store.clone();
 
-    let symbols = timeout(StdDuration::from_secs(2), symbol_store.list())
-        .await
-        .map_err(|_| Error::msg("redis list() timed out"))??;
+    let symbols = timeout(
+        StdDuration::from_secs(2),
+        symbol_store.list(invocation::scope(ctx)),
+    )
+    .await
+    .map_err(|_| Error::msg("redis list() timed out"))??;
 
+    let mut meta = symbol_store.list_meta(invocation::scope(ctx)).await?;
     info!(total_symbols = symbols.len(), "loaded symbols");
 
-    let mut embeds: Vec<CreateEmbed> = Vec::new();
-    let mut attachments: Vec<CreateAttachment> = Vec::new();
-
-    const CONCURRENCY: usize = 8;
-    const BATCH_SIZE: usize = 10;
-
-    let mut tasks = stream::iter(symbols)
-        .map(|symbol| {
-            let price_client = price_client.clone();
-
-            let span = tracing::info_span!("trigger_symbol", symbol = %symbol);
-
-            async move {
-                let bars = match price_client
-                    .fetch_price(symbol.as_str(), Duration::days(300), Timeframe::Day1, 365)
-                    .await
-                {
-                    Ok(b) => {
-                        debug!(bars = b.len(), "fetched price bars");
-                        b
-                    }
-                    Err(e) => {
-                        warn!(error = ?e, "fetch_price failed");
-                        return Ok::<Option<Hit>, Error>(None);
-                    }
-                };
-
-                if bars.is_empty() {
-                    debug!("no bars returned");
-                    return Ok::<Option<Hit>, Error>(None);
-                }
-
-                let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
-                let dates: Vec<String> = bars
-                    .iter()
-                    .map(|b| b.timestamp.format("%Y-%m-%d").to_string())
-                    .collect();
-
-                let (sig, ema12, ema26) = calculate(&closes);
-                info!(signal = ?sig, "calculated indicators");
-
-                match sig {
-                    Signal::Buy | Signal::Sell => {
-                        let filename = format!("{}_chart.png", symbol);
-                        let title = format!("{} Analysis", symbol.to_uppercase());
-                        let desc = format!("Current Signal: {:?}", sig);
-
-                        let color = match sig {
-                            Signal::Buy => 0x00FF00,
-                            Signal::Sell => 0xFF0000,
-                            _ => 0x808080,
-                        };
-
-                        let embed = CreateEmbed::default()
-                            .title(title)
-                            .description(desc)
-                            .color(color)
-                            .image(format!("attachment://{}", filename));
-
-                        // chart generation is CPU-bound; run in blocking task
-                        let symbol_s = symbol.to_string();
-                        let closes_c = closes.clone();
-                        let ema12_c = ema12.clone();
-                        let ema26_c = ema26.clone();
-                        let dates_c = dates.clone();
-
-                        debug!("generating chart (spawn_blocking)");
-                        let image_bytes = match tokio::task::spawn_blocking(move || {
-                            generate_chart(&symbol_s, &closes_c, &ema12_c, &ema26_c, &dates_c)
-                        })
-                        .await
-                        {
-                            Ok(r) => match r {
-                                Ok(bytes) => {
-                                    info!(bytes = bytes.len(), "chart generated");
-                                    bytes
-                                }
-                                Err(e) => {
-                                    warn!(error = ?e, "generate_chart failed");
-                                    return Ok::<Option<Hit>, Error>(None);
-                                }
-                            },
-                            Err(e) => {
-                                warn!(error = ?e, "spawn_blocking join failed");
-                                return Ok::<Option<Hit>, Error>(None);
-                            }
-                        };
-
-                        let attachment = CreateAttachment::bytes(image_bytes, filename);
-                        Ok::<Option<Hit>, Error>(Some(Hit { embed, attachment }))
-                    }
-
-                    Signal::BullishZone | Signal::BearishZone | Signal::None => {
-                        debug!("no actionable signal");
-                        Ok::<Option<Hit>, Error>(None)
-                    }
-                }
-            }
-            .instrument(span)
-        })
-        .buffer_unordered(CONCURRENCY);
+    let sink = WebhookSink::new(
+        batch::interaction_sink(ctx, false).await?,
+        ctx.data().webhook.clone(),
+    );
+    let mut batcher = MessageBatcher::new(sink).with_max_bytes(ctx.data().config.max_message_bytes);
+    let mut results = scan(
+        price_client.clone(),
+        renderer,
+        symbols.clone(),
+        invocation::strategy(ctx).await,
+        ctx.data().config.signal_band_pct,
+        ctx.data().config.weekly_confirmation,
+        ctx.data().config.min_chart_bars,
+    );
 
     let mut processed: usize = 0;
     let mut hits: usize = 0;
-    let mut failures: usize = 0;
+    let mut chartless: usize = 0;
+    let mut fallback: usize = 0;
+    let mut failed: Vec<String> = Vec::new();
+    let mut last_readings = Vec::with_capacity(symbols.len());
 
-    while let Some(res) = tasks.next().await {
+    while let Some((symbol, res)) = results.next().await {
         processed += 1;
+        if let Ok(ScanOutcome {
+            reading: Some(reading),
+            ..
+        }) = &res
+        {
+            last_readings.push((symbol.clone(), *reading));
+        }
 
         match res {
-            Ok(Some(hit)) => {
+            Ok(ScanOutcome { hit: Some(hit), .. }) => {
                 hits += 1;
-                embeds.push(hit.embed);
-                attachments.push(hit.attachment);
-
-                if embeds.len() == BATCH_SIZE {
-                    info!(processed, hits, "sending batch");
-                    ctx.send(poise::CreateReply {
-                        embeds: take(&mut embeds),
-                        attachments: take(&mut attachments),
-                        ..Default::default()
-                    })
-                    .await?;
+                chartless += usize::from(hit.chart_failed);
+                fallback += usize::from(hit.fallback.is_some());
+                // muted symbols still post, but stay out of the webhook
+                let quiet = report::quiet_until(&meta, &symbol);
+                let event = quiet.is_none().then(|| SignalEvent::from_hit(&hit));
+                let (embed, attachment) = report::hit_message(locale, hit, quiet, &style, tz);
+                // one hit too big to post costs its own post, not the rest
+                if let Err(overflow) = discord_text::check_embed(&embed) {
+                    warn!(%symbol, %overflow, ?embed, "hit embed over Discord's limits, skipping it");
+                    continue;
                 }
+                batcher.push(embed, attachment, event).await?;
             }
-            Ok(None) => {
-                // normal: no signal or per-symbol skipped due to handled error
+            Ok(_) => {
+                // normal: no actionable signal
             }
             Err(e) => {
-                failures += 1;
-                error!(error = ?e, processed, "symbol task returned Err");
+                warn!(%symbol, error = ?e, processed, "scan failed");
+                failed.push(symbol);
             }
         }
     }
 
-    info!(processed, hits, failures, "completed trigger scan");
+    info!(
+        processed,
+        hits,
+        failures = failed.len(),
+        "completed trigger scan"
+    );
 
-    if !embeds.is_empty() {
-        info!(remaining = embeds.len(), "sending final batch");
-        ctx.send(poise::CreateReply {
-            embeds,
-            attachments,
-            ..Default::default()
-        })
-        .await?;
-    } else {
-        info!("no actionable signals found");
-        ctx.send(poise::CreateReply {
-            content: Some("No Buy/Sell signals found.".to_string()),
-            ..Default::default()
-        })
-        .await?;
+    if let Err(e) = symbol_store
+        .set_last_readings(invocation::scope(ctx), &last_readings)
+        .await
+    {
+        warn!(error = ?e, "failed to save last readings");
     }
 
+    stock::scan::backfill_added(
+        price_client.as_ref(),
+        &symbol_store,
+        invocation::scope(ctx),
+        &symbols,
+        &mut meta,
+    )
+    .await;
+
+    batcher
+        .finish_with(
+            t!(ctx, MessageKey::NoSignalsFound),
+            ctx.data().config.announce_empty_scans,
+            |rejected| {
+                report::incomplete_summary(
+                    locale,
+                    processed,
+                    &failed,
+                    report::Chartless {
+                        undrawn: chartless,
+                        rejected,
+                    },
+                    fallback,
+                )
+            },
+        )
+        .await?;
+
     Ok(())
 }
@@ -1,55 +1,293 @@
-use crate::{Context, Error};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::Utc;
+use poise::{CreateReply, serenity_prelude as serenity};
+use regex::Regex;
+use stock::{
+    Scope,
+    alias::{self, Resolved},
+    assets::{self, SymbolInfo},
+};
+
+use crate::{
+    Context, Data, Error, discord_text,
+    i18n::{self, MessageKey},
+    invocation, t,
+};
 
 use tracing::{debug, info, instrument, warn};
 
+pub const COMPONENT_PREFIX: &str = "watch_";
+const CONFIRM_PREFIX: &str = "watch_confirm_add_";
+const CANCEL_ID: &str = "watch_cancel_add";
+
+/// Lists longer than this are shown for review before anything is added.
+pub const CONFIRM_THRESHOLD: usize = 10;
+
+/// A stock ticker: a letter, then up to nine letters, digits, or `.`/`-`
+/// share-class separators (BRK.B, BF-B).
+static TICKER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Z][A-Z0-9.\-]{0,9}$").unwrap());
+/// A crypto pair like BTC/USD. Bases may start with a digit (1INCH).
+static PAIR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z0-9]{2,10}/[A-Z]{3,5}$").unwrap());
+
+/// Whether `symbol`, already uppercased, is something worth putting on a
+/// watchlist. Stricter than [`stock::validate_symbol`], which only keeps
+/// requests well-formed.
+pub fn is_watchable(symbol: &str) -> bool {
+    (TICKER.is_match(symbol) || PAIR.is_match(symbol)) && stock::validate_symbol(symbol).is_ok()
+}
+
+/// Longest autocomplete choice name or value Discord accepts.
+const CHOICE_LIMIT: usize = 100;
+/// Most autocomplete choices Discord shows.
+const MAX_CHOICES: usize = 25;
+
+/// `partial` split before its last comma-separated entry: what to keep as
+/// typed, with its trailing comma, and the entry being typed.
+pub fn split_last_entry(partial: &str) -> (&str, &str) {
+    match partial.rfind(',') {
+        Some(i) => (&partial[..=i], &partial[i + 1..]),
+        None => ("", partial),
+    }
+}
+
+/// What a symbol looks like in the autocomplete list:
+/// `AAPL · Apple Inc. Common Stock (NASDAQ)`, cut to fit.
+pub fn choice_name(info: &SymbolInfo) -> String {
+    let name = format!("{} · {} ({})", info.symbol, info.name, info.exchange);
+    discord_text::truncate_field(&name, CHOICE_LIMIT)
+}
+
+/// Suggest listed symbols for the entry being typed, by ticker or company
+/// name. Entries before it are kept as typed.
+async fn autocomplete_symbol(ctx: Context<'_>, partial: &str) -> Vec<serenity::AutocompleteChoice> {
+    let (kept, typing) = split_last_entry(partial);
+    if typing.trim().is_empty() {
+        return Vec::new();
+    }
+    match ctx.data().price_client.search_symbols(typing).await {
+        Ok(found) => found
+            .iter()
+            .map(|info| (choice_name(info), format!("{kept}{}", info.symbol)))
+            .filter(|(_, value)| value.chars().count() <= CHOICE_LIMIT)
+            .map(|(name, value)| serenity::AutocompleteChoice::new(name, value))
+            .collect(),
+        Err(e) => {
+            warn!(error = ?e, "symbol search failed");
+            Vec::new()
+        }
+    }
+}
+
+/// Suggest watched symbols for a command that acts on one, by prefix or
+/// within a typo of what's typed. With nothing typed yet, the first few
+/// watched symbols are offered.
+pub async fn autocomplete_watched(
+    ctx: Context<'_>,
+    partial: &str,
+) -> Vec<serenity::AutocompleteChoice> {
+    let store = &ctx.data().symbol_store;
+    let scope = invocation::scope(ctx);
+    let found = if partial.trim().is_empty() {
+        store.list(scope).await.map(|mut symbols| {
+            symbols.sort();
+            symbols.truncate(MAX_CHOICES);
+            symbols
+        })
+    } else {
+        store.search(scope, partial, MAX_CHOICES).await
+    };
+    match found {
+        Ok(found) => found
+            .into_iter()
+            .map(|symbol| serenity::AutocompleteChoice::new(symbol.clone(), symbol))
+            .collect(),
+        Err(e) => {
+            warn!(error = ?e, "watchlist search failed");
+            Vec::new()
+        }
+    }
+}
+
+/// Split `/stock watch` input on commas into uppercased symbols worth
+/// watching and the tokens that aren't, each in input order. Blank tokens
+/// are dropped, and a symbol given more than once, in any case, is kept
+/// only where it first appears.
+pub fn parse_symbols(raw: &str) -> (Vec<String>, Vec<String>) {
+    let mut seen = HashSet::new();
+    raw.split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty() && seen.insert(s.clone()))
+        .partition(|s| is_watchable(s))
+}
+
+/// Why a symbol can't be watched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejected {
+    /// Nothing was given.
+    Empty,
+    /// Not shaped like a ticker or a crypto pair.
+    Invalid(String),
+    /// Well formed, but not on the asset list.
+    Unlisted(String),
+}
+
+/// A single symbol or alias checked the way `/stock watch` checks each entry:
+/// the alias resolved, the ticker's form, then its listing when `listed`
+/// isn't empty.
+pub fn validate_one(
+    raw: &str,
+    aliases: &HashMap<String, String>,
+    listed: &[SymbolInfo],
+) -> Result<Resolved, Rejected> {
+    if raw.trim().is_empty() {
+        return Err(Rejected::Empty);
+    }
+    let resolved = alias::resolve(raw, aliases, |t| assets::is_listed(listed, t));
+    let (mut valid, invalid) = parse_symbols(&resolved.symbol);
+    if let Some(invalid) = invalid.into_iter().next() {
+        return Err(Rejected::Invalid(invalid));
+    }
+    // one symbol per entry; a list is for the slash command
+    if valid.len() != 1 {
+        return Err(Rejected::Invalid(resolved.symbol));
+    }
+    let symbol = valid.remove(0);
+    if !listed.is_empty() && !assets::unlisted(listed, std::slice::from_ref(&symbol)).is_empty() {
+        return Err(Rejected::Unlisted(symbol));
+    }
+    Ok(Resolved { symbol, ..resolved })
+}
+
+/// Whether adding `count` symbols at once should ask for confirmation.
+pub fn needs_confirmation(count: usize) -> bool {
+    count > CONFIRM_THRESHOLD
+}
+
 #[poise::command(slash_command)]
 #[instrument(name = "cmd_watch", skip(ctx), fields(user_id = %ctx.author().id, raw = %symbol))]
 pub async fn watch(
     ctx: Context<'_>,
-    #[description = "Ticker symbol(s), comma-separated (e.g., TSLA,MSFT)"] symbol: String,
+    #[description = "Ticker symbol(s), comma-separated (e.g., TSLA,MSFT)"]
+    #[autocomplete = "autocomplete_symbol"]
+    symbol: String,
 ) -> Result<(), Error> {
-    ctx.defer().await?;
-    debug!("deferred reply");
+    let ephemeral = invocation::ephemeral(ctx).await;
+    if ephemeral {
+        ctx.defer_ephemeral().await?;
+    } else {
+        ctx.defer().await?;
+    }
+    debug!(ephemeral, "deferred reply");
 
     let store = &ctx.data().symbol_store;
+    let scope = invocation::scope(ctx);
 
-    let symbols: Vec<String> = symbol
-        .split(',')
-        .map(|s| s.trim().to_uppercase())
-        .filter(|s| !s.is_empty())
-        .collect();
+    // names are resolved one by one, so "apple, msft" watches both
+    let tokens: Vec<&str> = symbol.split(',').collect();
+    let resolved = invocation::resolve_symbols(ctx, &tokens).await;
+    let mut notes = Vec::new();
+    for r in &resolved {
+        if let Some(note) = invocation::alias_note(ctx, r).await {
+            notes.push(note);
+        }
+    }
+    if !notes.is_empty() {
+        discord_text::send_split(ctx, &notes.join("\n"), ephemeral, vec![]).await?;
+    }
+
+    let resolved: Vec<&str> = resolved.iter().map(|r| r.symbol.as_str()).collect();
+    let (mut symbols, invalid) = parse_symbols(&resolved.join(","));
 
     info!(count = symbols.len(), symbols = %symbols.join(", "), "parsed symbols");
 
+    if !invalid.is_empty() {
+        warn!(invalid = %invalid.join(", "), "rejected invalid symbols");
+        let content = t!(ctx, MessageKey::IgnoredInvalidSymbols, invalid.join(", "));
+        discord_text::send_split(ctx, &content, ephemeral, vec![]).await?;
+    }
+
+    // an unreachable asset list isn't a reason to refuse the watch
+    match ctx.data().price_client.list_assets().await {
+        Ok(listed) if !listed.is_empty() => {
+            let unlisted: Vec<String> = assets::unlisted(&listed, &symbols)
+                .into_iter()
+                .cloned()
+                .collect();
+            if !unlisted.is_empty() {
+                warn!(unlisted = %unlisted.join(", "), "rejected unlisted symbols");
+                symbols.retain(|s| !unlisted.contains(s));
+                let content = t!(ctx, MessageKey::UnlistedSymbols, unlisted.join(", "));
+                discord_text::send_split(ctx, &content, ephemeral, vec![]).await?;
+            }
+        }
+        Ok(_) => debug!("no asset list, skipping listing check"),
+        Err(e) => warn!(error = ?e, "failed to load asset list, skipping listing check"),
+    }
+
     if symbols.is_empty() {
         warn!("no valid symbols provided");
-        ctx.say("No valid symbols provided.").await?;
+        ctx.send(
+            CreateReply::default()
+                .content(t!(ctx, MessageKey::NoValidSymbols))
+                .ephemeral(ephemeral),
+        )
+        .await?;
         return Ok(());
     }
 
-    let mut added = Vec::new();
-    let mut already = Vec::new();
+    if needs_confirmation(symbols.len()) {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let req_id = format!("{}-{ts}", ctx.author().id.get());
 
-    for sym in symbols {
-        match store.add(&sym).await? {
-            true => {
-                info!(symbol = %sym, "added symbol to watchlist");
-                added.push(sym);
-            }
-            false => {
-                debug!(symbol = %sym, "symbol already watched");
-                already.push(sym);
-            }
-        }
+        store
+            .set_pending_add(req_id.clone(), symbols.clone())
+            .await?;
+
+        info!(
+            req_id = %req_id,
+            count = symbols.len(),
+            "initiated watch confirmation"
+        );
+
+        let row = serenity::CreateActionRow::Buttons(vec![
+            serenity::CreateButton::new(format!("{CONFIRM_PREFIX}{req_id}"))
+                .label(t!(ctx, MessageKey::ButtonConfirm))
+                .style(serenity::ButtonStyle::Primary),
+            serenity::CreateButton::new(CANCEL_ID)
+                .label(t!(ctx, MessageKey::ButtonCancel))
+                .style(serenity::ButtonStyle::Secondary),
+        ]);
+
+        let content = t!(
+            ctx,
+            MessageKey::WatchConfirmPrompt,
+            symbols.len(),
+            symbols.join(", ")
+        );
+        discord_text::send_split(ctx, &content, ephemeral, vec![row]).await?;
+        return Ok(());
     }
 
+    let added = store.add_many(scope, &symbols).await?;
+    let already = already_watched(&symbols, &added);
+
     if !added.is_empty() {
-        ctx.say(format!("Now watching: {}", added.join(", ")))
-            .await?;
+        record_added(ctx.data(), scope, &added).await;
+        let content = t!(ctx, MessageKey::NowWatching, added.join(", "));
+        discord_text::send_split(ctx, &content, ephemeral, vec![]).await?;
     }
     if !already.is_empty() {
-        ctx.say(format!("Already watching: {}", already.join(", ")))
-            .await?;
+        let content = t!(ctx, MessageKey::AlreadyWatching, already.join(", "));
+        discord_text::send_split(ctx, &content, ephemeral, vec![]).await?;
     }
 
     info!(
@@ -60,3 +298,169 @@ pub async fn watch(
 
     Ok(())
 }
+
+#[instrument(
+    name = "component_watch",
+    skip(ctx, data, interaction),
+    fields(custom_id = %interaction.data.custom_id, user_id = %interaction.user.id)
+)]
+pub async fn handle_component(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ComponentInteraction,
+) -> Result<(), Error> {
+    let id = interaction.data.custom_id.as_str();
+    let locale = i18n::resolve(
+        &data.symbol_store,
+        interaction.guild_id,
+        Some(&interaction.locale),
+    )
+    .await;
+
+    if id == CANCEL_ID {
+        info!("cancelled watch operation");
+
+        interaction
+            .create_response(
+                ctx,
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .content(t!(locale, MessageKey::Cancelled))
+                        .components(vec![]),
+                ),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    let Some(req_id) = id.strip_prefix(CONFIRM_PREFIX) else {
+        debug!("ignored unrelated component interaction");
+        return Ok(());
+    };
+
+    if let Some(owner) = req_id.split('-').next()
+        && owner != interaction.user.id.get().to_string()
+    {
+        warn!(owner = %owner, req_id = %req_id, "attempted to confirm request");
+
+        interaction
+            .create_response(
+                ctx,
+                serenity::CreateInteractionResponse::Message(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .content(t!(locale, MessageKey::WatchNotOwner))
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let Some(symbols) = data
+        .symbol_store
+        .get_pending_add(req_id.to_string())
+        .await?
+    else {
+        warn!(req_id = %req_id, "session expired or not found");
+
+        interaction
+            .create_response(
+                ctx,
+                serenity::CreateInteractionResponse::Message(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .content(t!(locale, MessageKey::WatchSessionExpired))
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let scope = match interaction.guild_id {
+        Some(guild_id) => Scope::Guild(guild_id.get()),
+        None => Scope::User(interaction.user.id.get()),
+    };
+
+    let added = data.symbol_store.add_many(scope, &symbols).await?;
+    let already = already_watched(&symbols, &added);
+
+    info!(
+        req_id = %req_id,
+        added = added.len(),
+        already = already.len(),
+        "confirmed watch"
+    );
+
+    let mut lines = Vec::new();
+    if !added.is_empty() {
+        lines.push(t!(locale, MessageKey::NowWatching, added.join(", ")));
+    }
+    if !already.is_empty() {
+        lines.push(t!(locale, MessageKey::AlreadyWatching, already.join(", ")));
+    }
+
+    // the first chunk replaces the prompt, any others follow it
+    let mut chunks =
+        discord_text::split_content(&lines.join("\n"), discord_text::CONTENT_LIMIT).into_iter();
+    interaction
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                serenity::CreateInteractionResponseMessage::new()
+                    .content(chunks.next().unwrap_or_default())
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+    for chunk in chunks {
+        interaction
+            .create_followup(
+                ctx,
+                serenity::CreateInteractionResponseFollowup::new().content(chunk),
+            )
+            .await?;
+    }
+
+    // after responding, so a slow price fetch can't time out the interaction
+    record_added(data, scope, &added).await;
+
+    debug!("updated message to final result");
+    Ok(())
+}
+
+/// The symbols in `requested` that `add_many` didn't newly add.
+pub fn already_watched(requested: &[String], added: &[String]) -> Vec<String> {
+    requested
+        .iter()
+        .filter(|s| !added.contains(s))
+        .cloned()
+        .collect()
+}
+
+/// Best-effort: remember when and at what price `symbols` were added. A
+/// failed price fetch stores no price and the next scan backfills it.
+pub async fn record_added(data: &Data, scope: Scope, symbols: &[String]) {
+    let now = Utc::now();
+
+    let quotes = match data.price_client.fetch_quotes(symbols).await {
+        Ok(quotes) => quotes,
+        Err(e) => {
+            warn!(error = ?e, "failed to fetch prices for added symbols");
+            Default::default()
+        }
+    };
+
+    for symbol in symbols {
+        let price = quotes.get(symbol).map(|q| q.price);
+        if let Err(e) = data
+            .symbol_store
+            .record_added(scope, symbol, now, price)
+            .await
+        {
+            warn!(%symbol, error = ?e, "failed to record added price");
+        } else {
+            debug!(%symbol, ?price, "recorded added price");
+        }
+    }
+}
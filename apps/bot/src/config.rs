@@ -1,16 +1,184 @@
-use std::env::var;
+use std::{env::var, time::Duration};
+
+use anyhow::{Context, Result, bail, ensure};
+use stock::{
+    earnings,
+    indicators::cdc::{DEFAULT_BAND_PCT, DEFAULT_MIN_CHART_BARS, SignalColors, parse_hex_color},
+};
+
+use crate::{batch::DEFAULT_MAX_BYTES, registration::CommandScope};
+
+/// How far back a reversal looks for the post it supersedes, in days, when
+/// `SUPERSEDE_DAYS` isn't set.
+pub const DEFAULT_SUPERSEDE_DAYS: i64 = 5;
+
+/// Where the JSON API listens when `API_ADDR` isn't set.
+pub const DEFAULT_API_ADDR: &str = "0.0.0.0:8080";
 
 #[derive(Clone)]
 pub struct Config {
     pub discord_token: String,
     pub version: String,
+    /// Per-user cooldown between `/stock trigger` runs. Zero disables it.
+    pub trigger_cooldown: Duration,
+    /// Cumulative attachment bytes allowed in one outgoing message.
+    pub max_message_bytes: usize,
+    /// Whether scans that find nothing post a "no signals" message.
+    pub announce_empty_scans: bool,
+    /// Request the privileged MessageContent intent so cashtags can be read.
+    pub message_content_intent: bool,
+    /// Endpoint receiving a signed JSON POST for every scan hit.
+    pub webhook_url: Option<String>,
+    /// Shared HMAC-SHA256 key for the webhook signature header.
+    pub webhook_secret: Option<String>,
+    /// How far, in percent, the fast EMA must clear the slow one before the
+    /// CDC signal flips.
+    pub signal_band_pct: f64,
+    /// Fewest bars a chart is drawn from, from `MIN_CHART_BARS`. Shorter
+    /// histories still get a signal, just no chart.
+    pub min_chart_bars: usize,
+    /// Embed colors for each signal, from `COLOR_BUY`, `COLOR_SELL`,
+    /// `COLOR_BULLISH`, `COLOR_BEARISH` and `COLOR_NONE`.
+    pub signal_colors: SignalColors,
+    /// Whether the scheduled daily scan runs. Off in dev so it doesn't post
+    /// into a real channel.
+    pub daily_enabled: bool,
+    /// Only post a daily crossover when the weekly trend agrees.
+    pub weekly_confirmation: bool,
+    /// Fill the bar cache ten minutes before the daily run.
+    pub daily_prewarm: bool,
+    /// Days after a daily hit within which the opposite crossover edits its
+    /// post to say it was superseded. Zero turns that off.
+    pub supersede_days: i64,
+    /// Days ahead the daily summary warns of a watched symbol's earnings,
+    /// from `EARNINGS_WITHIN_DAYS`. Only with an earnings provider set.
+    pub earnings_within_days: i64,
+    /// Bearer token for the JSON API. The API only runs when it's set.
+    pub api_token: Option<String>,
+    /// Address the JSON API listens on.
+    pub api_addr: String,
+    /// Where slash commands are registered, from `COMMAND_SCOPE`.
+    pub command_scope: CommandScope,
+    /// Users besides the application's owners who may run owner-only
+    /// commands, from `OWNER_IDS`.
+    pub owner_ids: Vec<u64>,
+}
+
+/// An on/off env value: `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off`,
+/// any case. Unset or empty falls back to `default`; anything else is an
+/// error, so a typo doesn't quietly leave the default in place.
+pub fn parse_flag(raw: Option<&str>, default: bool) -> Result<bool> {
+    match raw.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("") => Ok(default),
+        Some("true" | "1" | "yes" | "on") => Ok(true),
+        Some("false" | "0" | "no" | "off") => Ok(false),
+        Some(other) => bail!("expected true/false, 1/0, yes/no or on/off, got {other:?}"),
+    }
+}
+
+/// `name` parsed by [`parse_flag`].
+fn flag_var(name: &str, default: bool) -> Result<bool> {
+    parse_flag(var(name).ok().as_deref(), default).with_context(|| format!("invalid {name}"))
+}
+
+/// `name` parsed as a hex color, or `default` when it isn't set.
+fn color_var(name: &str, default: u32) -> Result<u32> {
+    match var(name) {
+        Ok(raw) if !raw.trim().is_empty() => {
+            parse_hex_color(&raw).with_context(|| format!("invalid {name}"))
+        }
+        _ => Ok(default),
+    }
+}
+
+/// Signal colors from the environment. Zones follow the Buy/Sell colors
+/// unless set on their own.
+pub fn signal_colors_from_env() -> Result<SignalColors> {
+    let default = SignalColors::default();
+    let buy = color_var("COLOR_BUY", default.buy)?;
+    let sell = color_var("COLOR_SELL", default.sell)?;
+    Ok(SignalColors {
+        buy,
+        sell,
+        bullish_zone: color_var("COLOR_BULLISH", buy)?,
+        bearish_zone: color_var("COLOR_BEARISH", sell)?,
+        none: color_var("COLOR_NONE", default.none)?,
+    })
+}
+
+/// A comma-separated list of user ids, as in `OWNER_IDS`. Empty is none.
+pub fn parse_user_ids(raw: &str) -> Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for id in raw.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        let parsed: u64 = id
+            .parse()
+            .with_context(|| format!("invalid user id {id:?}"))?;
+        ensure!(parsed != 0, "invalid user id 0");
+        if !ids.contains(&parsed) {
+            ids.push(parsed);
+        }
+    }
+    Ok(ids)
 }
 
 impl Config {
-    pub fn from_env() -> Self {
-        Self {
+    /// Load from the environment. Fails on malformed colors, flags or
+    /// command scope so a typo is caught at startup rather than later.
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
             discord_token: var("DISCORD_TOKEN").expect("DISCORD_TOKEN not set"),
             version: var("APP_VERSION").unwrap_or_else(|_| "Unknown".to_string()),
-        }
+            trigger_cooldown: Duration::from_secs(
+                var("TRIGGER_COOLDOWN_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(60),
+            ),
+            max_message_bytes: var("DISCORD_MAX_MESSAGE_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_BYTES),
+            announce_empty_scans: var("ANNOUNCE_EMPTY_SCANS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(true),
+            message_content_intent: var("MESSAGE_CONTENT_INTENT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(false),
+            webhook_url: var("WEBHOOK_URL").ok().filter(|v| !v.is_empty()),
+            webhook_secret: var("WEBHOOK_SECRET").ok().filter(|v| !v.is_empty()),
+            signal_band_pct: var("SIGNAL_BAND_PCT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v: &f64| v.is_finite() && *v >= 0.0)
+                .unwrap_or(DEFAULT_BAND_PCT),
+            min_chart_bars: var("MIN_CHART_BARS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v: &usize| *v > 0)
+                .unwrap_or(DEFAULT_MIN_CHART_BARS),
+            signal_colors: signal_colors_from_env()?,
+            daily_enabled: flag_var("DAILY_ENABLED", true)?,
+            weekly_confirmation: flag_var("WEEKLY_CONFIRMATION", false)?,
+            daily_prewarm: flag_var("DAILY_PREWARM", false)?,
+            supersede_days: var("SUPERSEDE_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SUPERSEDE_DAYS),
+            earnings_within_days: var("EARNINGS_WITHIN_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v: &i64| *v >= 0)
+                .unwrap_or(earnings::DEFAULT_WITHIN_DAYS),
+            api_token: var("API_TOKEN").ok().filter(|v| !v.is_empty()),
+            api_addr: var("API_ADDR").unwrap_or_else(|_| DEFAULT_API_ADDR.to_string()),
+            command_scope: var("COMMAND_SCOPE")
+                .unwrap_or_default()
+                .parse()
+                .context("invalid COMMAND_SCOPE")?,
+            owner_ids: parse_user_ids(&var("OWNER_IDS").unwrap_or_default())
+                .context("invalid OWNER_IDS")?,
+        })
     }
 }
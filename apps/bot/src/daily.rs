@@ -1,186 +1,1148 @@
-use std::{mem::take, sync::Arc};
-
-use anyhow::Result;
-use bot::Error;
-use chrono::Duration;
-use serenity::all::{ChannelId, CreateAttachment, CreateEmbed, CreateMessage, Http};
-use serenity::futures::{StreamExt, stream};
-use stock::indicators::cdc::{Signal, calculate, generate_chart};
-use stock::{PriceClient, SymbolStore, Timeframe};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    mem::take,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
+use anyhow::{Result, ensure};
+use chrono::{NaiveDate, Utc};
+use serenity::all::{
+    ChannelId, CreateAttachment, CreateEmbed, CreateMessage, EditMessage, GuildId, Http,
+    Mentionable, MessageId, ReactionType, UserId,
+};
+use serenity::futures::StreamExt;
+use stock::report::{GuildRun, RunArchive, RunRecord, SymbolRecord};
+use stock::scan::{ScanOutcome, prewarm, scan_timed, top_setup};
+use stock::strategy::Strategy;
+use stock::timing::{DEGRADATION_WINDOW, Recorder, ScanTimings, Stage, degraded};
+use stock::{
+    ChartRenderer, PostedSignal, PriceSource, Scope, SymbolStore, Timeframe,
+    calendar::{DEFAULT_TIMEZONE, session_date},
+    earnings::{self, EarningsSource},
+    indicators::cdc::Signal,
+};
 use tracing::{debug, error, info, instrument, warn};
-use tracing_futures::Instrument;
 
-struct Hit {
-    embed: CreateEmbed,
-    attachment: CreateAttachment,
+use crate::{
+    batch::{BatchSink, ChannelSink, FallbackSink, IdempotentSink, MessageBatcher, PostedHit},
+    command::stock::add_symbol,
+    config::Config,
+    digest::{self, DigestHit},
+    discord_text::{CONTENT_LIMIT, split_content},
+    i18n::{self, Locale, MessageKey},
+    notify::{SignalEvent, Webhook, WebhookSink},
+    report, spotlight,
+    style::SignalStyle,
+    t,
+};
+
+/// Where one guild's daily scan goes.
+#[derive(Debug, Clone, Copy)]
+pub struct Target {
+    pub guild_id: GuildId,
+    pub channel: ChannelId,
+    /// First run since the guild's daily pause ran out.
+    pub resumed: bool,
 }
 
+/// Run the daily scan for every guild with a configured channel. `fallback`
+/// is the legacy `DISCORD_TARGET_CHANNEL_ID` target, used only when its guild
+/// hasn't configured a channel of its own. Only the first run of a session
+/// date does anything, so a restart or misfired trigger doesn't post twice.
+#[allow(clippy::too_many_arguments)]
 #[instrument(
     name = "run_daily",
-    skip(http, price_client, symbol_store),
-    fields(channel_id = %channel)
+    skip(
+        http,
+        price_client,
+        renderer,
+        symbol_store,
+        config,
+        webhook,
+        archive,
+        earnings
+    )
 )]
 pub async fn run_daily(
     http: Arc<Http>,
-    channel: ChannelId,
-    price_client: Arc<PriceClient>,
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
     symbol_store: Arc<SymbolStore>,
+    config: Config,
+    webhook: Option<Webhook>,
+    archive: Arc<RunArchive>,
+    earnings: Option<Arc<dyn EarningsSource>>,
+    fallback: Option<Target>,
 ) -> Result<()> {
-    let symbols = symbol_store.list().await?;
-    info!(total_symbols = symbols.len(), "loaded symbols");
+    let started_at = Utc::now();
+    let today = session_date(started_at);
+    match symbol_store.try_daily_lock(today).await {
+        Ok(true) => debug!(%today, "claimed daily lock"),
+        Ok(false) => {
+            info!(%today, "already ran today");
+            return Ok(());
+        }
+        // a missed day is worse than a repeat, and posted batches are
+        // still skipped by the ledger
+        Err(e) => warn!(error = ?e, "failed to take daily lock, running anyway"),
+    }
 
-    let mut embeds: Vec<CreateEmbed> = Vec::new();
-    let mut attachments: Vec<CreateAttachment> = Vec::new();
-
-    const CONCURRENCY: usize = 8;
-    const BATCH_SIZE: usize = 10;
-
-    let mut tasks = stream::iter(symbols)
-        .map(|symbol| {
-            let price_client = price_client.clone();
-
-            let span = tracing::info_span!("daily_symbol", symbol = %symbol);
-
-            async move {
-                let bars = match price_client
-                    .fetch_price(symbol.as_str(), Duration::days(300), Timeframe::Day1, 365)
-                    .await
-                {
-                    Ok(b) => {
-                        debug!(bars = b.len(), "fetched price bars");
-                        b
-                    }
-                    Err(e) => {
-                        warn!(error = ?e, "fetch_price failed");
-                        return Ok::<Option<Hit>, Error>(None);
-                    }
-                };
-
-                if bars.is_empty() {
-                    debug!("no bars returned");
-                    return Ok::<Option<Hit>, Error>(None);
-                }
+    let started = Instant::now();
+    let history = wall_history(&archive, today).await;
+    let timings = Arc::new(ScanTimings::default());
+    let targets = match targets(&symbol_store, fallback, true).await {
+        Ok(targets) => targets,
+        Err(e) => {
+            release_daily_lock(&symbol_store, today).await;
+            return Err(e);
+        }
+    };
+    info!(guilds = targets.len(), "resolved daily targets");
 
-                let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
-                let dates: Vec<String> = bars
-                    .iter()
-                    .map(|b| b.timestamp.format("%Y-%m-%d").to_string())
-                    .collect();
-
-                let (sig, ema12, ema26) = calculate(&closes);
-                info!(signal = ?sig, "calculated indicators");
-
-                match sig {
-                    Signal::Buy | Signal::Sell => {
-                        let filename = format!("{}_chart.png", symbol);
-                        let title = format!("{} Analysis", symbol.to_uppercase());
-                        let desc = format!("Current Signal: {:?}", sig);
-
-                        let color = match sig {
-                            Signal::Buy => 0x00FF00,
-                            Signal::Sell => 0xFF0000,
-                            _ => 0x808080,
-                        };
-
-                        let embed = CreateEmbed::default()
-                            .title(title)
-                            .description(desc)
-                            .color(color)
-                            .image(format!("attachment://{}", filename));
-
-                        let symbol_s = symbol.to_string();
-                        let closes_c = closes.clone();
-                        let ema12_c = ema12.clone();
-                        let ema26_c = ema26.clone();
-                        let dates_c = dates.clone();
-
-                        debug!("generating chart (spawn_blocking)");
-                        let image_bytes = match tokio::task::spawn_blocking(move || {
-                            generate_chart(&symbol_s, &closes_c, &ema12_c, &ema26_c, &dates_c)
-                        })
-                        .await
-                        {
-                            Ok(r) => match r {
-                                Ok(bytes) => {
-                                    info!(bytes = bytes.len(), "chart generated");
-                                    bytes
-                                }
-                                Err(e) => {
-                                    warn!(error = ?e, "generate_chart failed");
-                                    return Ok::<Option<Hit>, Error>(None);
-                                }
-                            },
-                            Err(e) => {
-                                warn!(error = ?e, "spawn_blocking join failed");
-                                return Ok::<Option<Hit>, Error>(None);
-                            }
-                        };
-
-                        let attachment = CreateAttachment::bytes(image_bytes, filename);
-                        Ok::<Option<Hit>, Error>(Some(Hit { embed, attachment }))
-                    }
-                    Signal::BullishZone | Signal::BearishZone | Signal::None => {
-                        debug!("no actionable signal");
-                        Ok::<Option<Hit>, Error>(None)
-                    }
-                }
+    let channels: HashMap<GuildId, ChannelId> =
+        targets.iter().map(|t| (t.guild_id, t.channel)).collect();
+
+    let mut run = RunRecord::new(today, started_at);
+    let mut digest_hits = Vec::new();
+    let mut complete = true;
+    for target in targets {
+        match run_guild(
+            http.clone(),
+            target,
+            price_client.clone(),
+            renderer.clone(),
+            symbol_store.clone(),
+            &config,
+            webhook.clone(),
+            earnings.as_deref(),
+            &RunClock {
+                started,
+                history: &history,
+                timings: timings.clone(),
+            },
+        )
+        .await
+        {
+            Ok((guild_run, hits)) => {
+                run.guilds.push(guild_run);
+                digest_hits.extend(hits);
+            }
+            Err(e) => {
+                complete = false;
+                error!(guild_id = %target.guild_id, error = ?e, "daily run failed for guild")
+            }
+        }
+    }
+
+    let scanned: HashSet<String> = run
+        .guilds
+        .iter()
+        .flat_map(|g| g.symbols.iter().map(|r| r.symbol.to_uppercase()))
+        .collect();
+    match scan_personal(
+        price_client.clone(),
+        renderer.clone(),
+        &symbol_store,
+        &config,
+        &scanned,
+        timings.clone(),
+    )
+    .await
+    {
+        Ok(hits) => digest_hits.extend(hits),
+        Err(e) => error!(error = ?e, "personal watchlist scan failed"),
+    }
+
+    run.finished_at = Utc::now();
+    let wall = started.elapsed();
+    let run_timings = timings.finish(wall);
+    run.timings = Some(run_timings);
+    match degraded(wall, &history) {
+        Some(usual) => error!(
+            wall_ms = run_timings.wall_ms,
+            usual_ms = usual.as_millis() as u64,
+            fetch_ms = run_timings.fetch_ms,
+            indicators_ms = run_timings.indicators_ms,
+            render_ms = run_timings.render_ms,
+            send_ms = run_timings.send_ms,
+            "daily run degraded"
+        ),
+        None => info!(
+            wall_ms = run_timings.wall_ms,
+            fetch_ms = run_timings.fetch_ms,
+            indicators_ms = run_timings.indicators_ms,
+            render_ms = run_timings.render_ms,
+            send_ms = run_timings.send_ms,
+            "daily run timings"
+        ),
+    }
+    if let Err(e) = archive.write(&run).await {
+        warn!(error = ?e, "failed to archive daily run");
+    }
+
+    if let Err(e) = symbol_store.set_last_daily_run(Utc::now()).await {
+        warn!(error = ?e, "failed to record daily run");
+    }
+
+    if let Err(e) = send_digests(&http, &symbol_store, &digest_hits, &channels).await {
+        error!(error = ?e, "daily digests failed");
+    }
+
+    // a re-run posts what didn't go out; the ledger skips the batches that did
+    if !complete || run.guilds.iter().any(|g| !g.failed_batches.is_empty()) {
+        warn!(%today, "daily run incomplete, releasing the lock for a retry");
+        release_daily_lock(&symbol_store, today).await;
+    }
+
+    Ok(())
+}
+
+/// Hand back today's claim on the daily run. A lock that can't be released
+/// just expires, holding off a retry until then.
+async fn release_daily_lock(symbol_store: &SymbolStore, today: NaiveDate) {
+    if let Err(e) = symbol_store.release_daily_lock(today).await {
+        warn!(error = ?e, "failed to release daily lock");
+    }
+}
+
+/// `symbols` less the ones muted in `scope`. Mutes that can't be read
+/// mute nothing: a noisy symbol beats a silently skipped one.
+pub async fn unmuted(
+    symbol_store: &SymbolStore,
+    scope: Scope,
+    symbols: Vec<String>,
+) -> Vec<String> {
+    let muted = match symbol_store.list_muted(scope).await {
+        Ok(muted) => muted,
+        Err(e) => {
+            warn!(%scope, error = ?e, "failed to load muted symbols");
+            return symbols;
+        }
+    };
+    if muted.is_empty() {
+        return symbols;
+    }
+    let (muted, kept): (Vec<String>, Vec<String>) = symbols
+        .into_iter()
+        .partition(|s| muted.contains_key(&s.to_uppercase()));
+    info!(%scope, muted = %muted.join(", "), "skipping muted symbols");
+    kept
+}
+
+/// How long prewarmed bars are kept: past the 16:30 run started ten minutes
+/// later, with room for it to work through every guild.
+const PREWARM_TTL: Duration = Duration::from_secs(20 * 60);
+
+/// Fill the bar cache with every symbol the next daily run will scan, so
+/// that run mostly reads from the cache and posts right after the close.
+/// Best-effort throughout: whatever isn't warmed is fetched by the run as
+/// usual.
+#[instrument(name = "prewarm_daily", skip_all)]
+pub async fn prewarm_daily(
+    price_client: Arc<dyn PriceSource>,
+    symbol_store: Arc<SymbolStore>,
+    fallback: Option<Target>,
+) {
+    let targets = match targets(&symbol_store, fallback, false).await {
+        Ok(targets) => targets,
+        Err(e) => {
+            warn!(error = ?e, "failed to resolve daily targets, not prewarming");
+            return;
+        }
+    };
+
+    let mut scopes: Vec<Scope> = targets
+        .iter()
+        .map(|t| Scope::Guild(t.guild_id.get()))
+        .collect();
+    match symbol_store.list_digest_users().await {
+        Ok(users) => scopes.extend(users.into_iter().map(Scope::User)),
+        Err(e) => warn!(error = ?e, "failed to list digest users"),
+    }
+
+    let mut symbols = BTreeSet::new();
+    for scope in scopes {
+        match symbol_store.list(scope).await {
+            Ok(watchlist) => symbols.extend(watchlist.into_iter().map(|s| s.to_uppercase())),
+            Err(e) => warn!(%scope, error = ?e, "failed to load watchlist"),
+        }
+    }
+    let symbols: Vec<String> = symbols.into_iter().collect();
+    info!(count = symbols.len(), "prewarming daily symbols");
+
+    prewarm(price_client, &symbols, PREWARM_TTL).await;
+}
+
+/// Scan the symbols on digest members' personal watchlists that no server
+/// scanned today. Their hits only go out in digests, so they carry no link.
+#[instrument(name = "scan_personal", skip_all, fields(scanned = scanned.len()))]
+async fn scan_personal(
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbol_store: &SymbolStore,
+    config: &Config,
+    scanned: &HashSet<String>,
+    timings: Arc<ScanTimings>,
+) -> Result<Vec<DigestHit>> {
+    let mut watchlists = Vec::new();
+    for user_id in symbol_store.list_digest_users().await? {
+        match symbol_store.list(Scope::User(user_id)).await {
+            Ok(watchlist) => {
+                watchlists.push(unmuted(symbol_store, Scope::User(user_id), watchlist).await)
             }
-            .instrument(span)
+            Err(e) => warn!(user_id, error = ?e, "failed to load personal watchlist"),
+        }
+    }
+
+    let symbols = digest::unscanned(&watchlists, scanned);
+    if symbols.is_empty() {
+        debug!("personal watchlists already scanned");
+        return Ok(Vec::new());
+    }
+    info!(count = symbols.len(), "scanning personal watchlist symbols");
+
+    // personal watchlists belong to no server, so no server's strategy
+    let mut results = scan_timed(
+        price_client,
+        renderer,
+        symbols,
+        Strategy::default(),
+        config.signal_band_pct,
+        config.weekly_confirmation,
+        config.min_chart_bars,
+        timings,
+    );
+    let mut hits = Vec::new();
+    while let Some((symbol, res)) = results.next().await {
+        match res {
+            Ok(ScanOutcome { hit: Some(hit), .. }) => {
+                hits.push(DigestHit::new(&hit.symbol, hit.signal, hit.close));
+            }
+            Ok(_) => {}
+            Err(e) => warn!(%symbol, error = ?e, "personal scan failed"),
+        }
+    }
+    info!(hits = hits.len(), "personal watchlist scan done");
+    Ok(hits)
+}
+
+/// DM each member with the digest on the day's `hits` on their personal
+/// watchlist, one at a time with [`digest::DM_DELAY`] between sends. A member
+/// whose DMs are closed has the digest turned off and is told so once, in
+/// the daily channel of the server they turned it on from.
+#[instrument(name = "send_digests", skip_all, fields(hits = hits.len()))]
+async fn send_digests(
+    http: &Http,
+    symbol_store: &SymbolStore,
+    hits: &[DigestHit],
+    channels: &HashMap<GuildId, ChannelId>,
+) -> Result<()> {
+    if hits.is_empty() {
+        debug!("no hits, skipping digests");
+        return Ok(());
+    }
+
+    let mut sent: usize = 0;
+    for user_id in symbol_store.list_digest_users().await? {
+        let mut prefs = match symbol_store.get_user_pref(user_id).await {
+            Ok(prefs) if prefs.daily_digest => prefs,
+            Ok(_) => continue,
+            Err(e) => {
+                warn!(user_id, error = ?e, "failed to load user prefs");
+                continue;
+            }
+        };
+        let watchlist = match symbol_store.list(Scope::User(user_id)).await {
+            Ok(watchlist) => watchlist,
+            Err(e) => {
+                warn!(user_id, error = ?e, "failed to load personal watchlist");
+                continue;
+            }
+        };
+
+        let guild = prefs.digest_guild.map(GuildId::new);
+        let locale = i18n::resolve(symbol_store, guild, None).await;
+        let Some(text) = digest::assemble(locale, hits, &watchlist) else {
+            continue;
+        };
+
+        let user = UserId::new(user_id);
+        let mut failure = None;
+        for chunk in split_content(&text, CONTENT_LIMIT) {
+            let res = user
+                .direct_message(http, CreateMessage::new().content(chunk))
+                .await;
+            tokio::time::sleep(digest::DM_DELAY).await;
+            if let Err(e) = res {
+                failure = Some(e);
+                break;
+            }
+        }
+
+        let Some(e) = failure else {
+            sent += 1;
+            continue;
+        };
+        if !digest::is_closed_dms(&e) {
+            warn!(user_id, error = ?e, "failed to DM digest");
+            continue;
+        }
+
+        info!(user_id, "DMs closed, turning digest off");
+        prefs.daily_digest = false;
+        if let Err(e) = symbol_store.set_user_pref(user_id, &prefs).await {
+            warn!(user_id, error = ?e, "failed to turn digest off");
+            continue;
+        }
+        if let Some(channel) = guild.and_then(|g| channels.get(&g)) {
+            let notice = t!(locale, MessageKey::DigestDisabled, user.mention());
+            if let Err(e) = channel.say(http, notice).await {
+                warn!(user_id, error = ?e, "failed to post digest notice");
+            }
+        }
+    }
+
+    info!(sent, "sent daily digests");
+    Ok(())
+}
+
+/// Guilds with a daily channel that aren't paused, plus `fallback`. With
+/// `mark_resumed`, targets whose pause has run out are marked resumed; the
+/// pause itself is left for [`clear_daily_pause`] once their run goes out.
+async fn targets(
+    symbol_store: &SymbolStore,
+    fallback: Option<Target>,
+    mark_resumed: bool,
+) -> Result<Vec<Target>> {
+    let now = Utc::now();
+    let mut targets = Vec::new();
+    let mut paused = Vec::new();
+
+    for guild_id in symbol_store.list_guilds().await? {
+        let settings = match symbol_store.get_guild_settings(guild_id).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!(guild_id, error = ?e, "failed to load guild settings");
+                continue;
+            }
+        };
+
+        if let Some(until) = settings.daily_paused_at(now) {
+            info!(guild_id, %until, "daily signals paused, skipping guild");
+            paused.push(GuildId::new(guild_id));
+            continue;
+        }
+
+        let resumed = mark_resumed && settings.daily_pause_expired(now);
+
+        if let Some(channel) = settings.daily_channel {
+            targets.push(Target {
+                guild_id: GuildId::new(guild_id),
+                channel: ChannelId::new(channel),
+                resumed,
+            });
+        }
+    }
+
+    if let Some(fallback) = fallback
+        && !paused.contains(&fallback.guild_id)
+        && !targets.iter().any(|t| t.guild_id == fallback.guild_id)
+    {
+        debug!(guild_id = %fallback.guild_id, "using fallback channel");
+        targets.push(fallback);
+    }
+
+    Ok(targets)
+}
+
+/// Clear the guild's daily pause once a run after it has gone out, so a
+/// failed run keeps it and the next one posts the resume note again. A
+/// pause set again in the meantime is left alone.
+async fn clear_daily_pause(symbol_store: &SymbolStore, guild_id: GuildId) -> Result<()> {
+    let mut settings = symbol_store.get_guild_settings(guild_id.get()).await?;
+    if settings.daily_pause_expired(Utc::now()) {
+        settings.daily_paused_until = None;
+        symbol_store
+            .set_guild_settings(guild_id.get(), &settings)
+            .await?;
+        debug!(%guild_id, "cleared expired daily pause");
+    }
+    Ok(())
+}
+
+/// Post the chart of the day to every daily channel: the guild's
+/// strongest setup by [`top_setup`], skipped when nothing on its watchlist
+/// clears [`stock::spotlight::MIN_SCORE`].
+#[instrument(
+    name = "run_spotlight",
+    skip(http, price_client, renderer, symbol_store, config)
+)]
+pub async fn run_spotlight(
+    http: Arc<Http>,
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbol_store: Arc<SymbolStore>,
+    config: Config,
+    fallback: Option<Target>,
+) -> Result<()> {
+    let targets = targets(&symbol_store, fallback, false).await?;
+    info!(guilds = targets.len(), "resolved spotlight targets");
+
+    for target in targets {
+        if let Err(e) = spotlight_guild(
+            &http,
+            target,
+            price_client.clone(),
+            &renderer,
+            &symbol_store,
+            &config,
+        )
+        .await
+        {
+            error!(guild_id = %target.guild_id, error = ?e, "spotlight failed for guild");
+        }
+    }
+    Ok(())
+}
+
+#[instrument(
+    name = "run_spotlight_guild",
+    skip(http, price_client, renderer, symbol_store, config),
+    fields(guild_id = %target.guild_id, channel_id = %target.channel)
+)]
+async fn spotlight_guild(
+    http: &Http,
+    target: Target,
+    price_client: Arc<dyn PriceSource>,
+    renderer: &ChartRenderer,
+    symbol_store: &SymbolStore,
+    config: &Config,
+) -> Result<()> {
+    let symbols = symbol_store
+        .list(Scope::Guild(target.guild_id.get()))
+        .await?;
+    let Some((setup, chart)) = top_setup(
+        price_client,
+        renderer,
+        symbols,
+        config.signal_band_pct,
+        stock::spotlight::MIN_SCORE,
+    )
+    .await?
+    else {
+        info!("no setup strong enough, skipping post");
+        return Ok(());
+    };
+
+    let locale = i18n::resolve(symbol_store, Some(target.guild_id), None).await;
+    let style =
+        SignalStyle::for_guild(symbol_store, &config.signal_colors, Some(target.guild_id)).await;
+    let (embed, attachment) = spotlight::message(locale, &setup, chart, &style);
+    let message = target
+        .channel
+        .send_message(http, CreateMessage::new().embed(embed).add_file(attachment))
+        .await?;
+
+    for emoji in spotlight::POLL_REACTIONS {
+        if let Err(e) = message
+            .react(http, ReactionType::Unicode(emoji.to_string()))
+            .await
+        {
+            warn!(%emoji, error = ?e, "failed to add poll reaction");
+        }
+    }
+
+    info!(symbol = %setup.symbol, score = setup.score(), "posted chart of the day");
+    Ok(())
+}
+
+/// A hit ready to post: its symbol, embed, chart and webhook event.
+type Message = (
+    String,
+    CreateEmbed,
+    Option<CreateAttachment>,
+    Option<SignalEvent>,
+);
+
+type RoutedSink =
+    IdempotentSink<WebhookSink<FallbackSink<ChannelSink, ChannelSink>>, Arc<SymbolStore>>;
+
+/// The hits routed to one channel by their tags, posted as their batches
+/// fill. If the bot can't post there they fall back to the scan's own
+/// channel with a note; hits in the shared `fallen_back` set already went
+/// there from another route and aren't posted there again. Hits are keyed
+/// under the run and the channel, like the scan's own.
+struct RoutedPost {
+    channel: ChannelId,
+    batcher: MessageBatcher<RoutedSink>,
+    failed: Arc<Mutex<Vec<String>>>,
+    posted: [Arc<Mutex<Vec<PostedHit>>>; 2],
+}
+
+impl RoutedPost {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        http: Arc<Http>,
+        channel: ChannelId,
+        fallback: ChannelId,
+        symbol_store: &Arc<SymbolStore>,
+        webhook: Option<Webhook>,
+        run_key: &str,
+        fallen_back: &Arc<Mutex<HashSet<String>>>,
+        locale: Locale,
+        config: &Config,
+    ) -> Self {
+        let primary = ChannelSink::new(http.clone(), channel);
+        let secondary = ChannelSink::new(http, fallback);
+        let posted = [primary.posted.clone(), secondary.posted.clone()];
+        let sink = FallbackSink::new(
+            primary,
+            secondary,
+            t!(locale, MessageKey::RouteFallback, channel.mention()),
+        )
+        .sharing(fallen_back.clone());
+        let sink = IdempotentSink::new(
+            WebhookSink::new(sink, webhook),
+            symbol_store.clone(),
+            format!("{run_key}:{channel}"),
+        );
+        let failed = sink.failed.clone();
+        Self {
+            channel,
+            batcher: MessageBatcher::new(sink).with_max_bytes(config.max_message_bytes),
+            failed,
+            posted,
+        }
+    }
+
+    async fn push(&mut self, message: Message) {
+        let (symbol, embed, attachment, event) = message;
+        if let Err(e) = self
+            .batcher
+            .push_hit(&symbol, embed, attachment, event)
+            .await
+        {
+            warn!(%symbol, channel_id = %self.channel, error = ?e, "send routed batch failed");
+        }
+    }
+
+    /// Send what's left. Returns the keys of the batches given up on and
+    /// the hits that went out.
+    async fn finish(mut self, locale: Locale) -> (Vec<String>, Vec<PostedHit>) {
+        if let Err(e) = self
+            .batcher
+            .finish(t!(locale, MessageKey::NoSignalsFound), false, None)
+            .await
+        {
+            warn!(channel_id = %self.channel, error = ?e, "send routed batch failed");
+        }
+        let failed = take(&mut *self.failed.lock().unwrap());
+        let posted = self
+            .posted
+            .iter()
+            .flat_map(|hits| take(&mut *hits.lock().unwrap()))
+            .collect();
+        (failed, posted)
+    }
+}
+
+/// Remember where each of this run's `timeframe` hits was posted, and mark
+/// the posts they reverse as superseded, linking to the new one. A hit
+/// routed to several channels is remembered by its first post. Hits that
+/// didn't go out this run, like batches a re-run skipped, leave their old
+/// posts alone.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn record_posted(
+    http: &Http,
+    symbol_store: &SymbolStore,
+    target: Target,
+    timeframe: Timeframe,
+    locale: Locale,
+    posted: &[PostedHit],
+    sessions: &HashMap<String, (Signal, NaiveDate)>,
+    reversals: &HashMap<String, PostedSignal>,
+) {
+    let mut records: BTreeMap<&str, (PostedSignal, &PostedHit)> = BTreeMap::new();
+    for hit in posted {
+        let Some(&(signal, session)) = sessions.get(&hit.symbol) else {
+            continue;
+        };
+        records.entry(&hit.symbol).or_insert((
+            PostedSignal {
+                signal,
+                session,
+                channel_id: hit.channel.get(),
+                message_id: hit.message.get(),
+                embed_index: hit.embed_index,
+            },
+            hit,
+        ));
+    }
+
+    for (symbol, (record, hit)) in &records {
+        let Some(old) = reversals.get(*symbol) else {
+            continue;
+        };
+        let link = hit.message.link(hit.channel, Some(target.guild_id));
+        match supersede(http, locale, old, record, &link).await {
+            Ok(()) => info!(%symbol, "marked reversed post as superseded"),
+            // most likely deleted since; the new post stands on its own
+            Err(e) => debug!(%symbol, error = ?e, "failed to edit reversed post"),
+        }
+    }
+
+    let records: Vec<(String, PostedSignal)> = records
+        .into_iter()
+        .map(|(symbol, (record, _))| (symbol.to_string(), record))
+        .collect();
+    if let Err(e) = symbol_store
+        .set_posted_at(Scope::Guild(target.guild_id.get()), timeframe, &records)
+        .await
+    {
+        warn!(error = ?e, "failed to save posted signals");
+    }
+}
+
+/// Append the struck-through superseded note to `old`'s embed, linking to
+/// `link`, the post of `new`.
+async fn supersede(
+    http: &Http,
+    locale: Locale,
+    old: &PostedSignal,
+    new: &PostedSignal,
+    link: &str,
+) -> Result<()> {
+    let channel = ChannelId::new(old.channel_id);
+    let message = channel
+        .message(http, MessageId::new(old.message_id))
+        .await?;
+    ensure!(
+        old.embed_index < message.embeds.len(),
+        "post has no embed {}",
+        old.embed_index
+    );
+    let embeds: Vec<CreateEmbed> = message
+        .embeds
+        .into_iter()
+        .enumerate()
+        .map(|(i, embed)| {
+            if i != old.embed_index {
+                return CreateEmbed::from(embed);
+            }
+            let description = report::superseded(
+                locale,
+                embed.description.as_deref(),
+                new.signal,
+                new.session,
+                link,
+            );
+            CreateEmbed::from(embed).description(description)
         })
-        .buffer_unordered(CONCURRENCY);
+        .collect();
+    // files aren't named, so the charts stay as they are
+    channel
+        .edit_message(
+            http,
+            MessageId::new(old.message_id),
+            EditMessage::new().embeds(embeds),
+        )
+        .await?;
+    Ok(())
+}
+
+/// The daily run's clock, shared with each guild's part of it.
+struct RunClock<'a> {
+    started: Instant,
+    /// Wall clocks of earlier runs, newest first.
+    history: &'a [Duration],
+    timings: Arc<ScanTimings>,
+}
+
+/// Each of `symbols` reporting earnings within `within_days`, with the days
+/// until it does. A calendar that can't be reached flags nothing.
+async fn upcoming_earnings(
+    source: &dyn EarningsSource,
+    symbols: &[String],
+    within_days: i64,
+) -> Vec<(String, i64)> {
+    if symbols.is_empty() {
+        return Vec::new();
+    }
+    let today = session_date(Utc::now());
+    match source
+        .upcoming(symbols, today, today + chrono::Duration::days(within_days))
+        .await
+    {
+        Ok(dates) => {
+            let flagged = earnings::reporting_within(&dates, symbols, today, within_days);
+            debug!(
+                reports = dates.len(),
+                flagged = flagged.len(),
+                "loaded earnings calendar"
+            );
+            flagged
+        }
+        Err(e) => {
+            warn!(error = ?e, "failed to load earnings calendar");
+            Vec::new()
+        }
+    }
+}
+
+/// Wall clocks of the archived runs before `today`, newest first, for
+/// [`degraded`] to judge this run against. Runs archived without timings
+/// are skipped; an archive that can't be read just means no baseline.
+async fn wall_history(archive: &RunArchive, today: NaiveDate) -> Vec<Duration> {
+    let dates = match archive.list(DEGRADATION_WINDOW + 1).await {
+        Ok(dates) => dates,
+        Err(e) => {
+            warn!(error = ?e, "failed to list archived runs");
+            return Vec::new();
+        }
+    };
+
+    let mut history = Vec::with_capacity(DEGRADATION_WINDOW);
+    for date in dates.into_iter().filter(|d| *d != today) {
+        match archive.read(date).await {
+            Ok(Some(run)) => history.extend(run.timings.map(|t| t.wall())),
+            Ok(None) => {}
+            Err(e) => warn!(%date, error = ?e, "failed to read archived run"),
+        }
+    }
+    history.truncate(DEGRADATION_WINDOW);
+    history
+}
+
+#[instrument(
+    name = "run_daily_guild",
+    skip(http, price_client, renderer, symbol_store, config, webhook, earnings, clock),
+    fields(guild_id = %target.guild_id, channel_id = %target.channel)
+)]
+#[allow(clippy::too_many_arguments)]
+async fn run_guild(
+    http: Arc<Http>,
+    target: Target,
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbol_store: Arc<SymbolStore>,
+    config: &Config,
+    webhook: Option<Webhook>,
+    earnings: Option<&dyn EarningsSource>,
+    clock: &RunClock<'_>,
+) -> Result<(GuildRun, Vec<DigestHit>)> {
+    let scope = Scope::Guild(target.guild_id.get());
+    let symbols = unmuted(&symbol_store, scope, symbol_store.list(scope).await?).await;
+    let mut meta = symbol_store.list_meta(scope).await?;
+    info!(total_symbols = symbols.len(), "loaded symbols");
+
+    let locale = i18n::resolve(&symbol_store, Some(target.guild_id), None).await;
+    let style =
+        SignalStyle::for_guild(&symbol_store, &config.signal_colors, Some(target.guild_id)).await;
+    let (tz, spikes, tag_routes) =
+        match symbol_store.get_guild_settings(target.guild_id.get()).await {
+            Ok(settings) => (
+                settings.timezone(),
+                settings.volume_spikes(),
+                settings.tag_routes,
+            ),
+            Err(e) => {
+                warn!(error = ?e, "failed to load guild settings");
+                (DEFAULT_TIMEZONE, None, BTreeMap::new())
+            }
+        };
+
+    let sink = ChannelSink::new(http.clone(), target.channel);
+    let first_link = sink.first_link.clone();
+    let posted = sink.posted.clone();
+    let mut resume_noted = false;
+    if target.resumed {
+        info!("first run after a daily pause");
+        match sink
+            .send_notice(t!(locale, MessageKey::DailyResumedAfterPause))
+            .await
+        {
+            Ok(()) => resume_noted = true,
+            Err(e) => warn!(error = ?e, "failed to post resume note"),
+        }
+    }
+
+    // keyed by session date, so a re-run of the same day skips what went out
+    let run_key = format!(
+        "daily:{}:{}",
+        session_date(Utc::now()).format("%Y-%m-%d"),
+        target.guild_id
+    );
+    let sink = IdempotentSink::new(
+        WebhookSink::new(sink, webhook.clone()),
+        symbol_store.clone(),
+        run_key.clone(),
+    );
+    let failed_batches = sink.failed.clone();
+    let mut batcher = MessageBatcher::new(sink)
+        .with_max_bytes(config.max_message_bytes)
+        .with_closing_components(vec![add_symbol::button_row(locale)]);
+
+    let strategy = symbol_store
+        .get_strategy(target.guild_id.get())
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "failed to load strategy, using the default");
+            Strategy::default()
+        });
+
+    // where recent hits went, so a reversal can point back at its post
+    let previous = symbol_store.posted(scope).await.unwrap_or_else(|e| {
+        warn!(error = ?e, "failed to load posted signals");
+        HashMap::new()
+    });
+
+    let entries = symbol_store.list_entries(scope).await.unwrap_or_else(|e| {
+        warn!(error = ?e, "failed to load entry prices");
+        HashMap::new()
+    });
+
+    let mut results = scan_timed(
+        price_client.clone(),
+        renderer,
+        symbols.clone(),
+        strategy,
+        config.signal_band_pct,
+        config.weekly_confirmation,
+        config.min_chart_bars,
+        clock.timings.clone(),
+    );
 
     let mut processed: usize = 0;
     let mut hits: usize = 0;
-    let mut failures: usize = 0;
+    let mut chartless: usize = 0;
+    let mut fallback: usize = 0;
+    let mut failed: Vec<String> = Vec::new();
+    let mut last_readings = Vec::with_capacity(symbols.len());
+    let mut records = Vec::with_capacity(symbols.len());
+    let mut digest_hits = Vec::new();
+    let mut messages = Vec::new();
+    let mut sessions = HashMap::new();
+    let mut reversals = HashMap::new();
+    let mut routed: BTreeMap<u64, RoutedPost> = BTreeMap::new();
+    let fallen_back = Arc::default();
+    let mut sending = Duration::ZERO;
 
-    while let Some(res) = tasks.next().await {
+    while let Some((symbol, res)) = results.next().await {
         processed += 1;
+        records.push(SymbolRecord::from_result(&symbol, &res));
+        if let Ok(ScanOutcome {
+            reading: Some(reading),
+            ..
+        }) = &res
+        {
+            last_readings.push((symbol.clone(), *reading));
+        }
+        // a crossover on the same bar says more, so it's posted instead
+        if let (
+            Some(threshold),
+            Ok(ScanOutcome {
+                hit: None,
+                volume: Some(volume),
+                ..
+            }),
+        ) = (spikes, &res)
+            && volume.ratio >= threshold
+            && report::quiet_until(&meta, &symbol).is_none()
+        {
+            info!(%symbol, ratio = volume.ratio, "volume spike");
+            let embed = report::volume_spike_message(locale, &symbol, volume, tz);
+            messages.push((symbol.clone(), embed, None, None));
+        }
 
         match res {
-            Ok(Some(hit)) => {
+            Ok(ScanOutcome { hit: Some(hit), .. }) => {
                 hits += 1;
-                embeds.push(hit.embed);
-                attachments.push(hit.attachment);
-
-                if embeds.len() == BATCH_SIZE {
-                    info!(processed, hits, "sending batch");
-                    let msg = CreateMessage::new()
-                        .embeds(take(&mut embeds))
-                        .add_files(take(&mut attachments));
-
-                    if let Err(e) = channel.send_message(&http, msg).await {
-                        warn!(error = ?e, "send batch failed");
-                    } else {
-                        debug!("batch sent");
-                    }
+                chartless += usize::from(hit.chart_failed);
+                fallback += usize::from(hit.fallback.is_some());
+                digest_hits.push(DigestHit::new(&hit.symbol, hit.signal, hit.close));
+                // muted symbols still post, but stay out of the webhook
+                let quiet = report::quiet_until(&meta, &symbol);
+                let event = quiet.is_none().then(|| SignalEvent::from_hit(&hit));
+                let session = session_date(hit.timestamp);
+                let reversed = previous
+                    .get(&symbol)
+                    .filter(|post| {
+                        post.reversed_by(
+                            hit.signal,
+                            session,
+                            config.supersede_days,
+                            Timeframe::Day1,
+                        )
+                    })
+                    .copied();
+                sessions.insert(symbol.clone(), (hit.signal, session));
+                let position = entries
+                    .get(&symbol.to_uppercase())
+                    .and_then(|entry| report::position_field(locale, *entry, hit.close));
+                let (mut embed, attachment) = report::hit_message(locale, hit, quiet, &style, tz);
+                if let Some((name, value, inline)) = position {
+                    embed = embed.field(name, value, inline);
+                }
+                if let Some(post) = reversed {
+                    let link = MessageId::new(post.message_id)
+                        .link(ChannelId::new(post.channel_id), Some(target.guild_id));
+                    let (name, value, inline) = report::reversal_field(locale, &post, &link);
+                    embed = embed.field(name, value, inline);
+                    reversals.insert(symbol.clone(), post);
                 }
+                messages.push((symbol, embed, attachment, event));
             }
-            Ok(None) => {
-                // normal: no signal or skipped due to handled per-symbol issue
+            Ok(_) => {
+                // normal: no actionable signal
             }
             Err(e) => {
-                failures += 1;
-                error!(error = ?e, processed, "symbol task returned Err");
+                warn!(%symbol, error = ?e, processed, "scan failed");
+                failed.push(symbol);
+            }
+        }
+
+        // out as soon as a batch fills, rather than after the whole scan
+        let dispatching = Instant::now();
+        for (symbol, embed, attachment, event) in messages.drain(..) {
+            let channels = report::routes(&meta, &symbol, &tag_routes);
+            if channels.is_empty() {
+                if let Err(e) = batcher.push_hit(&symbol, embed, attachment, event).await {
+                    warn!(%symbol, error = ?e, "send batch failed");
+                } else {
+                    debug!(%symbol, "hit queued");
+                }
+                continue;
+            }
+            // the webhook hears about each hit once, whatever the routes
+            let mut event = event;
+            for channel in channels {
+                routed
+                    .entry(channel)
+                    .or_insert_with(|| {
+                        RoutedPost::new(
+                            http.clone(),
+                            ChannelId::new(channel),
+                            target.channel,
+                            &symbol_store,
+                            webhook.clone(),
+                            &run_key,
+                            &fallen_back,
+                            locale,
+                            config,
+                        )
+                    })
+                    .push((
+                        symbol.clone(),
+                        embed.clone(),
+                        attachment.clone(),
+                        event.take(),
+                    ))
+                    .await;
             }
         }
+        sending += dispatching.elapsed();
     }
 
-    info!(processed, hits, failures, "completed daily scan");
+    info!(
+        processed,
+        hits,
+        failures = failed.len(),
+        "completed daily scan"
+    );
 
-    if !embeds.is_empty() {
-        info!(remaining = embeds.len(), "sending final batch");
-        let msg = CreateMessage::new().embeds(embeds).add_files(attachments);
-        channel.send_message(&http, msg).await?;
-    } else {
-        info!("no actionable signals found");
-        // channel
-        //     .send_message(
-        //         &http,
-        //         CreateMessage::new().content("No Buy/Sell signals found."),
-        //     )
-        //     .await?;
+    let any_routed = !routed.is_empty();
+    let mut routed_hits = Vec::new();
+    let dispatching = Instant::now();
+    for post in routed.into_values() {
+        let (failed, hits) = post.finish(locale).await;
+        failed_batches.lock().unwrap().extend(failed);
+        routed_hits.extend(hits);
     }
+    sending += dispatching.elapsed();
 
-    Ok(())
+    if let Err(e) = symbol_store.set_last_readings(scope, &last_readings).await {
+        warn!(error = ?e, "failed to save last readings");
+    }
+
+    stock::scan::backfill_added(
+        price_client.as_ref(),
+        &symbol_store,
+        scope,
+        &symbols,
+        &mut meta,
+    )
+    .await;
+
+    let earnings = match earnings {
+        Some(source) => upcoming_earnings(source, &symbols, config.earnings_within_days).await,
+        None => Vec::new(),
+    };
+
+    // the run as a whole is only judged at the end; this is how it's going
+    // so far, so a guild posting late in a slow run can say so
+    let so_far = clock.started.elapsed();
+    let slow = degraded(so_far, clock.history).map(|usual| (so_far, usual));
+    let finishing = Instant::now();
+    batcher
+        .finish_with(
+            t!(locale, MessageKey::NoSignalsFound),
+            // routed hits went elsewhere, but there were some
+            config.announce_empty_scans && !any_routed,
+            |rejected| {
+                report::run_summary(
+                    locale,
+                    processed,
+                    &failed,
+                    report::Chartless {
+                        undrawn: chartless,
+                        rejected,
+                    },
+                    fallback,
+                    slow,
+                    &earnings,
+                )
+            },
+        )
+        .await?;
+    clock
+        .timings
+        .record(Stage::Send, sending + finishing.elapsed());
+
+    let posted_hits: Vec<PostedHit> = take(&mut *posted.lock().unwrap())
+        .into_iter()
+        .chain(routed_hits)
+        .collect();
+    record_posted(
+        &http,
+        &symbol_store,
+        target,
+        Timeframe::Day1,
+        locale,
+        &posted_hits,
+        &sessions,
+        &reversals,
+    )
+    .await;
+
+    let failed_batches = take(&mut *failed_batches.lock().unwrap());
+    if resume_noted
+        && failed_batches.is_empty()
+        && let Err(e) = clear_daily_pause(&symbol_store, target.guild_id).await
+    {
+        warn!(error = ?e, "failed to clear expired daily pause");
+    }
+    let link = first_link.get().cloned();
+    for hit in &mut digest_hits {
+        hit.link = link.clone();
+    }
+
+    Ok((
+        GuildRun {
+            guild_id: target.guild_id.get(),
+            symbols: records,
+            failed_batches,
+        },
+        digest_hits,
+    ))
 }
@@ -0,0 +1,116 @@
+//! The opt-in daily digest: after the daily scan, members who turned it on
+//! get a DM listing the day's hits that are on their personal watchlist.
+//!
+//! Assembly is pure; the scheduled job sends one digest at a time with a
+//! pause in between, since DMs are where the bot most often hits 429s.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    time::Duration,
+};
+
+use serenity::all::HttpError;
+use stock::indicators::cdc::Signal;
+
+use crate::{
+    fmt,
+    i18n::{Locale, MessageKey, tr},
+};
+
+/// Pause after each digest DM.
+pub const DM_DELAY: Duration = Duration::from_secs(1);
+
+/// Discord's "Cannot send messages to this user", what closed DMs give.
+const CANNOT_MESSAGE_USER: isize = 50007;
+
+/// One hit from the day's scan, with a link to where it was posted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestHit {
+    pub symbol: String,
+    pub signal: Signal,
+    pub close: f64,
+    /// Jump link to the start of the daily post it went out in, once sent.
+    pub link: Option<String>,
+}
+
+impl DigestHit {
+    pub fn new(symbol: &str, signal: Signal, close: f64) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            signal,
+            close,
+            link: None,
+        }
+    }
+}
+
+/// Digest text for a member watching `watchlist`: a header and one line per
+/// hit on it, by symbol. A symbol hit in several servers is listed once,
+/// from a post that can be linked when there is one. None when no hit is on
+/// the watchlist.
+pub fn assemble(locale: Locale, hits: &[DigestHit], watchlist: &[String]) -> Option<String> {
+    let mut matched: BTreeMap<String, &DigestHit> = BTreeMap::new();
+    for hit in hits {
+        if !watchlist
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(&hit.symbol))
+        {
+            continue;
+        }
+        matched
+            .entry(hit.symbol.to_uppercase())
+            .and_modify(|kept| {
+                if kept.link.is_none() && hit.link.is_some() {
+                    *kept = hit;
+                }
+            })
+            .or_insert(hit);
+    }
+    if matched.is_empty() {
+        return None;
+    }
+
+    let mut lines = vec![tr(locale, MessageKey::DigestHeader, &[&matched.len()])];
+    lines.extend(matched.into_iter().map(|(symbol, hit)| {
+        let link = hit
+            .link
+            .as_ref()
+            .map(|url| tr(locale, MessageKey::DigestLink, &[url]))
+            .unwrap_or_default();
+        let line = tr(
+            locale,
+            MessageKey::DigestLine,
+            &[
+                &symbol,
+                &tr(locale, MessageKey::for_signal(hit.signal), &[]),
+                &fmt::price(hit.close),
+            ],
+        );
+        format!("{line}{link}")
+    }));
+    Some(lines.join("\n"))
+}
+
+/// Symbols on the members' `watchlists` that no server scanned today, in
+/// order and once each. They get a scan of their own so a digest covers the
+/// member's whole watchlist, not just what some server also watches.
+pub fn unscanned(watchlists: &[Vec<String>], scanned: &HashSet<String>) -> Vec<String> {
+    watchlists
+        .iter()
+        .flatten()
+        .map(|s| s.to_uppercase())
+        .filter(|s| !scanned.contains(s))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Whether a failed DM means the member doesn't accept DMs from the bot, as
+/// opposed to something worth retrying tomorrow.
+pub fn is_closed_dms(err: &serenity::Error) -> bool {
+    matches!(
+        err,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(res))
+            if res.error.code == CANNOT_MESSAGE_USER
+    )
+}
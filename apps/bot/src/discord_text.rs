@@ -0,0 +1,214 @@
+//! Keeping message text inside Discord's limits.
+//!
+//! Discord rejects a message outright when its content or an embed runs
+//! long, and the error doesn't say which part. Anything built from user data
+//! of unbounded size (symbol lists, summaries) goes through here first:
+//! content is split into several messages, embed text is truncated, and
+//! [`check_embed`] names the part that would overflow. Lengths are counted
+//! in characters, as Discord counts them.
+
+use poise::CreateReply;
+use serde_json::Value;
+use serenity::all::{CreateActionRow, CreateEmbed, Embed};
+
+use crate::{Context, Error};
+
+/// Characters in a message's content.
+pub const CONTENT_LIMIT: usize = 2000;
+/// Characters in an embed title, field name or author name.
+pub const TITLE_LIMIT: usize = 256;
+pub const DESCRIPTION_LIMIT: usize = 4096;
+pub const FIELD_VALUE_LIMIT: usize = 1024;
+pub const FOOTER_LIMIT: usize = 2048;
+/// Fields in one embed.
+pub const MAX_FIELDS: usize = 25;
+/// Characters across all of an embed's text.
+pub const EMBED_LIMIT: usize = 6000;
+
+const ELLIPSIS: char = '…';
+
+fn len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Split `content` into chunks of at most `limit` characters, breaking
+/// between lines where possible and between words when a single line is too
+/// long. Only a word longer than `limit` on its own is cut mid-word. Empty
+/// content gives no chunks.
+pub fn split_content(content: &str, limit: usize) -> Vec<String> {
+    let limit = limit.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for line in content.lines() {
+        if len(line) <= limit {
+            append(&mut chunks, &mut current, line, "\n", limit);
+            continue;
+        }
+
+        // an overlong line starts a fresh chunk and is filled word by word
+        if !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        for word in line.split_whitespace() {
+            if len(word) <= limit {
+                append(&mut chunks, &mut current, word, " ", limit);
+                continue;
+            }
+            let chars: Vec<char> = word.chars().collect();
+            for piece in chars.chunks(limit) {
+                let piece: String = piece.iter().collect();
+                append(&mut chunks, &mut current, &piece, "", limit);
+            }
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Add `piece` to `current` after `sep`, starting a new chunk when it
+/// won't fit. `piece` is never longer than `limit`.
+fn append(chunks: &mut Vec<String>, current: &mut String, piece: &str, sep: &str, limit: usize) {
+    if current.is_empty() {
+        current.push_str(piece);
+    } else if len(current) + len(sep) + len(piece) <= limit {
+        current.push_str(sep);
+        current.push_str(piece);
+    } else {
+        chunks.push(std::mem::replace(current, piece.to_string()));
+    }
+}
+
+/// Reply with `content` split by [`split_content`] over as many messages as
+/// it takes. `components` go on the last one, under the end of the text.
+pub async fn send_split(
+    ctx: Context<'_>,
+    content: &str,
+    ephemeral: bool,
+    components: Vec<CreateActionRow>,
+) -> Result<(), Error> {
+    let mut chunks = split_content(content, CONTENT_LIMIT);
+    let last = chunks.pop().unwrap_or_default();
+    for chunk in chunks {
+        ctx.send(CreateReply::default().content(chunk).ephemeral(ephemeral))
+            .await?;
+    }
+
+    let mut reply = CreateReply::default().content(last).ephemeral(ephemeral);
+    if !components.is_empty() {
+        reply = reply.components(components);
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// `text` cut to at most `limit` characters, ending in `…` when anything was
+/// cut.
+pub fn truncate_field(text: &str, limit: usize) -> String {
+    if len(text) <= limit {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(limit.saturating_sub(1)).collect();
+    if limit > 0 {
+        out.push(ELLIPSIS);
+    }
+    out
+}
+
+/// The part of an embed that's over its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbedPart {
+    Title,
+    Description,
+    /// Name of the field at this index.
+    FieldName(usize),
+    /// Value of the field at this index.
+    FieldValue(usize),
+    /// Too many fields.
+    Fields,
+    Footer,
+    Author,
+    /// All of the text together.
+    Total,
+}
+
+/// Why an embed would be rejected: `part` is `len` against a `limit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbedOverflow {
+    pub part: EmbedPart,
+    pub len: usize,
+    pub limit: usize,
+}
+
+impl std::fmt::Display for EmbedOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "embed {:?} is {}, over the limit of {}",
+            self.part, self.len, self.limit
+        )
+    }
+}
+
+impl std::error::Error for EmbedOverflow {}
+
+/// Check `embed` against Discord's limits, returning the first part that's
+/// over.
+pub fn check_embed(embed: &CreateEmbed) -> Result<(), EmbedOverflow> {
+    let value = serde_json::to_value(embed).unwrap_or_default();
+    let text = |v: &Value| v.as_str().map(len).unwrap_or(0);
+    let check = |part, len, limit| {
+        if len > limit {
+            Err(EmbedOverflow { part, len, limit })
+        } else {
+            Ok(len)
+        }
+    };
+
+    let mut total = check(EmbedPart::Title, text(&value["title"]), TITLE_LIMIT)?;
+    total += check(
+        EmbedPart::Description,
+        text(&value["description"]),
+        DESCRIPTION_LIMIT,
+    )?;
+    total += check(
+        EmbedPart::Footer,
+        text(&value["footer"]["text"]),
+        FOOTER_LIMIT,
+    )?;
+    total += check(
+        EmbedPart::Author,
+        text(&value["author"]["name"]),
+        TITLE_LIMIT,
+    )?;
+
+    let fields = value["fields"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    check(EmbedPart::Fields, fields.len(), MAX_FIELDS)?;
+    for (i, field) in fields.iter().enumerate() {
+        total += check(EmbedPart::FieldName(i), text(&field["name"]), TITLE_LIMIT)?;
+        total += check(
+            EmbedPart::FieldValue(i),
+            text(&field["value"]),
+            FIELD_VALUE_LIMIT,
+        )?;
+    }
+
+    check(EmbedPart::Total, total, EMBED_LIMIT).map(|_| ())
+}
+
+/// `embed` without its image, for when the file it points at couldn't be
+/// sent. An embed that won't round-trip is returned as it was.
+pub fn without_image(embed: CreateEmbed) -> CreateEmbed {
+    let mut value = serde_json::to_value(&embed).unwrap_or_default();
+    if value
+        .as_object_mut()
+        .and_then(|v| v.remove("image"))
+        .is_none()
+    {
+        return embed;
+    }
+    serde_json::from_value::<Embed>(value).map_or(embed, CreateEmbed::from)
+}
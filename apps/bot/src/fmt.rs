@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use stock::{Timeframe, calendar::axis_label};
+
+/// Compact human uptime: `3d 4h 5m`, `4h 0m`, `5m 12s`, `12s`.
+pub fn uptime(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (days, hours, mins, secs) = (
+        secs / 86_400,
+        secs % 86_400 / 3_600,
+        secs % 3_600 / 60,
+        secs % 60,
+    );
+
+    if days > 0 {
+        format!("{days}d {hours}h {mins}m")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m {secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Short timing: `850ms` under a second, `1.24s` from there.
+pub fn elapsed(d: Duration) -> String {
+    if d < Duration::from_secs(1) {
+        format!("{}ms", d.as_millis())
+    } else {
+        format!("{:.2}s", d.as_secs_f64())
+    }
+}
+
+/// Dollar price with two decimals and thousands separators: `$1,234.50`.
+pub fn price(value: f64) -> String {
+    let cents = (value.abs() * 100.0).round() as u64;
+    let (dollars, cents) = (cents / 100, cents % 100);
+
+    let digits = dollars.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    let sign = if value < 0.0 && (dollars, cents) != (0, 0) {
+        "-"
+    } else {
+        ""
+    };
+    format!("{sign}${grouped}.{cents:02}")
+}
+
+/// Percentage with an explicit sign and one decimal: `+1.2%`, `-0.4%`.
+/// Anything that rounds to zero is `0.0%`.
+pub fn signed_pct(value: f64) -> String {
+    if (value * 10.0).round() == 0.0 {
+        "0.0%".to_string()
+    } else {
+        format!("{value:+.1}%")
+    }
+}
+
+/// Share volume scaled to thousands, millions or billions: `850`, `12.5K`,
+/// `3.2M`. Fractional crypto volume under a thousand keeps up to two
+/// places: `0.35`.
+pub fn volume(value: f64) -> String {
+    let abs = value.abs();
+    if abs >= 1e9 {
+        format!("{:.1}B", value / 1e9)
+    } else if abs >= 1e6 {
+        format!("{:.1}M", value / 1e6)
+    } else if abs >= 1e3 {
+        format!("{:.1}K", value / 1e3)
+    } else if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        let text = format!("{value:.2}");
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// Byte count in binary units: `512 B`, `1.5 KiB`, `3.2 MiB`.
+pub fn bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if n < 1024 {
+        return format!("{n} B");
+    }
+    let mut value = n as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// How [`time`] renders a timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeStyle {
+    /// `<t:…:R>`, like "in 3 days".
+    Relative,
+    /// `<t:…:f>`, the date and time.
+    Full,
+    /// `<t:…:D>`, the date alone.
+    Date,
+    /// `2024-03-10 14:30 EDT`, for text Discord shows as-is: embed titles
+    /// and footers, the bot's status.
+    Plain,
+    /// Chart axis label for a bar at this timeframe.
+    Axis(Timeframe),
+}
+
+/// `dt` for display. The `<t:…>` styles are Discord timestamp markdown,
+/// which each viewer sees in their own zone, so they ignore `tz`; use them
+/// in message content, embed descriptions and fields. Charts are images
+/// and plain text isn't rendered, so those are written out in `tz`.
+pub fn time(dt: DateTime<Utc>, tz: Tz, style: TimeStyle) -> String {
+    match style {
+        TimeStyle::Relative => format!("<t:{}:R>", dt.timestamp()),
+        TimeStyle::Full => format!("<t:{}:f>", dt.timestamp()),
+        TimeStyle::Date => format!("<t:{}:D>", dt.timestamp()),
+        TimeStyle::Plain => dt
+            .with_timezone(&tz)
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string(),
+        TimeStyle::Axis(timeframe) => axis_label(dt, timeframe, tz),
+    }
+}
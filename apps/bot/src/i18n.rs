@@ -0,0 +1,1279 @@
+use std::fmt::{Display, Write};
+
+use serenity::all::GuildId;
+use stock::SymbolStore;
+use stock::indicators::cdc::Signal;
+use tracing::{debug, warn};
+
+use crate::{Context, invocation};
+
+/// Languages shipped with the bot. Every [`MessageKey`] must have a string in
+/// each of them; the per-locale catalogs are exhaustive matches, so a missing
+/// translation is a compile error rather than a runtime fallback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Th,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Th];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Th => "th",
+        }
+    }
+
+    /// Map a Discord locale tag (`th`, `en-US`, `en-GB`, ...) to a shipped locale.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let lang = tag.split(['-', '_']).next().unwrap_or_default();
+        Self::ALL
+            .into_iter()
+            .find(|l| l.code().eq_ignore_ascii_case(lang))
+    }
+}
+
+/// Declares [`MessageKey`] together with [`MessageKey::ALL`], so the list
+/// tests walk can't fall behind the enum.
+macro_rules! message_keys {
+    ($($key:ident),* $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum MessageKey {
+            $($key),*
+        }
+
+        impl MessageKey {
+            /// Every key, in declaration order.
+            pub const ALL: &[MessageKey] = &[$(MessageKey::$key),*];
+        }
+    };
+}
+
+message_keys! {
+    NoValidSymbols,
+    NowWatching,
+    AlreadyWatching,
+    WatchlistEmpty,
+    DeleteSelectPrompt,
+    DeleteSelectPlaceholder,
+    DeleteConfirmPrompt,
+    DeleteNotOwner,
+    DeleteSessionExpired,
+    ConfirmExpires,
+    Deleted,
+    DeleteImpactAlerts,
+    DeleteImpactAdded,
+    DeleteImpactNothing,
+    DeleteCleaned,
+    DeleteFailed,
+    ButtonConfirm,
+    ButtonCancel,
+    Cancelled,
+    AnalysisTitle,
+    CurrentSignal,
+    DonchianBreakoutBullish,
+    DonchianBreakoutBearish,
+    NoSignalsFound,
+    ScanComplete,
+    ResultsContinued,
+    RouteFallback,
+    RouteSet,
+    RouteCleared,
+    SettingsRoutes,
+    TagInvalid,
+    TagsTooMany,
+    TagsSet,
+    TagsCleared,
+    TriggerCooldown,
+    SignalBuy,
+    SignalSell,
+    SignalBullishZone,
+    SignalBearishZone,
+    SignalNone,
+    GuildOnly,
+    SettingsTitle,
+    SettingsLocale,
+    SettingsDailyChannel,
+    NotSet,
+    LocaleAuto,
+    LocaleSet,
+    LocaleCleared,
+    DailyChannelSet,
+    DailyChannelCleared,
+    PrefsEphemeralOn,
+    PrefsEphemeralOff,
+    CashtagLine,
+    SettingsCashtagReactions,
+    SettingsCashtagReplies,
+    On,
+    Off,
+    CashtagsUpdated,
+    CashtagsIntentDisabled,
+    AboutTitle,
+    AboutVersion,
+    AboutUptime,
+    AboutWatchlist,
+    AboutLastDailyRun,
+    Never,
+    MutedUntil,
+    QuietSet,
+    QuietCleared,
+    MuteSet,
+    MuteSetIndefinite,
+    MuteCleared,
+    MuteNotMuted,
+    NotWatching,
+    WatchlistTitle,
+    SourceBars,
+    SourceIntraday,
+    ChartGenerated,
+    SourceSnapshot,
+    SourceStale,
+    SourceFallback,
+    InvalidPrice,
+    AlertSet,
+    AlertsTitle,
+    AlertsEmpty,
+    AlertSelectPlaceholder,
+    AlertDeleteConfirmPrompt,
+    AlertSessionExpired,
+    AlertsDeleted,
+    AlertModeOneShot,
+    AlertModeRecurring,
+    AlertLastFired,
+    AlertNeverFired,
+    AlertFired,
+    AlertMoveFired,
+    AlertNoReference,
+    SinceAdded,
+    SinceAddedApprox,
+    ChartAddedLine,
+    SettingsDailyPaused,
+    SettingsDailyRunning,
+    DailyPaused,
+    DailyResumed,
+    DailyNotPaused,
+    DailyResumedAfterPause,
+    PrefsTimeframeSet,
+    RunsDisabled,
+    RunsEmpty,
+    RunsRecent,
+    RunsInvalidDate,
+    RunsNotFound,
+    RunsAttached,
+    InvalidSymbols,
+    ScanIncomplete,
+    ScanFailedSymbols,
+    RunSlow,
+    ScanDegraded,
+    ScanUploadRejected,
+    ScanFallback,
+    ChartUnavailable,
+    AndMore,
+    WatchConfirmPrompt,
+    WatchNotOwner,
+    WatchSessionExpired,
+    GraphDidYouMean,
+    GraphChartSuggested,
+    GraphCharting,
+    DeleteNoMatch,
+    DividendsTitle,
+    DividendsUpcoming,
+    DividendsRecent,
+    DividendsNone,
+    DividendsDividend,
+    DividendsSpecialDividend,
+    DividendsPayable,
+    DividendsSplit,
+    DividendsReverseSplit,
+    AnalyzeNoData,
+    ChartInsufficientHistory,
+    AnalyzeShortHistory,
+    AnalyzeZone,
+    AnalyzeRsi,
+    AnalyzeMacd,
+    AnalyzeStop,
+    AnalyzeRange,
+    AnalyzeVolume,
+    AnalyzeEarnings,
+    AnalyzeStopValue,
+    AnalyzeRangeValue,
+    AnalyzeVolumeValue,
+    RsiOverbought,
+    RsiOversold,
+    MacdBullishCross,
+    MacdBearishCross,
+    MacdBullish,
+    MacdBearish,
+    InvalidIndicators,
+    AboutApiUsage,
+    OnboardingTitle,
+    OnboardingBody,
+    OnboardingSetChannel,
+    OnboardingNeedsManageGuild,
+    BenchmarkNoData,
+    SpotlightTitle,
+    SpotlightCrossedBullish,
+    SpotlightCrossedBearish,
+    SpotlightBullishStrengthening,
+    SpotlightBullishFading,
+    SpotlightBearishStrengthening,
+    SpotlightBearishFading,
+    SpotlightVolume,
+    SpotlightGap,
+    SpotlightPoll,
+    IgnoredInvalidSymbols,
+    ScanBenchmarkTitle,
+    ScanBenchmarkTotal,
+    ScanBenchmarkSymbols,
+    ScanBenchmarkSymbolsValue,
+    ScanBenchmarkFetch,
+    ScanBenchmarkRender,
+    ScanBenchmarkStage,
+    ScanBenchmarkNoSamples,
+    DigestHeader,
+    DigestLine,
+    DigestLink,
+    DigestOn,
+    DigestOff,
+    DigestDisabled,
+    PriceLastClose,
+    PrefsLeaderboardOn,
+    PrefsLeaderboardOff,
+    UsageStatsTitle,
+    UsageStatsEmpty,
+    UsageStatsTotal,
+    UsageStatsCommands,
+    UsageStatsCommandLine,
+    UsageStatsBusiest,
+    UsageStatsBusiestValue,
+    UsageStatsTopUsers,
+    UsageStatsUserLine,
+    UsageStatsNoUsers,
+    CleanAlready,
+    CleanDone,
+    SettingsStyle,
+    StyleSet,
+    StyleInvalid,
+    AboutStore,
+    AboutStoreValue,
+    SettingsTimezone,
+    TimezoneSet,
+    TimezoneInvalid,
+    ResyncUnchanged,
+    ResyncForced,
+    ResyncRegistered,
+    ResyncGuilds,
+    ResyncCleared,
+    ResyncDropped,
+    ResyncGuild,
+    ResyncInvalidGuild,
+    AdminCacheTitle,
+    AdminCacheBars,
+    AdminCacheSymbolList,
+    AdminCacheSignals,
+    AdminCacheDropped,
+    AdminCacheNotHeld,
+    AdminStoreTitle,
+    AdminStoreKeys,
+    AdminStoreMemory,
+    AdminStoreCircuits,
+    AdminStoreCircuitsValue,
+    Unavailable,
+    AdminLogLevelChanged,
+    AdminLogLevelInvalid,
+    SettingsStrategy,
+    StrategyCurrent,
+    StrategySet,
+    StrategyReset,
+    StrategyInvalid,
+    StrategyAdxPeriodUnused,
+    PrefsThemeSet,
+    PrefsThemePreview,
+    PrefsScaleSet,
+    SettingsWatermark,
+    SettingsIntraday,
+    IntradayCharts,
+    IntradayTextOnly,
+    IntradaySet,
+    IntradayOff,
+    IntradayNeedsChannel,
+    WatermarkSet,
+    WatermarkCleared,
+    BrowsePage,
+    BrowsePlaceholder,
+    BrowsePrev,
+    BrowseNext,
+    BrowseBack,
+    BrowseRemove,
+    BrowseRemoved,
+    BrowseRemoveNeedsManageGuild,
+    BrowseClose,
+    BrowseChange,
+    BrowseRange,
+    AliasInterpreted,
+    AliasAdded,
+    AliasReplaced,
+    AliasRemoved,
+    AliasNotFound,
+    AliasListTitle,
+    AliasListEmpty,
+    AliasEmpty,
+    AliasTooLong,
+    AliasShadowsTicker,
+    AliasLimit,
+    StoreUnavailable,
+    DataTitle,
+    DataCapped,
+    GraphsCapped,
+    GraphsNoData,
+    ReportCsvReady,
+    UnlistedSymbols,
+    CheckTitle,
+    CheckNoData,
+    CheckPosts,
+    CheckNoCrossover,
+    CheckWeakTrend,
+    CheckWeeklyDisagrees,
+    CheckWeeklyUnknown,
+    CheckAdx,
+    CheckAdxOff,
+    CheckWeekly,
+    CheckWeeklyNotNeeded,
+    CheckWeeklyOff,
+    CheckLeftOut,
+    CheckQuiet,
+    AddSymbolButton,
+    AddSymbolTitle,
+    AddSymbolTicker,
+    AddSymbolNote,
+    AddSymbolInvalid,
+    AddSymbolUnlisted,
+    AddSymbolNoteSaved,
+    ReversalTitle,
+    ReversesPost,
+    SupersededBy,
+    VolumeSpikeTitle,
+    VolumeSpike,
+    VolumeSpikeDetail,
+    SettingsVolumeSpikes,
+    VolumeSpikesOn,
+    VolumeSpikesOff,
+    EarningsToday,
+    EarningsTomorrow,
+    EarningsSoon,
+    SnapshotTitle,
+    SnapshotNoData,
+    SnapshotLast,
+    SnapshotBook,
+    SnapshotBookValue,
+    SnapshotPrevClose,
+    SnapshotDay,
+    SnapshotDayValue,
+    SnapshotSignal,
+    SnapshotNoSignal,
+    SnapshotAsOf,
+    PositionTitle,
+    PositionValue,
+    EntrySet,
+    EntryCleared,
+    EntryNone,
+    EntryInvalid,
+    EntryManagersOnly,
+    CommandInvalidSymbol,
+    CommandTimedOut,
+    CommandDataUnavailable,
+    CommandDataFailed,
+    CommandFailed,
+    SpreadTitle,
+    SpreadNow,
+    SpreadNowValue,
+    SpreadAbove,
+    SpreadAboveValue,
+    SpreadCrossovers,
+    SpreadCrossoversValue,
+    SpreadNoCrossovers,
+}
+
+impl MessageKey {
+    pub fn template(self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => en(self),
+            Locale::Th => th(self),
+        }
+    }
+
+    pub fn for_signal(signal: Signal) -> Self {
+        match signal {
+            Signal::Buy => MessageKey::SignalBuy,
+            Signal::Sell => MessageKey::SignalSell,
+            Signal::BullishZone => MessageKey::SignalBullishZone,
+            Signal::BearishZone => MessageKey::SignalBearishZone,
+            Signal::None => MessageKey::SignalNone,
+        }
+    }
+}
+
+fn en(key: MessageKey) -> &'static str {
+    use MessageKey::*;
+    match key {
+        NoValidSymbols => "No valid symbols provided.",
+        NowWatching => "Now watching: {0}",
+        AlreadyWatching => "Already watching: {0}",
+        WatchlistEmpty => "Watchlist is empty.",
+        DeleteSelectPrompt => "Select symbols to delete (you can pick multiple):",
+        DeleteSelectPlaceholder => "Choose symbols...",
+        DeleteConfirmPrompt => "Are you sure you want to delete **{0}** symbols?\n> {1}",
+        DeleteNotOwner => "❌ You can’t confirm someone else’s delete.",
+        DeleteSessionExpired => "❌ Session expired. Run /delete again.",
+        ConfirmExpires => "⏳ This request expires {0}.",
+        Deleted => "{0} was deleted.",
+        DeleteImpactAlerts => "{0} alerts",
+        DeleteImpactAdded => "added {0} days ago",
+        DeleteImpactNothing => "nothing else",
+        DeleteCleaned => "Also removed {0} related records.",
+        DeleteFailed => "⚠️ Couldn't delete: {0}",
+        ButtonConfirm => "Confirm",
+        ButtonCancel => "Cancel",
+        Cancelled => "Cancelled.",
+        AnalysisTitle => "{0} Analysis",
+        CurrentSignal => "Current Signal: {0}",
+        DonchianBreakoutBullish => "📈 Breakout above the {0}-day high",
+        DonchianBreakoutBearish => "📉 Breakdown below the {0}-day low",
+        NoSignalsFound => "No Buy/Sell signals found.",
+        ScanComplete => "✅ Scan complete.",
+        ResultsContinued => "Continued results for {0}'s /{1}",
+        RouteFallback => "⚠️ Can't post in {0}, so its signals are here instead.",
+        RouteSet => "Scan signals for symbols tagged `{0}` will be posted in {1}.",
+        RouteCleared => "Symbols tagged `{0}` are posted with the rest of the scan again.",
+        SettingsRoutes => "Tag routes: {0}",
+        TagInvalid => "⚠️ `{0}` isn't a tag: use up to {1} letters, digits, `-` or `_`.",
+        TagsTooMany => "⚠️ A symbol can have at most {0} tags.",
+        TagsSet => "Tagged {0}: {1}",
+        TagsCleared => "Cleared {0}'s tags.",
+        TriggerCooldown => "⏳ A scan was run recently. Try again in {0}s.",
+        SignalBuy => "Buy",
+        SignalSell => "Sell",
+        SignalBullishZone => "BullishZone",
+        SignalBearishZone => "BearishZone",
+        SignalNone => "None",
+        GuildOnly => "This command can only be used in a server.",
+        SettingsTitle => "Server settings",
+        SettingsLocale => "Language: {0}",
+        SettingsDailyChannel => "Daily signals channel: {0}",
+        NotSet => "not set",
+        LocaleAuto => "auto (each member's Discord language)",
+        LocaleSet => "Server language set to {0}.",
+        LocaleCleared => "Server language override cleared.",
+        DailyChannelSet => "Daily signals will be posted in {0}.",
+        DailyChannelCleared => "Daily signals are turned off for this server.",
+        PrefsEphemeralOn => "Watchlist replies will now only be visible to you.",
+        PrefsEphemeralOff => "Watchlist replies will now be posted publicly.",
+        CashtagLine => "{0} **{1}** ${2} · {3}",
+        SettingsCashtagReactions => "Cashtag reactions: {0}",
+        SettingsCashtagReplies => "Cashtag price replies: {0}",
+        On => "on",
+        Off => "off",
+        AboutTitle => "About",
+        AboutVersion => "Version",
+        AboutUptime => "Uptime",
+        AboutWatchlist => "Watchlist",
+        AboutLastDailyRun => "Last daily run",
+        Never => "never",
+        MutedUntil => "🔇 Muted until {0}",
+        QuietSet => "🔇 {0} is muted until {1}. Signals still show, greyed out.",
+        QuietCleared => "🔔 {0} is no longer muted.",
+        MuteSet => "🙈 {0} is left out of the daily scan until {1}.",
+        MuteSetIndefinite => "🙈 {0} is left out of the daily scan until you unmute it.",
+        MuteCleared => "👀 {0} is back in the daily scan.",
+        MuteNotMuted => "{0} isn't muted.",
+        NotWatching => "{0} isn't on the watchlist.",
+        WatchlistTitle => "Watchlist ({0})",
+        SourceBars => "Data: daily bars",
+        SourceIntraday => "Data: {0} bars",
+        ChartGenerated => "Generated {0}",
+        SourceSnapshot => "Data: live snapshot (daily bar lagging)",
+        SourceStale => "⚠️ Data: daily bars, last session {0}",
+        SourceFallback => "🔀 Data from fallback source {0}",
+        InvalidPrice => "Price must be greater than zero.",
+        AlertSet => "🔔 Alert set: {0}.",
+        AlertsTitle => "Your price alerts ({0})",
+        AlertsEmpty => "You have no price alerts.",
+        AlertSelectPlaceholder => "Choose alerts to delete...",
+        AlertDeleteConfirmPrompt => "Delete **{0}** alerts?\n> {1}",
+        AlertSessionExpired => "❌ Session expired. Run /stock alert list again.",
+        AlertsDeleted => "Deleted alerts: {0}",
+        AlertModeOneShot => "one-shot",
+        AlertModeRecurring => "recurring, at most every {0}",
+        AlertLastFired => "last fired {0}",
+        AlertNeverFired => "not fired yet",
+        AlertFired => "🔔 **{0}** is {1} ${2} (now ${3}).",
+        AlertMoveFired => "🔔 **{0}** moved {1} from ${2} (now ${3}).",
+        AlertNoReference => "❌ Couldn't get a current price for {0}.",
+        SinceAdded => "{0} since added",
+        SinceAddedApprox => "{0} since added (approx.)",
+        ChartAddedLine => "Added",
+        SettingsDailyPaused => "Daily signals: ⏸️ paused until {0} ({1} left)",
+        SettingsDailyRunning => "Daily signals: ▶️ running",
+        DailyPaused => "⏸️ Daily signals are paused until {0}. Settings are kept.",
+        DailyResumed => "▶️ Daily signals are back on.",
+        DailyNotPaused => "Daily signals aren't paused.",
+        DailyResumedAfterPause => "▶️ Daily signals resumed after a pause.",
+        PrefsTimeframeSet => "New charts will default to {0} bars.",
+        RunsDisabled => "Daily runs aren't being archived (DAILY_ARCHIVE is off).",
+        RunsEmpty => "No daily runs archived yet.",
+        RunsRecent => "Archived daily runs: {0}",
+        RunsInvalidDate => "`{0}` isn't a date. Use YYYY-MM-DD.",
+        RunsNotFound => "No archived run for this server on {0}.",
+        RunsAttached => "Daily run for {0}: {1} symbols.",
+        InvalidSymbols => "Skipped, not valid symbols: {0}",
+        ScanIncomplete => {
+            "⚠️ Scan incomplete: {0} of {1} symbols couldn't be fetched, so their signals are missing."
+        }
+        ScanFailedSymbols => "Failed: {0}",
+        RunSlow => "🐢 Today's run is slow: {0} so far against a usual {1}.",
+        ScanDegraded => "🖼️ {0} signals went out without a chart: it couldn't be drawn.",
+        ScanUploadRejected => {
+            "🖼️ {0} signals went out without a chart: Discord wouldn't take the upload."
+        }
+        ScanFallback => {
+            "🔀 {0} signals used data from a fallback source, whose feed can differ slightly."
+        }
+        ChartUnavailable => "Chart unavailable",
+        AndMore => "+{0} more",
+        WatchConfirmPrompt => "Add **{0}** symbols to the watchlist?\n> {1}",
+        WatchNotOwner => "❌ You can’t confirm someone else’s watch request.",
+        WatchSessionExpired => "❌ Session expired. Run /watch again.",
+        GraphDidYouMean => {
+            "❓ **{0}** isn't a symbol I know. Did you mean **{1}** from the watchlist?"
+        }
+        GraphChartSuggested => "Chart {0}",
+        GraphCharting => "📈 Charting **{0}**…",
+        DeleteNoMatch => "No watched symbol matches **{0}**.",
+        DividendsTitle => "{0} dividends and splits",
+        DividendsUpcoming => "Upcoming",
+        DividendsRecent => "Past year",
+        DividendsNone => "{0} has no dividends or splits in the past year, and none announced.",
+        DividendsDividend => "`{0}` dividend **{1}** a share",
+        DividendsSpecialDividend => "`{0}` special dividend **{1}** a share",
+        DividendsPayable => ", paid `{0}`",
+        DividendsSplit => "`{0}` {1} split",
+        DividendsReverseSplit => "`{0}` {1} reverse split",
+        AnalyzeNoData => "No price history for {0}.",
+        ChartInsufficientHistory => "Insufficient history to chart {0} ({1} bars).",
+        AnalyzeShortHistory => {
+            "⚠️ Only {0} bars of history: readings may be unreliable, and there's no chart."
+        }
+        AnalyzeZone => "CDC zone",
+        AnalyzeRsi => "RSI ({0})",
+        AnalyzeMacd => "MACD",
+        AnalyzeStop => "Stop ({0}× ATR)",
+        AnalyzeRange => "52-week range",
+        AnalyzeVolume => "Volume vs {0}-day avg",
+        AnalyzeEarnings => "Next earnings",
+        AnalyzeStopValue => "{0} ({1}) · ATR {2}",
+        AnalyzeRangeValue => "{0} – {1} · {2}% of range",
+        AnalyzeVolumeValue => "{0} vs {1} ({2}×)",
+        RsiOverbought => "overbought",
+        RsiOversold => "oversold",
+        MacdBullishCross => "Bullish crossover",
+        MacdBearishCross => "Bearish crossover",
+        MacdBullish => "Above signal",
+        MacdBearish => "Below signal",
+        InvalidIndicators => "❌ Couldn't read indicators `{0}`. Pick from: {1}",
+        AboutApiUsage => "Alpaca requests (last minute)",
+        OnboardingTitle => "👋 Thanks for adding me!",
+        OnboardingBody => {
+            "I post CDC Action Zone signals for the stocks your server watches.\n• `/stock watch TSLA,MSFT` adds symbols to the watchlist\n• `/stock graph` draws a chart\n• `/stock settings channel` picks where daily signals go\n\nNo daily channel is set yet, so daily signals are off. Press the button to post them here."
+        }
+        OnboardingSetChannel => "Set this channel for daily signals",
+        OnboardingNeedsManageGuild => {
+            "❌ Only members with Manage Server can set the daily channel."
+        }
+        BenchmarkNoData => "❌ No price history for benchmark `{0}`.",
+        SpotlightTitle => "📌 Chart of the day: {0}",
+        SpotlightCrossedBullish => "{0} crossed bullish in the last session{1}.",
+        SpotlightCrossedBearish => "{0} crossed bearish in the last session{1}.",
+        SpotlightBullishStrengthening => {
+            "{0} strengthened its bullish trend in the last session{1}."
+        }
+        SpotlightBullishFading => "{0}'s bullish trend lost momentum in the last session{1}.",
+        SpotlightBearishStrengthening => "{0} extended its bearish trend in the last session{1}.",
+        SpotlightBearishFading => "{0}'s bearish trend eased in the last session{1}.",
+        SpotlightVolume => " on {0}× average volume",
+        SpotlightGap => {
+            "The 12-day EMA now sits {0} from the 26-day, a {1} point move in one session."
+        }
+        SpotlightPoll => "Would you take this trade? React 📈 for yes or 📉 for no.",
+        IgnoredInvalidSymbols => "⚠️ Ignored (invalid): {0}",
+        ScanBenchmarkTitle => "⏱️ Scan benchmark",
+        ScanBenchmarkTotal => "Total time",
+        ScanBenchmarkSymbols => "Symbols",
+        ScanBenchmarkSymbolsValue => "{0} scanned · {1} hits · {2} failed",
+        ScanBenchmarkFetch => "Fetch",
+        ScanBenchmarkRender => "Chart render",
+        ScanBenchmarkStage => "avg {0} · p95 {1} · max {2} ({3} samples)",
+        ScanBenchmarkNoSamples => "No samples",
+        DigestHeader => "📬 **Today's signals on your watchlist** ({0})",
+        DigestLine => "• **{0}** {1} at {2}",
+        DigestLink => " · [view post]({0})",
+        DigestOn => {
+            "📬 After each daily scan you'll get a DM with any signals on your personal watchlist."
+        }
+        DigestOff => "📭 Daily digest turned off.",
+        DigestDisabled => {
+            "{0} I couldn't DM you your daily digest, so it's been turned off. Allow DMs from this server and run `/stock digest on` to get it back."
+        }
+        PriceLastClose => "{0} (last close)",
+        PrefsLeaderboardOn => "You'll be listed among the top users in /stock stats.",
+        PrefsLeaderboardOff => "You won't be listed among the top users in /stock stats.",
+        UsageStatsTitle => "📊 Command usage · last {0} days",
+        UsageStatsEmpty => "No commands were used in the last {0} days.",
+        UsageStatsTotal => "Invocations",
+        UsageStatsCommands => "By command",
+        UsageStatsCommandLine => "`/{0}` · {1}",
+        UsageStatsBusiest => "Busiest day",
+        UsageStatsBusiestValue => "{0} ({1} invocations)",
+        UsageStatsTopUsers => "Top users",
+        UsageStatsUserLine => "<@{0}> · {1}",
+        UsageStatsNoUsers => "No one has opted in with /stock prefs leaderboard.",
+        CleanAlready => "The watchlist is already clean ({0} symbols).",
+        CleanDone => {
+            "Cleaned the watchlist: {0} normalized, {1} merged or removed, {2} symbols left."
+        }
+        SettingsStyle => "Signal style: {0}",
+        StyleSet => "Signal style set to {0}. It applies from the next scan.",
+        StyleInvalid => "The style wasn't saved: {0}",
+        AboutStore => "Redis",
+        AboutStoreValue => {
+            "{0} keys · {1}\n{2} symbols in {3} watchlists · {4} alerts · {5} pending confirmations"
+        }
+        SettingsTimezone => "Chart timezone: {0}",
+        TimezoneSet => "Charts are now labelled in {0}, currently {1} there.",
+        TimezoneInvalid => "The timezone wasn't saved: {0}",
+        ResyncUnchanged => "Discord already has the current commands.",
+        ResyncForced => "Re-registered the global commands. Nothing had changed.",
+        ResyncRegistered => "Re-registered the global commands. {0} changes: {1}",
+        ResyncGuilds => "Registered the commands in {0} servers.",
+        ResyncCleared => "Removed stale global commands: {0}",
+        ResyncDropped => "Removed the commands from servers no longer listed: {0}",
+        ResyncGuild => "Registered the commands in server {0}; they're up to date there now.",
+        ResyncInvalidGuild => "`{0}` isn't a server id.",
+        AdminCacheTitle => "Caches flushed",
+        AdminCacheBars => "Bar histories",
+        AdminCacheSymbolList => "Listed symbols",
+        AdminCacheSignals => "Cashtag signals",
+        AdminCacheDropped => "dropped",
+        AdminCacheNotHeld => "wasn't cached",
+        AdminStoreTitle => "Redis store",
+        AdminStoreKeys => "Keys ({0})",
+        AdminStoreMemory => "Memory (whole server)",
+        AdminStoreCircuits => "Circuits",
+        AdminStoreCircuitsValue => "read `{0}` · write `{1}`",
+        Unavailable => "unavailable",
+        AdminLogLevelChanged => "Log filter is now `{0}` (was `{1}`).",
+        AdminLogLevelInvalid => "❌ {0}\nThe log filter is still `{1}`.",
+        SettingsStrategy => "Signal strategy: {0}",
+        StrategyCurrent => "Signals here use **{0}**.",
+        StrategySet => {
+            "Signals here now use **{0}**. Graphs, `/stock trigger` and the daily scan pick it up from now on."
+        }
+        StrategyReset => "Back to the default strategy, **{0}**.",
+        StrategyInvalid => "The strategy wasn't saved: {0}",
+        StrategyAdxPeriodUnused => "The ADX period only applies once a minimum ADX is set.",
+        PrefsThemeSet => "Your charts will now use the {0} theme.",
+        PrefsThemePreview => {
+            "A sample chart in the {0} theme. Nothing was changed; use `/stock prefs theme` to switch."
+        }
+        PrefsScaleSet => "Your charts will now be drawn at {0}.",
+        SettingsWatermark => "Share watermark: {0}",
+        SettingsIntraday => "Intraday scan: {0}",
+        IntradayCharts => "with charts",
+        IntradayTextOnly => "text only",
+        IntradaySet => {
+            "Intraday crossovers will be posted in {1} as each {0} bar closes during market hours, {2}."
+        }
+        IntradayOff => "The intraday scan is turned off for this server.",
+        IntradayNeedsChannel => "❌ Pick a channel to post intraday crossovers to.",
+        WatermarkSet => "`/stock share` images will now carry \"{0}\".",
+        WatermarkCleared => "`/stock share` images will no longer carry a watermark.",
+        BrowsePage => "Page {0}/{1} · pick a symbol to see its chart",
+        BrowsePlaceholder => "Choose a symbol...",
+        BrowsePrev => "◀ Prev",
+        BrowseNext => "Next ▶",
+        BrowseBack => "◀ Back to list",
+        BrowseRemove => "Remove from watchlist",
+        BrowseRemoved => "Removed {0} from the watchlist.",
+        BrowseRemoveNeedsManageGuild => {
+            "❌ Only members with Manage Server can remove symbols here."
+        }
+        BrowseClose => "Close",
+        BrowseChange => "Change",
+        BrowseRange => "1y range",
+        AliasInterpreted => "ℹ️ Interpreting '{0}' as {1}.",
+        AliasAdded => "✅ `{0}` now stands for **{1}**.",
+        AliasReplaced => "✅ `{0}` now stands for **{1}** instead of {2}.",
+        AliasRemoved => "🗑️ Removed the alias `{0}`.",
+        AliasNotFound => "❌ This server has no alias `{0}`.",
+        AliasListTitle => "Symbol aliases in this server",
+        AliasListEmpty => "No custom aliases yet. Add one with `/stock alias add`.",
+        AliasEmpty => "❌ Give the alias a name.",
+        AliasTooLong => "❌ Alias names can be at most {0} characters.",
+        AliasShadowsTicker => "❌ `{0}` is a ticker itself, so it can't be an alias.",
+        AliasLimit => "❌ This server already has {0} aliases. Remove one first.",
+        StoreUnavailable => {
+            "⚠️ The watchlist backend is temporarily unavailable. Try again in a moment."
+        }
+        DataTitle => "{0}: last {1} bars ({2})",
+        DataCapped => "Tables show at most {0} bars; pick format csv for up to {1}.",
+        GraphsCapped => "Charts at most {0} symbols at once; left out: {1}",
+        GraphsNoData => "No price history for: {0}",
+        ReportCsvReady => "📄 Indicators for {0} symbols ({1} failed).",
+        UnlistedSymbols => "⚠️ Ignored (not listed): {0}",
+        CheckTitle => "{0} daily scan check",
+        CheckNoData => "{0} has no daily history to check.",
+        CheckPosts => "✅ The daily scan would post a {0} today.",
+        CheckNoCrossover => "➖ No crossover on the latest bar, only {0}: nothing would post.",
+        CheckWeakTrend => "🚫 {0} crossover present but ADX {1} is below the {2} minimum.",
+        CheckWeeklyDisagrees => "🚫 {0} crossover present but the weekly trend is {1}.",
+        CheckWeeklyUnknown => {
+            "🚫 {0} crossover present but there isn't enough weekly history to confirm it."
+        }
+        CheckAdx => "ADX {0} against a minimum of {1}",
+        CheckAdxOff => "No ADX filter",
+        CheckWeekly => "Weekly trend: {0}",
+        CheckWeeklyNotNeeded => "Weekly confirmation on, only checked on a crossover",
+        CheckWeeklyOff => "Weekly confirmation off",
+        CheckLeftOut => "🙈 Left out of the daily scan here, so nothing would post.",
+        CheckQuiet => "🔇 Muted until {0}: a post would be greyed out.",
+        AddSymbolButton => "➕ Add symbol",
+        AddSymbolTitle => "Add to the watchlist",
+        AddSymbolTicker => "Ticker or alias",
+        AddSymbolNote => "Note (optional)",
+        AddSymbolInvalid => {
+            "⚠️ {0} isn't a ticker (like AAPL or BRK.B) or a crypto pair (like BTC/USD)."
+        }
+        AddSymbolUnlisted => "⚠️ {0} isn't listed on any exchange the price feed covers.",
+        AddSymbolNoteSaved => "📝 Note: {0}",
+        ReversalTitle => "↩️ Reversal",
+        ReversesPost => "Reverses the {0} posted {1} · [view post]({2})",
+        SupersededBy => "~~Superseded by {0} on {1}~~ · [view post]({2})",
+        VolumeSpikeTitle => "{0} Volume Spike",
+        VolumeSpike => "Volume spike: {0}× average",
+        VolumeSpikeDetail => "{0} traded against a {1}-bar average of {2}, closing at {3}.",
+        SettingsVolumeSpikes => "Volume spike alerts: {0}",
+        VolumeSpikesOn => {
+            "Volume spike alerts on: symbols trading {0}× their average volume will be posted."
+        }
+        VolumeSpikesOff => "Volume spike alerts off.",
+        EarningsToday => "⚠️ {0} reports earnings today",
+        EarningsTomorrow => "⚠️ {0} earnings tomorrow",
+        EarningsSoon => "⚠️ {0} earnings in {1} days",
+        SnapshotTitle => "{0} Snapshot",
+        SnapshotNoData => "No snapshot for {0}.",
+        SnapshotLast => "Last",
+        SnapshotBook => "Bid / Ask",
+        SnapshotBookValue => "{0} × {1} / {2} × {3}",
+        SnapshotPrevClose => "Previous close",
+        SnapshotDay => "Today",
+        SnapshotDayValue => "O {0} · H {1} · L {2} · C {3} · Vol {4}",
+        SnapshotSignal => "Signal",
+        SnapshotNoSignal => "Not enough history",
+        SnapshotAsOf => "As of {0}",
+        PositionTitle => "Open position",
+        PositionValue => "{0} from {1}",
+        EntrySet => "📌 Entry for {0} set at {1}. Signals and charts will show the P/L against it.",
+        EntryCleared => "Entry for {0} cleared.",
+        EntryNone => "No entry was set for {0}.",
+        EntryInvalid => "An entry price has to be above zero.",
+        EntryManagersOnly => {
+            "Entry prices here are the server's and show on its scan posts, so only members who can manage the server can set them. In a DM with the bot you can keep your own."
+        }
+        CommandInvalidSymbol => "❌ `{0}` isn't a valid ticker symbol.",
+        CommandTimedOut => "⏱️ That took too long to answer. Try again in a moment.",
+        CommandDataUnavailable => {
+            "⚠️ The market data provider is busy or down right now. Try again in a minute."
+        }
+        CommandDataFailed => "⚠️ The market data provider couldn't answer that request.",
+        CommandFailed => "⚠️ Something went wrong running that command. It's been logged.",
+        SpreadTitle => "{0} {1}/{2} Spread",
+        SpreadNow => "Spread",
+        SpreadNowValue => "{0} ({1} of the slow average)",
+        SpreadAbove => "Above zero",
+        SpreadAboveValue => "{0} of {1} bars",
+        SpreadCrossovers => "Crossovers",
+        SpreadCrossoversValue => "{0} in {1} bars, the last a {2} on {3}",
+        SpreadNoCrossovers => "None in {0} bars",
+        CashtagsUpdated => "Cashtag reactions: {0}, price replies: {1}.",
+        CashtagsIntentDisabled => {
+            "⚠️ The bot isn't reading message content, so cashtags won't be seen until MESSAGE_CONTENT_INTENT is enabled."
+        }
+    }
+}
+
+fn th(key: MessageKey) -> &'static str {
+    use MessageKey::*;
+    match key {
+        NoValidSymbols => "ไม่มีสัญลักษณ์หุ้นที่ถูกต้อง",
+        NowWatching => "เริ่มติดตาม: {0}",
+        AlreadyWatching => "ติดตามอยู่แล้ว: {0}",
+        WatchlistEmpty => "รายการติดตามว่างเปล่า",
+        DeleteSelectPrompt => "เลือกสัญลักษณ์ที่ต้องการลบ (เลือกได้หลายรายการ):",
+        DeleteSelectPlaceholder => "เลือกสัญลักษณ์...",
+        DeleteConfirmPrompt => "ยืนยันการลบ **{0}** สัญลักษณ์หรือไม่?\n> {1}",
+        DeleteNotOwner => "❌ คุณไม่สามารถยืนยันการลบของผู้อื่นได้",
+        DeleteSessionExpired => "❌ เซสชันหมดอายุแล้ว กรุณาใช้ /delete อีกครั้ง",
+        ConfirmExpires => "⏳ คำขอนี้จะหมดอายุ {0}",
+        Deleted => "ลบ {0} แล้ว",
+        DeleteImpactAlerts => "การแจ้งเตือน {0} รายการ",
+        DeleteImpactAdded => "เพิ่มเมื่อ {0} วันก่อน",
+        DeleteImpactNothing => "ไม่มีข้อมูลอื่น",
+        DeleteCleaned => "ลบข้อมูลที่เกี่ยวข้องอีก {0} รายการ",
+        DeleteFailed => "⚠️ ลบไม่สำเร็จ: {0}",
+        ButtonConfirm => "ยืนยัน",
+        ButtonCancel => "ยกเลิก",
+        Cancelled => "ยกเลิกแล้ว",
+        AnalysisTitle => "วิเคราะห์ {0}",
+        CurrentSignal => "สัญญาณปัจจุบัน: {0}",
+        DonchianBreakoutBullish => "📈 ราคาทะลุจุดสูงสุดในรอบ {0} วัน",
+        DonchianBreakoutBearish => "📉 ราคาหลุดจุดต่ำสุดในรอบ {0} วัน",
+        NoSignalsFound => "ไม่พบสัญญาณซื้อ/ขาย",
+        ScanComplete => "✅ สแกนเสร็จแล้ว",
+        ResultsContinued => "ผลต่อจากคำสั่ง /{1} ของ {0}",
+        RouteFallback => "⚠️ โพสต์ใน {0} ไม่ได้ สัญญาณของช่องนั้นจึงมาอยู่ที่นี่แทน",
+        RouteSet => "สัญญาณจากการสแกนของหุ้นที่มีแท็ก `{0}` จะถูกโพสต์ใน {1}",
+        RouteCleared => "หุ้นที่มีแท็ก `{0}` จะถูกโพสต์รวมกับผลสแกนอื่นตามเดิม",
+        SettingsRoutes => "ช่องตามแท็ก: {0}",
+        TagInvalid => "⚠️ `{0}` ใช้เป็นแท็กไม่ได้: ใช้ตัวอักษร ตัวเลข `-` หรือ `_` ได้ไม่เกิน {1} ตัว",
+        TagsTooMany => "⚠️ หุ้นหนึ่งตัวมีแท็กได้ไม่เกิน {0} แท็ก",
+        TagsSet => "ติดแท็ก {0}: {1}",
+        TagsCleared => "ลบแท็กของ {0} แล้ว",
+        TriggerCooldown => "⏳ เพิ่งมีการสแกนไปเมื่อสักครู่ กรุณาลองใหม่ในอีก {0} วินาที",
+        SignalBuy => "ซื้อ",
+        SignalSell => "ขาย",
+        SignalBullishZone => "โซนขาขึ้น",
+        SignalBearishZone => "โซนขาลง",
+        SignalNone => "ไม่มีสัญญาณ",
+        GuildOnly => "คำสั่งนี้ใช้ได้เฉพาะในเซิร์ฟเวอร์เท่านั้น",
+        SettingsTitle => "การตั้งค่าเซิร์ฟเวอร์",
+        SettingsLocale => "ภาษา: {0}",
+        SettingsDailyChannel => "ช่องสำหรับสัญญาณรายวัน: {0}",
+        NotSet => "ยังไม่ได้ตั้งค่า",
+        LocaleAuto => "อัตโนมัติ (ตามภาษา Discord ของสมาชิก)",
+        LocaleSet => "ตั้งค่าภาษาของเซิร์ฟเวอร์เป็น {0} แล้ว",
+        LocaleCleared => "ยกเลิกการกำหนดภาษาของเซิร์ฟเวอร์แล้ว",
+        DailyChannelSet => "สัญญาณรายวันจะถูกโพสต์ใน {0}",
+        DailyChannelCleared => "ปิดการโพสต์สัญญาณรายวันสำหรับเซิร์ฟเวอร์นี้แล้ว",
+        PrefsEphemeralOn => "ข้อความตอบกลับเกี่ยวกับรายการติดตามจะแสดงให้คุณเห็นเท่านั้น",
+        PrefsEphemeralOff => "ข้อความตอบกลับเกี่ยวกับรายการติดตามจะแสดงต่อสาธารณะ",
+        CashtagLine => "{0} **{1}** ${2} · {3}",
+        SettingsCashtagReactions => "รีแอคชันแคชแท็ก: {0}",
+        SettingsCashtagReplies => "ตอบกลับราคาแคชแท็ก: {0}",
+        On => "เปิด",
+        Off => "ปิด",
+        AboutTitle => "เกี่ยวกับบอท",
+        AboutVersion => "เวอร์ชัน",
+        AboutUptime => "เวลาทำงาน",
+        AboutWatchlist => "รายการติดตาม",
+        AboutLastDailyRun => "สแกนรายวันล่าสุด",
+        Never => "ยังไม่เคย",
+        MutedUntil => "🔇 ปิดเสียงถึง {0}",
+        QuietSet => "🔇 ปิดเสียง {0} ถึง {1} สัญญาณยังแสดงแต่เป็นสีเทา",
+        QuietCleared => "🔔 เลิกปิดเสียง {0} แล้ว",
+        MuteSet => "🙈 ไม่สแกน {0} ในรอบประจำวันจนถึง {1}",
+        MuteSetIndefinite => "🙈 ไม่สแกน {0} ในรอบประจำวันจนกว่าจะยกเลิก",
+        MuteCleared => "👀 กลับมาสแกน {0} ในรอบประจำวันแล้ว",
+        MuteNotMuted => "{0} ไม่ได้ถูกปิดการสแกน",
+        NotWatching => "{0} ไม่ได้อยู่ในรายการติดตาม",
+        WatchlistTitle => "รายการติดตาม ({0})",
+        SourceBars => "ข้อมูล: แท่งราคารายวัน",
+        SourceIntraday => "ข้อมูล: แท่งราคา {0}",
+        ChartGenerated => "สร้างเมื่อ {0}",
+        SourceSnapshot => "ข้อมูล: สแนปช็อตล่าสุด (แท่งรายวันยังไม่อัปเดต)",
+        SourceStale => "⚠️ ข้อมูล: แท่งราคารายวัน รอบล่าสุด {0}",
+        SourceFallback => "🔀 ข้อมูลจากแหล่งสำรอง {0}",
+        InvalidPrice => "ราคาต้องมากกว่าศูนย์",
+        AlertSet => "🔔 ตั้งการแจ้งเตือนแล้ว: {0}",
+        AlertsTitle => "การแจ้งเตือนราคาของคุณ ({0})",
+        AlertsEmpty => "คุณยังไม่มีการแจ้งเตือนราคา",
+        AlertSelectPlaceholder => "เลือกการแจ้งเตือนที่ต้องการลบ...",
+        AlertDeleteConfirmPrompt => "ลบการแจ้งเตือน **{0}** รายการหรือไม่?\n> {1}",
+        AlertSessionExpired => "❌ เซสชันหมดอายุแล้ว กรุณาใช้ /stock alert list อีกครั้ง",
+        AlertsDeleted => "ลบการแจ้งเตือนแล้ว: {0}",
+        AlertModeOneShot => "ครั้งเดียว",
+        AlertModeRecurring => "ซ้ำได้ ห่างกันอย่างน้อย {0}",
+        AlertLastFired => "แจ้งเตือนล่าสุด {0}",
+        AlertNeverFired => "ยังไม่เคยแจ้งเตือน",
+        AlertFired => "🔔 **{0}** อยู่ {1} ${2} แล้ว (ตอนนี้ ${3})",
+        AlertMoveFired => "🔔 **{0}** เคลื่อนไหว {1} จาก ${2} แล้ว (ตอนนี้ ${3})",
+        AlertNoReference => "❌ ไม่สามารถดึงราคาปัจจุบันของ {0} ได้",
+        SinceAdded => "{0} ตั้งแต่เริ่มติดตาม",
+        SinceAddedApprox => "{0} ตั้งแต่เริ่มติดตาม (โดยประมาณ)",
+        ChartAddedLine => "ราคาที่เริ่มติดตาม",
+        SettingsDailyPaused => "สัญญาณรายวัน: ⏸️ หยุดชั่วคราวถึง {0} (เหลือ {1})",
+        SettingsDailyRunning => "สัญญาณรายวัน: ▶️ ทำงานอยู่",
+        DailyPaused => "⏸️ หยุดสัญญาณรายวันชั่วคราวถึง {0} การตั้งค่ายังอยู่ครบ",
+        DailyResumed => "▶️ เปิดสัญญาณรายวันอีกครั้งแล้ว",
+        DailyNotPaused => "สัญญาณรายวันไม่ได้หยุดอยู่",
+        DailyResumedAfterPause => "▶️ สัญญาณรายวันกลับมาแล้วหลังหยุดชั่วคราว",
+        PrefsTimeframeSet => "กราฟใหม่จะใช้แท่งเทียน {0} เป็นค่าเริ่มต้น",
+        RunsDisabled => "ไม่ได้เก็บประวัติการสแกนรายวัน (DAILY_ARCHIVE ปิดอยู่)",
+        RunsEmpty => "ยังไม่มีประวัติการสแกนรายวัน",
+        RunsRecent => "ประวัติการสแกนรายวัน: {0}",
+        RunsInvalidDate => "`{0}` ไม่ใช่วันที่ ใช้รูปแบบ YYYY-MM-DD",
+        RunsNotFound => "ไม่พบประวัติการสแกนของเซิร์ฟเวอร์นี้ในวันที่ {0}",
+        RunsAttached => "การสแกนรายวันวันที่ {0}: {1} หุ้น",
+        InvalidSymbols => "ข้ามสัญลักษณ์ที่ไม่ถูกต้อง: {0}",
+        ScanIncomplete => "⚠️ สแกนไม่ครบ: ดึงข้อมูลไม่ได้ {0} จาก {1} หุ้น สัญญาณของหุ้นเหล่านั้นจึงขาดหายไป",
+        ScanFailedSymbols => "ล้มเหลว: {0}",
+        RunSlow => "🐢 รอบวันนี้ช้ากว่าปกติ: ใช้ไป {0} แล้ว จากปกติ {1}",
+        ScanDegraded => "🖼️ มี {0} สัญญาณที่ส่งไปโดยไม่มีกราฟ เพราะสร้างกราฟไม่สำเร็จ",
+        ScanUploadRejected => "🖼️ มี {0} สัญญาณที่ส่งไปโดยไม่มีกราฟ เพราะ Discord ไม่รับไฟล์ที่อัปโหลด",
+        ScanFallback => "🔀 มี {0} สัญญาณที่ใช้ข้อมูลจากแหล่งสำรอง ซึ่งอาจต่างจากแหล่งหลักเล็กน้อย",
+        ChartUnavailable => "ไม่มีกราฟ",
+        AndMore => "และอีก {0}",
+        WatchConfirmPrompt => "ยืนยันการเพิ่ม **{0}** สัญลักษณ์ลงในรายการหรือไม่?\n> {1}",
+        WatchNotOwner => "❌ คุณไม่สามารถยืนยันคำขอเพิ่มของผู้อื่นได้",
+        WatchSessionExpired => "❌ เซสชันหมดอายุแล้ว กรุณาใช้ /watch อีกครั้ง",
+        GraphDidYouMean => "❓ ไม่รู้จักสัญลักษณ์ **{0}** หมายถึง **{1}** ในรายการที่ติดตามหรือเปล่า?",
+        GraphChartSuggested => "ดูกราฟ {0}",
+        GraphCharting => "📈 กำลังสร้างกราฟ **{0}**…",
+        DeleteNoMatch => "ไม่มีสัญลักษณ์ที่ติดตามตรงกับ **{0}**",
+        DividendsTitle => "เงินปันผลและการแตกหุ้นของ {0}",
+        DividendsUpcoming => "ที่จะถึง",
+        DividendsRecent => "ในรอบปีที่ผ่านมา",
+        DividendsNone => "{0} ไม่มีเงินปันผลหรือการแตกหุ้นในรอบปีที่ผ่านมา และยังไม่มีประกาศใหม่",
+        DividendsDividend => "`{0}` เงินปันผล **{1}** ต่อหุ้น",
+        DividendsSpecialDividend => "`{0}` เงินปันผลพิเศษ **{1}** ต่อหุ้น",
+        DividendsPayable => " จ่าย `{0}`",
+        DividendsSplit => "`{0}` แตกหุ้น {1}",
+        DividendsReverseSplit => "`{0}` รวมหุ้น {1}",
+        AnalyzeNoData => "ไม่มีข้อมูลราคาของ {0}",
+        ChartInsufficientHistory => "ข้อมูลย้อนหลังของ {0} ไม่พอสำหรับสร้างกราฟ ({1} แท่ง)",
+        AnalyzeShortHistory => "⚠️ มีข้อมูลย้อนหลังเพียง {0} แท่ง ค่าที่แสดงอาจไม่น่าเชื่อถือ และไม่มีกราฟ",
+        AnalyzeZone => "โซน CDC",
+        AnalyzeRsi => "RSI ({0})",
+        AnalyzeMacd => "MACD",
+        AnalyzeStop => "จุดตัดขาดทุน ({0}× ATR)",
+        AnalyzeRange => "กรอบราคา 52 สัปดาห์",
+        AnalyzeVolume => "วอลุ่มเทียบค่าเฉลี่ย {0} วัน",
+        AnalyzeEarnings => "ประกาศงบครั้งถัดไป",
+        AnalyzeStopValue => "{0} ({1}) · ATR {2}",
+        AnalyzeRangeValue => "{0} – {1} · {2}% ของกรอบ",
+        AnalyzeVolumeValue => "{0} เทียบกับ {1} ({2}×)",
+        RsiOverbought => "ซื้อมากเกินไป",
+        RsiOversold => "ขายมากเกินไป",
+        MacdBullishCross => "ตัดขึ้น",
+        MacdBearishCross => "ตัดลง",
+        MacdBullish => "อยู่เหนือเส้นสัญญาณ",
+        MacdBearish => "อยู่ใต้เส้นสัญญาณ",
+        InvalidIndicators => "❌ อ่านรายการอินดิเคเตอร์ `{0}` ไม่ได้ เลือกได้จาก: {1}",
+        AboutApiUsage => "คำขอ Alpaca (นาทีล่าสุด)",
+        OnboardingTitle => "👋 ขอบคุณที่เพิ่มบอทเข้าเซิร์ฟเวอร์!",
+        OnboardingBody => {
+            "บอทจะโพสต์สัญญาณ CDC Action Zone ของหุ้นที่เซิร์ฟเวอร์ติดตาม\n• `/stock watch TSLA,MSFT` เพิ่มหุ้นเข้ารายการติดตาม\n• `/stock graph` ดูกราฟ\n• `/stock settings channel` เลือกช่องสำหรับสัญญาณรายวัน\n\nยังไม่ได้ตั้งช่องสำหรับสัญญาณรายวัน จึงยังไม่มีการโพสต์ กดปุ่มเพื่อให้โพสต์ในช่องนี้"
+        }
+        OnboardingSetChannel => "ใช้ช่องนี้สำหรับสัญญาณรายวัน",
+        OnboardingNeedsManageGuild => "❌ เฉพาะสมาชิกที่มีสิทธิ์จัดการเซิร์ฟเวอร์เท่านั้นที่ตั้งช่องสัญญาณรายวันได้",
+        BenchmarkNoData => "❌ ไม่พบข้อมูลราคาของดัชนีเปรียบเทียบ `{0}`",
+        SpotlightTitle => "📌 กราฟประจำวัน: {0}",
+        SpotlightCrossedBullish => "{0} ตัดขึ้นเป็นขาขึ้นในเซสชันล่าสุด{1}",
+        SpotlightCrossedBearish => "{0} ตัดลงเป็นขาลงในเซสชันล่าสุด{1}",
+        SpotlightBullishStrengthening => "{0} แนวโน้มขาขึ้นแข็งแรงขึ้นในเซสชันล่าสุด{1}",
+        SpotlightBullishFading => "{0} แนวโน้มขาขึ้นอ่อนแรงลงในเซสชันล่าสุด{1}",
+        SpotlightBearishStrengthening => "{0} แนวโน้มขาลงรุนแรงขึ้นในเซสชันล่าสุด{1}",
+        SpotlightBearishFading => "{0} แนวโน้มขาลงผ่อนแรงลงในเซสชันล่าสุด{1}",
+        SpotlightVolume => " ด้วยปริมาณซื้อขาย {0} เท่าของค่าเฉลี่ย",
+        SpotlightGap => "EMA 12 วันห่างจาก EMA 26 วัน {0} ขยับไป {1} จุดในเซสชันเดียว",
+        SpotlightPoll => "คุณจะเข้าเทรดนี้ไหม? กด 📈 ถ้าใช่ หรือ 📉 ถ้าไม่",
+        IgnoredInvalidSymbols => "⚠️ ข้าม (ไม่ถูกต้อง): {0}",
+        ScanBenchmarkTitle => "⏱️ วัดประสิทธิภาพการสแกน",
+        ScanBenchmarkTotal => "เวลาทั้งหมด",
+        ScanBenchmarkSymbols => "สัญลักษณ์",
+        ScanBenchmarkSymbolsValue => "สแกน {0} · สัญญาณ {1} · ล้มเหลว {2}",
+        ScanBenchmarkFetch => "ดึงข้อมูล",
+        ScanBenchmarkRender => "สร้างกราฟ",
+        ScanBenchmarkStage => "เฉลี่ย {0} · p95 {1} · สูงสุด {2} ({3} ตัวอย่าง)",
+        ScanBenchmarkNoSamples => "ไม่มีข้อมูล",
+        DigestHeader => "📬 **สัญญาณวันนี้ในรายการของคุณ** ({0})",
+        DigestLine => "• **{0}** {1} ที่ {2}",
+        DigestLink => " · [ดูโพสต์]({0})",
+        DigestOn => "📬 หลังการสแกนประจำวันแต่ละครั้ง คุณจะได้รับ DM สรุปสัญญาณในรายการส่วนตัวของคุณ",
+        DigestOff => "📭 ปิดสรุปประจำวันแล้ว",
+        DigestDisabled => {
+            "{0} ส่ง DM สรุปประจำวันถึงคุณไม่ได้ จึงปิดไว้ก่อน เปิดรับ DM จากเซิร์ฟเวอร์นี้แล้วใช้ `/stock digest on` เพื่อเปิดอีกครั้ง"
+        }
+        PriceLastClose => "{0} (ปิดล่าสุด)",
+        PrefsLeaderboardOn => "คุณจะถูกแสดงในรายชื่อผู้ใช้งานสูงสุดของ /stock stats",
+        PrefsLeaderboardOff => "คุณจะไม่ถูกแสดงในรายชื่อผู้ใช้งานสูงสุดของ /stock stats",
+        UsageStatsTitle => "📊 การใช้งานคำสั่ง · {0} วันล่าสุด",
+        UsageStatsEmpty => "ไม่มีการใช้คำสั่งในช่วง {0} วันล่าสุด",
+        UsageStatsTotal => "จำนวนครั้งทั้งหมด",
+        UsageStatsCommands => "แยกตามคำสั่ง",
+        UsageStatsCommandLine => "`/{0}` · {1}",
+        UsageStatsBusiest => "วันที่ใช้งานมากที่สุด",
+        UsageStatsBusiestValue => "{0} ({1} ครั้ง)",
+        UsageStatsTopUsers => "ผู้ใช้งานสูงสุด",
+        UsageStatsUserLine => "<@{0}> · {1}",
+        UsageStatsNoUsers => "ยังไม่มีใครเลือกแสดงชื่อด้วย /stock prefs leaderboard",
+        CleanAlready => "รายการติดตามเรียบร้อยอยู่แล้ว ({0} สัญลักษณ์)",
+        CleanDone => {
+            "จัดระเบียบรายการติดตามแล้ว: ปรับรูปแบบ {0} รายการ รวมหรือลบ {1} รายการ เหลือ {2} สัญลักษณ์"
+        }
+        SettingsStyle => "รูปแบบสัญญาณ: {0}",
+        StyleSet => "ตั้งรูปแบบสัญญาณเป็น {0} แล้ว จะมีผลตั้งแต่การสแกนครั้งถัดไป",
+        StyleInvalid => "ไม่ได้บันทึกรูปแบบ: {0}",
+        AboutStore => "Redis",
+        AboutStoreValue => {
+            "{0} คีย์ · {1}\n{2} สัญลักษณ์ใน {3} รายการติดตาม · การแจ้งเตือน {4} · รอยืนยัน {5}"
+        }
+        SettingsTimezone => "เขตเวลาของกราฟ: {0}",
+        TimezoneSet => "กราฟจะแสดงเวลาตามเขต {0} แล้ว ขณะนี้ที่นั่นเป็นเวลา {1}",
+        TimezoneInvalid => "ไม่ได้บันทึกเขตเวลา: {0}",
+        ResyncUnchanged => "Discord มีคำสั่งเวอร์ชันปัจจุบันอยู่แล้ว",
+        ResyncForced => "ลงทะเบียนคำสั่งแบบ global ใหม่แล้ว ไม่มีอะไรเปลี่ยน",
+        ResyncRegistered => "ลงทะเบียนคำสั่งแบบ global ใหม่แล้ว เปลี่ยน {0} รายการ: {1}",
+        ResyncGuilds => "ลงทะเบียนคำสั่งใน {0} เซิร์ฟเวอร์แล้ว",
+        ResyncCleared => "ลบคำสั่ง global ที่ค้างอยู่: {0}",
+        ResyncDropped => "ลบคำสั่งออกจากเซิร์ฟเวอร์ที่ไม่อยู่ในรายการแล้ว: {0}",
+        ResyncGuild => "ลงทะเบียนคำสั่งในเซิร์ฟเวอร์ {0} แล้ว ใช้งานได้ทันที",
+        ResyncInvalidGuild => "`{0}` ไม่ใช่ ID ของเซิร์ฟเวอร์",
+        AdminCacheTitle => "ล้างแคชแล้ว",
+        AdminCacheBars => "ข้อมูลราคาย้อนหลัง",
+        AdminCacheSymbolList => "รายชื่อสัญลักษณ์",
+        AdminCacheSignals => "สัญญาณจาก cashtag",
+        AdminCacheDropped => "ล้างแล้ว",
+        AdminCacheNotHeld => "ไม่ได้แคชไว้",
+        AdminStoreTitle => "ที่เก็บข้อมูล Redis",
+        AdminStoreKeys => "คีย์ ({0})",
+        AdminStoreMemory => "หน่วยความจำ (ทั้งเซิร์ฟเวอร์)",
+        AdminStoreCircuits => "เซอร์กิต",
+        AdminStoreCircuitsValue => "อ่าน `{0}` · เขียน `{1}`",
+        Unavailable => "ไม่มีข้อมูล",
+        AdminLogLevelChanged => "ตัวกรองล็อกตอนนี้คือ `{0}` (เดิม `{1}`)",
+        AdminLogLevelInvalid => "❌ {0}\nตัวกรองล็อกยังเป็น `{1}`",
+        SettingsStrategy => "กลยุทธ์สัญญาณ: {0}",
+        StrategyCurrent => "สัญญาณในเซิร์ฟเวอร์นี้ใช้ **{0}**",
+        StrategySet => {
+            "สัญญาณในเซิร์ฟเวอร์นี้ใช้ **{0}** แล้ว กราฟ `/stock trigger` และการสแกนรายวันจะใช้ตั้งแต่นี้ไป"
+        }
+        StrategyReset => "กลับไปใช้กลยุทธ์เริ่มต้น **{0}** แล้ว",
+        StrategyInvalid => "ไม่ได้บันทึกกลยุทธ์: {0}",
+        StrategyAdxPeriodUnused => "ช่วงเวลา ADX จะมีผลเมื่อกำหนดค่า ADX ขั้นต่ำแล้วเท่านั้น",
+        PrefsThemeSet => "กราฟของคุณจะใช้ธีม {0} นับจากนี้",
+        PrefsThemePreview => {
+            "ตัวอย่างกราฟในธีม {0} ยังไม่มีการเปลี่ยนแปลงใด ๆ ใช้ `/stock prefs theme` เพื่อเปลี่ยน"
+        }
+        PrefsScaleSet => "กราฟของคุณจะแสดงที่ความละเอียด {0} นับจากนี้",
+        SettingsWatermark => "ลายน้ำรูปที่แชร์: {0}",
+        SettingsIntraday => "สแกนระหว่างวัน: {0}",
+        IntradayCharts => "พร้อมกราฟ",
+        IntradayTextOnly => "ข้อความเท่านั้น",
+        IntradaySet => {
+            "สัญญาณตัดกันบนแท่งราคา {0} จะถูกโพสต์ใน {1} เมื่อแต่ละแท่งปิดระหว่างเวลาทำการตลาด ({2})"
+        }
+        IntradayOff => "ปิดการสแกนระหว่างวันสำหรับเซิร์ฟเวอร์นี้แล้ว",
+        IntradayNeedsChannel => "❌ กรุณาเลือกช่องสำหรับโพสต์สัญญาณระหว่างวัน",
+        WatermarkSet => "รูปจาก `/stock share` จะมีข้อความ \"{0}\" แล้ว",
+        WatermarkCleared => "รูปจาก `/stock share` จะไม่มีลายน้ำอีกต่อไป",
+        BrowsePage => "หน้า {0}/{1} · เลือกหุ้นเพื่อดูกราฟ",
+        BrowsePlaceholder => "เลือกหุ้น...",
+        BrowsePrev => "◀ ก่อนหน้า",
+        BrowseNext => "ถัดไป ▶",
+        BrowseBack => "◀ กลับไปที่รายการ",
+        BrowseRemove => "ลบออกจากรายการติดตาม",
+        BrowseRemoved => "ลบ {0} ออกจากรายการติดตามแล้ว",
+        BrowseRemoveNeedsManageGuild => "❌ เฉพาะสมาชิกที่มีสิทธิ์จัดการเซิร์ฟเวอร์เท่านั้นที่ลบหุ้นได้",
+        BrowseClose => "ราคาปิด",
+        BrowseChange => "เปลี่ยนแปลง",
+        BrowseRange => "ช่วงราคา 1 ปี",
+        AliasInterpreted => "ℹ️ ตีความ '{0}' เป็น {1}",
+        AliasAdded => "✅ `{0}` หมายถึง **{1}** แล้ว",
+        AliasReplaced => "✅ `{0}` หมายถึง **{1}** แทน {2} แล้ว",
+        AliasRemoved => "🗑️ ลบชื่อเรียก `{0}` แล้ว",
+        AliasNotFound => "❌ เซิร์ฟเวอร์นี้ไม่มีชื่อเรียก `{0}`",
+        AliasListTitle => "ชื่อเรียกหุ้นในเซิร์ฟเวอร์นี้",
+        AliasListEmpty => "ยังไม่มีชื่อเรียกที่กำหนดเอง เพิ่มได้ด้วย `/stock alias add`",
+        AliasEmpty => "❌ กรุณาตั้งชื่อเรียก",
+        AliasTooLong => "❌ ชื่อเรียกยาวได้ไม่เกิน {0} ตัวอักษร",
+        AliasShadowsTicker => "❌ `{0}` เป็นชื่อหุ้นอยู่แล้ว จึงใช้เป็นชื่อเรียกไม่ได้",
+        AliasLimit => "❌ เซิร์ฟเวอร์นี้มีชื่อเรียกครบ {0} รายการแล้ว กรุณาลบออกก่อน",
+        StoreUnavailable => "⚠️ ระบบเก็บรายการหุ้นขัดข้องชั่วคราว กรุณาลองใหม่อีกครั้งในอีกสักครู่",
+        DataTitle => "{0}: {1} แท่งล่าสุด ({2})",
+        DataCapped => "ตารางแสดงได้สูงสุด {0} แท่ง เลือกรูปแบบ csv เพื่อดูได้ถึง {1} แท่ง",
+        GraphsCapped => "สร้างกราฟได้ครั้งละไม่เกิน {0} ตัว ข้าม: {1}",
+        GraphsNoData => "ไม่มีข้อมูลราคาของ: {0}",
+        ReportCsvReady => "📄 ค่าอินดิเคเตอร์ของ {0} ตัว (ล้มเหลว {1} ตัว)",
+        UnlistedSymbols => "⚠️ ข้าม (ไม่พบในตลาด): {0}",
+        CheckTitle => "ตรวจสแกนรายวันของ {0}",
+        CheckNoData => "{0} ไม่มีข้อมูลรายวันให้ตรวจ",
+        CheckPosts => "✅ สแกนรายวันจะโพสต์สัญญาณ {0} วันนี้",
+        CheckNoCrossover => "➖ แท่งล่าสุดไม่มีจุดตัด มีเพียง {0} จึงไม่มีการโพสต์",
+        CheckWeakTrend => "🚫 มีจุดตัด {0} แต่ ADX {1} ต่ำกว่าขั้นต่ำ {2}",
+        CheckWeeklyDisagrees => "🚫 มีจุดตัด {0} แต่แนวโน้มรายสัปดาห์เป็น {1}",
+        CheckWeeklyUnknown => "🚫 มีจุดตัด {0} แต่ข้อมูลรายสัปดาห์ยังไม่พอจะยืนยัน",
+        CheckAdx => "ADX {0} เทียบกับขั้นต่ำ {1}",
+        CheckAdxOff => "ไม่มีตัวกรอง ADX",
+        CheckWeekly => "แนวโน้มรายสัปดาห์: {0}",
+        CheckWeeklyNotNeeded => "เปิดการยืนยันรายสัปดาห์ ตรวจเฉพาะเมื่อมีจุดตัด",
+        CheckWeeklyOff => "ปิดการยืนยันรายสัปดาห์",
+        CheckLeftOut => "🙈 หุ้นนี้ถูกนำออกจากสแกนรายวันในเซิร์ฟเวอร์นี้ จึงไม่มีการโพสต์",
+        CheckQuiet => "🔇 ปิดเสียงถึง {0} โพสต์จะแสดงเป็นสีเทา",
+        AddSymbolButton => "➕ เพิ่มหุ้น",
+        AddSymbolTitle => "เพิ่มในรายการติดตาม",
+        AddSymbolTicker => "สัญลักษณ์หรือชื่อเล่น",
+        AddSymbolNote => "โน้ต (ไม่บังคับ)",
+        AddSymbolInvalid => "⚠️ {0} ไม่ใช่สัญลักษณ์หุ้น (เช่น AAPL หรือ BRK.B) หรือคู่คริปโต (เช่น BTC/USD)",
+        AddSymbolUnlisted => "⚠️ {0} ไม่ได้จดทะเบียนในตลาดที่แหล่งราคาครอบคลุม",
+        AddSymbolNoteSaved => "📝 โน้ต: {0}",
+        ReversalTitle => "↩️ กลับทิศ",
+        ReversesPost => "กลับทิศจากสัญญาณ {0} ที่โพสต์เมื่อ {1} · [ดูโพสต์]({2})",
+        SupersededBy => "~~ถูกแทนที่ด้วยสัญญาณ {0} เมื่อ {1}~~ · [ดูโพสต์]({2})",
+        VolumeSpikeTitle => "ปริมาณซื้อขายพุ่ง {0}",
+        VolumeSpike => "ปริมาณซื้อขายพุ่ง: {0}× ของค่าเฉลี่ย",
+        VolumeSpikeDetail => "ซื้อขาย {0} เทียบกับค่าเฉลี่ย {1} แท่งที่ {2} ปิดที่ {3}",
+        SettingsVolumeSpikes => "แจ้งเตือนปริมาณซื้อขายพุ่ง: {0}",
+        VolumeSpikesOn => "เปิดแจ้งเตือนปริมาณซื้อขายพุ่ง: จะโพสต์สัญลักษณ์ที่มีปริมาณซื้อขาย {0}× ของค่าเฉลี่ย",
+        VolumeSpikesOff => "ปิดแจ้งเตือนปริมาณซื้อขายพุ่งแล้ว",
+        EarningsToday => "⚠️ {0} ประกาศงบวันนี้",
+        EarningsTomorrow => "⚠️ {0} ประกาศงบพรุ่งนี้",
+        EarningsSoon => "⚠️ {0} ประกาศงบในอีก {1} วัน",
+        SnapshotTitle => "ภาพรวม {0}",
+        SnapshotNoData => "ไม่มีข้อมูลล่าสุดของ {0}",
+        SnapshotLast => "ราคาล่าสุด",
+        SnapshotBook => "เสนอซื้อ / เสนอขาย",
+        SnapshotBookValue => "{0} × {1} / {2} × {3}",
+        SnapshotPrevClose => "ปิดวันก่อน",
+        SnapshotDay => "วันนี้",
+        SnapshotDayValue => "เปิด {0} · สูง {1} · ต่ำ {2} · ปิด {3} · ปริมาณ {4}",
+        SnapshotSignal => "สัญญาณ",
+        SnapshotNoSignal => "ข้อมูลย้อนหลังไม่พอ",
+        SnapshotAsOf => "ณ {0}",
+        PositionTitle => "สถานะที่ถืออยู่",
+        PositionValue => "{0} จาก {1}",
+        EntrySet => "📌 ตั้งราคาเข้าของ {0} ที่ {1} แล้ว สัญญาณและกราฟจะแสดงกำไร/ขาดทุนเทียบกับราคานี้",
+        EntryCleared => "ล้างราคาเข้าของ {0} แล้ว",
+        EntryNone => "ยังไม่ได้ตั้งราคาเข้าของ {0}",
+        EntryInvalid => "ราคาเข้าต้องมากกว่าศูนย์",
+        EntryManagersOnly => {
+            "ราคาเข้าในเซิร์ฟเวอร์นี้เป็นของเซิร์ฟเวอร์และแสดงในโพสต์สแกน จึงตั้งได้เฉพาะสมาชิกที่จัดการเซิร์ฟเวอร์ได้ ตั้งราคาเข้าของคุณเองได้ใน DM กับบอท"
+        }
+        CommandInvalidSymbol => "❌ `{0}` ไม่ใช่สัญลักษณ์หุ้นที่ถูกต้อง",
+        CommandTimedOut => "⏱️ ใช้เวลานานเกินไป กรุณาลองใหม่อีกครั้งในอีกสักครู่",
+        CommandDataUnavailable => "⚠️ ผู้ให้บริการข้อมูลตลาดไม่ว่างหรือขัดข้องอยู่ กรุณาลองใหม่ในอีกหนึ่งนาที",
+        CommandDataFailed => "⚠️ ผู้ให้บริการข้อมูลตลาดไม่สามารถตอบคำขอนี้ได้",
+        CommandFailed => "⚠️ เกิดข้อผิดพลาดขณะรันคำสั่งนี้ ระบบได้บันทึกไว้แล้ว",
+        SpreadTitle => "ส่วนต่าง {1}/{2} ของ {0}",
+        SpreadNow => "ส่วนต่าง",
+        SpreadNowValue => "{0} ({1} ของเส้นค่าเฉลี่ยช้า)",
+        SpreadAbove => "เหนือศูนย์",
+        SpreadAboveValue => "{0} จาก {1} แท่ง",
+        SpreadCrossovers => "จุดตัด",
+        SpreadCrossoversValue => "{0} ครั้งใน {1} แท่ง ครั้งล่าสุดเป็น{2} เมื่อ {3}",
+        SpreadNoCrossovers => "ไม่มีใน {0} แท่ง",
+        CashtagsUpdated => "รีแอคชันแคชแท็ก: {0}, ตอบกลับราคา: {1}",
+        CashtagsIntentDisabled => {
+            "⚠️ บอทไม่ได้อ่านเนื้อหาข้อความ จึงจะไม่เห็นแคชแท็กจนกว่าจะเปิด MESSAGE_CONTENT_INTENT"
+        }
+    }
+}
+
+/// Render `key` for `locale`, substituting positional `{0}`, `{1}`, ... placeholders.
+pub fn tr(locale: Locale, key: MessageKey, args: &[&(dyn Display + Sync)]) -> String {
+    let template = key.template(locale);
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let (head, tail) = rest.split_at(start);
+        out.push_str(head);
+
+        let arg = tail[1..].split_once('}').and_then(|(idx, after)| {
+            let arg = args.get(idx.parse::<usize>().ok()?)?;
+            Some((arg, after))
+        });
+
+        match arg {
+            Some((arg, after)) => {
+                let _ = write!(out, "{arg}");
+                rest = after;
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Resolve the reply locale: guild override first, then the member's Discord
+/// language, then English.
+pub async fn resolve(
+    store: &SymbolStore,
+    guild_id: Option<GuildId>,
+    user_locale: Option<&str>,
+) -> Locale {
+    if let Some(guild_id) = guild_id {
+        match store.get_guild_settings(guild_id.get()).await {
+            Ok(settings) => {
+                if let Some(locale) = settings.locale.as_deref().and_then(Locale::from_tag) {
+                    return locale;
+                }
+            }
+            Err(e) => warn!(error = ?e, %guild_id, "failed to load guild locale"),
+        }
+    }
+
+    user_locale.and_then(Locale::from_tag).unwrap_or_default()
+}
+
+/// Locale for a command invocation, resolved once and cached in the
+/// invocation cache so repeated `t!` calls don't go back to Redis.
+pub async fn locale(ctx: Context<'_>) -> Locale {
+    if let Some(cached) = invocation::get(ctx, |c| c.locale).await {
+        return cached;
+    }
+
+    let locale = resolve(&ctx.data().symbol_store, ctx.guild_id(), ctx.locale()).await;
+    debug!(locale = locale.code(), "resolved invocation locale");
+    invocation::store(ctx, |c| c.locale = Some(locale)).await;
+    locale
+}
+
+/// Anything `t!` can pull a locale out of.
+pub trait Localize {
+    fn locale(&self) -> impl Future<Output = Locale> + Send;
+}
+
+impl Localize for Locale {
+    async fn locale(&self) -> Locale {
+        *self
+    }
+}
+
+impl Localize for Context<'_> {
+    async fn locale(&self) -> Locale {
+        locale(*self).await
+    }
+}
+
+/// Translate a message for a command context or an already-resolved [`Locale`].
+///
+/// ```ignore
+/// ctx.say(t!(ctx, MessageKey::NowWatching, added.join(", "))).await?;
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($ctx:expr, $key:expr $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::tr(
+            $crate::i18n::Localize::locale(&$ctx).await,
+            $key,
+            &[$(&$arg as &(dyn ::std::fmt::Display + Sync)),*],
+        )
+    };
+}
@@ -0,0 +1,306 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::take,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serenity::all::{ChannelId, GuildId, Http, Mentionable, MessageId};
+use serenity::futures::StreamExt;
+use stock::scan::{ScanFrame, ScanOutcome, is_new_spike, is_repeat, scan_frame};
+use stock::strategy::Strategy;
+use stock::{
+    ChartRenderer, GuildSettings, PriceSource, Scope, SymbolStore,
+    calendar::{self, session_date},
+};
+
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    batch::{ChannelSink, FallbackSink, MessageBatcher},
+    config::Config,
+    daily::{self, Target, record_posted},
+    i18n::{self, MessageKey},
+    report,
+    style::SignalStyle,
+    t,
+};
+
+/// Scan every guild that turned the intraday scan on and post new
+/// crossovers to its intraday channel. Runs every quarter hour and does
+/// nothing outside market hours; each guild is scanned only when one of its
+/// bars has just closed at `now`, so every closed bar is seen once.
+#[instrument(
+    name = "run_intraday",
+    skip(http, price_client, renderer, symbol_store, config)
+)]
+pub async fn run_intraday(
+    http: Arc<Http>,
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbol_store: Arc<SymbolStore>,
+    config: Config,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if !calendar::in_market_hours(now) {
+        debug!("outside market hours, skipping intraday run");
+        return Ok(());
+    }
+
+    for guild_id in symbol_store.list_guilds().await? {
+        let settings = match symbol_store.get_guild_settings(guild_id).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!(guild_id, error = ?e, "failed to load guild settings");
+                continue;
+            }
+        };
+        let Some(channel) = settings.intraday.target() else {
+            continue;
+        };
+        if !calendar::closes_bar(now, settings.intraday.timeframe()) {
+            continue;
+        }
+
+        if let Err(e) = run_guild(
+            http.clone(),
+            GuildId::new(guild_id),
+            ChannelId::new(channel),
+            &settings,
+            price_client.clone(),
+            renderer.clone(),
+            &symbol_store,
+            &config,
+            now,
+        )
+        .await
+        {
+            warn!(guild_id, error = ?e, "intraday run failed for guild");
+        }
+    }
+    Ok(())
+}
+
+/// One guild's intraday scan. Hits already posted by an earlier run, and
+/// hits on a bar from before today's session, are left out. A hit that
+/// reverses a recent intraday post links back to it, and that post is
+/// marked superseded, as the daily scan does. With volume spike alerts on,
+/// a symbol without a crossover whose last closed bar is a new spike is
+/// posted too.
+#[instrument(
+    name = "run_intraday_guild",
+    skip(http, settings, price_client, renderer, symbol_store, config),
+    fields(%guild_id, channel_id = %channel, timeframe = settings.intraday.timeframe().as_str())
+)]
+#[allow(clippy::too_many_arguments)]
+async fn run_guild(
+    http: Arc<Http>,
+    guild_id: GuildId,
+    channel: ChannelId,
+    settings: &GuildSettings,
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbol_store: &SymbolStore,
+    config: &Config,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let scope = Scope::Guild(guild_id.get());
+    let timeframe = settings.intraday.timeframe();
+    let symbols = daily::unmuted(symbol_store, scope, symbol_store.list(scope).await?).await;
+    if symbols.is_empty() {
+        debug!("empty watchlist");
+        return Ok(());
+    }
+    let meta = symbol_store.list_meta(scope).await?;
+    let previous = symbol_store.last_readings_at(scope, timeframe).await?;
+    let posts = symbol_store
+        .posted_at(scope, timeframe)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "failed to load posted signals");
+            HashMap::new()
+        });
+
+    let locale = i18n::resolve(symbol_store, Some(guild_id), None).await;
+    let style = SignalStyle::for_guild(symbol_store, &config.signal_colors, Some(guild_id)).await;
+    let strategy = symbol_store
+        .get_strategy(guild_id.get())
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "failed to load strategy, using the default");
+            Strategy::default()
+        });
+
+    let today = session_date(now);
+    let spikes = settings.volume_spikes();
+    let mut results = scan_frame(
+        price_client,
+        renderer,
+        symbols.clone(),
+        ScanFrame::intraday(timeframe, settings.intraday.charts)
+            .min_chart_bars(config.min_chart_bars),
+        strategy,
+        config.signal_band_pct,
+    );
+
+    let mut processed: usize = 0;
+    let mut failed: Vec<String> = Vec::new();
+    let mut readings = Vec::with_capacity(symbols.len());
+    let mut messages = Vec::new();
+    let mut sessions = HashMap::new();
+    let mut reversals = HashMap::new();
+
+    while let Some((symbol, res)) = results.next().await {
+        processed += 1;
+        match res {
+            Ok(ScanOutcome {
+                reading,
+                hit,
+                volume,
+            }) => {
+                readings.extend(reading.map(|r| (symbol.clone(), r)));
+                let Some(hit) = hit else {
+                    if let (Some(threshold), Some(volume)) = (spikes, volume)
+                        && is_new_spike(
+                            previous.get(&symbol.to_uppercase()),
+                            &volume,
+                            threshold,
+                            today,
+                        )
+                        && report::quiet_until(&meta, &symbol).is_none()
+                    {
+                        info!(%symbol, ratio = volume.ratio, "volume spike");
+                        let embed = report::volume_spike_message(
+                            locale,
+                            &symbol,
+                            &volume,
+                            settings.timezone(),
+                        );
+                        messages.push((symbol, embed, None));
+                    }
+                    continue;
+                };
+                if session_date(hit.timestamp) != today {
+                    debug!(%symbol, bar = %hit.timestamp, "hit is on an earlier session");
+                    continue;
+                }
+                if is_repeat(previous.get(&symbol.to_uppercase()), &hit) {
+                    debug!(%symbol, signal = ?hit.signal, "hit already posted");
+                    continue;
+                }
+                let quiet = report::quiet_until(&meta, &symbol);
+                let session = session_date(hit.timestamp);
+                let reversed = posts
+                    .get(&symbol.to_uppercase())
+                    .filter(|post| {
+                        post.reversed_by(hit.signal, session, config.supersede_days, timeframe)
+                    })
+                    .copied();
+                sessions.insert(symbol.clone(), (hit.signal, session));
+                let (mut embed, attachment) =
+                    report::hit_message(locale, hit, quiet, &style, settings.timezone());
+                if let Some(post) = reversed {
+                    let link = MessageId::new(post.message_id)
+                        .link(ChannelId::new(post.channel_id), Some(guild_id));
+                    let (name, value, inline) = report::reversal_field(locale, &post, &link);
+                    embed = embed.field(name, value, inline);
+                    reversals.insert(symbol.clone(), post);
+                }
+                messages.push((symbol, embed, attachment));
+            }
+            Err(e) => {
+                warn!(%symbol, error = ?e, processed, "scan failed");
+                failed.push(symbol);
+            }
+        }
+    }
+
+    info!(
+        processed,
+        hits = messages.len(),
+        failures = failed.len(),
+        "completed intraday scan"
+    );
+
+    // saved before posting, so a failed post isn't retried every bar
+    if let Err(e) = symbol_store
+        .set_last_readings_at(scope, timeframe, &readings)
+        .await
+    {
+        warn!(error = ?e, "failed to save intraday readings");
+    }
+
+    messages.sort_by(|a, b| a.0.cmp(&b.0));
+    let sink = ChannelSink::new(http.clone(), channel);
+    let mut posted = vec![sink.posted.clone()];
+    let mut batcher = MessageBatcher::new(sink).with_max_bytes(config.max_message_bytes);
+    let mut routed: BTreeMap<u64, Vec<_>> = BTreeMap::new();
+    for (symbol, embed, attachment) in messages {
+        let channels = report::routes(&meta, &symbol, &settings.tag_routes);
+        if channels.is_empty() {
+            if let Err(e) = batcher.push_hit(&symbol, embed, attachment, None).await {
+                warn!(%symbol, error = ?e, "send batch failed");
+            }
+            continue;
+        }
+        for routed_channel in channels {
+            routed.entry(routed_channel).or_default().push((
+                symbol.clone(),
+                embed.clone(),
+                attachment.clone(),
+            ));
+        }
+    }
+    let fallen_back = Arc::default();
+    for (routed_channel, messages) in routed {
+        let routed_channel = ChannelId::new(routed_channel);
+        let primary = ChannelSink::new(http.clone(), routed_channel);
+        let secondary = ChannelSink::new(http.clone(), channel);
+        posted.extend([primary.posted.clone(), secondary.posted.clone()]);
+        let sink = FallbackSink::new(
+            primary,
+            secondary,
+            t!(locale, MessageKey::RouteFallback, routed_channel.mention()),
+        )
+        .sharing(Arc::clone(&fallen_back));
+        let mut routed_batcher = MessageBatcher::new(sink).with_max_bytes(config.max_message_bytes);
+        for (symbol, embed, attachment) in messages {
+            if let Err(e) = routed_batcher
+                .push_hit(&symbol, embed, attachment, None)
+                .await
+            {
+                warn!(%symbol, channel_id = %routed_channel, error = ?e, "send routed batch failed");
+            }
+        }
+        if let Err(e) = routed_batcher.flush().await {
+            warn!(channel_id = %routed_channel, error = ?e, "send routed batch failed");
+        }
+    }
+    // quiet when there's nothing new, and failures are only logged: the
+    // run repeats at the next bar
+    batcher
+        .finish(t!(locale, MessageKey::NoSignalsFound), false, None)
+        .await?;
+
+    let posted: Vec<_> = posted
+        .iter()
+        .flat_map(|hits| take(&mut *hits.lock().unwrap()))
+        .collect();
+    record_posted(
+        &http,
+        symbol_store,
+        Target {
+            guild_id,
+            channel,
+            resumed: false,
+        },
+        timeframe,
+        locale,
+        &posted,
+        &sessions,
+        &reversals,
+    )
+    .await;
+    Ok(())
+}
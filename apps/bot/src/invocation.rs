@@ -0,0 +1,227 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use chrono_tz::Tz;
+use stock::{
+    Scope, UserPrefs,
+    alias::{self, Resolved},
+    assets,
+    calendar::DEFAULT_TIMEZONE,
+    strategy::Strategy,
+};
+use tracing::{debug, warn};
+
+use crate::{
+    Context,
+    i18n::{Locale, MessageKey},
+    t,
+};
+
+/// Lookups memoized for the lifetime of one command invocation, stored in
+/// poise's invocation data so each is resolved at most once per command.
+#[derive(Debug, Default)]
+pub struct InvocationCache {
+    pub locale: Option<Locale>,
+    pub prefs: Option<UserPrefs>,
+    pub timezone: Option<Tz>,
+    pub strategy: Option<Strategy>,
+    pub aliases: Option<HashMap<String, String>>,
+}
+
+/// Read a cached value, if it has been resolved already.
+pub async fn get<T>(
+    ctx: Context<'_>,
+    read: impl FnOnce(&InvocationCache) -> Option<T>,
+) -> Option<T> {
+    let cache = ctx.invocation_data::<InvocationCache>().await?;
+    read(&cache)
+}
+
+/// Store a resolved value, creating the cache on first use.
+pub async fn store(ctx: Context<'_>, write: impl FnOnce(&mut InvocationCache)) {
+    if let Some(mut cache) = ctx.invocation_data::<InvocationCache>().await {
+        write(&mut cache);
+        return;
+    }
+
+    let mut cache = InvocationCache::default();
+    write(&mut cache);
+    ctx.set_invocation_data(cache).await;
+}
+
+/// The invoking user's preferences. Falls back to defaults if Redis is unavailable.
+pub async fn user_prefs(ctx: Context<'_>) -> UserPrefs {
+    if let Some(prefs) = get(ctx, |c| c.prefs.clone()).await {
+        return prefs;
+    }
+
+    let prefs = match ctx
+        .data()
+        .symbol_store
+        .get_user_pref(ctx.author().id.get())
+        .await
+    {
+        Ok(prefs) => prefs,
+        Err(e) => {
+            warn!(error = ?e, "failed to load user prefs");
+            UserPrefs::default()
+        }
+    };
+    debug!(?prefs, "resolved user prefs");
+
+    store(ctx, |c| c.prefs = Some(prefs.clone())).await;
+    prefs
+}
+
+/// Whether replies to the invoking user should be ephemeral.
+pub async fn ephemeral(ctx: Context<'_>) -> bool {
+    user_prefs(ctx).await.ephemeral_replies
+}
+
+/// Zone the server shows times in, or the default in DMs. Falls back to the
+/// default if Redis is unavailable.
+pub async fn timezone(ctx: Context<'_>) -> Tz {
+    if let Some(tz) = get(ctx, |c| c.timezone).await {
+        return tz;
+    }
+
+    let tz = match ctx.guild_id() {
+        Some(guild_id) => match ctx
+            .data()
+            .symbol_store
+            .get_guild_settings(guild_id.get())
+            .await
+        {
+            Ok(settings) => settings.timezone(),
+            Err(e) => {
+                warn!(error = ?e, "failed to load guild timezone");
+                DEFAULT_TIMEZONE
+            }
+        },
+        None => DEFAULT_TIMEZONE,
+    };
+    debug!(%tz, "resolved timezone");
+
+    store(ctx, |c| c.timezone = Some(tz)).await;
+    tz
+}
+
+/// The server's signal strategy, or the default in DMs. Falls back to the
+/// default if Redis is unavailable.
+pub async fn strategy(ctx: Context<'_>) -> Strategy {
+    if let Some(strategy) = get(ctx, |c| c.strategy).await {
+        return strategy;
+    }
+
+    let strategy = match ctx.guild_id() {
+        Some(guild_id) => ctx
+            .data()
+            .symbol_store
+            .get_strategy(guild_id.get())
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = ?e, "failed to load guild strategy");
+                Strategy::default()
+            }),
+        None => Strategy::default(),
+    };
+    debug!(%strategy, "resolved strategy");
+
+    store(ctx, |c| c.strategy = Some(strategy)).await;
+    strategy
+}
+
+/// The server's custom symbol aliases, or none in DMs. Falls back to none
+/// if Redis is unavailable, leaving the built-in ones.
+pub async fn aliases(ctx: Context<'_>) -> HashMap<String, String> {
+    if let Some(aliases) = get(ctx, |c| c.aliases.clone()).await {
+        return aliases;
+    }
+
+    let aliases = match ctx.guild_id() {
+        Some(guild_id) => ctx
+            .data()
+            .symbol_store
+            .list_aliases(guild_id.get())
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = ?e, "failed to load guild aliases");
+                HashMap::new()
+            }),
+        None => HashMap::new(),
+    };
+    debug!(count = aliases.len(), "resolved aliases");
+
+    store(ctx, |c| c.aliases = Some(aliases.clone())).await;
+    aliases
+}
+
+/// The ticker `input` stands for in this server. See [`alias::resolve`].
+pub async fn resolve_symbol(ctx: Context<'_>, input: &str) -> Resolved {
+    resolve_symbols(ctx, &[input]).await.remove(0)
+}
+
+/// [`resolve_symbol`] for each of `inputs`, in order.
+pub async fn resolve_symbols(ctx: Context<'_>, inputs: &[&str]) -> Vec<Resolved> {
+    let aliases = aliases(ctx).await;
+    let in_use = tickers_in_use(ctx, inputs).await;
+    inputs
+        .iter()
+        .map(|input| {
+            let resolved = alias::resolve(input, &aliases, |t| in_use.contains(t));
+            if let Some(name) = &resolved.via_alias {
+                debug!(%name, symbol = %resolved.symbol, "resolved alias");
+            }
+            resolved
+        })
+        .collect()
+}
+
+/// Those of `inputs`, uppercased, that are tickers in use here: watched in
+/// this scope or on the asset list. Only inputs a built-in alias would
+/// take are looked up, so a plain ticker costs nothing.
+async fn tickers_in_use(ctx: Context<'_>, inputs: &[&str]) -> HashSet<String> {
+    let candidates: Vec<String> = inputs
+        .iter()
+        .filter(|input| alias::builtin(input).is_some())
+        .map(|input| input.trim().to_uppercase())
+        .collect();
+    if candidates.is_empty() {
+        return HashSet::new();
+    }
+
+    let data = ctx.data();
+    let watched = data
+        .symbol_store
+        .list(scope(ctx))
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "failed to load watchlist for aliases");
+            Vec::new()
+        });
+    let listed = data.price_client.list_assets().await.unwrap_or_else(|e| {
+        warn!(error = ?e, "failed to load asset list for aliases");
+        Arc::from(Vec::new())
+    });
+    candidates
+        .into_iter()
+        .filter(|c| watched.contains(c) || assets::is_listed(&listed, c))
+        .collect()
+}
+
+/// The line telling the user how their input was read, when it went
+/// through an alias.
+pub async fn alias_note(ctx: Context<'_>, resolved: &Resolved) -> Option<String> {
+    let name = resolved.via_alias.as_ref()?;
+    Some(t!(ctx, MessageKey::AliasInterpreted, name, resolved.symbol))
+}
+
+/// Watchlist scope for the invocation: the server, or the user in DMs.
+pub fn scope(ctx: Context<'_>) -> Scope {
+    match ctx.guild_id() {
+        Some(guild_id) => Scope::Guild(guild_id.get()),
+        None => Scope::User(ctx.author().id.get()),
+    }
+}
@@ -1,13 +1,46 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
-use stock::{PriceClient, SymbolStore};
+use stock::{
+    ChartRenderer, PriceSource, SymbolStore, earnings::EarningsSource, report::RunArchive,
+};
 
+use crate::{config::Config, log_filter::LogFilter};
+
+pub mod alerts;
+pub mod analysis;
+pub mod api;
+pub mod batch;
+pub mod cashtag;
 pub mod command;
 pub mod config;
+pub mod daily;
+pub mod digest;
+pub mod discord_text;
+pub mod fmt;
+pub mod i18n;
+pub mod intraday;
+pub mod invocation;
+pub mod log_filter;
+pub mod notify;
+pub mod ohlcv;
+pub mod onboarding;
+pub mod registration;
+pub mod report;
+pub mod spotlight;
+pub mod style;
 
 pub struct Data {
     pub symbol_store: Arc<SymbolStore>,
-    pub price_client: Arc<PriceClient>,
+    pub price_client: Arc<dyn PriceSource>,
+    pub renderer: Arc<ChartRenderer>,
+    pub run_archive: Arc<RunArchive>,
+    pub config: Config,
+    pub signal_cache: cashtag::SignalCache,
+    pub started_at: Instant,
+    pub webhook: Option<notify::Webhook>,
+    pub log_filter: Arc<LogFilter>,
+    /// Earnings calendar, None when no provider is configured.
+    pub earnings: Option<Arc<dyn EarningsSource>>,
 }
 
 pub type Error = anyhow::Error;
@@ -0,0 +1,63 @@
+//! Changing what gets logged while the bot runs.
+//!
+//! The subscriber's [`EnvFilter`] sits behind a reload layer installed at
+//! startup, so an owner can turn on `debug` for one module while chasing a
+//! problem and turn it off again without a redeploy. Filters use the same
+//! syntax as `RUST_LOG`.
+
+use std::sync::Mutex;
+
+use anyhow::{Result, anyhow, bail};
+use tracing::{info, warn};
+use tracing_subscriber::{EnvFilter, Registry, reload};
+
+/// Parse `directives` as a filter, strictly: anything `RUST_LOG` would skip
+/// as malformed is an error here instead.
+pub fn parse(directives: &str) -> Result<EnvFilter> {
+    let directives = directives.trim();
+    if directives.is_empty() {
+        bail!("the filter is empty");
+    }
+    EnvFilter::builder()
+        .parse(directives)
+        .map_err(|e| anyhow!("{directives:?} isn't a valid filter: {e}"))
+}
+
+/// The live log filter and the directives it was built from.
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    current: Mutex<String>,
+}
+
+impl LogFilter {
+    /// Control the filter behind `handle`, which was built from `current`.
+    pub fn new(handle: reload::Handle<EnvFilter, Registry>, current: impl Into<String>) -> Self {
+        Self {
+            handle,
+            current: Mutex::new(current.into()),
+        }
+    }
+
+    /// Directives the filter was last set to.
+    pub fn current(&self) -> String {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// Swap in `directives`, returning the ones they replaced. An invalid
+    /// filter is rejected before anything changes, and a failed swap puts
+    /// the previous filter back.
+    pub fn set(&self, directives: &str) -> Result<String> {
+        let filter = parse(directives)?;
+        let mut current = self.current.lock().unwrap();
+        if let Err(e) = self.handle.reload(filter) {
+            warn!(error = %e, "log filter swap failed, restoring the previous one");
+            if let Ok(previous) = parse(&current) {
+                let _ = self.handle.reload(previous);
+            }
+            bail!("couldn't swap the log filter: {e}");
+        }
+        let previous = std::mem::replace(&mut *current, directives.trim().to_string());
+        info!(%previous, current = %*current, "log filter changed");
+        Ok(previous)
+    }
+}
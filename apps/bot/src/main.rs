@@ -1,52 +1,115 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
 use bot::{
-    Data,
+    Data, alerts,
+    api::{self, ApiState},
+    cashtag,
     command::{self, stock::stock_command},
     config::Config,
+    daily,
+    fmt::TimeStyle,
+    intraday,
+    log_filter::{self, LogFilter},
+    notify::Webhook,
+    onboarding, registration,
 };
 use chrono_tz::America::New_York;
 use poise::{Framework, FrameworkOptions};
 use serenity::all::{ActivityData, ClientBuilder, FullEvent, GatewayIntents, Interaction};
-use stock::{PriceClient, SymbolStore};
+use stock::{ChartRenderer, PriceSource, Scope, SymbolStore, report::RunArchive};
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tracing::{debug, error, info, instrument, warn};
 use tracing_futures::Instrument;
-use tracing_subscriber::{EnvFilter, fmt};
-
-mod daily;
+use tracing_subscriber::{EnvFilter, fmt, prelude::*, reload};
 
 #[tokio::main]
 #[instrument(name = "main", skip_all)]
 async fn main() -> Result<()> {
+    let started_at = Instant::now();
     dotenvy::dotenv().ok();
 
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-
-    fmt()
-        .with_env_filter(filter)
-        .with_target(true)
-        .with_line_number(true)
-        .compact()
+    let directives = std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|d| log_filter::parse(d).is_ok())
+        .unwrap_or_else(|| "info".to_string());
+    // behind a reload layer so `/stock admin loglevel` can swap it
+    let (filter, filter_handle) = reload::Layer::new(EnvFilter::new(&directives));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            fmt::layer()
+                .with_target(true)
+                .with_line_number(true)
+                .compact(),
+        )
         .init();
+    let log_filter = Arc::new(LogFilter::new(filter_handle, directives));
 
-    let config = Config::from_env();
+    let config = Config::from_env()?;
     info!(version = %config.version, "config loaded");
 
     let symbol_store = Arc::new(SymbolStore::from_env().await?);
     info!("symbol store initialized");
 
-    let price_client = Arc::new(PriceClient::from_env()?);
+    let price_client: Arc<dyn PriceSource> = stock::failover::from_env()?;
     info!("price client initialized");
+    let earnings = stock::earnings::from_env()?;
+
+    let renderer = Arc::new(ChartRenderer::from_env()?);
+
+    let run_archive = Arc::new(RunArchive::from_env(Arc::clone(&symbol_store))?);
+
+    let webhook = Webhook::from_config(&config)?;
+
+    if let Some(token) = &config.api_token {
+        let state = ApiState {
+            watchlist: Arc::clone(&symbol_store) as _,
+            prices: Arc::clone(&price_client),
+            token: token.as_str().into(),
+        };
+        let addr = config.api_addr.clone();
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(&addr, state).await {
+                error!(error = ?e, "api server failed");
+            }
+        });
+    } else {
+        info!("API_TOKEN not set, json api disabled");
+    }
 
-    let intents = GatewayIntents::non_privileged();
+    let mut intents = GatewayIntents::non_privileged();
+    if config.message_content_intent {
+        intents |= GatewayIntents::MESSAGE_CONTENT;
+        info!("message content intent enabled");
+    }
     let commands = vec![stock_command()];
 
     let framework = Framework::builder()
         .options(FrameworkOptions {
             event_handler: |serenity_ctx, event, _framework_ctx, data| {
                 Box::pin(async move {
+                    if let FullEvent::Message { new_message } = event
+                        && let Err(e) =
+                            cashtag::handle_message(serenity_ctx, data, new_message).await
+                    {
+                        warn!(error = ?e, "cashtag handler failed");
+                    }
+
+                    // is_new is only Some(true) for servers joined while running
+                    if let FullEvent::GuildCreate {
+                        guild,
+                        is_new: Some(true),
+                    } = event
+                        && let Err(e) =
+                            onboarding::handle_guild_create(serenity_ctx, data, guild).await
+                    {
+                        warn!(error = ?e, guild_id = %guild.id, "onboarding failed");
+                    }
+
                     if let FullEvent::InteractionCreate { interaction, .. } = event
                         && let Interaction::Component(component) = interaction
                     {
@@ -62,21 +125,67 @@ async fn main() -> Result<()> {
                             warn!(error = ?e, "handle_component failed");
                         }
                     }
+
+                    if let FullEvent::InteractionCreate { interaction, .. } = event
+                        && let Interaction::Modal(modal) = interaction
+                    {
+                        debug!(
+                            custom_id = %modal.data.custom_id,
+                            user_id = %modal.user.id,
+                            "modal submitted"
+                        );
+
+                        if let Err(e) =
+                            command::stock::handle_modal(serenity_ctx, data, modal).await
+                        {
+                            warn!(error = ?e, "handle_modal failed");
+                        }
+                    }
                     Ok(())
                 })
             },
+            pre_command: |ctx| {
+                Box::pin(async move {
+                    // counted off the command's path so a slow or unreachable
+                    // Redis never holds up or fails the command itself
+                    let store = Arc::clone(&ctx.data().symbol_store);
+                    let scope = bot::invocation::scope(ctx);
+                    let command = ctx.command().qualified_name.clone();
+                    let user_id = ctx.author().id.get();
+                    tokio::spawn(async move {
+                        if let Err(e) = store.incr_usage(scope, &command, user_id).await {
+                            warn!(error = ?e, %command, "usage count failed");
+                        }
+                    });
+                })
+            },
+            on_error: |error| Box::pin(command::on_error(error)),
             commands,
+            // added to the application's owners, which poise looks up itself
+            owners: config
+                .owner_ids
+                .iter()
+                .map(|&id| serenity::all::UserId::new(id))
+                .collect(),
             ..Default::default()
         })
         .setup({
             let symbol_store = Arc::clone(&symbol_store);
             let price_client = Arc::clone(&price_client);
+            let renderer = Arc::clone(&renderer);
+            let run_archive = Arc::clone(&run_archive);
             let config = config.clone();
+            let webhook = webhook.clone();
+            let earnings = earnings.clone();
 
             move |ctx, ready, framework| {
                 let symbol_store = Arc::clone(&symbol_store);
                 let price_client = Arc::clone(&price_client);
+                let renderer = Arc::clone(&renderer);
+                let run_archive = Arc::clone(&run_archive);
                 let config = config.clone();
+                let webhook = webhook.clone();
+                let earnings = earnings.clone();
 
                 Box::pin(async move {
                     info!(
@@ -85,11 +194,20 @@ async fn main() -> Result<()> {
                         "connected"
                     );
 
-                    poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                    info!("registered commands globally");
+                    registration::sync(
+                        ctx,
+                        &framework.options().commands,
+                        &config.command_scope,
+                        false,
+                    )
+                    .await?;
+
+                    let guilds: Vec<_> = ready.guilds.iter().map(|g| g.id).collect();
+                    onboarding::log_unconfigured(&symbol_store, &guilds).await;
 
                     // Status: toggle version / time
                     let ctx_clone = ctx.clone();
+                    let version = config.version.clone();
                     tokio::spawn(async move {
                         let mut show_version = true;
                         let mut tick = tokio::time::interval(Duration::from_secs(30));
@@ -98,14 +216,15 @@ async fn main() -> Result<()> {
                             tick.tick().await;
 
                             let text = if show_version {
-                                if config.version.starts_with('v') {
-                                    config.version.clone()
+                                if version.starts_with('v') {
+                                    version.clone()
                                 } else {
-                                    format!("Version - {}", config.version)
+                                    format!("Version - {}", version)
                                 }
                             } else {
-                                let now = chrono::Local::now();
-                                format!("Time - {}", now.format("%H:%M (%:z)"))
+                                let now =
+                                    bot::fmt::time(chrono::Utc::now(), New_York, TimeStyle::Plain);
+                                format!("Time - {now}")
                             };
 
                             ctx_clone.set_activity(Some(ActivityData::custom(text)));
@@ -116,6 +235,14 @@ async fn main() -> Result<()> {
                     Ok(Data {
                         symbol_store,
                         price_client,
+                        renderer,
+                        run_archive,
+                        config,
+                        signal_cache: Default::default(),
+                        started_at,
+                        webhook,
+                        log_filter,
+                        earnings,
                     })
                 })
             }
@@ -128,36 +255,221 @@ async fn main() -> Result<()> {
         .expect("Err creating client");
 
     let http = client.http.clone();
-    let channel_id: u64 = std::env::var("DISCORD_TARGET_CHANNEL_ID")?.parse()?;
-    let channel = serenity::all::ChannelId::new(channel_id);
-    info!(channel_id, "daily target channel loaded");
+    let fallback = match std::env::var("DISCORD_TARGET_CHANNEL_ID").ok() {
+        Some(raw) => {
+            let channel = serenity::all::ChannelId::new(raw.parse()?);
+            match channel.to_channel(&http).await.map(|c| c.guild()) {
+                Ok(Some(c)) => {
+                    info!(channel_id = %channel, guild_id = %c.guild_id, "fallback daily channel loaded");
+                    if let Err(e) = symbol_store
+                        .adopt_legacy_watchlist(Scope::Guild(c.guild_id.get()))
+                        .await
+                    {
+                        warn!(error = ?e, "failed to adopt legacy watchlist");
+                    }
+                    Some(daily::Target {
+                        guild_id: c.guild_id,
+                        channel,
+                        resumed: false,
+                    })
+                }
+                Ok(None) => {
+                    warn!(channel_id = %channel, "DISCORD_TARGET_CHANNEL_ID is not a guild channel");
+                    None
+                }
+                Err(e) => {
+                    warn!(channel_id = %channel, error = ?e, "failed to resolve DISCORD_TARGET_CHANNEL_ID");
+                    None
+                }
+            }
+        }
+        None => None,
+    };
 
     let sched = JobScheduler::new().await?;
     info!("job scheduler created");
 
-    let price_client_job = Arc::clone(&price_client);
-    let symbol_store_job = Arc::clone(&symbol_store);
+    if config.daily_enabled {
+        let price_client_job = Arc::clone(&price_client);
+        let renderer_job = Arc::clone(&renderer);
+        let symbol_store_job = Arc::clone(&symbol_store);
+        let config_job = config.clone();
+        let webhook_job = webhook.clone();
+        let run_archive_job = Arc::clone(&run_archive);
+        let earnings_job = earnings.clone();
+
+        sched
+            .add(Job::new_async_tz(
+                "0 30 16 * * Mon-Fri",
+                New_York,
+                move |_uuid, _l| {
+                    let http = http.clone();
+                    let price_client = Arc::clone(&price_client_job);
+                    let renderer = Arc::clone(&renderer_job);
+                    let symbol_store = Arc::clone(&symbol_store_job);
+                    let config = config_job.clone();
+                    let webhook = webhook_job.clone();
+                    let run_archive = Arc::clone(&run_archive_job);
+                    let earnings = earnings_job.clone();
+
+                    let span = tracing::info_span!("daily_job");
+                    Box::pin(
+                        async move {
+                            info!("starting daily run");
+                            if let Err(e) = daily::run_daily(
+                                http,
+                                price_client,
+                                renderer,
+                                symbol_store,
+                                config,
+                                webhook,
+                                run_archive,
+                                earnings,
+                                fallback,
+                            )
+                            .await
+                            {
+                                error!(error = ?e, "run_daily failed");
+                            } else {
+                                info!("daily run complete");
+                            }
+                        }
+                        .instrument(span),
+                    )
+                },
+            )?)
+            .await?;
+        info!("daily job registered");
+
+        if config.daily_prewarm {
+            let price_client_prewarm = Arc::clone(&price_client);
+            let symbol_store_prewarm = Arc::clone(&symbol_store);
+
+            sched
+                .add(Job::new_async_tz(
+                    "0 20 16 * * Mon-Fri",
+                    New_York,
+                    move |_uuid, _l| {
+                        let price_client = Arc::clone(&price_client_prewarm);
+                        let symbol_store = Arc::clone(&symbol_store_prewarm);
+
+                        let span = tracing::info_span!("prewarm_job");
+                        Box::pin(
+                            daily::prewarm_daily(price_client, symbol_store, fallback)
+                                .instrument(span),
+                        )
+                    },
+                )?)
+                .await?;
+            info!("prewarm job registered");
+        }
+
+        let http_spotlight = client.http.clone();
+        let price_client_spotlight = Arc::clone(&price_client);
+        let renderer_spotlight = Arc::clone(&renderer);
+        let symbol_store_spotlight = Arc::clone(&symbol_store);
+        let config_spotlight = config.clone();
+
+        sched
+            .add(Job::new_async_tz(
+                "0 0 9 * * Mon-Fri",
+                New_York,
+                move |_uuid, _l| {
+                    let http = http_spotlight.clone();
+                    let price_client = Arc::clone(&price_client_spotlight);
+                    let renderer = Arc::clone(&renderer_spotlight);
+                    let symbol_store = Arc::clone(&symbol_store_spotlight);
+                    let config = config_spotlight.clone();
+
+                    let span = tracing::info_span!("spotlight_job");
+                    Box::pin(
+                        async move {
+                            if let Err(e) = daily::run_spotlight(
+                                http,
+                                price_client,
+                                renderer,
+                                symbol_store,
+                                config,
+                                fallback,
+                            )
+                            .await
+                            {
+                                error!(error = ?e, "run_spotlight failed");
+                            }
+                        }
+                        .instrument(span),
+                    )
+                },
+            )?)
+            .await?;
+        info!("spotlight job registered");
+    } else {
+        info!("daily job disabled by DAILY_ENABLED");
+    }
+
+    let http_alerts = client.http.clone();
+    let price_client_alerts = Arc::clone(&price_client);
+    let symbol_store_alerts = Arc::clone(&symbol_store);
 
     sched
         .add(Job::new_async_tz(
-            "0 30 16 * * Mon-Fri",
+            "0 */15 9-16 * * Mon-Fri",
             New_York,
             move |_uuid, _l| {
-                let http = http.clone();
-                let channel = channel;
-                let price_client = Arc::clone(&price_client_job);
-                let symbol_store = Arc::clone(&symbol_store_job);
+                let http = http_alerts.clone();
+                let price_client = Arc::clone(&price_client_alerts);
+                let symbol_store = Arc::clone(&symbol_store_alerts);
 
-                let span = tracing::info_span!("daily_job", channel_id = %channel);
+                let span = tracing::info_span!("alerts_job");
                 Box::pin(
                     async move {
-                        info!("starting daily run");
                         if let Err(e) =
-                            daily::run_daily(http, channel, price_client, symbol_store).await
+                            alerts::run_alerts(http, price_client, symbol_store, chrono::Utc::now())
+                                .await
+                        {
+                            error!(error = ?e, "run_alerts failed");
+                        }
+                    }
+                    .instrument(span),
+                )
+            },
+        )?)
+        .await?;
+    info!("alerts job registered");
+
+    let http_intraday = client.http.clone();
+    let price_client_intraday = Arc::clone(&price_client);
+    let renderer_intraday = Arc::clone(&renderer);
+    let symbol_store_intraday = Arc::clone(&symbol_store);
+    let config_intraday = config.clone();
+
+    // every quarter hour, the shortest intraday bar; runs before 09:30 and
+    // after 16:00 return straight away
+    sched
+        .add(Job::new_async_tz(
+            "0 0,15,30,45 9-16 * * Mon-Fri",
+            New_York,
+            move |_uuid, _l| {
+                let http = http_intraday.clone();
+                let price_client = Arc::clone(&price_client_intraday);
+                let renderer = Arc::clone(&renderer_intraday);
+                let symbol_store = Arc::clone(&symbol_store_intraday);
+                let config = config_intraday.clone();
+
+                let span = tracing::info_span!("intraday_job");
+                Box::pin(
+                    async move {
+                        if let Err(e) = intraday::run_intraday(
+                            http,
+                            price_client,
+                            renderer,
+                            symbol_store,
+                            config,
+                            chrono::Utc::now(),
+                        )
+                        .await
                         {
-                            error!(error = ?e, "run_daily failed");
-                        } else {
-                            info!("daily run complete");
+                            error!(error = ?e, "run_intraday failed");
                         }
                     }
                     .instrument(span),
@@ -165,7 +477,7 @@ async fn main() -> Result<()> {
             },
         )?)
         .await?;
-    info!("daily job registered");
+    info!("intraday job registered");
 
     sched.shutdown_on_ctrl_c();
     sched.start().await?;
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use stock::{indicators::cdc::Signal, scan::ScanHit};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Error,
+    batch::{Batch, BatchSink},
+    config::Config,
+};
+
+/// Header carrying `sha256=<hex HMAC of the body>`.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Attempts after the first failed delivery.
+const RETRIES: u32 = 2;
+const BACKOFF: Duration = Duration::from_secs(1);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON body POSTed for every scan hit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalEvent {
+    pub symbol: String,
+    pub signal: String,
+    pub price: f64,
+    pub timestamp: DateTime<Utc>,
+    pub timeframe: String,
+    pub chart_url: Option<String>,
+}
+
+impl SignalEvent {
+    pub fn from_hit(hit: &ScanHit) -> Self {
+        Self {
+            symbol: hit.symbol.to_uppercase(),
+            signal: signal_name(hit.signal).to_string(),
+            price: hit.close,
+            timestamp: hit.timestamp,
+            timeframe: hit.timeframe.as_str().to_string(),
+            chart_url: None,
+        }
+    }
+}
+
+fn signal_name(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Buy => "buy",
+        Signal::Sell => "sell",
+        Signal::BullishZone => "bullish_zone",
+        Signal::BearishZone => "bearish_zone",
+        Signal::None => "none",
+    }
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `body` keyed with `secret`.
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Outbound webhook that receives every scan hit.
+#[derive(Clone)]
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    secret: String,
+}
+
+impl Webhook {
+    pub fn new(url: String, secret: String) -> Result<Self, Error> {
+        let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            url,
+            secret,
+        })
+    }
+
+    /// Build from `WEBHOOK_URL` / `WEBHOOK_SECRET`. Returns None when no URL is
+    /// configured, or when the secret is missing since receivers couldn't
+    /// verify unsigned payloads.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, Error> {
+        let Some(url) = config.webhook_url.clone() else {
+            return Ok(None);
+        };
+        let Some(secret) = config.webhook_secret.clone() else {
+            warn!("WEBHOOK_URL is set without WEBHOOK_SECRET, webhook disabled");
+            return Ok(None);
+        };
+
+        info!("signal webhook enabled");
+        Self::new(url, secret).map(Some)
+    }
+
+    /// POST one event, retrying with exponential backoff.
+    #[instrument(name = "webhook_deliver", skip(self, event), fields(symbol = %event.symbol))]
+    pub async fn deliver(&self, event: &SignalEvent) -> Result<(), Error> {
+        let body = serde_json::to_vec(event)?;
+        let signature = sign(self.secret.as_bytes(), &body);
+
+        let mut attempt = 0;
+        loop {
+            let res = self
+                .client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.clone())
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+
+            match res {
+                Ok(_) => {
+                    debug!(attempt, "webhook delivered");
+                    return Ok(());
+                }
+                Err(e) if attempt < RETRIES => {
+                    let delay = BACKOFF * 2u32.pow(attempt);
+                    warn!(attempt, error = ?e, ?delay, "webhook delivery failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Deliver `events` in the background so the caller never waits on them.
+    pub fn spawn(&self, events: Vec<SignalEvent>) {
+        for event in events {
+            let webhook = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = webhook.deliver(&event).await {
+                    warn!(symbol = %event.symbol, error = ?e, "webhook delivery gave up");
+                }
+            });
+        }
+    }
+}
+
+/// Wraps another sink and, once a batch has been posted, hands its events to
+/// the webhook. Delivery runs detached, so it can neither delay nor fail the
+/// Discord message.
+pub struct WebhookSink<S> {
+    inner: S,
+    webhook: Option<Webhook>,
+}
+
+impl<S> WebhookSink<S> {
+    pub fn new(inner: S, webhook: Option<Webhook>) -> Self {
+        Self { inner, webhook }
+    }
+}
+
+impl<S: BatchSink + Sync> BatchSink for WebhookSink<S> {
+    async fn send_batch(&self, mut batch: Batch) -> Result<(), Error> {
+        let events = std::mem::take(&mut batch.events);
+        self.inner.send_batch(batch).await?;
+
+        if let Some(webhook) = &self.webhook {
+            webhook.spawn(events);
+        }
+        Ok(())
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        self.inner.send_notice(content).await
+    }
+
+    async fn acknowledge(&self) -> Result<(), Error> {
+        self.inner.acknowledge().await
+    }
+}
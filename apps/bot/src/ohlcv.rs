@@ -0,0 +1,101 @@
+//! Raw bars as text, for `/stock data`: a monospace table for an embed, or
+//! CSV for an attachment. No indicators, just what
+//! [`fetch_price`](stock::PriceSource::fetch_price) returned.
+
+use chrono_tz::Tz;
+use stock::{Bar, Timeframe, calendar};
+
+use crate::fmt;
+
+/// Rows shown when no count is given.
+pub const DEFAULT_ROWS: usize = 20;
+/// Most rows in a table. Keeps it well inside an embed description.
+pub const MAX_TABLE_ROWS: usize = 40;
+/// Most rows in a CSV attachment.
+pub const MAX_CSV_ROWS: usize = 1000;
+
+const HEADER: [&str; 6] = ["Date", "Open", "High", "Low", "Close", "Volume"];
+
+/// When the bar opened: for intraday bars the date and time in `tz`, for
+/// daily and longer ones their session's date, which is the same in every
+/// zone.
+fn date(bar: &Bar, timeframe: Timeframe, tz: Tz) -> String {
+    if timeframe.is_intraday() {
+        bar.timestamp
+            .with_timezone(&tz)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    } else {
+        calendar::session_date(bar.timestamp)
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+}
+
+/// Two decimals, or four under a dollar so penny stocks and small coins
+/// still show movement.
+fn price(value: f64) -> String {
+    if value.abs() < 1.0 {
+        format!("{value:.4}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+/// `bars` as an aligned table, oldest first, ready to go in a code block.
+/// Dates are first and left-aligned; numbers are right-aligned.
+pub fn table(bars: &[Bar], timeframe: Timeframe, tz: Tz) -> String {
+    let rows: Vec<[String; 6]> = bars
+        .iter()
+        .map(|bar| {
+            [
+                date(bar, timeframe, tz),
+                price(bar.open),
+                price(bar.high),
+                price(bar.low),
+                price(bar.close),
+                fmt::volume(bar.volume),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADER.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let line = |cells: [&str; 6]| {
+        let mut out = format!("{:<w$}", cells[0], w = widths[0]);
+        for (cell, width) in cells.iter().zip(widths).skip(1) {
+            out.push_str(&format!("  {cell:>width$}"));
+        }
+        out
+    };
+    let mut lines = vec![line(HEADER)];
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.each_ref().map(String::as_str))),
+    );
+    lines.join("\n")
+}
+
+/// `bars` as CSV, oldest first, with a header row. Values are unrounded and
+/// times are RFC 3339 in UTC, so a spreadsheet gets exactly what the API
+/// returned.
+pub fn csv(bars: &[Bar]) -> String {
+    let mut out = String::from("timestamp,open,high,low,close,volume\n");
+    for bar in bars {
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            bar.timestamp.to_rfc3339(),
+            bar.open,
+            bar.high,
+            bar.low,
+            bar.close,
+            bar.volume
+        ));
+    }
+    out
+}
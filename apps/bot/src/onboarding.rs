@@ -0,0 +1,220 @@
+//! First contact with a new server: a setup message explaining the basics,
+//! with a button that makes the channel it landed in the daily channel.
+
+use poise::serenity_prelude as serenity;
+use serenity::{ChannelId, ChannelType, GuildId, Mentionable, Permissions};
+use stock::SymbolStore;
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    Data, Error,
+    i18n::{self, MessageKey},
+    t,
+};
+
+pub const COMPONENT_PREFIX: &str = "onboard_";
+const SET_CHANNEL_PREFIX: &str = "onboard_set_channel_";
+
+/// What the bot needs in a channel to post the setup message there.
+const POST_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::EMBED_LINKS);
+
+/// A guild channel as far as picking where to post goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelInfo {
+    pub id: ChannelId,
+    pub kind: ChannelType,
+    pub position: u16,
+    /// The bot can view, send and embed in it.
+    pub writable: bool,
+}
+
+/// Where the setup message goes: the system channel if the bot can write
+/// there, otherwise the topmost text channel it can.
+pub fn pick_channel(
+    system_channel: Option<ChannelId>,
+    channels: &[ChannelInfo],
+) -> Option<ChannelId> {
+    let usable = |c: &&ChannelInfo| c.writable && c.kind == ChannelType::Text;
+
+    if let Some(system) = system_channel
+        && channels.iter().filter(usable).any(|c| c.id == system)
+    {
+        return Some(system);
+    }
+
+    channels
+        .iter()
+        .filter(usable)
+        .min_by_key(|c| (c.position, c.id))
+        .map(|c| c.id)
+}
+
+/// Custom id of the button that sets `channel` as the daily channel.
+pub fn set_channel_id(channel: ChannelId) -> String {
+    format!("{SET_CHANNEL_PREFIX}{channel}")
+}
+
+/// The channel a set-channel button is for.
+pub fn parse_set_channel(custom_id: &str) -> Option<ChannelId> {
+    custom_id
+        .strip_prefix(SET_CHANNEL_PREFIX)?
+        .parse()
+        .ok()
+        .filter(|id| *id != 0)
+        .map(ChannelId::new)
+}
+
+/// Post the setup message when the bot joins a server. Rejoins of servers
+/// that already have a daily channel are left alone.
+#[instrument(name = "onboard_guild", skip_all, fields(guild_id = %guild.id))]
+pub async fn handle_guild_create(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild: &serenity::Guild,
+) -> Result<(), Error> {
+    let settings = data.symbol_store.get_guild_settings(guild.id.get()).await?;
+    if settings.daily_channel.is_some() {
+        debug!("daily channel already configured");
+        return Ok(());
+    }
+
+    let bot_id = ctx.cache.current_user().id;
+    let bot = guild.member(ctx, bot_id).await?;
+    let channels: Vec<ChannelInfo> = guild
+        .channels
+        .values()
+        .map(|c| ChannelInfo {
+            id: c.id,
+            kind: c.kind,
+            position: c.position,
+            writable: guild
+                .user_permissions_in(c, &bot)
+                .contains(POST_PERMISSIONS),
+        })
+        .collect();
+
+    let Some(channel) = pick_channel(guild.system_channel_id, &channels) else {
+        warn!(
+            channels = channels.len(),
+            "no channel to post the setup message in"
+        );
+        return Ok(());
+    };
+
+    let locale = i18n::resolve(
+        &data.symbol_store,
+        Some(guild.id),
+        Some(&guild.preferred_locale),
+    )
+    .await;
+
+    let embed = serenity::CreateEmbed::default()
+        .title(t!(locale, MessageKey::OnboardingTitle))
+        .description(t!(locale, MessageKey::OnboardingBody));
+    let row = serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(set_channel_id(channel))
+            .label(t!(locale, MessageKey::OnboardingSetChannel))
+            .style(serenity::ButtonStyle::Primary),
+    ]);
+
+    channel
+        .send_message(
+            ctx,
+            serenity::CreateMessage::new()
+                .embed(embed)
+                .components(vec![row]),
+        )
+        .await?;
+
+    info!(%channel, "posted setup message");
+    Ok(())
+}
+
+/// Set the daily channel from the setup message's button. Only members who
+/// can manage the server may press it.
+#[instrument(
+    name = "component_onboard",
+    skip(ctx, data, interaction),
+    fields(custom_id = %interaction.data.custom_id, user_id = %interaction.user.id)
+)]
+pub async fn handle_component(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::ComponentInteraction,
+) -> Result<(), Error> {
+    let (Some(guild_id), Some(channel)) = (
+        interaction.guild_id,
+        parse_set_channel(&interaction.data.custom_id),
+    ) else {
+        debug!("ignored unrelated component interaction");
+        return Ok(());
+    };
+
+    let locale = i18n::resolve(
+        &data.symbol_store,
+        Some(guild_id),
+        Some(&interaction.locale),
+    )
+    .await;
+
+    let allowed = interaction
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.manage_guild());
+    if !allowed {
+        warn!("member without Manage Server pressed the setup button");
+        interaction
+            .create_response(
+                ctx,
+                serenity::CreateInteractionResponse::Message(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .content(t!(locale, MessageKey::OnboardingNeedsManageGuild))
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let mut settings = data.symbol_store.get_guild_settings(guild_id.get()).await?;
+    settings.daily_channel = Some(channel.get());
+    data.symbol_store
+        .set_guild_settings(guild_id.get(), &settings)
+        .await?;
+
+    info!(%guild_id, %channel, "daily channel set from setup message");
+
+    interaction
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                serenity::CreateInteractionResponseMessage::new()
+                    .content(t!(locale, MessageKey::DailyChannelSet, channel.mention()))
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Log the servers in `guilds` that have no daily channel, so operators can
+/// see which ones the daily job will skip. Returns them.
+#[instrument(name = "reconcile_daily_channels", skip_all)]
+pub async fn log_unconfigured(store: &SymbolStore, guilds: &[GuildId]) -> Vec<GuildId> {
+    let mut missing = Vec::new();
+    for &guild_id in guilds {
+        match store.get_guild_settings(guild_id.get()).await {
+            Ok(settings) if settings.daily_channel.is_none() => {
+                warn!(%guild_id, "guild has no daily channel configured");
+                missing.push(guild_id);
+            }
+            Ok(_) => {}
+            Err(e) => warn!(%guild_id, error = ?e, "failed to load guild settings"),
+        }
+    }
+    info!(unconfigured = missing.len(), "checked daily channels");
+    missing
+}
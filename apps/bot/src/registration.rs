@@ -0,0 +1,309 @@
+//! Keeping Discord's copy of the slash commands in step with the code.
+//!
+//! Globally, the registered commands are compared with the local ones and
+//! only re-registered when they differ, since a global update takes a while
+//! to reach every client. Test servers can instead get guild commands,
+//! which update at once; any global commands left over are then removed so
+//! nothing shows up twice, as are those of servers taken off the list.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{Error, anyhow, bail, ensure};
+use poise::serenity_prelude as serenity;
+use serde::Serialize;
+use serde_json::{Value, json};
+use tracing::{info, instrument};
+
+/// Where slash commands are registered, from `COMMAND_SCOPE`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CommandScope {
+    /// Everywhere the bot is, as global commands.
+    #[default]
+    Global,
+    /// Only in these servers, as guild commands.
+    Guilds(Vec<u64>),
+}
+
+/// `global`, or `guild:` and a comma-separated list of server ids. Empty
+/// is global.
+impl FromStr for CommandScope {
+    type Err = Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        if raw.is_empty() || raw.eq_ignore_ascii_case("global") {
+            return Ok(CommandScope::Global);
+        }
+        let Some(ids) = raw.strip_prefix("guild:") else {
+            bail!("expected global or guild:<id>,<id>, got {raw:?}");
+        };
+
+        let mut guilds = Vec::new();
+        for id in ids.split(',').map(str::trim) {
+            let id: u64 = id
+                .parse()
+                .map_err(|_| anyhow!("invalid guild id {id:?} in {raw:?}"))?;
+            ensure!(id != 0, "invalid guild id 0 in {raw:?}");
+            if !guilds.contains(&id) {
+                guilds.push(id);
+            }
+        }
+        Ok(CommandScope::Guilds(guilds))
+    }
+}
+
+/// One difference between the registered commands and the local ones. Paths
+/// are command and option names joined by spaces, like
+/// `stock settings timezone`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(String),
+    Removed(String),
+    /// `field` of the command or option at `path` differs.
+    Changed {
+        path: String,
+        field: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added(path) => write!(f, "+{path}"),
+            Change::Removed(path) => write!(f, "-{path}"),
+            Change::Changed { path, field } => write!(f, "~{path} {field}"),
+        }
+    }
+}
+
+/// Fields compared on each command and option. The rest are set by Discord:
+/// ids and versions.
+const FIELDS: [&str; 16] = [
+    "type",
+    "description",
+    "default_member_permissions",
+    "required",
+    "autocomplete",
+    "choices",
+    "channel_types",
+    "min_value",
+    "max_value",
+    "min_length",
+    "max_length",
+    "dm_permission",
+    "contexts",
+    "nsfw",
+    "name_localizations",
+    "description_localizations",
+];
+
+/// What differs between `registered`, the commands as Discord returns them,
+/// and `local`, the definitions about to be registered, both serialized to
+/// JSON. Commands and options are matched by name, and fields Discord fills
+/// in with defaults compare equal to leaving them out. Empty when there's
+/// nothing to re-register.
+pub fn diff(registered: &[Value], local: &[Value]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_level("", registered, local, &mut changes);
+    changes
+}
+
+fn diff_level(parent: &str, registered: &[Value], local: &[Value], changes: &mut Vec<Change>) {
+    let path = |name: &str| {
+        if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{parent} {name}")
+        }
+    };
+
+    for old in registered {
+        if !local.iter().any(|new| name(new) == name(old)) {
+            changes.push(Change::Removed(path(name(old))));
+        }
+    }
+
+    for new in local {
+        let Some(old) = registered.iter().find(|old| name(old) == name(new)) else {
+            changes.push(Change::Added(path(name(new))));
+            continue;
+        };
+        let path = path(name(new));
+        for field in FIELDS {
+            if field_value(old, field) != field_value(new, field) {
+                changes.push(Change::Changed {
+                    path: path.clone(),
+                    field: field.to_string(),
+                });
+            }
+        }
+
+        // Discord lists options in the order they were registered
+        let (old_opts, new_opts) = (options(old), options(new));
+        let kept = |opts: &[Value], other: &[Value]| -> Vec<String> {
+            opts.iter()
+                .map(|o| name(o).to_string())
+                .filter(|n| other.iter().any(|o| name(o) == n))
+                .collect()
+        };
+        if kept(old_opts, new_opts) != kept(new_opts, old_opts) {
+            changes.push(Change::Changed {
+                path: path.clone(),
+                field: "option order".to_string(),
+            });
+        }
+        diff_level(&path, old_opts, new_opts, changes);
+    }
+}
+
+fn name(def: &Value) -> &str {
+    def["name"].as_str().unwrap_or_default()
+}
+
+fn options(def: &Value) -> &[Value] {
+    def["options"].as_array().map(Vec::as_slice).unwrap_or(&[])
+}
+
+/// `field` of a command or option with Discord's defaults filled in.
+fn field_value(def: &Value, field: &str) -> Value {
+    let value = def.get(field).cloned().unwrap_or(Value::Null);
+    match (field, value) {
+        // a command without a type is a slash command
+        ("type", Value::Null) => json!(1),
+        ("required" | "autocomplete" | "nsfw", Value::Null) => json!(false),
+        // a command is usable in DMs unless it says otherwise
+        ("dm_permission", Value::Null) => json!(true),
+        ("name_localizations" | "description_localizations", Value::Null) => json!({}),
+        ("choices" | "channel_types", Value::Null) => json!([]),
+        ("choices", Value::Array(choices)) => choices
+            .iter()
+            .map(|c| json!({ "name": c["name"], "value": c["value"] }))
+            .collect(),
+        // `1` and `1.0` are the same bound
+        ("min_value" | "max_value", Value::Number(n)) => json!(n.as_f64()),
+        (_, value) => value,
+    }
+}
+
+fn to_values<T: Serialize>(items: &[T]) -> Result<Vec<Value>, Error> {
+    items
+        .iter()
+        .map(|item| Ok(serde_json::to_value(item)?))
+        .collect()
+}
+
+/// What [`sync`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// The global commands already matched; nothing was sent.
+    Unchanged,
+    /// The global commands were re-registered. Empty when forced without
+    /// any change.
+    Registered(Vec<Change>),
+    /// Registered in these servers; `cleared` names the global commands
+    /// that were removed, and `dropped` the servers no longer listed whose
+    /// commands were removed.
+    Guilds {
+        guilds: Vec<u64>,
+        cleared: Vec<String>,
+        dropped: Vec<u64>,
+    },
+}
+
+/// Register `commands` in the server `guild_id` alone, leaving the global
+/// commands and other servers as they are. Guild commands update at once,
+/// which makes this the quick way to try out a change.
+#[instrument(name = "register_guild", skip(http, commands))]
+pub async fn register_guild<U, E>(
+    http: impl AsRef<serenity::Http>,
+    commands: &[poise::Command<U, E>],
+    guild_id: u64,
+) -> Result<(), Error> {
+    ensure!(guild_id != 0, "invalid guild id 0");
+    poise::builtins::register_in_guild(http, commands, serenity::GuildId::new(guild_id)).await?;
+    info!("registered commands in guild");
+    Ok(())
+}
+
+/// Bring Discord's commands in line with `commands` for `scope`. Globally,
+/// only re-registers when something differs, unless `force` is set.
+#[instrument(name = "sync_commands", skip(http, commands), fields(?scope, force))]
+pub async fn sync<U, E>(
+    http: impl AsRef<serenity::Http>,
+    commands: &[poise::Command<U, E>],
+    scope: &CommandScope,
+    force: bool,
+) -> Result<SyncOutcome, Error> {
+    let http = http.as_ref();
+    let registered = serenity::Command::get_global_commands(http).await?;
+
+    match scope {
+        CommandScope::Global => {
+            let local = poise::builtins::create_application_commands(commands);
+            let changes = diff(&to_values(&registered)?, &to_values(&local)?);
+            if changes.is_empty() && !force {
+                info!("global commands up to date");
+                return Ok(SyncOutcome::Unchanged);
+            }
+            for change in &changes {
+                info!(%change, "command changed");
+            }
+
+            serenity::Command::set_global_commands(http, local).await?;
+            info!(changes = changes.len(), "registered commands globally");
+            Ok(SyncOutcome::Registered(changes))
+        }
+        CommandScope::Guilds(guilds) => {
+            for &guild_id in guilds {
+                poise::builtins::register_in_guild(
+                    http,
+                    commands,
+                    serenity::GuildId::new(guild_id),
+                )
+                .await?;
+                info!(guild_id, "registered commands in guild");
+            }
+
+            let cleared: Vec<String> = registered.into_iter().map(|c| c.name).collect();
+            if !cleared.is_empty() {
+                serenity::Command::set_global_commands(http, Vec::new()).await?;
+                info!(cleared = %cleared.join(", "), "cleared stale global commands");
+            }
+            let dropped = clear_unlisted_guilds(http, guilds).await?;
+            Ok(SyncOutcome::Guilds {
+                guilds: guilds.clone(),
+                cleared,
+                dropped,
+            })
+        }
+    }
+}
+
+/// Remove the commands from every server the bot is in that `guilds`
+/// doesn't list, as when one was taken off `COMMAND_SCOPE`. Returns the
+/// servers that had any.
+async fn clear_unlisted_guilds(http: &serenity::Http, guilds: &[u64]) -> Result<Vec<u64>, Error> {
+    let mut dropped = Vec::new();
+    let mut after = None;
+    loop {
+        let page = http
+            .get_guilds(after.map(serenity::GuildPagination::After), None)
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some(last.id);
+        for guild in &page {
+            if guilds.contains(&guild.id.get()) {
+                continue;
+            }
+            if guild.id.get_commands(http).await?.is_empty() {
+                continue;
+            }
+            guild.id.set_commands(http, Vec::new()).await?;
+            info!(guild_id = %guild.id, "cleared commands from unlisted guild");
+            dropped.push(guild.id.get());
+        }
+    }
+    Ok(dropped)
+}
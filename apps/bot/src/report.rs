@@ -0,0 +1,502 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serenity::all::{CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use stock::{
+    Bar, DataSource, PostedSignal, SymbolMeta, Timeframe,
+    calendar::{self, DEFAULT_TIMEZONE},
+    indicators::{
+        cdc::{self, Signal},
+        volume::{DEFAULT_LOOKBACK, VolumeSpike},
+    },
+    position, route_channels,
+    scan::ScanHit,
+};
+
+use crate::{
+    analysis, discord_text,
+    fmt::{self, TimeStyle},
+    i18n::{Locale, MessageKey, tr},
+    style::SignalStyle,
+};
+
+const MUTED_COLOR: u32 = 0x808080;
+const WARNING_COLOR: u32 = 0xFFA500;
+const VOLUME_SPIKE_COLOR: u32 = 0x9B59B6;
+/// Failed symbols named in the summary footer before the rest are counted.
+pub const MAX_LISTED_FAILURES: usize = 10;
+
+/// End of `symbol`'s quiet period if one is running now.
+pub fn quiet_until(meta: &HashMap<String, SymbolMeta>, symbol: &str) -> Option<DateTime<Utc>> {
+    meta.get(&symbol.to_uppercase())
+        .and_then(|m| m.quiet_at(Utc::now()))
+}
+
+/// Channels `symbol`'s hits are routed to by its tags; empty for the scan's
+/// own channel.
+pub fn routes(
+    meta: &HashMap<String, SymbolMeta>,
+    symbol: &str,
+    routes: &BTreeMap<String, u64>,
+) -> Vec<u64> {
+    let tags = meta
+        .get(&symbol.to_uppercase())
+        .map_or(&[][..], |m| m.tags.as_slice());
+    route_channels(tags, routes)
+}
+
+/// `+12.3% since added`, or None when no added price is known. Backfilled
+/// prices are flagged as approximate.
+pub fn since_added(locale: Locale, meta: &SymbolMeta, price: f64) -> Option<String> {
+    let change = format!("{:+.1}%", meta.change_since_added(price)?);
+    let key = if meta.added_price_approx {
+        MessageKey::SinceAddedApprox
+    } else {
+        MessageKey::SinceAdded
+    };
+    Some(tr(locale, key, &[&change]))
+}
+
+/// Footer text naming where a hit's signal bar came from. Bars other than
+/// daily ones are named by their size.
+pub fn hit_source_label(locale: Locale, hit: &ScanHit) -> String {
+    if hit.timeframe != Timeframe::Day1 {
+        return tr(
+            locale,
+            MessageKey::SourceIntraday,
+            &[&hit.timeframe.as_str()],
+        );
+    }
+    source_label(locale, hit.source)
+}
+
+/// Footer text naming where the signal bar came from.
+pub fn source_label(locale: Locale, source: DataSource) -> String {
+    match source {
+        DataSource::Bars => tr(locale, MessageKey::SourceBars, &[]),
+        DataSource::Snapshot => tr(locale, MessageKey::SourceSnapshot, &[]),
+        DataSource::Stale { last } => {
+            tr(locale, MessageKey::SourceStale, &[&last.format("%Y-%m-%d")])
+        }
+    }
+}
+
+/// Attachment name for `symbol`'s chart, tagged with the signal so a
+/// downloaded chart still says what it showed: `AAPL_BUY_chart.png`.
+/// Charts without a signal are plain `AAPL_chart.png`. Slashes in crypto
+/// pairs become dashes.
+pub fn chart_filename(symbol: &str, signal: Signal) -> String {
+    let symbol = symbol.to_uppercase().replace('/', "-");
+    let tag = match signal {
+        Signal::Buy => "BUY",
+        Signal::Sell => "SELL",
+        Signal::BullishZone => "BULLISH",
+        Signal::BearishZone => "BEARISH",
+        Signal::None => return format!("{symbol}_chart.png"),
+    };
+    format!("{symbol}_{tag}_chart.png")
+}
+
+/// `Generated 2024-03-10 14:30 EDT`, for a chart embed's footer. Written
+/// out in `tz` since footers don't render Discord timestamps.
+pub fn generated_at(locale: Locale, at: DateTime<Utc>, tz: Tz) -> String {
+    tr(
+        locale,
+        MessageKey::ChartGenerated,
+        &[&fmt::time(at, tz, TimeStyle::Plain)],
+    )
+}
+
+/// Embed and chart attachment announcing one scan hit. The footer names the
+/// data source and when the message was made, in `tz`; muted symbols are
+/// rendered grey and say until when. Hits scanned without a chart get a
+/// text-only embed and no attachment.
+pub fn hit_message(
+    locale: Locale,
+    hit: ScanHit,
+    quiet_until: Option<DateTime<Utc>>,
+    style: &SignalStyle,
+    tz: Tz,
+) -> (CreateEmbed, Option<CreateAttachment>) {
+    let filename = chart_filename(&hit.symbol, hit.signal);
+    let title = tr(
+        locale,
+        MessageKey::AnalysisTitle,
+        &[&hit.symbol.to_uppercase()],
+    );
+    let signal = tr(locale, MessageKey::for_signal(hit.signal), &[]);
+    let mut desc = tr(locale, MessageKey::CurrentSignal, &[&signal]);
+    // in the description, where Discord renders the date for each viewer
+    if let Some(until) = quiet_until {
+        let until = fmt::time(until, DEFAULT_TIMEZONE, TimeStyle::Date);
+        desc.push('\n');
+        desc.push_str(&tr(locale, MessageKey::MutedUntil, &[&until]));
+    }
+
+    let mut footer = format!(
+        "{} · {}",
+        hit_source_label(locale, &hit),
+        generated_at(locale, Utc::now(), tz)
+    );
+    if let Some(fallback) = &hit.fallback {
+        footer.push_str(" · ");
+        footer.push_str(&tr(locale, MessageKey::SourceFallback, &[fallback]));
+    }
+    if hit.chart_failed {
+        footer.push_str(" · ");
+        footer.push_str(&tr(locale, MessageKey::ChartUnavailable, &[]));
+    }
+    let mut embed = CreateEmbed::default()
+        .title(title)
+        .description(desc)
+        .footer(CreateEmbedFooter::new(footer));
+    embed = match quiet_until {
+        Some(_) => embed.color(MUTED_COLOR),
+        None => style.apply(embed, hit.signal),
+    };
+    if hit.chart.is_empty() {
+        return (embed, None);
+    }
+
+    embed = embed.image(format!("attachment://{}", filename));
+    (embed, Some(CreateAttachment::bytes(hit.chart, filename)))
+}
+
+/// Embed announcing a volume spike on `symbol`, in its own purple whatever
+/// the signal. The footer says when the message was made, in `tz`.
+pub fn volume_spike_message(
+    locale: Locale,
+    symbol: &str,
+    spike: &VolumeSpike,
+    tz: Tz,
+) -> CreateEmbed {
+    let title = tr(
+        locale,
+        MessageKey::VolumeSpikeTitle,
+        &[&symbol.to_uppercase()],
+    );
+    let desc = [
+        tr(
+            locale,
+            MessageKey::VolumeSpike,
+            &[&format!("{:.1}", spike.ratio)],
+        ),
+        tr(
+            locale,
+            MessageKey::VolumeSpikeDetail,
+            &[
+                &fmt::volume(spike.volume),
+                &DEFAULT_LOOKBACK,
+                &fmt::volume(spike.average),
+                &fmt::price(spike.close),
+            ],
+        ),
+    ]
+    .join("\n");
+    CreateEmbed::default()
+        .title(title)
+        .description(desc)
+        .color(VOLUME_SPIKE_COLOR)
+        .footer(CreateEmbedFooter::new(generated_at(locale, Utc::now(), tz)))
+}
+
+/// Field for a hit that reverses `post`, pointing back at it through `link`.
+pub fn reversal_field(locale: Locale, post: &PostedSignal, link: &str) -> (String, String, bool) {
+    let signal = tr(locale, MessageKey::for_signal(post.signal), &[]);
+    (
+        tr(locale, MessageKey::ReversalTitle, &[]),
+        tr(
+            locale,
+            MessageKey::ReversesPost,
+            &[&signal, &post.session, &link],
+        ),
+        false,
+    )
+}
+
+/// Field giving the unrealized P/L at `price` of a position entered at
+/// `entry`: `+12.3% from $150.00`. None for an entry that isn't valid.
+pub fn position_field(locale: Locale, entry: f64, price: f64) -> Option<(String, String, bool)> {
+    let pnl = position::pnl_pct(entry, price)?;
+    Some((
+        tr(locale, MessageKey::PositionTitle, &[]),
+        tr(
+            locale,
+            MessageKey::PositionValue,
+            &[&fmt::signed_pct(pnl), &fmt::price(entry)],
+        ),
+        true,
+    ))
+}
+
+/// `description` of a posted hit with a struck-through note that `signal`
+/// on `session` superseded it, linking to the new post.
+pub fn superseded(
+    locale: Locale,
+    description: Option<&str>,
+    signal: Signal,
+    session: NaiveDate,
+    link: &str,
+) -> String {
+    let signal = tr(locale, MessageKey::for_signal(signal), &[]);
+    let note = tr(
+        locale,
+        MessageKey::SupersededBy,
+        &[&signal, &session, &link],
+    );
+    match description.filter(|d| !d.is_empty()) {
+        Some(description) => format!("{description}\n{note}"),
+        None => note,
+    }
+}
+
+/// `AAA, BBB, CCC +2 more`, naming at most [`MAX_LISTED_FAILURES`] symbols.
+pub fn failed_symbols(locale: Locale, failed: &[String]) -> String {
+    let mut listed = failed[..failed.len().min(MAX_LISTED_FAILURES)].join(", ");
+    let rest = failed.len().saturating_sub(MAX_LISTED_FAILURES);
+    if rest > 0 {
+        listed.push(' ');
+        listed.push_str(&tr(locale, MessageKey::AndMore, &[&rest]));
+    }
+    listed
+}
+
+/// Hits of a scan that went out without their chart.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Chartless {
+    /// The chart couldn't be drawn.
+    pub undrawn: usize,
+    /// Discord wouldn't take the upload, see [`MessageBatcher::degraded`].
+    ///
+    /// [`MessageBatcher::degraded`]: crate::batch::MessageBatcher::degraded
+    pub rejected: usize,
+}
+
+/// Summary for a scan where some symbols couldn't be fetched, so readers
+/// know signals may be missing, where `chartless` hits went out without
+/// their chart, or where `fallback` hits were scanned on a fallback
+/// source's data. None when every symbol was scanned and charted from the
+/// first source.
+pub fn incomplete_summary(
+    locale: Locale,
+    processed: usize,
+    failed: &[String],
+    chartless: Chartless,
+    fallback: usize,
+) -> Option<CreateEmbed> {
+    run_summary(locale, processed, failed, chartless, fallback, None, &[])
+}
+
+/// "⚠️ AAPL earnings in 2 days".
+pub fn earnings_line(locale: Locale, symbol: &str, days: i64) -> String {
+    match days {
+        0 => tr(locale, MessageKey::EarningsToday, &[&symbol]),
+        1 => tr(locale, MessageKey::EarningsTomorrow, &[&symbol]),
+        _ => tr(locale, MessageKey::EarningsSoon, &[&symbol, &days]),
+    }
+}
+
+/// [`incomplete_summary`], also warning when the run is `slow`: how long it
+/// has taken so far against how long it usually takes, and of each symbol
+/// in `earnings` reporting soon, with the days until it does. None when
+/// every symbol was scanned and charted from the first source, the run
+/// isn't slow and nothing reports soon.
+pub fn run_summary(
+    locale: Locale,
+    processed: usize,
+    failed: &[String],
+    chartless: Chartless,
+    fallback: usize,
+    slow: Option<(Duration, Duration)>,
+    earnings: &[(String, i64)],
+) -> Option<CreateEmbed> {
+    if failed.is_empty()
+        && chartless == Chartless::default()
+        && fallback == 0
+        && slow.is_none()
+        && earnings.is_empty()
+    {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    if !failed.is_empty() {
+        lines.push(tr(
+            locale,
+            MessageKey::ScanIncomplete,
+            &[&failed.len(), &processed],
+        ));
+    }
+    if chartless.undrawn > 0 {
+        lines.push(tr(locale, MessageKey::ScanDegraded, &[&chartless.undrawn]));
+    }
+    if chartless.rejected > 0 {
+        lines.push(tr(
+            locale,
+            MessageKey::ScanUploadRejected,
+            &[&chartless.rejected],
+        ));
+    }
+    if fallback > 0 {
+        lines.push(tr(locale, MessageKey::ScanFallback, &[&fallback]));
+    }
+    if let Some((elapsed, usual)) = slow {
+        lines.push(tr(
+            locale,
+            MessageKey::RunSlow,
+            &[&fmt::uptime(elapsed), &fmt::uptime(usual)],
+        ));
+    }
+    lines.extend(
+        earnings
+            .iter()
+            .map(|(symbol, days)| earnings_line(locale, symbol, *days)),
+    );
+
+    let mut embed = CreateEmbed::default()
+        .description(lines.join("\n"))
+        .color(WARNING_COLOR);
+    if !failed.is_empty() {
+        let footer = tr(
+            locale,
+            MessageKey::ScanFailedSymbols,
+            &[&failed_symbols(locale, failed)],
+        );
+        embed = embed.footer(CreateEmbedFooter::new(discord_text::truncate_field(
+            &footer,
+            discord_text::FOOTER_LIMIT,
+        )));
+    }
+    Some(embed)
+}
+
+/// Columns of the `/stock report` CSV, in order.
+pub const CSV_HEADER: [&str; 12] = [
+    "symbol",
+    "close",
+    "change_pct",
+    "ema12",
+    "ema26",
+    "zone",
+    "rsi14",
+    "from_52w_high_pct",
+    "avg_volume_20d",
+    "last_signal",
+    "last_signal_date",
+    "error",
+];
+
+/// One symbol's numbers in the `/stock report` CSV, from its daily bars.
+/// Readings the history is too short for are None and left blank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvRow {
+    pub close: f64,
+    /// Change from the previous close, in percent.
+    pub change_pct: Option<f64>,
+    pub ema12: f64,
+    pub ema26: f64,
+    /// Bullish or bearish, with a crossover on the latest bar counted as
+    /// the zone it starts.
+    pub zone: Signal,
+    pub rsi: Option<f64>,
+    /// How far the close is below the 52-week high, in percent, zero or
+    /// negative.
+    pub from_high_pct: Option<f64>,
+    /// Average volume over the 20 sessions before the latest.
+    pub avg_volume: Option<f64>,
+    /// The last crossover and the session it fired on.
+    pub last_signal: Option<(Signal, NaiveDate)>,
+}
+
+impl CsvRow {
+    /// The row for daily `bars`, oldest first. None when there are none.
+    pub fn from_bars(bars: &[Bar], band_pct: f64) -> Option<Self> {
+        let report = analysis::build("", bars, band_pct)?;
+        let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+        let (signal, ema12, ema26) = cdc::calculate(&closes, band_pct);
+        let zone = match signal {
+            Signal::Buy | Signal::BullishZone => Signal::BullishZone,
+            Signal::Sell | Signal::BearishZone => Signal::BearishZone,
+            Signal::None => Signal::None,
+        };
+        let last_signal = cdc::last_crossover(&ema12, &ema26, band_pct)
+            .map(|(i, signal)| (signal, calendar::session_date(bars[i].timestamp)));
+
+        Some(Self {
+            close: report.close,
+            change_pct: report.change_pct,
+            ema12: *ema12.last()?,
+            ema26: *ema26.last()?,
+            zone,
+            rsi: report.rsi,
+            from_high_pct: report.range.map(|r| (report.close / r.high - 1.0) * 100.0),
+            avg_volume: report.volume.map(|v| v.average),
+            last_signal,
+        })
+    }
+}
+
+/// The CSV column name for a signal.
+fn csv_signal(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Buy => "buy",
+        Signal::Sell => "sell",
+        Signal::BullishZone => "bullish",
+        Signal::BearishZone => "bearish",
+        Signal::None => "none",
+    }
+}
+
+/// `results` as CSV with a [`CSV_HEADER`] row, one row per symbol in the
+/// order given. A symbol that failed keeps its row, blank but for the
+/// error. Prices are rounded to four decimals and percentages to two;
+/// fields are quoted wherever they need it.
+pub fn build_csv(results: &[(String, Result<CsvRow, String>)]) -> Vec<u8> {
+    let opt = |value: Option<f64>, decimals: usize| {
+        value.map_or(String::new(), |v| format!("{v:.decimals$}"))
+    };
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(CSV_HEADER)
+        .expect("writing to memory can't fail");
+    for (symbol, result) in results {
+        let record = match result {
+            Ok(row) => [
+                symbol.clone(),
+                format!("{:.4}", row.close),
+                opt(row.change_pct, 2),
+                format!("{:.4}", row.ema12),
+                format!("{:.4}", row.ema26),
+                csv_signal(row.zone).to_string(),
+                opt(row.rsi, 2),
+                opt(row.from_high_pct, 2),
+                opt(row.avg_volume, 0),
+                row.last_signal
+                    .map_or(String::new(), |(s, _)| csv_signal(s).to_string()),
+                row.last_signal
+                    .map_or(String::new(), |(_, date)| date.to_string()),
+                String::new(),
+            ],
+            Err(e) => {
+                let mut record: [String; 12] = Default::default();
+                record[0] = symbol.clone();
+                record[11] = e.clone();
+                record
+            }
+        };
+        writer
+            .write_record(&record)
+            .expect("writing to memory can't fail");
+    }
+    writer.into_inner().expect("writing to memory can't fail")
+}
+
+/// `report-YYYY-MM-DD.csv`, dated by `at` in `tz`.
+pub fn csv_filename(at: DateTime<Utc>, tz: Tz) -> String {
+    format!("report-{}.csv", at.with_timezone(&tz).format("%Y-%m-%d"))
+}
@@ -0,0 +1,79 @@
+//! The morning chart-of-the-day post: a two-sentence summary of the setup
+//! the ranking picked, its chart, and a reaction poll.
+
+use serenity::all::{CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use stock::{indicators::cdc::Signal, spotlight::Setup};
+
+use crate::{
+    fmt,
+    i18n::{Locale, MessageKey, tr},
+    style::SignalStyle,
+};
+
+/// Reactions the bot adds for the "would you take the trade" poll, yes then
+/// no.
+pub const POLL_REACTIONS: [char; 2] = ['📈', '📉'];
+
+/// Volume ratios below this aren't worth mentioning.
+pub const NOTABLE_VOLUME_RATIO: f64 = 1.2;
+
+/// What happened to the setup, e.g. "AAPL crossed bullish in the last
+/// session on 1.4× average volume." followed by where the EMAs now sit.
+pub fn summary(locale: Locale, setup: &Setup) -> String {
+    let widening = setup.gap_change >= 0.0;
+    let key = match setup.signal {
+        Signal::Buy => MessageKey::SpotlightCrossedBullish,
+        Signal::Sell => MessageKey::SpotlightCrossedBearish,
+        _ if setup.is_bullish() || (setup.signal == Signal::None && setup.gap_pct >= 0.0) => {
+            if widening {
+                MessageKey::SpotlightBullishStrengthening
+            } else {
+                MessageKey::SpotlightBullishFading
+            }
+        }
+        _ if widening => MessageKey::SpotlightBearishFading,
+        _ => MessageKey::SpotlightBearishStrengthening,
+    };
+
+    let volume = setup
+        .volume_ratio
+        .filter(|r| *r >= NOTABLE_VOLUME_RATIO)
+        .map(|r| tr(locale, MessageKey::SpotlightVolume, &[&format!("{r:.1}")]))
+        .unwrap_or_default();
+
+    let what = tr(locale, key, &[&setup.symbol, &volume]);
+    let gap = tr(
+        locale,
+        MessageKey::SpotlightGap,
+        &[
+            &fmt::signed_pct(setup.gap_pct),
+            &format!("{:+.2}", setup.gap_change),
+        ],
+    );
+    format!("{what} {gap}")
+}
+
+/// Embed and chart attachment for the chart of the day. The poll question
+/// goes in the footer; the caller adds [`POLL_REACTIONS`] once it's sent.
+pub fn message(
+    locale: Locale,
+    setup: &Setup,
+    chart: Vec<u8>,
+    style: &SignalStyle,
+) -> (CreateEmbed, CreateAttachment) {
+    let filename = format!("{}_spotlight.png", setup.symbol);
+    let embed = CreateEmbed::default()
+        .title(tr(locale, MessageKey::SpotlightTitle, &[&setup.symbol]))
+        .description(summary(locale, setup))
+        .image(format!("attachment://{filename}"))
+        .footer(CreateEmbedFooter::new(tr(
+            locale,
+            MessageKey::SpotlightPoll,
+            &[],
+        )));
+
+    (
+        style.apply(embed, setup.signal),
+        CreateAttachment::bytes(chart, filename),
+    )
+}
@@ -0,0 +1,225 @@
+//! How signals look: the embed color and emoji for each zone.
+//!
+//! The bot-wide colors come from the environment ([`SignalColors`]); a
+//! server can switch to a built-in preset and override the buy and sell
+//! color and emoji in its settings. Every embed and reaction that shows a
+//! signal resolves a [`SignalStyle`] and goes through it, so the mapping
+//! lives here only.
+
+use anyhow::{Error, ensure};
+use serenity::all::{CreateEmbed, GuildId, ReactionType};
+use stock::{
+    GuildSettings, SymbolStore,
+    indicators::cdc::{Signal, SignalColors},
+};
+use tracing::warn;
+
+use crate::Context;
+
+/// Longest custom emoji accepted, `<a:name:id>` with a 32-character name.
+const MAX_EMOJI_LEN: usize = 60;
+
+/// A built-in look, picked by name in the server settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum StylePreset {
+    /// The bot-wide colors, green and red.
+    #[name = "default"]
+    Default,
+    /// Blue and orange from the Okabe–Ito palette, which stay apart under
+    /// the common kinds of color blindness.
+    #[name = "colorblind"]
+    Colorblind,
+}
+
+impl StylePreset {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StylePreset::Default => "default",
+            StylePreset::Colorblind => "colorblind",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "default" => Some(StylePreset::Default),
+            "colorblind" => Some(StylePreset::Colorblind),
+            _ => None,
+        }
+    }
+}
+
+/// Colors and emoji for each signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalStyle {
+    pub colors: SignalColors,
+    /// Shown for Buy and the bullish zone.
+    pub buy_emoji: String,
+    /// Shown for Sell and the bearish zone.
+    pub sell_emoji: String,
+    pub neutral_emoji: String,
+}
+
+impl Default for SignalStyle {
+    fn default() -> Self {
+        Self::preset(StylePreset::Default, &SignalColors::default())
+    }
+}
+
+impl SignalStyle {
+    /// `preset` as it looks with `base` as the bot-wide colors.
+    pub fn preset(preset: StylePreset, base: &SignalColors) -> Self {
+        match preset {
+            StylePreset::Default => Self {
+                colors: *base,
+                buy_emoji: "🟢".into(),
+                sell_emoji: "🔴".into(),
+                neutral_emoji: "⚪".into(),
+            },
+            StylePreset::Colorblind => Self {
+                colors: SignalColors {
+                    buy: 0x0072B2,
+                    sell: 0xE69F00,
+                    bullish_zone: 0x56B4E9,
+                    bearish_zone: 0xD55E00,
+                    none: base.none,
+                },
+                buy_emoji: "🔵".into(),
+                sell_emoji: "🟠".into(),
+                neutral_emoji: "⚪".into(),
+            },
+        }
+    }
+
+    /// The style a server configured: its preset, then its own colors and
+    /// emoji on top. An unknown preset name falls back to the default.
+    pub fn resolve(base: &SignalColors, settings: &GuildSettings) -> Self {
+        let preset = match settings.style_preset.as_deref() {
+            Some(name) => StylePreset::parse(name).unwrap_or_else(|| {
+                warn!(preset = name, "unknown style preset, using default");
+                StylePreset::Default
+            }),
+            None => StylePreset::Default,
+        };
+
+        let mut style = Self::preset(preset, base);
+        if let Some(color) = settings.buy_color {
+            style.colors.buy = color;
+            style.colors.bullish_zone = color;
+        }
+        if let Some(color) = settings.sell_color {
+            style.colors.sell = color;
+            style.colors.bearish_zone = color;
+        }
+        if let Some(emoji) = &settings.buy_emoji {
+            style.buy_emoji = emoji.clone();
+        }
+        if let Some(emoji) = &settings.sell_emoji {
+            style.sell_emoji = emoji.clone();
+        }
+        style
+    }
+
+    /// The style for `guild_id`, or the bot-wide one outside a server.
+    /// Falls back to it too if the settings can't be read.
+    pub async fn for_guild(
+        store: &SymbolStore,
+        base: &SignalColors,
+        guild_id: Option<GuildId>,
+    ) -> Self {
+        let Some(guild_id) = guild_id else {
+            return Self::preset(StylePreset::Default, base);
+        };
+        match store.get_guild_settings(guild_id.get()).await {
+            Ok(settings) => Self::resolve(base, &settings),
+            Err(e) => {
+                warn!(error = ?e, %guild_id, "failed to load guild style");
+                Self::preset(StylePreset::Default, base)
+            }
+        }
+    }
+
+    pub fn color(&self, signal: Signal) -> u32 {
+        signal.color(&self.colors)
+    }
+
+    pub fn emoji(&self, signal: Signal) -> &str {
+        match signal {
+            Signal::Buy | Signal::BullishZone => &self.buy_emoji,
+            Signal::Sell | Signal::BearishZone => &self.sell_emoji,
+            Signal::None => &self.neutral_emoji,
+        }
+    }
+
+    /// Reaction for `signal`, for either a unicode or a custom emoji.
+    pub fn reaction(&self, signal: Signal) -> ReactionType {
+        let emoji = self.emoji(signal);
+        ReactionType::try_from(emoji).unwrap_or_else(|_| ReactionType::Unicode(emoji.to_string()))
+    }
+
+    /// `embed` colored for `signal`.
+    pub fn apply(&self, embed: CreateEmbed, signal: Signal) -> CreateEmbed {
+        embed.color(self.color(signal))
+    }
+}
+
+/// One-line summary of a server's style settings: the preset, then any
+/// overrides, e.g. `colorblind · buy #00FF00 · sell 🔻`.
+pub fn describe(settings: &GuildSettings) -> String {
+    let mut parts = vec![
+        settings
+            .style_preset
+            .clone()
+            .unwrap_or_else(|| StylePreset::Default.as_str().to_string()),
+    ];
+    if let Some(color) = settings.buy_color {
+        parts.push(format!("buy #{color:06X}"));
+    }
+    if let Some(emoji) = &settings.buy_emoji {
+        parts.push(format!("buy {emoji}"));
+    }
+    if let Some(color) = settings.sell_color {
+        parts.push(format!("sell #{color:06X}"));
+    }
+    if let Some(emoji) = &settings.sell_emoji {
+        parts.push(format!("sell {emoji}"));
+    }
+    parts.join(" · ")
+}
+
+/// Style for a command invocation's server.
+pub async fn for_invocation(ctx: Context<'_>) -> SignalStyle {
+    let data = ctx.data();
+    SignalStyle::for_guild(
+        &data.symbol_store,
+        &data.config.signal_colors,
+        ctx.guild_id(),
+    )
+    .await
+}
+
+/// Check an emoji typed into the settings: a single unicode emoji or a
+/// custom one like `<:name:id>`.
+pub fn parse_emoji(raw: &str) -> Result<String, Error> {
+    let emoji = raw.trim();
+    ensure!(!emoji.is_empty(), "emoji can't be empty");
+    ensure!(
+        !emoji.chars().any(char::is_whitespace),
+        "expected a single emoji, got {raw:?}"
+    );
+    ensure!(
+        emoji.chars().count() <= MAX_EMOJI_LEN,
+        "expected a single emoji, got {} characters",
+        emoji.chars().count()
+    );
+    // unicode emoji are never plain ASCII; custom ones must parse as one
+    let valid = if emoji.starts_with('<') {
+        ReactionType::try_from(emoji).is_ok()
+    } else {
+        !emoji.is_ascii()
+    };
+    ensure!(
+        valid,
+        "expected an emoji like 🟢 or <:name:id>, got {raw:?}"
+    );
+    Ok(emoji.to_string())
+}
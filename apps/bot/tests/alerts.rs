@@ -0,0 +1,182 @@
+mod common;
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use bot::{
+    alerts::{fired_message, run_alerts},
+    i18n::Locale,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use futures::future::BoxFuture;
+use serde_json::json;
+use stock::{
+    Bar, PriceSource, Session, Snapshot, Timeframe, Trade, UserPrefs,
+    alert::{Alert, Direction},
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+use common::{discord_http, message_json, redis_store};
+
+const USER: u64 = 7;
+const DM_CHANNEL: u64 = 20;
+
+/// Every symbol trades at the same price.
+struct Trading(f64);
+
+impl PriceSource for Trading {
+    fn fetch_price<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _duration: Duration,
+        _timeframe: Timeframe,
+        _limit: usize,
+        _bypass_cache: bool,
+        _session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async { Ok(vec![]) })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        let snapshot = Snapshot {
+            latest_trade: Some(Trade {
+                timestamp: Utc::now(),
+                price: self.0,
+            }),
+            latest_quote: None,
+            daily_bar: None,
+            prev_daily_bar: None,
+        };
+        Box::pin(async move {
+            Ok(symbols
+                .iter()
+                .map(|s| (s.clone(), snapshot.clone()))
+                .collect())
+        })
+    }
+}
+
+fn new_york(hour: u32, min: u32) -> DateTime<Utc> {
+    New_York
+        .with_ymd_and_hms(2026, 10, 16, hour, min, 0)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// Discord answering DMs to [`USER`] on [`DM_CHANNEL`].
+async fn discord() -> MockServer {
+    let discord = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v10/users/@me/channels"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": DM_CHANNEL.to_string(),
+            "type": 1,
+            "last_message_id": null,
+            "recipients": [{
+                "id": USER.to_string(),
+                "username": "member",
+                "discriminator": "0000",
+                "global_name": null,
+                "avatar": null
+            }]
+        })))
+        .mount(&discord)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(format!("/api/v10/channels/{DM_CHANNEL}/messages")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_json(DM_CHANNEL)))
+        .mount(&discord)
+        .await;
+    discord
+}
+
+/// Contents of the DMs Discord was asked to send.
+async fn dms(discord: &MockServer) -> Vec<String> {
+    let dm_path = format!("/api/v10/channels/{DM_CHANNEL}/messages");
+    discord
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter(|r| r.url.path() == dm_path)
+        .map(|r| {
+            let body: serde_json::Value = r.body_json().unwrap();
+            body["content"].as_str().unwrap_or_default().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn fired_dms_are_written_in_the_given_language() {
+    let alert = Alert::new("AAPL", Direction::Above, 100.0, Utc::now());
+
+    let en = fired_message(Locale::En, &alert, 101.5);
+    let th = fired_message(Locale::Th, &alert, 101.5);
+    assert!(en.contains("AAPL") && en.contains("101.50"), "{en}");
+    assert!(th.contains("AAPL") && th.contains("101.50"), "{th}");
+    assert_ne!(en, th);
+}
+
+#[tokio::test]
+async fn alerts_wait_for_the_regular_session() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let store = Arc::new(store);
+    let alert = Alert::new("AAPL", Direction::Above, 100.0, Utc::now());
+    store.save_alert(USER, &alert).await.unwrap();
+    let discord = discord().await;
+
+    for early in [new_york(9, 0), new_york(9, 15), new_york(16, 0)] {
+        run_alerts(
+            discord_http(&discord),
+            Arc::new(Trading(120.0)),
+            store.clone(),
+            early,
+        )
+        .await
+        .unwrap();
+    }
+    assert!(dms(&discord).await.is_empty());
+    assert_eq!(store.list_alerts(USER).await.unwrap(), vec![alert]);
+}
+
+#[tokio::test]
+async fn a_fired_alert_dms_its_owner_once_in_their_language() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let store = Arc::new(store);
+    let alert = Alert::new("AAPL", Direction::Above, 100.0, Utc::now());
+    store.save_alert(USER, &alert).await.unwrap();
+    let prefs = UserPrefs {
+        locale: Some("th".into()),
+        ..Default::default()
+    };
+    store.set_user_pref(USER, &prefs).await.unwrap();
+    let discord = discord().await;
+
+    for now in [new_york(10, 0), new_york(10, 15)] {
+        run_alerts(
+            discord_http(&discord),
+            Arc::new(Trading(120.0)),
+            store.clone(),
+            now,
+        )
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(
+        dms(&discord).await,
+        vec![fired_message(Locale::Th, &alert, 120.0)]
+    );
+    assert!(store.list_alerts(USER).await.unwrap()[0].is_dormant());
+}
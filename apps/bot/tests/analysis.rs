@@ -0,0 +1,204 @@
+use anyhow::{Result, bail};
+use bot::{
+    analysis::{build, next_earnings},
+    i18n::Locale,
+};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use futures::future::BoxFuture;
+use stock::{
+    Bar,
+    earnings::{EarningsDate, EarningsSource},
+    indicators::cdc::DEFAULT_BAND_PCT,
+};
+
+/// `n` daily bars drifting up through a slow wave with a day-to-day wiggle,
+/// each a dollar either side of its close. The last bar trades three times
+/// the usual volume.
+fn bars(n: usize) -> Vec<Bar> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+    (0..n)
+        .map(|i| {
+            let wiggle = if i % 2 == 0 { 0.6 } else { -0.6 };
+            let close = 100.0 + 10.0 * (i as f64 / 15.0).sin() + i as f64 * 0.1 + wiggle;
+            Bar {
+                timestamp: start + Duration::days(i as i64),
+                open: close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: if i + 1 == n { 3_000_000.0 } else { 1_000_000.0 },
+                trade_count: None,
+                vwap: None,
+            }
+        })
+        .collect()
+}
+
+fn fields(n: usize, locale: Locale) -> Vec<(String, String)> {
+    build("aapl", &bars(n), DEFAULT_BAND_PCT)
+        .unwrap()
+        .fields(locale)
+}
+
+fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn full_history_shows_every_field() {
+    let report = build("aapl", &bars(300), DEFAULT_BAND_PCT).unwrap();
+
+    assert_eq!(report.symbol, "AAPL");
+    assert_eq!(report.headline(), "$138.14 (-0.6%)");
+    assert_eq!(
+        report.fields(Locale::En),
+        pairs(&[
+            ("CDC zone", "BullishZone"),
+            ("RSI (14)", "71.2 · overbought"),
+            ("MACD", "Above signal (+0.20)"),
+            ("Stop (2× ATR)", "$133.40 (-3.4%) · ATR $2.37"),
+            ("52-week range", "$95.36 – $139.91 · 96% of range"),
+            ("Volume vs 20-day avg", "3.0M vs 1.0M (3.0×)"),
+        ])
+    );
+}
+
+#[test]
+fn range_covers_the_last_year_only() {
+    // the first 20 bars fall out of the 252-session window
+    let range = build("aapl", &bars(272), DEFAULT_BAND_PCT)
+        .unwrap()
+        .range
+        .unwrap();
+    let window = &bars(272)[20..];
+    let low = window.iter().map(|b| b.low).fold(f64::INFINITY, f64::min);
+    assert_eq!(range.low, low);
+}
+
+#[test]
+fn short_history_omits_what_it_cannot_compute() {
+    // too short for MACD's slow and signal periods
+    assert_eq!(
+        fields(30, Locale::En),
+        pairs(&[
+            ("CDC zone", "BullishZone"),
+            ("RSI (14)", "52.0"),
+            ("Stop (2× ATR)", "$107.17 (-4.0%) · ATR $2.24"),
+            ("52-week range", "$99.17 – $114.07 · 84% of range"),
+            ("Volume vs 20-day avg", "3.0M vs 1.0M (3.0×)"),
+        ])
+    );
+
+    let report = build("aapl", &bars(10), DEFAULT_BAND_PCT).unwrap();
+    assert_eq!(report.headline(), "$105.95 (-0.5%)");
+    assert!(report.fields(Locale::En).is_empty());
+}
+
+#[test]
+fn single_bar_has_no_change() {
+    let report = build("aapl", &bars(1), DEFAULT_BAND_PCT).unwrap();
+    assert_eq!(report.change_pct, None);
+    assert_eq!(report.headline(), "$100.60");
+}
+
+#[test]
+fn no_bars_no_report() {
+    assert!(build("aapl", &[], DEFAULT_BAND_PCT).is_none());
+}
+
+#[test]
+fn bearish_stop_sits_above_the_close() {
+    let mut falling = bars(300);
+    falling.reverse();
+    let report = build("aapl", &falling, DEFAULT_BAND_PCT).unwrap();
+
+    let stop = report.stop.unwrap();
+    assert!(stop.price > report.close, "{report:?}");
+}
+
+#[test]
+fn fields_follow_the_locale() {
+    let names: Vec<String> = fields(300, Locale::Th)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "โซน CDC",
+            "RSI (14)",
+            "MACD",
+            "จุดตัดขาดทุน (2× ATR)",
+            "กรอบราคา 52 สัปดาห์",
+            "วอลุ่มเทียบค่าเฉลี่ย 20 วัน",
+        ]
+    );
+}
+
+#[test]
+fn a_known_earnings_date_comes_last() {
+    let mut report = build("aapl", &bars(300), DEFAULT_BAND_PCT).unwrap();
+    report.next_earnings = NaiveDate::from_ymd_opt(2025, 1, 30);
+    let fields = report.fields(Locale::En);
+    assert_eq!(
+        fields.last(),
+        Some(&("Next earnings".to_string(), "2025-01-30".to_string()))
+    );
+}
+
+/// Calendar answering with fixed dates, or failing when there are none.
+struct Calendar(Vec<EarningsDate>);
+
+impl EarningsSource for Calendar {
+    fn upcoming<'a>(
+        &'a self,
+        _symbols: &'a [String],
+        _from: NaiveDate,
+        _end: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<EarningsDate>>> {
+        Box::pin(async move {
+            if self.0.is_empty() {
+                bail!("calendar down");
+            }
+            Ok(self.0.clone())
+        })
+    }
+}
+
+fn day(d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, d).unwrap()
+}
+
+#[tokio::test]
+async fn next_earnings_is_the_nearest_report_of_the_symbol() {
+    let calendar = Calendar(vec![
+        EarningsDate {
+            symbol: "AAPL".into(),
+            date: day(30),
+        },
+        EarningsDate {
+            symbol: "MSFT".into(),
+            date: day(20),
+        },
+        EarningsDate {
+            symbol: "AAPL".into(),
+            date: day(25),
+        },
+    ]);
+    assert_eq!(
+        next_earnings(&calendar, "AAPL", day(10)).await,
+        Some(day(25))
+    );
+    assert_eq!(next_earnings(&calendar, "NVDA", day(10)).await, None);
+}
+
+#[tokio::test]
+async fn a_failing_calendar_leaves_the_date_out() {
+    assert_eq!(
+        next_earnings(&Calendar(vec![]), "AAPL", day(10)).await,
+        None
+    );
+}
@@ -0,0 +1,334 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use anyhow::Result;
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use bot::api::{self, ApiState, LatestSignal, WatchlistEntry, WatchlistSource};
+use chrono::{Duration, TimeZone, Utc};
+use futures::future::BoxFuture;
+use serde_json::Value;
+use stock::{
+    Bar, PriceSource, Scope, Session, Snapshot, StoreCircuits, SymbolMeta, Timeframe,
+    circuit::{CircuitState, StoreUnavailable},
+    indicators::cdc::Signal,
+    scan::ScanReading,
+    usage::ApiUsage,
+};
+use tower::ServiceExt;
+
+const TOKEN: &str = "s3cret";
+
+struct MockWatchlist;
+
+impl WatchlistSource for MockWatchlist {
+    fn watchlist(&self, scope: Scope) -> BoxFuture<'_, Result<Vec<WatchlistEntry>>> {
+        Box::pin(async move {
+            Ok(match scope {
+                Scope::Guild(1) => vec![WatchlistEntry {
+                    symbol: "AAPL".to_string(),
+                    meta: SymbolMeta {
+                        added_price: Some(180.0),
+                        ..Default::default()
+                    },
+                }],
+                _ => vec![],
+            })
+        })
+    }
+
+    fn latest_signals(&self, _scope: Scope) -> BoxFuture<'_, Result<Vec<LatestSignal>>> {
+        Box::pin(async move {
+            Ok(vec![
+                LatestSignal {
+                    symbol: "AAPL".to_string(),
+                    reading: Some(ScanReading {
+                        signal: Signal::Buy,
+                        close: 181.0,
+                        ema12: 180.0,
+                        ema26: 179.0,
+                        timestamp: Utc.with_ymd_and_hms(2024, 3, 1, 21, 0, 0).unwrap(),
+                    }),
+                },
+                LatestSignal {
+                    symbol: "MSFT".to_string(),
+                    reading: None,
+                },
+            ])
+        })
+    }
+}
+
+/// A store whose write circuit is open and read circuit half-open, with
+/// every call turned away.
+struct DownWatchlist;
+
+impl DownWatchlist {
+    fn unavailable() -> anyhow::Error {
+        StoreUnavailable {
+            circuit: "redis_read",
+            retry_in: std::time::Duration::from_secs(12),
+        }
+        .into()
+    }
+}
+
+impl WatchlistSource for DownWatchlist {
+    fn watchlist(&self, _scope: Scope) -> BoxFuture<'_, Result<Vec<WatchlistEntry>>> {
+        Box::pin(async { Err(Self::unavailable()) })
+    }
+
+    fn latest_signals(&self, _scope: Scope) -> BoxFuture<'_, Result<Vec<LatestSignal>>> {
+        Box::pin(async { Err(Self::unavailable()) })
+    }
+
+    fn circuits(&self) -> Option<StoreCircuits> {
+        Some(StoreCircuits {
+            read: CircuitState::HalfOpen,
+            write: CircuitState::Open,
+        })
+    }
+}
+
+/// Serves bars from `cached` and counts any call that would go to Alpaca.
+#[derive(Default)]
+struct MockPrices {
+    cached: HashMap<String, Vec<Bar>>,
+    fetches: AtomicUsize,
+    usage: Option<ApiUsage>,
+}
+
+impl PriceSource for MockPrices {
+    fn fetch_price<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _duration: Duration,
+        _timeframe: Timeframe,
+        _limit: usize,
+        _bypass_cache: bool,
+        _session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async { Ok(vec![]) })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        _symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async { Ok(HashMap::new()) })
+    }
+
+    fn cached_bars(&self, symbol: &str, _timeframe: Timeframe) -> Option<Vec<Bar>> {
+        self.cached.get(symbol).cloned()
+    }
+
+    fn usage(&self) -> Option<ApiUsage> {
+        self.usage
+    }
+}
+
+fn bar(days_ago: i64, close: f64) -> Bar {
+    Bar {
+        timestamp: Utc::now() - Duration::days(days_ago),
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 1000.0,
+        trade_count: None,
+        vwap: None,
+    }
+}
+
+fn state(prices: Arc<MockPrices>) -> ApiState {
+    ApiState {
+        watchlist: Arc::new(MockWatchlist),
+        prices,
+        token: TOKEN.into(),
+    }
+}
+
+async fn get(state: ApiState, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+    let mut request = Request::get(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    let response = api::router(state)
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn requests_without_the_token_are_rejected() {
+    let prices = Arc::new(MockPrices::default());
+    for uri in [
+        "/api/watchlist?scope=guild:1",
+        "/api/signals/latest?scope=guild:1",
+        "/api/symbols/AAPL/bars",
+    ] {
+        let (status, _) = get(state(prices.clone()), uri, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{uri} without a token");
+
+        let (status, _) = get(state(prices.clone()), uri, Some("wrong")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{uri} with a wrong token");
+    }
+}
+
+#[tokio::test]
+async fn watchlist_lists_symbols_with_metadata() {
+    let (status, body) = get(
+        state(Arc::default()),
+        "/api/watchlist?scope=guild:1",
+        Some(TOKEN),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["symbol"], "AAPL");
+    assert_eq!(body[0]["added_price"], 180.0);
+}
+
+#[tokio::test]
+async fn scope_is_required_and_validated() {
+    let (status, _) = get(state(Arc::default()), "/api/watchlist", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = get(
+        state(Arc::default()),
+        "/api/watchlist?scope=channel:1",
+        Some(TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn latest_signals_include_timestamps() {
+    let (status, body) = get(
+        state(Arc::default()),
+        "/api/signals/latest?scope=user:7",
+        Some(TOKEN),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["symbol"], "AAPL");
+    assert_eq!(body[0]["reading"]["timestamp"], "2024-03-01T21:00:00Z");
+    assert_eq!(body[1]["reading"], Value::Null);
+}
+
+#[tokio::test]
+async fn unknown_symbols_are_not_found() {
+    let prices = Arc::new(MockPrices::default());
+
+    let (status, _) = get(state(prices.clone()), "/api/symbols/NOPE/bars", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = get(state(prices.clone()), "/api/symbols/$$$/bars", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn bars_come_from_the_cache_only() {
+    let prices = Arc::new(MockPrices {
+        cached: HashMap::from([(
+            "AAPL".to_string(),
+            vec![bar(200, 150.0), bar(30, 170.0), bar(1, 180.0)],
+        )]),
+        ..Default::default()
+    });
+
+    let (status, body) = get(
+        state(prices.clone()),
+        "/api/symbols/aapl/bars?days=90",
+        Some(TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let closes: Vec<f64> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["c"].as_f64().unwrap())
+        .collect();
+    assert_eq!(closes, vec![170.0, 180.0]);
+
+    let (status, _) = get(state(prices.clone()), "/api/symbols/MSFT/bars", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    assert_eq!(prices.fetches.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn days_out_of_range_are_rejected() {
+    let (status, _) = get(
+        state(Arc::default()),
+        "/api/symbols/AAPL/bars?days=0",
+        Some(TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn health_needs_no_token() {
+    let prices = Arc::new(MockPrices::default());
+    let (status, body) = get(state(prices), "/api/health", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["store"], Value::Null);
+    assert_eq!(body["usage"], Value::Null);
+}
+
+#[tokio::test]
+async fn health_reports_the_request_budget() {
+    let prices = Arc::new(MockPrices {
+        usage: Some(ApiUsage {
+            used: 150,
+            limit: 200,
+            remaining: 40,
+        }),
+        ..MockPrices::default()
+    });
+    let (status, body) = get(state(prices), "/api/health", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["usage"]["used"], 150);
+    assert_eq!(body["usage"]["limit"], 200);
+    assert_eq!(body["usage"]["remaining"], 40);
+}
+
+#[tokio::test]
+async fn an_open_store_circuit_is_reported_as_unavailable() {
+    let down = ApiState {
+        watchlist: Arc::new(DownWatchlist),
+        ..state(Arc::new(MockPrices::default()))
+    };
+
+    let (status, body) = get(down.clone(), "/api/health", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["store"]["read"], "half_open");
+    assert_eq!(body["store"]["write"], "open");
+
+    for uri in [
+        "/api/watchlist?scope=guild:1",
+        "/api/signals/latest?scope=guild:1",
+    ] {
+        let (status, body) = get(down.clone(), uri, Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{uri}");
+        assert_eq!(body["error"], "watchlist backend temporarily unavailable");
+    }
+}
@@ -0,0 +1,918 @@
+mod common;
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use bot::{
+    Error,
+    batch::{
+        Batch, BatchLedger, BatchSink, Clock, FallbackSink, IdempotentSink, InteractionSink,
+        MAX_EMBEDS, MessageBatcher, SEND_ATTEMPTS, is_forbidden, is_transient, is_unknown_channel,
+        is_upload_rejected, token_expiring,
+    },
+    command::stock::add_symbol,
+    i18n::Locale,
+    notify::SignalEvent,
+    report::{Chartless, MAX_LISTED_FAILURES, incomplete_summary, run_summary},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serenity::all::{ErrorResponse, HttpError};
+
+use common::{MockSink, Sent, hit, sized_hit};
+
+#[tokio::test]
+async fn empty_scan_posts_notice_when_announced() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Notice("nothing".into())]);
+}
+
+#[tokio::test]
+async fn empty_scan_acknowledges_quietly_when_suppressed() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    batcher.finish("nothing".into(), false, None).await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Ack]);
+}
+
+#[tokio::test]
+async fn scan_with_hits_never_posts_notice() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    for n in 0..MAX_EMBEDS + 2 {
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Batch(MAX_EMBEDS), Sent::Batch(2)]);
+}
+
+#[tokio::test]
+async fn large_charts_flush_before_the_byte_ceiling() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone()).with_max_bytes(1000);
+
+    for n in 0..5 {
+        let (embed, attachment) = sized_hit(n, 400);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(
+        sink.sent(),
+        vec![Sent::Batch(2), Sent::Batch(2), Sent::Batch(1)]
+    );
+    assert_eq!(sink.bytes(), vec![800, 800, 400]);
+}
+
+#[tokio::test]
+async fn chart_over_the_ceiling_is_sent_without_its_file() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone()).with_max_bytes(1000);
+
+    let (embed, attachment) = sized_hit(0, 300);
+    batcher.push(embed, attachment, None).await.unwrap();
+    let (embed, attachment) = sized_hit(1, 5000);
+    batcher.push(embed, attachment, None).await.unwrap();
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Batch(2)]);
+    assert_eq!(sink.bytes(), vec![300]);
+}
+
+#[tokio::test]
+async fn charts_filling_the_ceiling_exactly_share_a_batch() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone()).with_max_bytes(1000);
+
+    for n in 0..4 {
+        let (embed, attachment) = sized_hit(n, 250);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Batch(4)]);
+}
+
+/// Turns down any batch of more than `limit` embeds, the way Discord does a
+/// message over its total text limit.
+struct PickySink {
+    inner: MockSink,
+    limit: usize,
+}
+
+impl BatchSink for PickySink {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        if batch.embeds.len() > self.limit {
+            return Err(anyhow!("Invalid Form Body"));
+        }
+        self.inner.send_batch(batch).await
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        self.inner.send_notice(content).await
+    }
+}
+
+#[tokio::test]
+async fn rejected_batch_is_retried_as_two_halves() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(PickySink {
+        inner: sink.clone(),
+        limit: 3,
+    });
+
+    for n in 0..6 {
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.flush().await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Batch(3), Sent::Batch(3)]);
+}
+
+#[tokio::test]
+async fn half_that_is_still_rejected_fails_the_flush() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(PickySink {
+        inner: sink.clone(),
+        limit: 2,
+    });
+
+    for n in 0..6 {
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+
+    assert!(batcher.flush().await.is_err());
+    assert!(sink.sent().is_empty());
+}
+
+fn signal_event(symbol: &str) -> SignalEvent {
+    SignalEvent {
+        symbol: symbol.into(),
+        signal: "buy".into(),
+        price: 100.0,
+        timestamp: Utc.with_ymd_and_hms(2024, 6, 18, 4, 0, 0).unwrap(),
+        timeframe: "1Day".into(),
+        chart_url: None,
+    }
+}
+
+#[tokio::test]
+async fn split_keeps_files_and_events_with_their_embeds() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(PickySink {
+        inner: sink.clone(),
+        limit: 3,
+    });
+
+    // only the first and last hits have a chart, only the last two an event
+    for (n, bytes) in [(0, Some(100)), (1, None), (2, None), (3, Some(300))] {
+        let (embed, attachment) = sized_hit(n, bytes.unwrap_or_default());
+        let symbol = format!("SYM{n}");
+        let event = (n >= 2).then(|| signal_event(&symbol));
+        batcher
+            .push_hit(&symbol, embed, bytes.map(|_| attachment), event)
+            .await
+            .unwrap();
+    }
+    batcher.flush().await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Batch(2), Sent::Batch(2)]);
+    assert_eq!(sink.bytes(), vec![100, 300]);
+    assert_eq!(sink.events(), vec![vec![], vec!["SYM2", "SYM3"]]);
+    let symbols = sink.symbols();
+    assert_eq!(
+        symbols[1],
+        [Some("SYM2".to_string()), Some("SYM3".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn closing_buttons_ride_on_the_last_batch() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone())
+        .with_closing_components(vec![add_symbol::button_row(Locale::En)]);
+
+    for n in 0..MAX_EMBEDS + 2 {
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(sink.components(), vec![0, 1]);
+}
+
+#[tokio::test]
+async fn closing_buttons_skip_a_plain_notice() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone())
+        .with_closing_components(vec![add_symbol::button_row(Locale::En)]);
+
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Notice("nothing".into())]);
+    assert!(sink.components().is_empty());
+}
+
+#[tokio::test]
+async fn hits_keep_their_symbols_across_batches() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    for n in 0..MAX_EMBEDS + 1 {
+        let (embed, attachment) = hit(n);
+        batcher
+            .push_hit(&format!("SYM{n}"), embed, attachment, None)
+            .await
+            .unwrap();
+    }
+    let (embed, attachment) = hit(99);
+    batcher.push(embed, attachment, None).await.unwrap();
+    let summary = incomplete_summary(Locale::En, 12, &["BAD".into()], Chartless::default(), 0);
+    batcher
+        .finish("nothing".into(), true, summary)
+        .await
+        .unwrap();
+
+    let symbols = sink.symbols();
+    assert_eq!(symbols[0].len(), MAX_EMBEDS);
+    assert_eq!(symbols[0][3].as_deref(), Some("SYM3"));
+    // neither a plain push nor the summary past the end is a hit
+    assert_eq!(symbols[1], [Some("SYM10".to_string()), None]);
+    assert_eq!(sink.sent(), vec![Sent::Batch(MAX_EMBEDS), Sent::Batch(3)]);
+}
+
+fn symbols(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("SYM{i}")).collect()
+}
+
+#[test]
+fn complete_scan_has_no_summary() {
+    assert!(incomplete_summary(Locale::En, 5, &[], Chartless::default(), 0).is_none());
+}
+
+#[tokio::test]
+async fn slow_run_posts_a_summary_without_failures() {
+    let slow = Some((Duration::from_secs(600), Duration::from_secs(120)));
+    let summary = run_summary(Locale::En, 5, &[], Chartless::default(), 0, slow, &[]).unwrap();
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(
+        json["description"],
+        "🐢 Today's run is slow: 10m 0s so far against a usual 2m 0s."
+    );
+    assert!(json.get("footer").is_none());
+
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+    batcher
+        .finish("nothing".into(), false, Some(summary))
+        .await
+        .unwrap();
+    assert_eq!(sink.sent(), vec![Sent::Batch(1)]);
+}
+
+#[test]
+fn symbols_reporting_soon_are_flagged_in_the_summary() {
+    let earnings = [
+        ("NVDA".to_string(), 0),
+        ("AAPL".to_string(), 1),
+        ("MSFT".to_string(), 2),
+    ];
+    let summary =
+        run_summary(Locale::En, 5, &[], Chartless::default(), 0, None, &earnings).unwrap();
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(
+        json["description"],
+        "⚠️ NVDA reports earnings today\n⚠️ AAPL earnings tomorrow\n⚠️ MSFT earnings in 2 days"
+    );
+    assert!(run_summary(Locale::En, 5, &[], Chartless::default(), 0, None, &[]).is_none());
+}
+
+#[tokio::test]
+async fn partial_scan_footer_lists_failed_symbols() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    let (embed, attachment) = hit(0);
+    batcher.push(embed, attachment, None).await.unwrap();
+    let failed = vec!["AAPL".to_string(), "MSFT".to_string()];
+    let summary = incomplete_summary(Locale::En, 5, &failed, Chartless::default(), 0);
+    batcher
+        .finish("nothing".into(), true, summary)
+        .await
+        .unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Batch(2)]);
+    assert_eq!(sink.footers(), vec!["Failed: AAPL, MSFT".to_string()]);
+}
+
+#[tokio::test]
+async fn partial_scan_footer_truncates_long_lists() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    let failed = symbols(MAX_LISTED_FAILURES + 3);
+    let summary = incomplete_summary(Locale::En, 20, &failed, Chartless::default(), 0);
+    batcher
+        .finish("nothing".into(), false, summary)
+        .await
+        .unwrap();
+
+    let listed = symbols(MAX_LISTED_FAILURES).join(", ");
+    assert_eq!(sink.sent(), vec![Sent::Batch(1)]);
+    assert_eq!(sink.footers(), vec![format!("Failed: {listed} +3 more")]);
+}
+
+#[tokio::test]
+async fn partial_scan_without_hits_still_announces() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    let summary = incomplete_summary(Locale::En, 3, &symbols(1), Chartless::default(), 0);
+    batcher
+        .finish("nothing".into(), true, summary)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sink.sent(),
+        vec![Sent::Notice("nothing".into()), Sent::Batch(1)]
+    );
+}
+
+#[tokio::test]
+async fn partial_scan_summary_starts_new_batch_when_full() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+
+    for n in 0..MAX_EMBEDS {
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    let summary = incomplete_summary(Locale::En, 12, &symbols(2), Chartless::default(), 0);
+    batcher
+        .finish("nothing".into(), true, summary)
+        .await
+        .unwrap();
+
+    assert_eq!(sink.sent(), vec![Sent::Batch(MAX_EMBEDS), Sent::Batch(1)]);
+}
+
+/// Remembers posted batch keys in memory, standing in for Redis.
+#[derive(Clone, Default)]
+struct MockLedger(Arc<Mutex<HashSet<String>>>);
+
+impl MockLedger {
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.0.lock().unwrap().iter().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl BatchLedger for MockLedger {
+    async fn is_posted(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.0.lock().unwrap().contains(key))
+    }
+
+    async fn mark_posted(&self, key: &str) -> Result<(), Error> {
+        self.0.lock().unwrap().insert(key.to_string());
+        Ok(())
+    }
+}
+
+/// Fails the batch holding hit 10, the first of the second batch, the next
+/// `failures` times it's sent, then behaves like [`MockSink`].
+#[derive(Clone, Default)]
+struct FlakySink {
+    inner: MockSink,
+    failures: Arc<Mutex<usize>>,
+    transient: bool,
+    attempts: Arc<Mutex<usize>>,
+}
+
+impl FlakySink {
+    fn new(failures: usize, transient: bool) -> Self {
+        Self {
+            failures: Arc::new(Mutex::new(failures)),
+            transient,
+            ..Default::default()
+        }
+    }
+
+    fn attempts(&self) -> usize {
+        std::mem::take(&mut self.attempts.lock().unwrap())
+    }
+}
+
+impl BatchSink for FlakySink {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        *self.attempts.lock().unwrap() += 1;
+        let second = batch.attachments.iter().any(|a| a.filename == "10.png");
+        let fail = second && {
+            let mut left = self.failures.lock().unwrap();
+            let fail = *left > 0;
+            *left = left.saturating_sub(1);
+            fail
+        };
+        if fail {
+            return Err(if self.transient {
+                server_error().await
+            } else {
+                anyhow!("Invalid Form Body")
+            });
+        }
+        self.inner.send_batch(batch).await
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        self.inner.send_notice(content).await
+    }
+}
+
+/// What serenity returns when Discord answers with a 503.
+async fn server_error() -> Error {
+    let res = axum::http::Response::builder()
+        .status(503)
+        .body("")
+        .unwrap();
+    let res = ErrorResponse::from_response(res.into(), reqwest::Method::POST).await;
+    serenity::Error::Http(HttpError::UnsuccessfulRequest(res)).into()
+}
+
+/// Push 25 hits, 3 batches' worth, through an idempotent sink backed by
+/// `ledger`. Returns what reached the sink and the batches given up on.
+async fn post_run(sink: &FlakySink, ledger: &MockLedger) -> (Vec<Sent>, Vec<String>) {
+    post_hits(sink, ledger, 0..25).await
+}
+
+/// [`post_run`] with the hits in `order`.
+async fn post_hits(
+    sink: &FlakySink,
+    ledger: &MockLedger,
+    order: impl IntoIterator<Item = usize>,
+) -> (Vec<Sent>, Vec<String>) {
+    let idempotent = IdempotentSink::new(sink.clone(), ledger.clone(), "daily:2024-07-16:7")
+        .with_retry_base(Duration::ZERO);
+    let failed = idempotent.failed.clone();
+    let mut batcher = MessageBatcher::new(idempotent);
+    for n in order {
+        let (embed, attachment) = hit(n);
+        // the daily job logs a failed batch and keeps going
+        let _ = batcher
+            .push_hit(&format!("S{n:02}"), embed, attachment, None)
+            .await;
+    }
+    let _ = batcher.finish("nothing".into(), true, None).await;
+
+    let failed = failed.lock().unwrap().clone();
+    (sink.inner.sent(), failed)
+}
+
+#[tokio::test]
+async fn server_errors_are_transient_and_rejections_are_not() {
+    assert!(is_transient(&server_error().await));
+    assert!(!is_transient(&anyhow!("Invalid Form Body")));
+}
+
+#[tokio::test]
+async fn rerun_posts_only_the_batch_that_failed() {
+    let sink = FlakySink::new(SEND_ATTEMPTS as usize, true);
+    let ledger = MockLedger::default();
+
+    let (sent, failed) = post_run(&sink, &ledger).await;
+    assert_eq!(sent, vec![Sent::Batch(MAX_EMBEDS), Sent::Batch(5)]);
+    let symbols: Vec<String> = (10..20).map(|n| format!("S{n:02}")).collect();
+    assert_eq!(
+        failed,
+        [format!("daily:2024-07-16:7:{}", symbols.join(","))]
+    );
+    assert_eq!(sink.attempts(), 2 + SEND_ATTEMPTS as usize);
+    let keys = ledger.keys();
+    assert_eq!(keys.len(), 15);
+    assert_eq!(keys[0], "daily:2024-07-16:7:S00");
+    assert!(!keys.contains(&"daily:2024-07-16:7:S10".to_string()));
+
+    let (sent, failed) = post_run(&sink, &ledger).await;
+    assert_eq!(sent, vec![Sent::Batch(MAX_EMBEDS)]);
+    assert!(failed.is_empty());
+    assert_eq!(sink.attempts(), 1);
+    assert_eq!(ledger.keys().len(), 25);
+}
+
+#[tokio::test]
+async fn rerun_skips_posted_hits_however_they_are_batched() {
+    let sink = FlakySink::new(SEND_ATTEMPTS as usize, true);
+    let ledger = MockLedger::default();
+    post_run(&sink, &ledger).await;
+    sink.attempts();
+
+    // hits arrive in completion order, so a re-run batches them differently
+    let (sent, failed) = post_hits(&sink, &ledger, (0..25).rev()).await;
+    assert_eq!(sent, vec![Sent::Batch(5), Sent::Batch(5)]);
+    assert!(failed.is_empty());
+    assert_eq!(ledger.keys().len(), 25);
+
+    let (sent, _) = post_hits(&sink, &ledger, (0..25).rev()).await;
+    assert!(sent.is_empty(), "{sent:?}");
+}
+
+#[tokio::test]
+async fn transient_failure_is_retried_in_place() {
+    let sink = FlakySink::new(1, true);
+    let ledger = MockLedger::default();
+
+    let (sent, failed) = post_run(&sink, &ledger).await;
+    assert_eq!(
+        sent,
+        vec![
+            Sent::Batch(MAX_EMBEDS),
+            Sent::Batch(MAX_EMBEDS),
+            Sent::Batch(5)
+        ]
+    );
+    assert!(failed.is_empty());
+    assert_eq!(sink.attempts(), 4);
+}
+
+#[tokio::test]
+async fn rejected_batch_is_not_retried_but_split() {
+    let sink = FlakySink::new(1, false);
+    let ledger = MockLedger::default();
+
+    let (sent, failed) = post_run(&sink, &ledger).await;
+    // the first half still holds hit 10, but its one failure is used up
+    assert_eq!(
+        sent,
+        vec![
+            Sent::Batch(MAX_EMBEDS),
+            Sent::Batch(5),
+            Sent::Batch(5),
+            Sent::Batch(5)
+        ]
+    );
+    assert!(failed.is_empty());
+    assert_eq!(sink.attempts(), 5);
+}
+
+/// What serenity returns when Discord won't take a message's files.
+async fn payload_too_large() -> Error {
+    let res = axum::http::Response::builder()
+        .status(413)
+        .body("")
+        .unwrap();
+    let res = ErrorResponse::from_response(res.into(), reqwest::Method::POST).await;
+    serenity::Error::Http(HttpError::UnsuccessfulRequest(res)).into()
+}
+
+/// Rejects every batch carrying files as too large, and records how many
+/// embeds still pointed at an image in the ones it let through.
+#[derive(Clone, Default)]
+struct NoUploadSink {
+    inner: MockSink,
+    images: Arc<Mutex<usize>>,
+}
+
+impl BatchSink for NoUploadSink {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        if !batch.attachments.is_empty() {
+            return Err(payload_too_large().await);
+        }
+        for embed in &batch.embeds {
+            if serde_json::to_value(embed)?.get("image").is_some() {
+                *self.images.lock().unwrap() += 1;
+            }
+        }
+        self.inner.send_batch(batch).await
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        self.inner.send_notice(content).await
+    }
+}
+
+#[tokio::test]
+async fn rejected_uploads_are_resent_as_text() {
+    assert!(is_upload_rejected(&payload_too_large().await));
+    assert!(!is_upload_rejected(&server_error().await));
+
+    let sink = NoUploadSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+    for n in 0..MAX_EMBEDS + 2 {
+        let (embed, attachment) = hit(n);
+        let embed = embed
+            .title(format!("hit {n}"))
+            .image(format!("attachment://{n}.png"));
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(
+        sink.inner.sent(),
+        vec![Sent::Batch(MAX_EMBEDS), Sent::Batch(2)]
+    );
+    assert_eq!(sink.inner.bytes(), vec![0, 0]);
+    assert_eq!(*sink.images.lock().unwrap(), 0);
+    assert_eq!(batcher.degraded(), MAX_EMBEDS + 2);
+}
+
+#[tokio::test]
+async fn the_summary_counts_uploads_rejected_in_the_last_batch() {
+    let sink = NoUploadSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+    for n in 0..2 {
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    let mut counted = None;
+    batcher
+        .finish_with("nothing".into(), true, |rejected| {
+            counted = Some(rejected);
+            incomplete_summary(
+                Locale::En,
+                2,
+                &[],
+                Chartless {
+                    undrawn: 0,
+                    rejected,
+                },
+                0,
+            )
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(counted, Some(2));
+    assert_eq!(sink.inner.sent(), vec![Sent::Batch(2), Sent::Batch(1)]);
+    let summary = incomplete_summary(
+        Locale::En,
+        2,
+        &[],
+        Chartless {
+            undrawn: 0,
+            rejected: 2,
+        },
+        0,
+    )
+    .unwrap();
+    assert_eq!(
+        serde_json::to_value(&summary).unwrap()["description"],
+        "🖼️ 2 signals went out without a chart: Discord wouldn't take the upload."
+    );
+}
+
+#[tokio::test]
+async fn chartless_hits_are_counted_in_the_summary() {
+    let summary = incomplete_summary(
+        Locale::En,
+        5,
+        &[],
+        Chartless {
+            undrawn: 2,
+            rejected: 0,
+        },
+        0,
+    )
+    .unwrap();
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(
+        json["description"],
+        "🖼️ 2 signals went out without a chart: it couldn't be drawn."
+    );
+    assert!(json.get("footer").is_none());
+}
+
+#[tokio::test]
+async fn fallback_hits_are_counted_in_the_summary() {
+    let summary = incomplete_summary(Locale::En, 5, &[], Chartless::default(), 3).unwrap();
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(
+        json["description"],
+        "🔀 3 signals used data from a fallback source, whose feed can differ slightly."
+    );
+}
+
+fn created() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 16, 14, 0, 0).unwrap()
+}
+
+#[test]
+fn tokens_count_as_expiring_a_minute_early() {
+    let created = created();
+    let minutes = |m: i64| created + chrono::Duration::minutes(m);
+    assert!(!token_expiring(created, created));
+    assert!(!token_expiring(
+        created,
+        minutes(14) - chrono::Duration::seconds(1)
+    ));
+    assert!(token_expiring(created, minutes(14)));
+    assert!(token_expiring(created, minutes(15)));
+    assert!(token_expiring(created, minutes(60)));
+}
+
+#[tokio::test]
+async fn results_move_to_the_channel_before_the_token_expires() {
+    let reply = MockSink::default();
+    let channel = MockSink::default();
+    let now = Arc::new(Mutex::new(created()));
+    let clock: Clock = {
+        let now = now.clone();
+        Arc::new(move || *now.lock().unwrap())
+    };
+    let sink = InteractionSink::new(reply.clone(), channel.clone(), created()).with_clock(clock);
+    let mut batcher = MessageBatcher::new(sink);
+
+    // a hit every 30 seconds fills a batch every five minutes: the ones
+    // sent 4.5 and 9.5 minutes in go through the token, the rest to the
+    // channel
+    for n in 0..45 {
+        *now.lock().unwrap() = created() + chrono::Duration::seconds(30 * n as i64);
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), true, None).await.unwrap();
+
+    assert_eq!(
+        reply.sent(),
+        vec![Sent::Batch(MAX_EMBEDS), Sent::Batch(MAX_EMBEDS)]
+    );
+    assert_eq!(
+        channel.sent(),
+        vec![
+            Sent::Batch(MAX_EMBEDS),
+            Sent::Batch(MAX_EMBEDS),
+            Sent::Batch(5)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn a_fresh_interaction_is_acknowledged_and_an_expired_one_is_not() {
+    let reply = MockSink::default();
+    let channel = MockSink::default();
+    let sink = InteractionSink::new(reply.clone(), channel.clone(), Utc::now());
+    sink.acknowledge().await.unwrap();
+    assert_eq!(reply.sent(), vec![Sent::Ack]);
+
+    let stale = Utc::now() - chrono::Duration::minutes(20);
+    let sink = InteractionSink::new(reply.clone(), channel.clone(), stale);
+    sink.acknowledge().await.unwrap();
+    assert!(reply.sent().is_empty());
+    assert_eq!(channel.sent(), vec![Sent::Ack]);
+}
+
+async fn forbidden() -> Error {
+    let res = axum::http::Response::builder()
+        .status(403)
+        .body(r#"{"code": 50013, "message": "Missing Permissions"}"#)
+        .unwrap();
+    let res = ErrorResponse::from_response(res.into(), reqwest::Method::POST).await;
+    serenity::Error::Http(HttpError::UnsuccessfulRequest(res)).into()
+}
+
+/// A channel the bot can't post in.
+struct ForbiddenSink;
+
+impl BatchSink for ForbiddenSink {
+    async fn send_batch(&self, _: Batch) -> Result<(), Error> {
+        Err(forbidden().await)
+    }
+
+    async fn send_notice(&self, _: String) -> Result<(), Error> {
+        Err(forbidden().await)
+    }
+}
+
+#[tokio::test]
+async fn missing_permissions_are_forbidden_and_other_failures_are_not() {
+    assert!(is_forbidden(&forbidden().await));
+    assert!(!is_forbidden(&server_error().await));
+    assert!(!is_forbidden(&anyhow!("Invalid Form Body")));
+}
+
+#[tokio::test]
+async fn a_channel_the_bot_cant_post_in_falls_back_with_one_note() {
+    let fallback = MockSink::default();
+    let sink = FallbackSink::new(ForbiddenSink, fallback.clone(), "can't post in #tech");
+    let mut batcher = MessageBatcher::new(sink);
+    for n in 0..MAX_EMBEDS + 3 {
+        let (embed, attachment) = hit(n);
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), false, None).await.unwrap();
+
+    assert_eq!(
+        fallback.sent(),
+        vec![
+            Sent::Notice("can't post in #tech".into()),
+            Sent::Batch(MAX_EMBEDS),
+            Sent::Batch(3),
+        ]
+    );
+}
+
+async fn unknown_channel() -> Error {
+    let res = axum::http::Response::builder()
+        .status(404)
+        .body(r#"{"code": 10003, "message": "Unknown Channel"}"#)
+        .unwrap();
+    let res = ErrorResponse::from_response(res.into(), reqwest::Method::POST).await;
+    serenity::Error::Http(HttpError::UnsuccessfulRequest(res)).into()
+}
+
+/// A channel deleted since it was set up.
+struct DeletedSink;
+
+impl BatchSink for DeletedSink {
+    async fn send_batch(&self, _: Batch) -> Result<(), Error> {
+        Err(unknown_channel().await)
+    }
+
+    async fn send_notice(&self, _: String) -> Result<(), Error> {
+        Err(unknown_channel().await)
+    }
+}
+
+#[tokio::test]
+async fn a_deleted_channel_falls_back_too() {
+    assert!(is_unknown_channel(&unknown_channel().await));
+    assert!(!is_unknown_channel(&forbidden().await));
+
+    let fallback = MockSink::default();
+    let sink = FallbackSink::new(DeletedSink, fallback.clone(), "can't post in #tech");
+    let mut batcher = MessageBatcher::new(sink);
+    let (embed, attachment) = hit(0);
+    batcher.push(embed, attachment, None).await.unwrap();
+    batcher.finish("nothing".into(), false, None).await.unwrap();
+
+    assert_eq!(
+        fallback.sent(),
+        vec![Sent::Notice("can't post in #tech".into()), Sent::Batch(1)]
+    );
+}
+
+#[tokio::test]
+async fn a_hit_routed_to_two_unreachable_channels_falls_back_once() {
+    let fallback = MockSink::default();
+    let fallen_back = Arc::default();
+    let routes: [(&str, &[&str]); 2] = [
+        ("can't post in #tech", &["NVDA", "AAPL"]),
+        ("can't post in #ai", &["NVDA"]),
+    ];
+    for (note, symbols) in routes {
+        let sink = FallbackSink::new(ForbiddenSink, fallback.clone(), note)
+            .sharing(Arc::clone(&fallen_back));
+        let mut batcher = MessageBatcher::new(sink);
+        for (n, symbol) in symbols.iter().enumerate() {
+            let (embed, attachment) = hit(n);
+            batcher
+                .push_hit(symbol, embed, attachment, None)
+                .await
+                .unwrap();
+        }
+        batcher.finish("nothing".into(), false, None).await.unwrap();
+    }
+
+    assert_eq!(
+        fallback.sent(),
+        vec![
+            Sent::Notice("can't post in #tech".into()),
+            Sent::Batch(2),
+            Sent::Notice("can't post in #ai".into()),
+        ]
+    );
+    assert_eq!(
+        fallback.symbols(),
+        vec![vec![Some("NVDA".to_string()), Some("AAPL".to_string())]]
+    );
+}
+
+#[tokio::test]
+async fn other_failures_are_not_sent_to_the_fallback() {
+    let fallback = MockSink::default();
+    let sink = FallbackSink::new(
+        FlakySink::new(1, true),
+        fallback.clone(),
+        "can't post in #tech",
+    );
+    let mut batch = Batch::default();
+    let (embed, attachment) = hit(10);
+    batch.embeds.push(embed);
+    batch.attachments.push(attachment);
+
+    assert!(sink.send_batch(batch).await.is_err());
+    assert!(fallback.sent().is_empty());
+}
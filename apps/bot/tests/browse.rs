@@ -0,0 +1,40 @@
+use bot::command::stock::browse::{PAGE_SIZE, page, page_count};
+
+fn symbols(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("S{i:03}")).collect()
+}
+
+#[test]
+fn page_count_rounds_up_and_never_hits_zero() {
+    assert_eq!(page_count(0), 1);
+    assert_eq!(page_count(1), 1);
+    assert_eq!(page_count(PAGE_SIZE), 1);
+    assert_eq!(page_count(PAGE_SIZE + 1), 2);
+    assert_eq!(page_count(3 * PAGE_SIZE), 3);
+}
+
+#[test]
+fn pages_split_the_watchlist_in_order() {
+    let list = symbols(PAGE_SIZE + 3);
+
+    let (first, shown) = page(&list, 0);
+    assert_eq!(first, 0);
+    assert_eq!(shown, &list[..PAGE_SIZE]);
+
+    let (second, shown) = page(&list, 1);
+    assert_eq!(second, 1);
+    assert_eq!(shown, &list[PAGE_SIZE..]);
+}
+
+#[test]
+fn a_page_past_the_end_shows_the_last_one() {
+    // the list shrank while the message was open on a later page
+    let list = symbols(PAGE_SIZE + 3);
+    let (index, shown) = page(&list, 5);
+    assert_eq!(index, 1);
+    assert_eq!(shown.len(), 3);
+
+    let (index, shown) = page(&[], 2);
+    assert_eq!(index, 0);
+    assert!(shown.is_empty());
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use bot::{
+    cashtag::{SignalCache, extract},
+    style::SignalStyle,
+};
+use stock::{indicators::cdc::Signal, scan::SignalReading};
+
+#[test]
+fn extracts_first_two_distinct_cashtags() {
+    assert_eq!(
+        extract("loading $amd and $NVDA, maybe $TSLA"),
+        vec!["AMD", "NVDA"]
+    );
+    assert_eq!(extract("$AMD $amd $MSFT"), vec!["AMD", "MSFT"]);
+    assert_eq!(extract("$BRK.B to the moon"), vec!["BRK.B"]);
+}
+
+#[test]
+fn ignores_dollar_amounts_and_non_tags() {
+    assert!(extract("paid $100 for it").is_empty());
+    assert!(extract("AMD without a dollar").is_empty());
+    assert!(extract("US$AMD").is_empty());
+    assert!(extract("$TOOLONG").is_empty());
+}
+
+#[test]
+fn emoji_follows_zone() {
+    let style = SignalStyle::default();
+    let emoji = |signal| style.emoji(signal).to_string();
+    assert_eq!(emoji(Signal::Buy), "🟢");
+    assert_eq!(emoji(Signal::BullishZone), "🟢");
+    assert_eq!(emoji(Signal::Sell), "🔴");
+    assert_eq!(emoji(Signal::BearishZone), "🔴");
+    assert_eq!(emoji(Signal::None), "⚪");
+}
+
+#[test]
+fn cache_expires_after_ttl() {
+    let cache = SignalCache::new(Duration::from_secs(600));
+    let reading = SignalReading {
+        signal: Signal::BullishZone,
+        close: 123.45,
+    };
+    let t0 = Instant::now();
+
+    assert_eq!(cache.get("AMD", t0), None);
+    cache.insert("AMD", reading, t0);
+
+    assert_eq!(
+        cache.get("AMD", t0 + Duration::from_secs(599)),
+        Some(reading)
+    );
+    assert_eq!(cache.get("AMD", t0 + Duration::from_secs(600)), None);
+    assert_eq!(cache.get("NVDA", t0), None);
+}
+
+#[test]
+fn clearing_the_cache_counts_what_went() {
+    let cache = SignalCache::new(Duration::from_secs(600));
+    let reading = SignalReading {
+        signal: Signal::Buy,
+        close: 10.0,
+    };
+    let now = Instant::now();
+    cache.insert("AMD", reading, now);
+    cache.insert("NVDA", reading, now);
+
+    assert_eq!(cache.clear(), 2);
+    assert_eq!(cache.get("AMD", now), None);
+    assert_eq!(cache.clear(), 0);
+}
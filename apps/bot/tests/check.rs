@@ -0,0 +1,89 @@
+use bot::{command::stock::check::explain, i18n::Locale};
+use stock::{indicators::cdc::Signal, scan::Decision, strategy::TrendCheck};
+
+fn crossover(signal: Signal) -> Decision {
+    Decision {
+        raw: signal,
+        trend: None,
+        daily: signal,
+        weekly: None,
+        signal,
+    }
+}
+
+#[test]
+fn a_crossover_every_filter_lets_through_posts() {
+    let lines = explain(Locale::En, &crossover(Signal::Buy), false);
+    assert_eq!(
+        lines,
+        [
+            "✅ The daily scan would post a Buy today.",
+            "• No ADX filter",
+            "• Weekly confirmation off",
+        ]
+    );
+}
+
+#[test]
+fn no_crossover_names_the_zone() {
+    let decision = crossover(Signal::BearishZone);
+    let lines = explain(Locale::En, &decision, true);
+    assert_eq!(
+        lines[0],
+        "➖ No crossover on the latest bar, only BearishZone: nothing would post."
+    );
+    assert_eq!(
+        lines[2],
+        "• Weekly confirmation on, only checked on a crossover"
+    );
+}
+
+#[test]
+fn a_weak_trend_gives_the_adx_and_its_minimum() {
+    let trend = TrendCheck {
+        strength: 18.24,
+        min: 25.0,
+    };
+    let decision = Decision {
+        trend: Some(trend),
+        daily: Signal::BullishZone,
+        signal: Signal::BullishZone,
+        ..crossover(Signal::Buy)
+    };
+    let lines = explain(Locale::En, &decision, false);
+    assert_eq!(
+        lines[0],
+        "🚫 Buy crossover present but ADX 18.2 is below the 25 minimum."
+    );
+    assert_eq!(lines[1], "• ADX 18.2 against a minimum of 25");
+}
+
+#[test]
+fn a_weekly_disagreement_names_the_weekly_trend() {
+    let decision = Decision {
+        weekly: Some(Signal::BullishZone),
+        signal: Signal::BearishZone,
+        ..crossover(Signal::Sell)
+    };
+    let lines = explain(Locale::En, &decision, true);
+    assert_eq!(
+        lines[0],
+        "🚫 Sell crossover present but the weekly trend is BullishZone."
+    );
+    assert_eq!(lines[2], "• Weekly trend: BullishZone");
+
+    let unknown = Decision {
+        weekly: Some(Signal::None),
+        ..decision
+    };
+    assert_eq!(
+        explain(Locale::En, &unknown, true)[0],
+        "🚫 Sell crossover present but there isn't enough weekly history to confirm it."
+    );
+}
+
+#[test]
+fn explanations_are_translated() {
+    let lines = explain(Locale::Th, &crossover(Signal::Sell), false);
+    assert_eq!(lines[0], "✅ สแกนรายวันจะโพสต์สัญญาณ ขาย วันนี้");
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use anyhow::{Context, anyhow};
+use bot::{command::error_reply, i18n::Locale};
+use reqwest::StatusCode;
+use stock::{InvalidSymbol, RenderTimeout, RequestFailed, circuit::StoreUnavailable};
+
+fn failed(status: StatusCode) -> anyhow::Error {
+    RequestFailed::new(
+        status,
+        format!("alpaca bars request for AAPL failed with {status}: secret detail"),
+    )
+    .into()
+}
+
+#[test]
+fn each_failure_gets_its_own_message() {
+    let cases = [
+        (
+            anyhow::Error::from(StoreUnavailable {
+                circuit: "redis_write",
+                retry_in: Duration::from_secs(10),
+            }),
+            "⚠️ The watchlist backend is temporarily unavailable. Try again in a moment.",
+        ),
+        (
+            InvalidSymbol("AA PL".into()).into(),
+            "❌ `AA PL` isn't a valid ticker symbol.",
+        ),
+        (
+            RenderTimeout {
+                symbol: "AAPL".into(),
+                after: Duration::from_secs(30),
+            }
+            .into(),
+            "⏱️ That took too long to answer. Try again in a moment.",
+        ),
+        (
+            failed(StatusCode::SERVICE_UNAVAILABLE),
+            "⚠️ The market data provider is busy or down right now. Try again in a minute.",
+        ),
+        (
+            failed(StatusCode::TOO_MANY_REQUESTS),
+            "⚠️ The market data provider is busy or down right now. Try again in a minute.",
+        ),
+        (
+            failed(StatusCode::FORBIDDEN),
+            "⚠️ The market data provider couldn't answer that request.",
+        ),
+        (
+            anyhow!("WRONGTYPE Operation against a key holding the wrong kind of value"),
+            "⚠️ Something went wrong running that command. It's been logged.",
+        ),
+    ];
+    for (error, expected) in cases {
+        assert_eq!(error_reply(Locale::En, &error), expected, "{error:?}");
+    }
+}
+
+#[test]
+fn causes_are_found_under_context() {
+    let error = Err::<(), _>(failed(StatusCode::BAD_GATEWAY))
+        .context("fetching bars for the graph")
+        .unwrap_err();
+    let reply = error_reply(Locale::En, &error);
+    assert!(reply.contains("busy or down"), "{reply}");
+    assert!(!reply.contains("secret detail"));
+}
+
+#[test]
+fn replies_are_translated() {
+    let error = InvalidSymbol("??".into()).into();
+    assert_eq!(
+        error_reply(Locale::Th, &error),
+        "❌ `??` ไม่ใช่สัญลักษณ์หุ้นที่ถูกต้อง"
+    );
+}
@@ -0,0 +1,173 @@
+//! Shared helpers for the bot integration tests.
+//!
+//! Redis-backed tests need a real server: set `TEST_REDIS_URL` to run them,
+//! otherwise they return early so plain `cargo test` passes offline.
+
+#![allow(dead_code)]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bot::{
+    Error,
+    batch::{Batch, BatchSink},
+};
+use serde_json::{Value, json};
+use serenity::all::{CreateAttachment, CreateEmbed, Http, HttpBuilder};
+use stock::SymbolStore;
+use wiremock::MockServer;
+
+#[derive(Debug, PartialEq)]
+pub enum Sent {
+    Batch(usize),
+    Notice(String),
+    Ack,
+}
+
+/// Records everything the batcher sends instead of talking to Discord.
+#[derive(Clone, Default)]
+pub struct MockSink {
+    pub sent: Arc<Mutex<Vec<Sent>>>,
+    /// Attachment bytes of each batch, in send order.
+    pub bytes: Arc<Mutex<Vec<usize>>>,
+    /// Footer text of every embed that had one, in send order.
+    pub footers: Arc<Mutex<Vec<String>>>,
+    /// Rows of buttons under each batch, in send order.
+    pub components: Arc<Mutex<Vec<usize>>>,
+    /// Hit symbols of each batch, in send order.
+    pub symbols: Arc<Mutex<Vec<Vec<Option<String>>>>>,
+    /// Symbols of the events carried by each batch, in send order.
+    pub events: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+impl MockSink {
+    pub fn sent(&self) -> Vec<Sent> {
+        std::mem::take(&mut self.sent.lock().unwrap())
+    }
+
+    pub fn bytes(&self) -> Vec<usize> {
+        std::mem::take(&mut self.bytes.lock().unwrap())
+    }
+
+    pub fn footers(&self) -> Vec<String> {
+        std::mem::take(&mut self.footers.lock().unwrap())
+    }
+
+    pub fn components(&self) -> Vec<usize> {
+        std::mem::take(&mut self.components.lock().unwrap())
+    }
+
+    pub fn symbols(&self) -> Vec<Vec<Option<String>>> {
+        std::mem::take(&mut self.symbols.lock().unwrap())
+    }
+
+    pub fn events(&self) -> Vec<Vec<String>> {
+        std::mem::take(&mut self.events.lock().unwrap())
+    }
+}
+
+impl BatchSink for MockSink {
+    async fn send_batch(&self, batch: Batch) -> Result<(), Error> {
+        self.bytes.lock().unwrap().push(batch.bytes());
+        self.components.lock().unwrap().push(batch.components.len());
+        self.symbols.lock().unwrap().push(batch.symbols.clone());
+        self.events
+            .lock()
+            .unwrap()
+            .push(batch.events.iter().map(|e| e.symbol.clone()).collect());
+        for embed in &batch.embeds {
+            let json = serde_json::to_value(embed)?;
+            if let Some(text) = json["footer"]["text"].as_str() {
+                self.footers.lock().unwrap().push(text.to_string());
+            }
+        }
+        self.sent
+            .lock()
+            .unwrap()
+            .push(Sent::Batch(batch.embeds.len()));
+        Ok(())
+    }
+
+    async fn send_notice(&self, content: String) -> Result<(), Error> {
+        self.sent.lock().unwrap().push(Sent::Notice(content));
+        Ok(())
+    }
+
+    async fn acknowledge(&self) -> Result<(), Error> {
+        self.sent.lock().unwrap().push(Sent::Ack);
+        Ok(())
+    }
+}
+
+pub fn hit(n: usize) -> (CreateEmbed, CreateAttachment) {
+    sized_hit(n, 16)
+}
+
+/// A hit whose chart is `bytes` long.
+pub fn sized_hit(n: usize, bytes: usize) -> (CreateEmbed, CreateAttachment) {
+    (
+        CreateEmbed::default(),
+        CreateAttachment::bytes(vec![0u8; bytes], format!("{n}.png")),
+    )
+}
+
+/// A store under a fresh key prefix, or None when `TEST_REDIS_URL` isn't set.
+pub async fn redis_store() -> Option<SymbolStore> {
+    let Ok(url) = std::env::var("TEST_REDIS_URL") else {
+        eprintln!("TEST_REDIS_URL not set, skipping");
+        return None;
+    };
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let prefix = format!("test:{}:{}", std::process::id(), nanos);
+
+    Some(
+        SymbolStore::new(&url, prefix)
+            .await
+            .expect("connect to TEST_REDIS_URL"),
+    )
+}
+
+/// Discord's REST API served by `discord`, unthrottled.
+pub fn discord_http(discord: &MockServer) -> Arc<Http> {
+    Arc::new(
+        HttpBuilder::new("test")
+            .proxy(discord.uri())
+            .ratelimiter_disabled(true)
+            .build(),
+    )
+}
+
+/// A message as Discord returns it for a post to `channel`.
+pub fn message_json(channel: u64) -> Value {
+    json!({
+        "id": "1",
+        "channel_id": channel.to_string(),
+        "author": {
+            "id": "2",
+            "username": "bot",
+            "discriminator": "0000",
+            "global_name": null,
+            "avatar": null,
+            "bot": true
+        },
+        "content": "",
+        "timestamp": "2026-10-16T20:30:00.000000+00:00",
+        "edited_timestamp": null,
+        "tts": false,
+        "mention_everyone": false,
+        "mentions": [],
+        "mention_roles": [],
+        "attachments": [],
+        "embeds": [],
+        "pinned": false,
+        "type": 0,
+        "flags": 0,
+        "components": []
+    })
+}
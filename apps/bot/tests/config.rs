@@ -0,0 +1,41 @@
+use bot::config::{parse_flag, parse_user_ids};
+
+#[test]
+fn flags_fall_back_to_the_default_when_unset() {
+    assert!(parse_flag(None, true).unwrap());
+    assert!(!parse_flag(None, false).unwrap());
+    assert!(parse_flag(Some(""), true).unwrap());
+    assert!(!parse_flag(Some("  "), false).unwrap());
+}
+
+#[test]
+fn flags_accept_common_spellings() {
+    for raw in ["true", "TRUE", "1", "yes", "on", " On "] {
+        assert!(
+            parse_flag(Some(raw), false).unwrap(),
+            "{raw:?} should be on"
+        );
+    }
+    for raw in ["false", "False", "0", "no", "off"] {
+        assert!(
+            !parse_flag(Some(raw), true).unwrap(),
+            "{raw:?} should be off"
+        );
+    }
+}
+
+#[test]
+fn unrecognised_flags_are_rejected() {
+    for raw in ["maybe", "flase", "2"] {
+        assert!(parse_flag(Some(raw), true).is_err(), "{raw:?} accepted");
+    }
+}
+
+#[test]
+fn owner_ids_parse_as_a_list() {
+    assert_eq!(parse_user_ids("").unwrap(), Vec::<u64>::new());
+    assert_eq!(parse_user_ids(" 1, 22 ,1,").unwrap(), vec![1, 22]);
+    for raw in ["abc", "1;2", "0", "-4"] {
+        assert!(parse_user_ids(raw).is_err(), "{raw:?} accepted");
+    }
+}
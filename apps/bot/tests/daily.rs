@@ -0,0 +1,188 @@
+mod common;
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{Result, bail};
+use bot::{
+    batch::DEFAULT_MAX_BYTES,
+    config::Config,
+    daily::{Target, run_daily},
+    registration::CommandScope,
+};
+use futures::future::BoxFuture;
+use serde_json::json;
+use serenity::all::{ChannelId, GuildId};
+use stock::{
+    Bar, ChartRenderer, PriceSource, Session, Snapshot, SymbolStore, Timeframe, earnings,
+    indicators::cdc::{DEFAULT_BAND_PCT, DEFAULT_MIN_CHART_BARS, SignalColors},
+    report::RunArchive,
+    settings::GuildSettings,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path_regex},
+};
+
+use common::{discord_http, message_json, redis_store};
+
+const CHANNEL: u64 = 10;
+
+/// A source with no data; the runs here scan an empty watchlist.
+struct NoPrices;
+
+impl PriceSource for NoPrices {
+    fn fetch_price<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _duration: chrono::Duration,
+        _timeframe: Timeframe,
+        _limit: usize,
+        _bypass_cache: bool,
+        _session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async { Ok(vec![]) })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        _symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(async { Ok(HashMap::new()) })
+    }
+}
+
+fn config() -> Config {
+    Config {
+        discord_token: "test".into(),
+        version: "test".into(),
+        trigger_cooldown: Duration::ZERO,
+        max_message_bytes: DEFAULT_MAX_BYTES,
+        // an empty scan still posts, so every run is seen
+        announce_empty_scans: true,
+        message_content_intent: false,
+        webhook_url: None,
+        webhook_secret: None,
+        signal_band_pct: DEFAULT_BAND_PCT,
+        min_chart_bars: DEFAULT_MIN_CHART_BARS,
+        signal_colors: SignalColors::default(),
+        daily_enabled: true,
+        weekly_confirmation: false,
+        daily_prewarm: false,
+        supersede_days: 0,
+        earnings_within_days: earnings::DEFAULT_WITHIN_DAYS,
+        api_token: None,
+        api_addr: "127.0.0.1:0".into(),
+        command_scope: CommandScope::Global,
+        owner_ids: vec![],
+    }
+}
+
+/// Answers every post to a channel with `response`.
+async fn mount_posts(discord: &MockServer, response: ResponseTemplate) {
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/api/v10/channels/\d+/messages$"))
+        .respond_with(response)
+        .mount(discord)
+        .await;
+}
+
+async fn posts(discord: &MockServer) -> usize {
+    discord.received_requests().await.unwrap_or_default().len()
+}
+
+async fn run(discord: &MockServer, store: &Arc<SymbolStore>) -> Result<()> {
+    let renderer =
+        ChartRenderer::with_render_fn(1, Duration::from_secs(5), |_| bail!("no charts in tests"))?;
+
+    run_daily(
+        discord_http(discord),
+        Arc::new(NoPrices),
+        Arc::new(renderer),
+        store.clone(),
+        config(),
+        None,
+        Arc::new(RunArchive::Off),
+        None,
+        Some(Target {
+            guild_id: GuildId::new(1),
+            channel: ChannelId::new(CHANNEL),
+            resumed: false,
+        }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn a_second_run_the_same_day_sends_nothing() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let store = Arc::new(store);
+    let discord = MockServer::start().await;
+    mount_posts(
+        &discord,
+        ResponseTemplate::new(200).set_body_json(message_json(CHANNEL)),
+    )
+    .await;
+
+    run(&discord, &store).await.unwrap();
+    let first = posts(&discord).await;
+    assert!(first > 0);
+
+    run(&discord, &store).await.unwrap();
+    assert_eq!(posts(&discord).await, first);
+}
+
+#[tokio::test]
+async fn a_failed_run_can_be_retried_the_same_day() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let store = Arc::new(store);
+    let discord = MockServer::start().await;
+    let rejected = json!({"code": 50035, "message": "Invalid Form Body"});
+    mount_posts(&discord, ResponseTemplate::new(400).set_body_json(rejected)).await;
+
+    run(&discord, &store).await.unwrap();
+    assert!(posts(&discord).await > 0);
+
+    discord.reset().await;
+    mount_posts(
+        &discord,
+        ResponseTemplate::new(200).set_body_json(message_json(CHANNEL)),
+    )
+    .await;
+    run(&discord, &store).await.unwrap();
+    assert!(posts(&discord).await > 0);
+}
+
+#[tokio::test]
+async fn an_expired_pause_is_kept_until_a_run_goes_out() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let store = Arc::new(store);
+    let settings = GuildSettings {
+        daily_channel: Some(CHANNEL),
+        daily_paused_until: Some(chrono::Utc::now() - chrono::Duration::days(1)),
+        ..Default::default()
+    };
+    store.set_guild_settings(1, &settings).await.unwrap();
+    let discord = MockServer::start().await;
+    let rejected = json!({"code": 50035, "message": "Invalid Form Body"});
+    mount_posts(&discord, ResponseTemplate::new(400).set_body_json(rejected)).await;
+
+    run(&discord, &store).await.unwrap();
+    let kept = store.get_guild_settings(1).await.unwrap();
+    assert_eq!(kept.daily_paused_until, settings.daily_paused_until);
+
+    discord.reset().await;
+    mount_posts(
+        &discord,
+        ResponseTemplate::new(200).set_body_json(message_json(CHANNEL)),
+    )
+    .await;
+    run(&discord, &store).await.unwrap();
+    let cleared = store.get_guild_settings(1).await.unwrap();
+    assert_eq!(cleared.daily_paused_until, None);
+}
@@ -0,0 +1,56 @@
+use anyhow::anyhow;
+use bot::{
+    command::stock::delete::{expires_line, impact_line, outcome_message},
+    i18n::Locale,
+};
+use chrono::{Duration, TimeZone, Utc};
+use stock::{DeleteOutcome, SymbolImpact, SymbolMeta};
+
+fn impact(symbol: &str, alerts: usize, added_days_ago: Option<i64>) -> SymbolImpact {
+    let now = Utc::now();
+    SymbolImpact {
+        symbol: symbol.into(),
+        watched: true,
+        alert_ids: (0..alerts).map(|i| format!("a{i}")).collect(),
+        meta: added_days_ago.map(|days| SymbolMeta {
+            added_at: Some(now - Duration::days(days)),
+            ..SymbolMeta::default()
+        }),
+        has_reading: false,
+    }
+}
+
+#[test]
+fn impact_line_names_alerts_and_age() {
+    let line = impact_line(Locale::En, &impact("TSLA", 2, Some(84)), Utc::now());
+    assert_eq!(line, "TSLA — 2 alerts, added 84 days ago");
+}
+
+#[test]
+fn impact_line_says_when_nothing_else_goes() {
+    let line = impact_line(Locale::En, &impact("AAPL", 0, None), Utc::now());
+    assert_eq!(line, "AAPL — nothing else");
+}
+
+#[test]
+fn outcome_message_counts_cleanup_and_reports_failures() {
+    let mut outcome = DeleteOutcome::default();
+    outcome.record(&impact("TSLA", 2, Some(1)), Ok(()));
+    outcome.record(&impact("AAPL", 1, None), Err(anyhow!("timeout")));
+
+    let message = outcome_message(Locale::En, &outcome);
+    assert_eq!(
+        message,
+        "TSLA was deleted.\nAlso removed 3 related records.\n⚠️ Couldn't delete: AAPL"
+    );
+}
+
+#[test]
+fn the_prompt_says_when_the_request_expires() {
+    let now = Utc.with_ymd_and_hms(2024, 6, 17, 14, 0, 0).unwrap();
+    let window = std::time::Duration::from_secs(600);
+    assert_eq!(
+        expires_line(Locale::En, now, window),
+        format!("⏳ This request expires <t:{}:R>.", now.timestamp() + 600)
+    );
+}
@@ -0,0 +1,87 @@
+use bot::{
+    digest::{DigestHit, assemble, unscanned},
+    i18n::Locale,
+};
+use std::collections::HashSet;
+
+use stock::indicators::cdc::Signal;
+
+fn hit(symbol: &str, signal: Signal, close: f64, link: Option<&str>) -> DigestHit {
+    DigestHit {
+        link: link.map(str::to_string),
+        ..DigestHit::new(symbol, signal, close)
+    }
+}
+
+fn watchlist(symbols: &[&str]) -> Vec<String> {
+    symbols.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn digest_lists_only_hits_on_the_watchlist() {
+    let hits = [
+        hit("TSLA", Signal::Sell, 250.0, None),
+        hit(
+            "AAPL",
+            Signal::Buy,
+            182.5,
+            Some("https://discord.com/channels/1/2/3"),
+        ),
+        hit("MSFT", Signal::Buy, 410.0, None),
+    ];
+
+    let text = assemble(Locale::En, &hits, &watchlist(&["aapl", "TSLA", "NVDA"])).unwrap();
+    assert_eq!(
+        text,
+        "📬 **Today's signals on your watchlist** (2)\n\
+         • **AAPL** Buy at $182.50 · [view post](https://discord.com/channels/1/2/3)\n\
+         • **TSLA** Sell at $250.00"
+    );
+}
+
+#[test]
+fn no_overlap_means_no_digest() {
+    let hits = [hit("MSFT", Signal::Buy, 410.0, None)];
+    assert_eq!(assemble(Locale::En, &hits, &watchlist(&["AAPL"])), None);
+    assert_eq!(assemble(Locale::En, &[], &watchlist(&["AAPL"])), None);
+    assert_eq!(assemble(Locale::En, &hits, &[]), None);
+}
+
+#[test]
+fn symbol_hit_in_several_servers_is_listed_once_with_a_link() {
+    let hits = [
+        hit("AAPL", Signal::Buy, 182.5, None),
+        hit(
+            "AAPL",
+            Signal::Buy,
+            182.5,
+            Some("https://discord.com/channels/9/8/7"),
+        ),
+        hit(
+            "AAPL",
+            Signal::Buy,
+            182.5,
+            Some("https://discord.com/channels/1/2/3"),
+        ),
+    ];
+
+    let text = assemble(Locale::En, &hits, &watchlist(&["AAPL"])).unwrap();
+    assert_eq!(text.lines().count(), 2, "{text}");
+    assert!(text.contains("(1)"), "{text}");
+    assert!(text.contains("channels/9/8/7"), "{text}");
+}
+
+#[test]
+fn digest_is_localized() {
+    let hits = [hit("AAPL", Signal::Buy, 182.5, None)];
+    let text = assemble(Locale::Th, &hits, &watchlist(&["AAPL"])).unwrap();
+    assert!(text.contains("ซื้อ"), "{text}");
+}
+
+#[test]
+fn only_symbols_no_server_scanned_are_scanned_again() {
+    let scanned: HashSet<String> = ["AAPL".to_string()].into();
+    let watchlists = [watchlist(&["TSLA", "AAPL"]), watchlist(&["tsla", "NVDA"])];
+    assert_eq!(unscanned(&watchlists, &scanned), ["NVDA", "TSLA"]);
+    assert!(unscanned(&[watchlist(&["aapl"])], &scanned).is_empty());
+}
@@ -0,0 +1,147 @@
+use bot::discord_text::{
+    CONTENT_LIMIT, DESCRIPTION_LIMIT, EMBED_LIMIT, EmbedOverflow, EmbedPart, FIELD_VALUE_LIMIT,
+    MAX_FIELDS, TITLE_LIMIT, check_embed, split_content, truncate_field,
+};
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+
+fn chars(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[test]
+fn content_at_the_limit_is_one_chunk() {
+    let content = "a".repeat(CONTENT_LIMIT);
+    assert_eq!(split_content(&content, CONTENT_LIMIT), [content]);
+}
+
+#[test]
+fn content_one_over_the_limit_splits_between_lines() {
+    let first = "a".repeat(CONTENT_LIMIT - 1);
+    let content = format!("{first}\nb");
+    assert_eq!(chars(&content), CONTENT_LIMIT + 1);
+
+    assert_eq!(
+        split_content(&content, CONTENT_LIMIT),
+        [first, "b".to_string()]
+    );
+}
+
+#[test]
+fn lines_are_packed_up_to_the_limit() {
+    let line = "x".repeat(9);
+    let content = [line.as_str(); 4].join("\n");
+    // two lines plus a newline is 19; a third would make 29
+    let chunks = split_content(&content, 20);
+    assert_eq!(
+        chunks,
+        [format!("{line}\n{line}"), format!("{line}\n{line}")]
+    );
+}
+
+#[test]
+fn mentions_are_never_split() {
+    let mention = "<@123456789012345678>";
+    let content = vec![mention; 200].join(" ");
+    let chunks = split_content(&content, CONTENT_LIMIT);
+
+    assert!(chunks.len() > 1);
+    for chunk in &chunks {
+        assert!(chars(chunk) <= CONTENT_LIMIT);
+        assert!(chunk.split(' ').all(|word| word == mention), "{chunk}");
+    }
+    assert_eq!(chunks.join(" "), content);
+}
+
+#[test]
+fn pathological_long_line_splits_between_words() {
+    let content = "word ".repeat(600).trim_end().to_string();
+    assert_eq!(chars(&content), 2999);
+
+    let chunks = split_content(&content, CONTENT_LIMIT);
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks.iter().all(|c| chars(c) <= CONTENT_LIMIT));
+    assert!(chunks.iter().all(|c| c.split(' ').all(|w| w == "word")));
+    assert_eq!(chunks.join(" "), content);
+}
+
+#[test]
+fn a_single_3000_character_word_is_hard_split() {
+    let content = "é".repeat(3000);
+    let chunks = split_content(&content, CONTENT_LIMIT);
+
+    assert_eq!(
+        chunks.iter().map(|c| chars(c)).collect::<Vec<_>>(),
+        [CONTENT_LIMIT, 1000]
+    );
+    assert_eq!(chunks.concat(), content);
+}
+
+#[test]
+fn empty_content_has_no_chunks() {
+    assert!(split_content("", CONTENT_LIMIT).is_empty());
+}
+
+#[test]
+fn truncate_leaves_text_at_the_limit_alone() {
+    let text = "a".repeat(FIELD_VALUE_LIMIT);
+    assert_eq!(truncate_field(&text, FIELD_VALUE_LIMIT), text);
+}
+
+#[test]
+fn truncate_adds_an_ellipsis_one_over_the_limit() {
+    let text = "ก".repeat(FIELD_VALUE_LIMIT + 1);
+    let cut = truncate_field(&text, FIELD_VALUE_LIMIT);
+
+    assert_eq!(chars(&cut), FIELD_VALUE_LIMIT);
+    assert!(cut.ends_with('…'));
+    assert!(cut.starts_with(&"ก".repeat(FIELD_VALUE_LIMIT - 1)));
+}
+
+#[test]
+fn embeds_within_limits_pass() {
+    let embed = CreateEmbed::default()
+        .title("a".repeat(TITLE_LIMIT))
+        .description("b".repeat(DESCRIPTION_LIMIT))
+        .field("name", "c".repeat(FIELD_VALUE_LIMIT), false)
+        .footer(CreateEmbedFooter::new("footer"));
+    assert_eq!(check_embed(&embed), Ok(()));
+}
+
+#[test]
+fn overflowing_parts_are_named() {
+    let title = CreateEmbed::default().title("a".repeat(TITLE_LIMIT + 1));
+    assert_eq!(
+        check_embed(&title),
+        Err(EmbedOverflow {
+            part: EmbedPart::Title,
+            len: TITLE_LIMIT + 1,
+            limit: TITLE_LIMIT,
+        })
+    );
+
+    let field = CreateEmbed::default().field("ok", "fine", false).field(
+        "long",
+        "v".repeat(FIELD_VALUE_LIMIT + 1),
+        false,
+    );
+    assert_eq!(
+        check_embed(&field).unwrap_err().part,
+        EmbedPart::FieldValue(1)
+    );
+
+    let fields =
+        CreateEmbed::default().fields((0..=MAX_FIELDS).map(|i| (i.to_string(), "v", true)));
+    assert_eq!(check_embed(&fields).unwrap_err().part, EmbedPart::Fields);
+}
+
+#[test]
+fn total_text_is_capped_across_parts() {
+    let embed = CreateEmbed::default()
+        .description("d".repeat(DESCRIPTION_LIMIT))
+        .fields((0..2).map(|i| (i.to_string(), "v".repeat(FIELD_VALUE_LIMIT), false)));
+    let err = check_embed(&embed).unwrap_err();
+
+    assert_eq!(err.part, EmbedPart::Total);
+    assert_eq!(err.len, DESCRIPTION_LIMIT + 2 * (1 + FIELD_VALUE_LIMIT));
+    assert_eq!(err.limit, EMBED_LIMIT);
+}
@@ -0,0 +1,85 @@
+use bot::{
+    command::stock::dividends::{action_lines, dividend_rate, split_ratio},
+    i18n::Locale,
+};
+use chrono::NaiveDate;
+use stock::corporate_actions::{CorporateActions, Dividend, Split};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn dividend(ex_date: NaiveDate, rate: f64) -> Dividend {
+    Dividend {
+        symbol: "AAPL".into(),
+        rate,
+        special: false,
+        foreign: false,
+        ex_date,
+        record_date: None,
+        payable_date: None,
+    }
+}
+
+fn split(ex_date: NaiveDate, new_rate: f64, old_rate: f64) -> Split {
+    Split {
+        symbol: "AAPL".into(),
+        new_rate,
+        old_rate,
+        ex_date,
+        payable_date: None,
+    }
+}
+
+#[test]
+fn split_ratios_read_new_for_old() {
+    let today = date(2026, 10, 16);
+    assert_eq!(split_ratio(&split(today, 4.0, 1.0)), "4-for-1");
+    assert_eq!(split_ratio(&split(today, 1.0, 10.0)), "1-for-10");
+    assert_eq!(split_ratio(&split(today, 3.0, 2.0)), "3-for-2");
+}
+
+#[test]
+fn dividend_rates_keep_finer_digits() {
+    assert_eq!(dividend_rate(0.26), "$0.26");
+    assert_eq!(dividend_rate(1.0), "$1.00");
+    assert_eq!(dividend_rate(0.0125), "$0.0125");
+}
+
+#[test]
+fn actions_split_into_upcoming_and_recent() {
+    let today = date(2026, 10, 16);
+    let mut paid = dividend(date(2026, 11, 10), 0.26);
+    paid.payable_date = Some(date(2026, 11, 13));
+    let actions = CorporateActions {
+        dividends: vec![
+            dividend(date(2026, 5, 12), 0.25),
+            dividend(date(2026, 8, 11), 0.26),
+            paid,
+        ],
+        splits: vec![split(date(2026, 9, 1), 1.0, 10.0)],
+    };
+
+    let (upcoming, recent) = action_lines(Locale::En, &actions, today);
+
+    assert_eq!(
+        upcoming,
+        vec!["`2026-11-10` dividend **$0.26** a share, paid `2026-11-13`"]
+    );
+    assert_eq!(
+        recent,
+        vec![
+            "`2026-09-01` 1-for-10 reverse split",
+            "`2026-08-11` dividend **$0.26** a share",
+            "`2026-05-12` dividend **$0.25** a share",
+        ]
+    );
+}
+
+#[test]
+fn no_actions_means_no_lines() {
+    let (upcoming, recent) =
+        action_lines(Locale::Th, &CorporateActions::default(), date(2026, 10, 16));
+    assert!(upcoming.is_empty());
+    assert!(recent.is_empty());
+}
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use bot::fmt::{TimeStyle, bytes, elapsed, price, signed_pct, time, uptime, volume};
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::{
+    America::{Los_Angeles, New_York},
+    Asia::Tokyo,
+    Europe::London,
+};
+use stock::Timeframe;
+
+#[test]
+fn uptime_uses_the_largest_units() {
+    assert_eq!(uptime(Duration::from_secs(0)), "0s");
+    assert_eq!(uptime(Duration::from_secs(59)), "59s");
+    assert_eq!(uptime(Duration::from_secs(5 * 60 + 12)), "5m 12s");
+    assert_eq!(uptime(Duration::from_secs(4 * 3600)), "4h 0m");
+    assert_eq!(
+        uptime(Duration::from_secs(3 * 86_400 + 4 * 3600 + 5 * 60 + 59)),
+        "3d 4h 5m"
+    );
+}
+
+#[test]
+fn elapsed_switches_to_seconds_at_one_second() {
+    assert_eq!(elapsed(Duration::from_millis(0)), "0ms");
+    assert_eq!(elapsed(Duration::from_micros(999_999)), "999ms");
+    assert_eq!(elapsed(Duration::from_secs(1)), "1.00s");
+    assert_eq!(elapsed(Duration::from_millis(12_345)), "12.35s");
+}
+
+#[test]
+fn prices_group_thousands() {
+    assert_eq!(price(0.0), "$0.00");
+    assert_eq!(price(9.999), "$10.00");
+    assert_eq!(price(182.5), "$182.50");
+    assert_eq!(price(1234.5), "$1,234.50");
+    assert_eq!(price(612_345.678), "$612,345.68");
+    assert_eq!(price(-1234.5), "-$1,234.50");
+    assert_eq!(price(-0.001), "$0.00");
+}
+
+#[test]
+fn percentages_carry_a_sign() {
+    assert_eq!(signed_pct(1.25), "+1.2%");
+    assert_eq!(signed_pct(-3.96), "-4.0%");
+    assert_eq!(signed_pct(0.04), "0.0%");
+    assert_eq!(signed_pct(-0.04), "0.0%");
+}
+
+#[test]
+fn volumes_scale_to_units() {
+    assert_eq!(volume(850.0), "850");
+    assert_eq!(volume(0.35), "0.35");
+    assert_eq!(volume(12.5), "12.5");
+    assert_eq!(volume(0.999), "1");
+    assert_eq!(volume(12_500.0), "12.5K");
+    assert_eq!(volume(3_240_000.0), "3.2M");
+    assert_eq!(volume(1_500_000_000.0), "1.5B");
+}
+
+#[test]
+fn bytes_use_binary_units() {
+    assert_eq!(bytes(0), "0 B");
+    assert_eq!(bytes(1023), "1023 B");
+    assert_eq!(bytes(1536), "1.5 KiB");
+    assert_eq!(bytes(3 * 1024 * 1024 + 200 * 1024), "3.2 MiB");
+    assert_eq!(bytes(5 << 40), "5.0 TiB");
+}
+
+fn utc(m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, m, d, h, min, 0).unwrap()
+}
+
+const HOURLY: TimeStyle = TimeStyle::Axis(Timeframe::Hour1);
+const DAILY: TimeStyle = TimeStyle::Axis(Timeframe::Day1);
+
+#[test]
+fn intraday_axis_skips_the_spring_forward_hour() {
+    // 2024-03-10 02:00 EST jumps to 03:00 EDT, at 07:00 UTC
+    assert_eq!(time(utc(3, 10, 6, 30), New_York, HOURLY), "03-10 01:30");
+    assert_eq!(time(utc(3, 10, 7, 30), New_York, HOURLY), "03-10 03:30");
+    // the open is 09:30 on both sides, an hour apart in UTC
+    assert_eq!(time(utc(3, 8, 14, 30), New_York, HOURLY), "03-08 09:30");
+    assert_eq!(time(utc(3, 11, 13, 30), New_York, HOURLY), "03-11 09:30");
+}
+
+#[test]
+fn intraday_axis_repeats_the_fall_back_hour() {
+    // 2024-11-03 02:00 EDT falls back to 01:00 EST, at 06:00 UTC
+    assert_eq!(time(utc(11, 3, 5, 30), New_York, HOURLY), "11-03 01:30");
+    assert_eq!(time(utc(11, 3, 6, 30), New_York, HOURLY), "11-03 01:30");
+    assert_eq!(time(utc(11, 3, 7, 30), New_York, HOURLY), "11-03 02:30");
+}
+
+#[test]
+fn intraday_axis_follows_the_chosen_zone() {
+    // London moves on 2024-03-31, three weeks after New York
+    let bar = utc(3, 20, 13, 30);
+    assert_eq!(time(bar, New_York, HOURLY), "03-20 09:30");
+    assert_eq!(time(bar, London, HOURLY), "03-20 13:30");
+    assert_eq!(time(utc(4, 2, 13, 30), London, HOURLY), "04-02 14:30");
+    assert_eq!(time(bar, Tokyo, HOURLY), "03-20 22:30");
+}
+
+#[test]
+fn daily_axis_keeps_the_session_date_in_any_zone() {
+    // Alpaca stamps daily bars at midnight New York time, EST then EDT
+    for bar in [utc(3, 8, 5, 0), utc(3, 11, 4, 0)] {
+        let session = time(bar, New_York, DAILY);
+        assert_eq!(time(bar, Los_Angeles, DAILY), session);
+        assert_eq!(time(bar, Tokyo, DAILY), session);
+    }
+    assert_eq!(time(utc(3, 8, 5, 0), Los_Angeles, DAILY), "2024-03-08");
+    assert_eq!(time(utc(3, 11, 4, 0), Los_Angeles, DAILY), "2024-03-11");
+}
+
+#[test]
+fn discord_styles_ignore_the_zone() {
+    let at = utc(3, 10, 7, 30);
+    let unix = at.timestamp();
+    for tz in [New_York, Tokyo] {
+        assert_eq!(time(at, tz, TimeStyle::Relative), format!("<t:{unix}:R>"));
+        assert_eq!(time(at, tz, TimeStyle::Full), format!("<t:{unix}:f>"));
+        assert_eq!(time(at, tz, TimeStyle::Date), format!("<t:{unix}:D>"));
+    }
+}
+
+#[test]
+fn plain_style_names_the_zone() {
+    assert_eq!(
+        time(utc(3, 10, 6, 30), New_York, TimeStyle::Plain),
+        "2024-03-10 01:30 EST"
+    );
+    assert_eq!(
+        time(utc(3, 10, 7, 30), New_York, TimeStyle::Plain),
+        "2024-03-10 03:30 EDT"
+    );
+}
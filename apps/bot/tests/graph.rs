@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use bot::{command::stock::graph::graph_message, i18n::Locale};
+use stock::{
+    ChartJob, ChartRenderer,
+    calendar::DEFAULT_TIMEZONE,
+    indicators::{cdc::Signal, donchian::Breakout},
+};
+
+fn job() -> ChartJob {
+    ChartJob {
+        symbol: "AAPL".into(),
+        closes: [1.0, 2.0, 3.0].into(),
+        ema12: [1.0, 2.0].into(),
+        ema26: [1.0, 2.0, 3.0].into(),
+        dates: ["a".into(), "b".into(), "c".into()].into(),
+        options: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn a_failed_render_still_answers_with_the_signal() {
+    let renderer = ChartRenderer::with_render_fn(1, Duration::from_secs(5), |job| {
+        bail!(
+            "length mismatch: {} closes, {} ema12",
+            job.closes.len(),
+            job.ema12.len()
+        )
+    })
+    .unwrap();
+    let chart = renderer.render(job()).await.ok();
+
+    let (embed, attachment) = graph_message(
+        Locale::En,
+        "aapl",
+        Signal::Buy,
+        Breakout::None,
+        chart,
+        DEFAULT_TIMEZONE,
+    );
+    assert!(attachment.is_none());
+    let json = serde_json::to_value(&embed).unwrap();
+    assert_eq!(json["title"], "AAPL Analysis");
+    assert_eq!(json["description"], "Current Signal: Buy");
+    assert!(json.get("image").is_none());
+    let footer = json["footer"]["text"].as_str().unwrap();
+    assert!(footer.ends_with(" · Chart unavailable"), "{footer}");
+}
+
+#[test]
+fn a_rendered_chart_is_attached() {
+    let (embed, attachment) = graph_message(
+        Locale::En,
+        "AAPL",
+        Signal::Sell,
+        Breakout::Bearish,
+        Some(vec![1, 2, 3]),
+        DEFAULT_TIMEZONE,
+    );
+    let attachment = attachment.expect("chart attached");
+    assert_eq!(attachment.filename, "AAPL_SELL_chart.png");
+    let json = serde_json::to_value(&embed).unwrap();
+    assert_eq!(json["image"]["url"], "attachment://AAPL_SELL_chart.png");
+    assert!(
+        json["description"].as_str().unwrap().contains("20-day low"),
+        "{json}"
+    );
+    assert!(
+        !json["footer"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("unavailable")
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use bot::{
+    batch::{MAX_EMBEDS, MessageBatcher},
+    command::stock::graphs::{GraphsRequest, MAX_GRAPHS, in_request_order},
+    i18n::Locale,
+    report::hit_message,
+    style::SignalStyle,
+};
+use chrono::Utc;
+use stock::{
+    DataSource, Timeframe, calendar::DEFAULT_TIMEZONE, indicators::cdc::Signal, scan::ScanHit,
+};
+
+use common::{MockSink, Sent};
+
+fn chart(symbol: &str) -> ScanHit {
+    ScanHit {
+        symbol: symbol.into(),
+        signal: Signal::BullishZone,
+        close: 10.0,
+        timestamp: Utc::now(),
+        timeframe: Timeframe::Day1,
+        source: DataSource::Bars,
+        chart: vec![0u8; 16],
+        chart_failed: false,
+        fallback: None,
+    }
+}
+
+#[test]
+fn the_list_is_capped_in_input_order() {
+    let raw: Vec<String> = (0..MAX_GRAPHS + 2).map(|n| format!("S{n}")).collect();
+    let request = GraphsRequest::parse(&format!("{}, s0, 1bad", raw.join(",")));
+
+    assert_eq!(request.symbols, raw[..MAX_GRAPHS]);
+    assert_eq!(request.over_cap, raw[MAX_GRAPHS..]);
+    assert_eq!(request.invalid, vec!["1BAD"]);
+}
+
+#[test]
+fn a_short_list_is_kept_whole() {
+    let request = GraphsRequest::parse("aapl, msft");
+    assert_eq!(request.symbols, vec!["AAPL", "MSFT"]);
+    assert!(request.over_cap.is_empty() && request.invalid.is_empty());
+}
+
+#[test]
+fn charts_come_back_in_the_order_asked() {
+    let asked = vec!["MSFT".to_string(), "AAPL".into(), "NVDA".into()];
+    let hits = vec![chart("NVDA"), chart("AAPL"), chart("MSFT")];
+    let ordered: Vec<String> = in_request_order(&asked, hits)
+        .into_iter()
+        .map(|h| h.symbol)
+        .collect();
+    assert_eq!(ordered, asked);
+}
+
+#[tokio::test]
+async fn every_chart_is_sent_ten_to_a_message() {
+    let sink = MockSink::default();
+    let mut batcher = MessageBatcher::new(sink.clone());
+    let style = SignalStyle::default();
+
+    for n in 0..MAX_GRAPHS {
+        let (embed, attachment) = hit_message(
+            Locale::En,
+            chart(&format!("S{n}")),
+            None,
+            &style,
+            DEFAULT_TIMEZONE,
+        );
+        assert!(attachment.is_some());
+        batcher.push(embed, attachment, None).await.unwrap();
+    }
+    batcher.finish("nothing".into(), false, None).await.unwrap();
+
+    assert_eq!(
+        sink.sent(),
+        vec![
+            Sent::Batch(MAX_EMBEDS),
+            Sent::Batch(MAX_GRAPHS - MAX_EMBEDS)
+        ]
+    );
+}
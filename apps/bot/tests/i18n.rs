@@ -0,0 +1,37 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use bot::i18n::{Locale, MessageKey, tr};
+
+/// More arguments than any template takes.
+const ARGS: usize = 10;
+
+/// Which positional arguments `key` shows when rendered in `locale`, plus
+/// whether any placeholder was left unfilled.
+fn rendered_args(locale: Locale, key: MessageKey) -> (BTreeSet<usize>, bool) {
+    let markers: Vec<String> = (0..ARGS).map(|i| format!("\u{1}{i}\u{1}")).collect();
+    let args: Vec<&(dyn Display + Sync)> = markers.iter().map(|m| m as _).collect();
+    let out = tr(locale, key, &args);
+
+    let shown = (0..ARGS).filter(|i| out.contains(&markers[*i])).collect();
+    let unfilled = out.split('{').skip(1).any(|part| {
+        part.split_once('}')
+            .is_some_and(|(idx, _)| idx.parse::<usize>().is_ok())
+    });
+    (shown, unfilled)
+}
+
+#[test]
+fn every_locale_fills_the_same_placeholders() {
+    for &key in MessageKey::ALL {
+        let (en, _) = rendered_args(Locale::En, key);
+        for locale in Locale::ALL {
+            let (shown, unfilled) = rendered_args(locale, key);
+            assert_eq!(shown, en, "{key:?} in {}", locale.code());
+            assert!(
+                !unfilled,
+                "{key:?} in {} has an unfilled placeholder",
+                locale.code()
+            );
+        }
+    }
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{Result, bail};
+use bot::{
+    batch::DEFAULT_MAX_BYTES, config::Config, intraday::run_intraday, registration::CommandScope,
+};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use futures::future::BoxFuture;
+use serde_json::json;
+use stock::{
+    Bar, ChartRenderer, GuildSettings, IntradaySettings, PostedSignal, PriceSource, Scope, Session,
+    Snapshot, Timeframe, earnings,
+    indicators::cdc::{DEFAULT_MIN_CHART_BARS, Signal, SignalColors},
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+use common::{discord_http, message_json, redis_store};
+
+const GUILD: u64 = 1;
+const CHANNEL: u64 = 10;
+const OLD_POST: u64 = 500;
+
+/// Monday 2025-01-06, 10:00 ET: the 09:00 hourly bar has just closed.
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 6, 15, 0, 0).unwrap()
+}
+
+/// Hourly bars up to the one from 09:00 ET, falling and then rallying so the
+/// averages cross up on the last of them.
+struct Rally;
+
+impl PriceSource for Rally {
+    fn fetch_price<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _duration: chrono::Duration,
+        _timeframe: Timeframe,
+        _limit: usize,
+        _bypass_cache: bool,
+        _session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        let falling = (0..60).map(|i| 100.0 - i as f64 * 0.5);
+        let rally = (1..=7).map(|i| 70.5 + i as f64 * 2.0);
+        let closes: Vec<f64> = falling.chain(rally).collect();
+        let last = now() - chrono::Duration::hours(1);
+        let bars: Vec<serde_json::Value> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let t = last - chrono::Duration::hours((closes.len() - 1 - i) as i64);
+                json!({ "t": t.to_rfc3339(), "o": c, "h": c + 1.0, "l": c - 1.0, "c": c, "v": 1000 })
+            })
+            .collect();
+        Box::pin(async move { Ok(serde_json::from_value(serde_json::Value::Array(bars))?) })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        _symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(async { Ok(HashMap::new()) })
+    }
+}
+
+fn config() -> Config {
+    Config {
+        discord_token: "test".into(),
+        version: "test".into(),
+        trigger_cooldown: Duration::ZERO,
+        max_message_bytes: DEFAULT_MAX_BYTES,
+        announce_empty_scans: false,
+        message_content_intent: false,
+        webhook_url: None,
+        webhook_secret: None,
+        // the fixture's crossover is only just past the slow average
+        signal_band_pct: 0.0,
+        min_chart_bars: DEFAULT_MIN_CHART_BARS,
+        signal_colors: SignalColors::default(),
+        daily_enabled: true,
+        weekly_confirmation: false,
+        daily_prewarm: false,
+        supersede_days: 5,
+        earnings_within_days: earnings::DEFAULT_WITHIN_DAYS,
+        api_token: None,
+        api_addr: "127.0.0.1:0".into(),
+        command_scope: CommandScope::Global,
+        owner_ids: vec![],
+    }
+}
+
+/// Discord taking new posts to [`CHANNEL`] and serving and editing the
+/// earlier post there.
+async fn discord() -> MockServer {
+    let discord = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(format!("/api/v10/channels/{CHANNEL}/messages")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_json(CHANNEL)))
+        .mount(&discord)
+        .await;
+    let mut old = message_json(CHANNEL);
+    old["id"] = json!(OLD_POST.to_string());
+    old["embeds"] = json!([{ "title": "UP", "description": "Sell" }]);
+    let old_path = format!("/api/v10/channels/{CHANNEL}/messages/{OLD_POST}");
+    for verb in ["GET", "PATCH"] {
+        Mock::given(method(verb))
+            .and(path(old_path.clone()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(old.clone()))
+            .mount(&discord)
+            .await;
+    }
+    discord
+}
+
+#[tokio::test]
+async fn an_intraday_reversal_supersedes_the_earlier_post_and_is_remembered() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let store = Arc::new(store);
+    let scope = Scope::Guild(GUILD);
+    let session = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+    store.add(scope, "UP").await.unwrap();
+    let settings = GuildSettings {
+        intraday: IntradaySettings {
+            enabled: true,
+            channel: Some(CHANNEL),
+            timeframe: Some(Timeframe::Hour1),
+            charts: false,
+        },
+        ..Default::default()
+    };
+    store.set_guild_settings(GUILD, &settings).await.unwrap();
+    let earlier = PostedSignal {
+        signal: Signal::Sell,
+        session,
+        channel_id: CHANNEL,
+        message_id: OLD_POST,
+        embed_index: 0,
+    };
+    store
+        .set_posted_at(scope, Timeframe::Hour1, &[("UP".into(), earlier)])
+        .await
+        .unwrap();
+    let discord = discord().await;
+    let renderer =
+        ChartRenderer::with_render_fn(1, Duration::from_secs(5), |_| bail!("no charts in tests"))
+            .unwrap();
+
+    run_intraday(
+        discord_http(&discord),
+        Arc::new(Rally),
+        Arc::new(renderer),
+        store.clone(),
+        config(),
+        now(),
+    )
+    .await
+    .unwrap();
+
+    let edited = discord
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .any(|r| r.method.as_str() == "PATCH");
+    assert!(edited, "the earlier post wasn't marked superseded");
+    let posted = store.posted_at(scope, Timeframe::Hour1).await.unwrap();
+    assert_eq!(
+        posted["UP"],
+        PostedSignal {
+            signal: Signal::Buy,
+            session,
+            channel_id: CHANNEL,
+            message_id: 1,
+            embed_index: 0,
+        }
+    );
+    // the daily scan's posts are kept apart
+    assert!(store.posted(scope).await.unwrap().is_empty());
+}
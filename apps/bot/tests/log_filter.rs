@@ -0,0 +1,42 @@
+use bot::log_filter::{LogFilter, parse};
+use tracing_subscriber::{EnvFilter, prelude::*, reload};
+
+fn filter(directives: &str) -> (impl tracing::Subscriber, LogFilter) {
+    let (layer, handle) = reload::Layer::new(EnvFilter::new(directives));
+    (
+        tracing_subscriber::registry().with(layer),
+        LogFilter::new(handle, directives),
+    )
+}
+
+#[test]
+fn parse_takes_rust_log_syntax() {
+    assert!(parse("info").is_ok());
+    assert!(parse(" info,stock=debug,bot::daily=trace ").is_ok());
+    assert!(parse("warn,[cmd_graph]=debug").is_ok());
+}
+
+#[test]
+fn parse_rejects_malformed_and_empty_filters() {
+    assert!(parse("").is_err());
+    assert!(parse("   ").is_err());
+    assert!(parse("stock=loud").is_err());
+    assert!(parse("info,[unclosed=debug").is_err());
+}
+
+#[test]
+fn set_swaps_the_filter_and_returns_the_previous_one() {
+    let (_subscriber, log_filter) = filter("info");
+
+    assert_eq!(log_filter.set("info,stock=debug ").unwrap(), "info");
+    assert_eq!(log_filter.current(), "info,stock=debug");
+}
+
+#[test]
+fn an_invalid_filter_leaves_the_current_one() {
+    let (_subscriber, log_filter) = filter("info");
+
+    let err = log_filter.set("stock=loud").unwrap_err();
+    assert!(err.to_string().contains("stock=loud"), "{err}");
+    assert_eq!(log_filter.current(), "info");
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use bot::{
+    batch::MessageBatcher,
+    notify::{SIGNATURE_HEADER, SignalEvent, Webhook, WebhookSink, sign},
+};
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{header, method, path},
+};
+
+use common::{MockSink, Sent, hit};
+
+fn event() -> SignalEvent {
+    SignalEvent {
+        symbol: "AAPL".into(),
+        signal: "buy".into(),
+        price: 189.5,
+        timestamp: Utc.with_ymd_and_hms(2024, 6, 18, 4, 0, 0).unwrap(),
+        timeframe: "1Day".into(),
+        chart_url: None,
+    }
+}
+
+#[test]
+fn signature_matches_rfc4231_vector() {
+    assert_eq!(
+        sign(b"Jefe", b"what do ya want for nothing?"),
+        "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+#[test]
+fn event_serializes_to_documented_shape() {
+    assert_eq!(
+        serde_json::to_value(event()).unwrap(),
+        json!({
+            "symbol": "AAPL",
+            "signal": "buy",
+            "price": 189.5,
+            "timestamp": "2024-06-18T04:00:00Z",
+            "timeframe": "1Day",
+            "chart_url": null,
+        })
+    );
+}
+
+#[tokio::test]
+async fn delivery_is_signed_and_retried() {
+    let server = MockServer::start().await;
+    let body = serde_json::to_vec(&event()).unwrap();
+    let signature = sign(b"secret", &body);
+
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(header(SIGNATURE_HEADER, signature.as_str()))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let webhook = Webhook::new(format!("{}/hook", server.uri()), "secret".into()).unwrap();
+    webhook.deliver(&event()).await.unwrap();
+}
+
+#[tokio::test]
+async fn sink_posts_to_discord_even_when_webhook_is_down() {
+    let webhook = Webhook::new("http://127.0.0.1:9/hook".into(), "secret".into()).unwrap();
+    let inner = MockSink::default();
+    let mut batcher = MessageBatcher::new(WebhookSink::new(inner.clone(), Some(webhook)));
+
+    let (embed, attachment) = hit(0);
+    batcher
+        .push(embed, attachment, Some(event()))
+        .await
+        .unwrap();
+    batcher.finish(String::new(), true, None).await.unwrap();
+
+    assert_eq!(inner.sent(), vec![Sent::Batch(1)]);
+}
@@ -0,0 +1,84 @@
+use bot::ohlcv::{csv, table};
+use chrono::{TimeZone, Utc};
+use chrono_tz::America::{Los_Angeles, New_York};
+use stock::{Bar, Timeframe};
+
+fn bar(day: u32, hour: u32, open: f64, close: f64, volume: f64) -> Bar {
+    Bar {
+        timestamp: Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap(),
+        open,
+        high: open.max(close) + 1.0,
+        low: open.min(close) - 1.0,
+        close,
+        volume,
+        trade_count: None,
+        vwap: None,
+    }
+}
+
+fn bars() -> Vec<Bar> {
+    vec![
+        bar(4, 5, 170.5, 172.25, 51_234_567.0),
+        bar(5, 5, 172.25, 169.0, 48_900.0),
+        bar(6, 5, 169.0, 1001.3, 850.0),
+    ]
+}
+
+#[test]
+fn table_lines_up_daily_bars_under_the_header() {
+    let table = table(&bars(), Timeframe::Day1, New_York);
+    assert_eq!(
+        table,
+        "\
+Date          Open     High     Low    Close  Volume
+2024-03-04  170.50   173.25  169.50   172.25   51.2M
+2024-03-05  172.25   173.25  168.00   169.00   48.9K
+2024-03-06  169.00  1002.30  168.00  1001.30     850"
+    );
+}
+
+#[test]
+fn daily_bars_keep_their_session_date_west_of_new_york() {
+    let table = table(&bars(), Timeframe::Day1, Los_Angeles);
+    let dates: Vec<&str> = table.lines().skip(1).map(|row| &row[..10]).collect();
+    assert_eq!(dates, ["2024-03-04", "2024-03-05", "2024-03-06"]);
+}
+
+#[test]
+fn intraday_tables_show_the_time_in_the_viewers_zone() {
+    let bars = vec![bar(4, 14, 10.0, 11.0, 100.0)];
+    let table = table(&bars, Timeframe::Hour1, New_York);
+    let row = table.lines().nth(1).unwrap();
+    assert!(row.starts_with("2024-03-04 09:00 "), "{row}");
+}
+
+#[test]
+fn prices_under_a_dollar_keep_four_places() {
+    let bars = vec![bar(4, 5, 0.1234, 0.15, 1_000_000.0)];
+    let table = table(&bars, Timeframe::Day1, New_York);
+    let row = table.lines().nth(1).unwrap();
+    assert!(row.contains("0.1234"), "{row}");
+    assert!(row.contains("0.1500"), "{row}");
+}
+
+#[test]
+fn an_empty_table_is_just_the_header() {
+    assert_eq!(
+        table(&[], Timeframe::Day1, New_York),
+        "Date  Open  High  Low  Close  Volume"
+    );
+}
+
+#[test]
+fn csv_has_a_header_and_unrounded_values() {
+    assert_eq!(
+        csv(&bars()),
+        "\
+timestamp,open,high,low,close,volume
+2024-03-04T05:00:00+00:00,170.5,173.25,169.5,172.25,51234567
+2024-03-05T05:00:00+00:00,172.25,173.25,168,169,48900
+2024-03-06T05:00:00+00:00,169,1002.3,168,1001.3,850
+"
+    );
+    assert_eq!(csv(&[]), "timestamp,open,high,low,close,volume\n");
+}
@@ -0,0 +1,77 @@
+use bot::onboarding::{ChannelInfo, parse_set_channel, pick_channel, set_channel_id};
+use serenity::all::{ChannelId, ChannelType};
+
+fn text(id: u64, position: u16, writable: bool) -> ChannelInfo {
+    ChannelInfo {
+        id: ChannelId::new(id),
+        kind: ChannelType::Text,
+        position,
+        writable,
+    }
+}
+
+#[test]
+fn system_channel_wins_when_writable() {
+    let channels = [text(1, 0, true), text(2, 5, true)];
+    assert_eq!(
+        pick_channel(Some(ChannelId::new(2)), &channels),
+        Some(ChannelId::new(2))
+    );
+}
+
+#[test]
+fn read_only_system_channel_falls_back_to_topmost_writable() {
+    let channels = [text(1, 0, false), text(2, 3, true), text(3, 1, true)];
+    assert_eq!(
+        pick_channel(Some(ChannelId::new(1)), &channels),
+        Some(ChannelId::new(3))
+    );
+}
+
+#[test]
+fn missing_system_channel_falls_back() {
+    let channels = [text(4, 2, true), text(5, 2, true)];
+    assert_eq!(
+        pick_channel(Some(ChannelId::new(9)), &channels),
+        Some(ChannelId::new(4))
+    );
+    assert_eq!(pick_channel(None, &channels), Some(ChannelId::new(4)));
+}
+
+#[test]
+fn only_text_channels_are_picked() {
+    let channels = [
+        ChannelInfo {
+            id: ChannelId::new(1),
+            kind: ChannelType::Voice,
+            position: 0,
+            writable: true,
+        },
+        ChannelInfo {
+            id: ChannelId::new(2),
+            kind: ChannelType::Category,
+            position: 0,
+            writable: true,
+        },
+        text(3, 7, true),
+    ];
+    assert_eq!(
+        pick_channel(Some(ChannelId::new(1)), &channels),
+        Some(ChannelId::new(3))
+    );
+}
+
+#[test]
+fn nowhere_to_post() {
+    assert_eq!(pick_channel(None, &[]), None);
+    assert_eq!(pick_channel(None, &[text(1, 0, false)]), None);
+}
+
+#[test]
+fn set_channel_button_round_trips() {
+    let channel = ChannelId::new(123456789);
+    assert_eq!(parse_set_channel(&set_channel_id(channel)), Some(channel));
+    assert_eq!(parse_set_channel("onboard_set_channel_0"), None);
+    assert_eq!(parse_set_channel("onboard_set_channel_abc"), None);
+    assert_eq!(parse_set_channel("watch_cancel_add"), None);
+}
@@ -0,0 +1,185 @@
+use bot::{
+    command::stock::stock_command,
+    registration::{Change, CommandScope, diff},
+};
+use serde_json::{Value, json};
+
+fn local_stock() -> Vec<Value> {
+    poise::builtins::create_application_commands(&[stock_command()])
+        .iter()
+        .map(|c| serde_json::to_value(c).unwrap())
+        .collect()
+}
+
+/// `/ping` with one option, as it goes out to Discord.
+fn local_ping() -> Value {
+    json!({
+        "name": "ping",
+        "description": "Check the bot is up",
+        "name_localizations": {},
+        "description_localizations": {},
+        "nsfw": false,
+        "options": [
+            { "type": 4, "name": "count", "description": "Times to ping", "required": false,
+              "min_value": 1, "max_value": 5, "choices": [], "channel_types": [], "options": [] },
+            { "type": 3, "name": "note", "description": "Echoed back", "required": false,
+              "choices": [], "channel_types": [], "options": [] }
+        ]
+    })
+}
+
+/// The same command as Discord returns it: ids and versions added, false
+/// and empty fields left out, bounds as floats.
+fn registered_ping() -> Value {
+    json!({
+        "id": "1200000000000000000",
+        "application_id": "1100000000000000000",
+        "version": "1300000000000000000",
+        "type": 1,
+        "name": "ping",
+        "description": "Check the bot is up",
+        "default_member_permissions": null,
+        "dm_permission": true,
+        "nsfw": false,
+        "options": [
+            { "type": 4, "name": "count", "description": "Times to ping",
+              "min_value": 1.0, "max_value": 5.0 },
+            { "type": 3, "name": "note", "description": "Echoed back" }
+        ]
+    })
+}
+
+#[test]
+fn the_real_commands_match_themselves() {
+    let local = local_stock();
+    assert_eq!(local.len(), 1);
+    assert!(diff(&local, &local).is_empty());
+}
+
+#[test]
+fn discord_defaults_are_not_changes() {
+    assert_eq!(diff(&[registered_ping()], &[local_ping()]), vec![]);
+}
+
+#[test]
+fn added_removed_and_changed_are_named_by_path() {
+    let mut local = local_ping();
+    local["options"][0]["description"] = json!("How many pings");
+    local["options"][1]["required"] = json!(true);
+    local["options"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "type": 5, "name": "loud", "description": "Ping loudly" }));
+    let old = json!({ "name": "pong", "description": "Gone" });
+
+    assert_eq!(
+        diff(&[registered_ping(), old], &[local]),
+        vec![
+            Change::Removed("pong".into()),
+            Change::Changed {
+                path: "ping count".into(),
+                field: "description".into()
+            },
+            Change::Changed {
+                path: "ping note".into(),
+                field: "required".into()
+            },
+            Change::Added("ping loud".into()),
+        ]
+    );
+}
+
+#[test]
+fn reordered_options_are_a_change() {
+    let mut local = local_ping();
+    local["options"].as_array_mut().unwrap().reverse();
+    let changes = diff(&[registered_ping()], &[local]);
+    assert_eq!(
+        changes,
+        vec![Change::Changed {
+            path: "ping".into(),
+            field: "option order".into()
+        }]
+    );
+    assert_eq!(changes[0].to_string(), "~ping option order");
+}
+
+#[test]
+fn bounds_and_permissions_are_compared() {
+    let mut local = local_ping();
+    local["options"][0]["max_value"] = json!(10);
+    local["default_member_permissions"] = json!("32");
+    assert_eq!(
+        diff(&[registered_ping()], &[local]),
+        vec![
+            Change::Changed {
+                path: "ping".into(),
+                field: "default_member_permissions".into()
+            },
+            Change::Changed {
+                path: "ping count".into(),
+                field: "max_value".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn dm_access_nsfw_and_localizations_are_compared() {
+    let mut local = local_ping();
+    local["dm_permission"] = json!(false);
+    local["nsfw"] = json!(true);
+    local["contexts"] = json!([0]);
+    local["description_localizations"] = json!({ "th": "เช็กว่าบอทยังทำงาน" });
+    assert_eq!(
+        diff(&[registered_ping()], &[local]),
+        [
+            "dm_permission",
+            "contexts",
+            "nsfw",
+            "description_localizations"
+        ]
+        .map(|field| Change::Changed {
+            path: "ping".into(),
+            field: field.into()
+        })
+    );
+}
+
+#[test]
+fn command_scope_parses_global_and_guild_lists() {
+    for raw in ["", " global ", "GLOBAL"] {
+        assert_eq!(raw.parse::<CommandScope>().unwrap(), CommandScope::Global);
+    }
+    assert_eq!(
+        "guild:1, 22,1".parse::<CommandScope>().unwrap(),
+        CommandScope::Guilds(vec![1, 22])
+    );
+    for raw in ["guild:", "guild:abc", "guild:0", "guilds:1", "1,2"] {
+        assert!(raw.parse::<CommandScope>().is_err(), "{raw:?} accepted");
+    }
+}
+
+#[test]
+fn admin_commands_are_owner_only() {
+    let stock = stock_command();
+    let admin = stock
+        .subcommands
+        .iter()
+        .find(|c| c.name == "admin")
+        .expect("admin command");
+    assert!(admin.owners_only);
+    assert!(!admin.subcommands.is_empty());
+    for command in &admin.subcommands {
+        assert!(command.owners_only, "{} is open to everyone", command.name);
+    }
+
+    // and nothing outside it is
+    let gated: Vec<&str> = stock
+        .subcommands
+        .iter()
+        .filter(|c| c.owners_only && c.name != "admin")
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(gated, Vec::<&str>::new());
+}
@@ -0,0 +1,337 @@
+use bot::{
+    i18n::Locale,
+    report::{
+        CSV_HEADER, CsvRow, build_csv, chart_filename, csv_filename, generated_at, hit_message,
+        hit_source_label, position_field, reversal_field, superseded, volume_spike_message,
+    },
+    style::SignalStyle,
+};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use stock::{
+    Bar, DataSource, PostedSignal, Timeframe,
+    calendar::DEFAULT_TIMEZONE,
+    indicators::{
+        cdc::{DEFAULT_BAND_PCT, Signal},
+        volume::VolumeSpike,
+    },
+    scan::ScanHit,
+};
+
+#[test]
+fn chart_filename_is_tagged_with_the_signal() {
+    let cases = [
+        (Signal::Buy, "AAPL_BUY_chart.png"),
+        (Signal::Sell, "AAPL_SELL_chart.png"),
+        (Signal::BullishZone, "AAPL_BULLISH_chart.png"),
+        (Signal::BearishZone, "AAPL_BEARISH_chart.png"),
+        (Signal::None, "AAPL_chart.png"),
+    ];
+    for (signal, expected) in cases {
+        assert_eq!(chart_filename("aapl", signal), expected, "{signal:?}");
+    }
+}
+
+#[test]
+fn chart_filename_keeps_crypto_pairs_a_single_path_segment() {
+    assert_eq!(
+        chart_filename("BTC/USD", Signal::Buy),
+        "BTC-USD_BUY_chart.png"
+    );
+}
+
+#[test]
+fn generated_at_is_written_out_in_the_given_zone() {
+    let at = Utc.with_ymd_and_hms(2024, 3, 11, 13, 30, 0).unwrap();
+    assert_eq!(
+        generated_at(Locale::En, at, chrono_tz::America::New_York),
+        "Generated 2024-03-11 09:30 EDT"
+    );
+    assert_eq!(
+        generated_at(Locale::En, at, chrono_tz::Asia::Bangkok),
+        "Generated 2024-03-11 20:30 +07"
+    );
+}
+
+fn hit(timeframe: Timeframe, chart: &[u8]) -> ScanHit {
+    ScanHit {
+        symbol: "AAPL".into(),
+        signal: Signal::Buy,
+        close: 190.0,
+        timestamp: Utc.with_ymd_and_hms(2024, 3, 11, 14, 0, 0).unwrap(),
+        timeframe,
+        source: DataSource::Bars,
+        chart: chart.to_vec(),
+        chart_failed: false,
+        fallback: None,
+    }
+}
+
+#[test]
+fn intraday_hits_name_their_bar_size() {
+    assert_eq!(
+        hit_source_label(Locale::En, &hit(Timeframe::Minute30, b"")),
+        "Data: 30Min bars"
+    );
+    assert_eq!(
+        hit_source_label(Locale::En, &hit(Timeframe::Day1, b"")),
+        "Data: daily bars"
+    );
+}
+
+#[test]
+fn hits_without_a_chart_have_no_attachment() {
+    let style = SignalStyle::default();
+    let (_, attachment) = hit_message(
+        Locale::En,
+        hit(Timeframe::Hour1, b""),
+        None,
+        &style,
+        DEFAULT_TIMEZONE,
+    );
+    assert!(attachment.is_none());
+
+    let (_, attachment) = hit_message(
+        Locale::En,
+        hit(Timeframe::Hour1, b"png"),
+        None,
+        &style,
+        DEFAULT_TIMEZONE,
+    );
+    assert_eq!(attachment.unwrap().filename, "AAPL_BUY_chart.png");
+}
+
+/// Daily bars over `closes`, starting on a Tuesday close.
+fn bars(closes: &[f64]) -> Vec<Bar> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Bar {
+            timestamp: start + Duration::days(i as i64),
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 1_000_000.0,
+            trade_count: None,
+            vwap: None,
+        })
+        .collect()
+}
+
+/// A long fall, then a rally that crosses the averages back up.
+fn crossover() -> Vec<f64> {
+    let falling = (0..60).map(|i| 100.0 - i as f64 * 0.5);
+    let rally = (1..=7).map(|i| 70.5 + i as f64 * 2.0);
+    falling.chain(rally).collect()
+}
+
+fn row() -> CsvRow {
+    CsvRow {
+        close: 182.5,
+        change_pct: Some(1.234),
+        ema12: 180.123456,
+        ema26: 175.0,
+        zone: Signal::BullishZone,
+        rsi: Some(61.0),
+        from_high_pct: Some(-3.5),
+        avg_volume: Some(1_234_567.4),
+        last_signal: Some((Signal::Buy, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap())),
+    }
+}
+
+fn parse(csv: &[u8]) -> Vec<Vec<String>> {
+    csv::Reader::from_reader(csv)
+        .records()
+        .map(|r| r.unwrap().iter().map(String::from).collect())
+        .collect()
+}
+
+#[test]
+fn rows_follow_the_header() {
+    let csv = build_csv(&[("AAPL".into(), Ok(row()))]);
+    let text = String::from_utf8(csv).unwrap();
+    let mut lines = text.lines();
+
+    assert_eq!(lines.next().unwrap(), CSV_HEADER.join(","));
+    assert_eq!(
+        lines.next().unwrap(),
+        "AAPL,182.5000,1.23,180.1235,175.0000,bullish,61.00,-3.50,1234567,buy,2024-03-01,"
+    );
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn failed_symbols_keep_a_row_with_the_error() {
+    let csv = build_csv(&[
+        ("AAPL".into(), Ok(row())),
+        ("BAD".into(), Err("HTTP 500".into())),
+    ]);
+    let rows = parse(&csv);
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1][0], "BAD");
+    assert!(rows[1][1..11].iter().all(String::is_empty));
+    assert_eq!(rows[1][11], "HTTP 500");
+}
+
+#[test]
+fn commas_and_quotes_are_quoted() {
+    let symbol = "BRK.B, \"class B\"";
+    let error = "no bars for \"BRK.B\", try again";
+    let csv = build_csv(&[(symbol.into(), Err(error.into()))]);
+    let text = String::from_utf8(csv.clone()).unwrap();
+
+    assert!(text.contains(r#""BRK.B, ""class B""""#), "{text}");
+    let rows = parse(&csv);
+    assert_eq!(rows[0].len(), CSV_HEADER.len());
+    assert_eq!(rows[0][0], symbol);
+    assert_eq!(rows[0][11], error);
+}
+
+#[test]
+fn short_histories_leave_readings_blank() {
+    let row = CsvRow::from_bars(&bars(&[10.0, 11.0]), DEFAULT_BAND_PCT).unwrap();
+    assert_eq!(row.change_pct.map(|c| c.round()), Some(10.0));
+    assert_eq!(row.rsi, None);
+    assert_eq!(row.from_high_pct, None);
+    assert_eq!(row.avg_volume, None);
+
+    let rows = parse(&build_csv(&[("NEW".into(), Ok(row))]));
+    assert_eq!(rows[0][6], "");
+    assert_eq!(rows[0][11], "");
+    assert!(CsvRow::from_bars(&[], DEFAULT_BAND_PCT).is_none());
+}
+
+#[test]
+fn rows_carry_the_last_crossover() {
+    let bars = bars(&crossover());
+    let row = CsvRow::from_bars(&bars, 0.0).unwrap();
+
+    assert_eq!(row.zone, Signal::BullishZone);
+    let (signal, date) = row.last_signal.unwrap();
+    assert_eq!(signal, Signal::Buy);
+    assert!(
+        date > NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+        "{date}"
+    );
+    assert!(row.from_high_pct.unwrap() <= 0.0);
+    assert_eq!(row.avg_volume, Some(1_000_000.0));
+    assert!(row.ema12 > row.ema26);
+}
+
+#[test]
+fn the_file_is_named_for_the_day() {
+    let at = Utc.with_ymd_and_hms(2024, 3, 12, 2, 0, 0).unwrap();
+    // still the 11th in New York
+    assert_eq!(csv_filename(at, DEFAULT_TIMEZONE), "report-2024-03-11.csv");
+    assert_eq!(csv_filename(at, chrono_tz::UTC), "report-2024-03-12.csv");
+}
+
+#[test]
+fn hits_whose_chart_failed_say_so() {
+    let mut failed = hit(Timeframe::Day1, b"");
+    failed.chart_failed = true;
+    let (embed, attachment) = hit_message(
+        Locale::En,
+        failed,
+        None,
+        &SignalStyle::default(),
+        DEFAULT_TIMEZONE,
+    );
+
+    assert!(attachment.is_none());
+    let json = serde_json::to_value(&embed).unwrap();
+    assert!(json.get("image").is_none());
+    let footer = json["footer"]["text"].as_str().unwrap();
+    assert!(footer.ends_with(" · Chart unavailable"), "{footer}");
+}
+
+#[test]
+fn hits_from_a_fallback_source_name_it() {
+    let mut hit = hit(Timeframe::Day1, b"png");
+    hit.fallback = Some("yahoo".into());
+    let (embed, _) = hit_message(
+        Locale::En,
+        hit,
+        None,
+        &SignalStyle::default(),
+        DEFAULT_TIMEZONE,
+    );
+
+    let json = serde_json::to_value(&embed).unwrap();
+    let footer = json["footer"]["text"].as_str().unwrap();
+    assert!(
+        footer.ends_with(" · 🔀 Data from fallback source yahoo"),
+        "{footer}"
+    );
+}
+
+#[test]
+fn a_reversal_points_back_and_strikes_through_the_old_post() {
+    let post = PostedSignal {
+        signal: Signal::Buy,
+        session: NaiveDate::from_ymd_opt(2024, 6, 17).unwrap(),
+        channel_id: 1,
+        message_id: 2,
+        embed_index: 0,
+    };
+    let (name, value, inline) = reversal_field(Locale::En, &post, "https://old");
+    assert_eq!(name, "↩️ Reversal");
+    assert_eq!(
+        value,
+        "Reverses the Buy posted 2024-06-17 · [view post](https://old)"
+    );
+    assert!(!inline);
+
+    let session = NaiveDate::from_ymd_opt(2024, 6, 18).unwrap();
+    assert_eq!(
+        superseded(
+            Locale::En,
+            Some("Current Signal: Buy"),
+            Signal::Sell,
+            session,
+            "https://new"
+        ),
+        "Current Signal: Buy\n~~Superseded by Sell on 2024-06-18~~ · [view post](https://new)"
+    );
+    assert_eq!(
+        superseded(Locale::En, None, Signal::Sell, session, "https://new"),
+        "~~Superseded by Sell on 2024-06-18~~ · [view post](https://new)"
+    );
+}
+
+#[test]
+fn a_volume_spike_gives_the_ratio_and_the_average() {
+    let spike = VolumeSpike {
+        volume: 4_200_000.0,
+        average: 1_000_000.0,
+        ratio: 4.2,
+        close: 187.5,
+        timestamp: Utc.with_ymd_and_hms(2024, 5, 1, 20, 0, 0).unwrap(),
+    };
+    let embed = volume_spike_message(Locale::En, "aapl", &spike, DEFAULT_TIMEZONE);
+    let json = serde_json::to_value(&embed).unwrap();
+    assert_eq!(json["title"], "AAPL Volume Spike");
+    let description = json["description"].as_str().unwrap();
+    assert!(
+        description.starts_with("Volume spike: 4.2× average\n"),
+        "{description}"
+    );
+    assert!(description.contains("4.2M traded against a 20-bar average of 1.0M"));
+}
+
+#[test]
+fn a_position_gives_its_pnl_against_the_entry() {
+    assert_eq!(
+        position_field(Locale::En, 150.0, 168.45),
+        Some((
+            "Open position".to_string(),
+            "+12.3% from $150.00".to_string(),
+            true
+        ))
+    );
+    let (_, value, _) = position_field(Locale::En, 200.0, 190.0).unwrap();
+    assert_eq!(value, "-5.0% from $200.00");
+    assert!(position_field(Locale::En, 0.0, 190.0).is_none());
+}
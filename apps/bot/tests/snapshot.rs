@@ -0,0 +1,77 @@
+use bot::{command::stock::snapshot::snapshot_message, i18n::Locale, style::SignalStyle};
+use stock::{Snapshot, calendar::DEFAULT_TIMEZONE, indicators::cdc::Signal};
+
+fn snapshot() -> Snapshot {
+    serde_json::from_str(
+        r#"{
+          "latestTrade": {"t": "2024-01-05T20:59:59Z", "p": 183.73},
+          "latestQuote": {"t": "2024-01-05T20:59:59Z", "bp": 183.7, "bs": 2,
+                          "ap": 183.75, "as": 3},
+          "dailyBar": {"t": "2024-01-05T05:00:00Z", "o": 181.99, "h": 184.0,
+                       "l": 180.17, "c": 183.73, "v": 62303300},
+          "prevDailyBar": {"t": "2024-01-04T05:00:00Z", "o": 182.15, "h": 183.09,
+                           "l": 180.88, "c": 180.0, "v": 71983600}
+        }"#,
+    )
+    .unwrap()
+}
+
+fn field<'a>(json: &'a serde_json::Value, name: &str) -> &'a str {
+    json["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == name)
+        .unwrap_or_else(|| panic!("no {name} field in {json}"))["value"]
+        .as_str()
+        .unwrap()
+}
+
+#[test]
+fn a_snapshot_shows_price_book_bar_and_signal() {
+    let embed = snapshot_message(
+        Locale::En,
+        "aapl",
+        &snapshot(),
+        Some(Signal::BullishZone),
+        &SignalStyle::default(),
+        DEFAULT_TIMEZONE,
+    );
+    let json = serde_json::to_value(&embed).unwrap();
+    assert_eq!(json["title"], "AAPL Snapshot");
+    assert_eq!(field(&json, "Last"), "$183.73 (+2.1%)");
+    assert_eq!(field(&json, "Bid / Ask"), "$183.70 × 2 / $183.75 × 3");
+    assert_eq!(field(&json, "Previous close"), "$180.00");
+    assert_eq!(
+        field(&json, "Today"),
+        "O $181.99 · H $184.00 · L $180.17 · C $183.73 · Vol 62.3M"
+    );
+    assert_eq!(field(&json, "Signal"), "BullishZone");
+}
+
+#[test]
+fn missing_parts_are_left_out() {
+    let snapshot = Snapshot {
+        latest_quote: None,
+        prev_daily_bar: None,
+        ..snapshot()
+    };
+    let embed = snapshot_message(
+        Locale::En,
+        "AAPL",
+        &snapshot,
+        None,
+        &SignalStyle::default(),
+        DEFAULT_TIMEZONE,
+    );
+    let json = serde_json::to_value(&embed).unwrap();
+    let names: Vec<&str> = json["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Last", "Today", "Signal"]);
+    assert_eq!(field(&json, "Last"), "$183.73");
+    assert_eq!(field(&json, "Signal"), "Not enough history");
+}
@@ -0,0 +1,67 @@
+use bot::{i18n::Locale, spotlight::summary};
+use stock::{indicators::cdc::Signal, spotlight::Setup};
+
+fn setup(signal: Signal, gap_pct: f64, gap_change: f64, volume_ratio: Option<f64>) -> Setup {
+    Setup {
+        symbol: "AAPL".to_string(),
+        signal,
+        close: 182.5,
+        gap_pct,
+        gap_change,
+        volume_ratio,
+    }
+}
+
+#[test]
+fn crossover_summary_mentions_volume() {
+    let text = summary(Locale::En, &setup(Signal::Buy, 0.3, 0.8, Some(1.44)));
+    assert_eq!(
+        text,
+        "AAPL crossed bullish in the last session on 1.4× average volume. \
+         The 12-day EMA now sits +0.3% from the 26-day, a +0.80 point move in one session."
+    );
+}
+
+#[test]
+fn ordinary_volume_is_left_out() {
+    let text = summary(Locale::En, &setup(Signal::Sell, -0.5, -0.6, Some(1.0)));
+    assert!(
+        text.starts_with("AAPL crossed bearish in the last session. "),
+        "{text}"
+    );
+}
+
+#[test]
+fn zones_describe_momentum() {
+    let cases = [
+        (
+            Signal::BullishZone,
+            2.0,
+            0.5,
+            "strengthened its bullish trend",
+        ),
+        (
+            Signal::BullishZone,
+            2.0,
+            -0.5,
+            "bullish trend lost momentum",
+        ),
+        (
+            Signal::BearishZone,
+            -2.0,
+            -0.5,
+            "extended its bearish trend",
+        ),
+        (Signal::BearishZone, -2.0, 0.5, "bearish trend eased"),
+    ];
+    for (signal, gap, change, phrase) in cases {
+        let text = summary(Locale::En, &setup(signal, gap, change, None));
+        assert!(text.contains(phrase), "{signal:?} {change}: {text}");
+    }
+}
+
+#[test]
+fn thai_summary_is_translated() {
+    let text = summary(Locale::Th, &setup(Signal::Buy, 0.3, 0.8, None));
+    assert!(text.starts_with("AAPL ตัดขึ้นเป็นขาขึ้น"), "{text}");
+}
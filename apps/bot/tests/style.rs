@@ -0,0 +1,100 @@
+use bot::style::{SignalStyle, StylePreset, describe, parse_emoji};
+use serenity::all::ReactionType;
+use stock::{
+    GuildSettings,
+    indicators::cdc::{Signal, SignalColors},
+};
+
+#[test]
+fn unset_style_is_the_bot_wide_colors() {
+    let base = SignalColors {
+        buy: 0x123456,
+        ..Default::default()
+    };
+    let style = SignalStyle::resolve(&base, &GuildSettings::default());
+    assert_eq!(style.colors, base);
+    assert_eq!(style.emoji(Signal::Buy), "🟢");
+}
+
+#[test]
+fn colorblind_preset_avoids_red_and_green() {
+    let settings = GuildSettings {
+        style_preset: Some("colorblind".into()),
+        ..Default::default()
+    };
+    let style = SignalStyle::resolve(&SignalColors::default(), &settings);
+    assert_eq!(style.color(Signal::Buy), 0x0072B2);
+    assert_eq!(style.color(Signal::Sell), 0xE69F00);
+    assert_eq!(style.emoji(Signal::BullishZone), "🔵");
+    assert_eq!(style.emoji(Signal::BearishZone), "🟠");
+    assert_eq!(style.emoji(Signal::None), "⚪");
+}
+
+#[test]
+fn overrides_apply_over_the_preset() {
+    let settings = GuildSettings {
+        style_preset: Some("colorblind".into()),
+        buy_color: Some(0x00AAFF),
+        sell_emoji: Some("🔻".into()),
+        ..Default::default()
+    };
+    let style = SignalStyle::resolve(&SignalColors::default(), &settings);
+    assert_eq!(style.color(Signal::Buy), 0x00AAFF);
+    assert_eq!(style.color(Signal::BullishZone), 0x00AAFF);
+    assert_eq!(style.color(Signal::Sell), 0xE69F00);
+    assert_eq!(style.emoji(Signal::Buy), "🔵");
+    assert_eq!(style.emoji(Signal::Sell), "🔻");
+}
+
+#[test]
+fn unknown_preset_falls_back_to_default() {
+    let settings = GuildSettings {
+        style_preset: Some("neon".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        SignalStyle::resolve(&SignalColors::default(), &settings),
+        SignalStyle::default()
+    );
+    assert_eq!(
+        StylePreset::parse(" Colorblind "),
+        Some(StylePreset::Colorblind)
+    );
+}
+
+#[test]
+fn custom_emoji_react_as_custom() {
+    let style = SignalStyle {
+        buy_emoji: "<:moon:600404340292059257>".into(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        style.reaction(Signal::Buy),
+        ReactionType::Custom { .. }
+    ));
+    assert_eq!(
+        style.reaction(Signal::Sell),
+        ReactionType::Unicode("🔴".into())
+    );
+}
+
+#[test]
+fn emoji_input_is_validated() {
+    assert_eq!(parse_emoji(" 🚀 ").unwrap(), "🚀");
+    assert!(parse_emoji("<:moon:600404340292059257>").is_ok());
+    for raw in ["", "up", ":rocket:", "🚀 🚀", "<:moon:notanid>"] {
+        assert!(parse_emoji(raw).is_err(), "{raw:?} accepted");
+    }
+}
+
+#[test]
+fn describe_lists_preset_then_overrides() {
+    assert_eq!(describe(&GuildSettings::default()), "default");
+    let settings = GuildSettings {
+        style_preset: Some("colorblind".into()),
+        buy_color: Some(0x00AAFF),
+        sell_emoji: Some("🔻".into()),
+        ..Default::default()
+    };
+    assert_eq!(describe(&settings), "colorblind · buy #00AAFF · sell 🔻");
+}
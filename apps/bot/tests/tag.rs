@@ -0,0 +1,18 @@
+use bot::command::stock::tag::parse_tags;
+
+#[test]
+fn tags_are_normalized_and_deduplicated() {
+    assert_eq!(
+        parse_tags("Tech, #ai,tech, ,"),
+        Ok(vec!["tech".to_string(), "ai".to_string()])
+    );
+    assert_eq!(parse_tags(""), Ok(Vec::new()));
+}
+
+#[test]
+fn the_first_bad_tag_is_named() {
+    assert_eq!(
+        parse_tags("tech, big tech, $$"),
+        Err("big tech".to_string())
+    );
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use bot::command::stock::watch::{
+    CONFIRM_THRESHOLD, Rejected, already_watched, choice_name, is_watchable, needs_confirmation,
+    parse_symbols, split_last_entry, validate_one,
+};
+use stock::assets::SymbolInfo;
+
+#[test]
+fn short_lists_are_added_straight_away() {
+    assert!(!needs_confirmation(1));
+    assert!(!needs_confirmation(CONFIRM_THRESHOLD));
+}
+
+#[test]
+fn long_lists_ask_first() {
+    assert!(needs_confirmation(CONFIRM_THRESHOLD + 1));
+    assert!(needs_confirmation(100));
+}
+
+#[test]
+fn real_tickers_are_watchable() {
+    for ok in ["AAPL", "F", "BRK.B", "BF-B", "SPY", "GOOGL", "ABCDEFGHIJ"] {
+        assert!(is_watchable(ok), "{ok:?}");
+    }
+}
+
+#[test]
+fn crypto_pairs_are_watchable() {
+    for ok in ["BTC/USD", "ETH/USDT", "1INCH/USD"] {
+        assert!(is_watchable(ok), "{ok:?}");
+    }
+}
+
+#[test]
+fn junk_is_not_watchable() {
+    for bad in [
+        "$$$",
+        "123 456",
+        "123",
+        "1ABC",
+        ".B",
+        "-AAPL",
+        "AA PL",
+        "ABCDEFGHIJK",
+        "BTC/",
+        "/USD",
+        "BTC/USD/X",
+        "aapl",
+    ] {
+        assert!(!is_watchable(bad), "{bad:?}");
+    }
+}
+
+#[test]
+fn parse_splits_valid_from_invalid() {
+    let (valid, invalid) = parse_symbols(" tsla, $$$ ,brk.b,, 123 456,btc/usd ");
+    assert_eq!(valid, ["TSLA", "BRK.B", "BTC/USD"]);
+    assert_eq!(invalid, ["$$$", "123 456"]);
+}
+
+#[test]
+fn duplicates_are_added_once() {
+    let (valid, invalid) = parse_symbols("AAPL,AAPL, aapl ,msft,$$$,$$$");
+    assert_eq!(valid, ["AAPL", "MSFT"]);
+    assert_eq!(invalid, ["$$$"]);
+
+    // each symbol is new once, so none is reported as already watched
+    assert!(already_watched(&valid, &valid).is_empty());
+    assert_eq!(already_watched(&valid, &["MSFT".into()]), ["AAPL"]);
+}
+
+#[test]
+fn autocomplete_completes_the_last_entry() {
+    assert_eq!(split_last_entry("app"), ("", "app"));
+    assert_eq!(split_last_entry("AAPL, MSFT,nv"), ("AAPL, MSFT,", "nv"));
+    assert_eq!(split_last_entry("AAPL,"), ("AAPL,", ""));
+}
+
+#[test]
+fn choices_show_the_name_and_exchange_within_discords_limit() {
+    let info = SymbolInfo {
+        symbol: "AAPL".into(),
+        name: "Apple Inc. Common Stock".into(),
+        exchange: "NASDAQ".into(),
+    };
+    assert_eq!(
+        choice_name(&info),
+        "AAPL · Apple Inc. Common Stock (NASDAQ)"
+    );
+
+    let long = SymbolInfo {
+        name: "Very Long Name ".repeat(10),
+        ..info
+    };
+    assert!(choice_name(&long).chars().count() <= 100);
+    assert!(choice_name(&long).starts_with("AAPL · Very"));
+}
+
+fn listed(symbols: &[&str]) -> Vec<SymbolInfo> {
+    symbols
+        .iter()
+        .map(|symbol| SymbolInfo {
+            symbol: symbol.to_string(),
+            name: String::new(),
+            exchange: "NASDAQ".into(),
+        })
+        .collect()
+}
+
+#[test]
+fn a_single_entry_is_checked_like_the_slash_command() {
+    let aliases = HashMap::from([("fruit".to_string(), "AAPL".to_string())]);
+    let listed = listed(&["AAPL", "MSFT"]);
+
+    let resolved = validate_one(" msft ", &aliases, &listed).unwrap();
+    assert_eq!(
+        (resolved.symbol.as_str(), resolved.via_alias),
+        ("MSFT", None)
+    );
+
+    let resolved = validate_one("Fruit", &aliases, &listed).unwrap();
+    assert_eq!(resolved.symbol, "AAPL");
+    assert_eq!(resolved.via_alias.as_deref(), Some("fruit"));
+
+    assert_eq!(
+        validate_one("ZZZZ", &aliases, &listed),
+        Err(Rejected::Unlisted("ZZZZ".into()))
+    );
+    assert_eq!(
+        validate_one("$$$", &aliases, &listed),
+        Err(Rejected::Invalid("$$$".into()))
+    );
+    assert_eq!(validate_one("  ", &aliases, &listed), Err(Rejected::Empty));
+}
+
+#[test]
+fn a_single_entry_takes_one_symbol() {
+    assert_eq!(
+        validate_one("AAPL,MSFT", &HashMap::new(), &[]),
+        Err(Rejected::Invalid("AAPL,MSFT".into()))
+    );
+}
+
+#[test]
+fn crypto_pairs_and_an_unknown_listing_pass() {
+    // an empty asset list means it couldn't be loaded
+    let resolved = validate_one("ZZZZ", &HashMap::new(), &[]).unwrap();
+    assert_eq!(resolved.symbol, "ZZZZ");
+    let resolved = validate_one("btc/usd", &HashMap::new(), &listed(&["AAPL"])).unwrap();
+    assert_eq!(resolved.symbol, "BTC/USD");
+}
@@ -0,0 +1,204 @@
+use std::{fmt, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Which side of the level an alert waits for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Above,
+    Below,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Above => "above",
+            Direction::Below => "below",
+        }
+    }
+
+    /// Side of `level` that `price` is on. A price exactly at the level
+    /// counts as having reached it from either side.
+    fn side(price: f64, level: f64, toward: Direction) -> Direction {
+        match toward {
+            Direction::Above if price >= level => Direction::Above,
+            Direction::Below if price <= level => Direction::Below,
+            Direction::Above => Direction::Below,
+            Direction::Below => Direction::Above,
+        }
+    }
+}
+
+/// Whether an alert goes dormant after firing or keeps watching the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mode {
+    #[default]
+    OneShot,
+    /// Re-fires each time the price crosses the level again, at most once
+    /// per `cooldown`.
+    Recurring { cooldown: Duration },
+}
+
+/// Which way `current` has moved at least `pct` percent from `reference`,
+/// if it has. A move of exactly `pct` counts.
+pub fn crossed_pct(reference: f64, current: f64, pct: f64) -> Option<Direction> {
+    if reference <= 0.0 || !reference.is_finite() {
+        return None;
+    }
+    let change = (current - reference) / reference * 100.0;
+    if change >= pct {
+        Some(Direction::Above)
+    } else if change <= -pct {
+        Some(Direction::Below)
+    } else {
+        None
+    }
+}
+
+/// A user's price alert on one symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Unique per user; used as the select menu value.
+    pub id: String,
+    pub symbol: String,
+    pub direction: Direction,
+    /// The level to watch, or for a move alert the reference price the
+    /// move is measured from.
+    pub price: f64,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub mode: Mode,
+    #[serde(default)]
+    pub last_fired_at: Option<DateTime<Utc>>,
+    /// Side of the level the price was last seen on. `None` until the first
+    /// evaluation.
+    #[serde(default)]
+    pub side: Option<Direction>,
+    /// Set on move alerts: fire once the price is this many percent away
+    /// from `price`, either way.
+    #[serde(default)]
+    pub move_pct: Option<f64>,
+}
+
+impl Alert {
+    pub fn new(symbol: &str, direction: Direction, price: f64, now: DateTime<Utc>) -> Self {
+        Self {
+            id: format!("{:X}", now.timestamp_micros()),
+            symbol: symbol.trim().to_uppercase(),
+            direction,
+            price,
+            created_at: now,
+            mode: Mode::OneShot,
+            last_fired_at: None,
+            side: None,
+            move_pct: None,
+        }
+    }
+
+    /// An alert on a move of `pct` percent either way from `reference`,
+    /// the price when it was set. Move alerts are always one-shot.
+    pub fn on_move(symbol: &str, reference: f64, pct: f64, now: DateTime<Utc>) -> Self {
+        Self {
+            move_pct: Some(pct),
+            ..Self::new(symbol, Direction::Above, reference, now)
+        }
+    }
+
+    /// Measure a move alert from `reference` from now on, re-arming it.
+    pub fn rebase(&mut self, reference: f64, now: DateTime<Utc>) {
+        self.price = reference;
+        self.created_at = now;
+        self.last_fired_at = None;
+        self.side = None;
+    }
+
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// One-shot alerts that already fired and never will again.
+    pub fn is_dormant(&self) -> bool {
+        self.mode == Mode::OneShot && self.last_fired_at.is_some()
+    }
+
+    /// Feed the latest `price` observed at `now`, returning true if the alert
+    /// fires. Updates the stored side and last-fired time in place.
+    ///
+    /// An alert fires when the price is on its side of the level and it
+    /// hasn't fired since the price was last seen on the other side, so a
+    /// recurring alert re-arms only by crossing back. A crossing inside the
+    /// cooldown keeps the alert armed and fires once the cooldown is over if
+    /// the price is still past the level.
+    pub fn evaluate(&mut self, price: f64, now: DateTime<Utc>) -> bool {
+        if self.is_dormant() {
+            return false;
+        }
+
+        if let Some(pct) = self.move_pct {
+            let Some(side) = crossed_pct(self.price, price, pct) else {
+                return false;
+            };
+            self.side = Some(side);
+            self.last_fired_at = Some(now);
+            return true;
+        }
+
+        let side = Direction::side(price, self.price, self.direction);
+        if side != self.direction {
+            self.side = Some(side);
+            return false;
+        }
+
+        let armed = self.side != Some(self.direction);
+        let cooled = match (self.mode, self.last_fired_at) {
+            (Mode::Recurring { cooldown }, Some(at)) => {
+                now.signed_duration_since(at).to_std().unwrap_or_default() >= cooldown
+            }
+            _ => true,
+        };
+        if !(armed && cooled) {
+            return false;
+        }
+
+        self.side = Some(side);
+        self.last_fired_at = Some(now);
+        true
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(pct) = self.move_pct {
+            return write!(f, "{} ±{pct}% from ${:.2}", self.symbol, self.price);
+        }
+        write!(
+            f,
+            "{} {} ${:.2}",
+            self.symbol,
+            self.direction.as_str(),
+            self.price
+        )
+    }
+}
+
+/// Order alerts for display: by symbol, then by price.
+pub fn sort(alerts: &mut [Alert]) {
+    alerts.sort_by(|a, b| {
+        a.symbol
+            .cmp(&b.symbol)
+            .then(a.price.total_cmp(&b.price))
+            .then(a.created_at.cmp(&b.created_at))
+    });
+}
+
+/// Split `alerts` into those whose id was selected and the rest, both in
+/// their original order. Unknown ids are ignored.
+pub fn split_selected(alerts: Vec<Alert>, ids: &[String]) -> (Vec<Alert>, Vec<Alert>) {
+    alerts
+        .into_iter()
+        .partition(|a| ids.iter().any(|id| id.eq_ignore_ascii_case(&a.id)))
+}
@@ -0,0 +1,276 @@
+//! Names people type in place of tickers: "apple" for AAPL, "bitcoin" for
+//! BTC/USD.
+//!
+//! A built-in table covers common company names and slang. Guilds add their
+//! own on top, stored by [`SymbolStore`](crate::SymbolStore) and passed to
+//! [`resolve`] as a map. Neither can turn a ticker into something else: an
+//! input that is a known ticker always means that ticker, and a built-in
+//! name gives way to a ticker spelled the same that is watched or listed.
+
+use std::{collections::HashMap, fmt};
+
+use crate::validate_symbol;
+
+/// Longest alias name accepted, after normalizing.
+pub const MAX_ALIAS_LEN: usize = 32;
+
+/// Most custom aliases one guild can keep.
+pub const MAX_GUILD_ALIASES: usize = 100;
+
+/// Built-in aliases, name to ticker. Names are already normalized, and none
+/// is spelled like a listed ticker: "hp" would hide HP, "btc" the BTC fund.
+const BUILTIN: &[(&str, &str)] = &[
+    // big tech
+    ("apple", "AAPL"),
+    ("microsoft", "MSFT"),
+    ("google", "GOOGL"),
+    ("alphabet", "GOOGL"),
+    ("amazon", "AMZN"),
+    ("facebook", "META"),
+    ("meta platforms", "META"),
+    ("tesla", "TSLA"),
+    ("nvidia", "NVDA"),
+    ("netflix", "NFLX"),
+    ("adobe", "ADBE"),
+    ("salesforce", "CRM"),
+    ("oracle", "ORCL"),
+    ("intel", "INTC"),
+    ("amd", "AMD"),
+    ("advanced micro devices", "AMD"),
+    ("ibm", "IBM"),
+    ("cisco", "CSCO"),
+    ("qualcomm", "QCOM"),
+    ("broadcom", "AVGO"),
+    ("micron", "MU"),
+    ("texas instruments", "TXN"),
+    ("tsmc", "TSM"),
+    ("taiwan semiconductor", "TSM"),
+    ("asml", "ASML"),
+    ("arm", "ARM"),
+    ("palantir", "PLTR"),
+    ("snowflake", "SNOW"),
+    ("shopify", "SHOP"),
+    ("spotify", "SPOT"),
+    ("uber", "UBER"),
+    ("lyft", "LYFT"),
+    ("airbnb", "ABNB"),
+    ("doordash", "DASH"),
+    ("paypal", "PYPL"),
+    ("block", "XYZ"),
+    ("square", "XYZ"),
+    ("coinbase", "COIN"),
+    ("robinhood", "HOOD"),
+    ("zoom", "ZM"),
+    ("snapchat", "SNAP"),
+    ("snap", "SNAP"),
+    ("pinterest", "PINS"),
+    ("reddit", "RDDT"),
+    ("roblox", "RBLX"),
+    ("unity", "U"),
+    ("dell", "DELL"),
+    ("super micro", "SMCI"),
+    ("supermicro", "SMCI"),
+    ("crowdstrike", "CRWD"),
+    ("servicenow", "NOW"),
+    ("alibaba", "BABA"),
+    ("baidu", "BIDU"),
+    ("sea limited", "SE"),
+    ("microstrategy", "MSTR"),
+    ("strategy", "MSTR"),
+    // consumer and industrial
+    ("walmart", "WMT"),
+    ("costco", "COST"),
+    ("target", "TGT"),
+    ("home depot", "HD"),
+    ("nike", "NKE"),
+    ("starbucks", "SBUX"),
+    ("mcdonalds", "MCD"),
+    ("mcdonald's", "MCD"),
+    ("coca cola", "KO"),
+    ("coca-cola", "KO"),
+    ("pepsi", "PEP"),
+    ("pepsico", "PEP"),
+    ("disney", "DIS"),
+    ("procter & gamble", "PG"),
+    ("p&g", "PG"),
+    ("johnson & johnson", "JNJ"),
+    ("pfizer", "PFE"),
+    ("moderna", "MRNA"),
+    ("eli lilly", "LLY"),
+    ("lilly", "LLY"),
+    ("novo nordisk", "NVO"),
+    ("unitedhealth", "UNH"),
+    ("boeing", "BA"),
+    ("lockheed martin", "LMT"),
+    ("caterpillar", "CAT"),
+    ("general motors", "GM"),
+    ("gm", "GM"),
+    ("rivian", "RIVN"),
+    ("lucid", "LCID"),
+    ("nio", "NIO"),
+    ("toyota", "TM"),
+    ("gamestop", "GME"),
+    ("amc", "AMC"),
+    ("exxon", "XOM"),
+    ("exxonmobil", "XOM"),
+    ("chevron", "CVX"),
+    // banks and payments
+    ("berkshire", "BRK.B"),
+    ("berkshire hathaway", "BRK.B"),
+    ("jpmorgan", "JPM"),
+    ("jp morgan", "JPM"),
+    ("goldman sachs", "GS"),
+    ("goldman", "GS"),
+    ("morgan stanley", "MS"),
+    ("bank of america", "BAC"),
+    ("wells fargo", "WFC"),
+    ("citigroup", "C"),
+    ("citi", "C"),
+    ("visa", "V"),
+    ("mastercard", "MA"),
+    ("american express", "AXP"),
+    ("amex", "AXP"),
+    // funds and slang
+    ("s&p 500", "SPY"),
+    ("s&p", "SPY"),
+    ("sp500", "SPY"),
+    ("nasdaq", "QQQ"),
+    ("nasdaq 100", "QQQ"),
+    ("dow jones", "DIA"),
+    ("russell", "IWM"),
+    ("russell 2000", "IWM"),
+    ("gold", "GLD"),
+    ("silver", "SLV"),
+    ("bonds", "TLT"),
+    ("vix", "VIXY"),
+    ("ark", "ARKK"),
+    // crypto
+    ("bitcoin", "BTC/USD"),
+    ("ethereum", "ETH/USD"),
+    ("ether", "ETH/USD"),
+    ("solana", "SOL/USD"),
+    ("dogecoin", "DOGE/USD"),
+    ("doge", "DOGE/USD"),
+];
+
+/// Tickers the built-in table knows, besides the ones it maps to: ETFs
+/// people type as-is. None of these can be an alias name.
+const KNOWN_TICKERS: &[&str] = &[
+    "SPY", "QQQ", "DIA", "IWM", "VOO", "VTI", "GLD", "SLV", "TLT", "ARKK", "SOXL", "TQQQ", "SQQQ",
+    "GOOG",
+];
+
+/// `symbol` as typed, or the ticker an alias stood for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolved {
+    /// Uppercased ticker to look up.
+    pub symbol: String,
+    /// The alias name it was resolved through, normalized. None when the
+    /// input was used as the ticker.
+    pub via_alias: Option<String>,
+}
+
+/// Why an alias can't be added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasError {
+    /// The name is blank once trimmed.
+    Empty,
+    /// The name is longer than [`MAX_ALIAS_LEN`].
+    TooLong,
+    /// The name is itself a known ticker, which it would hide.
+    ShadowsTicker(String),
+    /// The target isn't shaped like a ticker.
+    InvalidSymbol(String),
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AliasError::Empty => write!(f, "alias name is empty"),
+            AliasError::TooLong => write!(f, "alias name is over {MAX_ALIAS_LEN} characters"),
+            AliasError::ShadowsTicker(t) => write!(f, "alias name is the ticker {t}"),
+            AliasError::InvalidSymbol(s) => write!(f, "invalid symbol {s:?}"),
+        }
+    }
+}
+
+impl std::error::Error for AliasError {}
+
+/// Lowercase `name` with surrounding whitespace trimmed and inner runs of
+/// it collapsed to one space, so "  Home   Depot" and "home depot" match.
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// The ticker a built-in alias stands for.
+pub fn builtin(name: &str) -> Option<&'static str> {
+    let name = normalize(name);
+    BUILTIN
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, symbol)| *symbol)
+}
+
+/// Whether `symbol`, in any case, is a ticker the built-in table knows.
+pub fn is_known_ticker(symbol: &str) -> bool {
+    let symbol = symbol.trim().to_uppercase();
+    KNOWN_TICKERS.contains(&symbol.as_str()) || BUILTIN.iter().any(|(_, s)| *s == symbol)
+}
+
+/// Check a guild alias from `name` to `symbol`, returning both normalized:
+/// the name lowercased, the symbol uppercased.
+pub fn validate(name: &str, symbol: &str) -> Result<(String, String), AliasError> {
+    let name = normalize(name);
+    if name.is_empty() {
+        return Err(AliasError::Empty);
+    }
+    if name.chars().count() > MAX_ALIAS_LEN {
+        return Err(AliasError::TooLong);
+    }
+    if is_known_ticker(&name) {
+        return Err(AliasError::ShadowsTicker(name.to_uppercase()));
+    }
+
+    let symbol = symbol.trim().to_uppercase();
+    if validate_symbol(&symbol).is_err() {
+        return Err(AliasError::InvalidSymbol(symbol));
+    }
+    Ok((name, symbol))
+}
+
+/// What `input` means given a guild's `custom` aliases (normalized name to
+/// ticker). A known ticker always means itself; then the guild's aliases
+/// win over the built-in ones; anything else is taken as a ticker.
+/// `in_use` says whether an uppercased input is a ticker the guild watches
+/// or the market lists, which no built-in alias hides.
+pub fn resolve(
+    input: &str,
+    custom: &HashMap<String, String>,
+    in_use: impl Fn(&str) -> bool,
+) -> Resolved {
+    let as_typed = || Resolved {
+        symbol: input.trim().to_uppercase(),
+        via_alias: None,
+    };
+    if is_known_ticker(input) {
+        return as_typed();
+    }
+
+    let name = normalize(input);
+    let symbol = custom.get(&name).cloned().or_else(|| {
+        builtin(&name)
+            .filter(|_| !in_use(&input.trim().to_uppercase()))
+            .map(str::to_string)
+    });
+    match symbol {
+        // "spy" is its own ticker; nothing was interpreted
+        Some(symbol) if symbol != name.to_uppercase() => Resolved {
+            symbol,
+            via_alias: Some(name),
+        },
+        _ => as_typed(),
+    }
+}
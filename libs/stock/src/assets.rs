@@ -0,0 +1,115 @@
+//! The symbols Alpaca lists, for finding a ticker from part of it or from
+//! the company's name.
+//!
+//! `GET /v2/assets` on the trading API returns every US equity at once,
+//! some tens of thousands, so the list is fetched rarely and searched here.
+//! Crypto pairs aren't in it.
+
+use std::collections::HashSet;
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+/// A listed symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    pub symbol: String,
+    pub name: String,
+    /// Listing exchange, e.g. `NASDAQ` or `NYSE`.
+    pub exchange: String,
+}
+
+// https://docs.alpaca.markets/reference/get-v2-assets-1
+#[derive(Debug, Deserialize)]
+struct Asset {
+    symbol: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    exchange: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    tradable: bool,
+}
+
+/// The active, tradable assets in an assets response, sorted by symbol.
+/// Delisted and untradable ones are left out: there are no bars to fetch
+/// for them.
+pub fn parse(body: &str) -> Result<Vec<SymbolInfo>, Error> {
+    let assets: Vec<Asset> = serde_json::from_str(body)?;
+    let mut symbols: Vec<SymbolInfo> = assets
+        .into_iter()
+        .filter(|a| a.status == "active" && a.tradable)
+        .map(|a| SymbolInfo {
+            symbol: a.symbol,
+            name: a.name.trim().to_string(),
+            exchange: a.exchange,
+        })
+        .collect();
+    symbols.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    symbols.dedup_by(|a, b| a.symbol == b.symbol);
+    Ok(symbols)
+}
+
+/// How well `info` matches `query`, lower is better. None when it
+/// doesn't match at all. `query` is already uppercased.
+fn rank(info: &SymbolInfo, query: &str) -> Option<u8> {
+    let name = info.name.to_uppercase();
+    if info.symbol == query {
+        Some(0)
+    } else if info.symbol.starts_with(query) {
+        Some(1)
+    } else if name.starts_with(query) {
+        Some(2)
+    } else if name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+    {
+        Some(3)
+    } else if name.contains(query) {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+/// Up to `limit` of `symbols` matching `query` by ticker or name, best
+/// first: the exact ticker, tickers it starts, names it starts, names with
+/// a word it starts, then names containing it. Ties go to the shorter
+/// ticker, so `AAPL` comes before `AAPLW`. A blank query matches nothing.
+pub fn search(symbols: &[SymbolInfo], query: &str, limit: usize) -> Vec<SymbolInfo> {
+    let query = query.trim().to_uppercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<(u8, &SymbolInfo)> = symbols
+        .iter()
+        .filter_map(|info| Some((rank(info, &query)?, info)))
+        .collect();
+    hits.sort_by(|(ra, a), (rb, b)| {
+        ra.cmp(rb)
+            .then(a.symbol.len().cmp(&b.symbol.len()))
+            .then(a.symbol.cmp(&b.symbol))
+    });
+    hits.into_iter()
+        .take(limit)
+        .map(|(_, info)| info.clone())
+        .collect()
+}
+
+/// Whether `symbol` is among `symbols`.
+pub fn is_listed(symbols: &[SymbolInfo], symbol: &str) -> bool {
+    symbols.iter().any(|s| s.symbol == symbol)
+}
+
+/// Those of `candidates` not among `symbols`, in the order given. Crypto
+/// pairs like `BTC/USD` are never unlisted, as the list has no crypto.
+pub fn unlisted<'a>(symbols: &[SymbolInfo], candidates: &'a [String]) -> Vec<&'a String> {
+    let listed: HashSet<&str> = symbols.iter().map(|s| s.symbol.as_str()).collect();
+    candidates
+        .iter()
+        .filter(|c| !c.contains('/') && !listed.contains(c.as_str()))
+        .collect()
+}
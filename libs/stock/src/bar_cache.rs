@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::price_client::{Bar, Session, Timeframe};
+
+/// How long fetched bars are kept after they stop being served as is, so
+/// the next fetch of the same shape only asks for what came after them.
+pub const HISTORY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Identifies one `fetch_price` request shape. The window is kept in days so
+/// requests made moments apart (with slightly different `now`) share an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub days: i64,
+    pub limit: usize,
+    pub session: Session,
+}
+
+impl CacheKey {
+    pub fn new(
+        symbol: &str,
+        timeframe: Timeframe,
+        days: i64,
+        limit: usize,
+        session: Session,
+    ) -> Self {
+        Self {
+            symbol: symbol.trim().to_uppercase(),
+            timeframe,
+            days,
+            limit,
+            session,
+        }
+    }
+}
+
+struct Entry {
+    /// Until when the bars are served without a request.
+    fresh_until: Instant,
+    /// Until when they're kept to be extended by a later fetch.
+    kept_until: Instant,
+    bars: Vec<Bar>,
+}
+
+/// Short-lived in-memory cache of fetched bars. A zero TTL disables it.
+/// Entries are kept with their expiry, so warmed ones can outlive the TTL.
+/// Past it they stay around for [`HISTORY_TTL`] as a starting point that
+/// [`stale`](Self::stale) hands out, but are no longer served.
+pub struct BarCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+}
+
+impl BarCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<Vec<Bar>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|entry| entry.fresh_until > Instant::now())
+            .map(|entry| entry.bars.clone())
+    }
+
+    /// The bars last fetched for `key`, expired or not, while they're
+    /// still kept.
+    pub fn stale(&self, key: &CacheKey) -> Option<Vec<Bar>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|entry| entry.kept_until > Instant::now())
+            .map(|entry| entry.bars.clone())
+    }
+
+    /// The longest unexpired history cached for `symbol` at `timeframe`,
+    /// whatever window, limit and session it was fetched with.
+    pub fn longest(&self, symbol: &str, timeframe: Timeframe) -> Option<Vec<Bar>> {
+        let symbol = symbol.trim().to_uppercase();
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter(|(key, entry)| {
+                key.symbol == symbol && key.timeframe == timeframe && entry.fresh_until > now
+            })
+            .map(|(_, entry)| &entry.bars)
+            .max_by_key(|bars| bars.len())
+            .cloned()
+    }
+
+    /// Drop every entry, warmed ones included. Returns how many there were.
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let evicted = entries.len();
+        entries.clear();
+        evicted
+    }
+
+    pub fn insert(&self, key: CacheKey, bars: Vec<Bar>) {
+        self.insert_for(key, bars, self.ttl);
+    }
+
+    /// Cache `bars` for `ttl` rather than the cache's own TTL, for warming
+    /// ahead of a run. A zero `ttl` stores nothing.
+    pub fn insert_for(&self, key: CacheKey, bars: Vec<Bar>, ttl: Duration) {
+        if ttl.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| entry.kept_until > now);
+        entries.insert(
+            key,
+            Entry {
+                fresh_until: now + ttl,
+                kept_until: now + ttl.max(HISTORY_TTL),
+                bars,
+            },
+        );
+    }
+}
+
+/// `cached` extended with `fresh`, bars fetched from the last cached one
+/// on. Cached bars from the first fresh one on are replaced, since the last
+/// may have still been forming; bars before `start` fall out of the window,
+/// and only the most recent `limit` are kept.
+pub fn merge(
+    mut cached: Vec<Bar>,
+    fresh: Vec<Bar>,
+    start: DateTime<Utc>,
+    limit: usize,
+) -> Vec<Bar> {
+    if let Some(first) = fresh.first() {
+        cached.retain(|b| b.timestamp < first.timestamp);
+    }
+    cached.extend(fresh);
+    cached.retain(|b| b.timestamp >= start);
+    let excess = cached.len().saturating_sub(limit);
+    cached.drain(..excess);
+    cached
+}
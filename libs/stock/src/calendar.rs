@@ -0,0 +1,120 @@
+use anyhow::{Error, anyhow};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::{America::New_York, Tz};
+
+use crate::Timeframe;
+
+/// Zone times are shown in when a server hasn't picked one: the exchange's.
+pub const DEFAULT_TIMEZONE: Tz = New_York;
+
+/// Offset that keeps a whole US trading day, pre-market through after-hours
+/// (04:00-20:00 ET), and Alpaca's midnight-ET daily bar timestamps on the
+/// same calendar date whether or not daylight saving is in effect.
+const SESSION_OFFSET_HOURS: i64 = -4;
+
+/// Time on the session clock after which today's bar is expected. This is
+/// 10:30 ET in summer and 09:30 ET in winter, so never before the open.
+const SESSION_OPEN: NaiveTime = NaiveTime::from_hms_opt(10, 30, 0).unwrap();
+
+/// Regular trading hours, New York time. The close is exclusive, so the bar
+/// that starts at 16:00 is after-hours.
+const REGULAR_OPEN: NaiveTime = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+const REGULAR_CLOSE: NaiveTime = NaiveTime::from_hms_opt(16, 0, 0).unwrap();
+
+/// Trading sessions in a year, give or take a holiday.
+pub const SESSIONS_PER_YEAR: i64 = 252;
+
+/// How many `timeframe` bars one regular session holds: a bar for the day,
+/// or every intraday bar trading between the open and the close, the
+/// hourly one from 09:00 ET included.
+pub fn bars_per_session(timeframe: Timeframe) -> usize {
+    if !timeframe.is_intraday() {
+        return 1;
+    }
+    let session = (REGULAR_CLOSE - REGULAR_OPEN).num_minutes() as u64;
+    let bar = timeframe.bar_length().num_minutes().max(1) as u64;
+    session.div_ceil(bar) as usize
+}
+
+/// Whether `timestamp` falls in regular trading hours, 09:30-16:00 ET.
+/// Weekends and holidays aren't checked.
+pub fn is_regular_hours(timestamp: DateTime<Utc>) -> bool {
+    let time = timestamp.with_timezone(&New_York).time();
+    (REGULAR_OPEN..REGULAR_CLOSE).contains(&time)
+}
+
+/// Whether a bar spanning `length` from `start` trades in regular hours at
+/// all, so the hourly bar from 09:00 ET, which holds the open, counts.
+/// Weekends and holidays aren't checked; there are no bars then to filter.
+pub fn overlaps_regular_hours(start: DateTime<Utc>, length: Duration) -> bool {
+    let local = start.with_timezone(&New_York).naive_local();
+    let open = local.date().and_time(REGULAR_OPEN);
+    let close = local.date().and_time(REGULAR_CLOSE);
+    local < close && local + length > open
+}
+
+/// Whether `now` is on a weekday between the regular open and close,
+/// 09:30-16:00 ET, both included so a run at the close still sees the last
+/// bar. Holidays aren't known here; a run on one finds no bars from today.
+pub fn in_market_hours(now: DateTime<Utc>) -> bool {
+    let local = now.with_timezone(&New_York);
+    !matches!(local.weekday(), Weekday::Sat | Weekday::Sun)
+        && (REGULAR_OPEN..=REGULAR_CLOSE).contains(&local.time())
+}
+
+/// Whether `now` is the minute a `timeframe` bar closes, counting from
+/// midnight New York time: every quarter hour for 15-minute bars, on the
+/// hour for hourly ones.
+pub fn closes_bar(now: DateTime<Utc>, timeframe: Timeframe) -> bool {
+    let minutes = now
+        .with_timezone(&New_York)
+        .time()
+        .num_seconds_from_midnight()
+        / 60;
+    minutes as i64 % timeframe.bar_length().num_minutes().max(1) == 0
+}
+
+/// Trading session a timestamp belongs to.
+pub fn session_date(timestamp: DateTime<Utc>) -> NaiveDate {
+    (timestamp + Duration::hours(SESSION_OFFSET_HOURS)).date_naive()
+}
+
+/// Most recent session that should have a bar at `now`: today once the
+/// market has opened on a weekday, otherwise the previous weekday.
+///
+/// Exchange holidays are not modelled; on those days the latest bar looks
+/// stale and callers fall back to whatever fresher data they have.
+pub fn latest_session(now: DateTime<Utc>) -> NaiveDate {
+    let local = now + Duration::hours(SESSION_OFFSET_HOURS);
+    let mut date = local.date_naive();
+    if local.time() < SESSION_OPEN {
+        date = date.pred_opt().expect("date in range");
+    }
+    while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        date = date.pred_opt().expect("date in range");
+    }
+    date
+}
+
+/// An IANA zone name like `Asia/Bangkok`.
+pub fn parse_timezone(raw: &str) -> Result<Tz, Error> {
+    let name = raw.trim();
+    name.parse::<Tz>()
+        .map_err(|_| anyhow!("unknown timezone {name:?}, expected an IANA name like Asia/Bangkok"))
+}
+
+/// Chart axis label for a bar starting at `timestamp`. Intraday bars show
+/// the date and time in `tz`, so a label follows that zone's daylight
+/// saving. Longer bars show their session's date, which doesn't move with
+/// the zone: the midnight-ET daily bar is still the same session in Tokyo
+/// or Los Angeles.
+pub fn axis_label(timestamp: DateTime<Utc>, timeframe: Timeframe, tz: Tz) -> String {
+    if timeframe.is_intraday() {
+        timestamp
+            .with_timezone(&tz)
+            .format("%m-%d %H:%M")
+            .to_string()
+    } else {
+        session_date(timestamp).format("%Y-%m-%d").to_string()
+    }
+}
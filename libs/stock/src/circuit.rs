@@ -0,0 +1,242 @@
+//! Circuit breaker for a backend that can go unresponsive.
+//!
+//! Each call through a [`CircuitBreaker`] either reaches the backend or, once
+//! enough of them in a row have failed, is turned away at once with
+//! [`StoreUnavailable`] instead of waiting out a timeout of its own. After a
+//! cool-down one call is let through as a probe: if it gets an answer the
+//! circuit closes again, if not it opens for twice as long, up to a cap, so
+//! a backend that stays down is probed less and less often.
+
+use std::{
+    fmt,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Consecutive failures that open a circuit by default.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long an open circuit turns calls away by default before probing.
+pub const DEFAULT_COOL_DOWN: Duration = Duration::from_secs(30);
+
+/// Longest the cool-down grows to by default after failed probes.
+pub const DEFAULT_MAX_COOL_DOWN: Duration = Duration::from_secs(10 * 60);
+
+/// Where a circuit is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls fail fast until the cool-down is over.
+    Open,
+    /// The cool-down is over and one probe call decides what comes next.
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Name as the health check reports it: `closed`, `open` or `half_open`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// A call turned away because its circuit is open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreUnavailable {
+    /// Name of the circuit that turned it away.
+    pub circuit: &'static str,
+    /// Time left before the next probe.
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for StoreUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "store unavailable: {} circuit open, next try in {}s",
+            self.circuit,
+            self.retry_in.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for StoreUnavailable {}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    failures: u32,
+    /// When the circuit last opened.
+    opened_at: Option<Instant>,
+    /// Times it opened since it was last closed; each one after the first
+    /// doubles the cool-down.
+    opens: u32,
+    /// When the half-open probe was let through. A probe that never reports
+    /// back, say because its caller was dropped, is given up on after a
+    /// cool-down.
+    probe_started: Option<Instant>,
+}
+
+/// Counts consecutive failures of one class of calls and opens after
+/// `threshold` of them.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    threshold: u32,
+    cool_down: Duration,
+    max_cool_down: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            name,
+            threshold: threshold.max(1),
+            cool_down,
+            max_cool_down: DEFAULT_MAX_COOL_DOWN,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: None,
+                opens: 0,
+                probe_started: None,
+            }),
+        }
+    }
+
+    /// Cap the cool-down's growth at `max` instead of
+    /// [`DEFAULT_MAX_COOL_DOWN`]. Never below the first cool-down.
+    pub fn with_max_cool_down(mut self, max: Duration) -> Self {
+        self.max_cool_down = max;
+        self
+    }
+
+    /// The cool-down after the circuit opened `opens` times in a row.
+    fn cool_down_for(&self, opens: u32) -> Duration {
+        let doublings = opens.saturating_sub(1).min(16);
+        self.cool_down
+            .saturating_mul(1 << doublings)
+            .min(self.max_cool_down.max(self.cool_down))
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The state as of `now`. An open circuit whose cool-down has run out
+    /// reads as half-open even before a call probes it.
+    pub fn state_at(&self, now: Instant) -> CircuitState {
+        let inner = self.inner.lock().unwrap();
+        let cool_down = self.cool_down_for(inner.opens);
+        match (inner.state, inner.opened_at) {
+            (CircuitState::Open, Some(at)) if now.duration_since(at) >= cool_down => {
+                CircuitState::HalfOpen
+            }
+            (state, _) => state,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state_at(Instant::now())
+    }
+
+    /// Whether a call starting at `now` may go ahead. Turns it away while
+    /// the circuit is open, and while a half-open probe is out.
+    pub fn try_acquire(&self, now: Instant) -> Result<(), StoreUnavailable> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                let cool_down = self.cool_down_for(inner.opens);
+                let elapsed = inner
+                    .opened_at
+                    .map_or(cool_down, |at| now.duration_since(at));
+                if elapsed < cool_down {
+                    return Err(self.unavailable(cool_down - elapsed));
+                }
+                inner.state = CircuitState::HalfOpen;
+                inner.probe_started = Some(now);
+                info!(circuit = self.name, "circuit half-open, probing");
+                Ok(())
+            }
+            CircuitState::HalfOpen => {
+                let probing = inner
+                    .probe_started
+                    .is_some_and(|at| now.duration_since(at) < self.cool_down);
+                if probing {
+                    return Err(self.unavailable(Duration::ZERO));
+                }
+                inner.probe_started = Some(now);
+                Ok(())
+            }
+        }
+    }
+
+    /// Report how a call let through by [`Self::try_acquire`] went.
+    /// `healthy` is whether the backend answered, even with an error of
+    /// its own.
+    pub fn record(&self, now: Instant, healthy: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if healthy {
+            if inner.state != CircuitState::Closed {
+                info!(circuit = self.name, "circuit closed");
+            }
+            inner.state = CircuitState::Closed;
+            inner.failures = 0;
+            inner.opened_at = None;
+            inner.opens = 0;
+            inner.probe_started = None;
+            return;
+        }
+
+        inner.failures = inner.failures.saturating_add(1);
+        let reopen = inner.state == CircuitState::HalfOpen;
+        if reopen || (inner.state == CircuitState::Closed && inner.failures >= self.threshold) {
+            inner.opens = inner.opens.saturating_add(1);
+            warn!(
+                circuit = self.name,
+                failures = inner.failures,
+                cool_down_secs = self.cool_down_for(inner.opens).as_secs(),
+                "circuit opened"
+            );
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(now);
+            inner.probe_started = None;
+        }
+    }
+
+    /// Run `op` through the breaker. `is_outage` picks out the errors that
+    /// mean the backend didn't answer; any other result counts as healthy.
+    pub async fn call<T>(
+        &self,
+        op: impl Future<Output = Result<T, Error>>,
+        is_outage: impl Fn(&Error) -> bool,
+    ) -> Result<T, Error> {
+        self.try_acquire(Instant::now())?;
+        let result = op.await;
+        let healthy = match &result {
+            Ok(_) => true,
+            Err(e) => !is_outage(e),
+        };
+        self.record(Instant::now(), healthy);
+        result
+    }
+
+    fn unavailable(&self, retry_in: Duration) -> StoreUnavailable {
+        StoreUnavailable {
+            circuit: self.name,
+            retry_in,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Dividends and splits from Alpaca's corporate actions.
+//!
+//! `GET /v1/corporate-actions` on the data API groups actions by type. Only
+//! cash dividends and forward and reverse splits are read here; mergers,
+//! spin-offs and the rest are ignored. Announced actions come back before
+//! their ex-date, so a range reaching into the future lists upcoming ones.
+
+use anyhow::Error;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Action types asked for, as the `types` query parameter.
+pub const TYPES: &str = "cash_dividend,forward_split,reverse_split";
+
+/// A cash dividend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dividend {
+    pub symbol: String,
+    /// Cash per share.
+    pub rate: f64,
+    /// A one-off rather than a regular dividend.
+    #[serde(default)]
+    pub special: bool,
+    /// Paid by a foreign issuer, so withholding may apply.
+    #[serde(default)]
+    pub foreign: bool,
+    /// First session the shares trade without the dividend.
+    pub ex_date: NaiveDate,
+    #[serde(default)]
+    pub record_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+/// A stock split: `old_rate` shares become `new_rate`. Reverse splits have
+/// `new_rate` below `old_rate`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Split {
+    pub symbol: String,
+    pub new_rate: f64,
+    pub old_rate: f64,
+    /// First session the shares trade at the new count.
+    pub ex_date: NaiveDate,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+impl Split {
+    pub fn is_reverse(&self) -> bool {
+        self.new_rate < self.old_rate
+    }
+}
+
+/// A symbol's dividends and splits, each sorted by ex-date, oldest first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorporateActions {
+    pub dividends: Vec<Dividend>,
+    pub splits: Vec<Split>,
+}
+
+impl CorporateActions {
+    pub fn is_empty(&self) -> bool {
+        self.dividends.is_empty() && self.splits.is_empty()
+    }
+}
+
+// https://docs.alpaca.markets/reference/corporateactions-1
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(default)]
+    corporate_actions: Option<Actions>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Actions {
+    #[serde(default)]
+    cash_dividends: Option<Vec<Dividend>>,
+    #[serde(default)]
+    forward_splits: Option<Vec<Split>>,
+    #[serde(default)]
+    reverse_splits: Option<Vec<Split>>,
+}
+
+/// The dividends and splits in a corporate actions response. Types that
+/// are missing or null count as none.
+pub fn parse(body: &str) -> Result<CorporateActions, Error> {
+    let response: Response = serde_json::from_str(body)?;
+    let actions = response.corporate_actions.unwrap_or_default();
+    let mut dividends = actions.cash_dividends.unwrap_or_default();
+    dividends.sort_by_key(|d| d.ex_date);
+    let mut splits: Vec<Split> = actions
+        .forward_splits
+        .into_iter()
+        .chain(actions.reverse_splits)
+        .flatten()
+        .collect();
+    splits.sort_by_key(|s| s.ex_date);
+    Ok(CorporateActions { dividends, splits })
+}
@@ -0,0 +1,197 @@
+//! Upcoming earnings dates, so the daily summary can warn before a
+//! watchlist symbol reports.
+//!
+//! Calendars sit behind [`EarningsSource`]. Finnhub's earnings calendar is
+//! the only one today, picked with `EARNINGS_PROVIDER=finnhub` and keyed
+//! with `EARNINGS_API_KEY`. With no provider configured there is no
+//! calendar and nothing is flagged.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Context, Error, Result};
+use chrono::NaiveDate;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use tracing::{debug, info, instrument, warn};
+
+use crate::RequestFailed;
+
+/// Days ahead a report is flagged when none is configured.
+pub const DEFAULT_WITHIN_DAYS: i64 = 3;
+
+/// Finnhub's API, used when `EARNINGS_BASE_URL` isn't set.
+pub const FINNHUB_API: &str = "https://finnhub.io/api/v1";
+
+/// A symbol's scheduled earnings report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EarningsDate {
+    pub symbol: String,
+    pub date: NaiveDate,
+}
+
+/// An earnings calendar. Methods return boxed futures so the bot can hold
+/// an `Arc<dyn EarningsSource>`, and tests a mock.
+pub trait EarningsSource: Send + Sync {
+    /// Reports of `symbols` scheduled from `from` to `end`, both included.
+    /// Symbols without one are missing from the result.
+    fn upcoming<'a>(
+        &'a self,
+        symbols: &'a [String],
+        from: NaiveDate,
+        end: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<EarningsDate>>>;
+}
+
+/// Each of `symbols` reporting within `within_days` of `today`, with the
+/// days until its nearest report. Nearest first, then by symbol. A report
+/// today is zero days out; past ones aren't flagged.
+pub fn reporting_within(
+    dates: &[EarningsDate],
+    symbols: &[String],
+    today: NaiveDate,
+    within_days: i64,
+) -> Vec<(String, i64)> {
+    let mut nearest: HashMap<String, i64> = HashMap::new();
+    for earnings in dates {
+        let days = (earnings.date - today).num_days();
+        if !(0..=within_days).contains(&days) {
+            continue;
+        }
+        let symbol = earnings.symbol.to_uppercase();
+        if !symbols.iter().any(|s| s.eq_ignore_ascii_case(&symbol)) {
+            continue;
+        }
+        nearest
+            .entry(symbol)
+            .and_modify(|d| *d = (*d).min(days))
+            .or_insert(days);
+    }
+    let mut flagged: Vec<(String, i64)> = nearest.into_iter().collect();
+    flagged.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    flagged
+}
+
+// https://finnhub.io/docs/api/earnings-calendar
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinnhubResponse {
+    #[serde(default)]
+    earnings_calendar: Option<Vec<FinnhubEarnings>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubEarnings {
+    symbol: String,
+    date: NaiveDate,
+}
+
+/// The reports in a Finnhub earnings calendar response. A missing or null
+/// calendar counts as none.
+pub fn parse_finnhub(body: &str) -> Result<Vec<EarningsDate>, Error> {
+    let response: FinnhubResponse = serde_json::from_str(body)?;
+    Ok(response
+        .earnings_calendar
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| EarningsDate {
+            symbol: e.symbol.to_uppercase(),
+            date: e.date,
+        })
+        .collect())
+}
+
+/// Finnhub's earnings calendar. One request covers every symbol: the
+/// calendar for the range is fetched whole and filtered here.
+pub struct Finnhub {
+    client: reqwest::Client,
+    base_api: String,
+    token: String,
+}
+
+impl Finnhub {
+    pub fn new(base_api: impl Into<String>, token: impl Into<String>) -> Result<Self> {
+        let base_api = base_api.into().trim_end_matches('/').to_string();
+        reqwest::Url::parse(&base_api)
+            .with_context(|| format!("invalid earnings API {base_api:?}"))?;
+        Ok(Self {
+            client: reqwest::Client::builder().build()?,
+            base_api,
+            token: token.into(),
+        })
+    }
+
+    #[instrument(name = "fetch_earnings", skip(self, symbols), fields(symbols = symbols.len()))]
+    pub async fn fetch_upcoming(
+        &self,
+        symbols: &[String],
+        from: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<EarningsDate>, Error> {
+        let url = format!("{}/calendar/earnings", self.base_api);
+        let res = self
+            .client
+            .get(url)
+            .query(&[
+                ("from", from.to_string()),
+                ("to", end.to_string()),
+                ("token", self.token.clone()),
+            ])
+            .send()
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(RequestFailed::new(
+                status,
+                format!("finnhub earnings calendar request failed with {status}: {body}"),
+            )
+            .into());
+        }
+
+        let mut dates = parse_finnhub(&res.text().await?)?;
+        dates.retain(|d| symbols.iter().any(|s| s.eq_ignore_ascii_case(&d.symbol)));
+        debug!(reports = dates.len(), "fetched earnings calendar");
+        Ok(dates)
+    }
+}
+
+impl EarningsSource for Finnhub {
+    fn upcoming<'a>(
+        &'a self,
+        symbols: &'a [String],
+        from: NaiveDate,
+        end: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<EarningsDate>>> {
+        Box::pin(self.fetch_upcoming(symbols, from, end))
+    }
+}
+
+/// The calendar `EARNINGS_PROVIDER` names, keyed with `EARNINGS_API_KEY`.
+/// None when no provider is set or it isn't one this knows.
+pub fn from_env() -> Result<Option<Arc<dyn EarningsSource>>> {
+    let provider = std::env::var("EARNINGS_PROVIDER")
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match provider.as_str() {
+        "" | "none" => {
+            debug!("no earnings provider configured");
+            Ok(None)
+        }
+        "finnhub" => {
+            let token = std::env::var("EARNINGS_API_KEY")
+                .context("EARNINGS_PROVIDER=finnhub needs EARNINGS_API_KEY")?;
+            let base_api = std::env::var("EARNINGS_BASE_URL")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| FINNHUB_API.to_string());
+            info!(%base_api, "earnings provider: finnhub");
+            Ok(Some(Arc::new(Finnhub::new(base_api, token)?)))
+        }
+        _ => {
+            warn!(%provider, "unknown earnings provider, skipping");
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Price data from the first of several sources that answers.
+//!
+//! A [`FailoverSource`] tries its sources in the order they're configured
+//! with `PRICE_PROVIDERS`. A source that is down, timing out or rate
+//! limiting is passed over for the next one; any other error, an unknown
+//! symbol say, is the answer and goes back to the caller as is. Each source
+//! sits behind a [`CircuitBreaker`]: after a few outages in a row it is
+//! demoted and skipped until its cool-down is over.
+//!
+//! Feeds differ slightly, so [`PriceSource::fetch_price_served`] tells
+//! which fallback served the bars.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration as StdDuration, Instant},
+};
+
+use anyhow::{Error, Result, bail};
+use chrono::{Duration, NaiveDate};
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use tracing::{debug, info, warn};
+
+use crate::{
+    Bar, CacheFlush, InvalidSymbol, PriceClient, PriceSource, RequestFailed, Session, Snapshot,
+    Timeframe,
+    assets::SymbolInfo,
+    circuit::{CircuitBreaker, CircuitState, DEFAULT_COOL_DOWN, DEFAULT_FAILURE_THRESHOLD},
+    corporate_actions::CorporateActions,
+    indicators::annotation::AnnotationKind,
+    usage::ApiUsage,
+    yahoo::YahooSource,
+};
+
+/// Sources used when `PRICE_PROVIDERS` isn't set.
+pub const DEFAULT_PROVIDERS: &str = "alpaca";
+
+/// Whether `e` means the source couldn't answer, so the next one should be
+/// asked: a timeout, a refused connection, a server error or a rate limit.
+/// Invalid symbols and requests the source turned down are not.
+pub fn is_retryable(e: &Error) -> bool {
+    if e.is::<InvalidSymbol>() {
+        return false;
+    }
+    e.chain().any(|cause| {
+        if let Some(failed) = cause.downcast_ref::<RequestFailed>() {
+            return failed.is_outage();
+        }
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_timeout()
+                || err.is_connect()
+                || err
+                    .status()
+                    .is_some_and(|s| s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS);
+        }
+        cause.is::<tokio::time::error::Elapsed>()
+    })
+}
+
+struct Provider {
+    name: &'static str,
+    source: Arc<dyn PriceSource>,
+    circuit: CircuitBreaker,
+}
+
+/// Sources tried in order until one answers. See the [module docs](self).
+pub struct FailoverSource {
+    providers: Vec<Provider>,
+}
+
+impl FailoverSource {
+    /// Failover over `sources`, first one first, each demoted after
+    /// [`DEFAULT_FAILURE_THRESHOLD`] outages in a row for
+    /// [`DEFAULT_COOL_DOWN`].
+    pub fn new(sources: Vec<(&'static str, Arc<dyn PriceSource>)>) -> Result<Self> {
+        if sources.is_empty() {
+            bail!("failover needs at least one price source");
+        }
+        let providers = sources
+            .into_iter()
+            .map(|(name, source)| Provider {
+                name,
+                source,
+                circuit: CircuitBreaker::new(name, DEFAULT_FAILURE_THRESHOLD, DEFAULT_COOL_DOWN),
+            })
+            .collect();
+        Ok(Self { providers })
+    }
+
+    /// Demote a source after `threshold` outages in a row, for `cool_down`.
+    pub fn with_demotion(mut self, threshold: u32, cool_down: StdDuration) -> Self {
+        for provider in &mut self.providers {
+            provider.circuit = CircuitBreaker::new(provider.name, threshold, cool_down);
+        }
+        self
+    }
+
+    /// Names of the sources, in the order they're tried.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.providers.iter().map(|p| p.name).collect()
+    }
+
+    /// Sources skipped until their cool-down is over.
+    pub fn demoted(&self) -> Vec<&'static str> {
+        self.providers
+            .iter()
+            .filter(|p| p.circuit.state() == CircuitState::Open)
+            .map(|p| p.name)
+            .collect()
+    }
+
+    fn primary(&self) -> &Provider {
+        &self.providers[0]
+    }
+
+    /// `op` on each source in turn until one answers, with the name of the
+    /// one that did.
+    async fn first<'a, T>(
+        &'a self,
+        what: &str,
+        op: impl Fn(&'a dyn PriceSource) -> BoxFuture<'a, Result<T>>,
+    ) -> Result<(T, &'static str)> {
+        let mut last_outage = None;
+        for provider in &self.providers {
+            if provider.circuit.try_acquire(Instant::now()).is_err() {
+                debug!(provider = provider.name, what, "source demoted, skipping");
+                continue;
+            }
+            match op(provider.source.as_ref()).await {
+                Ok(value) => {
+                    provider.circuit.record(Instant::now(), true);
+                    return Ok((value, provider.name));
+                }
+                Err(e) if is_retryable(&e) => {
+                    provider.circuit.record(Instant::now(), false);
+                    warn!(
+                        provider = provider.name,
+                        what,
+                        error = ?e,
+                        "price source failed, trying the next"
+                    );
+                    last_outage = Some(e);
+                }
+                Err(e) => {
+                    // it answered; the request was the problem
+                    provider.circuit.record(Instant::now(), true);
+                    return Err(e);
+                }
+            }
+        }
+        match last_outage {
+            Some(e) => Err(e.context(format!("{what}: every price source failed"))),
+            None => bail!(
+                "{what}: every price source is demoted ({})",
+                self.names().join(", ")
+            ),
+        }
+    }
+}
+
+impl PriceSource for FailoverSource {
+    fn fetch_price<'a>(
+        &'a self,
+        symbol: &'a str,
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        bypass_cache: bool,
+        session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            let (bars, _) = self
+                .fetch_price_served(symbol, duration, timeframe, limit, bypass_cache, session)
+                .await?;
+            Ok(bars)
+        })
+    }
+
+    fn fetch_price_served<'a>(
+        &'a self,
+        symbol: &'a str,
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        bypass_cache: bool,
+        session: Session,
+    ) -> BoxFuture<'a, Result<(Vec<Bar>, Option<String>)>> {
+        Box::pin(async move {
+            let (bars, name) = self
+                .first("fetching bars", |source| {
+                    source.fetch_price(symbol, duration, timeframe, limit, bypass_cache, session)
+                })
+                .await?;
+            if name == self.primary().name {
+                return Ok((bars, None));
+            }
+            info!(%symbol, provider = name, "bars served by a fallback");
+            Ok((bars, Some(name.to_string())))
+        })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(async move {
+            let (snapshots, _) = self
+                .first("fetching snapshots", |source| source.fetch_prices(symbols))
+                .await?;
+            Ok(snapshots)
+        })
+    }
+
+    fn fetch_snapshot<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<Option<Snapshot>>> {
+        Box::pin(async move {
+            let (snapshot, _) = self
+                .first("fetching a snapshot", |source| {
+                    source.fetch_snapshot(symbol)
+                })
+                .await?;
+            Ok(snapshot)
+        })
+    }
+
+    /// The first source's budget; fallbacks are only asked while it's out.
+    fn usage(&self) -> Option<ApiUsage> {
+        self.primary().source.usage()
+    }
+
+    fn list_assets(&self) -> BoxFuture<'_, Result<Arc<[SymbolInfo]>>> {
+        Box::pin(async move {
+            let (assets, _) = self
+                .first("listing assets", |source| source.list_assets())
+                .await?;
+            Ok(assets)
+        })
+    }
+
+    fn flush_cache(&self) -> BoxFuture<'_, CacheFlush> {
+        Box::pin(async move {
+            let mut flushed = CacheFlush::default();
+            for provider in &self.providers {
+                let one = provider.source.flush_cache().await;
+                flushed.bars += one.bars;
+                flushed.assets |= one.assets;
+            }
+            flushed
+        })
+    }
+
+    fn search_symbols<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<SymbolInfo>>> {
+        Box::pin(async move {
+            let (found, _) = self
+                .first("searching symbols", |source| source.search_symbols(query))
+                .await?;
+            Ok(found)
+        })
+    }
+
+    fn fetch_events<'a>(
+        &'a self,
+        symbol: &'a str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<(NaiveDate, AnnotationKind)>>> {
+        Box::pin(async move {
+            let (events, _) = self
+                .first("fetching events", |source| {
+                    source.fetch_events(symbol, start, end)
+                })
+                .await?;
+            Ok(events)
+        })
+    }
+
+    fn fetch_corporate_actions<'a>(
+        &'a self,
+        symbol: &'a str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> BoxFuture<'a, Result<CorporateActions>> {
+        Box::pin(async move {
+            let (actions, _) = self
+                .first("fetching corporate actions", |source| {
+                    source.fetch_corporate_actions(symbol, start, end)
+                })
+                .await?;
+            Ok(actions)
+        })
+    }
+
+    fn cached_bars(&self, symbol: &str, timeframe: Timeframe) -> Option<Vec<Bar>> {
+        self.providers
+            .iter()
+            .find_map(|p| p.source.cached_bars(symbol, timeframe))
+    }
+
+    fn warm_bars<'a>(
+        &'a self,
+        symbols: &'a [String],
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        session: Session,
+        ttl: StdDuration,
+    ) -> BoxFuture<'a, Result<usize>> {
+        Box::pin(async move {
+            let (warmed, _) = self
+                .first("warming bars", |source| {
+                    source.warm_bars(symbols, duration, timeframe, limit, session, ttl)
+                })
+                .await?;
+            Ok(warmed)
+        })
+    }
+}
+
+/// The price source `PRICE_PROVIDERS` names, a comma-separated list of
+/// `alpaca` and `yahoo` tried in order; [`DEFAULT_PROVIDERS`] when unset. Unknown names are skipped
+/// with a warning. A single source is returned as is, more are wrapped in
+/// a [`FailoverSource`].
+pub fn from_env() -> Result<Arc<dyn PriceSource>> {
+    let raw = std::env::var("PRICE_PROVIDERS")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PROVIDERS.to_string());
+
+    let mut sources: Vec<(&'static str, Arc<dyn PriceSource>)> = Vec::new();
+    for name in raw.split(',').map(|n| n.trim().to_ascii_lowercase()) {
+        if sources.iter().any(|(known, _)| *known == name) {
+            continue;
+        }
+        match name.as_str() {
+            "alpaca" => sources.push(("alpaca", Arc::new(PriceClient::from_env()?))),
+            "yahoo" => sources.push(("yahoo", Arc::new(YahooSource::from_env()?))),
+            "" => {}
+            _ => warn!(provider = %name, "unknown price provider, skipping"),
+        }
+    }
+
+    info!(providers = ?sources.iter().map(|(n, _)| *n).collect::<Vec<_>>(), "price providers");
+    match sources.len() {
+        0 => bail!("PRICE_PROVIDERS {raw:?} names no known price provider"),
+        1 => Ok(sources.remove(0).1),
+        _ => Ok(Arc::new(FailoverSource::new(sources)?)),
+    }
+}
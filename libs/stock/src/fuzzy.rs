@@ -0,0 +1,84 @@
+//! Finding a watched symbol from part of it or a typo.
+//!
+//! With a long watchlist it's easy to forget whether it tracks `GOOG` or
+//! `GOOGL`, or to type `APPL`. Matching is on tickers only, over a list
+//! small enough to scan in full: tickers the query starts come first, then
+//! tickers a few edits away from it.
+
+/// Edits between `a` and `b`: insertions, deletions, substitutions and
+/// swaps of two neighbouring characters, each counting one. Counting a swap
+/// as one edit keeps slips like `APAL` for `AAPL` within reach.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows i-2, i-1 and i of the distance table
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Most edits a typo of `query` can be from what was meant: one for short
+/// tickers, two from five characters up. Any more and unrelated tickers
+/// start to match.
+fn max_distance(query: &str) -> usize {
+    if query.chars().count() < 5 { 1 } else { 2 }
+}
+
+/// How well `symbol` matches `query`, lower is better. None when it doesn't
+/// match at all. `query` is already uppercased.
+fn rank(symbol: &str, query: &str) -> Option<(u8, usize)> {
+    if symbol == query {
+        Some((0, 0))
+    } else if symbol.starts_with(query) {
+        Some((1, symbol.len() - query.len()))
+    } else {
+        let distance = edit_distance(symbol, query);
+        (distance <= max_distance(query)).then_some((2, distance))
+    }
+}
+
+/// Up to `limit` of `symbols` matching `query`, best first: the exact
+/// ticker, tickers it starts (shortest first), then tickers within a typo
+/// of it (fewest edits first). Ties go alphabetically. A blank query
+/// matches nothing.
+pub fn search(symbols: &[String], query: &str, limit: usize) -> Vec<String> {
+    let query = query.trim().to_uppercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<((u8, usize), &String)> = symbols
+        .iter()
+        .filter_map(|symbol| Some((rank(symbol, &query)?, symbol)))
+        .collect();
+    hits.sort();
+    hits.dedup_by(|a, b| a.1 == b.1);
+    hits.into_iter()
+        .take(limit)
+        .map(|(_, symbol)| symbol.clone())
+        .collect()
+}
+
+/// The watched symbol `query` most likely meant, when it isn't one itself.
+pub fn suggestion(symbols: &[String], query: &str) -> Option<String> {
+    let query = query.trim().to_uppercase();
+    search(symbols, &query, 1)
+        .into_iter()
+        .find(|symbol| *symbol != query)
+}
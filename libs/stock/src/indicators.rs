@@ -1 +1,13 @@
+pub mod adx;
+pub mod annotation;
+pub mod atr;
+pub mod bollinger;
 pub mod cdc;
+pub mod donchian;
+pub mod downsample;
+pub mod macd;
+pub mod relative;
+pub mod rsi;
+pub mod spread;
+pub mod volume;
+pub mod vwap;
@@ -0,0 +1,75 @@
+use tracing::{debug, instrument};
+
+/// Period used when none is configured.
+pub const DEFAULT_PERIOD: usize = 14;
+/// ADX at or above this is usually read as a trending market.
+pub const TRENDING: f64 = 25.0;
+
+/// Average directional index over `period` bars, aligned with the inputs.
+///
+/// Follows Wilder: the directional movements and true range are smoothed
+/// over `period` bars, their directional indices combined into DX, and DX
+/// smoothed again into ADX. It measures how strongly the price trends, not
+/// which way. The first `2 * period - 1` values are `NaN` while both
+/// smoothings warm up.
+#[instrument(name = "adx_calculate", skip(highs, lows, closes), fields(n = closes.len(), period))]
+pub fn calculate(highs: &[f64], lows: &[f64], closes: &[f64], period: usize) -> Vec<f64> {
+    let n = highs.len().min(lows.len()).min(closes.len());
+    let mut adx = vec![f64::NAN; n];
+    if period == 0 {
+        debug!("invalid period");
+        return adx;
+    }
+    let p = period as f64;
+
+    let (mut tr_sum, mut plus_sum, mut minus_sum) = (0.0, 0.0, 0.0);
+    let (mut dx_count, mut dx_sum, mut last) = (0, 0.0, f64::NAN);
+
+    for i in 1..n {
+        let up = highs[i] - highs[i - 1];
+        let down = lows[i - 1] - lows[i];
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+        let true_range = (highs[i] - lows[i])
+            .max((highs[i] - closes[i - 1]).abs())
+            .max((lows[i] - closes[i - 1]).abs());
+
+        if i <= period {
+            tr_sum += true_range;
+            plus_sum += plus_dm;
+            minus_sum += minus_dm;
+            if i < period {
+                continue;
+            }
+        } else {
+            tr_sum += true_range - tr_sum / p;
+            plus_sum += plus_dm - plus_sum / p;
+            minus_sum += minus_dm - minus_sum / p;
+        }
+
+        let (plus_di, minus_di) = if tr_sum > 0.0 {
+            (100.0 * plus_sum / tr_sum, 100.0 * minus_sum / tr_sum)
+        } else {
+            (0.0, 0.0)
+        };
+        let spread = plus_di + minus_di;
+        let dx = if spread > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / spread
+        } else {
+            0.0
+        };
+
+        dx_count += 1;
+        if dx_count < period {
+            dx_sum += dx;
+            continue;
+        }
+        last = if dx_count == period {
+            (dx_sum + dx) / p
+        } else {
+            (last * (p - 1.0) + dx) / p
+        };
+        adx[i] = last;
+    }
+    adx
+}
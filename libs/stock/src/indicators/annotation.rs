@@ -0,0 +1,65 @@
+//! Corporate events marked on a chart: earnings and ex-dividend dates.
+//!
+//! Events are dated by calendar day, bars by session, and the two don't
+//! always line up: an ex-date can be announced for a holiday, and earnings
+//! released on a Saturday are first traded the Monday after. Each event is
+//! drawn at the first bar on or after its date; events before the first
+//! bar or after the last one are outside the chart and dropped.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// What an annotation marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    Earnings,
+    ExDividend,
+}
+
+impl AnnotationKind {
+    /// One-letter label drawn at the top of the line.
+    pub fn label(self) -> &'static str {
+        match self {
+            AnnotationKind::Earnings => "E",
+            AnnotationKind::ExDividend => "D",
+        }
+    }
+}
+
+/// Index of the first of `dates` on or after `date`. `dates` are the
+/// session dates of the bars, oldest first; intraday bars repeat a date and
+/// the session's first bar is picked. None when `date` is before the first
+/// bar or after the last one.
+pub fn snap(dates: &[NaiveDate], date: NaiveDate) -> Option<usize> {
+    if dates.first().is_none_or(|first| date < *first) {
+        return None;
+    }
+    let index = dates.partition_point(|d| *d < date);
+    (index < dates.len()).then_some(index)
+}
+
+/// Where each of `annotations` falls among the drawn points of a chart
+/// whose window starts at `start_idx` of `dates` and keeps the points
+/// `keep` (offsets into the window), sorted by point. An event on a bar
+/// downsampling dropped is drawn at the next kept point. Two events of the
+/// same kind on one point are drawn once.
+pub fn points(
+    dates: &[NaiveDate],
+    annotations: &[(NaiveDate, AnnotationKind)],
+    start_idx: usize,
+    keep: &[usize],
+) -> Vec<(usize, AnnotationKind)> {
+    let window = dates.get(start_idx..).unwrap_or_default();
+    let mut points: Vec<(usize, AnnotationKind)> = annotations
+        .iter()
+        .filter_map(|&(date, kind)| {
+            let offset = snap(window, date)?;
+            let point = keep.iter().position(|&k| k >= offset)?;
+            Some((point, kind))
+        })
+        .collect();
+    points.sort();
+    points.dedup();
+    points
+}
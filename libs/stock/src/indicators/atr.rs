@@ -0,0 +1,34 @@
+use ta::Next;
+use ta::indicators::ExponentialMovingAverage;
+use tracing::{debug, instrument};
+
+/// Period used when none is configured.
+pub const DEFAULT_PERIOD: usize = 14;
+
+/// Average true range over `period` bars, aligned with the inputs.
+///
+/// True range is the widest of the bar's own range and its gaps from the
+/// previous close, smoothed with an EMA. The first `period` values are `NaN`
+/// while it warms up.
+#[instrument(name = "atr_calculate", skip(highs, lows, closes), fields(n = closes.len(), period))]
+pub fn calculate(highs: &[f64], lows: &[f64], closes: &[f64], period: usize) -> Vec<f64> {
+    let n = highs.len().min(lows.len()).min(closes.len());
+    let Ok(mut ema) = ExponentialMovingAverage::new(period) else {
+        debug!("invalid period");
+        return vec![f64::NAN; n];
+    };
+
+    (0..n)
+        .map(|i| {
+            let range = highs[i] - lows[i];
+            let true_range = match i.checked_sub(1).map(|p| closes[p]) {
+                Some(prev) => range
+                    .max((highs[i] - prev).abs())
+                    .max((lows[i] - prev).abs()),
+                None => range,
+            };
+            let value = ema.next(true_range);
+            if i < period { f64::NAN } else { value }
+        })
+        .collect()
+}
@@ -0,0 +1,36 @@
+use ta::Next;
+use ta::indicators::BollingerBands;
+use tracing::{debug, instrument};
+
+/// Period used when none is configured.
+pub const DEFAULT_PERIOD: usize = 20;
+/// Standard deviations between the middle band and each outer band.
+pub const DEFAULT_MULTIPLIER: f64 = 2.0;
+
+/// Bollinger bands over `period` bars, returned as `(upper, middle, lower)`.
+///
+/// Bars before the first full window are `NaN`, matching the Donchian
+/// channel, so charts leave a gap instead of drawing a partial band.
+#[instrument(name = "bollinger_calculate", skip(closes), fields(n = closes.len(), period, multiplier))]
+pub fn calculate(closes: &[f64], period: usize, multiplier: f64) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let n = closes.len();
+    let mut upper = vec![f64::NAN; n];
+    let mut middle = vec![f64::NAN; n];
+    let mut lower = vec![f64::NAN; n];
+
+    let Ok(mut bands) = BollingerBands::new(period, multiplier) else {
+        debug!("invalid period or multiplier");
+        return (upper, middle, lower);
+    };
+
+    for (i, &close) in closes.iter().enumerate() {
+        let out = bands.next(close);
+        if i + 1 >= period {
+            upper[i] = out.upper;
+            middle[i] = out.average;
+            lower[i] = out.lower;
+        }
+    }
+
+    (upper, middle, lower)
+}
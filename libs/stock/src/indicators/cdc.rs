@@ -1,15 +1,34 @@
-use anyhow::{Error, bail, ensure};
+use anyhow::{Context, Error, anyhow, bail, ensure};
+use std::{
+    io::Cursor,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
+
 use charming::{
     Chart, ImageFormat, ImageRenderer,
-    component::{Axis, Title},
-    element::{AxisType, LineStyle, Symbol, TextStyle},
-    series::Line,
+    component::{Axis, Grid, Title},
+    element::{
+        AxisLabel, AxisType, ItemStyle, Label, LineStyle, LineStyleType, MarkLine, MarkLineData,
+        MarkLineVariant, SplitLine, Symbol, TextStyle,
+    },
+    series::{Bar, Line},
 };
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 use ta::Next;
 use ta::indicators::ExponentialMovingAverage;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+use super::{
+    annotation::{self, AnnotationKind},
+    bollinger,
+    downsample::{downsample, pick},
+    macd, relative, rsi, spread,
+};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Signal {
     Buy,
     Sell,
@@ -18,8 +37,67 @@ pub enum Signal {
     None,
 }
 
-#[instrument(name = "cdc_calculate", skip(closes), fields(n = closes.len()))]
-pub fn calculate(closes: &[f64]) -> (Signal, Vec<f64>, Vec<f64>) {
+impl Signal {
+    /// Embed color for this signal under `colors`.
+    pub fn color(self, colors: &SignalColors) -> u32 {
+        match self {
+            Signal::Buy => colors.buy,
+            Signal::Sell => colors.sell,
+            Signal::BullishZone => colors.bullish_zone,
+            Signal::BearishZone => colors.bearish_zone,
+            Signal::None => colors.none,
+        }
+    }
+}
+
+/// Embed colors per signal as `0xRRGGBB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalColors {
+    pub buy: u32,
+    pub sell: u32,
+    pub bullish_zone: u32,
+    pub bearish_zone: u32,
+    pub none: u32,
+}
+
+impl Default for SignalColors {
+    fn default() -> Self {
+        Self {
+            buy: 0x00FF00,
+            sell: 0xFF0000,
+            bullish_zone: 0x00FF00,
+            bearish_zone: 0xFF0000,
+            none: 0xFFFFFF,
+        }
+    }
+}
+
+/// Parse `RRGGBB` or `#RRGGBB` into `0xRRGGBB`.
+pub fn parse_hex_color(raw: &str) -> Result<u32, Error> {
+    let hex = raw.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if let Some(bad) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        bail!("{bad:?} in {raw:?} isn't a hex digit; expected a color like #00FF00");
+    }
+    ensure!(
+        hex.len() == 6,
+        "{raw:?} has {} hex digits; expected 6, like #00FF00",
+        hex.len()
+    );
+    Ok(u32::from_str_radix(hex, 16)?)
+}
+
+/// Hysteresis band, in percent of the slow EMA, used when none is configured.
+pub const DEFAULT_BAND_PCT: f64 = 0.1;
+
+/// CDC signal for the latest bar, plus the EMA12/EMA26 series.
+///
+/// The trend only flips once the fast EMA clears the slow one by more than
+/// `band_pct` percent; inside the band it keeps its prior state, so a tight
+/// crossover can't flip Buy/Sell/Buy on consecutive runs. A band of zero is
+/// a plain crossover.
+#[instrument(name = "cdc_calculate", skip(closes), fields(n = closes.len(), band_pct))]
+pub fn calculate(closes: &[f64], band_pct: f64) -> (Signal, Vec<f64>, Vec<f64>) {
     let mut ema12 = ExponentialMovingAverage::new(12).unwrap();
     let mut ema26 = ExponentialMovingAverage::new(26).unwrap();
 
@@ -31,36 +109,385 @@ pub fn calculate(closes: &[f64]) -> (Signal, Vec<f64>, Vec<f64>) {
         ema26_vals.push(ema26.next(x));
     }
 
-    if closes.len() < 2 {
+    let signal = crossover(&ema12_vals, &ema26_vals, band_pct);
+    (signal, ema12_vals, ema26_vals)
+}
+
+/// Signal for the latest bar of any fast/slow pair of averages, with the
+/// same hysteresis band as [`calculate`].
+pub fn crossover(fast: &[f64], slow: &[f64], band_pct: f64) -> Signal {
+    if fast.len() < 2 || slow.len() < 2 {
         debug!("not enough data for signal");
-        return (Signal::None, ema12_vals, ema26_vals);
+        return Signal::None;
     }
 
-    let c = closes.len() - 1;
-    let p = closes.len() - 2;
+    let trend = trend(fast, slow, band_pct);
+    let bull = trend[trend.len() - 1];
+    let flipped = bull != trend[trend.len() - 2];
+
+    let signal = match (bull, flipped) {
+        (true, true) => Signal::Buy,
+        (false, true) => Signal::Sell,
+        (true, false) => Signal::BullishZone,
+        (false, false) => Signal::BearishZone,
+    };
 
-    let prev_fast = ema12_vals[p];
-    let prev_slow = ema26_vals[p];
-    let cur_fast = ema12_vals[c];
-    let cur_slow = ema26_vals[c];
+    info!(signal = ?signal, "signal computed");
+    signal
+}
 
-    let signal = if prev_fast <= prev_slow && cur_fast > cur_slow {
+/// The most recent bar the trend flipped on, with the same hysteresis band
+/// as [`crossover`], and whether that flip was a Buy or a Sell. `None` when
+/// the averages never crossed.
+pub fn last_crossover(fast: &[f64], slow: &[f64], band_pct: f64) -> Option<(usize, Signal)> {
+    let trend = trend(fast, slow, band_pct);
+    let index = (1..trend.len()).rev().find(|&i| trend[i] != trend[i - 1])?;
+    let signal = if trend[index] {
         Signal::Buy
-    } else if prev_fast >= prev_slow && cur_fast < cur_slow {
-        Signal::Sell
-    } else if cur_fast > cur_slow {
-        Signal::BullishZone
     } else {
-        Signal::BearishZone
+        Signal::Sell
     };
+    Some((index, signal))
+}
 
-    info!(signal = ?signal, "signal computed");
-    (signal, ema12_vals, ema26_vals)
+/// Whether the trend is bullish at each bar, flipping only once the fast
+/// average clears the slow one by more than the band.
+pub fn trend(fast: &[f64], slow: &[f64], band_pct: f64) -> Vec<bool> {
+    trend_from(fast, slow, band_pct, None)
 }
 
+/// [`trend`] carried on from `before`, the trend at the bar before the
+/// first one given, for averages cut from a longer history. Without it the
+/// first bar starts on whichever side the fast average is.
+pub fn trend_from(fast: &[f64], slow: &[f64], band_pct: f64, before: Option<bool>) -> Vec<bool> {
+    let band = band_pct.max(0.0) / 100.0;
+    let mut bull = before;
+    fast.iter()
+        .zip(slow)
+        .map(|(&fast, &slow)| {
+            let threshold = slow.abs() * band;
+            let next = match bull {
+                None => fast > slow,
+                Some(true) => fast >= slow - threshold,
+                Some(false) => fast > slow + threshold,
+            };
+            bull = Some(next);
+            next
+        })
+        .collect()
+}
+
+/// A daily crossover, kept only when the weekly trend points the same way:
+/// a Buy needs the weekly fast EMA above the slow one, a Sell below it.
+/// Unconfirmed crossovers fall back to the zone they crossed into, and
+/// anything that isn't a crossover passes through unchanged. A weekly
+/// [`Signal::None`] confirms nothing.
+pub fn confirm(daily: Signal, weekly: Signal) -> Signal {
+    match (daily, weekly) {
+        (Signal::Buy, Signal::Buy | Signal::BullishZone) => Signal::Buy,
+        (Signal::Buy, _) => Signal::BullishZone,
+        (Signal::Sell, Signal::Sell | Signal::BearishZone) => Signal::Sell,
+        (Signal::Sell, _) => Signal::BearishZone,
+        (other, _) => other,
+    }
+}
+
+/// Most points drawn per series before the chart is downsampled.
+pub const DEFAULT_MAX_POINTS: usize = 400;
+
+/// Bars a chart draws: the most recent ones, up to this many.
+pub const CHART_LOOKBACK: usize = 90;
+
+/// Bars a chart with only the averages needs, its window and the ones
+/// before it for the Bollinger bands to settle. The averages are computed
+/// over the full history before cutting, and the crossover band's state
+/// carries in through [`ChartOptions::trend_before`].
+pub const CHART_HISTORY: usize = CHART_LOOKBACK + 60;
+
+/// Which indicators a chart draws. EMAs sit on the price panel with the
+/// Bollinger bands; volume, RSI, MACD and the EMA spread each get a panel
+/// underneath, in that order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IndicatorSet {
+    pub ema: bool,
+    pub bollinger: bool,
+    pub rsi: bool,
+    pub volume: bool,
+    pub macd: bool,
+    /// The fast average less the slow one, around a zero line.
+    pub spread: bool,
+}
+
+impl IndicatorSet {
+    /// Nothing but the price line.
+    pub const NONE: Self = Self {
+        ema: false,
+        bollinger: false,
+        rsi: false,
+        volume: false,
+        macd: false,
+        spread: false,
+    };
+
+    /// Names accepted by [`FromStr`], in display order.
+    pub const NAMES: [&'static str; 6] = ["ema", "bollinger", "volume", "rsi", "macd", "spread"];
+
+    /// Panels drawn below the price panel.
+    fn panels(&self) -> usize {
+        [self.volume, self.rsi, self.macd, self.spread]
+            .iter()
+            .filter(|on| **on)
+            .count()
+    }
+}
+
+/// EMAs only, the chart every command drew before indicators were selectable.
+impl Default for IndicatorSet {
+    fn default() -> Self {
+        Self {
+            ema: true,
+            ..Self::NONE
+        }
+    }
+}
+
+/// Parse a comma-separated list such as `ema,rsi,volume`. Case and spaces
+/// are ignored; `bb` and `vol` are accepted as short names.
+impl FromStr for IndicatorSet {
+    type Err = Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut set = Self::NONE;
+        for name in raw.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name.to_lowercase().as_str() {
+                "ema" => set.ema = true,
+                "bollinger" | "bb" => set.bollinger = true,
+                "rsi" => set.rsi = true,
+                "volume" | "vol" => set.volume = true,
+                "macd" => set.macd = true,
+                "spread" => set.spread = true,
+                _ => bail!(
+                    "unknown indicator {name:?}, expected one of {}",
+                    Self::NAMES.join(", ")
+                ),
+            }
+        }
+        Ok(set)
+    }
+}
+
+/// Colors a chart is drawn in, picked per user with `/stock prefs theme`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartTheme {
+    /// Light lines on near-black, the look charts always had.
+    #[default]
+    Dark,
+    Light,
+    /// Blue and orange in place of green and red.
+    Colorblind,
+}
+
+/// Pixel density a chart is drawn at, picked per user with
+/// `/stock prefs scale`. The layout is the same at every scale: a 2x chart
+/// has twice the pixels each way, so it stays sharp on high-DPI screens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartScale {
+    #[default]
+    X1,
+    X2,
+}
+
+impl ChartScale {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChartScale::X1 => "1x",
+            ChartScale::X2 => "2x",
+        }
+    }
+
+    /// How many times [`CHART_WIDTH`] and [`CHART_HEIGHT`] the image is.
+    pub fn factor(self) -> u32 {
+        match self {
+            ChartScale::X1 => 1,
+            ChartScale::X2 => 2,
+        }
+    }
+}
+
+/// The parts of a chart a [`ChartTheme`] colors. Indicator panels keep
+/// their own colors in every theme.
+struct Palette {
+    background: &'static str,
+    title: &'static str,
+    label: &'static str,
+    grid: &'static str,
+    bull: &'static str,
+    bear: &'static str,
+    fast: &'static str,
+    slow: &'static str,
+}
+
+impl ChartTheme {
+    pub const ALL: [ChartTheme; 3] = [ChartTheme::Dark, ChartTheme::Light, ChartTheme::Colorblind];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChartTheme::Dark => "dark",
+            ChartTheme::Light => "light",
+            ChartTheme::Colorblind => "colorblind",
+        }
+    }
+
+    fn palette(self) -> Palette {
+        match self {
+            ChartTheme::Dark => Palette {
+                background: "#0b0c17",
+                title: "#ffffff",
+                label: "#a0a0a0",
+                grid: "#2d2f45",
+                bull: "#00d084",
+                bear: "#ff4d4f",
+                fast: "#0064FF",
+                slow: "#FF6400",
+            },
+            ChartTheme::Light => Palette {
+                background: "#ffffff",
+                title: "#1a1a1a",
+                label: "#555555",
+                grid: "#e3e5ec",
+                bull: "#00a36c",
+                bear: "#d9363e",
+                fast: "#0050cc",
+                slow: "#e05a00",
+            },
+            // Okabe-Ito colors, which stay apart for the common color
+            // vision deficiencies
+            ChartTheme::Colorblind => Palette {
+                background: "#0b0c17",
+                title: "#ffffff",
+                label: "#a0a0a0",
+                grid: "#2d2f45",
+                bull: "#0072B2",
+                bear: "#E69F00",
+                fast: "#56B4E9",
+                slow: "#CC79A7",
+            },
+        }
+    }
+}
+
+/// Optional overlays drawn on top of the price/EMA chart.
+#[derive(Debug, Clone)]
+pub struct ChartOptions {
+    /// Donchian channel `(upper, lower)`, aligned with `prices`.
+    pub donchian: Option<(Vec<f64>, Vec<f64>)>,
+    /// VWAP line, aligned with `prices`.
+    pub vwap: Option<Vec<f64>>,
+    /// Price the symbol was added to the watchlist at, drawn as a level.
+    pub added_price: Option<f64>,
+    /// Legend name of the added-price level.
+    pub added_name: String,
+    /// Longer windows are downsampled to this many points, with every series
+    /// kept on the same picks. Below 3 draws every point.
+    pub max_points: usize,
+    /// Indicators to draw. Those computed from closes are worked out here;
+    /// the volume panel also needs `volumes`.
+    pub indicators: IndicatorSet,
+    /// Volume per bar, aligned with `prices`.
+    pub volumes: Option<Vec<f64>>,
+    /// Benchmark to compare against in a panel of its own.
+    pub benchmark: Option<Benchmark>,
+    /// Legend names of the fast and slow averages.
+    pub average_names: (String, String),
+    pub theme: ChartTheme,
+    /// Label the last crossover of the averages in the window with its
+    /// date and whether it was a Buy or a Sell.
+    pub annotate_crossover: bool,
+    /// Hysteresis band the signal is worked out with, so the annotated
+    /// crossover is the one the signal saw.
+    pub band_pct: f64,
+    pub scale: ChartScale,
+    /// Earnings and ex-dividend dates to mark with a dashed line. Those
+    /// outside the window are skipped; see [`annotation`].
+    pub annotations: Vec<(NaiveDate, AnnotationKind)>,
+    /// Session date of each bar, aligned with `prices`. Annotations are
+    /// placed by it and dropped without it.
+    pub bar_dates: Option<Vec<NaiveDate>>,
+    /// The averages' [`trend`] at the bar before the first one given, when
+    /// they were cut from a longer history, so the band picks up where the
+    /// full history left it.
+    pub trend_before: Option<bool>,
+    /// Strip to add under the chart, for sharing; see [`brand_chart`].
+    pub brand: Option<BrandOptions>,
+}
+
+/// A benchmark drawn alongside the symbol, both rebased to
+/// [`relative::BASE`] at the start of the window, with the spread between
+/// them on a second axis.
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    pub symbol: String,
+    /// Closes aligned with `prices`.
+    pub closes: Vec<f64>,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            donchian: None,
+            vwap: None,
+            added_price: None,
+            added_name: "Added".to_string(),
+            max_points: DEFAULT_MAX_POINTS,
+            indicators: IndicatorSet::default(),
+            volumes: None,
+            benchmark: None,
+            average_names: ("EMA12".to_string(), "EMA26".to_string()),
+            theme: ChartTheme::default(),
+            annotate_crossover: false,
+            band_pct: 0.0,
+            scale: ChartScale::default(),
+            annotations: Vec::new(),
+            bar_dates: None,
+            trend_before: None,
+            brand: None,
+        }
+    }
+}
+
+const FONT: &str = "JetBrainsMono Nerd Font";
+
+/// Size a chart is laid out at; [`ChartScale`] multiplies it.
+pub const CHART_WIDTH: u32 = 1280;
+pub const CHART_HEIGHT: u32 = 720;
+
+/// Fewest bars worth charting when none is configured: enough for the slow
+/// EMA to settle. A handful of bars draws a chart that looks like something
+/// and says nothing.
+pub const DEFAULT_MIN_CHART_BARS: usize = 26;
+
+/// Whether `bars` bars are enough to chart, needing at least `min_bars` and
+/// never fewer than one. The signal has no such floor: it's computed on
+/// whatever history there is.
+pub fn enough_to_chart(bars: usize, min_bars: usize) -> bool {
+    bars >= min_bars.max(1)
+}
+
+/// Most pixels a rendered chart may have, enough for 2x. Anything bigger
+/// would be slow to draw and too heavy to post.
+pub const MAX_CHART_PIXELS: u64 = 2560 * 1440;
+
+/// Vertical layout in percent of the chart height.
+const TOP_PCT: f64 = 10.0;
+const BOTTOM_PCT: f64 = 14.0;
+const PANEL_PCT: f64 = 14.0;
+const PANEL_GAP_PCT: f64 = 4.0;
+
 #[instrument(
     name = "cdc_generate_chart",
-    skip(prices, ema12, ema26, dates),
+    skip(prices, ema12, ema26, dates, options),
     fields(
         symbol = %symbol,
         prices = prices.len(),
@@ -75,7 +502,99 @@ pub fn generate_chart(
     ema12: &[f64],
     ema26: &[f64],
     dates: &[String],
+    options: &ChartOptions,
+) -> Result<Vec<u8>, Error> {
+    let png = match render_chart(symbol, prices, ema12, ema26, dates, options) {
+        // an event marker is never worth losing the chart over
+        Err(e) if !options.annotations.is_empty() => {
+            warn!(
+                error = ?e,
+                annotations = options.annotations.len(),
+                "chart failed with annotations, drawing it without"
+            );
+            let options = ChartOptions {
+                annotations: Vec::new(),
+                ..options.clone()
+            };
+            render_chart(symbol, prices, ema12, ema26, dates, &options)
+        }
+        result => result,
+    }?;
+    match &options.brand {
+        Some(brand) => brand_chart(&png, brand),
+        None => Ok(png),
+    }
+}
+
+fn render_chart(
+    symbol: &str,
+    prices: &[f64],
+    ema12: &[f64],
+    ema26: &[f64],
+    dates: &[String],
+    options: &ChartOptions,
 ) -> Result<Vec<u8>, Error> {
+    let chart = build_chart(symbol, prices, ema12, ema26, dates, options)?;
+
+    let mut renderer = ImageRenderer::new(CHART_WIDTH, CHART_HEIGHT);
+    let png_bytes = match options.scale {
+        ChartScale::X1 => renderer.render_format(ImageFormat::Png, &chart)?,
+        // echarts still lays the chart out at 1x; scaling the SVG keeps
+        // text and lines in proportion
+        scale => rasterize(&renderer.render(&chart)?, scale)?,
+    };
+
+    info!(
+        bytes = png_bytes.len(),
+        scale = options.scale.as_str(),
+        "chart rendered"
+    );
+    Ok(png_bytes)
+}
+
+/// `svg`, laid out at [`CHART_WIDTH`] by [`CHART_HEIGHT`], drawn to a PNG
+/// `scale` times that size each way.
+pub fn rasterize(svg: &str, scale: ChartScale) -> Result<Vec<u8>, Error> {
+    use resvg::{tiny_skia::Pixmap, usvg};
+
+    let factor = scale.factor();
+    let (width, height) = (CHART_WIDTH * factor, CHART_HEIGHT * factor);
+    ensure!(
+        u64::from(width) * u64::from(height) <= MAX_CHART_PIXELS,
+        "a {width}x{height} chart is over the {MAX_CHART_PIXELS} pixel limit"
+    );
+
+    let options = usvg::Options {
+        fontdb: raster_fonts(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let Some(mut pixmap) = Pixmap::new(width, height) else {
+        bail!("can't draw a {width}x{height} chart");
+    };
+    let factor = factor as f32;
+    resvg::render(
+        &tree,
+        usvg::Transform::from_scale(factor, factor),
+        &mut pixmap.as_mut(),
+    );
+
+    let image = image::RgbaImage::from_raw(width, height, pixmap.take())
+        .ok_or_else(|| anyhow!("chart buffer has the wrong size"))?;
+    let mut out = Vec::new();
+    image.write_to(&mut Cursor::new(&mut out), image::ImageFormat::Png)?;
+    Ok(out)
+}
+
+/// The chart [`generate_chart`] renders, before it is turned into an image.
+pub fn build_chart(
+    symbol: &str,
+    prices: &[f64],
+    ema12: &[f64],
+    ema26: &[f64],
+    dates: &[String],
+    options: &ChartOptions,
+) -> Result<Chart, Error> {
     ensure!(!prices.is_empty(), "prices is empty");
     ensure!(
         prices.len() == ema12.len() && prices.len() == ema26.len() && prices.len() == dates.len(),
@@ -85,30 +604,76 @@ pub fn generate_chart(
         ema26.len(),
         dates.len()
     );
+    if let Some((upper, lower)) = &options.donchian {
+        ensure!(
+            upper.len() == prices.len() && lower.len() == prices.len(),
+            "donchian length mismatch: prices={}, upper={}, lower={}",
+            prices.len(),
+            upper.len(),
+            lower.len()
+        );
+    }
+    if let Some(vwap) = &options.vwap {
+        ensure!(
+            vwap.len() == prices.len(),
+            "vwap length mismatch: prices={}, vwap={}",
+            prices.len(),
+            vwap.len()
+        );
+    }
+    if let Some(volumes) = &options.volumes {
+        ensure!(
+            volumes.len() == prices.len(),
+            "volume length mismatch: prices={}, volumes={}",
+            prices.len(),
+            volumes.len()
+        );
+    }
 
-    const LOOKBACK: usize = 90;
-    const WIDTH: u32 = 1280;
-    const HEIGHT: u32 = 720;
+    if let Some(benchmark) = &options.benchmark {
+        ensure!(
+            benchmark.closes.len() == prices.len(),
+            "benchmark length mismatch: prices={}, benchmark={}",
+            prices.len(),
+            benchmark.closes.len()
+        );
+    }
 
-    let lookback = LOOKBACK.min(prices.len());
+    let lookback = CHART_LOOKBACK.min(prices.len());
     let start_idx = prices.len().saturating_sub(lookback);
 
-    let display_prices = &prices[start_idx..];
-    let display_ema12 = &ema12[start_idx..];
-    let display_ema26 = &ema26[start_idx..];
-    let display_dates = &dates[start_idx..];
+    // every series is cut to the same points, picked from the closes
+    let keep = downsample(&prices[start_idx..], options.max_points);
+    let window = |series: &[f64]| pick(&series[start_idx..], &keep);
+
+    let display_prices = window(prices);
+    let display_ema12 = window(ema12);
+    let display_ema26 = window(ema26);
+    let display_dates = pick(&dates[start_idx..], &keep);
+    // the band's trend over everything given, not just the window
+    let sides = trend_from(ema12, ema26, options.band_pct, options.trend_before);
+    let display_sides = pick(&sides[start_idx..], &keep);
 
     let n = display_prices.len();
     if n == 0 {
         bail!("no data to display after slicing");
     }
+    let display_spread = options
+        .indicators
+        .spread
+        .then(|| spread::calculate(&display_ema12, &display_ema26));
 
-    debug!(lookback = n, start_idx, "prepared display window");
+    debug!(
+        lookback = prices.len() - start_idx,
+        points = n,
+        start_idx,
+        "prepared display window"
+    );
 
     let mut price_green = vec![f64::NAN; n];
     let mut price_red = vec![f64::NAN; n];
 
-    let mut prev_bull = display_ema12[0] > display_ema26[0];
+    let mut prev_bull = display_sides[0];
     if prev_bull {
         price_green[0] = display_prices[0];
     } else {
@@ -116,7 +681,7 @@ pub fn generate_chart(
     }
 
     for i in 1..n {
-        let bull = display_ema12[i] > display_ema26[i];
+        let bull = display_sides[i];
 
         if bull {
             price_green[i] = display_prices[i];
@@ -134,83 +699,718 @@ pub fn generate_chart(
     }
 
     let last_price = *display_prices.last().unwrap_or(&0.0);
+    // about nine date labels however many points are drawn
+    let label_interval = (n / 9).saturating_sub(1) as f64;
 
-    let chart = Chart::new()
-        .background_color("#0b0c17")
-        .title(
-            Title::new()
-                .text(format!("{} | ${:.2}", symbol.to_uppercase(), last_price))
-                .left("center")
-                .top("2%")
-                .text_style(
-                    TextStyle::new()
-                        .color("#ffffff")
-                        .font_size(14)
-                        .font_family("JetBrainsMono Nerd Font"),
-                ),
-        )
-        .x_axis(
-            Axis::new()
-                .type_(AxisType::Category)
-                .data(display_dates.to_vec())
-                .axis_label(
-                    charming::element::AxisLabel::new()
-                        .rotate(45)
-                        .interval(9)
-                        .color("#a0a0a0")
-                        .font_family("JetBrainsMono Nerd Font"),
-                )
-                .split_line(
-                    charming::element::SplitLine::new()
-                        .line_style(charming::element::LineStyle::new().color("#2d2f45")),
-                ),
-        )
-        .y_axis(
+    let indicators = options.indicators;
+    let volumes = options.volumes.as_ref().filter(|_| indicators.volume);
+    let panels = indicators.panels() - usize::from(indicators.volume && volumes.is_none())
+        + usize::from(options.benchmark.is_some());
+
+    let palette = options.theme.palette();
+    let mut chart = Chart::new().background_color(palette.background).title(
+        Title::new()
+            .text(format!("{} | ${:.2}", symbol.to_uppercase(), last_price))
+            .left("center")
+            .top("2%")
+            .text_style(
+                TextStyle::new()
+                    .color(palette.title)
+                    .font_size(14)
+                    .font_family(FONT),
+            ),
+    );
+
+    // one grid per panel, the price panel taking what the others leave
+    let main_pct = 100.0 - TOP_PCT - BOTTOM_PCT - panels as f64 * (PANEL_PCT + PANEL_GAP_PCT);
+    let mut grid_top = TOP_PCT;
+    for grid in 0..=panels {
+        let height = if grid == 0 { main_pct } else { PANEL_PCT };
+        if panels > 0 {
+            chart = chart.grid(
+                Grid::new()
+                    .left("8%")
+                    .right("4%")
+                    .top(format!("{grid_top}%"))
+                    .height(format!("{height}%")),
+            );
+        }
+        grid_top += height + PANEL_GAP_PCT;
+
+        let last = grid == panels;
+        chart = chart
+            .x_axis(
+                Axis::new()
+                    .type_(AxisType::Category)
+                    .grid_index(grid as f64)
+                    .data(display_dates.clone())
+                    .axis_label(
+                        AxisLabel::new()
+                            .show(last)
+                            .rotate(45)
+                            .interval(label_interval)
+                            .color(palette.label)
+                            .font_family(FONT),
+                    )
+                    .split_line(SplitLine::new().line_style(LineStyle::new().color(palette.grid))),
+            )
+            .y_axis(
+                Axis::new()
+                    .type_(AxisType::Value)
+                    .grid_index(grid as f64)
+                    .scale(true)
+                    .axis_label(AxisLabel::new().color(palette.label).font_family(FONT))
+                    .split_line(SplitLine::new().line_style(LineStyle::new().color(palette.grid))),
+            );
+    }
+
+    // the spread sits on its own axis to the right of the benchmark panel,
+    // after the one y axis each grid has
+    let spread_axis = panels as f64 + 1.0;
+    if options.benchmark.is_some() {
+        chart = chart.y_axis(
             Axis::new()
                 .type_(AxisType::Value)
+                .grid_index(panels as f64)
+                .position("right")
                 .scale(true)
-                .axis_label(
-                    charming::element::AxisLabel::new()
-                        .color("#a0a0a0")
-                        .font_family("JetBrainsMono Nerd Font"),
+                .axis_label(AxisLabel::new().color(palette.label).font_family(FONT))
+                .split_line(SplitLine::new().show(false)),
+        );
+    }
+
+    let mut bull_line = Line::new()
+        .name("Price (Bull)")
+        .data(price_green)
+        .symbol(Symbol::None)
+        .line_style(LineStyle::new().width(2).color(palette.bull));
+    let mut bear_line = Line::new()
+        .name("Price (Bear)")
+        .data(price_red)
+        .symbol(Symbol::None)
+        .line_style(LineStyle::new().width(2).color(palette.bear));
+
+    let annotation = options
+        .annotate_crossover
+        .then(|| crossover_point(&sides, options.trend_before, start_idx, &keep))
+        .flatten();
+    if let Some((point, signal)) = annotation {
+        let (name, color) = match signal {
+            Signal::Buy => ("Buy", palette.bull),
+            _ => ("Sell", palette.bear),
+        };
+        debug!(point, ?signal, "annotating crossover");
+        let mark = MarkLine::new()
+            .symbol(vec![Symbol::None, Symbol::None])
+            .line_style(
+                LineStyle::new()
+                    .width(1)
+                    .color(color)
+                    .type_(LineStyleType::Dashed),
+            )
+            .label(
+                Label::new()
+                    .formatter(format!("{name}: {}", display_dates[point]).as_str())
+                    .color(color)
+                    .font_family(FONT),
+            )
+            .data(vec![MarkLineVariant::Simple(
+                MarkLineData::new().x_axis(point as f64),
+            )]);
+        match signal {
+            Signal::Buy => bull_line = bull_line.mark_line(mark),
+            _ => bear_line = bear_line.mark_line(mark),
+        }
+    }
+
+    chart = chart.series(bull_line).series(bear_line);
+
+    let events = event_points(options, prices.len(), start_idx, &keep);
+    for (kind, name, color) in [
+        (AnnotationKind::Earnings, "Earnings", "#4cc9f0"),
+        (AnnotationKind::ExDividend, "Ex-Dividend", "#f5c542"),
+    ] {
+        let lines: Vec<MarkLineData> = events
+            .iter()
+            .filter(|(_, k)| *k == kind)
+            .map(|&(point, _)| {
+                MarkLineData::new().x_axis(point as f64).label(
+                    Label::new()
+                        .formatter(kind.label())
+                        .color(color)
+                        .font_family(FONT),
                 )
-                .split_line(
-                    charming::element::SplitLine::new()
-                        .line_style(charming::element::LineStyle::new().color("#2d2f45")),
-                ),
-        )
-        .series(
+            })
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        debug!(?kind, count = lines.len(), "annotating events");
+        chart = chart.series(
             Line::new()
-                .name("Price (Bull)")
-                .data(price_green)
+                .name(name)
+                .data(vec![f64::NAN; n])
                 .symbol(Symbol::None)
-                .line_style(LineStyle::new().width(2).color("#00d084")),
-        )
-        .series(
+                .mark_line(
+                    MarkLine::new()
+                        .symbol(vec![Symbol::None, Symbol::None])
+                        .line_style(
+                            LineStyle::new()
+                                .width(1)
+                                .opacity(0.8)
+                                .color(color)
+                                .type_(LineStyleType::Dashed),
+                        )
+                        .data(lines.into_iter().map(MarkLineVariant::Simple).collect()),
+                ),
+        );
+    }
+
+    if indicators.ema {
+        chart = chart
+            .series(
+                Line::new()
+                    .name(options.average_names.0.as_str())
+                    .data(display_ema12)
+                    .symbol(Symbol::None)
+                    .line_style(LineStyle::new().width(1).color(palette.fast)),
+            )
+            .series(
+                Line::new()
+                    .name(options.average_names.1.as_str())
+                    .data(display_ema26)
+                    .symbol(Symbol::None)
+                    .line_style(LineStyle::new().width(1).color(palette.slow)),
+            );
+    }
+
+    if indicators.bollinger {
+        let (upper, middle, lower) = bollinger::calculate(
+            prices,
+            bollinger::DEFAULT_PERIOD,
+            bollinger::DEFAULT_MULTIPLIER,
+        );
+        for (name, values, style) in [
+            ("Bollinger High", &upper, LineStyleType::Solid),
+            ("Bollinger Mid", &middle, LineStyleType::Dotted),
+            ("Bollinger Low", &lower, LineStyleType::Solid),
+        ] {
+            chart = chart.series(
+                Line::new()
+                    .name(name)
+                    .data(window(values))
+                    .symbol(Symbol::None)
+                    .line_style(
+                        LineStyle::new()
+                            .width(1)
+                            .opacity(0.7)
+                            .color("#f5c542")
+                            .type_(style),
+                    ),
+            );
+        }
+    }
+
+    if let Some((upper, lower)) = &options.donchian {
+        for (name, values) in [("Donchian High", upper), ("Donchian Low", lower)] {
+            chart = chart.series(
+                Line::new()
+                    .name(name)
+                    .data(window(values))
+                    .symbol(Symbol::None)
+                    .line_style(
+                        LineStyle::new()
+                            .width(1)
+                            .color("#8c8c8c")
+                            .type_(LineStyleType::Dashed),
+                    ),
+            );
+        }
+    }
+
+    if let Some(vwap) = &options.vwap {
+        chart = chart.series(
             Line::new()
-                .name("Price (Bear)")
-                .data(price_red)
+                .name("VWAP")
+                .data(window(vwap))
                 .symbol(Symbol::None)
-                .line_style(LineStyle::new().width(2).color("#ff4d4f")),
-        )
-        .series(
+                .line_style(
+                    LineStyle::new()
+                        .width(1)
+                        .color("#c77dff")
+                        .type_(LineStyleType::Dotted),
+                ),
+        );
+    }
+
+    if let Some(added) = options.added_price {
+        chart = chart.series(
             Line::new()
-                .name("EMA12")
-                .data(display_ema12.to_vec())
+                .name(options.added_name.as_str())
+                .data(vec![added; n])
                 .symbol(Symbol::None)
-                .line_style(LineStyle::new().width(1).color("#0064FF")),
-        )
-        .series(
+                .line_style(
+                    LineStyle::new()
+                        .width(1)
+                        .opacity(0.6)
+                        .color("#d9d9d9")
+                        .type_(LineStyleType::Dashed),
+                ),
+        );
+    }
+
+    let mut panel = 0.0;
+
+    if let Some(volumes) = volumes {
+        panel += 1.0;
+        chart = chart.series(
+            Bar::new()
+                .name("Volume")
+                .data(window(volumes))
+                .x_axis_index(panel)
+                .y_axis_index(panel)
+                .item_style(ItemStyle::new().color("#4a5a8a")),
+        );
+    }
+
+    if indicators.rsi {
+        panel += 1.0;
+        let values = rsi::calculate(prices, rsi::DEFAULT_PERIOD);
+        chart = chart.series(
             Line::new()
-                .name("EMA26")
-                .data(display_ema26.to_vec())
+                .name("RSI")
+                .data(window(&values))
                 .symbol(Symbol::None)
-                .line_style(LineStyle::new().width(1).color("#FF6400")),
+                .x_axis_index(panel)
+                .y_axis_index(panel)
+                .line_style(LineStyle::new().width(1).color("#c77dff")),
+        );
+    }
+
+    if indicators.macd {
+        panel += 1.0;
+        let (line, signal, histogram) = macd::calculate(
+            prices,
+            macd::FAST_PERIOD,
+            macd::SLOW_PERIOD,
+            macd::SIGNAL_PERIOD,
         );
+        chart = chart
+            .series(
+                Bar::new()
+                    .name("MACD Histogram")
+                    .data(window(&histogram))
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .item_style(ItemStyle::new().color("#5c5f7a")),
+            )
+            .series(
+                Line::new()
+                    .name("MACD")
+                    .data(window(&line))
+                    .symbol(Symbol::None)
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .line_style(LineStyle::new().width(1).color("#0064FF")),
+            )
+            .series(
+                Line::new()
+                    .name("MACD Signal")
+                    .data(window(&signal))
+                    .symbol(Symbol::None)
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .line_style(LineStyle::new().width(1).color("#FF6400")),
+            );
+    }
 
-    let mut renderer = ImageRenderer::new(WIDTH, HEIGHT);
-    let png_bytes = renderer.render_format(ImageFormat::Png, &chart)?;
+    if let Some(values) = display_spread {
+        panel += 1.0;
+        let (bull, bear) = spread::split_by_trend(&values, &display_sides);
+        chart = chart
+            .series(
+                Line::new()
+                    .name("Zero")
+                    .data(vec![0.0; n])
+                    .symbol(Symbol::None)
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .line_style(
+                        LineStyle::new()
+                            .width(1)
+                            .color(palette.label)
+                            .type_(LineStyleType::Dashed),
+                    ),
+            )
+            .series(
+                Line::new()
+                    .name("Spread (Bull)")
+                    .data(bull)
+                    .symbol(Symbol::None)
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .line_style(LineStyle::new().width(1).color(palette.bull)),
+            )
+            .series(
+                Line::new()
+                    .name("Spread (Bear)")
+                    .data(bear)
+                    .symbol(Symbol::None)
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .line_style(LineStyle::new().width(1).color(palette.bear)),
+            );
+    }
 
-    info!(bytes = png_bytes.len(), "chart rendered");
-    Ok(png_bytes)
+    if let Some(benchmark) = &options.benchmark {
+        panel += 1.0;
+        let symbol_line = relative::rebase(&display_prices);
+        let benchmark_line = relative::rebase(&window(&benchmark.closes));
+        let spread = relative::spread(&symbol_line, &benchmark_line);
+        chart = chart
+            .series(
+                Bar::new()
+                    .name("Spread")
+                    .data(spread)
+                    .x_axis_index(panel)
+                    .y_axis_index(spread_axis)
+                    .item_style(ItemStyle::new().color("#4a5a8a").opacity(0.6)),
+            )
+            .series(
+                Line::new()
+                    .name(symbol.to_uppercase())
+                    .data(symbol_line)
+                    .symbol(Symbol::None)
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .line_style(LineStyle::new().width(1).color("#ffffff")),
+            )
+            .series(
+                Line::new()
+                    .name(benchmark.symbol.to_uppercase())
+                    .data(benchmark_line)
+                    .symbol(Symbol::None)
+                    .x_axis_index(panel)
+                    .y_axis_index(panel)
+                    .line_style(LineStyle::new().width(1).color("#c77dff")),
+            );
+    }
+
+    debug!(?indicators, panels, "built chart");
+    Ok(chart)
+}
+
+/// Where `options.annotations` fall among the drawn points. Annotations
+/// that can't be placed are dropped with a warning rather than failing the
+/// chart.
+fn event_points(
+    options: &ChartOptions,
+    len: usize,
+    start_idx: usize,
+    keep: &[usize],
+) -> Vec<(usize, AnnotationKind)> {
+    if options.annotations.is_empty() {
+        return Vec::new();
+    }
+    match &options.bar_dates {
+        Some(dates) if dates.len() == len => {
+            annotation::points(dates, &options.annotations, start_idx, keep)
+        }
+        Some(dates) => {
+            warn!(
+                prices = len,
+                bar_dates = dates.len(),
+                "bar dates don't line up with prices, dropping annotations"
+            );
+            Vec::new()
+        }
+        None => {
+            warn!("no bar dates to place annotations by, dropping them");
+            Vec::new()
+        }
+    }
+}
+
+/// Where the last flip of `trend` falls among the drawn points, for a
+/// window starting at `start_idx` and keeping `keep`. A flip on a point
+/// downsampling dropped is drawn at the kept point before it; one before
+/// the window isn't drawn. The first bar is a flip when it leaves `before`.
+fn crossover_point(
+    trend: &[bool],
+    before: Option<bool>,
+    start_idx: usize,
+    keep: &[usize],
+) -> Option<(usize, Signal)> {
+    let index = (0..trend.len()).rev().find(|&i| match i {
+        0 => before.is_some_and(|b| b != trend[0]),
+        i => trend[i] != trend[i - 1],
+    })?;
+    let signal = if trend[index] {
+        Signal::Buy
+    } else {
+        Signal::Sell
+    };
+    let offset = index.checked_sub(start_idx)?;
+    let point = keep.iter().rposition(|&k| k <= offset)?;
+    Some((point, signal))
+}
+
+/// Size of a sparkline when the caller has no reason to pick another.
+pub const SPARKLINE_SIZE: (u32, u32) = (240, 60);
+
+/// Blocks [`sparkline_unicode`] draws with, lowest first.
+const SPARK_BLOCKS: [char; 5] = ['▁', '▂', '▃', '▅', '▇'];
+
+/// Where each of `closes` sits between their low (0) and high (1). A flat
+/// series sits in the middle.
+fn spark_levels(closes: &[f64]) -> Vec<f64> {
+    let (low, high) = closes
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &c| {
+            (lo.min(c), hi.max(c))
+        });
+    let range = high - low;
+    closes
+        .iter()
+        .map(|&c| if range > 0.0 { (c - low) / range } else { 0.5 })
+        .collect()
+}
+
+/// Bare trend line of `closes` as a `width`×`height` PNG: no axes, no
+/// labels, on a transparent background. Green when the last close is above
+/// the first, red when below, grey when flat.
+///
+/// Drawn directly rather than through the chart renderer, so a listing can
+/// afford one per row.
+#[instrument(name = "cdc_generate_sparkline", skip(closes), fields(n = closes.len(), width, height))]
+pub fn generate_sparkline(closes: &[f64], width: u32, height: u32) -> Result<Vec<u8>, Error> {
+    use tiny_skia::{Color, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+    let closes: Vec<f64> = closes.iter().copied().filter(|c| c.is_finite()).collect();
+    ensure!(!closes.is_empty(), "no closes to draw");
+    let Some(mut pixmap) = Pixmap::new(width, height) else {
+        bail!("can't draw a {width}x{height} sparkline");
+    };
+
+    let (first, last) = (closes[0], closes[closes.len() - 1]);
+    let (r, g, b) = if last > first {
+        (0x00, 0xd0, 0x84)
+    } else if last < first {
+        (0xff, 0x4d, 0x4f)
+    } else {
+        (0xa0, 0xa0, 0xa0)
+    };
+
+    // keep the stroke clear of the edges
+    const PAD: f32 = 2.0;
+    let (w, h) = (width as f32 - 2.0 * PAD, height as f32 - 2.0 * PAD);
+    let step = if closes.len() > 1 {
+        w / (closes.len() - 1) as f32
+    } else {
+        0.0
+    };
+    let point = |i: usize, level: f64| (PAD + step * i as f32, PAD + h * (1.0 - level as f32));
+
+    let levels = spark_levels(&closes);
+    let mut path = PathBuilder::new();
+    let (x, y) = point(0, levels[0]);
+    path.move_to(x, y);
+    if levels.len() == 1 {
+        path.line_to(PAD + w, y);
+    }
+    for (i, &level) in levels.iter().enumerate().skip(1) {
+        let (x, y) = point(i, level);
+        path.line_to(x, y);
+    }
+    let Some(path) = path.finish() else {
+        bail!("sparkline path is empty");
+    };
+
+    let mut paint = Paint::default();
+    paint.set_color(Color::from_rgba8(r, g, b, 0xff));
+    paint.anti_alias = true;
+    let stroke = Stroke {
+        width: 2.0,
+        ..Default::default()
+    };
+    pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+
+    let png = pixmap.encode_png()?;
+    debug!(bytes = png.len(), "sparkline rendered");
+    Ok(png)
+}
+
+/// `closes` as a row of block characters, one per close, for when an image
+/// would cost too much. A flat series is a row of middle blocks; an empty
+/// one is an empty string.
+pub fn sparkline_unicode(closes: &[f64]) -> String {
+    let closes: Vec<f64> = closes.iter().copied().filter(|c| c.is_finite()).collect();
+    let top = (SPARK_BLOCKS.len() - 1) as f64;
+    spark_levels(&closes)
+        .into_iter()
+        .map(|level| SPARK_BLOCKS[(level * top).round() as usize])
+        .collect()
+}
+
+/// Height of the strip [`brand_chart`] adds under a chart.
+pub const BRAND_STRIP_HEIGHT: u32 = 40;
+
+/// Longest watermark [`sanitize_watermark`] keeps, in characters.
+pub const WATERMARK_MAX_CHARS: usize = 40;
+
+const BADGE_WIDTH: u32 = 96;
+
+/// What [`brand_chart`] writes in the strip under a shared chart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BrandOptions {
+    pub bot_name: String,
+    /// Date of the chart's last bar, already formatted.
+    pub date: String,
+    pub signal: Signal,
+    /// Set per guild; pass it through [`sanitize_watermark`] first.
+    pub watermark: Option<String>,
+}
+
+/// `raw` cut down to something safe to draw: control and invisible
+/// formatting characters removed, whitespace runs collapsed to single
+/// spaces, and at most [`WATERMARK_MAX_CHARS`] characters. `None` when
+/// nothing is left.
+pub fn sanitize_watermark(raw: &str) -> Option<String> {
+    let invisible = |c: char| {
+        c.is_control()
+            || matches!(c, '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' | '\u{FEFF}')
+    };
+    let cleaned: String = raw
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .filter(|&c| !invisible(c))
+        .collect();
+    let cleaned: String = cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(WATERMARK_MAX_CHARS)
+        .collect();
+    let cleaned = cleaned.trim_end();
+    (!cleaned.is_empty()).then(|| cleaned.to_string())
+}
+
+/// `png`, a rendered chart, with a strip [`BRAND_STRIP_HEIGHT`] pixels tall
+/// added along the bottom: a badge for the signal, the bot's name and date,
+/// and the watermark on the right. The chart itself is left uncovered, so
+/// the result is that much taller and just as wide.
+#[instrument(name = "cdc_brand_chart", skip_all, fields(bytes = png.len(), signal = ?options.signal))]
+pub fn brand_chart(png: &[u8], options: &BrandOptions) -> Result<Vec<u8>, Error> {
+    use image::{ImageFormat as RasterFormat, RgbaImage, imageops};
+
+    let chart = image::load_from_memory_with_format(png, RasterFormat::Png)
+        .context("chart is not a readable PNG")?
+        .to_rgba8();
+    let (width, height) = chart.dimensions();
+    let total = height
+        .checked_add(BRAND_STRIP_HEIGHT)
+        .ok_or_else(|| anyhow!("chart is too tall to brand"))?;
+
+    let strip = brand_strip(width, options)?;
+    let mut branded = RgbaImage::new(width, total);
+    imageops::replace(&mut branded, &chart, 0, 0);
+    imageops::replace(&mut branded, &strip, 0, i64::from(height));
+
+    let mut out = Vec::new();
+    branded.write_to(&mut Cursor::new(&mut out), RasterFormat::Png)?;
+    debug!(width, height = total, bytes = out.len(), "chart branded");
+    Ok(out)
+}
+
+/// The strip itself, drawn as SVG so the text goes through the same font
+/// handling as the charts.
+fn brand_strip(width: u32, options: &BrandOptions) -> Result<image::RgbaImage, Error> {
+    use resvg::{tiny_skia::Pixmap, usvg};
+
+    let palette = ChartTheme::Dark.palette();
+    let (badge, label) = match options.signal {
+        Signal::Buy => (palette.bull, "BUY"),
+        Signal::Sell => (palette.bear, "SELL"),
+        Signal::BullishZone => (palette.bull, "BULLISH"),
+        Signal::BearishZone => (palette.bear, "BEARISH"),
+        Signal::None => (palette.label, "NO SIGNAL"),
+    };
+    let height = BRAND_STRIP_HEIGHT;
+    let byline = format!("{} · {}", options.bot_name, options.date);
+    let watermark = options.watermark.as_deref().unwrap_or_default();
+
+    let svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}">
+<rect width="{width}" height="{height}" fill="{background}"/>
+<rect width="{width}" height="1" fill="{grid}"/>
+<rect x="12" y="9" width="{BADGE_WIDTH}" height="22" rx="4" fill="{badge}"/>
+<g font-family="{FONT}, monospace" font-size="13">
+<text x="{badge_mid}" y="25" text-anchor="middle" font-weight="bold" fill="{background}">{label}</text>
+<text x="{byline_x}" y="25" fill="{title}">{byline}</text>
+<text x="{right}" y="25" text-anchor="end" fill="{muted}">{watermark}</text>
+</g>
+</svg>"##,
+        background = palette.background,
+        grid = palette.grid,
+        title = palette.title,
+        muted = palette.label,
+        badge_mid = 12 + BADGE_WIDTH / 2,
+        byline_x = 12 + BADGE_WIDTH + 12,
+        right = width.saturating_sub(12),
+        byline = escape_xml(&byline),
+        watermark = escape_xml(watermark),
+    );
+
+    let options = usvg::Options {
+        fontdb: raster_fonts(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &options)?;
+    let Some(mut pixmap) = Pixmap::new(width, height) else {
+        bail!("can't draw a {width}x{height} strip");
+    };
+    resvg::render(&tree, usvg::Transform::identity(), &mut pixmap.as_mut());
+
+    // the background is opaque, so premultiplied pixels are plain RGBA
+    image::RgbaImage::from_raw(width, height, pixmap.take())
+        .ok_or_else(|| anyhow!("strip buffer has the wrong size"))
+}
+
+/// System fonts for drawing SVG ourselves, loaded once on first use.
+/// `monospace` falls back to any installed monospaced face rather than
+/// fontdb's Courier New.
+fn raster_fonts() -> Arc<resvg::usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<resvg::usvg::fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = resvg::usvg::fontdb::Database::new();
+            fonts.load_system_fonts();
+            let mono = fonts
+                .faces()
+                .find(|face| face.monospaced)
+                .and_then(|face| face.families.first())
+                .map(|(family, _)| family.clone());
+            if let Some(family) = mono {
+                fonts.set_monospace_family(family);
+            }
+            debug!(faces = fonts.len(), "loaded fonts for rasterizing");
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
 }
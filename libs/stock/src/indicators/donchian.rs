@@ -0,0 +1,64 @@
+use tracing::{debug, instrument};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Breakout {
+    Bullish,
+    Bearish,
+    None,
+}
+
+/// Donchian channel over `period` bars, returned as `(upper, lower, middle)`.
+///
+/// Each value covers the window ending at that bar (inclusive). Bars before
+/// the first full window are `NaN` so charts leave a gap instead of drawing a
+/// misleading partial channel.
+#[instrument(name = "donchian_calculate", skip(highs, lows), fields(n = highs.len(), period))]
+pub fn calculate(highs: &[f64], lows: &[f64], period: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let n = highs.len().min(lows.len());
+    let mut upper = vec![f64::NAN; n];
+    let mut lower = vec![f64::NAN; n];
+    let mut middle = vec![f64::NAN; n];
+
+    if period == 0 || n < period {
+        debug!("not enough data for channel");
+        return (upper, lower, middle);
+    }
+
+    for i in (period - 1)..n {
+        let window = i + 1 - period..=i;
+        let hi = highs[window.clone()]
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let lo = lows[window].iter().copied().fold(f64::INFINITY, f64::min);
+
+        upper[i] = hi;
+        lower[i] = lo;
+        middle[i] = (hi + lo) / 2.0;
+    }
+
+    (upper, lower, middle)
+}
+
+/// Turtle-style breakout on the latest bar: the close pushing above the
+/// previous bar's upper channel is bullish, below its lower channel bearish.
+pub fn breakout(closes: &[f64], upper: &[f64], lower: &[f64]) -> Breakout {
+    let n = closes.len();
+    if n < 2 || upper.len() != n || lower.len() != n {
+        return Breakout::None;
+    }
+
+    let close = closes[n - 1];
+    let prior_upper = upper[n - 2];
+    let prior_lower = lower[n - 2];
+
+    if prior_upper.is_nan() || prior_lower.is_nan() {
+        Breakout::None
+    } else if close > prior_upper {
+        Breakout::Bullish
+    } else if close < prior_lower {
+        Breakout::Bearish
+    } else {
+        Breakout::None
+    }
+}
@@ -0,0 +1,76 @@
+use tracing::{debug, instrument};
+
+/// Indices of at most `max_points` values that keep the shape of `values`,
+/// picked with largest-triangle-three-buckets.
+///
+/// The first and last points are always kept. In between, each bucket keeps
+/// the point spanning the largest triangle with the previous pick and the
+/// next bucket's average, so peaks and troughs survive where plain striding
+/// would skip them. Series that already fit, or a `max_points` below 3, keep
+/// every index.
+#[instrument(name = "downsample", skip(values), fields(n = values.len(), max_points))]
+pub fn downsample(values: &[f64], max_points: usize) -> Vec<usize> {
+    let n = values.len();
+    if n <= max_points || max_points < 3 {
+        return (0..n).collect();
+    }
+
+    let every = (n - 2) as f64 / (max_points - 2) as f64;
+    let bucket = |i: usize| ((i as f64 * every) as usize + 1).min(n);
+
+    let mut picked = Vec::with_capacity(max_points);
+    picked.push(0);
+    let mut a = 0;
+
+    for i in 0..max_points - 2 {
+        let (avg_x, avg_y) = average(values, bucket(i + 1), bucket(i + 2), values[a]);
+        let (ax, ay) = (a as f64, values[a]);
+
+        let (start, end) = (bucket(i), bucket(i + 1));
+        let mut best = start;
+        let mut best_area = -1.0;
+        for (j, &y) in values.iter().enumerate().take(end).skip(start) {
+            let area = ((ax - avg_x) * (y - ay) - (ax - j as f64) * (avg_y - ay)).abs();
+            // NaN areas compare false, so gaps are never picked over real points
+            if area > best_area {
+                best_area = area;
+                best = j;
+            }
+        }
+
+        picked.push(best);
+        // a bucket that is all gap keeps the gap but can't anchor the next
+        if values[best].is_finite() {
+            a = best;
+        }
+    }
+
+    picked.push(n - 1);
+    debug!(kept = picked.len(), "downsampled series");
+    picked
+}
+
+/// The values of `series` at `indices`, for downsampling every series on a
+/// chart with the same picks.
+pub fn pick<T: Clone>(series: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|&i| series[i].clone()).collect()
+}
+
+/// Mean position and value of the finite points in `start..end`, falling back
+/// to `fallback` at the bucket's start when none are.
+fn average(values: &[f64], start: usize, end: usize, fallback: f64) -> (f64, f64) {
+    let (mut sum_x, mut sum_y, mut count) = (0.0, 0.0, 0.0);
+    for (x, &y) in values.iter().enumerate().take(end).skip(start) {
+        if y.is_finite() {
+            sum_x += x as f64;
+            sum_y += y;
+            count += 1.0;
+        }
+    }
+
+    if count == 0.0 {
+        (start as f64, fallback)
+    } else {
+        (sum_x / count, sum_y / count)
+    }
+}
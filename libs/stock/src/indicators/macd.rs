@@ -0,0 +1,71 @@
+use ta::Next;
+use ta::indicators::MovingAverageConvergenceDivergence;
+use tracing::{debug, instrument};
+
+pub const FAST_PERIOD: usize = 12;
+pub const SLOW_PERIOD: usize = 26;
+pub const SIGNAL_PERIOD: usize = 9;
+
+/// Where the MACD line stands against its signal line on the latest bar.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MacdState {
+    /// Crossed above the signal line on the latest bar.
+    BullishCross,
+    /// Crossed below the signal line on the latest bar.
+    BearishCross,
+    Bullish,
+    Bearish,
+}
+
+/// MACD over the given periods, returned as `(macd, signal, histogram)`.
+///
+/// Bars before the signal line has a full slow window behind it are `NaN`.
+#[instrument(name = "macd_calculate", skip(closes), fields(n = closes.len(), fast, slow, signal))]
+pub fn calculate(
+    closes: &[f64],
+    fast: usize,
+    slow: usize,
+    signal: usize,
+) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let n = closes.len();
+    let mut macd_line = vec![f64::NAN; n];
+    let mut signal_line = vec![f64::NAN; n];
+    let mut histogram = vec![f64::NAN; n];
+
+    let Ok(mut macd) = MovingAverageConvergenceDivergence::new(fast, slow, signal) else {
+        debug!("invalid periods");
+        return (macd_line, signal_line, histogram);
+    };
+
+    let warmup = slow + signal - 1;
+    for (i, &close) in closes.iter().enumerate() {
+        let out = macd.next(close);
+        if i + 1 >= warmup {
+            macd_line[i] = out.macd;
+            signal_line[i] = out.signal;
+            histogram[i] = out.histogram;
+        }
+    }
+
+    (macd_line, signal_line, histogram)
+}
+
+/// State of the latest bar, or None until two bars have a signal line.
+pub fn state(macd: &[f64], signal: &[f64]) -> Option<MacdState> {
+    let n = macd.len().min(signal.len());
+    if n < 2 {
+        return None;
+    }
+
+    let (prev, last) = (macd[n - 2] - signal[n - 2], macd[n - 1] - signal[n - 1]);
+    if !prev.is_finite() || !last.is_finite() {
+        return None;
+    }
+
+    Some(match (prev > 0.0, last > 0.0) {
+        (false, true) => MacdState::BullishCross,
+        (true, false) => MacdState::BearishCross,
+        (_, true) => MacdState::Bullish,
+        (_, false) => MacdState::Bearish,
+    })
+}
@@ -0,0 +1,41 @@
+use std::cmp::Ordering;
+
+use crate::Bar;
+
+/// Level both lines of a benchmark comparison start from.
+pub const BASE: f64 = 100.0;
+
+/// The bars of `a` and `b` stamped at the same time, paired up oldest
+/// first, so two histories line up bar for bar even when either is missing
+/// some. Both are oldest first.
+pub fn align<'a>(a: &'a [Bar], b: &'a [Bar]) -> Vec<(&'a Bar, &'a Bar)> {
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = Vec::with_capacity(a.len().min(b.len()));
+    while i < a.len() && j < b.len() {
+        match a[i].timestamp.cmp(&b[j].timestamp) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                pairs.push((&a[i], &b[j]));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    pairs
+}
+
+/// `values` scaled so the first finite, non-zero one reads [`BASE`]. Gaps
+/// stay gaps; a series with nothing to rebase from is all gaps.
+pub fn rebase(values: &[f64]) -> Vec<f64> {
+    match values.iter().find(|v| v.is_finite() && **v != 0.0) {
+        Some(&first) => values.iter().map(|v| v * BASE / first).collect(),
+        None => vec![f64::NAN; values.len()],
+    }
+}
+
+/// Points `symbol` is ahead of `benchmark` once both are rebased, aligned
+/// with the inputs.
+pub fn spread(symbol: &[f64], benchmark: &[f64]) -> Vec<f64> {
+    symbol.iter().zip(benchmark).map(|(s, b)| s - b).collect()
+}
@@ -0,0 +1,31 @@
+use ta::Next;
+use ta::indicators::RelativeStrengthIndex;
+use tracing::{debug, instrument};
+
+/// Period used when none is configured.
+pub const DEFAULT_PERIOD: usize = 14;
+/// RSI at or above this reads as overbought.
+pub const OVERBOUGHT: f64 = 70.0;
+/// RSI at or below this reads as oversold.
+pub const OVERSOLD: f64 = 30.0;
+
+/// Relative strength index over `period` bars, aligned with `closes`.
+///
+/// The first `period` values are `NaN` while the averages warm up, so a short
+/// history never reports a reading off a handful of bars.
+#[instrument(name = "rsi_calculate", skip(closes), fields(n = closes.len(), period))]
+pub fn calculate(closes: &[f64], period: usize) -> Vec<f64> {
+    let Ok(mut rsi) = RelativeStrengthIndex::new(period) else {
+        debug!("invalid period");
+        return vec![f64::NAN; closes.len()];
+    };
+
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| {
+            let value = rsi.next(close);
+            if i < period { f64::NAN } else { value }
+        })
+        .collect()
+}
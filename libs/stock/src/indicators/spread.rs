@@ -0,0 +1,46 @@
+//! The gap between the fast and slow averages. Its sign is roughly the
+//! trend the crossover signal follows and its size is how far apart the
+//! averages have pulled. The trend itself only flips once the gap clears
+//! the hysteresis band, so crossings and sides are read from
+//! [`cdc::trend`] rather than the raw sign.
+
+use super::cdc::{self, Signal};
+
+/// `fast - slow` at each bar, aligned with the inputs. A bar missing either
+/// average is `NaN`.
+pub fn calculate(fast: &[f64], slow: &[f64]) -> Vec<f64> {
+    fast.iter().zip(slow).map(|(f, s)| f - s).collect()
+}
+
+/// Where the trend of `fast` against `slow` flips, with the same
+/// hysteresis band as [`cdc::crossover`]: [`Signal::Buy`] at the bar it
+/// turns bullish, [`Signal::Sell`] where it turns bearish. A spread that
+/// dips across zero without clearing the band isn't a crossing.
+pub fn crossings(fast: &[f64], slow: &[f64], band_pct: f64) -> Vec<(usize, Signal)> {
+    cdc::trend(fast, slow, band_pct)
+        .windows(2)
+        .enumerate()
+        .filter_map(|(i, pair)| match pair {
+            [false, true] => Some((i + 1, Signal::Buy)),
+            [true, false] => Some((i + 1, Signal::Sell)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `spread` split by `trend`, its [`cdc::trend`] at each bar, into the
+/// bullish and bearish stretches, each `NaN` where the other is drawn, so
+/// the two can be colored apart. The bar before a flip is in both, which
+/// keeps the line joined.
+pub fn split_by_trend(spread: &[f64], trend: &[bool]) -> (Vec<f64>, Vec<f64>) {
+    let mut bull = vec![f64::NAN; spread.len()];
+    let mut bear = vec![f64::NAN; spread.len()];
+    for (i, (&value, &side)) in spread.iter().zip(trend).enumerate() {
+        let line = if side { &mut bull } else { &mut bear };
+        line[i] = value;
+        if i > 0 && trend[i - 1] != side {
+            line[i - 1] = spread[i - 1];
+        }
+    }
+    (bull, bear)
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use tracing::{debug, instrument};
+
+use crate::Bar;
+
+/// Bars the latest one is compared against when none is configured.
+pub const DEFAULT_LOOKBACK: usize = 20;
+/// Multiple of the average that makes a spike when none is configured.
+pub const DEFAULT_THRESHOLD: f64 = 3.0;
+/// Trailing average volume below which a symbol trades too thinly for a
+/// ratio to mean anything: a few hundred shares against an average of ten
+/// would otherwise read as a huge spike.
+pub const MIN_AVERAGE_VOLUME: f64 = 10_000.0;
+
+/// The latest bar's volume against the bars before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeSpike {
+    pub volume: f64,
+    /// Mean volume of the `lookback` bars before the latest.
+    pub average: f64,
+    /// `volume` over `average`.
+    pub ratio: f64,
+    pub close: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The latest bar's volume against the mean of the `lookback` bars before
+/// it, the latest bar left out so a spike can't lift its own baseline. None
+/// with fewer than `lookback` earlier bars, or when their average is under
+/// [`MIN_AVERAGE_VOLUME`].
+///
+/// A latest bar still in progress is judged on what has traded so far, so a
+/// partial session can only hide a spike, never invent one.
+#[instrument(name = "volume_relative", skip(bars), fields(n = bars.len(), lookback))]
+pub fn relative(bars: &[Bar], lookback: usize) -> Option<VolumeSpike> {
+    let (latest, earlier) = bars.split_last()?;
+    if lookback == 0 || earlier.len() < lookback {
+        debug!("not enough history for a volume average");
+        return None;
+    }
+
+    let window = &earlier[earlier.len() - lookback..];
+    let average = window.iter().map(|b| b.volume).sum::<f64>() / lookback as f64;
+    if average < MIN_AVERAGE_VOLUME {
+        debug!(average, "average volume under the floor");
+        return None;
+    }
+    Some(VolumeSpike {
+        volume: latest.volume,
+        average,
+        ratio: latest.volume / average,
+        close: latest.close,
+        timestamp: latest.timestamp,
+    })
+}
+
+/// [`relative`] volume when it's at least `threshold` times the average.
+pub fn spike(bars: &[Bar], lookback: usize, threshold: f64) -> Option<VolumeSpike> {
+    relative(bars, lookback).filter(|spike| spike.ratio >= threshold)
+}
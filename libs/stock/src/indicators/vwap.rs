@@ -0,0 +1,53 @@
+use chrono::NaiveDate;
+use tracing::{debug, instrument};
+
+use crate::{Bar, Timeframe, calendar::session_date};
+
+/// Cumulative VWAP, reset at the start of every session.
+///
+/// `prices`, `volumes` and `sessions` are aligned per bar. Bars traded before
+/// any volume in their session are `NaN`; later zero-volume bars carry the
+/// running value forward.
+#[instrument(name = "vwap_cumulative", skip(prices, volumes, sessions), fields(n = prices.len()))]
+pub fn cumulative(prices: &[f64], volumes: &[f64], sessions: &[NaiveDate]) -> Vec<f64> {
+    let n = prices.len().min(volumes.len()).min(sessions.len());
+    let mut out = Vec::with_capacity(n);
+
+    let mut session = None;
+    let mut pv = 0.0;
+    let mut vol = 0.0;
+
+    for i in 0..n {
+        if session != Some(sessions[i]) {
+            session = Some(sessions[i]);
+            pv = 0.0;
+            vol = 0.0;
+        }
+
+        pv += prices[i] * volumes[i];
+        vol += volumes[i];
+        out.push(if vol > 0.0 { pv / vol } else { f64::NAN });
+    }
+
+    out
+}
+
+/// VWAP line for a chart of `bars`: each bar's own VWAP on daily and longer
+/// timeframes, a session-anchored cumulative VWAP intraday.
+pub fn for_bars(bars: &[Bar], timeframe: Timeframe) -> Vec<f64> {
+    if !timeframe.is_intraday() {
+        return bars.iter().map(|b| b.vwap.unwrap_or(f64::NAN)).collect();
+    }
+
+    // the bar's own VWAP is the exact average price for its volume; fall back
+    // to the typical price when Alpaca omits it
+    let prices: Vec<f64> = bars
+        .iter()
+        .map(|b| b.vwap.unwrap_or((b.high + b.low + b.close) / 3.0))
+        .collect();
+    let volumes: Vec<f64> = bars.iter().map(|b| b.volume).collect();
+    let sessions: Vec<NaiveDate> = bars.iter().map(|b| session_date(b.timestamp)).collect();
+    debug!(bars = bars.len(), "computing session vwap");
+
+    cumulative(&prices, &volumes, &sessions)
+}
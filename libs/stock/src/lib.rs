@@ -1,7 +1,47 @@
+mod bar_cache;
 mod price_client;
+mod price_source;
+mod renderer;
+mod series;
+mod settings;
 mod symbol_store;
 
+pub mod alert;
+pub mod alias;
+pub mod assets;
+pub mod calendar;
+pub mod circuit;
+pub mod corporate_actions;
+pub mod earnings;
+pub mod failover;
+pub mod fuzzy;
 pub mod indicators;
+pub mod position;
+pub mod report;
+pub mod scan;
+pub mod spotlight;
+pub mod stats;
+pub mod strategy;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod timing;
+pub mod usage;
+pub mod yahoo;
 
-pub use price_client::{PriceClient, Timeframe};
-pub use symbol_store::SymbolStore;
+pub use price_client::{
+    Bar, BidAsk, CacheFlush, DATA_API, Feed, InvalidSymbol, PaperOrLive, PriceClient, Quote,
+    QuoteSource, RequestFailed, Session, Snapshot, Timeframe, Trade, normalize_data_api,
+    validate_symbol,
+};
+pub use price_source::PriceSource;
+pub use renderer::{ChartJob, ChartRenderer, RenderTimeout};
+pub use series::{DataSource, OhlcvSeries};
+pub use settings::{
+    GuildSettings, INTRADAY_TIMEFRAMES, IntradaySettings, MAX_NOTE_LEN, MAX_TAG_LEN, MAX_TAGS,
+    PostedSignal, SymbolMeta, UserPrefs, normalize_note, normalize_tag, route_channels,
+};
+pub use symbol_store::{
+    BrowseState, DeleteOutcome, KeyStats, Scope, StoreCircuits, StoreStats, StoredAlert,
+    SymbolImpact, SymbolStore, WatchlistCleanup, key_category, mute_active, mute_score, mute_until,
+    used_memory,
+};
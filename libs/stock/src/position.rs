@@ -0,0 +1,14 @@
+//! Rough entry prices members keep for symbols they hold, and how far the
+//! price has moved from them. Entries are what the member says they paid;
+//! nothing here knows about real fills or position sizes.
+
+/// Whether `price` can be an entry: finite and above zero.
+pub fn valid_entry(price: f64) -> bool {
+    price.is_finite() && price > 0.0
+}
+
+/// Unrealized profit or loss at `price` on a position entered at `entry`,
+/// in percent. None for an entry that isn't [`valid_entry`].
+pub fn pnl_pct(entry: f64, price: f64) -> Option<f64> {
+    valid_entry(entry).then(|| (price - entry) / entry * 100.0)
+}
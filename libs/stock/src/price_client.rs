@@ -1,16 +1,302 @@
-use anyhow::{Error, Result};
-use chrono::{DateTime, Duration, Utc};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration as StdDuration, Instant},
+};
+
+use anyhow::{Error, Result, anyhow};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use reqwest::{
-    Client,
+    Client, RequestBuilder, Response, StatusCode, Url,
     header::{HeaderMap, HeaderValue},
 };
-use serde::Deserialize;
-use tracing::{debug, info, instrument};
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    assets::{self, SymbolInfo},
+    bar_cache::{self, BarCache, CacheKey},
+    calendar,
+    corporate_actions::{self, CorporateActions},
+    indicators::annotation::AnnotationKind,
+    usage::{self, ApiUsage, UsageTracker},
+};
+
+/// Trading API the asset list comes from when `APCA_TRADING_BASE_URL`
+/// isn't set. Paper and live list the same assets.
+pub const DEFAULT_TRADING_API: &str = "https://paper-api.alpaca.markets";
+
+/// Market data API bars, snapshots and corporate actions come from, for
+/// paper and live keys alike.
+pub const DATA_API: &str = "https://data.alpaca.markets";
+
+const DATA_HOST: &str = "data.alpaca.markets";
+
+const LIVE_TRADING_API: &str = "https://api.alpaca.markets";
+
+/// How long the asset list is reused. Listings change a few times a day at
+/// most.
+pub const ASSET_CACHE_TTL: StdDuration = StdDuration::from_secs(6 * 3600);
+
+/// Most results [`PriceClient::search_symbols`] returns; Discord shows 25
+/// autocomplete choices.
+pub const SEARCH_LIMIT: usize = 25;
+
+/// How long fetched bars are reused when `BAR_CACHE_TTL_SECS` isn't set.
+pub const DEFAULT_CACHE_TTL: StdDuration = StdDuration::from_secs(60);
+
+/// Longest symbol accepted, generous for share classes and crypto pairs.
+pub const MAX_SYMBOL_LEN: usize = 15;
+
+/// Bars asked for per page of a multi-symbol request. Alpaca counts the
+/// limit across all the symbols in it.
+const MULTI_PAGE_LIMIT: usize = 10_000;
+
+/// Header Alpaca reports the requests left in the current minute in.
+const RATE_LIMIT_REMAINING: &str = "X-RateLimit-Remaining";
+
+/// Send `req`, counting it against `usage` and taking Alpaca's own
+/// remaining count from the response.
+async fn send_counted(usage: &UsageTracker, req: RequestBuilder) -> Result<Response, Error> {
+    usage.record();
+    let res = req.send().await?;
+    if let Some(remaining) = res
+        .headers()
+        .get(RATE_LIMIT_REMAINING)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+    {
+        usage.observe_remaining(remaining);
+    }
+    Ok(res)
+}
+
+/// A symbol that can't be a ticker. Returned before any request is made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSymbol(pub String);
+
+impl fmt::Display for InvalidSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid symbol {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSymbol {}
+
+/// An Alpaca request answered with an error status, kept apart from other
+/// errors so an outage can be told from a request Alpaca won't serve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestFailed {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl RequestFailed {
+    pub fn new(status: StatusCode, message: String) -> Self {
+        Self { status, message }
+    }
+
+    /// Whether the status says Alpaca is down or busy rather than that the
+    /// request was wrong: a server error or a rate limit.
+    pub fn is_outage(&self) -> bool {
+        self.status.is_server_error() || self.status == StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
+impl fmt::Display for RequestFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RequestFailed {}
+
+/// Check `symbol` is shaped like a ticker: ASCII letters and digits, with
+/// `.` or `-` share-class separators (BRK.B, BF-B), or a `BASE/QUOTE` pair.
+/// Pairs are sent to the stocks endpoints with the slash percent-encoded;
+/// the crypto endpoints aren't supported.
+pub fn validate_symbol(symbol: &str) -> Result<(), InvalidSymbol> {
+    let part_ok = |part: &str| {
+        part.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    };
+
+    let valid = !symbol.is_empty()
+        && symbol.len() <= MAX_SYMBOL_LEN
+        && match symbol.split_once('/') {
+            Some((base, quote)) => part_ok(base) && part_ok(quote),
+            None => part_ok(symbol),
+        };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(InvalidSymbol(symbol.to_string()))
+    }
+}
+
+/// Alpaca market data feed. IEX is free but only covers 08:00-17:00 ET, so
+/// its extended-hours bars are thin; SIP covers 04:00-20:00 and needs a paid
+/// plan for recent data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Feed {
+    #[default]
+    Iex,
+    Sip,
+}
+
+impl Feed {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feed::Iex => "iex",
+            Feed::Sip => "sip",
+        }
+    }
+
+    /// Parse a feed name as set in `ALPACA_FEED`, ignoring case.
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "iex" => Some(Feed::Iex),
+            "sip" => Some(Feed::Sip),
+            _ => None,
+        }
+    }
+}
+
+/// Which Alpaca account a trading API belongs to, picked with
+/// `ALPACA_ACCOUNT`. Market data comes from [`DATA_API`] either way; only
+/// the asset list is read from the trading API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaperOrLive {
+    #[default]
+    Paper,
+    Live,
+}
+
+impl PaperOrLive {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaperOrLive::Paper => "paper",
+            PaperOrLive::Live => "live",
+        }
+    }
+
+    /// Parse an account name as set in `ALPACA_ACCOUNT`, ignoring case.
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "paper" => Some(PaperOrLive::Paper),
+            "live" => Some(PaperOrLive::Live),
+            _ => None,
+        }
+    }
+
+    pub fn trading_api(&self) -> &'static str {
+        match self {
+            PaperOrLive::Paper => DEFAULT_TRADING_API,
+            PaperOrLive::Live => LIVE_TRADING_API,
+        }
+    }
+
+    /// The account `url` is Alpaca's trading API for, if it's one of them.
+    pub fn of_url(url: &str) -> Option<Self> {
+        match Url::parse(url.trim()).ok()?.host_str()? {
+            "paper-api.alpaca.markets" => Some(PaperOrLive::Paper),
+            "api.alpaca.markets" => Some(PaperOrLive::Live),
+            _ => None,
+        }
+    }
+}
+
+/// `raw` as a base for the market data endpoints, as set in
+/// `APCA_API_BASE_URL`. A trading API would answer every bars request with
+/// a 404, so one is swapped for [`DATA_API`]; a trailing `/v2` is dropped
+/// since the endpoints add their own. Any other host, a proxy or a mock, is
+/// kept as is.
+pub fn normalize_data_api(raw: &str) -> Result<String> {
+    let mut url =
+        Url::parse(raw.trim()).map_err(|e| anyhow!("data API {raw:?} is not a URL: {e}"))?;
+    if let Some(account) = PaperOrLive::of_url(raw) {
+        warn!(
+            configured = raw,
+            account = account.as_str(),
+            data_api = DATA_API,
+            "data API points at the trading API, using the data API instead"
+        );
+        return Ok(DATA_API.to_string());
+    }
+
+    let versioned = url
+        .path()
+        .trim_end_matches('/')
+        .strip_suffix("/v2")
+        .map(str::to_string);
+    if let Some(base) = versioned {
+        warn!(configured = raw, "dropping /v2 from the data API");
+        url.set_path(&base);
+    }
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+/// Which trading hours intraday bars should cover. Alpaca's bars endpoint
+/// has no session parameter and always returns pre-market and after-hours
+/// minute bars along with the regular session, so `Regular` is applied to
+/// the response. Daily and longer bars are regular-session aggregates
+/// either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Session {
+    /// 09:30-16:00 ET only.
+    #[default]
+    Regular,
+    /// Everything the feed has, pre-market and after-hours included.
+    Extended,
+}
+
+impl Session {
+    /// Whether a `timeframe` bar starting at `timestamp` belongs in this
+    /// session.
+    pub fn includes(&self, timeframe: Timeframe, timestamp: DateTime<Utc>) -> bool {
+        match self {
+            Session::Extended => true,
+            Session::Regular => {
+                !timeframe.is_intraday()
+                    || calendar::overlaps_regular_hours(timestamp, timeframe.bar_length())
+            }
+        }
+    }
+}
+
+/// What [`PriceClient::flush_cache`] threw away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheFlush {
+    /// Cached bar histories, unexpired or not.
+    pub bars: usize,
+    /// Whether a listed-symbols list was held.
+    pub assets: bool,
+}
+
+/// The asset list and when it was fetched. Locked across the fetch, so a
+/// burst of callers makes one.
+type AssetCache = tokio::sync::Mutex<Option<(Instant, Arc<[SymbolInfo]>)>>;
 
 #[derive(Clone)]
 pub struct PriceClient {
     client: Client,
     base_api: String,
+    feed: Feed,
+    cache: Arc<BarCache>,
+    usage: Arc<UsageTracker>,
+    trading_api: String,
+    /// Budget of the trading API, counted apart from market data's.
+    trading_usage: Arc<UsageTracker>,
+    assets: Arc<AssetCache>,
+    /// Whether a background fetch of the asset list is under way.
+    warming: Arc<AtomicBool>,
 }
 
 impl PriceClient {
@@ -24,22 +310,175 @@ impl PriceClient {
             .default_headers(headers)
             .build()?;
 
-        info!("price client initialized");
-        Ok(Self { client, base_api })
+        // keys for a trading API are keys for that account
+        let trading_api = PaperOrLive::of_url(&base_api)
+            .map_or(DEFAULT_TRADING_API, |account| account.trading_api())
+            .to_string();
+        let base_api = normalize_data_api(&base_api)?;
+
+        info!(%base_api, %trading_api, "price client initialized");
+        Ok(Self {
+            client,
+            base_api,
+            feed: Feed::default(),
+            cache: Arc::new(BarCache::new(DEFAULT_CACHE_TTL)),
+            usage: Arc::new(UsageTracker::default()),
+            trading_api,
+            trading_usage: Arc::new(UsageTracker::default()),
+            assets: Arc::default(),
+            warming: Arc::default(),
+        })
+    }
+
+    /// `base_api` with `segments` appended, each percent-encoded as a single
+    /// path segment.
+    fn endpoint(&self, segments: &[&str]) -> Result<Url, Error> {
+        let mut url = Url::parse(&self.base_api)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("base URL {} can't take a path", self.base_api))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// URL of the bars endpoint for `symbol`, after validating it.
+    pub fn bars_url(&self, symbol: &str) -> Result<Url, Error> {
+        validate_symbol(symbol)?;
+        self.endpoint(&["v2", "stocks", symbol, "bars"])
+    }
+
+    /// URL of the snapshot endpoint for `symbol`, after validating it.
+    pub fn snapshot_url(&self, symbol: &str) -> Result<Url, Error> {
+        validate_symbol(symbol)?;
+        self.endpoint(&["v2", "stocks", symbol, "snapshot"])
+    }
+
+    /// Replace the bar cache with one using `ttl`. Zero disables caching.
+    pub fn with_cache_ttl(mut self, ttl: StdDuration) -> Self {
+        self.cache = Arc::new(BarCache::new(ttl));
+        self
+    }
+
+    /// List assets from the trading API at `base` instead of
+    /// [`DEFAULT_TRADING_API`].
+    pub fn with_trading_api(mut self, base: String) -> Self {
+        self.trading_api = base;
+        self
+    }
+
+    /// Request data from `feed` instead of IEX.
+    pub fn with_feed(mut self, feed: Feed) -> Self {
+        self.feed = feed;
+        self
+    }
+
+    /// Replace the usage tracker with one allowing `limit` requests a minute.
+    pub fn with_rate_limit(mut self, limit: u32) -> Self {
+        self.usage = Arc::new(UsageTracker::new(limit));
+        self
+    }
+
+    /// Empty the bar cache and the listed-symbols list, so the next requests
+    /// go to Alpaca.
+    #[instrument(name = "flush_cache", skip(self))]
+    pub async fn flush_cache(&self) -> CacheFlush {
+        let flushed = CacheFlush {
+            bars: self.cache.clear(),
+            assets: self.assets.lock().await.take().is_some(),
+        };
+        info!(
+            bars = flushed.bars,
+            assets = flushed.assets,
+            "flushed caches"
+        );
+        flushed
+    }
+
+    /// Bars for `symbol` already in the cache, never fetching. The longest
+    /// unexpired history at `timeframe` wins.
+    pub fn cached_bars(&self, symbol: &str, timeframe: Timeframe) -> Option<Vec<Bar>> {
+        self.cache.longest(symbol, timeframe)
+    }
+
+    /// Requests sent in the last minute and what's left of the budget,
+    /// shared by every clone of this client.
+    pub fn usage(&self) -> ApiUsage {
+        self.usage.usage()
+    }
+
+    /// Requests sent to the trading API in the last minute and what's left
+    /// of its budget. Listing assets counts here, not against market data.
+    pub fn trading_usage(&self) -> ApiUsage {
+        self.trading_usage.usage()
+    }
+
+    /// Send `req` to the market data API, counting it against the request
+    /// budget and taking Alpaca's own remaining count from the response.
+    async fn send(&self, req: RequestBuilder) -> Result<Response, Error> {
+        send_counted(&self.usage, req).await
     }
 
     /// Create a new PriceClient from environment variables.
     /// Expects APCA_API_BASE_URL, APCA_API_KEY_ID and APCA_API_SECRET_KEY to be set.
+    /// BAR_CACHE_TTL_SECS optionally overrides the bar cache lifetime,
+    /// ALPACA_RATE_LIMIT the requests allowed a minute, ALPACA_FEED the
+    /// data feed (`iex` or `sip`), and APCA_TRADING_BASE_URL the trading API
+    /// assets are listed from, or ALPACA_ACCOUNT (`paper` or `live`) which
+    /// of Alpaca's it is. See [`normalize_data_api`] for the base URL.
     #[instrument(name = "price_client_from_env", skip_all)]
     pub fn from_env() -> Result<Self> {
         let base_api = std::env::var("APCA_API_BASE_URL")?;
         let key_id = std::env::var("APCA_API_KEY_ID")?;
         let secret = std::env::var("APCA_API_SECRET_KEY")?;
 
-        debug!(base_api = %base_api, "loaded alpaca env vars");
-        Self::new(base_api, key_id, secret)
+        let ttl = std::env::var("BAR_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(StdDuration::from_secs)
+            .unwrap_or(DEFAULT_CACHE_TTL);
+
+        let rate_limit = std::env::var("ALPACA_RATE_LIMIT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(usage::DEFAULT_LIMIT);
+
+        let feed = std::env::var("ALPACA_FEED")
+            .ok()
+            .and_then(|v| Feed::parse(&v))
+            .unwrap_or_default();
+
+        let account = std::env::var("ALPACA_ACCOUNT")
+            .ok()
+            .and_then(|v| PaperOrLive::parse(&v));
+        let trading_api = std::env::var("APCA_TRADING_BASE_URL")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .filter(|v| {
+                let data = Url::parse(v.trim()).is_ok_and(|url| url.host_str() == Some(DATA_HOST));
+                if data {
+                    warn!(configured = %v, "trading API points at the data API, ignoring it");
+                }
+                !data
+            })
+            .or_else(|| account.map(|a| a.trading_api().to_string()));
+
+        debug!(base_api = %base_api, ?trading_api, ?account, ?ttl, rate_limit, ?feed, "loaded alpaca env vars");
+        let client = Self::new(base_api, key_id, secret)?
+            .with_cache_ttl(ttl)
+            .with_rate_limit(rate_limit)
+            .with_feed(feed);
+        Ok(match trading_api {
+            Some(trading_api) => client.with_trading_api(trading_api),
+            None => client,
+        })
     }
 
+    /// Fetch bars for `symbol` in `session`, serving recent identical
+    /// requests from the cache unless `bypass_cache` is set. Live results
+    /// always refresh it. Once a cached result expires, only the bars from
+    /// its last one on are requested and merged in, so a symbol charted
+    /// again doesn't fetch its whole history each time. A delta that fills
+    /// `limit` may have been cut short, so the window is fetched in full.
     #[instrument(
         name = "fetch_price",
         skip(self),
@@ -47,7 +486,9 @@ impl PriceClient {
             symbol = %symbol,
             timeframe = %timeframe.as_str(),
             limit = limit,
-            duration_days = duration.num_days()
+            duration_days = duration.num_days(),
+            bypass_cache,
+            ?session
         )
     )]
     pub async fn fetch_price(
@@ -56,36 +497,446 @@ impl PriceClient {
         duration: Duration,
         timeframe: Timeframe,
         limit: usize,
+        bypass_cache: bool,
+        session: Session,
     ) -> Result<Vec<Bar>, Error> {
+        let url = self.bars_url(symbol)?;
+        let key = CacheKey::new(symbol, timeframe, duration.num_days(), limit, session);
+        if !bypass_cache && let Some(bars) = self.cache.get(&key) {
+            debug!(bars = bars.len(), "bar cache hit");
+            return Ok(bars);
+        }
+
         let end = Utc::now();
         let start = end - duration;
+        let stale = (!bypass_cache)
+            .then(|| self.cache.stale(&key))
+            .flatten()
+            .filter(|bars| bars.last().is_some_and(|b| b.timestamp >= start));
 
-        let url = format!(
-            "{}/v2/stocks/{}/bars",
-            self.base_api.trim_end_matches('/'),
-            symbol
-        );
+        let bars = match stale {
+            Some(cached) => {
+                // the last cached bar may have been still forming
+                let since = cached.last().map_or(start, |b| b.timestamp);
+                let fresh = self
+                    .fetch_range(&url, symbol, timeframe, since, end, limit, session)
+                    .await?;
+                if fresh.len() >= limit {
+                    // cut at `limit`, the delta may not reach back to the
+                    // cached bars, so they can't be trusted to join up
+                    debug!(fresh = fresh.len(), since = %since.to_rfc3339(), "delta filled the limit, fetching in full");
+                    self.fetch_range(&url, symbol, timeframe, start, end, limit, session)
+                        .await?
+                } else {
+                    debug!(cached = cached.len(), fresh = fresh.len(), since = %since.to_rfc3339(), "extending cached bars");
+                    bar_cache::merge(cached, fresh, start, limit)
+                }
+            }
+            None => {
+                self.fetch_range(&url, symbol, timeframe, start, end, limit, session)
+                    .await?
+            }
+        };
 
+        info!(bars = bars.len(), "fetched bars");
+        self.cache.insert(key, bars.clone());
+        Ok(bars)
+    }
+
+    /// The newest `limit` bars of `symbol` from `start` to `end`, oldest
+    /// first. Pages come newest first so paging can stop once `limit` are
+    /// in hand.
+    #[allow(clippy::too_many_arguments)]
+    async fn fetch_range(
+        &self,
+        url: &Url,
+        symbol: &str,
+        timeframe: Timeframe,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: usize,
+        session: Session,
+    ) -> Result<Vec<Bar>, Error> {
         debug!(%url, start = %start.to_rfc3339(), end = %end.to_rfc3339(), "requesting bars");
 
-        let res: BarsResponse = self
-            .client
-            .get(url)
-            .query(&[
-                ("feed", "iex"),
-                ("timeframe", timeframe.as_str()),
-                ("start", &start.to_rfc3339()),
-                ("end", &end.to_rfc3339()),
-                ("limit", &limit.to_string()),
-            ])
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
+        let mut bars = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("feed", self.feed.as_str().to_string()),
+                ("timeframe", timeframe.as_str().to_string()),
+                ("start", start.to_rfc3339()),
+                ("end", end.to_rfc3339()),
+                ("limit", limit.to_string()),
+                ("sort", "desc".to_string()),
+            ];
+            if let Some(token) = page_token.take() {
+                query.push(("page_token", token));
+            }
+
+            let res = self
+                .send(self.client.get(url.clone()).query(&query))
+                .await?;
+
+            let status = res.status();
+            if !status.is_success() {
+                let body = res.text().await.unwrap_or_default();
+                return Err(RequestFailed::new(
+                    status,
+                    format!("alpaca bars request for {symbol} failed with {status}: {body}"),
+                )
+                .into());
+            }
+
+            let page: BarsResponse = res.json().await?;
+            debug!(
+                bars = page.bars.len(),
+                more = page.next_page_token.is_some(),
+                "fetched page"
+            );
+            bars.extend(
+                page.bars
+                    .into_iter()
+                    .filter(|b| session.includes(timeframe, b.timestamp)),
+            );
+
+            match page.next_page_token {
+                Some(token) if bars.len() < limit => page_token = Some(token),
+                _ => break,
+            }
+        }
+        bars.sort_by_key(|b| b.timestamp);
+        let excess = bars.len().saturating_sub(limit);
+        bars.drain(..excess);
+        Ok(bars)
+    }
+
+    /// Fetch bars for several `symbols` in one multi-symbol request and
+    /// cache them for `ttl` under the same keys [`Self::fetch_price`] uses
+    /// for that `duration`, `timeframe`, `limit` and `session`, so a later
+    /// identical `fetch_price` is served from the cache. Symbols Alpaca has
+    /// nothing for are cached as empty, as `fetch_price` would. Returns how
+    /// many symbols were cached.
+    #[instrument(
+        name = "warm_bars",
+        skip(self, symbols),
+        fields(
+            count = symbols.len(),
+            timeframe = %timeframe.as_str(),
+            limit,
+            duration_days = duration.num_days(),
+            ?session,
+            ?ttl
+        )
+    )]
+    pub async fn warm_bars(
+        &self,
+        symbols: &[String],
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        session: Session,
+        ttl: StdDuration,
+    ) -> Result<usize, Error> {
+        let symbols: Vec<String> = symbols
+            .iter()
+            .map(|s| s.trim().to_uppercase())
+            .filter(|symbol| match validate_symbol(symbol) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!(error = %e, "not warming invalid symbol");
+                    false
+                }
+            })
+            .collect();
+        if symbols.is_empty() {
+            return Ok(0);
+        }
+
+        let url = self.endpoint(&["v2", "stocks", "bars"])?;
+        let end = Utc::now();
+        let start = end - duration;
+        let mut fetched: HashMap<String, Vec<Bar>> = HashMap::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("symbols", symbols.join(",")),
+                ("feed", self.feed.as_str().to_string()),
+                ("timeframe", timeframe.as_str().to_string()),
+                ("start", start.to_rfc3339()),
+                ("end", end.to_rfc3339()),
+                ("limit", MULTI_PAGE_LIMIT.to_string()),
+            ];
+            if let Some(token) = page_token.take() {
+                query.push(("page_token", token));
+            }
+
+            let res = self
+                .send(self.client.get(url.clone()).query(&query))
+                .await?;
+            let status = res.status();
+            if !status.is_success() {
+                let body = res.text().await.unwrap_or_default();
+                return Err(RequestFailed::new(
+                    status,
+                    format!("alpaca multi-bars request failed with {status}: {body}"),
+                )
+                .into());
+            }
+
+            let page: MultiBarsResponse = res.json().await?;
+            for (symbol, bars) in page.bars {
+                fetched.entry(symbol).or_default().extend(
+                    bars.into_iter()
+                        .filter(|b| session.includes(timeframe, b.timestamp)),
+                );
+            }
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        for symbol in &symbols {
+            let mut bars = fetched.remove(symbol).unwrap_or_default();
+            let excess = bars.len().saturating_sub(limit);
+            bars.drain(..excess);
+            let key = CacheKey::new(symbol, timeframe, duration.num_days(), limit, session);
+            self.cache.insert_for(key, bars, ttl);
+        }
+        info!(warmed = symbols.len(), "warmed bar cache");
+        Ok(symbols.len())
+    }
+
+    /// Fetch snapshots for several symbols in one request. Symbols Alpaca has
+    /// no data for are missing from the result.
+    #[instrument(name = "fetch_snapshots", skip(self, symbols), fields(count = symbols.len()))]
+    pub async fn fetch_snapshots(
+        &self,
+        symbols: &[String],
+    ) -> Result<HashMap<String, Snapshot>, Error> {
+        let symbols: Vec<&str> = symbols
+            .iter()
+            .map(String::as_str)
+            .filter(|symbol| match validate_symbol(symbol) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!(error = %e, "skipping snapshot for invalid symbol");
+                    false
+                }
+            })
+            .collect();
+        if symbols.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let url = self.endpoint(&["v2", "stocks", "snapshots"])?;
+        let res = self
+            .send(self.client.get(url).query(&[
+                ("feed", self.feed.as_str()),
+                ("symbols", &symbols.join(",")),
+            ]))
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(RequestFailed::new(
+                status,
+                format!("alpaca snapshots request failed with {status}: {body}"),
+            )
+            .into());
+        }
+
+        let snapshots: HashMap<String, Option<Snapshot>> = res.json().await?;
+        let snapshots: HashMap<String, Snapshot> = snapshots
+            .into_iter()
+            .filter_map(|(symbol, snapshot)| Some((symbol, snapshot?)))
+            .collect();
+        debug!(found = snapshots.len(), "fetched snapshots");
+        Ok(snapshots)
+    }
+
+    /// Every active, tradable asset, from the cache when it's younger than
+    /// [`ASSET_CACHE_TTL`].
+    #[instrument(name = "list_assets", skip(self))]
+    pub async fn list_assets(&self) -> Result<Arc<[SymbolInfo]>, Error> {
+        let mut cached = self.assets.lock().await;
+        if let Some((fetched, assets)) = cached.as_ref()
+            && fetched.elapsed() < ASSET_CACHE_TTL
+        {
+            return Ok(Arc::clone(assets));
+        }
+
+        let mut url = Url::parse(&self.trading_api)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("base URL {} can't take a path", self.trading_api))?
+            .pop_if_empty()
+            .extend(["v2", "assets"]);
+        let res = send_counted(
+            &self.trading_usage,
+            self.client
+                .get(url)
+                .query(&[("status", "active"), ("asset_class", "us_equity")]),
+        )
+        .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(RequestFailed::new(
+                status,
+                format!("alpaca assets request failed with {status}: {body}"),
+            )
+            .into());
+        }
+
+        let assets: Arc<[SymbolInfo]> = assets::parse(&res.text().await?)?.into();
+        info!(count = assets.len(), "fetched asset list");
+        *cached = Some((Instant::now(), Arc::clone(&assets)));
+        Ok(assets)
+    }
+
+    /// Fetch the asset list in the background unless it's fresh or already
+    /// being fetched.
+    pub fn warm_assets(&self) {
+        if self.warming.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let client = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = client.list_assets().await {
+                warn!(error = ?e, "failed to warm the asset list");
+            }
+            client.warming.store(false, Ordering::Release);
+        });
+    }
+
+    /// Up to [`SEARCH_LIMIT`] listed symbols matching `query` by ticker or
+    /// company name, best first. See [`assets::search`].
+    ///
+    /// Autocomplete has three seconds to answer, so this never waits on
+    /// Alpaca: a missing or stale list is fetched in the background while
+    /// the search runs on whatever is held: nothing at first, or while that
+    /// fetch is under way.
+    #[instrument(name = "search_symbols", skip(self), fields(query = %query))]
+    pub async fn search_symbols(&self, query: &str) -> Result<Vec<SymbolInfo>, Error> {
+        // locked only while a fetch is under way
+        let held = self
+            .assets
+            .try_lock()
+            .ok()
+            .and_then(|cached| cached.clone());
+        if !held
+            .as_ref()
+            .is_some_and(|(fetched, _)| fetched.elapsed() < ASSET_CACHE_TTL)
+        {
+            self.warm_assets();
+        }
+        let Some((_, assets)) = held else {
+            debug!("asset list not loaded yet");
+            return Ok(Vec::new());
+        };
+        let found = assets::search(&assets, query, SEARCH_LIMIT);
+        debug!(count = found.len(), "searched symbols");
+        Ok(found)
+    }
+
+    /// Dividends and splits of `symbol` with an ex-date from `start` to
+    /// `end`. Crypto pairs have no corporate actions, and nothing is
+    /// fetched for them.
+    #[instrument(name = "fetch_corporate_actions", skip(self), fields(symbol = %symbol, %start, %end))]
+    pub async fn fetch_corporate_actions(
+        &self,
+        symbol: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<CorporateActions, Error> {
+        validate_symbol(symbol)?;
+        if symbol.contains('/') {
+            return Ok(CorporateActions::default());
+        }
+
+        let url = self.endpoint(&["v1", "corporate-actions"])?;
+        let res = self
+            .send(self.client.get(url).query(&[
+                ("symbols", symbol),
+                ("types", corporate_actions::TYPES),
+                ("start", &start.to_string()),
+                ("end", &end.to_string()),
+                ("limit", "1000"),
+            ]))
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(RequestFailed::new(
+                status,
+                format!(
+                    "alpaca corporate actions request for {symbol} failed with {status}: {body}"
+                ),
+            )
+            .into());
+        }
+
+        let actions = corporate_actions::parse(&res.text().await?)?;
+        debug!(
+            dividends = actions.dividends.len(),
+            splits = actions.splits.len(),
+            "fetched corporate actions"
+        );
+        Ok(actions)
+    }
+
+    /// Ex-dividend dates of `symbol` from `start` to `end`, oldest first.
+    /// Alpaca's corporate actions don't cover earnings, so none are
+    /// returned.
+    #[instrument(name = "fetch_events", skip(self), fields(symbol = %symbol, %start, %end))]
+    pub async fn fetch_events(
+        &self,
+        symbol: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, AnnotationKind)>, Error> {
+        let actions = self.fetch_corporate_actions(symbol, start, end).await?;
+        let mut events: Vec<(NaiveDate, AnnotationKind)> = actions
+            .dividends
+            .into_iter()
+            .map(|dividend| (dividend.ex_date, AnnotationKind::ExDividend))
+            .collect();
+        events.dedup();
+        Ok(events)
+    }
+
+    /// Fetch the latest snapshot for `symbol`. Returns `None` when Alpaca has
+    /// none for it. Snapshots are live and never cached.
+    #[instrument(name = "fetch_snapshot", skip(self), fields(symbol = %symbol))]
+    pub async fn fetch_snapshot(&self, symbol: &str) -> Result<Option<Snapshot>, Error> {
+        let url = self.snapshot_url(symbol)?;
+        let res = self
+            .send(self.client.get(url).query(&[("feed", self.feed.as_str())]))
             .await?;
 
-        info!(bars = res.bars.len(), "fetched bars");
-        Ok(res.bars)
+        let status = res.status();
+        if status == StatusCode::NOT_FOUND {
+            debug!("no snapshot");
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(RequestFailed::new(
+                status,
+                format!("alpaca snapshot request for {symbol} failed with {status}: {body}"),
+            )
+            .into());
+        }
+
+        let snapshot: Snapshot = res.json().await?;
+        debug!(daily_bar = snapshot.daily_bar.is_some(), "fetched snapshot");
+        Ok(Some(snapshot))
     }
 }
 
@@ -93,15 +944,23 @@ impl PriceClient {
 // Match Alpaca API JSON
 // https://docs.alpaca.markets/reference/stockbars
 //
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Timeframe {
+    #[serde(rename = "1Min")]
     Minute1,
+    #[serde(rename = "5Min")]
     Minute5,
+    #[serde(rename = "15Min")]
     Minute15,
+    #[serde(rename = "30Min")]
     Minute30,
+    #[serde(rename = "1Hour")]
     Hour1,
+    #[serde(rename = "1Day")]
     Day1,
+    #[serde(rename = "1Week")]
     Week1,
+    #[serde(rename = "1Month")]
     Month1,
 }
 
@@ -118,14 +977,72 @@ impl Timeframe {
             Timeframe::Month1 => "1Month",
         }
     }
+
+    /// Bars shorter than a trading day.
+    pub fn is_intraday(&self) -> bool {
+        !matches!(self, Timeframe::Day1 | Timeframe::Week1 | Timeframe::Month1)
+    }
+
+    /// How long one bar spans. A month is taken as 31 days, its longest.
+    pub fn bar_length(&self) -> Duration {
+        match self {
+            Timeframe::Minute1 => Duration::minutes(1),
+            Timeframe::Minute5 => Duration::minutes(5),
+            Timeframe::Minute15 => Duration::minutes(15),
+            Timeframe::Minute30 => Duration::minutes(30),
+            Timeframe::Hour1 => Duration::hours(1),
+            Timeframe::Day1 => Duration::days(1),
+            Timeframe::Week1 => Duration::weeks(1),
+            Timeframe::Month1 => Duration::days(31),
+        }
+    }
+
+    /// How far back a chart at this timeframe reaches, enough for a few
+    /// hundred bars without paging through months of minute data.
+    pub fn lookback(&self) -> Duration {
+        match self {
+            Timeframe::Minute1 => Duration::days(4),
+            Timeframe::Minute5 => Duration::days(10),
+            Timeframe::Minute15 => Duration::days(30),
+            Timeframe::Minute30 => Duration::days(45),
+            Timeframe::Hour1 => Duration::days(90),
+            Timeframe::Day1 => Duration::days(300),
+            Timeframe::Week1 => Duration::weeks(365),
+            Timeframe::Month1 => Duration::days(365 * 25),
+        }
+    }
+
+    /// Pick the chart timeframe: the one asked for, else the one last used
+    /// for the symbol, else the user's default, else daily.
+    pub fn resolve(
+        requested: Option<Timeframe>,
+        remembered: Option<Timeframe>,
+        preferred: Option<Timeframe>,
+    ) -> Timeframe {
+        requested
+            .or(remembered)
+            .or(preferred)
+            .unwrap_or(Timeframe::Day1)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct BarsResponse {
     pub bars: Vec<Bar>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
 }
 
+/// A page of the multi-symbol bars endpoint, keyed by symbol.
 #[derive(Debug, Deserialize, Clone)]
+struct MultiBarsResponse {
+    #[serde(default)]
+    bars: HashMap<String, Vec<Bar>>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Bar {
     #[serde(rename = "t")]
     pub timestamp: DateTime<Utc>,
@@ -142,6 +1059,127 @@ pub struct Bar {
     #[serde(rename = "c")]
     pub close: f64,
 
-    #[serde(rename = "v")]
-    pub volume: i64,
+    /// Shares traded, or units of the base currency for a crypto pair, which
+    /// can be fractional.
+    #[serde(rename = "v", deserialize_with = "volume")]
+    pub volume: f64,
+
+    /// Trades in the bar.
+    #[serde(rename = "n", default)]
+    pub trade_count: Option<u64>,
+
+    /// Volume-weighted average price over the bar.
+    #[serde(rename = "vw", default)]
+    pub vwap: Option<f64>,
+}
+
+/// Stock bars carry volume as an integer and crypto bars as a float; take
+/// either.
+fn volume<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Volume {
+        Whole(u64),
+        Fractional(f64),
+    }
+
+    Ok(match Volume::deserialize(deserializer)? {
+        Volume::Whole(v) => v as f64,
+        Volume::Fractional(v) => v,
+    })
+}
+
+/// Latest market data for one symbol.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    #[serde(default)]
+    pub latest_trade: Option<Trade>,
+
+    #[serde(default)]
+    pub latest_quote: Option<BidAsk>,
+
+    /// Current session's bar so far.
+    #[serde(default)]
+    pub daily_bar: Option<Bar>,
+
+    #[serde(default)]
+    pub prev_daily_bar: Option<Bar>,
+}
+
+impl Snapshot {
+    /// Last traded price, falling back to the current daily bar's close.
+    pub fn price(&self) -> Option<f64> {
+        self.quote().map(|q| q.price)
+    }
+
+    /// [`price`](Self::price), saying which part of the snapshot it came
+    /// from.
+    pub fn quote(&self) -> Option<Quote> {
+        let trade = self.latest_trade.as_ref().map(|t| Quote {
+            price: t.price,
+            source: QuoteSource::Trade,
+        });
+        trade.or_else(|| {
+            self.daily_bar.as_ref().map(|b| Quote {
+                price: b.close,
+                source: QuoteSource::SessionBar,
+            })
+        })
+    }
+}
+
+/// A symbol's latest price and where it came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    pub price: f64,
+    pub source: QuoteSource,
+}
+
+/// Where a [`Quote`] came from, freshest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteSource {
+    /// The snapshot's latest trade.
+    Trade,
+    /// The snapshot's bar for the current session.
+    SessionBar,
+    /// Close of the most recent daily bar, for symbols the snapshot had no
+    /// price for.
+    LastClose,
+}
+
+/// The best bid and ask at the time of the snapshot.
+#[derive(Debug, Deserialize, Clone)]
+pub struct BidAsk {
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+
+    #[serde(rename = "bp")]
+    pub bid_price: f64,
+
+    #[serde(rename = "bs", default)]
+    pub bid_size: f64,
+
+    #[serde(rename = "ap")]
+    pub ask_price: f64,
+
+    #[serde(rename = "as", default)]
+    pub ask_size: f64,
+}
+
+impl BidAsk {
+    /// Ask over bid, or None when either side is empty, as it is outside
+    /// market hours on some feeds.
+    pub fn spread(&self) -> Option<f64> {
+        (self.bid_price > 0.0 && self.ask_price > 0.0).then(|| self.ask_price - self.bid_price)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Trade {
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+
+    #[serde(rename = "p")]
+    pub price: f64,
 }
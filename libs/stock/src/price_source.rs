@@ -0,0 +1,294 @@
+use std::{collections::HashMap, sync::Arc, time::Duration as StdDuration};
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use futures::{StreamExt, future::BoxFuture, stream};
+
+use tracing::{debug, warn};
+
+use crate::{
+    Bar, CacheFlush, PriceClient, Quote, QuoteSource, Session, Snapshot, Timeframe,
+    assets::{self, SymbolInfo},
+    corporate_actions::CorporateActions,
+    indicators::annotation::AnnotationKind,
+    price_client::SEARCH_LIMIT,
+    usage::ApiUsage,
+};
+
+/// Days of daily bars looked through for a last close.
+const LAST_CLOSE_DAYS: i64 = 14;
+const LAST_CLOSE_BARS: usize = 20;
+/// Last-close lookups in flight at once.
+const LAST_CLOSE_CONCURRENCY: usize = 8;
+
+/// A market data provider. Alpaca is the only one today; anything that can
+/// serve daily bars and latest prices can stand in for it, including mocks
+/// in tests, and several can be chained with
+/// [`FailoverSource`](crate::failover::FailoverSource).
+///
+/// Methods return boxed futures so the bot can hold an
+/// `Arc<dyn PriceSource>`.
+pub trait PriceSource: Send + Sync {
+    /// Bars for `symbol` covering `duration` up to now, oldest first and at
+    /// most `limit` of them, within `session` hours. `bypass_cache` skips any
+    /// cache the source keeps.
+    fn fetch_price<'a>(
+        &'a self,
+        symbol: &'a str,
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        bypass_cache: bool,
+        session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>>;
+
+    /// Latest snapshot for each of `symbols`. Symbols the source has no data
+    /// for are missing from the result.
+    fn fetch_prices<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>>;
+
+    /// Latest snapshot for one symbol. Defaults to a one-symbol
+    /// [`fetch_prices`](Self::fetch_prices).
+    fn fetch_snapshot<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<Option<Snapshot>>> {
+        Box::pin(async move {
+            let mut snapshots = self.fetch_prices(&[symbol.to_string()]).await?;
+            Ok(snapshots.remove(symbol))
+        })
+    }
+
+    /// Latest price for each of `symbols`: the snapshot's when it has one,
+    /// else the close of the symbol's most recent daily bar, tagged
+    /// [`QuoteSource::LastClose`]. Symbols with neither are missing from the
+    /// result.
+    fn fetch_quotes<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Quote>>> {
+        Box::pin(async move {
+            let snapshots = self.fetch_prices(symbols).await?;
+            let mut quotes = HashMap::with_capacity(symbols.len());
+            let mut missing = Vec::new();
+            for symbol in symbols {
+                match snapshots.get(symbol).and_then(Snapshot::quote) {
+                    Some(quote) => {
+                        quotes.insert(symbol.clone(), quote);
+                    }
+                    None => missing.push(symbol),
+                }
+            }
+
+            let mut closes = stream::iter(missing)
+                .map(|symbol| async move {
+                    let bars = self
+                        .fetch_price(
+                            symbol,
+                            Duration::days(LAST_CLOSE_DAYS),
+                            Timeframe::Day1,
+                            LAST_CLOSE_BARS,
+                            false,
+                            Session::Regular,
+                        )
+                        .await;
+                    (symbol, bars)
+                })
+                .buffer_unordered(LAST_CLOSE_CONCURRENCY);
+            while let Some((symbol, bars)) = closes.next().await {
+                match bars {
+                    Ok(bars) => {
+                        if let Some(bar) = bars.last() {
+                            debug!(%symbol, "no snapshot price, using last close");
+                            quotes.insert(
+                                symbol.clone(),
+                                Quote {
+                                    price: bar.close,
+                                    source: QuoteSource::LastClose,
+                                },
+                            );
+                        }
+                    }
+                    Err(e) => warn!(%symbol, error = ?e, "last close fallback failed"),
+                }
+            }
+            Ok(quotes)
+        })
+    }
+
+    /// Request budget the source is working within, if it has one.
+    fn usage(&self) -> Option<ApiUsage> {
+        None
+    }
+
+    /// Every symbol the source can serve. Empty when it can't say, and
+    /// callers then take any well-formed symbol.
+    fn list_assets(&self) -> BoxFuture<'_, Result<Arc<[SymbolInfo]>>> {
+        Box::pin(async { Ok(Arc::from([])) })
+    }
+
+    /// Drop whatever the source has cached. Sources without a cache have
+    /// nothing to drop.
+    fn flush_cache(&self) -> BoxFuture<'_, CacheFlush> {
+        Box::pin(async { CacheFlush::default() })
+    }
+
+    /// Up to [`SEARCH_LIMIT`] symbols matching `query` by ticker or name,
+    /// best first. Defaults to searching [`list_assets`](Self::list_assets).
+    fn search_symbols<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<SymbolInfo>>> {
+        Box::pin(async move {
+            let listed = self.list_assets().await?;
+            Ok(assets::search(&listed, query, SEARCH_LIMIT))
+        })
+    }
+
+    /// Earnings and ex-dividend dates of `symbol` from `start` to `end`, for
+    /// chart annotations. Sources without corporate actions have none.
+    fn fetch_events<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _start: NaiveDate,
+        _end: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<(NaiveDate, AnnotationKind)>>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    /// Dividends and splits of `symbol` with an ex-date from `start` to
+    /// `end`. Sources without corporate actions have none.
+    fn fetch_corporate_actions<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _start: NaiveDate,
+        _end: NaiveDate,
+    ) -> BoxFuture<'a, Result<CorporateActions>> {
+        Box::pin(async { Ok(CorporateActions::default()) })
+    }
+
+    /// Bars for `symbol` the source already holds, without fetching. Sources
+    /// that don't cache have none.
+    fn cached_bars(&self, _symbol: &str, _timeframe: Timeframe) -> Option<Vec<Bar>> {
+        None
+    }
+
+    /// [`fetch_price`](Self::fetch_price), with the fallback source that
+    /// served the bars when it wasn't the first one. Feeds differ slightly,
+    /// so hits say so. Sources without fallbacks serve every bar themselves.
+    fn fetch_price_served<'a>(
+        &'a self,
+        symbol: &'a str,
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        bypass_cache: bool,
+        session: Session,
+    ) -> BoxFuture<'a, Result<(Vec<Bar>, Option<String>)>> {
+        Box::pin(async move {
+            let bars = self
+                .fetch_price(symbol, duration, timeframe, limit, bypass_cache, session)
+                .await?;
+            Ok((bars, None))
+        })
+    }
+
+    /// Fetch `symbols` ahead of time so that matching
+    /// [`fetch_price`](Self::fetch_price) calls within `ttl` are served
+    /// without a request. Returns how many were warmed; sources that don't
+    /// cache warm none.
+    fn warm_bars<'a>(
+        &'a self,
+        _symbols: &'a [String],
+        _duration: Duration,
+        _timeframe: Timeframe,
+        _limit: usize,
+        _session: Session,
+        _ttl: StdDuration,
+    ) -> BoxFuture<'a, Result<usize>> {
+        Box::pin(async { Ok(0) })
+    }
+}
+
+impl PriceSource for PriceClient {
+    fn fetch_price<'a>(
+        &'a self,
+        symbol: &'a str,
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        bypass_cache: bool,
+        session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(PriceClient::fetch_price(
+            self,
+            symbol,
+            duration,
+            timeframe,
+            limit,
+            bypass_cache,
+            session,
+        ))
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(self.fetch_snapshots(symbols))
+    }
+
+    fn fetch_snapshot<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<Option<Snapshot>>> {
+        Box::pin(PriceClient::fetch_snapshot(self, symbol))
+    }
+
+    fn usage(&self) -> Option<ApiUsage> {
+        Some(PriceClient::usage(self))
+    }
+
+    fn list_assets(&self) -> BoxFuture<'_, Result<Arc<[SymbolInfo]>>> {
+        Box::pin(PriceClient::list_assets(self))
+    }
+
+    fn search_symbols<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<SymbolInfo>>> {
+        Box::pin(PriceClient::search_symbols(self, query))
+    }
+
+    fn fetch_events<'a>(
+        &'a self,
+        symbol: &'a str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<(NaiveDate, AnnotationKind)>>> {
+        Box::pin(PriceClient::fetch_events(self, symbol, start, end))
+    }
+
+    fn fetch_corporate_actions<'a>(
+        &'a self,
+        symbol: &'a str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> BoxFuture<'a, Result<CorporateActions>> {
+        Box::pin(PriceClient::fetch_corporate_actions(
+            self, symbol, start, end,
+        ))
+    }
+
+    fn flush_cache(&self) -> BoxFuture<'_, CacheFlush> {
+        Box::pin(PriceClient::flush_cache(self))
+    }
+
+    fn cached_bars(&self, symbol: &str, timeframe: Timeframe) -> Option<Vec<Bar>> {
+        PriceClient::cached_bars(self, symbol, timeframe)
+    }
+
+    fn warm_bars<'a>(
+        &'a self,
+        symbols: &'a [String],
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        session: Session,
+        ttl: StdDuration,
+    ) -> BoxFuture<'a, Result<usize>> {
+        Box::pin(PriceClient::warm_bars(
+            self, symbols, duration, timeframe, limit, session, ttl,
+        ))
+    }
+}
@@ -0,0 +1,392 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{debug, info, instrument, warn};
+
+use crate::indicators::cdc::{Benchmark, ChartOptions, ChartTheme, calculate, generate_chart};
+
+/// Render threads when `CHART_RENDER_WORKERS` isn't set.
+pub const DEFAULT_WORKERS: usize = 2;
+/// How long a job may take, queueing included, when
+/// `CHART_RENDER_TIMEOUT_SECS` isn't set.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Jobs waiting for a worker before `render` starts applying backpressure.
+const QUEUE: usize = 64;
+
+/// Everything `generate_chart` needs for one chart. The series are shared,
+/// so a job is handed to a render thread, or kept by a caller, uncopied.
+#[derive(Debug, Clone)]
+pub struct ChartJob {
+    pub symbol: String,
+    pub closes: Arc<[f64]>,
+    pub ema12: Arc<[f64]>,
+    pub ema26: Arc<[f64]>,
+    pub dates: Arc<[String]>,
+    pub options: ChartOptions,
+}
+
+impl ChartJob {
+    /// A made-up symbol over a fixed series, drawn in `theme`. Shows what a
+    /// theme looks like without fetching anything.
+    pub fn sample(theme: ChartTheme) -> Self {
+        let closes: Vec<f64> = (0..120)
+            .map(|i| {
+                let i = i as f64;
+                100.0 + 8.0 * (i / 9.0).sin() + 0.15 * i
+            })
+            .collect();
+        let (_, ema12, ema26) = calculate(&closes, 0.0);
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date");
+        let dates = (0..closes.len())
+            .map(|i| (start + chrono::Duration::days(i as i64)).to_string())
+            .collect();
+
+        Self {
+            symbol: "SAMPLE".to_string(),
+            closes: closes.into(),
+            ema12: ema12.into(),
+            ema26: ema26.into(),
+            dates,
+            options: ChartOptions {
+                theme,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Hash of everything that goes into the chart. Jobs with the same
+    /// fingerprint draw the same PNG.
+    pub fn fingerprint(&self) -> u64 {
+        fn floats(h: &mut DefaultHasher, values: &[f64]) {
+            values.len().hash(h);
+            values.iter().for_each(|v| v.to_bits().hash(h));
+        }
+
+        let Self {
+            symbol,
+            closes,
+            ema12,
+            ema26,
+            dates,
+            options,
+        } = self;
+        let ChartOptions {
+            donchian,
+            vwap,
+            added_price,
+            added_name,
+            max_points,
+            indicators,
+            volumes,
+            benchmark,
+            average_names,
+            theme,
+            annotate_crossover,
+            band_pct,
+            scale,
+            annotations,
+            bar_dates,
+            trend_before,
+            brand,
+        } = options;
+
+        let mut h = DefaultHasher::new();
+        symbol.hash(&mut h);
+        floats(&mut h, closes);
+        floats(&mut h, ema12);
+        floats(&mut h, ema26);
+        dates.hash(&mut h);
+        donchian.is_some().hash(&mut h);
+        if let Some((upper, lower)) = donchian {
+            floats(&mut h, upper);
+            floats(&mut h, lower);
+        }
+        vwap.is_some().hash(&mut h);
+        if let Some(vwap) = vwap {
+            floats(&mut h, vwap);
+        }
+        added_price.map(f64::to_bits).hash(&mut h);
+        added_name.hash(&mut h);
+        max_points.hash(&mut h);
+        indicators.hash(&mut h);
+        volumes.is_some().hash(&mut h);
+        if let Some(volumes) = volumes {
+            floats(&mut h, volumes);
+        }
+        average_names.hash(&mut h);
+        theme.hash(&mut h);
+        annotate_crossover.hash(&mut h);
+        band_pct.to_bits().hash(&mut h);
+        scale.hash(&mut h);
+        annotations.hash(&mut h);
+        bar_dates.hash(&mut h);
+        trend_before.hash(&mut h);
+        brand.hash(&mut h);
+        benchmark.is_some().hash(&mut h);
+        if let Some(Benchmark { symbol, closes }) = benchmark {
+            symbol.hash(&mut h);
+            floats(&mut h, closes);
+        }
+        h.finish()
+    }
+}
+
+/// A chart that wasn't rendered within the renderer's timeout. Callers can
+/// downcast to it to skip the chart instead of failing outright.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderTimeout {
+    pub symbol: String,
+    pub after: Duration,
+}
+
+impl std::fmt::Display for RenderTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "chart render for {} timed out after {:?}",
+            self.symbol, self.after
+        )
+    }
+}
+
+impl std::error::Error for RenderTimeout {}
+
+type RenderFn = dyn Fn(&ChartJob) -> Result<Vec<u8>> + Send + Sync;
+
+struct Envelope {
+    job: ChartJob,
+    reply: oneshot::Sender<Result<Vec<u8>>>,
+}
+
+/// How a render shared between callers ended, as each of them sees it.
+#[derive(Clone)]
+enum Shared {
+    Done(Arc<Vec<u8>>),
+    TimedOut(RenderTimeout),
+    Failed(String),
+}
+
+impl Shared {
+    fn from_result(res: &Result<Arc<Vec<u8>>>) -> Self {
+        match res {
+            Ok(png) => Shared::Done(png.clone()),
+            Err(e) => match e.downcast_ref::<RenderTimeout>() {
+                Some(timeout) => Shared::TimedOut(timeout.clone()),
+                None => Shared::Failed(format!("{e:#}")),
+            },
+        }
+    }
+
+    fn into_result(self) -> Result<Vec<u8>> {
+        match self {
+            Shared::Done(png) => Ok(png.as_ref().clone()),
+            Shared::TimedOut(timeout) => Err(timeout.into()),
+            Shared::Failed(msg) => Err(anyhow!(msg)),
+        }
+    }
+}
+
+/// Renders in progress by [`ChartJob::fingerprint`]; callers asking for one
+/// already underway wait on its result instead of queueing a copy.
+type InFlight = Mutex<HashMap<u64, watch::Receiver<Option<Shared>>>>;
+
+/// Drops a render's in-flight entry once it's done, or once its caller
+/// gives up on it, so waiting callers never hang on a render nobody runs.
+struct InFlightGuard<'a> {
+    in_flight: &'a InFlight,
+    key: u64,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight
+            .lock()
+            .expect("in-flight renders lock")
+            .remove(&self.key);
+    }
+}
+
+/// Renders charts on a small dedicated thread pool so CPU-heavy rendering
+/// never crowds Tokio's blocking pool. Jobs are served first come, first
+/// served, and identical jobs submitted while one is rendering share its
+/// result.
+pub struct ChartRenderer {
+    tx: mpsc::Sender<Envelope>,
+    timeout: Duration,
+    in_flight: InFlight,
+}
+
+impl ChartRenderer {
+    /// Renderer backed by [`generate_chart`].
+    pub fn new(workers: usize, timeout: Duration) -> Result<Self> {
+        Self::with_render_fn(workers, timeout, |job| {
+            generate_chart(
+                &job.symbol,
+                &job.closes,
+                &job.ema12,
+                &job.ema26,
+                &job.dates,
+                &job.options,
+            )
+        })
+    }
+
+    /// Create a ChartRenderer from environment variables.
+    /// CHART_RENDER_WORKERS and CHART_RENDER_TIMEOUT_SECS optionally override
+    /// the defaults.
+    pub fn from_env() -> Result<Self> {
+        let workers = std::env::var("CHART_RENDER_WORKERS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_WORKERS);
+        let timeout = std::env::var("CHART_RENDER_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TIMEOUT);
+
+        Self::new(workers, timeout)
+    }
+
+    /// Renderer running `render` instead of [`generate_chart`].
+    pub fn with_render_fn<F>(workers: usize, timeout: Duration, render: F) -> Result<Self>
+    where
+        F: Fn(&ChartJob) -> Result<Vec<u8>> + Send + Sync + 'static,
+    {
+        if workers == 0 {
+            bail!("chart renderer needs at least one worker");
+        }
+
+        let (tx, rx) = mpsc::channel::<Envelope>(QUEUE);
+        let rx = Arc::new(Mutex::new(rx));
+        let render: Arc<RenderFn> = Arc::new(render);
+
+        for n in 0..workers {
+            let rx = rx.clone();
+            let render = render.clone();
+            thread::Builder::new()
+                .name(format!("chart-render-{n}"))
+                .spawn(move || work(rx, render))
+                .context("spawn chart render worker")?;
+        }
+
+        info!(workers, ?timeout, "chart renderer started");
+        Ok(Self {
+            tx,
+            timeout,
+            in_flight: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Queue `job` and wait for its PNG. Fails with [`RenderTimeout`] if it
+    /// isn't done within the timeout; the job is then abandoned and skipped
+    /// if still queued.
+    ///
+    /// If an identical job is already rendering, waits for that one instead
+    /// and gets the same bytes.
+    #[instrument(name = "chart_render", skip(self, job), fields(symbol = %job.symbol))]
+    pub async fn render(&self, job: ChartJob) -> Result<Vec<u8>> {
+        let key = job.fingerprint();
+        loop {
+            let (tx, mut rx) = {
+                let mut in_flight = self.in_flight.lock().expect("in-flight renders lock");
+                match in_flight.get(&key) {
+                    Some(rx) => (None, rx.clone()),
+                    None => {
+                        let (tx, rx) = watch::channel(None);
+                        in_flight.insert(key, rx.clone());
+                        (Some(tx), rx)
+                    }
+                }
+            };
+
+            let Some(tx) = tx else {
+                debug!("joining in-flight render");
+                match rx.wait_for(Option::is_some).await {
+                    Ok(shared) => return shared.clone().expect("waited for Some").into_result(),
+                    // its caller gave up before it finished; start over
+                    Err(_) => continue,
+                }
+            };
+
+            let guard = InFlightGuard {
+                in_flight: &self.in_flight,
+                key,
+            };
+            let res = self.render_queued(job).await.map(Arc::new);
+            tx.send_replace(Some(Shared::from_result(&res)));
+            drop((guard, tx, rx));
+            // with nobody else holding the chart, it's handed over uncopied
+            return res.map(|png| Arc::try_unwrap(png).unwrap_or_else(|png| png.as_ref().clone()));
+        }
+    }
+
+    async fn render_queued(&self, job: ChartJob) -> Result<Vec<u8>> {
+        let symbol = job.symbol.clone();
+        let (reply, rx) = oneshot::channel();
+
+        let wait = async {
+            self.tx
+                .send(Envelope { job, reply })
+                .await
+                .map_err(|_| anyhow!("chart renderer is shut down"))?;
+            rx.await
+                .map_err(|_| anyhow!("chart render worker dropped the job"))?
+        };
+
+        match tokio::time::timeout(self.timeout, wait).await {
+            Ok(res) => res,
+            Err(_) => {
+                warn!(timeout = ?self.timeout, "chart render timed out");
+                Err(RenderTimeout {
+                    symbol,
+                    after: self.timeout,
+                }
+                .into())
+            }
+        }
+    }
+}
+
+fn work(rx: Arc<Mutex<mpsc::Receiver<Envelope>>>, render: Arc<RenderFn>) {
+    loop {
+        // only one idle worker waits on the channel at a time, the rest wait
+        // on the lock, so jobs go out in submission order
+        let next = rx.lock().expect("render queue lock").blocking_recv();
+        let Some(Envelope { job, reply }) = next else {
+            debug!("chart renderer closed, worker exiting");
+            return;
+        };
+
+        if reply.is_closed() {
+            debug!(symbol = %job.symbol, "skipping abandoned chart job");
+            continue;
+        }
+
+        // a chart that panics fails its own job, not the worker
+        let res = panic::catch_unwind(AssertUnwindSafe(|| render(&job))).unwrap_or_else(|panic| {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown cause");
+            warn!(symbol = %job.symbol, reason, "chart render panicked");
+            Err(anyhow!(
+                "chart render for {} panicked: {reason}",
+                job.symbol
+            ))
+        });
+        if reply.send(res).is_err() {
+            debug!(symbol = %job.symbol, "chart finished after its caller gave up");
+        }
+    }
+}
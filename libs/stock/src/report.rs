@@ -0,0 +1,225 @@
+//! Machine-readable archives of daily scan runs.
+//!
+//! Each run is one [`RunRecord`] per New York session date, holding every
+//! guild's per-symbol results. Archives are meant to be read back long after
+//! they were written, so the document carries [`SCHEMA_VERSION`] and new
+//! fields must be optional.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument};
+
+use crate::{
+    SymbolStore,
+    indicators::cdc::Signal,
+    scan::{ScanOutcome, ScanReading},
+    timing::RunTimings,
+};
+
+/// Version written into every new [`RunRecord`]. Bump it when the meaning of
+/// an existing field changes, not when an optional field is added.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How long Redis keeps an archived run.
+pub const REDIS_RETENTION_DAYS: i64 = 90;
+
+/// One daily run across every guild it posted to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub version: u32,
+    /// Session date the run scanned.
+    pub date: NaiveDate,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub guilds: Vec<GuildRun>,
+    /// Where the run's time went. Missing on runs archived before timings
+    /// were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<RunTimings>,
+}
+
+impl RunRecord {
+    pub fn new(date: NaiveDate, started_at: DateTime<Utc>) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            date,
+            started_at,
+            finished_at: started_at,
+            guilds: Vec::new(),
+            timings: None,
+        }
+    }
+
+    /// The part of the run that scanned `guild_id`'s watchlist.
+    pub fn guild(&self, guild_id: u64) -> Option<&GuildRun> {
+        self.guilds.iter().find(|g| g.guild_id == guild_id)
+    }
+}
+
+/// Results for one guild's watchlist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuildRun {
+    pub guild_id: u64,
+    pub symbols: Vec<SymbolRecord>,
+    /// Batches that still failed to post after retrying, by batch key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_batches: Vec<String>,
+}
+
+/// What the scan found for one symbol. Everything but the symbol is
+/// optional: symbols without history have no reading, failed ones only an
+/// error.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SymbolRecord {
+    pub symbol: String,
+    pub signal: Option<Signal>,
+    pub close: Option<f64>,
+    pub ema12: Option<f64>,
+    pub ema26: Option<f64>,
+    /// Bar the values were read from.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Ranking score, once scans produce one.
+    pub score: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl SymbolRecord {
+    /// Record for a symbol's scan result, successful or not.
+    pub fn from_result(symbol: &str, result: &Result<ScanOutcome>) -> Self {
+        match result {
+            Ok(outcome) => Self::from_reading(symbol, outcome.reading.as_ref()),
+            Err(e) => Self {
+                symbol: symbol.to_uppercase(),
+                error: Some(format!("{e:#}")),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn from_reading(symbol: &str, reading: Option<&ScanReading>) -> Self {
+        let mut record = Self {
+            symbol: symbol.to_uppercase(),
+            ..Default::default()
+        };
+        if let Some(r) = reading {
+            record.signal = Some(r.signal);
+            record.close = Some(r.close);
+            record.ema12 = Some(r.ema12);
+            record.ema26 = Some(r.ema26);
+            record.timestamp = Some(r.timestamp);
+        }
+        record
+    }
+}
+
+/// Where finished runs are written, picked with `DAILY_ARCHIVE`.
+#[derive(Clone)]
+pub enum RunArchive {
+    /// Nothing is kept.
+    Off,
+    /// `{prefix}:runs:{date}` in Redis, expiring after
+    /// [`REDIS_RETENTION_DAYS`].
+    Redis(Arc<SymbolStore>),
+    /// `run-YYYY-MM-DD.json` files in a directory.
+    File(PathBuf),
+}
+
+impl RunArchive {
+    /// Build from `DAILY_ARCHIVE` (`redis`, `file` or `off`, the default).
+    /// File archives go to `DAILY_ARCHIVE_DIR`, defaulting to `./runs`.
+    pub fn from_env(store: Arc<SymbolStore>) -> Result<Self> {
+        let mode = std::env::var("DAILY_ARCHIVE").unwrap_or_default();
+        let archive = match mode.trim().to_lowercase().as_str() {
+            "" | "off" => Self::Off,
+            "redis" => Self::Redis(store),
+            "file" => Self::File(
+                std::env::var("DAILY_ARCHIVE_DIR")
+                    .unwrap_or_else(|_| "runs".to_string())
+                    .into(),
+            ),
+            other => bail!("DAILY_ARCHIVE must be redis, file or off, got {other:?}"),
+        };
+        info!(mode = archive.name(), "run archive configured");
+        Ok(archive)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Redis(_) => "redis",
+            Self::File(_) => "file",
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, Self::Off)
+    }
+
+    /// Path a file archive uses for `date`.
+    pub fn file_name(date: NaiveDate) -> String {
+        format!("run-{}.json", date.format("%Y-%m-%d"))
+    }
+
+    /// Persist `run`, replacing any earlier run for the same date.
+    #[instrument(name = "run_archive_write", skip_all, fields(mode = self.name(), date = %run.date))]
+    pub async fn write(&self, run: &RunRecord) -> Result<()> {
+        match self {
+            Self::Off => Ok(()),
+            Self::Redis(store) => store.save_run(run).await,
+            Self::File(dir) => {
+                tokio::fs::create_dir_all(dir).await?;
+                let path = dir.join(Self::file_name(run.date));
+                tokio::fs::write(&path, serde_json::to_vec_pretty(run)?).await?;
+                debug!(path = %path.display(), "run archived");
+                Ok(())
+            }
+        }
+    }
+
+    /// Read back the run archived for `date`, if any.
+    #[instrument(name = "run_archive_read", skip(self), fields(mode = self.name()))]
+    pub async fn read(&self, date: NaiveDate) -> Result<Option<RunRecord>> {
+        match self {
+            Self::Off => Ok(None),
+            Self::Redis(store) => store.get_run(date).await,
+            Self::File(dir) => match tokio::fs::read(dir.join(Self::file_name(date))).await {
+                Ok(raw) => Ok(Some(serde_json::from_slice(&raw)?)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+        }
+    }
+
+    /// Most recent archived dates, newest first.
+    pub async fn list(&self, limit: usize) -> Result<Vec<NaiveDate>> {
+        match self {
+            Self::Off => Ok(Vec::new()),
+            Self::Redis(store) => store.list_runs(limit).await,
+            Self::File(dir) => {
+                let mut dates = Vec::new();
+                let mut entries = match tokio::fs::read_dir(dir).await {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(dates),
+                    Err(e) => return Err(e.into()),
+                };
+                while let Some(entry) = entries.next_entry().await? {
+                    let name = entry.file_name();
+                    let date = name
+                        .to_str()
+                        .and_then(|n| n.strip_prefix("run-")?.strip_suffix(".json"))
+                        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+                    if let Some(date) = date {
+                        dates.push(date);
+                    }
+                }
+                dates.sort_unstable_by(|a, b| b.cmp(a));
+                dates.truncate(limit);
+                Ok(dates)
+            }
+        }
+    }
+}
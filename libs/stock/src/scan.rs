@@ -0,0 +1,973 @@
+use std::{any::Any, collections::HashMap, sync::Arc, time::Instant};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use futures::{Stream, StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use tokio::task::AbortHandle;
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::{
+    Bar, ChartJob, ChartRenderer, DataSource, OhlcvSeries, PriceSource, RenderTimeout, Scope,
+    Session, SymbolMeta, SymbolStore, Timeframe, calendar,
+    indicators::{
+        cdc::{
+            CHART_HISTORY, ChartOptions, DEFAULT_MIN_CHART_BARS, Signal, calculate, confirm,
+            enough_to_chart, trend,
+        },
+        volume::{self, VolumeSpike},
+    },
+    spotlight::{self, Setup},
+    strategy::{Strategy, TrendCheck},
+    timing::{Recorder, Stage},
+    usage::ApiUsage,
+};
+
+/// Symbols scanned at once.
+pub const CONCURRENCY: usize = 8;
+
+/// Requests left in the window below which a scan slows down.
+pub const LOW_BUDGET: u32 = 50;
+
+/// Requests a [`prewarm`] keeps in flight, low so it never competes with
+/// interactive commands for the request budget.
+pub const PREWARM_CONCURRENCY: usize = 2;
+
+/// Symbols per multi-symbol request in a [`prewarm`].
+const PREWARM_BATCH: usize = 50;
+
+const LOOKBACK_DAYS: i64 = 300;
+const BAR_LIMIT: usize = 365;
+
+/// How far back an intraday scan fetches: enough bars for the slow average
+/// on hourly bars without paging through months of them.
+pub const INTRADAY_LOOKBACK_DAYS: i64 = 30;
+/// Most bars an intraday scan fetches; a month of 15-minute bars fits.
+const INTRADAY_BAR_LIMIT: usize = 10_000;
+
+/// The bars a scan reads and whether its hits get charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanFrame {
+    pub timeframe: Timeframe,
+    /// How far back bars are fetched.
+    pub lookback: Duration,
+    /// Most bars fetched.
+    pub limit: usize,
+    /// Render a chart for each hit. Without, hits carry an empty chart.
+    pub charts: bool,
+    /// Make every symbol with history a hit, whatever its signal, so each
+    /// one is charted. For charting a list on request rather than scanning.
+    pub every_symbol: bool,
+    /// Fewest bars a hit is charted with; below it the hit goes out
+    /// without a chart. See [`enough_to_chart`].
+    pub min_chart_bars: usize,
+}
+
+impl ScanFrame {
+    /// The daily scan: a year of daily bars, charted.
+    pub fn daily() -> Self {
+        Self {
+            timeframe: Timeframe::Day1,
+            lookback: Duration::days(LOOKBACK_DAYS),
+            limit: BAR_LIMIT,
+            charts: true,
+            every_symbol: false,
+            min_chart_bars: DEFAULT_MIN_CHART_BARS,
+        }
+    }
+
+    /// An intraday scan on `timeframe` bars over the last
+    /// [`INTRADAY_LOOKBACK_DAYS`].
+    pub fn intraday(timeframe: Timeframe, charts: bool) -> Self {
+        Self {
+            timeframe,
+            lookback: Duration::days(INTRADAY_LOOKBACK_DAYS),
+            limit: INTRADAY_BAR_LIMIT,
+            charts,
+            every_symbol: false,
+            min_chart_bars: DEFAULT_MIN_CHART_BARS,
+        }
+    }
+
+    /// A charted scan on `timeframe` bars: [`daily`](Self::daily) for
+    /// daily bars, [`intraday`](Self::intraday) for shorter ones, and the
+    /// timeframe's own lookback for longer ones.
+    pub fn at(timeframe: Timeframe) -> Self {
+        match timeframe {
+            Timeframe::Day1 => Self::daily(),
+            tf if tf.is_intraday() => Self::intraday(tf, true),
+            tf => Self {
+                timeframe: tf,
+                lookback: tf.lookback(),
+                limit: BAR_LIMIT,
+                charts: true,
+                every_symbol: false,
+                min_chart_bars: DEFAULT_MIN_CHART_BARS,
+            },
+        }
+    }
+
+    /// About how many bars the frame gets: the trading sessions in its
+    /// lookback, at [`calendar::SESSIONS_PER_YEAR`], times the bars in
+    /// each, within its limit. Some 207 for the daily scan.
+    pub fn expected_bars(&self) -> usize {
+        let days = self.lookback.num_days().max(0);
+        let bars = match self.timeframe {
+            Timeframe::Week1 => days / 7,
+            Timeframe::Month1 => days / 31,
+            tf => {
+                let sessions = days * calendar::SESSIONS_PER_YEAR / 365;
+                sessions * calendar::bars_per_session(tf) as i64
+            }
+        };
+        (bars as usize).min(self.limit)
+    }
+
+    /// This frame, with every symbol a hit.
+    pub fn every_symbol(self) -> Self {
+        Self {
+            every_symbol: true,
+            ..self
+        }
+    }
+
+    /// This frame, charting hits with at least `bars` bars.
+    pub fn min_chart_bars(self, bars: usize) -> Self {
+        Self {
+            min_chart_bars: bars,
+            ..self
+        }
+    }
+}
+
+/// A symbol whose latest bar produced a Buy or Sell crossover.
+#[derive(Debug, Clone)]
+pub struct ScanHit {
+    pub symbol: String,
+    pub signal: Signal,
+    /// Close of the bar the signal fired on.
+    pub close: f64,
+    pub timestamp: DateTime<Utc>,
+    pub timeframe: Timeframe,
+    /// Where the signal bar came from.
+    pub source: DataSource,
+    /// Rendered PNG chart. Empty when the scan ran without charts or the
+    /// chart failed.
+    pub chart: Vec<u8>,
+    /// The chart failed to render, so the hit goes out as text alone.
+    pub chart_failed: bool,
+    /// Fallback source the bars came from when the first one was down.
+    pub fallback: Option<String>,
+}
+
+/// Whether `hit` was already reported: `previous`, the reading saved after
+/// the last scan at the same timeframe, has the same signal on the same
+/// bar. A run repeated before the next bar closes sees the same bar again.
+pub fn is_repeat(previous: Option<&ScanReading>, hit: &ScanHit) -> bool {
+    previous.is_some_and(|p| p.signal == hit.signal && p.timestamp == hit.timestamp)
+}
+
+/// Whether `volume`, a scan's last closed bar, is a spike to post: at least
+/// `threshold` times its average, on the `today` session, and on a bar other
+/// than the one `previous`, the last run's reading, already covered.
+pub fn is_new_spike(
+    previous: Option<&ScanReading>,
+    volume: &VolumeSpike,
+    threshold: f64,
+    today: NaiveDate,
+) -> bool {
+    volume.ratio >= threshold
+        && calendar::session_date(volume.timestamp) == today
+        && previous.is_none_or(|p| p.timestamp != volume.timestamp)
+}
+
+/// Latest CDC values a scan computed for a symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScanReading {
+    pub signal: Signal,
+    pub close: f64,
+    /// Fast and slow averages under the scan's strategy; the names date from
+    /// when every scan was EMA 12/26.
+    pub ema12: f64,
+    pub ema26: f64,
+    /// Time of the bar the values are for.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Everything a scan learned about one symbol.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutcome {
+    /// None when Alpaca has no history for the symbol.
+    pub reading: Option<ScanReading>,
+    /// Set when the latest bar is a Buy or Sell crossover.
+    pub hit: Option<ScanHit>,
+    /// The last closed bar's volume against its trailing average, for
+    /// callers that post volume spikes. None without enough volume history.
+    pub volume: Option<VolumeSpike>,
+}
+
+/// Latest CDC state for a symbol, without a chart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalReading {
+    pub signal: Signal,
+    pub close: f64,
+}
+
+/// Fetch daily bars for `symbol` and compute its current signal with the
+/// given hysteresis band. Returns `None` when Alpaca has no history for it.
+#[instrument(name = "latest_signal", skip(price_client), fields(symbol = %symbol, band_pct))]
+pub async fn latest_signal(
+    price_client: &dyn PriceSource,
+    symbol: &str,
+    band_pct: f64,
+) -> Result<Option<SignalReading>> {
+    let bars = price_client
+        .fetch_price(
+            symbol,
+            Duration::days(LOOKBACK_DAYS),
+            Timeframe::Day1,
+            BAR_LIMIT,
+            false,
+            Session::Regular,
+        )
+        .await?;
+
+    let Some(last) = bars.last() else {
+        debug!("no bars returned");
+        return Ok(None);
+    };
+
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let (signal, _, _) = calculate(&closes, band_pct);
+    Ok(Some(SignalReading {
+        signal,
+        close: last.close,
+    }))
+}
+
+/// Fetch `frame`'s bars for `symbol`, read its latest signal under
+/// `strategy`, and render a chart if the latest bar is a Buy or Sell
+/// crossover and the frame has charts. Zones and empty histories are not
+/// hits.
+///
+/// IEX daily bars can lag a session behind; when they do, today's bar is
+/// synthesized from the symbol's snapshot before the signal is computed.
+/// Bars of any other size are taken as they are.
+///
+/// A frame with `every_symbol` makes any symbol with history a hit,
+/// carrying its current signal, zone or not.
+///
+/// With `weekly_confirm`, a crossover only counts as a hit when the weekly
+/// trend agrees (see [`confirm`]); weekly bars are fetched only then. The
+/// reading keeps the daily signal either way.
+///
+/// A chart that fails to render, or doesn't within the renderer's timeout,
+/// leaves the hit without one and marked [`chart_failed`](ScanHit::chart_failed):
+/// a crossover is worth reporting without its picture, and one stuck render
+/// can't hold up a scan.
+///
+/// With `timings`, the fetch, indicator and render stages are recorded
+/// there.
+#[allow(clippy::too_many_arguments)]
+#[instrument(name = "scan_symbol", skip(price_client, renderer, frame, timings), fields(symbol = %symbol, timeframe = frame.timeframe.as_str(), %strategy, band_pct, weekly_confirm))]
+pub async fn scan_symbol(
+    price_client: &dyn PriceSource,
+    renderer: &ChartRenderer,
+    symbol: &str,
+    frame: ScanFrame,
+    strategy: Strategy,
+    band_pct: f64,
+    weekly_confirm: bool,
+    timings: Option<&dyn Recorder>,
+) -> Result<ScanOutcome> {
+    let fetch_started = Instant::now();
+    let loaded = load_series(price_client, symbol, frame).await?;
+    if let Some(timings) = timings {
+        timings.record(Stage::Fetch, fetch_started.elapsed());
+    }
+    let Some((series, source, fallback)) = loaded else {
+        return Ok(ScanOutcome::default());
+    };
+
+    let last = series.bars.last().cloned().expect("series is not empty");
+    let volume = volume::relative(&series.bars, volume::DEFAULT_LOOKBACK);
+
+    let (decision, ema12, ema26) = decide(
+        price_client,
+        symbol,
+        &series.bars,
+        strategy,
+        band_pct,
+        weekly_confirm,
+        timings,
+    )
+    .await?;
+    let reading = ScanReading {
+        signal: decision.daily,
+        close: last.close,
+        ema12: *ema12.last().expect("series is not empty"),
+        ema26: *ema26.last().expect("series is not empty"),
+        timestamp: last.timestamp,
+    };
+    let signal = decision.signal;
+    if !is_crossover(signal) && !frame.every_symbol {
+        debug!(?signal, "no actionable signal");
+        return Ok(ScanOutcome {
+            reading: Some(reading),
+            hit: None,
+            volume,
+        });
+    }
+
+    let hit = |chart: Vec<u8>, chart_failed: bool| ScanHit {
+        symbol: symbol.to_string(),
+        signal,
+        close: last.close,
+        timestamp: last.timestamp,
+        timeframe: frame.timeframe,
+        source,
+        chart,
+        chart_failed,
+        fallback: fallback.clone(),
+    };
+    // a handful of bars draws a chart that says nothing
+    if !frame.charts || !enough_to_chart(series.bars.len(), frame.min_chart_bars) {
+        info!(?signal, bars = series.bars.len(), "hit, without chart");
+        return Ok(ScanOutcome {
+            reading: Some(reading),
+            hit: Some(hit(Vec::new(), false)),
+            volume,
+        });
+    }
+
+    // the averages need the full history, the chart only its last bars and
+    // the band's state where they start
+    let (closes, dates) = series.tail_at(frame.timeframe, CHART_HISTORY);
+    let cut = ema12.len().saturating_sub(closes.len());
+    let trend_before = cut
+        .checked_sub(1)
+        .map(|before| trend(&ema12[..cut], &ema26[..cut], band_pct)[before]);
+
+    debug!(bars = closes.len(), "generating chart");
+    let render_started = Instant::now();
+    let chart = renderer
+        .render(ChartJob {
+            symbol: symbol.to_string(),
+            closes: closes.into(),
+            ema12: ema12[cut..].into(),
+            ema26: ema26[cut..].into(),
+            dates: dates.into(),
+            options: ChartOptions {
+                average_names: strategy.line_names(),
+                annotate_crossover: is_crossover(signal),
+                band_pct,
+                trend_before,
+                ..Default::default()
+            },
+        })
+        .await;
+    if let Some(timings) = timings {
+        timings.record(Stage::Render, render_started.elapsed());
+    }
+    // a broken or stuck render costs this symbol its chart, not its post
+    let hit = match chart {
+        Ok(chart) => {
+            info!(?signal, bytes = chart.len(), "hit");
+            hit(chart, false)
+        }
+        Err(e) => {
+            let timed_out = e.is::<RenderTimeout>();
+            warn!(?signal, timed_out, error = ?e, "chart render failed, posting hit without it");
+            hit(Vec::new(), true)
+        }
+    };
+    Ok(ScanOutcome {
+        reading: Some(reading),
+        hit: Some(hit),
+        volume,
+    })
+}
+
+/// `frame`'s bars for `symbol` as a scan reads them, with where the latest
+/// one came from and the fallback source that served them, if any. Daily
+/// bars lagging the session get today's bar from the snapshot. None without
+/// history.
+async fn load_series(
+    price_client: &dyn PriceSource,
+    symbol: &str,
+    frame: ScanFrame,
+) -> Result<Option<(OhlcvSeries, DataSource, Option<String>)>> {
+    let (mut bars, fallback) = price_client
+        .fetch_price_served(
+            symbol,
+            frame.lookback,
+            frame.timeframe,
+            frame.limit,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    // a crossover on a bar still forming can be gone by its close; a chart
+    // on request still shows it
+    if frame.timeframe.is_intraday() && !frame.every_symbol {
+        let now = Utc::now();
+        bars.retain(|bar| bar.timestamp + frame.timeframe.bar_length() <= now);
+    }
+
+    if bars.is_empty() {
+        debug!("no bars returned");
+        return Ok(None);
+    }
+
+    let mut series = OhlcvSeries::new(bars);
+    let session = calendar::latest_session(Utc::now());
+    let daily = frame.timeframe == Timeframe::Day1;
+    let snapshot = if !daily || series.is_fresh(session) {
+        None
+    } else {
+        debug!(%session, "bars lag the session, fetching snapshot");
+        price_client
+            .fetch_snapshot(symbol)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = ?e, "snapshot fetch failed");
+                None
+            })
+    };
+    let source = if daily {
+        series.ensure_fresh(session, snapshot.as_ref())
+    } else {
+        DataSource::Bars
+    };
+    Ok(Some((series, source, fallback)))
+}
+
+/// What a scan decides for a symbol's latest bar, filter by filter, so the
+/// decision can be explained as well as acted on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decision {
+    /// The strategy's crossover signal before any filter.
+    pub raw: Signal,
+    /// The ADX check, when the strategy has one.
+    pub trend: Option<TrendCheck>,
+    /// `raw` after the ADX filter, the signal a [`ScanReading`] keeps.
+    pub daily: Signal,
+    /// The weekly signal, when a crossover had to be confirmed by it.
+    pub weekly: Option<Signal>,
+    /// The signal after every filter. Only a Buy or Sell is posted.
+    pub signal: Signal,
+}
+
+/// Why a [`Decision`] is posted or not.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// A crossover every filter let through.
+    Posts,
+    /// No crossover on the latest bar, only the zone it's in.
+    NoCrossover,
+    /// A crossover on a trend too weak for the ADX filter.
+    WeakTrend(TrendCheck),
+    /// A crossover the weekly signal doesn't confirm; [`Signal::None`]
+    /// when there wasn't enough weekly history to tell.
+    WeeklyDisagrees(Signal),
+}
+
+impl Decision {
+    /// Whether the scan posts it.
+    pub fn posts(&self) -> bool {
+        is_crossover(self.signal)
+    }
+
+    /// The first filter that stopped the crossover, if one did.
+    pub fn verdict(&self) -> Verdict {
+        if !is_crossover(self.raw) {
+            return Verdict::NoCrossover;
+        }
+        if let Some(trend) = self.trend.filter(|t| !t.passes()) {
+            return Verdict::WeakTrend(trend);
+        }
+        match self.weekly {
+            Some(weekly) if !self.posts() => Verdict::WeeklyDisagrees(weekly),
+            _ => Verdict::Posts,
+        }
+    }
+}
+
+fn is_crossover(signal: Signal) -> bool {
+    matches!(signal, Signal::Buy | Signal::Sell)
+}
+
+/// Run a scan's filters over `bars`, oldest first: `strategy`'s crossover
+/// and ADX filter, then with `weekly_confirm` the weekly trend, fetched
+/// only for a crossover. Returns the decision with the strategy's fast and
+/// slow averages. With `timings`, the indicators and the weekly fetch are
+/// recorded there.
+pub async fn decide(
+    price_client: &dyn PriceSource,
+    symbol: &str,
+    bars: &[Bar],
+    strategy: Strategy,
+    band_pct: f64,
+    weekly_confirm: bool,
+    timings: Option<&dyn Recorder>,
+) -> Result<(Decision, Vec<f64>, Vec<f64>)> {
+    let indicators_started = Instant::now();
+    let (assessment, fast, slow) = strategy.assess(bars, band_pct);
+    if let Some(timings) = timings {
+        timings.record(Stage::Indicators, indicators_started.elapsed());
+    }
+    let mut decision = Decision {
+        raw: assessment.raw,
+        trend: assessment.trend,
+        daily: assessment.signal,
+        weekly: None,
+        signal: assessment.signal,
+    };
+    if weekly_confirm && is_crossover(decision.daily) {
+        let fetch_started = Instant::now();
+        let weekly = weekly_signal(price_client, symbol, strategy, band_pct).await?;
+        if let Some(timings) = timings {
+            timings.record(Stage::Fetch, fetch_started.elapsed());
+        }
+        decision.weekly = Some(weekly);
+        decision.signal = confirm(decision.daily, weekly);
+        if decision.signal != decision.daily {
+            info!(
+                signal = ?decision.daily,
+                ?weekly,
+                "weekly trend disagrees, dropping crossover"
+            );
+        }
+    }
+    Ok((decision, fast, slow))
+}
+
+/// The daily scan's decision on `symbol`'s latest bar, with that bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub decision: Decision,
+    pub close: f64,
+    pub timestamp: DateTime<Utc>,
+    /// Where the latest bar came from.
+    pub source: DataSource,
+    /// Fallback source the bars came from when the first one was down.
+    pub fallback: Option<String>,
+}
+
+/// Decide on `symbol` exactly as [`scan_symbol`] would in the daily scan,
+/// without rendering anything. None without history.
+#[instrument(name = "check_symbol", skip(price_client), fields(symbol = %symbol, %strategy, band_pct, weekly_confirm))]
+pub async fn check_symbol(
+    price_client: &dyn PriceSource,
+    symbol: &str,
+    strategy: Strategy,
+    band_pct: f64,
+    weekly_confirm: bool,
+) -> Result<Option<Check>> {
+    let Some((series, source, fallback)) =
+        load_series(price_client, symbol, ScanFrame::daily()).await?
+    else {
+        return Ok(None);
+    };
+    let last = series.bars.last().cloned().expect("series is not empty");
+    let (decision, _, _) = decide(
+        price_client,
+        symbol,
+        &series.bars,
+        strategy,
+        band_pct,
+        weekly_confirm,
+        None,
+    )
+    .await?;
+    debug!(?decision, "checked");
+    Ok(Some(Check {
+        decision,
+        close: last.close,
+        timestamp: last.timestamp,
+        source,
+        fallback,
+    }))
+}
+
+/// `strategy`'s signal on `symbol`'s weekly bars. None without enough
+/// history.
+async fn weekly_signal(
+    price_client: &dyn PriceSource,
+    symbol: &str,
+    strategy: Strategy,
+    band_pct: f64,
+) -> Result<Signal> {
+    let bars = price_client
+        .fetch_price(
+            symbol,
+            Timeframe::Week1.lookback(),
+            Timeframe::Week1,
+            BAR_LIMIT,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    let (signal, _, _) = strategy.evaluate(&bars, band_pct);
+    debug!(?signal, bars = bars.len(), "weekly signal");
+    Ok(signal)
+}
+
+/// Symbols to scan at once with `usage` left of the request budget: the full
+/// [`CONCURRENCY`] when the budget is unknown or at least [`LOW_BUDGET`],
+/// scaled down with it below that, and never less than one.
+pub fn concurrency_for(usage: Option<ApiUsage>) -> usize {
+    match usage {
+        Some(usage) if usage.remaining < LOW_BUDGET => {
+            (CONCURRENCY * usage.remaining as usize / LOW_BUDGET as usize).max(1)
+        }
+        _ => CONCURRENCY,
+    }
+}
+
+/// Scan `symbols` concurrently, yielding each symbol with its result as soon
+/// as it completes. Concurrency is picked from the source's request budget
+/// when the scan starts. See [`scan_symbol`] for `strategy` and
+/// `weekly_confirm`; hits with fewer than `min_chart_bars` bars go out
+/// without a chart.
+///
+/// Each symbol is scanned on its own task, so a panic while scanning one
+/// comes back as that symbol's error and the rest of the scan carries on.
+pub fn scan(
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbols: Vec<String>,
+    strategy: Strategy,
+    band_pct: f64,
+    weekly_confirm: bool,
+    min_chart_bars: usize,
+) -> impl Stream<Item = (String, Result<ScanOutcome>)> {
+    scan_inner(
+        price_client,
+        renderer,
+        symbols,
+        ScanFrame::daily().min_chart_bars(min_chart_bars),
+        strategy,
+        band_pct,
+        weekly_confirm,
+        None,
+    )
+}
+
+/// [`scan`], recording how long each symbol's fetch, indicators and render
+/// took in `timings`.
+#[allow(clippy::too_many_arguments)]
+pub fn scan_timed(
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbols: Vec<String>,
+    strategy: Strategy,
+    band_pct: f64,
+    weekly_confirm: bool,
+    min_chart_bars: usize,
+    timings: Arc<dyn Recorder>,
+) -> impl Stream<Item = (String, Result<ScanOutcome>)> {
+    scan_inner(
+        price_client,
+        renderer,
+        symbols,
+        ScanFrame::daily().min_chart_bars(min_chart_bars),
+        strategy,
+        band_pct,
+        weekly_confirm,
+        Some(timings),
+    )
+}
+
+/// [`scan`] on `frame`'s bars rather than daily ones. There is no weekly
+/// confirmation: a weekly trend says little about an hourly crossover.
+pub fn scan_frame(
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbols: Vec<String>,
+    frame: ScanFrame,
+    strategy: Strategy,
+    band_pct: f64,
+) -> impl Stream<Item = (String, Result<ScanOutcome>)> {
+    scan_inner(
+        price_client,
+        renderer,
+        symbols,
+        frame,
+        strategy,
+        band_pct,
+        false,
+        None,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn scan_inner(
+    price_client: Arc<dyn PriceSource>,
+    renderer: Arc<ChartRenderer>,
+    symbols: Vec<String>,
+    frame: ScanFrame,
+    strategy: Strategy,
+    band_pct: f64,
+    weekly_confirm: bool,
+    timings: Option<Arc<dyn Recorder>>,
+) -> impl Stream<Item = (String, Result<ScanOutcome>)> {
+    let usage = price_client.usage();
+    let concurrency = concurrency_for(usage);
+    if let Some(usage) = usage
+        && concurrency < CONCURRENCY
+    {
+        warn!(
+            used = usage.used,
+            remaining = usage.remaining,
+            concurrency,
+            "request budget low, scanning with reduced concurrency"
+        );
+    }
+
+    stream::iter(symbols)
+        .map(move |symbol| {
+            let price_client = price_client.clone();
+            let renderer = renderer.clone();
+            let timings = timings.clone();
+            async move {
+                let task = {
+                    let symbol = symbol.clone();
+                    async move {
+                        scan_symbol(
+                            price_client.as_ref(),
+                            &renderer,
+                            &symbol,
+                            frame,
+                            strategy,
+                            band_pct,
+                            weekly_confirm,
+                            timings.as_deref(),
+                        )
+                        .await
+                    }
+                };
+                let res = isolated(&symbol, task).await;
+                (symbol, res)
+            }
+        })
+        .buffer_unordered(concurrency)
+}
+
+/// Run `task` on a task of its own, turning a panic in it into an error
+/// for `symbol` instead of unwinding whoever is driving the scan. Dropping
+/// the future, as a dropped scan stream does, aborts the task rather than
+/// leaving it fetching in the background.
+async fn isolated<T: Send + 'static>(
+    symbol: &str,
+    task: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    let handle = tokio::spawn(task);
+    let _abort = AbortOnDrop(handle.abort_handle());
+    match handle.await {
+        Ok(res) => res,
+        Err(e) if e.is_panic() => {
+            let message = panic_message(e.into_panic());
+            error!(%symbol, panic = %message, "scan panicked");
+            Err(anyhow!("scan panicked: {message}"))
+        }
+        Err(e) => Err(anyhow!("scan task was cancelled: {e}")),
+    }
+}
+
+/// Aborts a spawned task when dropped. Aborting one that already finished
+/// does nothing.
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// The message a panic was raised with, when it was given one.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "unknown panic".to_string(), |s| s.to_string()),
+    }
+}
+
+/// Fetch the daily bars a [`scan`] of `symbols` will ask for and keep them
+/// for `ttl`, so the scan itself runs mostly from the cache. Batches go out
+/// through the source's multi-symbol fetch, [`PREWARM_CONCURRENCY`] at a
+/// time.
+///
+/// Best-effort: a failed batch is logged and its symbols are simply fetched
+/// by the scan as usual. Returns how many symbols were warmed.
+#[instrument(name = "prewarm", skip_all, fields(symbols = symbols.len(), ?ttl))]
+pub async fn prewarm(
+    price_client: Arc<dyn PriceSource>,
+    symbols: &[String],
+    ttl: std::time::Duration,
+) -> usize {
+    let started = Instant::now();
+    let batches: Vec<Vec<String>> = symbols
+        .chunks(PREWARM_BATCH)
+        .map(<[String]>::to_vec)
+        .collect();
+    let warmed: usize = stream::iter(batches)
+        .map(move |batch| {
+            let price_client = price_client.clone();
+            async move {
+                price_client
+                    .warm_bars(
+                        &batch,
+                        Duration::days(LOOKBACK_DAYS),
+                        Timeframe::Day1,
+                        BAR_LIMIT,
+                        Session::Regular,
+                        ttl,
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(batch = batch.len(), error = ?e, "prewarm batch failed");
+                        0
+                    })
+            }
+        })
+        .buffer_unordered(PREWARM_CONCURRENCY)
+        .fold(0, |total, n| async move { total + n })
+        .await;
+
+    info!(
+        warmed,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "prewarmed bar cache"
+    );
+    warmed
+}
+
+/// The scan in rank mode: read every one of `symbols`, keep the strongest
+/// setup if it scores at least `min_score`, and render a chart for it
+/// alone. Symbols that fail to fetch are logged and left out.
+#[instrument(name = "top_setup", skip_all, fields(symbols = symbols.len(), band_pct, min_score))]
+pub async fn top_setup(
+    price_client: Arc<dyn PriceSource>,
+    renderer: &ChartRenderer,
+    symbols: Vec<String>,
+    band_pct: f64,
+    min_score: f64,
+) -> Result<Option<(Setup, Vec<u8>)>> {
+    let concurrency = concurrency_for(price_client.usage());
+    let candidates: Vec<(Setup, Vec<Bar>)> = stream::iter(symbols)
+        .map(|symbol| {
+            let price_client = price_client.clone();
+            async move {
+                let res = price_client
+                    .fetch_price(
+                        &symbol,
+                        Duration::days(LOOKBACK_DAYS),
+                        Timeframe::Day1,
+                        BAR_LIMIT,
+                        false,
+                        Session::Regular,
+                    )
+                    .await;
+                (symbol, res)
+            }
+        })
+        .buffer_unordered(concurrency)
+        .filter_map(|(symbol, res)| async move {
+            match res {
+                Ok(bars) => spotlight::assess(&symbol, &bars, band_pct).map(|s| (s, bars)),
+                Err(e) => {
+                    warn!(%symbol, error = ?e, "fetch failed, leaving symbol out of the ranking");
+                    None
+                }
+            }
+        })
+        .collect()
+        .await;
+
+    let Some(top) = spotlight::rank(candidates.iter().map(|(s, _)| s.clone()), min_score) else {
+        info!(
+            candidates = candidates.len(),
+            "nothing cleared the minimum score"
+        );
+        return Ok(None);
+    };
+    let (_, bars) = candidates
+        .into_iter()
+        .find(|(s, _)| s.symbol == top.symbol)
+        .expect("ranked setup is a candidate");
+
+    let series = OhlcvSeries::new(bars);
+    let closes = series.closes();
+    let (_, ema12, ema26) = calculate(&closes, band_pct);
+    let chart = renderer
+        .render(ChartJob {
+            symbol: top.symbol.clone(),
+            closes: closes.into(),
+            ema12: ema12.into(),
+            ema26: ema26.into(),
+            dates: series.dates().into(),
+            options: ChartOptions::default(),
+        })
+        .await?;
+
+    info!(symbol = %top.symbol, score = top.score(), "picked top setup");
+    Ok(Some((top, chart)))
+}
+
+/// Fill in missing added prices for `symbols` from their daily bars, saving
+/// and updating `meta` as it goes. Meant to run right after a scan, so the
+/// bars normally come from the cache. Returns how many were backfilled.
+#[instrument(name = "backfill_added", skip_all, fields(%scope, symbols = symbols.len()))]
+pub async fn backfill_added(
+    price_client: &dyn PriceSource,
+    store: &SymbolStore,
+    scope: Scope,
+    symbols: &[String],
+    meta: &mut HashMap<String, SymbolMeta>,
+) -> usize {
+    let mut filled = 0;
+
+    for symbol in symbols {
+        let entry = meta.entry(symbol.to_uppercase()).or_default();
+        if entry.added_price.is_some() {
+            continue;
+        }
+
+        let bars = match price_client
+            .fetch_price(
+                symbol,
+                Duration::days(LOOKBACK_DAYS),
+                Timeframe::Day1,
+                BAR_LIMIT,
+                false,
+                Session::Regular,
+            )
+            .await
+        {
+            Ok(bars) => bars,
+            Err(e) => {
+                warn!(%symbol, error = ?e, "backfill fetch failed");
+                continue;
+            }
+        };
+
+        if !entry.backfill_added(&bars) {
+            continue;
+        }
+        match store.set_meta(scope, symbol, entry).await {
+            Ok(()) => {
+                debug!(%symbol, price = ?entry.added_price, approx = entry.added_price_approx, "backfilled added price");
+                filled += 1;
+            }
+            Err(e) => warn!(%symbol, error = ?e, "failed to save backfilled added price"),
+        }
+    }
+
+    if filled > 0 {
+        info!(filled, "backfilled added prices");
+    }
+    filled
+}
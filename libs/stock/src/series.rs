@@ -0,0 +1,98 @@
+use chrono::NaiveDate;
+use tracing::{info, warn};
+
+use crate::{
+    Bar, Snapshot, Timeframe,
+    calendar::{DEFAULT_TIMEZONE, axis_label, session_date},
+};
+
+/// Where the latest bar of a series came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataSource {
+    /// Historical bars already covered the expected session.
+    Bars,
+    /// The latest bar was synthesized from the snapshot's daily bar.
+    Snapshot,
+    /// Bars lag the expected session and no fresher data was available;
+    /// `last` is the session of the newest bar.
+    Stale { last: NaiveDate },
+}
+
+/// Price bars for one symbol, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct OhlcvSeries {
+    pub bars: Vec<Bar>,
+}
+
+impl OhlcvSeries {
+    pub fn new(bars: Vec<Bar>) -> Self {
+        Self { bars }
+    }
+
+    /// Session of the newest bar.
+    pub fn last_session(&self) -> Option<NaiveDate> {
+        self.bars.last().map(|b| session_date(b.timestamp))
+    }
+
+    /// Whether the newest bar is from `session` or later.
+    pub fn is_fresh(&self, session: NaiveDate) -> bool {
+        self.last_session().is_some_and(|last| last >= session)
+    }
+
+    /// Make sure the series reaches `session`. When the bars lag behind and
+    /// the snapshot's daily bar is from that session, it is appended as
+    /// today's bar. Returns which source the latest bar now comes from.
+    pub fn ensure_fresh(&mut self, session: NaiveDate, snapshot: Option<&Snapshot>) -> DataSource {
+        if self.is_fresh(session) {
+            return DataSource::Bars;
+        }
+
+        let daily = snapshot
+            .and_then(|s| s.daily_bar.as_ref())
+            .filter(|b| session_date(b.timestamp) >= session);
+
+        match daily {
+            Some(bar) => {
+                info!(%session, close = bar.close, "synthesized latest bar from snapshot");
+                self.bars.push(bar.clone());
+                DataSource::Snapshot
+            }
+            None => {
+                let last = self.last_session().unwrap_or(NaiveDate::MIN);
+                warn!(%session, %last, "bars are stale and no fresher snapshot");
+                DataSource::Stale { last }
+            }
+        }
+    }
+
+    pub fn closes(&self) -> Vec<f64> {
+        self.bars.iter().map(|b| b.close).collect()
+    }
+
+    /// Axis labels for the daily bars, one per bar.
+    pub fn dates(&self) -> Vec<String> {
+        self.dates_at(Timeframe::Day1)
+    }
+
+    /// Axis labels for bars of `timeframe`, one per bar. Intraday bars are
+    /// labelled with the time as well as the date.
+    pub fn dates_at(&self, timeframe: Timeframe) -> Vec<String> {
+        labels(&self.bars, timeframe)
+    }
+
+    /// Closes and axis labels of the last `n` bars only, for a chart that
+    /// draws no further back. Spares formatting a label for every bar.
+    pub fn tail_at(&self, timeframe: Timeframe, n: usize) -> (Vec<f64>, Vec<String>) {
+        let tail = &self.bars[self.bars.len().saturating_sub(n)..];
+        (
+            tail.iter().map(|b| b.close).collect(),
+            labels(tail, timeframe),
+        )
+    }
+}
+
+fn labels(bars: &[Bar], timeframe: Timeframe) -> Vec<String> {
+    bars.iter()
+        .map(|b| axis_label(b.timestamp, timeframe, DEFAULT_TIMEZONE))
+        .collect()
+}
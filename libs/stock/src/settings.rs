@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    Bar, Timeframe,
+    calendar::{DEFAULT_TIMEZONE, parse_timezone, session_date},
+    indicators::{
+        cdc::{ChartScale, ChartTheme, Signal},
+        volume,
+    },
+};
+
+/// Per-guild configuration persisted by [`crate::SymbolStore`].
+///
+/// Every field is optional so older stored documents keep deserializing as
+/// new settings are added.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// Locale code (e.g. `th`) overriding each member's Discord language.
+    pub locale: Option<String>,
+    /// Channel the daily scan posts this guild's watchlist to.
+    pub daily_channel: Option<u64>,
+    /// React to `$TICKER` mentions with the symbol's current zone.
+    pub cashtag_reactions: bool,
+    /// Also reply to `$TICKER` mentions with a one-line price.
+    pub cashtag_replies: bool,
+    /// The daily scan skips this guild until then. Left in place after it
+    /// passes so the next run can say it resumed, then cleared.
+    pub daily_paused_until: Option<DateTime<Utc>>,
+    /// Built-in signal style by name (e.g. `colorblind`); the default when
+    /// unset.
+    pub style_preset: Option<String>,
+    /// Embed color for buy signals as `0xRRGGBB`, over the preset's.
+    pub buy_color: Option<u32>,
+    /// Embed color for sell signals as `0xRRGGBB`, over the preset's.
+    pub sell_color: Option<u32>,
+    /// Emoji for buy signals, over the preset's.
+    pub buy_emoji: Option<String>,
+    /// Emoji for sell signals, over the preset's.
+    pub sell_emoji: Option<String>,
+    /// IANA zone name charts are labelled in; New York when unset.
+    pub display_timezone: Option<String>,
+    /// Text in the corner of `/stock share` images, already sanitized.
+    pub share_watermark: Option<String>,
+    /// The scan on intraday bars, run as each one closes.
+    pub intraday: IntradaySettings,
+    /// Channel each symbol tag's hits go to instead of the scan's own.
+    pub tag_routes: BTreeMap<String, u64>,
+    /// Post a symbol whose latest bar trades far above its average volume,
+    /// crossover or not.
+    pub volume_spike_alerts: bool,
+    /// Multiple of the average volume that makes a spike; the default when
+    /// unset.
+    pub volume_spike_threshold: Option<f64>,
+}
+
+/// Longest tag, in characters.
+pub const MAX_TAG_LEN: usize = 32;
+/// Most tags one symbol carries.
+pub const MAX_TAGS: usize = 10;
+
+/// A tag as stored: lowercase, without a leading `#`. None when it's empty,
+/// too long, or has anything but letters, digits, `-` and `_`.
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#').to_lowercase();
+    let valid = !tag.is_empty()
+        && tag.chars().count() <= MAX_TAG_LEN
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(tag)
+}
+
+/// Longest note on a watched symbol, in characters.
+pub const MAX_NOTE_LEN: usize = 200;
+
+/// A note as stored: trimmed and on one line. None when it's empty or
+/// longer than [`MAX_NOTE_LEN`].
+pub fn normalize_note(raw: &str) -> Option<String> {
+    let note = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!note.is_empty() && note.chars().count() <= MAX_NOTE_LEN).then_some(note)
+}
+
+/// Channels a hit on a symbol tagged `tags` goes to: each routed tag's, in
+/// tag order, each channel once. Empty when no tag is routed, meaning the
+/// scan's own channel.
+pub fn route_channels(tags: &[String], routes: &BTreeMap<String, u64>) -> Vec<u64> {
+    let mut channels = Vec::new();
+    for channel in tags.iter().filter_map(|tag| routes.get(tag)) {
+        if !channels.contains(channel) {
+            channels.push(*channel);
+        }
+    }
+    channels
+}
+
+/// Timeframes the intraday scan can run on.
+pub const INTRADAY_TIMEFRAMES: [Timeframe; 3] =
+    [Timeframe::Minute15, Timeframe::Minute30, Timeframe::Hour1];
+
+/// A guild's intraday scan: crossovers on shorter bars, posted during
+/// market hours to a channel of their own.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntradaySettings {
+    pub enabled: bool,
+    /// Channel intraday hits are posted to, apart from the daily channel.
+    pub channel: Option<u64>,
+    /// Bar size, one of [`INTRADAY_TIMEFRAMES`]; an hour when unset.
+    pub timeframe: Option<Timeframe>,
+    /// Attach a chart to each hit. Off by default: text-only posts keep
+    /// the runs quick.
+    pub charts: bool,
+}
+
+impl IntradaySettings {
+    /// Bar size to scan. A stored timeframe the scan doesn't support falls
+    /// back to an hour.
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+            .filter(|tf| INTRADAY_TIMEFRAMES.contains(tf))
+            .unwrap_or(Timeframe::Hour1)
+    }
+
+    /// Channel to post to, when the scan is on and has somewhere to post.
+    pub fn target(&self) -> Option<u64> {
+        self.channel.filter(|_| self.enabled)
+    }
+}
+
+impl GuildSettings {
+    /// End of the daily pause if it is still running at `now`.
+    pub fn daily_paused_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.daily_paused_until.filter(|until| *until > now)
+    }
+
+    /// A pause ran out at or before `now` and no daily run has cleared it yet.
+    pub fn daily_pause_expired(&self, now: DateTime<Utc>) -> bool {
+        self.daily_paused_until.is_some_and(|until| until <= now)
+    }
+
+    /// Multiple of the average volume the scans post spikes at, or None
+    /// with volume spike alerts off.
+    pub fn volume_spikes(&self) -> Option<f64> {
+        self.volume_spike_alerts.then(|| {
+            self.volume_spike_threshold
+                .unwrap_or(volume::DEFAULT_THRESHOLD)
+        })
+    }
+
+    /// Zone to show times in. A name that no longer parses falls back to
+    /// the default rather than failing the render.
+    pub fn timezone(&self) -> Tz {
+        match self.display_timezone.as_deref().map(parse_timezone) {
+            Some(Ok(tz)) => tz,
+            Some(Err(e)) => {
+                warn!(error = %e, "stored timezone is invalid, using default");
+                DEFAULT_TIMEZONE
+            }
+            None => DEFAULT_TIMEZONE,
+        }
+    }
+}
+
+/// Per-user preferences persisted by [`crate::SymbolStore`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserPrefs {
+    /// Reply to watchlist management commands with ephemeral messages.
+    pub ephemeral_replies: bool,
+    /// Chart timeframe for symbols the user hasn't graphed before.
+    pub default_timeframe: Option<Timeframe>,
+    /// DM a digest of the daily scan's hits on the user's own watchlist.
+    pub daily_digest: bool,
+    /// Server the digest was turned on from; a notice goes to its daily
+    /// channel if the DM can't be delivered.
+    pub digest_guild: Option<u64>,
+    /// List the user among the top users in `/stock stats`.
+    pub show_in_stats: bool,
+    /// Colors of the charts the user asks for.
+    pub chart_theme: ChartTheme,
+    /// Pixel density of the charts the user asks for.
+    pub chart_scale: ChartScale,
+    /// Discord language the user last set an alert in, for the DMs alerts
+    /// send.
+    pub locale: Option<String>,
+}
+
+/// Per-symbol metadata within one watchlist scope.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SymbolMeta {
+    /// Signals are still computed but demoted until this time.
+    pub quiet_until: Option<DateTime<Utc>>,
+    /// When the symbol was put on the watchlist.
+    pub added_at: Option<DateTime<Utc>>,
+    /// Price when the symbol was put on the watchlist.
+    pub added_price: Option<f64>,
+    /// `added_price` was backfilled from history rather than recorded live.
+    pub added_price_approx: bool,
+    /// Labels like `tech` grouping symbols, normalized by [`normalize_tag`].
+    pub tags: Vec<String>,
+    /// Why the symbol is watched, normalized by [`normalize_note`].
+    pub note: Option<String>,
+}
+
+impl SymbolMeta {
+    /// End of the quiet period if it is still running at `now`. Expired
+    /// periods are ignored here rather than cleaned up.
+    pub fn quiet_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.quiet_until.filter(|until| *until > now)
+    }
+
+    /// Percent change from the added price to `price`.
+    pub fn change_since_added(&self, price: f64) -> Option<f64> {
+        self.added_price
+            .filter(|p| *p > 0.0)
+            .map(|added| (price - added) / added * 100.0)
+    }
+
+    /// Fill in a missing added price from `bars` (oldest first). Symbols added
+    /// while the price fetch failed take the close of the first bar since
+    /// they were added. Symbols from before added prices were recorded take
+    /// the earliest bar available and are marked approximate. Returns true
+    /// if anything changed.
+    pub fn backfill_added(&mut self, bars: &[Bar]) -> bool {
+        if self.added_price.is_some() {
+            return false;
+        }
+
+        match self.added_at {
+            Some(at) => {
+                let session = session_date(at);
+                let Some(bar) = bars.iter().find(|b| session_date(b.timestamp) >= session) else {
+                    return false;
+                };
+                self.added_price = Some(bar.close);
+            }
+            None => {
+                let Some(first) = bars.first() else {
+                    return false;
+                };
+                self.added_at = Some(first.timestamp);
+                self.added_price = Some(first.close);
+                self.added_price_approx = true;
+            }
+        }
+        true
+    }
+}
+
+/// Where a scan hit was posted, so a reversal soon after can point back
+/// at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostedSignal {
+    pub signal: Signal,
+    /// Session the hit was for.
+    pub session: NaiveDate,
+    pub channel_id: u64,
+    pub message_id: u64,
+    /// Position of the hit's embed in its message.
+    pub embed_index: usize,
+}
+
+impl PostedSignal {
+    /// Whether `signal` on `session` reverses this post of a `timeframe`
+    /// hit: Buy after Sell or Sell after Buy, no more than `within_days` on.
+    /// A daily post is reversed only by a later session, an intraday one by
+    /// a later bar of its own session too. Zero days turns reversals off.
+    pub fn reversed_by(
+        &self,
+        signal: Signal,
+        session: NaiveDate,
+        within_days: i64,
+        timeframe: Timeframe,
+    ) -> bool {
+        let opposite = matches!(
+            (self.signal, signal),
+            (Signal::Buy, Signal::Sell) | (Signal::Sell, Signal::Buy)
+        );
+        let first = if timeframe.is_intraday() { 0 } else { 1 };
+        let days = (session - self.session).num_days();
+        opposite && within_days > 0 && (first..=within_days).contains(&days)
+    }
+}
@@ -0,0 +1,95 @@
+//! Picking the chart of the day: the watchlist symbol whose CDC setup moved
+//! the most in the last session.
+//!
+//! A setup is scored by how far the gap between the fast and slow EMAs moved
+//! over the last bar, in percentage points of the slow EMA. Fresh crossovers
+//! and accelerating trends score high; symbols drifting sideways score near
+//! zero, and a day where nothing clears [`MIN_SCORE`] gets no post at all.
+
+use crate::{
+    Bar,
+    indicators::cdc::{Signal, calculate},
+};
+
+/// Score a setup needs to be worth posting, in percentage points of EMA gap
+/// change over one session.
+pub const MIN_SCORE: f64 = 0.25;
+
+/// Fewest bars a symbol needs before its EMAs are meaningful enough to rank.
+pub const MIN_BARS: usize = 30;
+
+/// Sessions averaged for the volume comparison, not counting the latest.
+pub const VOLUME_AVERAGE_BARS: usize = 20;
+
+/// One symbol's setup as of its latest bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setup {
+    pub symbol: String,
+    pub signal: Signal,
+    pub close: f64,
+    /// Fast EMA minus slow EMA, in percent of the slow EMA.
+    pub gap_pct: f64,
+    /// Change in `gap_pct` over the last bar, in percentage points.
+    pub gap_change: f64,
+    /// Latest volume over the previous [`VOLUME_AVERAGE_BARS`]' average, when
+    /// there is enough history and volume to compare.
+    pub volume_ratio: Option<f64>,
+}
+
+impl Setup {
+    /// How strong the setup is, whichever way it points.
+    pub fn score(&self) -> f64 {
+        self.gap_change.abs()
+    }
+
+    pub fn is_bullish(&self) -> bool {
+        matches!(self.signal, Signal::Buy | Signal::BullishZone)
+    }
+}
+
+/// Read `symbol`'s setup from its daily `bars`, oldest first. None with
+/// fewer than [`MIN_BARS`] bars or a gap that isn't finite.
+pub fn assess(symbol: &str, bars: &[Bar], band_pct: f64) -> Option<Setup> {
+    let n = bars.len();
+    if n < MIN_BARS {
+        return None;
+    }
+
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let (signal, ema12, ema26) = calculate(&closes, band_pct);
+    let gap = |i: usize| (ema12[i] - ema26[i]) / ema26[i] * 100.0;
+    let (gap_pct, previous) = (gap(n - 1), gap(n - 2));
+    if !gap_pct.is_finite() || !previous.is_finite() {
+        return None;
+    }
+
+    let volume_ratio = (n > VOLUME_AVERAGE_BARS)
+        .then(|| {
+            let window = &bars[n - 1 - VOLUME_AVERAGE_BARS..n - 1];
+            let average = window.iter().map(|b| b.volume).sum::<f64>() / VOLUME_AVERAGE_BARS as f64;
+            (average > 0.0).then(|| bars[n - 1].volume / average)
+        })
+        .flatten();
+
+    Some(Setup {
+        symbol: symbol.to_uppercase(),
+        signal,
+        close: bars[n - 1].close,
+        gap_pct,
+        gap_change: gap_pct - previous,
+        volume_ratio,
+    })
+}
+
+/// The highest scoring of `setups`, if it reaches `min_score`. Ties go to
+/// the symbol that sorts first, so the pick doesn't depend on scan order.
+pub fn rank(setups: impl IntoIterator<Item = Setup>, min_score: f64) -> Option<Setup> {
+    setups
+        .into_iter()
+        .filter(|s| s.score().is_finite() && s.score() >= min_score)
+        .max_by(|a, b| {
+            a.score()
+                .total_cmp(&b.score())
+                .then_with(|| b.symbol.cmp(&a.symbol))
+        })
+}
@@ -0,0 +1,110 @@
+//! Command usage counters.
+//!
+//! Every invocation bumps a per-day Redis hash of command name to count,
+//! `{prefix}:usage:{scope}:{yyyymmdd}`, kept apart per server and expiring
+//! after [`RETENTION_DAYS`]. Members who opted in are also counted per
+//! user, in a sibling hash. The functions here turn a window of those days
+//! into what `/stock stats` shows.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::Scope;
+
+/// Days a usage hash is kept.
+pub const RETENTION_DAYS: i64 = 90;
+
+/// Users listed in the top-users ranking.
+pub const TOP_USERS: usize = 5;
+
+/// Key of `scope`'s command counts for `date`, before the store's prefix.
+pub fn day_key(scope: Scope, date: NaiveDate) -> String {
+    format!("usage:{scope}:{}", date.format("%Y%m%d"))
+}
+
+/// Key of `scope`'s per-user counts for `date`, before the store's prefix.
+pub fn users_key(scope: Scope, date: NaiveDate) -> String {
+    format!("{}:users", day_key(scope, date))
+}
+
+/// The `days` dates ending with `today`, oldest first. Capped at
+/// [`RETENTION_DAYS`], since nothing older is kept.
+pub fn window(today: NaiveDate, days: u32) -> Vec<NaiveDate> {
+    let days = i64::from(days).clamp(1, RETENTION_DAYS);
+    (0..days)
+        .rev()
+        .map(|back| today - Duration::days(back))
+        .collect()
+}
+
+/// One day's counters as read back from Redis.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DayUsage {
+    pub date: NaiveDate,
+    /// Invocations by qualified command name, e.g. `stock watch`.
+    pub commands: HashMap<String, u64>,
+    /// Invocations by user, only those still opted in.
+    pub users: HashMap<u64, u64>,
+}
+
+impl DayUsage {
+    pub fn total(&self) -> u64 {
+        self.commands.values().sum()
+    }
+}
+
+/// Usage over a window of days.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSummary {
+    pub total: u64,
+    /// Every command used, most used first, ties by name.
+    pub commands: Vec<(String, u64)>,
+    /// Up to [`TOP_USERS`] opted-in users, most active first, ties by id.
+    pub top_users: Vec<(u64, u64)>,
+    /// The day with the most invocations, the earliest on a tie. None when
+    /// nothing was used.
+    pub busiest_day: Option<(NaiveDate, u64)>,
+}
+
+/// Add up `days`.
+pub fn summarize(days: &[DayUsage]) -> UsageSummary {
+    let mut commands: HashMap<&str, u64> = HashMap::new();
+    let mut users: HashMap<u64, u64> = HashMap::new();
+    let mut busiest_day: Option<(NaiveDate, u64)> = None;
+
+    for day in days {
+        for (command, count) in &day.commands {
+            *commands.entry(command).or_default() += count;
+        }
+        for (user, count) in &day.users {
+            *users.entry(*user).or_default() += count;
+        }
+
+        let total = day.total();
+        let busier = match busiest_day {
+            Some((date, best)) => total > best || (total == best && day.date < date),
+            None => total > 0,
+        };
+        if busier {
+            busiest_day = Some((day.date, total));
+        }
+    }
+
+    let mut commands: Vec<(String, u64)> = commands
+        .into_iter()
+        .map(|(command, count)| (command.to_string(), count))
+        .collect();
+    commands.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut top_users: Vec<(u64, u64)> = users.into_iter().collect();
+    top_users.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_users.truncate(TOP_USERS);
+
+    UsageSummary {
+        total: commands.iter().map(|(_, count)| count).sum(),
+        commands,
+        top_users,
+        busiest_day,
+    }
+}
@@ -0,0 +1,262 @@
+//! How a guild turns bars into signals: which pair of moving averages has
+//! to cross, and what has to hold for a crossover to count.
+//!
+//! The default is the original CDC setup, EMA 12 over EMA 26 with no
+//! filters, so guilds that never pick a strategy see no change.
+
+use std::fmt;
+
+use anyhow::{Error, ensure};
+use serde::{Deserialize, Serialize};
+use ta::Next;
+use ta::indicators::{ExponentialMovingAverage, SimpleMovingAverage};
+use tracing::{debug, instrument};
+
+use crate::{
+    Bar,
+    indicators::{
+        adx,
+        cdc::{Signal, crossover},
+    },
+};
+
+/// Kind of moving average the fast and slow lines are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaKind {
+    #[default]
+    Ema,
+    Sma,
+}
+
+impl MaKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MaKind::Ema => "EMA",
+            MaKind::Sma => "SMA",
+        }
+    }
+
+    /// The average over `period` bars, aligned with `closes`.
+    pub fn calculate(self, closes: &[f64], period: usize) -> Vec<f64> {
+        match self {
+            MaKind::Ema => {
+                let Ok(mut ma) = ExponentialMovingAverage::new(period) else {
+                    return vec![f64::NAN; closes.len()];
+                };
+                closes.iter().map(|&x| ma.next(x)).collect()
+            }
+            MaKind::Sma => {
+                let Ok(mut ma) = SimpleMovingAverage::new(period) else {
+                    return vec![f64::NAN; closes.len()];
+                };
+                closes.iter().map(|&x| ma.next(x)).collect()
+            }
+        }
+    }
+}
+
+/// Crossovers only count while the trend is strong enough: ADX over
+/// `period` bars at or above `min` on the signal bar.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdxFilter {
+    pub period: usize,
+    pub min: f64,
+}
+
+impl Default for AdxFilter {
+    fn default() -> Self {
+        Self {
+            period: adx::DEFAULT_PERIOD,
+            min: adx::TRENDING,
+        }
+    }
+}
+
+/// ADX on the signal bar against a strategy's minimum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendCheck {
+    pub strength: f64,
+    pub min: f64,
+}
+
+impl TrendCheck {
+    /// Whether the trend is strong enough for a crossover to count. ADX
+    /// that hasn't warmed up isn't.
+    pub fn passes(&self) -> bool {
+        self.strength >= self.min
+    }
+}
+
+/// What [`Strategy::assess`] made of the latest bar, filter by filter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Assessment {
+    /// The crossover signal before any filter.
+    pub raw: Signal,
+    /// The ADX check, when the strategy has one.
+    pub trend: Option<TrendCheck>,
+    /// `raw` after the ADX filter.
+    pub signal: Signal,
+}
+
+/// A guild's signal strategy, persisted by [`crate::SymbolStore`].
+///
+/// Missing fields take their defaults so stored documents keep
+/// deserializing as filters are added.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Strategy {
+    pub ma: MaKind,
+    pub fast: usize,
+    pub slow: usize,
+    /// No trend-strength filter when unset.
+    pub adx: Option<AdxFilter>,
+}
+
+/// EMA 12/26 without filters, the CDC setup every scan used before
+/// strategies were configurable.
+impl Default for Strategy {
+    fn default() -> Self {
+        Self {
+            ma: MaKind::Ema,
+            fast: 12,
+            slow: 26,
+            adx: None,
+        }
+    }
+}
+
+/// `EMA 12/26`, with the filter appended when there is one:
+/// `SMA 5/20, ADX 14 ≥ 25`.
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{}", self.ma.as_str(), self.fast, self.slow)?;
+        if let Some(filter) = self.adx {
+            write!(f, ", ADX {} ≥ {}", filter.period, filter.min)?;
+        }
+        Ok(())
+    }
+}
+
+impl Strategy {
+    /// Check the periods make sense on scans reading `bars` bars, as
+    /// [`ScanFrame::expected_bars`] counts them: the fast average shorter
+    /// than the slow one, the slow one short enough to cross within them,
+    /// an ADX that warms up within them, and an ADX minimum between 0 and
+    /// 100. A server scanning several frames passes the fewest.
+    ///
+    /// [`ScanFrame::expected_bars`]: crate::scan::ScanFrame::expected_bars
+    pub fn validate(&self, bars: usize) -> Result<(), Error> {
+        ensure!(self.fast >= 1, "the fast period must be at least 1");
+        ensure!(
+            self.fast < self.slow,
+            "the fast period ({}) must be shorter than the slow one ({})",
+            self.fast,
+            self.slow
+        );
+        // a crossover needs the bar before the latest one averaged too
+        ensure!(
+            self.slow < bars,
+            "the slow period ({}) can be at most {}: scans read about {bars} bars",
+            self.slow,
+            bars.saturating_sub(1)
+        );
+        if let Some(filter) = self.adx {
+            // both of Wilder's smoothings have to warm up
+            ensure!(
+                filter.period >= 2 && filter.period * 2 <= bars,
+                "the ADX period ({}) must be between 2 and {}: scans read about {bars} bars",
+                filter.period,
+                bars / 2
+            );
+            ensure!(
+                (0.0..=100.0).contains(&filter.min),
+                "the ADX minimum ({}) must be between 0 and 100",
+                filter.min
+            );
+        }
+        Ok(())
+    }
+
+    /// Legend names for the fast and slow lines, like `SMA5` and `SMA20`.
+    pub fn line_names(&self) -> (String, String) {
+        let kind = self.ma.as_str();
+        (
+            format!("{kind}{}", self.fast),
+            format!("{kind}{}", self.slow),
+        )
+    }
+
+    /// The fast and slow averages of `closes`.
+    pub fn averages(&self, closes: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        (
+            self.ma.calculate(closes, self.fast),
+            self.ma.calculate(closes, self.slow),
+        )
+    }
+
+    /// Signal for the latest of `bars`, plus the fast and slow averages.
+    /// The crossover works like [`crate::indicators::cdc::calculate`] with
+    /// this strategy's averages; the ADX filter, when set, then turns a
+    /// crossover on a weak trend into the zone it crossed into.
+    pub fn evaluate(&self, bars: &[Bar], band_pct: f64) -> (Signal, Vec<f64>, Vec<f64>) {
+        let (assessment, fast, slow) = self.assess(bars, band_pct);
+        (assessment.signal, fast, slow)
+    }
+
+    /// [`Self::evaluate`], keeping the crossover before the ADX filter and
+    /// the ADX it was held to.
+    #[instrument(name = "strategy_evaluate", skip(self, bars), fields(strategy = %self, n = bars.len(), band_pct))]
+    pub fn assess(&self, bars: &[Bar], band_pct: f64) -> (Assessment, Vec<f64>, Vec<f64>) {
+        let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+        let (fast, slow) = self.averages(&closes);
+        let signal = crossover(&fast, &slow, band_pct);
+
+        let Some(filter) = self.adx else {
+            let assessment = Assessment {
+                raw: signal,
+                trend: None,
+                signal,
+            };
+            return (assessment, fast, slow);
+        };
+        let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+        let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+        let strength = adx::calculate(&highs, &lows, &closes, filter.period)
+            .last()
+            .copied()
+            .unwrap_or(f64::NAN);
+        let filtered = require_trend(signal, strength, filter.min);
+        if filtered != signal {
+            debug!(
+                ?signal,
+                strength,
+                min = filter.min,
+                "trend too weak, dropping crossover"
+            );
+        }
+        let assessment = Assessment {
+            raw: signal,
+            trend: Some(TrendCheck {
+                strength,
+                min: filter.min,
+            }),
+            signal: filtered,
+        };
+        (assessment, fast, slow)
+    }
+}
+
+/// A crossover, kept only when `strength` is at least `min`. Weaker ones,
+/// or ones before ADX has warmed up, fall back to the zone they crossed
+/// into; anything that isn't a crossover passes through unchanged.
+pub fn require_trend(signal: Signal, strength: f64, min: f64) -> Signal {
+    if strength >= min {
+        return signal;
+    }
+    match signal {
+        Signal::Buy => Signal::BullishZone,
+        Signal::Sell => Signal::BearishZone,
+        other => other,
+    }
+}
@@ -1,14 +1,354 @@
-use std::time::Duration;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Error;
-use fred::{prelude::*, socket2::TcpKeepalive};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use fred::{prelude::*, socket2::TcpKeepalive, types::InfoKind};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
 
 use tracing::{debug, error, info, instrument, warn};
 
+use crate::{
+    GuildSettings, INTRADAY_TIMEFRAMES, PostedSignal, SymbolMeta, Timeframe, UserPrefs,
+    alert::{self, Alert},
+    circuit::{CircuitBreaker, CircuitState, DEFAULT_COOL_DOWN, DEFAULT_FAILURE_THRESHOLD},
+    fuzzy,
+    indicators::cdc::Signal,
+    report::{REDIS_RETENTION_DAYS, RunRecord},
+    scan::ScanReading,
+    stats::{self, DayUsage},
+    strategy::Strategy,
+};
+
+/// How long a posted batch of a scheduled run is remembered. Covers a
+/// same-day re-run with room to spare.
+const POSTED_BATCH_TTL: Duration = Duration::from_secs(3 * 86_400);
+
+/// How long a daily run's lock holds off another run of the same day.
+const DAILY_LOCK_TTL: Duration = Duration::from_secs(86_400);
+
+/// How long a pending add waits for its confirmation.
+const PENDING_TTL: Duration = Duration::from_secs(300);
+
+/// How long a pending delete waits for its confirmation when
+/// `PENDING_DELETE_TTL` isn't set.
+pub const DEFAULT_PENDING_DELETE_TTL: Duration = Duration::from_secs(300);
+
+/// How long a `/stock browse` message keeps its place after the last
+/// interaction with it.
+const BROWSE_STATE_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Writes back what an alert run made of one alert, only while the alert
+/// still reads as the run found it. KEYS: the user's alerts, the alert
+/// users. ARGV: user id, alert id, the alert as read, its new state or
+/// empty to delete it.
+const SETTLE_ALERT: &str = r"
+if redis.call('HGET', KEYS[1], ARGV[2]) ~= ARGV[3] then return 0 end
+if ARGV[4] == '' then
+  redis.call('HDEL', KEYS[1], ARGV[2])
+  if redis.call('HLEN', KEYS[1]) == 0 then redis.call('SREM', KEYS[2], ARGV[1]) end
+else
+  redis.call('HSET', KEYS[1], ARGV[2], ARGV[4])
+end
+return 1
+";
+
+/// Deletes alerts and drops their owner from the alert users once none
+/// are left, in one step so an alert added meanwhile keeps its owner
+/// listed. KEYS: the user's alerts, the alert users. ARGV: user id, then
+/// the alert ids.
+const REMOVE_ALERTS: &str = r"
+local removed = 0
+if #ARGV > 1 then removed = redis.call('HDEL', KEYS[1], unpack(ARGV, 2)) end
+if redis.call('HLEN', KEYS[1]) == 0 then redis.call('SREM', KEYS[2], ARGV[1]) end
+return removed
+";
+
+/// Whose watchlist an operation applies to. Servers each get their own list;
+/// DMs fall back to the invoking user's personal list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Guild(u64),
+    User(u64),
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scope::Guild(id) => write!(f, "guild:{id}"),
+            Scope::User(id) => write!(f, "user:{id}"),
+        }
+    }
+}
+
+/// Parse the [`Display`](std::fmt::Display) form, `guild:{id}` or
+/// `user:{id}`.
+impl std::str::FromStr for Scope {
+    type Err = Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let parsed = match raw.trim().split_once(':') {
+            Some(("guild", id)) => id.parse().ok().map(Scope::Guild),
+            Some(("user", id)) => id.parse().ok().map(Scope::User),
+            _ => None,
+        };
+        parsed.ok_or_else(|| anyhow::anyhow!("scope must be guild:<id> or user:<id>, got {raw:?}"))
+    }
+}
+
+/// Key counts and footprint of everything under the store's prefix, for
+/// operators keeping an eye on Redis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StoreStats {
+    /// Keys under the prefix.
+    pub keys: usize,
+    /// Watchlists, one per server or member with a personal list.
+    pub watchlists: usize,
+    /// Symbols across every watchlist.
+    pub symbols: usize,
+    /// Delete and add confirmations waiting on a button press.
+    pub pending_sessions: usize,
+    /// Price alerts across every member.
+    pub alerts: usize,
+    /// Bytes Redis reports for those keys. None when `MEMORY USAGE` isn't
+    /// available, as on some managed Redis plans.
+    pub memory_bytes: Option<u64>,
+}
+
+/// Keys under the store's prefix by category, with Redis' own memory
+/// figure, for the owners' maintenance commands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyStats {
+    /// Keys per [`key_category`], sorted by category.
+    pub categories: BTreeMap<String, usize>,
+    /// `used_memory` from `INFO memory`. That covers the whole Redis
+    /// server, not just this prefix. None when INFO isn't allowed.
+    pub used_memory: Option<u64>,
+}
+
+impl KeyStats {
+    pub fn keys(&self) -> usize {
+        self.categories.values().sum()
+    }
+}
+
+/// What kind of record `key`, with the store's prefix already stripped,
+/// holds. Per-scope keys like `guild:1:watchlist` and `user:5:alerts` go by
+/// their record (`watchlist`, `alerts`), the rest by their first segment
+/// (`pending_del`, `runs`, `usage`).
+pub fn key_category(key: &str) -> &str {
+    let mut segments = key.split(':');
+    let first = segments.next().unwrap_or_default();
+    match (first, segments.next(), segments.next()) {
+        ("guild" | "user", Some(_), Some(record)) => record,
+        _ => first,
+    }
+}
+
+/// Sorted-set score of a mute lasting until `until`: its Unix time, or
+/// infinity for one that lasts until lifted.
+pub fn mute_score(until: Option<DateTime<Utc>>) -> f64 {
+    until.map_or(f64::INFINITY, |until| until.timestamp() as f64)
+}
+
+/// When a mute scored `score` ends. None when it doesn't.
+pub fn mute_until(score: f64) -> Option<DateTime<Utc>> {
+    score
+        .is_finite()
+        .then(|| DateTime::from_timestamp(score as i64, 0))
+        .flatten()
+}
+
+/// Whether a mute scored `score` still holds at `now`. Mutes lift by
+/// themselves once their expiry passes.
+pub fn mute_active(score: f64, now: DateTime<Utc>) -> bool {
+    score > now.timestamp() as f64
+}
+
+/// `used_memory` out of an `INFO memory` reply, in bytes.
+pub fn used_memory(info: &str) -> Option<u64> {
+    info.lines()
+        .find_map(|line| line.trim().strip_prefix("used_memory:"))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// What a watchlist browser message is showing: a page of the list, or one
+/// symbol's detail view opened from that page.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrowseState {
+    pub page: usize,
+    #[serde(default)]
+    pub symbol: Option<String>,
+}
+
+/// An alert as an alert run read it, so [`SymbolStore::settle_alert`] can
+/// tell whether its owner changed or deleted it since.
+#[derive(Debug, Clone)]
+pub struct StoredAlert {
+    pub alert: Alert,
+    raw: String,
+}
+
+/// What [`SymbolStore::clean`] changed, or would change, in a watchlist.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchlistCleanup {
+    /// The watchlist afterwards, sorted.
+    pub symbols: Vec<String>,
+    /// Members stored in a non-normalized form, with what they became.
+    pub renamed: Vec<(String, String)>,
+    /// Members dropped as duplicates of another, or for being blank.
+    pub removed: Vec<String>,
+}
+
+impl WatchlistCleanup {
+    /// Work out the cleanup of a watchlist holding `members`. Where several
+    /// members normalize the same, an already normalized one is kept;
+    /// otherwise the first in sort order is renamed and the rest removed.
+    pub fn plan(members: &[String]) -> Self {
+        let mut groups: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+        for member in members {
+            groups
+                .entry(SymbolStore::normalize(member))
+                .or_default()
+                .push(member);
+        }
+
+        let mut cleanup = Self::default();
+        for (symbol, mut stored) in groups {
+            stored.sort();
+            if symbol.is_empty() {
+                cleanup.removed.extend(stored.into_iter().cloned());
+                continue;
+            }
+            let keep = stored.iter().position(|s| **s == symbol).unwrap_or(0);
+            let kept = stored.remove(keep);
+            if *kept != symbol {
+                cleanup.renamed.push((kept.clone(), symbol.clone()));
+            }
+            cleanup.removed.extend(stored.into_iter().cloned());
+            cleanup.symbols.push(symbol);
+        }
+        cleanup.removed.sort();
+        cleanup
+    }
+
+    /// Whether the watchlist was already clean.
+    pub fn is_clean(&self) -> bool {
+        self.renamed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// What deleting one symbol from a watchlist takes with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolImpact {
+    pub symbol: String,
+    /// Whether the symbol is on the watchlist at all.
+    pub watched: bool,
+    /// Ids of the owner's alerts on the symbol. Only a personal watchlist
+    /// has an owner: deleting from a server's list leaves members' own
+    /// alerts alone.
+    pub alert_ids: Vec<String>,
+    /// Stored metadata: when it was added, any quiet period.
+    pub meta: Option<SymbolMeta>,
+    /// Whether a last scan reading is stored.
+    pub has_reading: bool,
+}
+
+impl SymbolImpact {
+    /// The impact of deleting each of `symbols` from a watchlist holding
+    /// `members`, given its stored `meta` and `readings` and the owner's
+    /// `alerts`. Symbols are normalized and kept in the order given.
+    pub fn collect(
+        symbols: &[String],
+        members: &HashSet<String>,
+        meta: &HashMap<String, SymbolMeta>,
+        readings: &HashSet<String>,
+        alerts: &[Alert],
+    ) -> Vec<Self> {
+        symbols
+            .iter()
+            .map(|symbol| {
+                let symbol = SymbolStore::normalize(symbol);
+                Self {
+                    watched: members.contains(&symbol),
+                    alert_ids: alerts
+                        .iter()
+                        .filter(|a| SymbolStore::normalize(&a.symbol) == symbol)
+                        .map(|a| a.id.clone())
+                        .collect(),
+                    meta: meta.get(&symbol).cloned(),
+                    has_reading: readings.contains(&symbol),
+                    symbol,
+                }
+            })
+            .collect()
+    }
+
+    /// Records deleted along with the watchlist entry.
+    pub fn records(&self) -> usize {
+        self.alert_ids.len() + usize::from(self.meta.is_some()) + usize::from(self.has_reading)
+    }
+}
+
+/// How a [`SymbolStore::remove_cascade`] went.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeleteOutcome {
+    /// Symbols no longer on the watchlist, in the order asked. Includes
+    /// any that were already gone.
+    pub removed: Vec<String>,
+    /// Associated records deleted with them.
+    pub cleaned: usize,
+    /// Symbols whose delete failed, with why. Nothing of theirs was
+    /// deleted.
+    pub failed: Vec<(String, String)>,
+}
+
+impl DeleteOutcome {
+    /// Add how deleting `impact`'s symbol went.
+    pub fn record(&mut self, impact: &SymbolImpact, result: Result<(), Error>) {
+        match result {
+            Ok(()) => {
+                self.removed.push(impact.symbol.clone());
+                self.cleaned += impact.records();
+            }
+            Err(e) => self.failed.push((impact.symbol.clone(), format!("{e:#}"))),
+        }
+    }
+}
+
+/// Which circuit a store call goes through. Reads and writes trip
+/// separately, so a replica that still answers reads keeps `/stock list`
+/// working while writes fail fast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Read,
+    Write,
+}
+
+/// State of the store's circuits, for a health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreCircuits {
+    pub read: CircuitState,
+    pub write: CircuitState,
+}
+
+#[derive(Debug)]
+struct Breakers {
+    read: CircuitBreaker,
+    write: CircuitBreaker,
+}
+
 #[derive(Clone)]
 pub struct SymbolStore {
     client: Client,
     key_prefix: String,
+    breakers: Arc<Breakers>,
+    pending_delete_ttl: Duration,
 }
 
 impl SymbolStore {
@@ -60,11 +400,35 @@ impl SymbolStore {
         client.init().await?;
         info!("redis connected");
 
-        Ok(Self { client, key_prefix })
+        let breakers = Arc::new(Breakers {
+            read: CircuitBreaker::new("redis_read", DEFAULT_FAILURE_THRESHOLD, DEFAULT_COOL_DOWN),
+            write: CircuitBreaker::new("redis_write", DEFAULT_FAILURE_THRESHOLD, DEFAULT_COOL_DOWN),
+        });
+        Ok(Self {
+            client,
+            key_prefix,
+            breakers,
+            pending_delete_ttl: DEFAULT_PENDING_DELETE_TTL,
+        })
+    }
+
+    /// How long a pending delete waits for its confirmation. Zero is
+    /// ignored, as a request that expires at once could never be confirmed.
+    pub fn with_pending_delete_ttl(mut self, ttl: Duration) -> Self {
+        if !ttl.is_zero() {
+            self.pending_delete_ttl = ttl;
+        }
+        self
+    }
+
+    /// How long a pending delete waits for its confirmation.
+    pub fn pending_delete_window(&self) -> Duration {
+        self.pending_delete_ttl
     }
 
     /// Create a new SymbolStore from environment variables.
-    /// Expects REDIS_URL and REDIS_KEY_PREFIX to be set.
+    /// Expects REDIS_URL and REDIS_KEY_PREFIX to be set. PENDING_DELETE_TTL
+    /// is how many seconds a delete waits for its confirmation.
     #[instrument(name = "symbol_store_from_env", skip_all)]
     pub async fn from_env() -> Result<Self, Error> {
         use std::env;
@@ -74,61 +438,1000 @@ impl SymbolStore {
         let key_prefix = env::var("REDIS_KEY_PREFIX")
             .map_err(|_| Error::msg("REDIS_KEY_PREFIX environment variable not set"))?;
 
-        info!(key_prefix = %key_prefix, "creating SymbolStore from env");
-        Self::new(&redis_url, key_prefix).await
+        let pending_delete_ttl = env::var("PENDING_DELETE_TTL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_PENDING_DELETE_TTL);
+
+        info!(key_prefix = %key_prefix, ?pending_delete_ttl, "creating SymbolStore from env");
+        Ok(Self::new(&redis_url, key_prefix)
+            .await?
+            .with_pending_delete_ttl(pending_delete_ttl))
+    }
+
+    /// Where the read and write circuits are at.
+    pub fn circuit_state(&self) -> StoreCircuits {
+        StoreCircuits {
+            read: self.breakers.read.state(),
+            write: self.breakers.write.state(),
+        }
+    }
+
+    /// Run `op` through the circuit for `class`. Fails fast with
+    /// [`StoreUnavailable`](crate::circuit::StoreUnavailable) while that
+    /// circuit is open.
+    async fn guarded<T>(
+        &self,
+        class: Op,
+        op: impl Future<Output = Result<T, Error>>,
+    ) -> Result<T, Error> {
+        let breaker = match class {
+            Op::Read => &self.breakers.read,
+            Op::Write => &self.breakers.write,
+        };
+        breaker.call(op, is_outage).await
     }
 
     fn normalize(symbol: &str) -> String {
         symbol.trim().to_uppercase()
     }
 
-    fn watchlist_key(&self) -> String {
+    fn watchlist_key(&self, scope: Scope) -> String {
+        format!("{}:{}:watchlist", self.key_prefix, scope)
+    }
+
+    fn meta_key(&self, scope: Scope) -> String {
+        format!("{}:{}:meta", self.key_prefix, scope)
+    }
+
+    fn muted_key(&self, scope: Scope) -> String {
+        format!("{}:{}:muted", self.key_prefix, scope)
+    }
+
+    fn last_signal_key(&self, scope: Scope) -> String {
+        format!("{}:{}:last_signal", self.key_prefix, scope)
+    }
+
+    fn posted_key(&self, scope: Scope) -> String {
+        format!("{}:{}:posted", self.key_prefix, scope)
+    }
+
+    /// Posted hits at `timeframe`, daily ones under the unqualified key
+    /// like [`Self::last_signal_key_at`].
+    fn posted_key_at(&self, scope: Scope, timeframe: Timeframe) -> String {
+        match timeframe {
+            Timeframe::Day1 => self.posted_key(scope),
+            _ => format!(
+                "{}:{}:posted:{}",
+                self.key_prefix,
+                scope,
+                timeframe.as_str()
+            ),
+        }
+    }
+
+    fn entry_key(&self, scope: Scope) -> String {
+        format!("{}:{}:entry", self.key_prefix, scope)
+    }
+
+    /// Last readings at `timeframe`. Daily ones keep the unqualified key
+    /// they had before intraday scans, so the two never overwrite each
+    /// other.
+    fn last_signal_key_at(&self, scope: Scope, timeframe: Timeframe) -> String {
+        match timeframe {
+            Timeframe::Day1 => self.last_signal_key(scope),
+            _ => format!(
+                "{}:{}:last_signal:{}",
+                self.key_prefix,
+                scope,
+                timeframe.as_str()
+            ),
+        }
+    }
+
+    fn intraday_signal_keys(&self, scope: Scope) -> Vec<String> {
+        INTRADAY_TIMEFRAMES
+            .iter()
+            .map(|tf| self.last_signal_key_at(scope, *tf))
+            .collect()
+    }
+
+    /// Pre-scoping global watchlist, kept only so it can be adopted.
+    fn legacy_watchlist_key(&self) -> String {
         format!("{}:watchlist", self.key_prefix)
     }
 
+    fn last_daily_run_key(&self) -> String {
+        format!("{}:daily:last_run", self.key_prefix)
+    }
+
+    fn daily_lock_key(&self, date: NaiveDate) -> String {
+        format!("{}:daily_lock:{}", self.key_prefix, date.format("%Y-%m-%d"))
+    }
+
+    fn guilds_key(&self) -> String {
+        format!("{}:guilds", self.key_prefix)
+    }
+
     fn pending_del_key(&self, request_id: String) -> String {
         format!("{}:pending_del:{}", self.key_prefix, request_id)
     }
 
+    fn pending_add_key(&self, request_id: String) -> String {
+        format!("{}:pending_add:{}", self.key_prefix, request_id)
+    }
+
+    fn browse_key(&self, message_id: u64) -> String {
+        format!("{}:browse:{message_id}", self.key_prefix)
+    }
+
+    fn aliases_key(&self, guild_id: u64) -> String {
+        format!("{}:aliases:{guild_id}", self.key_prefix)
+    }
+
+    fn cooldown_key(&self, scope: &str, user_id: u64) -> String {
+        format!("{}:cooldown:{}:{}", self.key_prefix, scope, user_id)
+    }
+
+    fn guild_settings_key(&self, guild_id: u64) -> String {
+        format!("{}:guild:{}:settings", self.key_prefix, guild_id)
+    }
+
+    fn guild_strategy_key(&self, guild_id: u64) -> String {
+        format!("{}:guild:{}:strategy", self.key_prefix, guild_id)
+    }
+
+    fn user_prefs_key(&self, user_id: u64) -> String {
+        format!("{}:user:{}:prefs", self.key_prefix, user_id)
+    }
+
+    fn graph_timeframes_key(&self, user_id: u64) -> String {
+        format!("{}:user:{}:graph_timeframes", self.key_prefix, user_id)
+    }
+
+    fn run_key(&self, date: NaiveDate) -> String {
+        format!("{}:runs:{}", self.key_prefix, date.format("%Y-%m-%d"))
+    }
+
+    fn posted_batch_key(&self, key: &str) -> String {
+        format!("{}:posted:{key}", self.key_prefix)
+    }
+
+    fn runs_index_key(&self) -> String {
+        format!("{}:runs", self.key_prefix)
+    }
+
+    fn alerts_key(&self, user_id: u64) -> String {
+        format!("{}:user:{}:alerts", self.key_prefix, user_id)
+    }
+
+    fn alert_users_key(&self) -> String {
+        format!("{}:alert_users", self.key_prefix)
+    }
+
+    fn digest_users_key(&self) -> String {
+        format!("{}:digest_users", self.key_prefix)
+    }
+
+    fn stats_users_key(&self) -> String {
+        format!("{}:stats_users", self.key_prefix)
+    }
+
+    fn usage_key(&self, scope: Scope, date: NaiveDate) -> String {
+        format!("{}:{}", self.key_prefix, stats::day_key(scope, date))
+    }
+
+    fn usage_users_key(&self, scope: Scope, date: NaiveDate) -> String {
+        format!("{}:{}", self.key_prefix, stats::users_key(scope, date))
+    }
+
     /// Add a stock symbol
     /// Returns true if it was newly added
-    #[instrument(name = "symbol_store_add", skip(self), fields(symbol = %symbol))]
-    pub async fn add(&self, symbol: &str) -> Result<bool, Error> {
-        let normalized = Self::normalize(symbol);
-        let added: i64 = self.client.sadd(self.watchlist_key(), normalized).await?;
-        debug!(added, "sadd done");
-        Ok(added == 1)
+    #[instrument(name = "symbol_store_add", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn add(&self, scope: Scope, symbol: &str) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let normalized = Self::normalize(symbol);
+            let added: i64 = self
+                .client
+                .sadd(self.watchlist_key(scope), normalized)
+                .await?;
+            debug!(added, "sadd done");
+            Ok(added == 1)
+        })
+        .await
+    }
+
+    /// Add several stock symbols
+    /// Returns the ones that were newly added, in the order given
+    #[instrument(name = "symbol_store_add_many", skip(self, symbols), fields(%scope, symbol_count = symbols.len()))]
+    pub async fn add_many(&self, scope: Scope, symbols: &[String]) -> Result<Vec<String>, Error> {
+        let mut added = Vec::new();
+        for symbol in symbols {
+            if self.add(scope, symbol).await? {
+                added.push(Self::normalize(symbol));
+            }
+        }
+        debug!(added = added.len(), "add_many done");
+        Ok(added)
     }
 
     /// Remove a stock symbol
     /// Returns true if it existed
-    #[instrument(name = "symbol_store_remove", skip(self), fields(symbol = %symbol))]
-    pub async fn remove(&self, symbol: &str) -> Result<bool, Error> {
-        let normalized = Self::normalize(symbol);
-        let removed: i64 = self.client.srem(self.watchlist_key(), normalized).await?;
-        debug!(removed, "srem done");
-        Ok(removed == 1)
+    #[instrument(name = "symbol_store_remove", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn remove(&self, scope: Scope, symbol: &str) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let normalized = Self::normalize(symbol);
+            let removed: i64 = self
+                .client
+                .srem(self.watchlist_key(scope), normalized.clone())
+                .await?;
+            let _: i64 = self
+                .client
+                .hdel(self.meta_key(scope), normalized.clone())
+                .await?;
+            let _: i64 = self
+                .client
+                .hdel(self.last_signal_key(scope), normalized.clone())
+                .await?;
+            for key in self.intraday_signal_keys(scope) {
+                let _: i64 = self.client.hdel(key, normalized.clone()).await?;
+            }
+            let _: i64 = self
+                .client
+                .zrem(self.muted_key(scope), normalized.clone())
+                .await?;
+            let _: i64 = self
+                .client
+                .hdel(self.entry_key(scope), normalized.clone())
+                .await?;
+            debug!(removed, "srem done");
+            Ok(removed == 1)
+        })
+        .await
+    }
+
+    /// What deleting each of `symbols` from `scope` would take with it. The
+    /// reads go out in one pipeline. Alerts are counted only on a personal
+    /// watchlist, where they belong to its owner.
+    #[instrument(name = "symbol_store_impact_report", skip(self, symbols), fields(%scope, symbol_count = symbols.len()))]
+    pub async fn impact_report(
+        &self,
+        scope: Scope,
+        symbols: &[String],
+    ) -> Result<Vec<SymbolImpact>, Error> {
+        self.guarded(Op::Read, async {
+            let owner = match scope {
+                Scope::User(user_id) => Some(user_id),
+                Scope::Guild(_) => None,
+            };
+            let pipeline = self.client.pipeline();
+            let _: () = pipeline.smembers(self.watchlist_key(scope)).await?;
+            let _: () = pipeline.hgetall(self.meta_key(scope)).await?;
+            let _: () = pipeline.hkeys(self.last_signal_key(scope)).await?;
+            type Reads = (
+                HashSet<String>,
+                HashMap<String, String>,
+                HashSet<String>,
+                HashMap<String, String>,
+            );
+            let (members, raw_meta, readings, raw_alerts): Reads = match owner {
+                Some(user_id) => {
+                    let _: () = pipeline.hgetall(self.alerts_key(user_id)).await?;
+                    pipeline.all().await?
+                }
+                None => {
+                    let (members, raw_meta, readings) = pipeline.all().await?;
+                    (members, raw_meta, readings, HashMap::new())
+                }
+            };
+            let alerts = Self::parse_alerts(raw_alerts);
+
+            // unreadable metadata is still a record the delete cleans up
+            let meta: HashMap<String, SymbolMeta> = raw_meta
+                .into_iter()
+                .map(|(symbol, raw)| {
+                    let meta = serde_json::from_str(&raw)
+                        .inspect_err(|e| warn!(%symbol, error = ?e, "unreadable symbol meta"))
+                        .unwrap_or_default();
+                    (symbol, meta)
+                })
+                .collect();
+
+            let impact = SymbolImpact::collect(symbols, &members, &meta, &readings, &alerts);
+            debug!(
+                records = impact.iter().map(SymbolImpact::records).sum::<usize>(),
+                "impact gathered"
+            );
+            Ok(impact)
+        })
+        .await
+    }
+
+    /// Delete each of `impact`'s symbols from `scope` along with its
+    /// metadata, last reading and alerts. Each symbol goes in one
+    /// transaction, so it is either gone with everything it listed or left
+    /// as it was; one failing doesn't stop the rest.
+    #[instrument(name = "symbol_store_remove_cascade", skip(self, impact), fields(%scope, symbol_count = impact.len()))]
+    pub async fn remove_cascade(&self, scope: Scope, impact: &[SymbolImpact]) -> DeleteOutcome {
+        let owner = match scope {
+            Scope::User(user_id) => Some(user_id),
+            Scope::Guild(_) => None,
+        };
+        let mut outcome = DeleteOutcome::default();
+        for symbol in impact {
+            let result = self
+                .guarded(Op::Write, self.remove_with_records(scope, owner, symbol))
+                .await;
+            if let Err(e) = &result {
+                error!(symbol = %symbol.symbol, error = ?e, "failed to delete symbol");
+            }
+            outcome.record(symbol, result);
+        }
+
+        if let Some(user_id) = owner
+            && impact.iter().any(|i| !i.alert_ids.is_empty())
+            && let Err(e) = self
+                .guarded(Op::Write, self.forget_alert_user_if_empty(user_id))
+                .await
+        {
+            warn!(user_id, error = ?e, "failed to update alert users");
+        }
+        info!(
+            removed = outcome.removed.len(),
+            cleaned = outcome.cleaned,
+            failed = outcome.failed.len(),
+            "cascade delete done"
+        );
+        outcome
+    }
+
+    async fn remove_with_records(
+        &self,
+        scope: Scope,
+        owner: Option<u64>,
+        impact: &SymbolImpact,
+    ) -> Result<(), Error> {
+        let trx = self.client.multi();
+        let _: () = trx
+            .srem(self.watchlist_key(scope), impact.symbol.clone())
+            .await?;
+        let _: () = trx
+            .hdel(self.meta_key(scope), impact.symbol.clone())
+            .await?;
+        let _: () = trx
+            .hdel(self.last_signal_key(scope), impact.symbol.clone())
+            .await?;
+        for key in self.intraday_signal_keys(scope) {
+            let _: () = trx.hdel(key, impact.symbol.clone()).await?;
+        }
+        let _: () = trx
+            .zrem(self.muted_key(scope), impact.symbol.clone())
+            .await?;
+        let _: () = trx
+            .hdel(self.entry_key(scope), impact.symbol.clone())
+            .await?;
+        if let Some(user_id) = owner
+            && !impact.alert_ids.is_empty()
+        {
+            let _: () = trx
+                .hdel(self.alerts_key(user_id), impact.alert_ids.clone())
+                .await?;
+        }
+        let results: Vec<i64> = trx.exec(true).await?;
+        let removed = results.first().is_some_and(|n| *n == 1);
+        debug!(symbol = %impact.symbol, removed, "cascade transaction done");
+        Ok(())
+    }
+
+    async fn forget_alert_user_if_empty(&self, user_id: u64) -> Result<(), Error> {
+        self.delete_alerts(user_id, &[]).await?;
+        Ok(())
+    }
+
+    async fn delete_alerts(&self, user_id: u64, ids: &[String]) -> Result<i64, Error> {
+        let mut args = vec![user_id.to_string()];
+        args.extend(ids.iter().cloned());
+        let removed: i64 = self
+            .client
+            .eval(
+                REMOVE_ALERTS,
+                vec![self.alerts_key(user_id), self.alert_users_key()],
+                args,
+            )
+            .await?;
+        Ok(removed)
+    }
+
+    /// Returns true if the symbol is watched in `scope`
+    #[instrument(name = "symbol_store_contains", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn contains(&self, scope: Scope, symbol: &str) -> Result<bool, Error> {
+        self.guarded(Op::Read, async {
+            let normalized = Self::normalize(symbol);
+            let found: bool = self
+                .client
+                .sismember(self.watchlist_key(scope), normalized)
+                .await?;
+            Ok(found)
+        })
+        .await
+    }
+
+    /// Get Symbol Metadata
+    /// Returns defaults when nothing has been stored for the symbol
+    #[instrument(name = "symbol_store_get_meta", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn get_meta(&self, scope: Scope, symbol: &str) -> Result<SymbolMeta, Error> {
+        self.guarded(Op::Read, async {
+            let raw: Option<String> = self
+                .client
+                .hget(self.meta_key(scope), Self::normalize(symbol))
+                .await?;
+            match raw {
+                Some(raw) => Ok(serde_json::from_str(&raw)?),
+                None => Ok(SymbolMeta::default()),
+            }
+        })
+        .await
+    }
+
+    /// Set Symbol Metadata
+    #[instrument(name = "symbol_store_set_meta", skip(self, meta), fields(%scope, symbol = %symbol))]
+    pub async fn set_meta(
+        &self,
+        scope: Scope,
+        symbol: &str,
+        meta: &SymbolMeta,
+    ) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(meta)?;
+            let _: i64 = self
+                .client
+                .hset(self.meta_key(scope), (Self::normalize(symbol), raw))
+                .await?;
+            debug!("symbol meta saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Metadata for every symbol in `scope` that has any stored
+    #[instrument(name = "symbol_store_list_meta", skip(self), fields(%scope))]
+    pub async fn list_meta(&self, scope: Scope) -> Result<HashMap<String, SymbolMeta>, Error> {
+        self.guarded(Op::Read, async {
+            let raw: HashMap<String, String> = self.client.hgetall(self.meta_key(scope)).await?;
+            let mut out = HashMap::with_capacity(raw.len());
+            for (symbol, raw) in raw {
+                match serde_json::from_str(&raw) {
+                    Ok(meta) => {
+                        out.insert(symbol, meta);
+                    }
+                    Err(e) => warn!(%symbol, error = ?e, "skipping unreadable symbol meta"),
+                }
+            }
+            debug!(count = out.len(), "hgetall done");
+            Ok(out)
+        })
+        .await
+    }
+
+    /// Demote the symbol's signals until `until`
+    #[instrument(name = "symbol_store_set_quiet", skip(self), fields(%scope, symbol = %symbol, until = %until))]
+    pub async fn set_quiet(
+        &self,
+        scope: Scope,
+        symbol: &str,
+        until: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let mut meta = self.get_meta(scope, symbol).await?;
+        meta.quiet_until = Some(until);
+        self.set_meta(scope, symbol, &meta).await
+    }
+
+    /// Replace the symbol's tags. `tags` are already normalized.
+    #[instrument(name = "symbol_store_set_tags", skip(self), fields(%scope, symbol = %symbol, ?tags))]
+    pub async fn set_tags(
+        &self,
+        scope: Scope,
+        symbol: &str,
+        tags: Vec<String>,
+    ) -> Result<(), Error> {
+        let mut meta = self.get_meta(scope, symbol).await?;
+        meta.tags = tags;
+        self.set_meta(scope, symbol, &meta).await
+    }
+
+    /// Replace the symbol's note, or clear it with None. `note` is already
+    /// normalized.
+    #[instrument(name = "symbol_store_set_note", skip(self, note), fields(%scope, symbol = %symbol))]
+    pub async fn set_note(
+        &self,
+        scope: Scope,
+        symbol: &str,
+        note: Option<String>,
+    ) -> Result<(), Error> {
+        let mut meta = self.get_meta(scope, symbol).await?;
+        meta.note = note;
+        self.set_meta(scope, symbol, &meta).await
+    }
+
+    /// Record when and at what price the symbol was put on the watchlist.
+    /// `price` is None when it couldn't be fetched; the next scan backfills it.
+    #[instrument(name = "symbol_store_record_added", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn record_added(
+        &self,
+        scope: Scope,
+        symbol: &str,
+        at: DateTime<Utc>,
+        price: Option<f64>,
+    ) -> Result<(), Error> {
+        let mut meta = self.get_meta(scope, symbol).await?;
+        meta.added_at = Some(at);
+        meta.added_price = price;
+        meta.added_price_approx = false;
+        self.set_meta(scope, symbol, &meta).await
+    }
+
+    /// End the symbol's quiet period early
+    #[instrument(name = "symbol_store_clear_quiet", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn clear_quiet(&self, scope: Scope, symbol: &str) -> Result<(), Error> {
+        let mut meta = self.get_meta(scope, symbol).await?;
+        meta.quiet_until = None;
+        self.set_meta(scope, symbol, &meta).await
+    }
+
+    /// Record the rough price `symbol` was bought at, replacing any before.
+    /// `price` is already checked by [`crate::position::valid_entry`].
+    #[instrument(name = "symbol_store_set_entry", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn set_entry(&self, scope: Scope, symbol: &str, price: f64) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let _: i64 = self
+                .client
+                .hset(self.entry_key(scope), (Self::normalize(symbol), price))
+                .await?;
+            debug!(price, "entry price saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Forget `symbol`'s entry price. Returns false if it had none.
+    #[instrument(name = "symbol_store_clear_entry", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn clear_entry(&self, scope: Scope, symbol: &str) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let removed: i64 = self
+                .client
+                .hdel(self.entry_key(scope), Self::normalize(symbol))
+                .await?;
+            Ok(removed == 1)
+        })
+        .await
+    }
+
+    /// `symbol`'s entry price, if one was recorded.
+    #[instrument(name = "symbol_store_get_entry", skip(self), fields(%scope, symbol = %symbol))]
+    pub async fn get_entry(&self, scope: Scope, symbol: &str) -> Result<Option<f64>, Error> {
+        self.guarded(Op::Read, async {
+            let price: Option<f64> = self
+                .client
+                .hget(self.entry_key(scope), Self::normalize(symbol))
+                .await?;
+            Ok(price)
+        })
+        .await
+    }
+
+    /// Entry prices of every symbol in `scope` that has one.
+    #[instrument(name = "symbol_store_list_entries", skip(self), fields(%scope))]
+    pub async fn list_entries(&self, scope: Scope) -> Result<HashMap<String, f64>, Error> {
+        self.guarded(Op::Read, async {
+            let entries: HashMap<String, f64> = self.client.hgetall(self.entry_key(scope)).await?;
+            debug!(count = entries.len(), "entry prices loaded");
+            Ok(entries)
+        })
+        .await
+    }
+
+    /// Leave `symbol` out of the scheduled scans until `until`, or until
+    /// unmuted when None. Muting again replaces the expiry.
+    #[instrument(name = "symbol_store_mute", skip(self), fields(%scope, ?until))]
+    pub async fn mute(
+        &self,
+        scope: Scope,
+        symbol: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let _: i64 = self
+                .client
+                .zadd(
+                    self.muted_key(scope),
+                    None,
+                    None,
+                    false,
+                    false,
+                    (mute_score(until), Self::normalize(symbol)),
+                )
+                .await?;
+            debug!("symbol muted");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Lift `symbol`'s mute. False when it wasn't muted, or its mute had
+    /// already lifted.
+    #[instrument(name = "symbol_store_unmute", skip(self), fields(%scope))]
+    pub async fn unmute(&self, scope: Scope, symbol: &str) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let key = self.muted_key(scope);
+            let symbol = Self::normalize(symbol);
+            let score: Option<f64> = self.client.zscore(&key, symbol.clone()).await?;
+            let _: i64 = self.client.zrem(&key, symbol).await?;
+            let was_muted = score.is_some_and(|score| mute_active(score, Utc::now()));
+            debug!(was_muted, "symbol unmuted");
+            Ok(was_muted)
+        })
+        .await
+    }
+
+    /// Whether `symbol` is muted now. A mute past its expiry has lifted.
+    #[instrument(name = "symbol_store_is_muted", skip(self), fields(%scope))]
+    pub async fn is_muted(&self, scope: Scope, symbol: &str) -> Result<bool, Error> {
+        self.guarded(Op::Read, async {
+            let score: Option<f64> = self
+                .client
+                .zscore(self.muted_key(scope), Self::normalize(symbol))
+                .await?;
+            Ok(score.is_some_and(|score| mute_active(score, Utc::now())))
+        })
+        .await
+    }
+
+    /// Symbols muted now, each with its expiry, None for indefinite. Expired
+    /// mutes are dropped on the way.
+    #[instrument(name = "symbol_store_list_muted", skip(self), fields(%scope))]
+    pub async fn list_muted(
+        &self,
+        scope: Scope,
+    ) -> Result<HashMap<String, Option<DateTime<Utc>>>, Error> {
+        self.guarded(Op::Write, async {
+            let key = self.muted_key(scope);
+            let now = Utc::now();
+            let expired: i64 = self
+                .client
+                .zremrangebyscore(&key, f64::NEG_INFINITY, now.timestamp() as f64)
+                .await?;
+            let muted: Vec<(String, f64)> = self
+                .client
+                .zrangebyscore(&key, f64::NEG_INFINITY, f64::INFINITY, true, None)
+                .await?;
+            debug!(muted = muted.len(), expired, "listed muted symbols");
+            Ok(muted
+                .into_iter()
+                .filter(|(_, score)| mute_active(*score, now))
+                .map(|(symbol, score)| (symbol, mute_until(score)))
+                .collect())
+        })
+        .await
     }
 
     /// Get all symbols
-    #[instrument(name = "symbol_store_list", skip(self))]
-    pub async fn list(&self) -> Result<Vec<String>, Error> {
-        let members: Vec<String> = self.client.smembers(self.watchlist_key()).await?;
-        debug!(count = members.len(), "smembers done");
-        Ok(members)
+    #[instrument(name = "symbol_store_list", skip(self), fields(%scope))]
+    pub async fn list(&self, scope: Scope) -> Result<Vec<String>, Error> {
+        self.guarded(Op::Read, async {
+            let members: Vec<String> = self.client.smembers(self.watchlist_key(scope)).await?;
+            debug!(count = members.len(), "smembers done");
+            Ok(members)
+        })
+        .await
+    }
+
+    /// Up to `limit` watched symbols matching `query` by prefix or within a
+    /// typo of it, best first. See [`fuzzy::search`].
+    #[instrument(name = "symbol_store_search", skip(self), fields(%scope))]
+    pub async fn search(
+        &self,
+        scope: Scope,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        let symbols = self.list(scope).await?;
+        let found = fuzzy::search(&symbols, query, limit);
+        debug!(
+            watched = symbols.len(),
+            found = found.len(),
+            "searched watchlist"
+        );
+        Ok(found)
+    }
+
+    /// Store the latest daily scan reading for each of `readings`' symbols
+    pub async fn set_last_readings(
+        &self,
+        scope: Scope,
+        readings: &[(String, ScanReading)],
+    ) -> Result<(), Error> {
+        self.set_last_readings_at(scope, Timeframe::Day1, readings)
+            .await
+    }
+
+    /// Store the latest scan reading at `timeframe` for each of `readings`'
+    /// symbols
+    #[instrument(name = "symbol_store_set_last_readings", skip(self, readings), fields(%scope, timeframe = timeframe.as_str(), count = readings.len()))]
+    pub async fn set_last_readings_at(
+        &self,
+        scope: Scope,
+        timeframe: Timeframe,
+        readings: &[(String, ScanReading)],
+    ) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            if readings.is_empty() {
+                return Ok(());
+            }
+            let fields = readings
+                .iter()
+                .map(|(symbol, reading)| {
+                    Ok((Self::normalize(symbol), serde_json::to_string(reading)?))
+                })
+                .collect::<Result<Vec<(String, String)>, Error>>()?;
+            let _: i64 = self
+                .client
+                .hset(self.last_signal_key_at(scope, timeframe), fields)
+                .await?;
+            debug!("last readings saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// The last scan reading at `timeframe` of every symbol in `scope` that
+    /// has one
+    #[instrument(name = "symbol_store_last_readings_at", skip(self), fields(%scope, timeframe = timeframe.as_str()))]
+    pub async fn last_readings_at(
+        &self,
+        scope: Scope,
+        timeframe: Timeframe,
+    ) -> Result<HashMap<String, ScanReading>, Error> {
+        self.guarded(Op::Read, async {
+            let raw: HashMap<String, String> = self
+                .client
+                .hgetall(self.last_signal_key_at(scope, timeframe))
+                .await?;
+            let mut out = HashMap::with_capacity(raw.len());
+            for (symbol, raw) in raw {
+                match serde_json::from_str(&raw) {
+                    Ok(reading) => {
+                        out.insert(symbol, reading);
+                    }
+                    Err(e) => warn!(%symbol, error = ?e, "skipping unreadable last reading"),
+                }
+            }
+            debug!(count = out.len(), "hgetall done");
+            Ok(out)
+        })
+        .await
+    }
+
+    /// Remember where each of `posted`'s symbols last had a daily hit posted
+    pub async fn set_posted(
+        &self,
+        scope: Scope,
+        posted: &[(String, PostedSignal)],
+    ) -> Result<(), Error> {
+        self.set_posted_at(scope, Timeframe::Day1, posted).await
+    }
+
+    /// Remember where each of `posted`'s symbols last had a `timeframe` hit
+    /// posted
+    #[instrument(name = "symbol_store_set_posted", skip(self, posted), fields(%scope, timeframe = timeframe.as_str(), count = posted.len()))]
+    pub async fn set_posted_at(
+        &self,
+        scope: Scope,
+        timeframe: Timeframe,
+        posted: &[(String, PostedSignal)],
+    ) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            if posted.is_empty() {
+                return Ok(());
+            }
+            let fields = posted
+                .iter()
+                .map(|(symbol, post)| Ok((Self::normalize(symbol), serde_json::to_string(post)?)))
+                .collect::<Result<Vec<(String, String)>, Error>>()?;
+            let _: i64 = self
+                .client
+                .hset(self.posted_key_at(scope, timeframe), fields)
+                .await?;
+            debug!("posted signals saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Where each symbol in `scope` last had a daily hit posted
+    pub async fn posted(&self, scope: Scope) -> Result<HashMap<String, PostedSignal>, Error> {
+        self.posted_at(scope, Timeframe::Day1).await
+    }
+
+    /// Where each symbol in `scope` last had a `timeframe` hit posted
+    #[instrument(name = "symbol_store_posted", skip(self), fields(%scope, timeframe = timeframe.as_str()))]
+    pub async fn posted_at(
+        &self,
+        scope: Scope,
+        timeframe: Timeframe,
+    ) -> Result<HashMap<String, PostedSignal>, Error> {
+        self.guarded(Op::Read, async {
+            let raw: HashMap<String, String> = self
+                .client
+                .hgetall(self.posted_key_at(scope, timeframe))
+                .await?;
+            let mut out = HashMap::with_capacity(raw.len());
+            for (symbol, raw) in raw {
+                match serde_json::from_str(&raw) {
+                    Ok(post) => {
+                        out.insert(symbol, post);
+                    }
+                    Err(e) => warn!(%symbol, error = ?e, "skipping unreadable posted signal"),
+                }
+            }
+            debug!(count = out.len(), "hgetall done");
+            Ok(out)
+        })
+        .await
+    }
+
+    /// Every symbol in `scope` with its last scan reading, sorted by symbol.
+    /// Symbols never scanned yet have None. Both reads go out in one
+    /// pipeline.
+    #[instrument(name = "symbol_store_list_last_readings", skip(self), fields(%scope))]
+    pub async fn list_last_readings(
+        &self,
+        scope: Scope,
+    ) -> Result<Vec<(String, Option<ScanReading>)>, Error> {
+        self.guarded(Op::Read, async {
+            let pipeline = self.client.pipeline();
+            let _: () = pipeline.smembers(self.watchlist_key(scope)).await?;
+            let _: () = pipeline.hgetall(self.last_signal_key(scope)).await?;
+            let (members, readings): (Vec<String>, HashMap<String, String>) =
+                pipeline.all().await?;
+
+            let mut out: Vec<(String, Option<ScanReading>)> = members
+                .into_iter()
+                .map(|symbol| {
+                    let reading = readings.get(&symbol).and_then(|raw| {
+                        serde_json::from_str(raw)
+                            .inspect_err(
+                                |e| warn!(%symbol, error = ?e, "skipping unreadable last reading"),
+                            )
+                            .ok()
+                    });
+                    (symbol, reading)
+                })
+                .collect();
+            out.sort_by(|a, b| a.0.cmp(&b.0));
+            debug!(count = out.len(), "listed last readings");
+            Ok(out)
+        })
+        .await
+    }
+
+    /// Every symbol in `scope` with its last scanned signal, sorted by
+    /// symbol, in one round trip. Symbols never scanned yet have None.
+    pub async fn list_with_last_signals(
+        &self,
+        scope: Scope,
+    ) -> Result<Vec<(String, Option<Signal>)>, Error> {
+        Ok(self
+            .list_last_readings(scope)
+            .await?
+            .into_iter()
+            .map(|(symbol, reading)| (symbol, reading.map(|r| r.signal)))
+            .collect())
     }
 
     /// Total number of tracked symbols
-    #[instrument(name = "symbol_store_len", skip(self))]
-    pub async fn len(&self) -> Result<usize, Error> {
-        let count: i64 = self.client.scard(self.watchlist_key()).await?;
-        Ok(count as usize)
+    #[instrument(name = "symbol_store_len", skip(self), fields(%scope))]
+    pub async fn len(&self, scope: Scope) -> Result<usize, Error> {
+        self.guarded(Op::Read, async {
+            let count: i64 = self.client.scard(self.watchlist_key(scope)).await?;
+            Ok(count as usize)
+        })
+        .await
     }
 
     /// Returns true if there are no tracked symbols
-    #[instrument(name = "symbol_store_is_empty", skip(self))]
-    pub async fn is_empty(&self) -> Result<bool, Error> {
-        Ok(self.len().await? == 0)
+    #[instrument(name = "symbol_store_is_empty", skip(self), fields(%scope))]
+    pub async fn is_empty(&self, scope: Scope) -> Result<bool, Error> {
+        Ok(self.len(scope).await? == 0)
+    }
+
+    /// Rewrite the watchlist in `scope` so every member is in normalized
+    /// form, merging members that only differ by case or whitespace. Their
+    /// metadata and last reading move along when the normalized symbol has
+    /// none of its own.
+    #[instrument(name = "symbol_store_clean", skip(self), fields(%scope))]
+    pub async fn clean(&self, scope: Scope) -> Result<WatchlistCleanup, Error> {
+        self.guarded(Op::Write, async {
+            // not self.list: this is one write-class call, and the read
+            // circuit shouldn't count it twice
+            let members: Vec<String> = self.client.smembers(self.watchlist_key(scope)).await?;
+            let cleanup = WatchlistCleanup::plan(&members);
+            if cleanup.is_clean() {
+                debug!(count = members.len(), "watchlist already clean");
+                return Ok(cleanup);
+            }
+
+            let stale: Vec<&String> = cleanup
+                .renamed
+                .iter()
+                .map(|(from, _)| from)
+                .chain(&cleanup.removed)
+                .collect();
+            for from in &stale {
+                let to = Self::normalize(from);
+                for key in [self.meta_key(scope), self.last_signal_key(scope)] {
+                    let raw: Option<String> = self.client.hget(&key, from.as_str()).await?;
+                    if let Some(raw) = raw
+                        && !to.is_empty()
+                    {
+                        let _: bool = self.client.hsetnx(&key, to.as_str(), raw).await?;
+                    }
+                    let _: i64 = self.client.hdel(&key, from.as_str()).await?;
+                }
+            }
+
+            let key = self.watchlist_key(scope);
+            let _: i64 = self
+                .client
+                .srem(&key, stale.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+                .await?;
+            if !cleanup.renamed.is_empty() {
+                let _: i64 = self
+                    .client
+                    .sadd(
+                        &key,
+                        cleanup
+                            .renamed
+                            .iter()
+                            .map(|(_, to)| to.as_str())
+                            .collect::<Vec<_>>(),
+                    )
+                    .await?;
+            }
+            info!(
+                renamed = cleanup.renamed.len(),
+                removed = cleanup.removed.len(),
+                "watchlist cleaned"
+            );
+            Ok(cleanup)
+        })
+        .await
+    }
+
+    /// Move the pre-scoping global watchlist into `scope`
+    /// Returns true if it was adopted; never overwrites an existing scoped list
+    #[instrument(name = "symbol_store_adopt_legacy_watchlist", skip(self), fields(%scope))]
+    pub async fn adopt_legacy_watchlist(&self, scope: Scope) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let legacy = self.legacy_watchlist_key();
+            let exists: i64 = self.client.exists(legacy.clone()).await?;
+            if exists == 0 {
+                debug!("no legacy watchlist");
+                return Ok(false);
+            }
+
+            let moved: bool = self
+                .client
+                .renamenx(legacy, self.watchlist_key(scope))
+                .await?;
+            if moved {
+                info!("adopted legacy watchlist");
+            } else {
+                warn!("scoped watchlist already exists, legacy watchlist left in place");
+            }
+            Ok(moved)
+        })
+        .await
     }
 
     /// Set Pending Delete
@@ -138,34 +1441,911 @@ impl SymbolStore {
         fields(req_id = %id, symbol_count = symbols.len())
     )]
     pub async fn set_pending_delete(&self, id: String, symbols: Vec<String>) -> Result<i64, Error> {
-        let symbols: Vec<String> = symbols.into_iter().map(|s| Self::normalize(&s)).collect();
-
-        let del_key = self.pending_del_key(id.clone());
-        let _: i64 = self.client.del(del_key.clone()).await?;
-
-        let added = if symbols.is_empty() {
-            warn!("no symbols provided for pending delete");
-            0
-        } else {
-            let added: i64 = self.client.sadd(del_key.clone(), symbols).await?;
-            added
-        };
-
-        let _: i64 = self.client.expire(del_key, 300, None).await?;
-        debug!(added, "pending delete set");
-
-        Ok(added)
+        self.set_pending(self.pending_del_key(id), symbols, self.pending_delete_ttl)
+            .await
     }
 
     /// Get Pending Delete
     #[instrument(name = "symbol_store_get_pending_delete", skip(self), fields(req_id = %id))]
     pub async fn get_pending_delete(&self, id: String) -> Result<Option<Vec<String>>, Error> {
-        let members: Vec<String> = self.client.smembers(self.pending_del_key(id)).await?;
-        if members.is_empty() {
-            Ok(None)
-        } else {
-            debug!(count = members.len(), "pending delete loaded");
+        self.get_pending(self.pending_del_key(id)).await
+    }
+
+    /// Restart the expiry of the pending delete `id`, so its
+    /// [`Self::pending_delete_window`] counts from when its confirmation was
+    /// shown rather than from the selection. False when it has already
+    /// expired.
+    #[instrument(name = "symbol_store_refresh_pending_delete", skip(self), fields(req_id = %id))]
+    pub async fn refresh_pending_delete(&self, id: String) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let refreshed: i64 = self
+                .client
+                .expire(
+                    self.pending_del_key(id),
+                    self.pending_delete_ttl.as_secs() as i64,
+                    None,
+                )
+                .await?;
+            debug!(refreshed = refreshed == 1, "pending delete refreshed");
+            Ok(refreshed == 1)
+        })
+        .await
+    }
+
+    /// How long the pending delete `id` has left, or `None` once it has
+    /// expired.
+    #[instrument(name = "symbol_store_pending_delete_ttl", skip(self), fields(req_id = %id))]
+    pub async fn pending_delete_ttl(&self, id: String) -> Result<Option<Duration>, Error> {
+        self.guarded(Op::Read, async {
+            let ttl: i64 = self.client.ttl(self.pending_del_key(id)).await?;
+            Ok(u64::try_from(ttl).ok().map(Duration::from_secs))
+        })
+        .await
+    }
+
+    /// Consume the pending delete `id`: its symbols are returned and the
+    /// request removed in one transaction, so when a confirmation races
+    /// another only one gets them and the other sees `None`, like an
+    /// expired request.
+    #[instrument(name = "symbol_store_take_pending_delete", skip(self), fields(req_id = %id))]
+    pub async fn take_pending_delete(&self, id: String) -> Result<Option<Vec<String>>, Error> {
+        self.guarded(Op::Write, async {
+            let key = self.pending_del_key(id);
+            let trx = self.client.multi();
+            let _: () = trx.smembers(key.clone()).await?;
+            let _: () = trx.del(key).await?;
+            let (mut members, _): (Vec<String>, i64) = trx.exec(true).await?;
+            if members.is_empty() {
+                return Ok(None);
+            }
+            members.sort();
+            debug!(count = members.len(), "pending delete taken");
             Ok(Some(members))
+        })
+        .await
+    }
+
+    /// Remember what browser message `message_id` is showing. The state
+    /// expires 15 minutes after it was last saved.
+    #[instrument(name = "symbol_store_set_browse_state", skip(self), fields(message_id))]
+    pub async fn set_browse_state(
+        &self,
+        message_id: u64,
+        state: &BrowseState,
+    ) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(state)?;
+            let _: () = self
+                .client
+                .set(
+                    self.browse_key(message_id),
+                    raw,
+                    Some(Expiration::EX(BROWSE_STATE_TTL.as_secs() as i64)),
+                    None,
+                    false,
+                )
+                .await?;
+            debug!("browse state saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// What browser message `message_id` was showing, or `None` once its
+    /// state has expired.
+    #[instrument(name = "symbol_store_get_browse_state", skip(self), fields(message_id))]
+    pub async fn get_browse_state(&self, message_id: u64) -> Result<Option<BrowseState>, Error> {
+        self.guarded(Op::Read, async {
+            let raw: Option<String> = self.client.get(self.browse_key(message_id)).await?;
+            match raw {
+                Some(raw) => Ok(Some(serde_json::from_str(&raw)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    /// `guild_id`'s custom aliases, normalized name to ticker.
+    #[instrument(name = "symbol_store_list_aliases", skip(self))]
+    pub async fn list_aliases(&self, guild_id: u64) -> Result<HashMap<String, String>, Error> {
+        self.guarded(Op::Read, async {
+            let aliases: HashMap<String, String> =
+                self.client.hgetall(self.aliases_key(guild_id)).await?;
+            debug!(count = aliases.len(), "aliases loaded");
+            Ok(aliases)
+        })
+        .await
+    }
+
+    /// Point `guild_id`'s alias `name` at `symbol`, replacing what it pointed
+    /// at before. Both are expected checked by [`crate::alias::validate`].
+    /// Returns true if the name is new.
+    #[instrument(name = "symbol_store_set_alias", skip(self))]
+    pub async fn set_alias(&self, guild_id: u64, name: &str, symbol: &str) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let added: i64 = self
+                .client
+                .hset(self.aliases_key(guild_id), (name, symbol))
+                .await?;
+            Ok(added == 1)
+        })
+        .await
+    }
+
+    /// Drop `guild_id`'s alias `name`. Returns false if it had none by that
+    /// name.
+    #[instrument(name = "symbol_store_remove_alias", skip(self))]
+    pub async fn remove_alias(&self, guild_id: u64, name: &str) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let removed: i64 = self.client.hdel(self.aliases_key(guild_id), name).await?;
+            Ok(removed == 1)
+        })
+        .await
+    }
+
+    /// Set Pending Add
+    #[instrument(
+        name = "symbol_store_set_pending_add",
+        skip(self, symbols),
+        fields(req_id = %id, symbol_count = symbols.len())
+    )]
+    pub async fn set_pending_add(&self, id: String, symbols: Vec<String>) -> Result<i64, Error> {
+        self.set_pending(self.pending_add_key(id), symbols, PENDING_TTL)
+            .await
+    }
+
+    /// Get Pending Add
+    #[instrument(name = "symbol_store_get_pending_add", skip(self), fields(req_id = %id))]
+    pub async fn get_pending_add(&self, id: String) -> Result<Option<Vec<String>>, Error> {
+        self.get_pending(self.pending_add_key(id)).await
+    }
+
+    /// Replace the symbols awaiting confirmation under `key`. They expire
+    /// after `ttl`.
+    async fn set_pending(
+        &self,
+        key: String,
+        symbols: Vec<String>,
+        ttl: Duration,
+    ) -> Result<i64, Error> {
+        self.guarded(Op::Write, async {
+            let symbols: Vec<String> = symbols.into_iter().map(|s| Self::normalize(&s)).collect();
+
+            let _: i64 = self.client.del(key.clone()).await?;
+
+            let added = if symbols.is_empty() {
+                warn!("no symbols provided for pending request");
+                0
+            } else {
+                let added: i64 = self.client.sadd(key.clone(), symbols).await?;
+                added
+            };
+
+            let _: i64 = self.client.expire(key, ttl.as_secs() as i64, None).await?;
+            debug!(added, "pending request set");
+
+            Ok(added)
+        })
+        .await
+    }
+
+    async fn get_pending(&self, key: String) -> Result<Option<Vec<String>>, Error> {
+        self.guarded(Op::Read, async {
+            let mut members: Vec<String> = self.client.smembers(key).await?;
+            if members.is_empty() {
+                Ok(None)
+            } else {
+                members.sort();
+                debug!(count = members.len(), "pending request loaded");
+                Ok(Some(members))
+            }
+        })
+        .await
+    }
+
+    /// Start a per-user cooldown window for `scope`
+    /// Returns None when the window was started, or the seconds left on an active one
+    ///
+    /// The window is claimed with `SET NX EX`, so it never exists without
+    /// its expiry. A key left without one, as an older INCR-then-EXPIRE
+    /// could leave it, gets `secs` put on it rather than blocking the user
+    /// for good.
+    #[instrument(
+        name = "symbol_store_try_cooldown",
+        skip(self),
+        fields(scope, user_id, secs)
+    )]
+    pub async fn try_cooldown(
+        &self,
+        scope: &str,
+        user_id: u64,
+        secs: u64,
+    ) -> Result<Option<u64>, Error> {
+        self.guarded(Op::Write, async {
+            let key = self.cooldown_key(scope, user_id);
+            loop {
+                let started: Option<String> = self
+                    .client
+                    .set(
+                        key.clone(),
+                        1,
+                        Some(Expiration::EX(secs as i64)),
+                        Some(SetOptions::NX),
+                        false,
+                    )
+                    .await?;
+
+                if started.is_some() {
+                    debug!("cooldown started");
+                    return Ok(None);
+                }
+
+                let ttl: i64 = self.client.ttl(key.clone()).await?;
+                match ttl {
+                    // expired between the two calls; claim it again
+                    -2 => continue,
+                    -1 => {
+                        warn!("cooldown without an expiry, setting one");
+                        let _: i64 = self.client.expire(key, secs as i64, None).await?;
+                        return Ok(Some(secs.max(1)));
+                    }
+                    ttl => {
+                        debug!(ttl, "cooldown active");
+                        return Ok(Some(ttl.max(1) as u64));
+                    }
+                }
+            }
+        })
+        .await
+    }
+
+    /// Get Guild Settings
+    /// Returns defaults when nothing has been stored for the guild
+    #[instrument(name = "symbol_store_get_guild_settings", skip(self), fields(guild_id))]
+    pub async fn get_guild_settings(&self, guild_id: u64) -> Result<GuildSettings, Error> {
+        self.guarded(Op::Read, async {
+            let raw: Option<String> = self.client.get(self.guild_settings_key(guild_id)).await?;
+            match raw {
+                Some(raw) => Ok(serde_json::from_str(&raw)?),
+                None => {
+                    debug!("no guild settings stored");
+                    Ok(GuildSettings::default())
+                }
+            }
+        })
+        .await
+    }
+
+    /// Set Guild Settings
+    #[instrument(
+        name = "symbol_store_set_guild_settings",
+        skip(self, settings),
+        fields(guild_id)
+    )]
+    pub async fn set_guild_settings(
+        &self,
+        guild_id: u64,
+        settings: &GuildSettings,
+    ) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(settings)?;
+            let _: () = self
+                .client
+                .set(self.guild_settings_key(guild_id), raw, None, None, false)
+                .await?;
+            let _: i64 = self.client.sadd(self.guilds_key(), guild_id).await?;
+            debug!("guild settings saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Get the guild's signal strategy
+    /// Returns the default when none has been picked
+    #[instrument(name = "symbol_store_get_strategy", skip(self), fields(guild_id))]
+    pub async fn get_strategy(&self, guild_id: u64) -> Result<Strategy, Error> {
+        self.guarded(Op::Read, async {
+            let raw: Option<String> = self.client.get(self.guild_strategy_key(guild_id)).await?;
+            match raw {
+                Some(raw) => Ok(serde_json::from_str(&raw)?),
+                None => {
+                    debug!("no strategy stored");
+                    Ok(Strategy::default())
+                }
+            }
+        })
+        .await
+    }
+
+    /// Set the guild's signal strategy
+    #[instrument(name = "symbol_store_set_strategy", skip(self), fields(guild_id, strategy = %strategy))]
+    pub async fn set_strategy(&self, guild_id: u64, strategy: &Strategy) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(strategy)?;
+            let _: () = self
+                .client
+                .set(self.guild_strategy_key(guild_id), raw, None, None, false)
+                .await?;
+            debug!("strategy saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Go back to the default strategy
+    #[instrument(name = "symbol_store_clear_strategy", skip(self), fields(guild_id))]
+    pub async fn clear_strategy(&self, guild_id: u64) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let _: i64 = self.client.del(self.guild_strategy_key(guild_id)).await?;
+            debug!("strategy cleared");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Guilds that have stored settings
+    #[instrument(name = "symbol_store_list_guilds", skip(self))]
+    pub async fn list_guilds(&self) -> Result<Vec<u64>, Error> {
+        self.guarded(Op::Read, async {
+            let members: Vec<u64> = self.client.smembers(self.guilds_key()).await?;
+            debug!(count = members.len(), "smembers done");
+            Ok(members)
+        })
+        .await
+    }
+
+    /// Get User Preferences
+    /// Returns defaults when nothing has been stored for the user
+    #[instrument(name = "symbol_store_get_user_pref", skip(self), fields(user_id))]
+    pub async fn get_user_pref(&self, user_id: u64) -> Result<UserPrefs, Error> {
+        self.guarded(Op::Read, async {
+            let raw: Option<String> = self.client.get(self.user_prefs_key(user_id)).await?;
+            match raw {
+                Some(raw) => Ok(serde_json::from_str(&raw)?),
+                None => {
+                    debug!("no user prefs stored");
+                    Ok(UserPrefs::default())
+                }
+            }
+        })
+        .await
+    }
+
+    /// Set User Preferences
+    /// Also keeps the user in or out of the daily digest and stats indexes
+    #[instrument(
+        name = "symbol_store_set_user_pref",
+        skip(self, prefs),
+        fields(user_id)
+    )]
+    pub async fn set_user_pref(&self, user_id: u64, prefs: &UserPrefs) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(prefs)?;
+            let _: () = self
+                .client
+                .set(self.user_prefs_key(user_id), raw, None, None, false)
+                .await?;
+            let _: i64 = if prefs.daily_digest {
+                self.client.sadd(self.digest_users_key(), user_id).await?
+            } else {
+                self.client.srem(self.digest_users_key(), user_id).await?
+            };
+            let _: i64 = if prefs.show_in_stats {
+                self.client.sadd(self.stats_users_key(), user_id).await?
+            } else {
+                self.client.srem(self.stats_users_key(), user_id).await?
+            };
+            debug!("user prefs saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Users who turned the daily digest on
+    #[instrument(name = "symbol_store_list_digest_users", skip(self))]
+    pub async fn list_digest_users(&self) -> Result<Vec<u64>, Error> {
+        self.guarded(Op::Read, async {
+            let users: Vec<u64> = self.client.smembers(self.digest_users_key()).await?;
+            debug!(count = users.len(), "smembers done");
+            Ok(users)
+        })
+        .await
+    }
+
+    /// Timeframe the user last graphed `symbol` at, if any
+    #[instrument(name = "symbol_store_get_graph_timeframe", skip(self), fields(user_id, symbol = %symbol))]
+    pub async fn get_graph_timeframe(
+        &self,
+        user_id: u64,
+        symbol: &str,
+    ) -> Result<Option<Timeframe>, Error> {
+        self.guarded(Op::Read, async {
+            let raw: Option<String> = self
+                .client
+                .hget(self.graph_timeframes_key(user_id), Self::normalize(symbol))
+                .await?;
+            match raw {
+                Some(raw) => Ok(Some(serde_json::from_str(&raw)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    /// Remember the timeframe the user graphed `symbol` at
+    #[instrument(name = "symbol_store_set_graph_timeframe", skip(self), fields(user_id, symbol = %symbol, timeframe = %timeframe.as_str()))]
+    pub async fn set_graph_timeframe(
+        &self,
+        user_id: u64,
+        symbol: &str,
+        timeframe: Timeframe,
+    ) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(&timeframe)?;
+            let _: i64 = self
+                .client
+                .hset(
+                    self.graph_timeframes_key(user_id),
+                    (Self::normalize(symbol), raw),
+                )
+                .await?;
+            debug!("graph timeframe saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Archive a daily run under its date for [`REDIS_RETENTION_DAYS`],
+    /// replacing any earlier run for the same date
+    #[instrument(name = "symbol_store_save_run", skip(self, run), fields(date = %run.date))]
+    pub async fn save_run(&self, run: &RunRecord) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(run)?;
+            let ttl = Duration::from_secs(REDIS_RETENTION_DAYS as u64 * 86_400);
+            let _: () = self
+                .client
+                .set(
+                    self.run_key(run.date),
+                    raw,
+                    Some(Expiration::EX(ttl.as_secs() as i64)),
+                    None,
+                    false,
+                )
+                .await?;
+
+            // the index has no TTL of its own; drop dates whose documents expired
+            let day = run.date.num_days_from_ce() as f64;
+            let _: i64 = self
+                .client
+                .zadd(
+                    self.runs_index_key(),
+                    None,
+                    None,
+                    false,
+                    false,
+                    (day, run.date.format("%Y-%m-%d").to_string()),
+                )
+                .await?;
+            let _: i64 = self
+                .client
+                .zremrangebyscore(
+                    self.runs_index_key(),
+                    f64::NEG_INFINITY,
+                    day - REDIS_RETENTION_DAYS as f64,
+                )
+                .await?;
+            debug!("run archived");
+            Ok(())
+        })
+        .await
+    }
+
+    /// The run archived for `date`, if it is still kept
+    #[instrument(name = "symbol_store_get_run", skip(self), fields(date = %date))]
+    pub async fn get_run(&self, date: NaiveDate) -> Result<Option<RunRecord>, Error> {
+        self.guarded(Op::Read, async {
+            let raw: Option<String> = self.client.get(self.run_key(date)).await?;
+            match raw {
+                Some(raw) => Ok(Some(serde_json::from_str(&raw)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    /// Dates of the most recent archived runs, newest first
+    #[instrument(name = "symbol_store_list_runs", skip(self), fields(limit))]
+    pub async fn list_runs(&self, limit: usize) -> Result<Vec<NaiveDate>, Error> {
+        self.guarded(Op::Read, async {
+            if limit == 0 {
+                return Ok(Vec::new());
+            }
+            let raw: Vec<String> = self
+                .client
+                .zrevrange(self.runs_index_key(), 0, limit as i64 - 1, false)
+                .await?;
+            let dates = raw
+                .iter()
+                .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                .collect::<Vec<_>>();
+            debug!(count = dates.len(), "listed runs");
+            Ok(dates)
+        })
+        .await
+    }
+
+    /// Record when the daily job last completed
+    #[instrument(name = "symbol_store_set_last_daily_run", skip(self), fields(at = %at))]
+    pub async fn set_last_daily_run(&self, at: DateTime<Utc>) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let _: () = self
+                .client
+                .set(self.last_daily_run_key(), at.timestamp(), None, None, false)
+                .await?;
+            debug!("last daily run saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Claim the daily run for `date`. True for the first claim of the day,
+    /// false once a run has it: the lock lasts a day, well past any restart
+    /// or misfire that could trigger a second run, unless the run gives it
+    /// back with [`Self::release_daily_lock`].
+    #[instrument(name = "symbol_store_try_daily_lock", skip(self), fields(%date))]
+    pub async fn try_daily_lock(&self, date: NaiveDate) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let claimed: Option<String> = self
+                .client
+                .set(
+                    self.daily_lock_key(date),
+                    Utc::now().timestamp(),
+                    Some(Expiration::EX(DAILY_LOCK_TTL.as_secs() as i64)),
+                    Some(SetOptions::NX),
+                    false,
+                )
+                .await?;
+            debug!(claimed = claimed.is_some(), "daily lock attempted");
+            Ok(claimed.is_some())
+        })
+        .await
+    }
+
+    /// Give up the daily run for `date`, so a run that failed can be tried
+    /// again the same day.
+    #[instrument(name = "symbol_store_release_daily_lock", skip(self), fields(%date))]
+    pub async fn release_daily_lock(&self, date: NaiveDate) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let _: i64 = self.client.del(self.daily_lock_key(date)).await?;
+            debug!("daily lock released");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Remember that batch `key` of a scheduled run went out, for long
+    /// enough that a re-run of the same day skips it
+    #[instrument(name = "symbol_store_mark_batch_posted", skip(self), fields(key = %key))]
+    pub async fn mark_batch_posted(&self, key: &str) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let _: () = self
+                .client
+                .set(
+                    self.posted_batch_key(key),
+                    Utc::now().timestamp(),
+                    Some(Expiration::EX(POSTED_BATCH_TTL.as_secs() as i64)),
+                    None,
+                    false,
+                )
+                .await?;
+            debug!("batch marked posted");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Whether batch `key` was posted by an earlier attempt at its run
+    #[instrument(name = "symbol_store_is_batch_posted", skip(self), fields(key = %key))]
+    pub async fn is_batch_posted(&self, key: &str) -> Result<bool, Error> {
+        self.guarded(Op::Read, async {
+            let exists: i64 = self.client.exists(self.posted_batch_key(key)).await?;
+            Ok(exists > 0)
+        })
+        .await
+    }
+
+    /// When the daily job last completed, if ever
+    #[instrument(name = "symbol_store_get_last_daily_run", skip(self))]
+    pub async fn get_last_daily_run(&self) -> Result<Option<DateTime<Utc>>, Error> {
+        self.guarded(Op::Read, async {
+            let ts: Option<i64> = self.client.get(self.last_daily_run_key()).await?;
+            Ok(ts.and_then(|ts| DateTime::from_timestamp(ts, 0)))
+        })
+        .await
+    }
+
+    /// Save a price alert for `user_id`, replacing any with the same id
+    #[instrument(name = "symbol_store_save_alert", skip(self, alert), fields(user_id, alert_id = %alert.id))]
+    pub async fn save_alert(&self, user_id: u64, alert: &Alert) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let raw = serde_json::to_string(alert)?;
+            let _: i64 = self
+                .client
+                .hset(self.alerts_key(user_id), (alert.id.clone(), raw))
+                .await?;
+            let _: i64 = self.client.sadd(self.alert_users_key(), user_id).await?;
+            debug!("alert saved");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Users who have any alerts
+    #[instrument(name = "symbol_store_list_alert_users", skip(self))]
+    pub async fn list_alert_users(&self) -> Result<Vec<u64>, Error> {
+        self.guarded(Op::Read, async {
+            let users: Vec<u64> = self.client.smembers(self.alert_users_key()).await?;
+            debug!(count = users.len(), "smembers done");
+            Ok(users)
+        })
+        .await
+    }
+
+    /// All of `user_id`'s alerts, sorted by symbol then price
+    #[instrument(name = "symbol_store_list_alerts", skip(self), fields(user_id))]
+    pub async fn list_alerts(&self, user_id: u64) -> Result<Vec<Alert>, Error> {
+        self.guarded(Op::Read, self.read_alerts(user_id)).await
+    }
+
+    /// `user_id`'s alerts for an alert run to evaluate and hand back to
+    /// [`Self::settle_alert`]. Unordered.
+    #[instrument(name = "symbol_store_list_stored_alerts", skip(self), fields(user_id))]
+    pub async fn list_stored_alerts(&self, user_id: u64) -> Result<Vec<StoredAlert>, Error> {
+        self.guarded(Op::Read, async {
+            let raw: HashMap<String, String> =
+                self.client.hgetall(self.alerts_key(user_id)).await?;
+            let alerts = raw
+                .into_iter()
+                .filter_map(|(id, raw)| match serde_json::from_str(&raw) {
+                    Ok(alert) => Some(StoredAlert { alert, raw }),
+                    Err(e) => {
+                        warn!(alert_id = %id, error = ?e, "skipping unreadable alert");
+                        None
+                    }
+                })
+                .collect();
+            Ok(alerts)
+        })
+        .await
+    }
+
+    /// Write back what a run made of `stored`: its new state, or None to
+    /// delete it. False, and nothing written, when the owner changed or
+    /// deleted the alert since it was read, so a run never brings back or
+    /// reverts one.
+    #[instrument(name = "symbol_store_settle_alert", skip(self, stored, next), fields(user_id, alert_id = %stored.alert.id))]
+    pub async fn settle_alert(
+        &self,
+        user_id: u64,
+        stored: &StoredAlert,
+        next: Option<&Alert>,
+    ) -> Result<bool, Error> {
+        self.guarded(Op::Write, async {
+            let next = match next {
+                Some(alert) => serde_json::to_string(alert)?,
+                None => String::new(),
+            };
+            let written: i64 = self
+                .client
+                .eval(
+                    SETTLE_ALERT,
+                    vec![self.alerts_key(user_id), self.alert_users_key()],
+                    vec![
+                        user_id.to_string(),
+                        stored.alert.id.clone(),
+                        stored.raw.clone(),
+                        next,
+                    ],
+                )
+                .await?;
+            debug!(written = written == 1, "alert settled");
+            Ok(written == 1)
+        })
+        .await
+    }
+
+    async fn read_alerts(&self, user_id: u64) -> Result<Vec<Alert>, Error> {
+        let raw: HashMap<String, String> = self.client.hgetall(self.alerts_key(user_id)).await?;
+        Ok(Self::parse_alerts(raw))
+    }
+
+    fn parse_alerts(raw: HashMap<String, String>) -> Vec<Alert> {
+        let mut alerts = Vec::with_capacity(raw.len());
+        for (id, raw) in raw {
+            match serde_json::from_str(&raw) {
+                Ok(alert) => alerts.push(alert),
+                Err(e) => warn!(alert_id = %id, error = ?e, "skipping unreadable alert"),
+            }
         }
+        alert::sort(&mut alerts);
+        debug!(count = alerts.len(), "alerts parsed");
+        alerts
+    }
+
+    /// Delete the alerts with the given ids
+    /// Returns the alerts that were actually removed
+    #[instrument(name = "symbol_store_remove_alerts", skip(self, ids), fields(user_id, count = ids.len()))]
+    pub async fn remove_alerts(&self, user_id: u64, ids: &[String]) -> Result<Vec<Alert>, Error> {
+        self.guarded(Op::Write, async {
+            let alerts = self.read_alerts(user_id).await?;
+            let (removed, _) = alert::split_selected(alerts, ids);
+            let fields: Vec<String> = removed.iter().map(|a| a.id.clone()).collect();
+            self.delete_alerts(user_id, &fields).await?;
+            debug!(removed = removed.len(), "alerts removed");
+            Ok(removed)
+        })
+        .await
+    }
+
+    /// Count one invocation of `command` in `scope` today, and one for
+    /// `user_id` when they opted into the stats. Both hashes expire after
+    /// [`stats::RETENTION_DAYS`].
+    #[instrument(name = "symbol_store_incr_usage", skip(self), fields(%scope, command = %command, user_id))]
+    pub async fn incr_usage(&self, scope: Scope, command: &str, user_id: u64) -> Result<(), Error> {
+        self.guarded(Op::Write, async {
+            let today = Utc::now().date_naive();
+            let ttl = stats::RETENTION_DAYS * 86_400;
+
+            let key = self.usage_key(scope, today);
+            let _: i64 = self.client.hincrby(&key, command, 1).await?;
+            let _: i64 = self.client.expire(&key, ttl, None).await?;
+
+            let listed: bool = self
+                .client
+                .sismember(self.stats_users_key(), user_id)
+                .await?;
+            if listed {
+                let key = self.usage_users_key(scope, today);
+                let _: i64 = self.client.hincrby(&key, user_id.to_string(), 1).await?;
+                let _: i64 = self.client.expire(&key, ttl, None).await?;
+            }
+            debug!(listed, "usage counted");
+            Ok(())
+        })
+        .await
     }
+
+    /// `scope`'s counters for each of `dates`, empty for days nothing was
+    /// used. Users who have since opted out of the stats are left out.
+    #[instrument(name = "symbol_store_usage_for", skip(self, dates), fields(%scope, days = dates.len()))]
+    pub async fn usage_for(
+        &self,
+        scope: Scope,
+        dates: &[NaiveDate],
+    ) -> Result<Vec<DayUsage>, Error> {
+        self.guarded(Op::Read, async {
+            let listed: HashSet<u64> = self.client.smembers(self.stats_users_key()).await?;
+            let mut days = Vec::with_capacity(dates.len());
+            for &date in dates {
+                let commands: HashMap<String, u64> =
+                    self.client.hgetall(self.usage_key(scope, date)).await?;
+                let users: HashMap<String, u64> = self
+                    .client
+                    .hgetall(self.usage_users_key(scope, date))
+                    .await?;
+                let users = users
+                    .into_iter()
+                    .filter_map(|(user, count)| Some((user.parse().ok()?, count)))
+                    .filter(|(user, _)| listed.contains(user))
+                    .collect();
+                days.push(DayUsage {
+                    date,
+                    commands,
+                    users,
+                });
+            }
+            debug!(days = days.len(), "usage read");
+            Ok(days)
+        })
+        .await
+    }
+
+    /// Count the keys under the prefix by [`key_category`] with `SCAN`, and
+    /// read Redis' memory use from `INFO memory`.
+    #[instrument(name = "symbol_store_key_stats", skip(self))]
+    pub async fn key_stats(&self) -> Result<KeyStats, Error> {
+        self.guarded(Op::Read, async {
+            let prefix = format!("{}:", self.key_prefix);
+            let keys = self.scan_keys().await?;
+
+            let mut stats = KeyStats::default();
+            for key in &keys {
+                let Some(name) = key.strip_prefix(&prefix) else {
+                    continue;
+                };
+                *stats
+                    .categories
+                    .entry(key_category(name).to_string())
+                    .or_default() += 1;
+            }
+            stats.used_memory = match self.client.info::<String>(Some(InfoKind::Memory)).await {
+                Ok(info) => used_memory(&info),
+                Err(e) => {
+                    warn!(error = ?e, "INFO memory unavailable");
+                    None
+                }
+            };
+            debug!(keys = stats.keys(), ?stats.used_memory, "key stats collected");
+            Ok(stats)
+        })
+        .await
+    }
+
+    /// Count what the store holds, walking its keys with `SCAN`
+    #[instrument(name = "symbol_store_stats", skip(self))]
+    pub async fn stats(&self) -> Result<StoreStats, Error> {
+        self.guarded(Op::Read, async {
+            let keys = self.scan_keys().await?;
+
+            let mut stats = StoreStats {
+                keys: keys.len(),
+                memory_bytes: Some(0),
+                ..Default::default()
+            };
+            for name in keys.iter().map(String::as_str) {
+                if name.ends_with(":watchlist") {
+                    let count: u64 = self.client.scard(name).await?;
+                    stats.watchlists += 1;
+                    stats.symbols += count as usize;
+                } else if name.ends_with(":alerts") {
+                    let count: u64 = self.client.hlen(name).await?;
+                    stats.alerts += count as usize;
+                } else if name.contains(":pending_del:") || name.contains(":pending_add:") {
+                    stats.pending_sessions += 1;
+                }
+
+                if let Some(total) = stats.memory_bytes {
+                    match self.client.memory_usage::<Option<u64>, _>(name, None).await {
+                        Ok(bytes) => stats.memory_bytes = Some(total + bytes.unwrap_or(0)),
+                        Err(e) => {
+                            warn!(error = ?e, "MEMORY USAGE unavailable");
+                            stats.memory_bytes = None;
+                        }
+                    }
+                }
+            }
+            debug!(?stats, "store stats collected");
+            Ok(stats)
+        })
+        .await
+    }
+
+    /// Every key under the prefix, walked with `SCAN`, for [`Self::stats`]
+    /// and [`Self::key_stats`]. Keys that aren't valid UTF-8 are skipped.
+    async fn scan_keys(&self) -> Result<Vec<String>, Error> {
+        let keys: Vec<Key> = self
+            .client
+            .scan_buffered(format!("{}:*", self.key_prefix), Some(500), None)
+            .try_collect()
+            .await?;
+        Ok(keys
+            .iter()
+            .filter_map(|key| key.as_str().map(str::to_string))
+            .collect())
+    }
+}
+
+/// Whether `e` means Redis didn't answer, as opposed to answering with an
+/// error of its own or a value that didn't parse.
+fn is_outage(e: &Error) -> bool {
+    e.downcast_ref::<fred::error::Error>().is_some_and(|e| {
+        matches!(
+            e.kind(),
+            fred::error::ErrorKind::IO
+                | fred::error::ErrorKind::Timeout
+                | fred::error::ErrorKind::Canceled
+                | fred::error::ErrorKind::Backpressure
+                | fred::error::ErrorKind::Routing
+        )
+    })
 }
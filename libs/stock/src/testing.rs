@@ -0,0 +1,205 @@
+//! Golden-image checks for chart rendering, behind the `testing` feature.
+//!
+//! A fixed series checked in at `fixtures/chart_series.csv` is drawn in every
+//! [`variants`] combination and each PNG is boiled down to a small grayscale
+//! [`Thumbnail`]. Thumbnails are compared with a tolerance rather than byte
+//! for byte: fonts come from the system, so antialiasing and glyph widths
+//! differ slightly between machines, while a moved panel, a lost series or a
+//! swapped color does not get past it.
+//!
+//! References live in a plain text file, one `name hex` line per variant;
+//! see [`parse_goldens`] and [`format_goldens`].
+
+use std::{collections::BTreeMap, io::Cursor};
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use chrono::NaiveDate;
+use image::{GrayImage, ImageFormat, Luma, imageops::FilterType};
+
+use crate::{
+    ChartJob,
+    indicators::cdc::{ChartOptions, ChartScale, ChartTheme, IndicatorSet, calculate},
+};
+
+const FIXTURE: &str = include_str!("../fixtures/chart_series.csv");
+
+/// Size every chart is shrunk to before comparing, the chart's aspect ratio
+/// at a size that keeps the reference file readable.
+pub const THUMBNAIL_WIDTH: u32 = 64;
+pub const THUMBNAIL_HEIGHT: u32 = 36;
+
+/// Largest mean difference per thumbnail pixel, out of 255, still taken as
+/// the same chart.
+pub const DEFAULT_TOLERANCE: f64 = 1.5;
+
+/// The fixture: session dates, closes and volumes, one per bar.
+pub fn fixture() -> (Vec<NaiveDate>, Vec<f64>, Vec<f64>) {
+    let mut dates = Vec::new();
+    let mut closes = Vec::new();
+    let mut volumes = Vec::new();
+    for line in FIXTURE.lines().skip(1).filter(|l| !l.trim().is_empty()) {
+        let mut fields = line.split(',');
+        let (Some(date), Some(close), Some(volume)) = (fields.next(), fields.next(), fields.next())
+        else {
+            panic!("fixture line {line:?} needs date,close,volume");
+        };
+        dates.push(date.parse().expect("fixture date"));
+        closes.push(close.parse().expect("fixture close"));
+        volumes.push(volume.parse().expect("fixture volume"));
+    }
+    (dates, closes, volumes)
+}
+
+/// The fixture as a chart job drawn with `indicators` in `theme` at `scale`,
+/// its last crossover annotated.
+pub fn fixture_job(indicators: IndicatorSet, theme: ChartTheme, scale: ChartScale) -> ChartJob {
+    let (bar_dates, closes, volumes) = fixture();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    ChartJob {
+        symbol: "GOLDEN".to_string(),
+        dates: bar_dates.iter().map(NaiveDate::to_string).collect(),
+        closes: closes.into(),
+        ema12: ema12.into(),
+        ema26: ema26.into(),
+        options: ChartOptions {
+            indicators,
+            volumes: Some(volumes),
+            theme,
+            scale,
+            annotate_crossover: true,
+            bar_dates: Some(bar_dates),
+            ..Default::default()
+        },
+    }
+}
+
+/// Every combination checked against a reference, by name: the averages in
+/// each theme at each scale, and every indicator panel once.
+pub fn variants() -> Vec<(String, ChartJob)> {
+    let mut variants = Vec::new();
+    for theme in ChartTheme::ALL {
+        for scale in [ChartScale::X1, ChartScale::X2] {
+            variants.push((
+                format!("{}-{}", theme.as_str(), scale.as_str()),
+                fixture_job(IndicatorSet::default(), theme, scale),
+            ));
+        }
+    }
+    let all = IndicatorSet {
+        ema: true,
+        bollinger: true,
+        rsi: true,
+        volume: true,
+        macd: true,
+        spread: true,
+    };
+    variants.push((
+        "all-indicators".to_string(),
+        fixture_job(all, ChartTheme::Dark, ChartScale::X1),
+    ));
+    variants
+}
+
+/// A chart shrunk to [`THUMBNAIL_WIDTH`] by [`THUMBNAIL_HEIGHT`] grayscale
+/// pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail(Vec<u8>);
+
+impl Thumbnail {
+    pub fn from_png(png: &[u8]) -> Result<Self> {
+        let image = image::load_from_memory_with_format(png, ImageFormat::Png)
+            .context("decode chart PNG")?
+            .to_luma8();
+        let small = image::imageops::resize(
+            &image,
+            THUMBNAIL_WIDTH,
+            THUMBNAIL_HEIGHT,
+            FilterType::Triangle,
+        );
+        Ok(Self(small.into_raw()))
+    }
+
+    /// Mean absolute difference per pixel, from 0 (identical) to 255.
+    pub fn distance(&self, other: &Self) -> f64 {
+        let total: u64 = self
+            .0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| u64::from(a.abs_diff(*b)))
+            .sum();
+        total as f64 / self.0.len() as f64
+    }
+
+    /// Where the two differ, brighter for larger differences, blown up
+    /// eight times so it can be looked at.
+    pub fn diff_png(&self, other: &Self) -> Result<Vec<u8>> {
+        let diff = GrayImage::from_fn(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT, |x, y| {
+            let i = (y * THUMBNAIL_WIDTH + x) as usize;
+            Luma([self.0[i].abs_diff(other.0[i])])
+        });
+        let diff = image::imageops::resize(
+            &diff,
+            THUMBNAIL_WIDTH * 8,
+            THUMBNAIL_HEIGHT * 8,
+            FilterType::Nearest,
+        );
+        let mut out = Vec::new();
+        diff.write_to(&mut Cursor::new(&mut out), ImageFormat::Png)?;
+        Ok(out)
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let len = (THUMBNAIL_WIDTH * THUMBNAIL_HEIGHT) as usize;
+        ensure!(
+            hex.len() == len * 2,
+            "thumbnail has {} hex digits, expected {}",
+            hex.len(),
+            len * 2
+        );
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|b| u8::from_str_radix(b, 16).ok())
+                    .ok_or_else(|| anyhow!("bad hex at {i}"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self(bytes))
+    }
+}
+
+/// Reference thumbnails by variant name. Blank lines and `#` comments are
+/// skipped.
+pub fn parse_goldens(raw: &str) -> Result<BTreeMap<String, Thumbnail>> {
+    let mut goldens = BTreeMap::new();
+    for (n, line) in raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, hex)) = line.split_once(' ') else {
+            bail!("line {}: expected `name hex`", n + 1);
+        };
+        let thumbnail =
+            Thumbnail::from_hex(hex.trim()).with_context(|| format!("line {}", n + 1))?;
+        goldens.insert(name.to_string(), thumbnail);
+    }
+    Ok(goldens)
+}
+
+/// `goldens` in the form [`parse_goldens`] reads, after a header saying how
+/// to regenerate them.
+pub fn format_goldens(goldens: &BTreeMap<String, Thumbnail>) -> String {
+    let mut out = String::from(
+        "# Chart golden thumbnails, see stock::testing.\n\
+         # Regenerate with: cargo test -p stock --test golden -- --ignored regen_goldens\n",
+    );
+    for (name, thumbnail) in goldens {
+        out.push_str(&format!("{name} {}\n", thumbnail.to_hex()));
+    }
+    out
+}
@@ -0,0 +1,202 @@
+//! Stage timings for the scan pipeline.
+//!
+//! Tracing spans say when each stage ran; this keeps the durations
+//! themselves so the benchmark command can report averages and tails. A
+//! [`TimingCollector`] holds the most recent samples per stage, dropping the
+//! oldest past its capacity.
+//!
+//! The daily run keeps [`ScanTimings`] instead: just the total per stage,
+//! archived with the run so a run that has slowed down can be spotted
+//! against the ones before it.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Samples kept per stage by [`TimingCollector::default`].
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+/// Past runs a run's wall clock is compared against.
+pub const DEGRADATION_WINDOW: usize = 10;
+
+/// How many times the usual wall clock a run can take before it counts as
+/// degraded.
+pub const DEGRADATION_FACTOR: f64 = 2.0;
+
+/// Fewer past runs than this aren't a baseline worth comparing against.
+pub const MIN_HISTORY: usize = 3;
+
+/// A timed step of scanning one symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Fetching bars, plus the snapshot when the bars lag.
+    Fetch,
+    /// Working out the signal from the bars.
+    Indicators,
+    /// Rendering the chart for a hit.
+    Render,
+    /// Posting the results.
+    Send,
+}
+
+/// Somewhere the scan pipeline reports how long each stage took.
+pub trait Recorder: Send + Sync {
+    fn record(&self, stage: Stage, elapsed: Duration);
+}
+
+/// Aggregate of one stage's samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageStats {
+    pub count: usize,
+    pub mean: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl StageStats {
+    /// Stats over `samples`, in any order. None when there are none.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let total: Duration = sorted.iter().sum();
+
+        Some(Self {
+            count: sorted.len(),
+            mean: total / u32::try_from(sorted.len()).ok().filter(|n| *n > 0)?,
+            p95: percentile(&sorted, 95.0)?,
+            max: *sorted.last()?,
+        })
+    }
+}
+
+/// The `pct`th percentile of `sorted` by nearest rank: the smallest sample
+/// at least `pct` percent of the samples are no greater than.
+pub fn percentile(sorted: &[Duration], pct: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Rolling per-stage durations, shared by every task of a scan.
+pub struct TimingCollector {
+    capacity: usize,
+    samples: Mutex<HashMap<Stage, VecDeque<Duration>>>,
+}
+
+impl TimingCollector {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, stage: Stage, elapsed: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        let stage = samples.entry(stage).or_default();
+        if stage.len() == self.capacity {
+            stage.pop_front();
+        }
+        stage.push_back(elapsed);
+    }
+
+    pub fn stats(&self, stage: Stage) -> Option<StageStats> {
+        let samples = self.samples.lock().unwrap();
+        let stage: Vec<Duration> = samples.get(&stage)?.iter().copied().collect();
+        StageStats::from_samples(&stage)
+    }
+}
+
+impl Default for TimingCollector {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Recorder for TimingCollector {
+    fn record(&self, stage: Stage, elapsed: Duration) {
+        TimingCollector::record(self, stage, elapsed);
+    }
+}
+
+/// Where one run's time went, in milliseconds. Stage totals are summed over
+/// every symbol, so with symbols scanned concurrently they can add up to
+/// more than the wall clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunTimings {
+    pub fetch_ms: u64,
+    pub indicators_ms: u64,
+    pub render_ms: u64,
+    pub send_ms: u64,
+    pub wall_ms: u64,
+}
+
+impl RunTimings {
+    pub fn wall(&self) -> Duration {
+        Duration::from_millis(self.wall_ms)
+    }
+}
+
+/// Running per-stage totals for one run, shared by every task of its scans.
+#[derive(Debug, Default)]
+pub struct ScanTimings {
+    totals: Mutex<HashMap<Stage, Duration>>,
+}
+
+impl ScanTimings {
+    pub fn total(&self, stage: Stage) -> Duration {
+        let totals = self.totals.lock().unwrap();
+        totals.get(&stage).copied().unwrap_or_default()
+    }
+
+    /// The totals so far, for a run that took `wall` end to end.
+    pub fn finish(&self, wall: Duration) -> RunTimings {
+        let ms = |d: Duration| u64::try_from(d.as_millis()).unwrap_or(u64::MAX);
+        RunTimings {
+            fetch_ms: ms(self.total(Stage::Fetch)),
+            indicators_ms: ms(self.total(Stage::Indicators)),
+            render_ms: ms(self.total(Stage::Render)),
+            send_ms: ms(self.total(Stage::Send)),
+            wall_ms: ms(wall),
+        }
+    }
+}
+
+impl Recorder for ScanTimings {
+    fn record(&self, stage: Stage, elapsed: Duration) {
+        let mut totals = self.totals.lock().unwrap();
+        *totals.entry(stage).or_default() += elapsed;
+    }
+}
+
+/// Middle of `samples`, in any order, or the mean of the middle two for an
+/// even count. None when there are none.
+pub fn median(samples: &[Duration]) -> Option<Duration> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(sorted[mid]),
+        _ => Some((sorted[mid - 1] + sorted[mid]) / 2),
+    }
+}
+
+/// The usual wall clock when `current` took more than
+/// [`DEGRADATION_FACTOR`] times it, judged against the most recent
+/// [`DEGRADATION_WINDOW`] of `history`, newest first. None when the run is
+/// within bounds or there are fewer than [`MIN_HISTORY`] runs to go on.
+pub fn degraded(current: Duration, history: &[Duration]) -> Option<Duration> {
+    let recent = &history[..history.len().min(DEGRADATION_WINDOW)];
+    if recent.len() < MIN_HISTORY {
+        return None;
+    }
+    let usual = median(recent)?;
+    (current.as_secs_f64() > usual.as_secs_f64() * DEGRADATION_FACTOR).then_some(usual)
+}
@@ -0,0 +1,137 @@
+//! Alpaca request budget.
+//!
+//! The free tier allows [`DEFAULT_LIMIT`] requests a minute across every
+//! caller sharing the key. [`UsageTracker`] counts requests in a sliding
+//! window so long jobs like the daily scan can slow down before the budget
+//! runs out instead of failing halfway through.
+
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Requests a minute Alpaca's free tier allows.
+pub const DEFAULT_LIMIT: u32 = 200;
+
+/// Span the budget applies to.
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+/// Point-in-time view of the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiUsage {
+    /// Requests sent in the last [`WINDOW`].
+    pub used: u32,
+    pub limit: u32,
+    /// Requests left, taking Alpaca's own count into account when it has
+    /// reported one recently.
+    pub remaining: u32,
+}
+
+/// Sliding-window request counter. Methods taking an [`Instant`] let tests
+/// drive the clock; the others use `Instant::now()`.
+pub struct UsageTracker {
+    limit: u32,
+    inner: Mutex<Window>,
+}
+
+#[derive(Default)]
+struct Window {
+    sent: VecDeque<Instant>,
+    /// Last `X-RateLimit-Remaining` Alpaca returned, and when.
+    reported: Option<(Instant, u32)>,
+}
+
+impl Window {
+    fn prune(&mut self, now: Instant) {
+        while let Some(&at) = self.sent.front() {
+            if now.saturating_duration_since(at) < WINDOW {
+                break;
+            }
+            self.sent.pop_front();
+        }
+        if let Some((at, _)) = self.reported
+            && now.saturating_duration_since(at) >= WINDOW
+        {
+            self.reported = None;
+        }
+    }
+}
+
+impl UsageTracker {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            inner: Mutex::new(Window::default()),
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Count a request sent now.
+    pub fn record(&self) {
+        self.record_at(Instant::now());
+    }
+
+    pub fn record_at(&self, at: Instant) {
+        let mut window = self.inner.lock().unwrap();
+        window.prune(at);
+        window.sent.push_back(at);
+    }
+
+    /// Take Alpaca's `X-RateLimit-Remaining` as of now. Requests from other
+    /// processes sharing the key only show up here.
+    pub fn observe_remaining(&self, remaining: u32) {
+        self.observe_remaining_at(Instant::now(), remaining);
+    }
+
+    pub fn observe_remaining_at(&self, at: Instant, remaining: u32) {
+        let mut window = self.inner.lock().unwrap();
+        window.prune(at);
+        window.reported = Some((at, remaining));
+    }
+
+    pub fn usage(&self) -> ApiUsage {
+        self.usage_at(Instant::now())
+    }
+
+    /// Requests sent in the last [`WINDOW`].
+    pub fn usage_last_minute(&self) -> u32 {
+        self.usage().used
+    }
+
+    /// Requests left in the current window.
+    pub fn remaining_budget(&self) -> u32 {
+        self.usage().remaining
+    }
+
+    pub fn usage_at(&self, now: Instant) -> ApiUsage {
+        let mut window = self.inner.lock().unwrap();
+        window.prune(now);
+
+        let used = window.sent.len() as u32;
+        let mut remaining = self.limit.saturating_sub(used);
+        // Alpaca's count includes requests we never saw; trust whichever is
+        // lower, minus what we've sent since it was reported
+        if let Some((at, reported)) = window.reported {
+            let since = window.sent.iter().filter(|&&t| t > at).count() as u32;
+            remaining = remaining.min(reported.saturating_sub(since));
+        }
+
+        ApiUsage {
+            used,
+            limit: self.limit,
+            remaining,
+        }
+    }
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT)
+    }
+}
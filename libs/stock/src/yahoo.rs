@@ -0,0 +1,289 @@
+//! Bars and latest prices from Yahoo Finance's chart API, a fallback for
+//! when Alpaca is out.
+//!
+//! The API takes no key and carries no usage budget, but it only answers
+//! what a chart needs: bars and the latest trade. Everything else falls to
+//! the [`PriceSource`] defaults, so a [`FailoverSource`] keeps asking the
+//! other sources for it.
+//!
+//! [`FailoverSource`]: crate::failover::FailoverSource
+
+use std::collections::HashMap;
+
+use anyhow::{Error, Result, anyhow};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use futures::{StreamExt, future::BoxFuture, stream};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::{
+    Bar, PriceSource, RequestFailed, Session, Snapshot, Timeframe, Trade, calendar,
+    failover::is_retryable, validate_symbol,
+};
+
+/// Chart API used when `YAHOO_BASE_URL` isn't set.
+pub const DEFAULT_YAHOO_API: &str = "https://query1.finance.yahoo.com";
+
+/// Snapshot requests in flight at once; the chart API has one per symbol.
+const SNAPSHOT_CONCURRENCY: usize = 8;
+
+/// Yahoo Finance's chart API. See the [module docs](self).
+pub struct YahooSource {
+    client: Client,
+    base_api: String,
+}
+
+impl YahooSource {
+    pub fn new(base_api: String) -> Result<Self> {
+        // without a browser-like agent the API turns requests down
+        let client = Client::builder()
+            .user_agent("Mozilla/5.0 (compatible; stock-bot)")
+            .build()?;
+        info!(%base_api, "yahoo source initialized");
+        Ok(Self { client, base_api })
+    }
+
+    /// Source on `YAHOO_BASE_URL`, [`DEFAULT_YAHOO_API`] when unset.
+    pub fn from_env() -> Result<Self> {
+        let base_api = std::env::var("YAHOO_BASE_URL")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_YAHOO_API.to_string());
+        Self::new(base_api.trim().to_string())
+    }
+
+    /// URL of the chart endpoint for `symbol`, after validating it. Yahoo
+    /// writes share classes with a dash: BRK.B is BRK-B.
+    pub fn chart_url(&self, symbol: &str) -> Result<Url, Error> {
+        validate_symbol(symbol)?;
+        let mut url = Url::parse(&self.base_api)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("base URL {} can't take a path", self.base_api))?
+            .pop_if_empty()
+            .extend(["v8", "finance", "chart", &symbol.replace('.', "-")]);
+        Ok(url)
+    }
+
+    /// The chart for `symbol` from `start` to `end` in `interval` bars.
+    async fn chart(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: &str,
+    ) -> Result<ChartResult> {
+        let url = self.chart_url(symbol)?;
+        let res = self
+            .client
+            .get(url)
+            .query(&[
+                ("period1", start.timestamp().to_string()),
+                ("period2", end.timestamp().to_string()),
+                ("interval", interval.to_string()),
+                ("includePrePost", "false".to_string()),
+            ])
+            .send()
+            .await?;
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(RequestFailed::new(
+                status,
+                format!("yahoo chart request failed with {status}: {body}"),
+            )
+            .into());
+        }
+        let chart: ChartResponse = res.json().await?;
+        if let Some(error) = chart.chart.error {
+            return Err(anyhow!(
+                "yahoo chart error for {symbol}: {}",
+                error.description
+            ));
+        }
+        chart
+            .chart
+            .result
+            .and_then(|mut results| results.pop())
+            .ok_or_else(|| anyhow!("yahoo returned no chart for {symbol}"))
+    }
+}
+
+/// Yahoo's name for `timeframe`'s bars.
+fn interval(timeframe: Timeframe) -> &'static str {
+    match timeframe {
+        Timeframe::Minute1 => "1m",
+        Timeframe::Minute5 => "5m",
+        Timeframe::Minute15 => "15m",
+        Timeframe::Minute30 => "30m",
+        Timeframe::Hour1 => "60m",
+        Timeframe::Day1 => "1d",
+        Timeframe::Week1 => "1wk",
+        Timeframe::Month1 => "1mo",
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chart {
+    result: Option<Vec<ChartResult>>,
+    error: Option<ChartError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartError {
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartResult {
+    meta: ChartMeta,
+    #[serde(default)]
+    timestamp: Vec<i64>,
+    indicators: Indicators,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartMeta {
+    regular_market_price: Option<f64>,
+    regular_market_time: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Indicators {
+    quote: Vec<Quotes>,
+}
+
+/// One column per field, a null where Yahoo has no trade for the bar.
+#[derive(Debug, Default, Deserialize)]
+struct Quotes {
+    #[serde(default)]
+    open: Vec<Option<f64>>,
+    #[serde(default)]
+    high: Vec<Option<f64>>,
+    #[serde(default)]
+    low: Vec<Option<f64>>,
+    #[serde(default)]
+    close: Vec<Option<f64>>,
+    #[serde(default)]
+    volume: Vec<Option<f64>>,
+}
+
+impl ChartResult {
+    /// The chart's bars, oldest first, skipping those Yahoo left empty.
+    /// Bars a day or longer are stamped midnight New York time, as
+    /// Alpaca's are, rather than at the open.
+    fn bars(self, timeframe: Timeframe) -> Vec<Bar> {
+        let quotes = self.indicators.quote.into_iter().next().unwrap_or_default();
+        let at = |column: &[Option<f64>], i: usize| column.get(i).copied().flatten();
+        self.timestamp
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &t)| {
+                let mut timestamp = Utc.timestamp_opt(t, 0).single()?;
+                if !timeframe.is_intraday() {
+                    let midnight = calendar::session_date(timestamp).and_hms_opt(0, 0, 0)?;
+                    timestamp = New_York
+                        .from_local_datetime(&midnight)
+                        .single()?
+                        .with_timezone(&Utc);
+                }
+                Some(Bar {
+                    timestamp,
+                    open: at(&quotes.open, i)?,
+                    high: at(&quotes.high, i)?,
+                    low: at(&quotes.low, i)?,
+                    close: at(&quotes.close, i)?,
+                    volume: at(&quotes.volume, i).unwrap_or_default(),
+                    trade_count: None,
+                    vwap: None,
+                })
+            })
+            .collect()
+    }
+
+    /// The latest trade, from the chart's header.
+    fn snapshot(&self) -> Option<Snapshot> {
+        let price = self.meta.regular_market_price?;
+        let timestamp = Utc
+            .timestamp_opt(self.meta.regular_market_time?, 0)
+            .single()?;
+        Some(Snapshot {
+            latest_trade: Some(Trade { timestamp, price }),
+            latest_quote: None,
+            daily_bar: None,
+            prev_daily_bar: None,
+        })
+    }
+}
+
+impl PriceSource for YahooSource {
+    fn fetch_price<'a>(
+        &'a self,
+        symbol: &'a str,
+        duration: Duration,
+        timeframe: Timeframe,
+        limit: usize,
+        _bypass_cache: bool,
+        session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            let end = Utc::now();
+            let chart = self
+                .chart(symbol, end - duration, end, interval(timeframe))
+                .await?;
+            let mut bars: Vec<Bar> = chart
+                .bars(timeframe)
+                .into_iter()
+                .filter(|bar| session.includes(timeframe, bar.timestamp))
+                .collect();
+            let excess = bars.len().saturating_sub(limit);
+            bars.drain(..excess);
+            debug!(%symbol, bars = bars.len(), "fetched yahoo bars");
+            Ok(bars)
+        })
+    }
+
+    /// Latest snapshots from each symbol's chart header. An outage fails
+    /// the lot so the next source is asked; a symbol Yahoo doesn't know is
+    /// left out.
+    fn fetch_prices<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(async move {
+            let end = Utc::now();
+            let mut charts = stream::iter(symbols)
+                .map(|symbol| async move {
+                    let chart = self
+                        .chart(
+                            symbol,
+                            end - Duration::days(1),
+                            end,
+                            interval(Timeframe::Day1),
+                        )
+                        .await;
+                    (symbol, chart)
+                })
+                .buffer_unordered(SNAPSHOT_CONCURRENCY);
+
+            let mut snapshots = HashMap::with_capacity(symbols.len());
+            while let Some((symbol, chart)) = charts.next().await {
+                match chart {
+                    Ok(chart) => {
+                        snapshots.extend(chart.snapshot().map(|s| (symbol.clone(), s)));
+                    }
+                    Err(e) if is_retryable(&e) => return Err(e),
+                    Err(e) => warn!(%symbol, error = ?e, "no yahoo snapshot"),
+                }
+            }
+            Ok(snapshots)
+        })
+    }
+}
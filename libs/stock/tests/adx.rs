@@ -0,0 +1,56 @@
+use stock::indicators::adx::{DEFAULT_PERIOD, calculate};
+
+fn bars(closes: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let highs = closes.iter().map(|c| c + 0.5).collect();
+    let lows = closes.iter().map(|c| c - 0.5).collect();
+    (highs, lows)
+}
+
+#[test]
+fn warms_up_over_two_periods() {
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+    let (highs, lows) = bars(&closes);
+
+    let adx = calculate(&highs, &lows, &closes, DEFAULT_PERIOD);
+    assert_eq!(adx.len(), closes.len());
+    let warmup = 2 * DEFAULT_PERIOD - 1;
+    assert!(adx[..warmup].iter().all(|v| v.is_nan()));
+    assert!(adx[warmup..].iter().all(|v| !v.is_nan()));
+}
+
+#[test]
+fn steady_trend_reads_strong() {
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 - i as f64).collect();
+    let (highs, lows) = bars(&closes);
+
+    let adx = calculate(&highs, &lows, &closes, DEFAULT_PERIOD);
+    assert!(*adx.last().unwrap() > 90.0);
+}
+
+#[test]
+fn chop_reads_weak() {
+    let closes: Vec<f64> = (0..60)
+        .map(|i| if i % 2 == 0 { 101.0 } else { 99.0 })
+        .collect();
+    let (highs, lows) = bars(&closes);
+
+    let adx = calculate(&highs, &lows, &closes, DEFAULT_PERIOD);
+    assert!(*adx.last().unwrap() < 20.0);
+}
+
+#[test]
+fn short_history_and_zero_period_are_all_nan() {
+    let closes = vec![100.0; 10];
+    let (highs, lows) = bars(&closes);
+
+    assert!(
+        calculate(&highs, &lows, &closes, DEFAULT_PERIOD)
+            .iter()
+            .all(|v| v.is_nan())
+    );
+    assert!(
+        calculate(&highs, &lows, &closes, 0)
+            .iter()
+            .all(|v| v.is_nan())
+    );
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use stock::alert::{self, Alert, Direction, Mode, crossed_pct};
+
+use common::redis_store;
+
+const USER: u64 = 42;
+
+fn alerts() -> Vec<Alert> {
+    let t0 = Utc.with_ymd_and_hms(2024, 7, 1, 14, 0, 0).unwrap();
+    vec![
+        Alert::new("tsla", Direction::Below, 180.0, t0),
+        Alert::new("AAPL", Direction::Above, 250.0, t0 + Duration::seconds(1)),
+        Alert::new("AAPL", Direction::Below, 200.0, t0 + Duration::seconds(2)),
+    ]
+}
+
+fn t(mins: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 7, 1, 14, 0, 0).unwrap() + Duration::minutes(mins)
+}
+
+fn recurring(direction: Direction, level: f64, cooldown_mins: u64) -> Alert {
+    Alert::new("SPY", direction, level, t(0)).with_mode(Mode::Recurring {
+        cooldown: StdDuration::from_secs(cooldown_mins * 60),
+    })
+}
+
+/// Feed one price every 15 minutes, returning which evaluations fired.
+fn run(alert: &mut Alert, prices: &[f64]) -> Vec<bool> {
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| alert.evaluate(p, t(15 * i as i64)))
+        .collect()
+}
+
+fn ids(alerts: &[Alert]) -> Vec<String> {
+    alerts.iter().map(|a| a.id.clone()).collect()
+}
+
+#[test]
+fn alerts_sort_by_symbol_then_price() {
+    let mut list = alerts();
+    alert::sort(&mut list);
+
+    let labels: Vec<String> = list.iter().map(|a| a.to_string()).collect();
+    assert_eq!(
+        labels,
+        vec![
+            "AAPL below $200.00",
+            "AAPL above $250.00",
+            "TSLA below $180.00"
+        ]
+    );
+}
+
+#[test]
+fn ids_are_unique_and_uppercase() {
+    let list = alerts();
+    let ids = ids(&list);
+    assert_ne!(ids[0], ids[1]);
+    assert!(ids.iter().all(|id| *id == id.to_uppercase()));
+}
+
+#[test]
+fn split_selected_partitions_by_id() {
+    let list = alerts();
+    let pick = vec![list[2].id.to_lowercase(), "missing".to_string()];
+
+    let (removed, kept) = alert::split_selected(list.clone(), &pick);
+
+    assert_eq!(removed, vec![list[2].clone()]);
+    assert_eq!(kept, vec![list[0].clone(), list[1].clone()]);
+}
+
+#[test]
+fn split_selected_with_no_ids_keeps_everything() {
+    let list = alerts();
+    let (removed, kept) = alert::split_selected(list.clone(), &[]);
+    assert!(removed.is_empty());
+    assert_eq!(kept, list);
+}
+
+#[tokio::test]
+async fn list_and_remove_alerts() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let list = alerts();
+    for a in &list {
+        store.save_alert(USER, a).await.unwrap();
+    }
+
+    let stored = store.list_alerts(USER).await.unwrap();
+    assert_eq!(stored.len(), 3);
+    assert_eq!(stored[0].symbol, "AAPL");
+    assert!(store.list_alerts(USER + 1).await.unwrap().is_empty());
+
+    let removed = store
+        .remove_alerts(USER, &[list[0].id.clone(), "missing".into()])
+        .await
+        .unwrap();
+    assert_eq!(removed, vec![list[0].clone()]);
+    assert_eq!(store.list_alerts(USER).await.unwrap().len(), 2);
+}
+
+#[test]
+fn one_shot_fires_once_then_goes_dormant() {
+    let mut alert = Alert::new("SPY", Direction::Above, 500.0, t(0));
+
+    assert_eq!(
+        run(&mut alert, &[495.0, 501.0, 490.0, 505.0]),
+        vec![false, true, false, false]
+    );
+    assert!(alert.is_dormant());
+    assert_eq!(alert.last_fired_at, Some(t(15)));
+}
+
+#[test]
+fn recurring_does_not_refire_while_staying_past_the_level() {
+    let mut alert = recurring(Direction::Above, 500.0, 0);
+
+    assert_eq!(
+        run(&mut alert, &[495.0, 501.0, 503.0, 510.0, 502.0]),
+        vec![false, true, false, false, false]
+    );
+    assert_eq!(alert.side, Some(Direction::Above));
+}
+
+#[test]
+fn recurring_rearms_after_crossing_back() {
+    let mut alert = recurring(Direction::Above, 500.0, 0);
+
+    assert_eq!(
+        run(&mut alert, &[495.0, 501.0, 499.0, 501.0]),
+        vec![false, true, false, true]
+    );
+}
+
+#[test]
+fn hovering_around_the_level_is_limited_by_the_cooldown() {
+    // crosses every 15 minutes, but may only fire once an hour
+    let mut alert = recurring(Direction::Above, 500.0, 60);
+    let prices = [
+        499.9, 500.1, 499.9, 500.1, 499.9, 500.1, 499.9, 500.1, 500.2,
+    ];
+
+    assert_eq!(
+        run(&mut alert, &prices),
+        vec![false, true, false, false, false, true, false, false, false]
+    );
+}
+
+#[test]
+fn crossing_inside_the_cooldown_fires_when_it_ends() {
+    let mut alert = recurring(Direction::Below, 100.0, 45);
+
+    // fires at 15, re-arms at 30, crosses again at 45 inside the cooldown,
+    // still below at 60 when the cooldown is over
+    assert_eq!(
+        run(&mut alert, &[101.0, 99.0, 101.0, 99.0, 98.0, 97.0]),
+        vec![false, true, false, false, true, false]
+    );
+}
+
+#[test]
+fn exactly_at_the_level_counts_as_reached() {
+    let mut above = Alert::new("SPY", Direction::Above, 500.0, t(0));
+    assert!(above.evaluate(500.0, t(0)));
+
+    let mut below = Alert::new("SPY", Direction::Below, 500.0, t(0));
+    assert!(below.evaluate(500.0, t(0)));
+}
+
+#[test]
+fn alerts_saved_before_modes_default_to_one_shot() {
+    let raw = r#"{"id":"A1","symbol":"SPY","direction":"above","price":500.0,"created_at":"2024-07-01T14:00:00Z"}"#;
+    let alert: Alert = serde_json::from_str(raw).unwrap();
+    assert_eq!(alert.mode, Mode::OneShot);
+    assert_eq!(alert.last_fired_at, None);
+
+    let recurring = recurring(Direction::Above, 500.0, 30);
+    let round: Alert = serde_json::from_str(&serde_json::to_string(&recurring).unwrap()).unwrap();
+    assert_eq!(round, recurring);
+}
+
+#[test]
+fn crossed_pct_detects_moves_both_ways() {
+    assert_eq!(crossed_pct(100.0, 105.0, 5.0), Some(Direction::Above));
+    assert_eq!(crossed_pct(100.0, 112.0, 5.0), Some(Direction::Above));
+    assert_eq!(crossed_pct(100.0, 95.0, 5.0), Some(Direction::Below));
+    assert_eq!(crossed_pct(100.0, 80.0, 5.0), Some(Direction::Below));
+    assert_eq!(crossed_pct(100.0, 104.9, 5.0), None);
+    assert_eq!(crossed_pct(100.0, 95.1, 5.0), None);
+    assert_eq!(crossed_pct(0.0, 10.0, 5.0), None);
+}
+
+#[test]
+fn move_alert_fires_once_on_a_drop() {
+    let mut alert = Alert::on_move("aapl", 200.0, 5.0, t(0));
+    assert_eq!(alert.to_string(), "AAPL ±5% from $200.00");
+
+    assert_eq!(
+        run(&mut alert, &[198.0, 192.0, 189.0, 180.0]),
+        [false, false, true, false]
+    );
+    assert_eq!(alert.side, Some(Direction::Below));
+    assert!(alert.is_dormant());
+}
+
+#[test]
+fn move_alert_fires_on_a_rise() {
+    let mut alert = Alert::on_move("AAPL", 200.0, 5.0, t(0));
+    assert_eq!(run(&mut alert, &[205.0, 210.0]), [false, true]);
+    assert_eq!(alert.side, Some(Direction::Above));
+}
+
+#[test]
+fn rebasing_a_move_alert_measures_from_the_new_reference() {
+    let mut alert = Alert::on_move("AAPL", 200.0, 5.0, t(0));
+    assert!(alert.evaluate(210.0, t(15)));
+
+    alert.rebase(210.0, t(30));
+    assert!(!alert.is_dormant());
+    assert_eq!(alert.created_at, t(30));
+    // 212 would have been a 6% move from the old reference
+    assert!(!alert.evaluate(212.0, t(45)));
+    assert!(alert.evaluate(199.0, t(60)));
+    assert_eq!(alert.side, Some(Direction::Below));
+}
+
+#[test]
+fn level_alerts_deserialize_without_a_move() {
+    let raw = r#"{"id":"1","symbol":"SPY","direction":"above","price":500.0,"created_at":"2024-07-01T14:00:00Z"}"#;
+    let alert: Alert = serde_json::from_str(raw).unwrap();
+    assert_eq!(alert.move_pct, None);
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use stock::alias::{
+    AliasError, MAX_ALIAS_LEN, Resolved, builtin, is_known_ticker, normalize, resolve, validate,
+};
+
+fn custom(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, symbol)| (name.to_string(), symbol.to_string()))
+        .collect()
+}
+
+/// No ticker is watched or listed.
+fn unused(_: &str) -> bool {
+    false
+}
+
+fn via(symbol: &str, name: &str) -> Resolved {
+    Resolved {
+        symbol: symbol.into(),
+        via_alias: Some(name.into()),
+    }
+}
+
+fn as_typed(symbol: &str) -> Resolved {
+    Resolved {
+        symbol: symbol.into(),
+        via_alias: None,
+    }
+}
+
+#[test]
+fn names_normalize_to_single_spaced_lowercase() {
+    assert_eq!(normalize("  Home   Depot "), "home depot");
+    assert_eq!(normalize("APPLE"), "apple");
+    assert_eq!(normalize("   "), "");
+}
+
+#[test]
+fn builtin_names_resolve_in_any_case() {
+    let none = HashMap::new();
+    assert_eq!(resolve("apple", &none, unused), via("AAPL", "apple"));
+    assert_eq!(resolve(" Google ", &none, unused), via("GOOGL", "google"));
+    assert_eq!(resolve("Bitcoin", &none, unused), via("BTC/USD", "bitcoin"));
+    assert_eq!(
+        resolve("home  depot", &none, unused),
+        via("HD", "home depot")
+    );
+    assert_eq!(builtin("BERKSHIRE"), Some("BRK.B"));
+}
+
+#[test]
+fn tickers_and_unknown_input_pass_through_uppercased() {
+    let none = HashMap::new();
+    assert_eq!(resolve("aapl", &none, unused), as_typed("AAPL"));
+    assert_eq!(resolve(" nvda ", &none, unused), as_typed("NVDA"));
+    assert_eq!(resolve("zzzz", &none, unused), as_typed("ZZZZ"));
+    assert_eq!(resolve("brk.b", &none, unused), as_typed("BRK.B"));
+}
+
+#[test]
+fn slang_that_is_its_own_ticker_is_not_reported_as_an_alias() {
+    let none = HashMap::new();
+    assert_eq!(resolve("spy", &none, unused), as_typed("SPY"));
+    assert_eq!(resolve("qqq", &none, unused), as_typed("QQQ"));
+    assert_eq!(resolve("amd", &none, unused), as_typed("AMD"));
+}
+
+#[test]
+fn guild_aliases_win_over_builtin_ones() {
+    let aliases = custom(&[("apple", "APLE"), ("fruit", "AAPL")]);
+    assert_eq!(resolve("apple", &aliases, unused), via("APLE", "apple"));
+    assert_eq!(resolve("Fruit", &aliases, unused), via("AAPL", "fruit"));
+    // names the guild didn't touch still use the table
+    assert_eq!(resolve("tesla", &aliases, unused), via("TSLA", "tesla"));
+}
+
+#[test]
+fn known_tickers_beat_any_alias() {
+    // stored before the check existed, or written by hand: still ignored
+    let aliases = custom(&[("tsla", "F"), ("spy", "VOO")]);
+    assert_eq!(resolve("tsla", &aliases, unused), as_typed("TSLA"));
+    assert_eq!(resolve("SPY", &aliases, unused), as_typed("SPY"));
+}
+
+#[test]
+fn valid_aliases_come_back_normalized() {
+    assert_eq!(
+        validate("  My   Fave ", " nvda "),
+        Ok(("my fave".to_string(), "NVDA".to_string()))
+    );
+    assert_eq!(
+        validate("digital gold", "btc/usd"),
+        Ok(("digital gold".to_string(), "BTC/USD".to_string()))
+    );
+}
+
+#[test]
+fn aliases_cannot_shadow_known_tickers() {
+    assert!(is_known_ticker("aapl"));
+    assert!(is_known_ticker("VOO"));
+    assert!(!is_known_ticker("apple"));
+
+    assert_eq!(
+        validate("aapl", "MSFT"),
+        Err(AliasError::ShadowsTicker("AAPL".into()))
+    );
+    assert_eq!(
+        validate("Spy", "VOO"),
+        Err(AliasError::ShadowsTicker("SPY".into()))
+    );
+}
+
+#[test]
+fn alias_names_must_be_present_and_short() {
+    assert_eq!(validate("   ", "AAPL"), Err(AliasError::Empty));
+    let long = "x".repeat(MAX_ALIAS_LEN + 1);
+    assert_eq!(validate(&long, "AAPL"), Err(AliasError::TooLong));
+    let longest = "x".repeat(MAX_ALIAS_LEN);
+    assert!(validate(&longest, "AAPL").is_ok());
+}
+
+#[test]
+fn alias_targets_must_be_tickers() {
+    assert_eq!(
+        validate("fruit", "apple inc"),
+        Err(AliasError::InvalidSymbol("APPLE INC".into()))
+    );
+    assert_eq!(
+        validate("fruit", ""),
+        Err(AliasError::InvalidSymbol(String::new()))
+    );
+}
+
+#[test]
+fn builtin_names_never_stand_for_a_listed_ticker() {
+    let none = HashMap::new();
+    for name in ["hp", "coke", "btc", "eth"] {
+        assert_eq!(builtin(name), None, "{name}");
+        assert_eq!(resolve(name, &none, unused), as_typed(&name.to_uppercase()));
+    }
+    // the names behind them still resolve
+    assert_eq!(resolve("bitcoin", &none, unused), via("BTC/USD", "bitcoin"));
+    assert_eq!(resolve("coca cola", &none, unused), via("KO", "coca cola"));
+}
+
+#[test]
+fn watched_or_listed_tickers_beat_builtin_names() {
+    let none = HashMap::new();
+    let listed = |symbol: &str| ["GOLD", "UBER"].contains(&symbol);
+    assert_eq!(resolve("gold", &none, listed), as_typed("GOLD"));
+    assert_eq!(resolve(" Gold ", &none, listed), as_typed("GOLD"));
+    assert_eq!(resolve("gold", &none, unused), via("GLD", "gold"));
+    // names that aren't tickers are unaffected
+    assert_eq!(resolve("tesla", &none, listed), via("TSLA", "tesla"));
+}
+
+#[test]
+fn guild_aliases_apply_even_over_a_listed_ticker() {
+    let aliases = custom(&[("gold", "IAU")]);
+    let listed = |symbol: &str| symbol == "GOLD";
+    assert_eq!(resolve("gold", &aliases, listed), via("IAU", "gold"));
+}
@@ -0,0 +1,110 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use stock::indicators::annotation::{AnnotationKind, points, snap};
+
+fn day(m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, m, d).unwrap()
+}
+
+/// Sessions from March 25 to April 5, 2024: weekdays, less Good Friday
+/// (March 29).
+fn sessions() -> Vec<NaiveDate> {
+    day(3, 25)
+        .iter_days()
+        .take_while(|d| *d <= day(4, 5))
+        .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+        .filter(|d| *d != day(3, 29))
+        .collect()
+}
+
+#[test]
+fn trading_days_land_on_their_own_bar() {
+    let dates = sessions();
+    assert_eq!(snap(&dates, day(3, 25)), Some(0));
+    assert_eq!(snap(&dates, day(3, 28)), Some(3));
+    assert_eq!(snap(&dates, day(4, 1)), Some(4));
+    assert_eq!(snap(&dates, day(4, 5)), Some(dates.len() - 1));
+}
+
+#[test]
+fn weekends_and_holidays_snap_to_the_next_bar() {
+    let dates = sessions();
+    // Good Friday, then the weekend after it: all first traded on Monday
+    for date in [day(3, 29), day(3, 30), day(3, 31)] {
+        assert_eq!(dates[snap(&dates, date).unwrap()], day(4, 1), "{date}");
+    }
+}
+
+#[test]
+fn dates_outside_the_bars_are_skipped() {
+    let dates = sessions();
+    // the Sunday before the first bar is outside the window, not snapped in
+    assert_eq!(snap(&dates, day(3, 24)), None);
+    assert_eq!(snap(&dates, day(1, 2)), None);
+    // after the last bar: the Saturday following, and well past
+    assert_eq!(snap(&dates, day(4, 6)), None);
+    assert_eq!(snap(&dates, day(12, 31)), None);
+    assert_eq!(snap(&[], day(3, 25)), None);
+}
+
+#[test]
+fn intraday_bars_snap_to_the_first_of_the_session() {
+    let dates = vec![day(3, 27), day(3, 27), day(3, 28), day(3, 28), day(3, 28)];
+    assert_eq!(snap(&dates, day(3, 28)), Some(2));
+}
+
+#[test]
+fn points_are_counted_from_the_window_start() {
+    let dates = sessions();
+    let keep: Vec<usize> = (0..dates.len() - 3).collect();
+    let annotations = [
+        // before the window, which starts at the third bar
+        (day(3, 26), AnnotationKind::Earnings),
+        (day(3, 30), AnnotationKind::ExDividend),
+        (day(4, 2), AnnotationKind::Earnings),
+    ];
+    assert_eq!(
+        points(&dates, &annotations, 3, &keep),
+        vec![
+            (1, AnnotationKind::ExDividend),
+            (2, AnnotationKind::Earnings)
+        ]
+    );
+}
+
+#[test]
+fn events_on_dropped_points_move_to_the_next_kept_one() {
+    let dates = sessions();
+    // downsampled to every other bar, plus the last
+    let keep = vec![0, 2, 4, 6, 8];
+    let annotations = [
+        (day(3, 26), AnnotationKind::Earnings),
+        (day(3, 27), AnnotationKind::ExDividend),
+    ];
+    assert_eq!(
+        points(&dates, &annotations, 0, &keep),
+        vec![
+            (1, AnnotationKind::Earnings),
+            (1, AnnotationKind::ExDividend)
+        ]
+    );
+}
+
+#[test]
+fn repeated_events_are_drawn_once() {
+    let dates = sessions();
+    let keep: Vec<usize> = (0..dates.len()).collect();
+    let annotations = [
+        (day(3, 29), AnnotationKind::ExDividend),
+        (day(4, 1), AnnotationKind::ExDividend),
+    ];
+    assert_eq!(
+        points(&dates, &annotations, 0, &keep),
+        vec![(4, AnnotationKind::ExDividend)]
+    );
+}
+
+#[test]
+fn labels_are_single_letters() {
+    assert_eq!(AnnotationKind::Earnings.label(), "E");
+    assert_eq!(AnnotationKind::ExDividend.label(), "D");
+}
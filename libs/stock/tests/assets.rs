@@ -0,0 +1,96 @@
+use stock::assets::{SymbolInfo, parse, search, unlisted};
+
+/// An assets response in Alpaca's shape, trimmed to the fields read plus a
+/// couple that aren't.
+const ASSETS: &str = r#"[
+  {"id": "b0b6dd9d", "class": "us_equity", "exchange": "NASDAQ", "symbol": "AAPL",
+   "name": "Apple Inc. Common Stock", "status": "active", "tradable": true,
+   "marginable": true, "shortable": true},
+  {"id": "1", "class": "us_equity", "exchange": "NASDAQ", "symbol": "APLE",
+   "name": "Apple Hospitality REIT, Inc. Common Stock", "status": "active", "tradable": true},
+  {"id": "2", "class": "us_equity", "exchange": "NASDAQ", "symbol": "AAPLW",
+   "name": "Made-up Warrant", "status": "active", "tradable": true},
+  {"id": "3", "class": "us_equity", "exchange": "NYSE", "symbol": "PINE",
+   "name": "Alpine Income Property Trust, Inc.", "status": "active", "tradable": true},
+  {"id": "4", "class": "us_equity", "exchange": "NASDAQ", "symbol": "MSFT",
+   "name": "Microsoft Corporation Common Stock", "status": "active", "tradable": true},
+  {"id": "5", "class": "us_equity", "exchange": "OTC", "symbol": "OLDCO",
+   "name": "Delisted Apple Supplier", "status": "inactive", "tradable": false},
+  {"id": "6", "class": "us_equity", "exchange": "NYSE", "symbol": "HALT",
+   "name": "Untradable Apple Corp", "status": "active", "tradable": false},
+  {"id": "7", "class": "us_equity", "exchange": "NYSE", "symbol": "NONAME",
+   "status": "active", "tradable": true}
+]"#;
+
+fn listed() -> Vec<SymbolInfo> {
+    parse(ASSETS).unwrap()
+}
+
+fn symbols(found: &[SymbolInfo]) -> Vec<&str> {
+    found.iter().map(|s| s.symbol.as_str()).collect()
+}
+
+#[test]
+fn parse_keeps_active_tradable_assets_sorted() {
+    let listed = listed();
+    assert_eq!(
+        symbols(&listed),
+        vec!["AAPL", "AAPLW", "APLE", "MSFT", "NONAME", "PINE"]
+    );
+    assert_eq!(
+        listed[0],
+        SymbolInfo {
+            symbol: "AAPL".into(),
+            name: "Apple Inc. Common Stock".into(),
+            exchange: "NASDAQ".into(),
+        }
+    );
+    // a missing name reads as empty rather than failing the whole list
+    assert_eq!(listed[4].name, "");
+}
+
+#[test]
+fn parse_rejects_something_that_isnt_an_asset_list() {
+    assert!(parse(r#"{"message": "forbidden"}"#).is_err());
+    assert_eq!(parse("[]").unwrap(), vec![]);
+}
+
+#[test]
+fn exact_ticker_comes_first_then_shorter_tickers() {
+    let found = search(&listed(), "aapl", 10);
+    assert_eq!(symbols(&found), vec!["AAPL", "AAPLW"]);
+}
+
+#[test]
+fn partial_tickers_match_by_prefix() {
+    assert_eq!(symbols(&search(&listed(), "ms", 10)), vec!["MSFT"]);
+    assert_eq!(symbols(&search(&listed(), "AP", 10)), vec!["APLE", "AAPL"]);
+}
+
+#[test]
+fn names_match_by_word_then_anywhere() {
+    // "Apple ..." names first, then "Alpine", which only contains it
+    let found = search(&listed(), "apple", 10);
+    assert_eq!(symbols(&found), vec!["AAPL", "APLE"]);
+    let found = search(&listed(), "pine", 10);
+    assert_eq!(symbols(&found), vec!["PINE"]);
+    let found = search(&listed(), "lpine", 10);
+    assert_eq!(symbols(&found), vec!["PINE"]);
+    let found = search(&listed(), "microsoft corp", 10);
+    assert_eq!(symbols(&found), vec!["MSFT"]);
+}
+
+#[test]
+fn search_stops_at_the_limit_and_ignores_blank_queries() {
+    assert_eq!(search(&listed(), "a", 2).len(), 2);
+    assert!(search(&listed(), "   ", 10).is_empty());
+    assert!(search(&listed(), "zzzz", 10).is_empty());
+}
+
+#[test]
+fn unlisted_names_symbols_missing_from_the_list() {
+    let candidates: Vec<String> = ["AAPL", "FAKE", "BTC/USD", "OLDCO"]
+        .map(String::from)
+        .to_vec();
+    assert_eq!(unlisted(&listed(), &candidates), vec!["FAKE", "OLDCO"]);
+}
@@ -0,0 +1,24 @@
+use stock::indicators::atr::{DEFAULT_PERIOD, calculate};
+
+#[test]
+fn constant_range_averages_to_that_range() {
+    let n = 40;
+    let closes = vec![100.0; n];
+    let highs = vec![101.0; n];
+    let lows = vec![99.0; n];
+
+    let atr = calculate(&highs, &lows, &closes, DEFAULT_PERIOD);
+    assert!(atr[..DEFAULT_PERIOD].iter().all(|v| v.is_nan()));
+    assert!((atr[n - 1] - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn gaps_count_toward_true_range() {
+    // each bar opens 10 above the last close with a range of 1
+    let closes: Vec<f64> = (0..40).map(|i| 100.0 + 10.0 * i as f64).collect();
+    let highs: Vec<f64> = closes.iter().map(|c| c + 0.5).collect();
+    let lows: Vec<f64> = closes.iter().map(|c| c - 0.5).collect();
+
+    let atr = calculate(&highs, &lows, &closes, DEFAULT_PERIOD);
+    assert!(*atr.last().unwrap() > 9.0);
+}
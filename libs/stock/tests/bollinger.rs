@@ -0,0 +1,26 @@
+use stock::indicators::bollinger::{DEFAULT_MULTIPLIER, DEFAULT_PERIOD, calculate};
+
+#[test]
+fn flat_prices_collapse_the_bands() {
+    let closes = vec![50.0; 30];
+    let (upper, middle, lower) = calculate(&closes, DEFAULT_PERIOD, DEFAULT_MULTIPLIER);
+
+    assert!(upper[..DEFAULT_PERIOD - 1].iter().all(|v| v.is_nan()));
+    for i in DEFAULT_PERIOD - 1..closes.len() {
+        assert_eq!((upper[i], middle[i], lower[i]), (50.0, 50.0, 50.0));
+    }
+}
+
+#[test]
+fn bands_straddle_the_average() {
+    let closes: Vec<f64> = (0..40)
+        .map(|i| if i % 2 == 0 { 101.0 } else { 99.0 })
+        .collect();
+    let (upper, middle, lower) = calculate(&closes, DEFAULT_PERIOD, DEFAULT_MULTIPLIER);
+
+    let last = closes.len() - 1;
+    assert!((middle[last] - 100.0).abs() < 1e-9);
+    // population deviation of an even ±1 wiggle is 1
+    assert!((upper[last] - 102.0).abs() < 1e-9);
+    assert!((lower[last] - 98.0).abs() < 1e-9);
+}
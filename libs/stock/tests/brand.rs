@@ -0,0 +1,123 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, Rgba, RgbaImage};
+use stock::indicators::cdc::{
+    BRAND_STRIP_HEIGHT, BrandOptions, ChartOptions, Signal, WATERMARK_MAX_CHARS, brand_chart,
+    calculate, generate_chart, sanitize_watermark,
+};
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let img = RgbaImage::from_pixel(width, height, Rgba([11, 12, 23, 255]));
+    let mut out = Vec::new();
+    img.write_to(&mut Cursor::new(&mut out), ImageFormat::Png)
+        .unwrap();
+    out
+}
+
+fn options(watermark: Option<&str>) -> BrandOptions {
+    BrandOptions {
+        bot_name: "Stock Bot".to_string(),
+        date: "2024-07-01".to_string(),
+        signal: Signal::Buy,
+        watermark: watermark.map(str::to_string),
+    }
+}
+
+fn dimensions(png: &[u8]) -> (u32, u32) {
+    let img = image::load_from_memory_with_format(png, ImageFormat::Png).unwrap();
+    (img.width(), img.height())
+}
+
+#[test]
+fn strip_is_added_below_the_chart() {
+    let branded = brand_chart(&png(1280, 720), &options(Some("r/stocks"))).unwrap();
+    assert_eq!(dimensions(&branded), (1280, 720 + BRAND_STRIP_HEIGHT));
+
+    // the chart itself is left as it was
+    let img = image::load_from_memory(&branded).unwrap().to_rgba8();
+    assert_eq!(*img.get_pixel(640, 360), Rgba([11, 12, 23, 255]));
+}
+
+#[test]
+fn a_chart_asked_to_be_branded_comes_with_the_strip() {
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 + f64::from(i % 7)).collect();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let dates: Vec<String> = (0..closes.len()).map(|i| format!("D{i}")).collect();
+    let chart = |brand| {
+        let options = ChartOptions {
+            brand,
+            ..Default::default()
+        };
+        generate_chart("AAPL", &closes, &ema12, &ema26, &dates, &options).unwrap()
+    };
+
+    let (width, height) = dimensions(&chart(None));
+    let branded = chart(Some(options(None)));
+    assert_eq!(dimensions(&branded), (width, height + BRAND_STRIP_HEIGHT));
+}
+
+#[test]
+fn odd_sizes_brand_without_panicking() {
+    for (width, height) in [(1, 1), (7, 3), (13, 2001), (3001, 5)] {
+        let branded = brand_chart(&png(width, height), &options(Some("watermark")))
+            .unwrap_or_else(|e| panic!("{width}x{height}: {e:?}"));
+        assert_eq!(dimensions(&branded), (width, height + BRAND_STRIP_HEIGHT));
+    }
+}
+
+#[test]
+fn every_signal_gets_a_badge() {
+    for signal in [
+        Signal::Buy,
+        Signal::Sell,
+        Signal::BullishZone,
+        Signal::BearishZone,
+        Signal::None,
+    ] {
+        let options = BrandOptions {
+            signal,
+            ..options(None)
+        };
+        assert!(brand_chart(&png(320, 200), &options).is_ok(), "{signal:?}");
+    }
+}
+
+#[test]
+fn markup_in_text_is_drawn_not_parsed() {
+    let options = BrandOptions {
+        bot_name: "<Bot & \"co\">".to_string(),
+        ..options(Some("</text><script/>'"))
+    };
+    assert!(brand_chart(&png(640, 360), &options).is_ok());
+}
+
+#[test]
+fn garbage_is_an_error() {
+    assert!(brand_chart(b"not a png", &options(None)).is_err());
+    assert!(brand_chart(&[], &options(None)).is_err());
+}
+
+#[test]
+fn watermarks_are_cleaned_and_capped() {
+    assert_eq!(
+        sanitize_watermark("  my\tserver\n\nname "),
+        Some("my server name".to_string())
+    );
+    assert_eq!(
+        sanitize_watermark("a\u{0}b\u{202E}c\u{200B}d"),
+        Some("abcd".to_string())
+    );
+    assert_eq!(sanitize_watermark(" \u{200B}\n "), None);
+    assert_eq!(sanitize_watermark(""), None);
+
+    let long = "x".repeat(100);
+    assert_eq!(
+        sanitize_watermark(&long).unwrap().chars().count(),
+        WATERMARK_MAX_CHARS
+    );
+    let thai = "ก".repeat(100);
+    assert_eq!(
+        sanitize_watermark(&thai).unwrap().chars().count(),
+        WATERMARK_MAX_CHARS
+    );
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use stock::indicators::cdc::{
+    DEFAULT_BAND_PCT, DEFAULT_MIN_CHART_BARS, Signal, SignalColors, calculate, confirm,
+    enough_to_chart, last_crossover, parse_hex_color,
+};
+
+use common::{crossover_closes, flat_closes};
+
+/// Flat closes, then a wiggle of `amp` around the same level.
+fn wiggle(amp: f64, n: usize) -> Vec<f64> {
+    let mut closes = vec![100.0; 40];
+    closes.extend((0..n).map(|i| if i % 2 == 0 { 100.0 + amp } else { 100.0 - amp }));
+    closes
+}
+
+fn signals(closes: &[f64], band_pct: f64) -> Vec<Signal> {
+    (41..=closes.len())
+        .map(|n| calculate(&closes[..n], band_pct).0)
+        .collect()
+}
+
+#[test]
+fn zero_band_flip_flops_on_a_tight_wiggle() {
+    let got = signals(&wiggle(0.05, 10), 0.0);
+    assert!(got.contains(&Signal::Buy), "{got:?}");
+    assert!(got.contains(&Signal::Sell), "{got:?}");
+}
+
+#[test]
+fn signal_holds_inside_the_band() {
+    let got = signals(&wiggle(0.05, 10), DEFAULT_BAND_PCT);
+    assert!(got.iter().all(|s| *s == Signal::BearishZone), "{got:?}");
+}
+
+#[test]
+fn decisive_move_still_flips() {
+    let mut closes = wiggle(0.05, 10);
+    closes.extend((1..=10).map(|i| 100.0 + i as f64));
+
+    let got = signals(&closes, DEFAULT_BAND_PCT);
+    let buy = got.iter().position(|s| *s == Signal::Buy).expect("a buy");
+    assert!(got[..buy].iter().all(|s| *s == Signal::BearishZone));
+    assert!(got[buy + 1..].iter().all(|s| *s == Signal::BullishZone));
+}
+
+#[test]
+fn band_delays_a_marginal_crossover() {
+    // the fixture crosses by a hair on its last bar
+    let mut closes = crossover_closes();
+    assert_eq!(calculate(&closes, 0.0).0, Signal::Buy);
+    assert_eq!(calculate(&closes, DEFAULT_BAND_PCT).0, Signal::BearishZone);
+
+    // and flips once the rally carries on
+    while calculate(&closes, DEFAULT_BAND_PCT).0 == Signal::BearishZone {
+        assert!(closes.len() < 80, "never flipped");
+        closes.push(closes.last().unwrap() + 2.0);
+    }
+    assert_eq!(calculate(&closes, DEFAULT_BAND_PCT).0, Signal::Buy);
+}
+
+#[test]
+fn short_series_has_no_signal() {
+    assert_eq!(calculate(&[1.0], DEFAULT_BAND_PCT).0, Signal::None);
+}
+
+#[test]
+fn hex_colors_parse_with_or_without_hash() {
+    assert_eq!(parse_hex_color("#00FF00").unwrap(), 0x00FF00);
+    assert_eq!(parse_hex_color("ff8000").unwrap(), 0xFF8000);
+    assert_eq!(parse_hex_color("  #0064ff ").unwrap(), 0x0064FF);
+}
+
+#[test]
+fn malformed_hex_colors_are_rejected() {
+    for raw in [
+        "",
+        "#",
+        "#FFF",
+        "#GG0000",
+        "0x00FF00",
+        "#00FF00FF",
+        "+12345",
+    ] {
+        assert!(parse_hex_color(raw).is_err(), "{raw:?} parsed");
+    }
+}
+
+#[test]
+fn hex_color_errors_say_what_is_wrong() {
+    let err = parse_hex_color("#GG0000").unwrap_err().to_string();
+    assert!(err.contains("'G'"), "{err}");
+    let err = parse_hex_color("#FFF").unwrap_err().to_string();
+    assert!(err.contains("3 hex digits"), "{err}");
+}
+
+#[test]
+fn signal_color_follows_the_scheme() {
+    let colors = SignalColors {
+        buy: 0x0064FF,
+        sell: 0xFF8000,
+        ..Default::default()
+    };
+
+    assert_eq!(Signal::Buy.color(&colors), 0x0064FF);
+    assert_eq!(Signal::Sell.color(&colors), 0xFF8000);
+    assert_eq!(Signal::BullishZone.color(&colors), 0x00FF00);
+    assert_eq!(Signal::None.color(&SignalColors::default()), 0xFFFFFF);
+}
+
+#[test]
+fn weekly_confirmation_truth_table() {
+    use Signal::*;
+
+    let weekly = [Buy, Sell, BullishZone, BearishZone, None];
+    let table = [
+        (Buy, [Buy, BullishZone, Buy, BullishZone, BullishZone]),
+        (Sell, [BearishZone, Sell, BearishZone, Sell, BearishZone]),
+        (BullishZone, [BullishZone; 5]),
+        (BearishZone, [BearishZone; 5]),
+        (None, [None; 5]),
+    ];
+
+    for (daily, expected) in table {
+        for (weekly, expected) in weekly.iter().zip(expected) {
+            assert_eq!(
+                confirm(daily, *weekly),
+                expected,
+                "daily {daily:?}, weekly {weekly:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn last_crossover_is_where_the_signal_fired() {
+    let closes = crossover_closes();
+    // the first prefix whose signal is a Buy ends on the crossover bar
+    let fired = (2..=closes.len())
+        .find(|&n| calculate(&closes[..n], 0.0).0 == Signal::Buy)
+        .unwrap();
+
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    assert_eq!(
+        last_crossover(&ema12, &ema26, 0.0),
+        Some((fired - 1, Signal::Buy))
+    );
+}
+
+#[test]
+fn last_crossover_finds_the_latest_of_several() {
+    let closes = wiggle(0.5, 9);
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let flips: Vec<usize> = (1..ema12.len())
+        .filter(|&i| (ema12[i] > ema26[i]) != (ema12[i - 1] > ema26[i - 1]))
+        .collect();
+    assert!(flips.len() > 1, "{flips:?}");
+
+    let (index, signal) = last_crossover(&ema12, &ema26, 0.0).unwrap();
+    assert_eq!(index, *flips.last().unwrap());
+    let expected = if ema12[index] > ema26[index] {
+        Signal::Buy
+    } else {
+        Signal::Sell
+    };
+    assert_eq!(signal, expected);
+}
+
+#[test]
+fn last_crossover_agrees_with_the_band() {
+    let closes = wiggle(0.05, 10);
+    let (signal, ema12, ema26) = calculate(&closes, DEFAULT_BAND_PCT);
+    let crossed = last_crossover(&ema12, &ema26, DEFAULT_BAND_PCT);
+    let fired_last = crossed.is_some_and(|(i, _)| i == closes.len() - 1);
+    assert_eq!(fired_last, matches!(signal, Signal::Buy | Signal::Sell));
+}
+
+#[test]
+fn no_crossover_in_a_flat_series() {
+    let (_, ema12, ema26) = calculate(&flat_closes(), 0.0);
+    assert_eq!(last_crossover(&ema12, &ema26, 0.0), None);
+}
+
+#[test]
+fn charts_need_the_minimum_bars() {
+    assert!(!enough_to_chart(3, DEFAULT_MIN_CHART_BARS));
+    assert!(!enough_to_chart(
+        DEFAULT_MIN_CHART_BARS - 1,
+        DEFAULT_MIN_CHART_BARS
+    ));
+    assert!(enough_to_chart(
+        DEFAULT_MIN_CHART_BARS,
+        DEFAULT_MIN_CHART_BARS
+    ));
+    // a floor of zero still needs something to draw
+    assert!(!enough_to_chart(0, 0));
+    assert!(enough_to_chart(1, 0));
+}
+
+#[test]
+fn short_histories_still_get_a_signal() {
+    let closes = &crossover_closes()[..DEFAULT_MIN_CHART_BARS - 1];
+    assert!(!enough_to_chart(closes.len(), DEFAULT_MIN_CHART_BARS));
+    assert_ne!(calculate(closes, DEFAULT_BAND_PCT).0, Signal::None);
+}
@@ -0,0 +1,545 @@
+use chrono::{Duration, NaiveDate};
+use serde_json::Value;
+use stock::{
+    ChartJob,
+    indicators::annotation::AnnotationKind,
+    indicators::cdc::{
+        Benchmark, CHART_HEIGHT, CHART_HISTORY, CHART_WIDTH, ChartOptions, ChartScale, ChartTheme,
+        IndicatorSet, Signal, build_chart, calculate, generate_chart, last_crossover, rasterize,
+        trend,
+    },
+};
+
+fn chart(indicators: IndicatorSet, volumes: bool) -> Value {
+    let closes: Vec<f64> = (0..120)
+        .map(|i| 100.0 + 10.0 * (i as f64 / 10.0).sin())
+        .collect();
+    let dates: Vec<String> = (0..closes.len()).map(|i| format!("d{i}")).collect();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let options = ChartOptions {
+        indicators,
+        volumes: volumes.then(|| vec![1_000.0; closes.len()]),
+        ..Default::default()
+    };
+
+    let chart = build_chart("TEST", &closes, &ema12, &ema26, &dates, &options).unwrap();
+    serde_json::to_value(&chart).unwrap()
+}
+
+fn series(chart: &Value) -> Vec<&str> {
+    chart["series"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect()
+}
+
+/// Number of x axes, which is one per panel.
+fn panels(chart: &Value) -> usize {
+    match &chart["xAxis"] {
+        Value::Array(axes) => axes.len(),
+        _ => 1,
+    }
+}
+
+#[test]
+fn default_draws_price_and_emas() {
+    let chart = chart(IndicatorSet::default(), false);
+    assert_eq!(
+        series(&chart),
+        vec!["Price (Bull)", "Price (Bear)", "EMA12", "EMA26"]
+    );
+    assert_eq!(panels(&chart), 1);
+}
+
+#[test]
+fn unselected_indicators_are_skipped() {
+    let chart = chart(IndicatorSet::NONE, true);
+    assert_eq!(series(&chart), vec!["Price (Bull)", "Price (Bear)"]);
+    assert_eq!(panels(&chart), 1);
+}
+
+#[test]
+fn bollinger_overlays_the_price_panel() {
+    let set = IndicatorSet {
+        bollinger: true,
+        ..IndicatorSet::NONE
+    };
+    let chart = chart(set, false);
+    assert_eq!(
+        series(&chart),
+        vec![
+            "Price (Bull)",
+            "Price (Bear)",
+            "Bollinger High",
+            "Bollinger Mid",
+            "Bollinger Low"
+        ]
+    );
+    assert_eq!(panels(&chart), 1);
+}
+
+#[test]
+fn every_indicator_gets_its_series_and_panels() {
+    let set = IndicatorSet {
+        ema: true,
+        bollinger: true,
+        rsi: true,
+        volume: true,
+        macd: true,
+        spread: true,
+    };
+    let chart = chart(set, true);
+    assert_eq!(
+        series(&chart),
+        vec![
+            "Price (Bull)",
+            "Price (Bear)",
+            "EMA12",
+            "EMA26",
+            "Bollinger High",
+            "Bollinger Mid",
+            "Bollinger Low",
+            "Volume",
+            "RSI",
+            "MACD Histogram",
+            "MACD",
+            "MACD Signal",
+            "Zero",
+            "Spread (Bull)",
+            "Spread (Bear)",
+        ]
+    );
+    assert_eq!(panels(&chart), 5);
+    assert_eq!(chart["grid"].as_array().unwrap().len(), 5);
+
+    // subpanel series sit on their own axes, in panel order
+    let axis = |name: &str| {
+        chart["series"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["name"] == name)
+            .unwrap()["yAxisIndex"]
+            .as_f64()
+    };
+    assert_eq!(axis("EMA12"), None);
+    assert_eq!(axis("Volume"), Some(1.0));
+    assert_eq!(axis("RSI"), Some(2.0));
+    assert_eq!(axis("MACD Signal"), Some(3.0));
+    assert_eq!(axis("Spread (Bear)"), Some(4.0));
+}
+
+#[test]
+fn spread_is_drawn_around_zero_in_the_signal_colors() {
+    let set = IndicatorSet {
+        spread: true,
+        ..IndicatorSet::NONE
+    };
+    let chart = chart(set, false);
+    assert_eq!(
+        series(&chart),
+        vec![
+            "Price (Bull)",
+            "Price (Bear)",
+            "Zero",
+            "Spread (Bull)",
+            "Spread (Bear)"
+        ]
+    );
+    assert_eq!(panels(&chart), 2);
+
+    let data = |i: usize| chart["series"][i]["data"].as_array().unwrap().clone();
+    assert!(data(2).iter().all(|v| v == 0.0));
+    // each point is on the side its sign says, and both sides are drawn
+    let (bull, bear) = (data(3), data(4));
+    assert!(bull.iter().any(|v| v.as_f64().is_some_and(|v| v > 0.0)));
+    assert!(bear.iter().any(|v| v.as_f64().is_some_and(|v| v < 0.0)));
+    assert_eq!(
+        chart["series"][3]["lineStyle"]["color"],
+        chart["series"][0]["lineStyle"]["color"]
+    );
+    assert_eq!(
+        chart["series"][4]["lineStyle"]["color"],
+        chart["series"][1]["lineStyle"]["color"]
+    );
+}
+
+#[test]
+fn volume_without_data_draws_no_panel() {
+    let set = IndicatorSet {
+        volume: true,
+        rsi: true,
+        ..IndicatorSet::NONE
+    };
+    let chart = chart(set, false);
+    assert_eq!(series(&chart), vec!["Price (Bull)", "Price (Bear)", "RSI"]);
+    assert_eq!(panels(&chart), 2);
+}
+
+#[test]
+fn indicator_lists_parse() {
+    assert_eq!("".parse::<IndicatorSet>().unwrap(), IndicatorSet::NONE);
+    assert_eq!(
+        " EMA , bb,vol ".parse::<IndicatorSet>().unwrap(),
+        IndicatorSet {
+            ema: true,
+            bollinger: true,
+            volume: true,
+            ..IndicatorSet::NONE
+        }
+    );
+    assert_eq!(
+        "rsi,macd".parse::<IndicatorSet>().unwrap(),
+        IndicatorSet {
+            rsi: true,
+            macd: true,
+            ..IndicatorSet::NONE
+        }
+    );
+    assert_eq!(
+        "spread".parse::<IndicatorSet>().unwrap(),
+        IndicatorSet {
+            spread: true,
+            ..IndicatorSet::NONE
+        }
+    );
+
+    let err = "ema,stoch".parse::<IndicatorSet>().unwrap_err().to_string();
+    assert!(err.contains("stoch"), "{err}");
+}
+
+#[test]
+fn benchmark_gets_a_panel_with_the_spread_on_its_own_axis() {
+    let closes: Vec<f64> = (0..120).map(|i| 100.0 + i as f64).collect();
+    let dates: Vec<String> = (0..closes.len()).map(|i| format!("d{i}")).collect();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let options = ChartOptions {
+        indicators: IndicatorSet::NONE,
+        benchmark: Some(Benchmark {
+            symbol: "spy".into(),
+            closes: vec![50.0; closes.len()],
+        }),
+        ..Default::default()
+    };
+
+    let chart = build_chart("TEST", &closes, &ema12, &ema26, &dates, &options).unwrap();
+    let chart = serde_json::to_value(&chart).unwrap();
+    assert_eq!(
+        series(&chart),
+        vec!["Price (Bull)", "Price (Bear)", "Spread", "TEST", "SPY"]
+    );
+    assert_eq!(panels(&chart), 2);
+
+    let spread = &chart["series"][2];
+    assert_eq!(spread["xAxisIndex"], 1.0);
+    assert_eq!(spread["yAxisIndex"], 2.0);
+    assert_eq!(chart["yAxis"][2]["position"], "right");
+
+    // both lines start the 90-bar window at 100
+    assert_eq!(chart["series"][3]["data"][0], 100.0);
+    assert_eq!(chart["series"][4]["data"][0], 100.0);
+    assert_eq!(spread["data"][0], 0.0);
+}
+
+#[test]
+fn mismatched_benchmark_is_rejected() {
+    let closes = vec![1.0; 10];
+    let dates: Vec<String> = (0..10).map(|i| format!("d{i}")).collect();
+    let options = ChartOptions {
+        benchmark: Some(Benchmark {
+            symbol: "SPY".into(),
+            closes: vec![1.0; 9],
+        }),
+        ..Default::default()
+    };
+    assert!(build_chart("TEST", &closes, &closes, &closes, &dates, &options).is_err());
+}
+
+#[test]
+fn themes_change_the_colors() {
+    let colors = |theme| {
+        let job = ChartJob::sample(theme);
+        let chart = build_chart(
+            &job.symbol,
+            &job.closes,
+            &job.ema12,
+            &job.ema26,
+            &job.dates,
+            &job.options,
+        )
+        .unwrap();
+        let chart = serde_json::to_value(&chart).unwrap();
+        (
+            chart["backgroundColor"].clone(),
+            chart["series"][0]["lineStyle"]["color"].clone(),
+        )
+    };
+
+    let dark = colors(ChartTheme::Dark);
+    assert_eq!(dark.0, "#0b0c17");
+    assert_ne!(colors(ChartTheme::Light).0, dark.0);
+    assert_ne!(colors(ChartTheme::Colorblind).1, dark.1);
+}
+
+#[test]
+fn theme_is_part_of_the_fingerprint() {
+    assert_eq!(
+        ChartJob::sample(ChartTheme::Dark).fingerprint(),
+        ChartJob::sample(ChartTheme::Dark).fingerprint()
+    );
+    assert_ne!(
+        ChartJob::sample(ChartTheme::Dark).fingerprint(),
+        ChartJob::sample(ChartTheme::Light).fingerprint()
+    );
+}
+
+#[test]
+fn scale_is_part_of_the_fingerprint() {
+    let mut retina = ChartJob::sample(ChartTheme::Dark);
+    retina.options.scale = ChartScale::X2;
+    assert_ne!(
+        ChartJob::sample(ChartTheme::Dark).fingerprint(),
+        retina.fingerprint()
+    );
+}
+
+fn dimensions(png: &[u8]) -> (u32, u32) {
+    let img = image::load_from_memory_with_format(png, image::ImageFormat::Png).unwrap();
+    (img.width(), img.height())
+}
+
+/// A stand-in for what echarts lays out: the chart's own size, with a line
+/// and some text to scale.
+fn chart_svg() -> String {
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{CHART_WIDTH}" height="{CHART_HEIGHT}">
+<rect width="{CHART_WIDTH}" height="{CHART_HEIGHT}" fill="#0b0c17"/>
+<path d="M0 700 L1280 20" stroke="#00d084" stroke-width="2"/>
+<text x="20" y="40" font-size="18" fill="#ffffff">TEST</text>
+</svg>"##
+    )
+}
+
+/// A real chart of [`rally_closes`] drawn at `scale`.
+fn rendered(scale: ChartScale) -> Vec<u8> {
+    let closes = rally_closes();
+    let dates: Vec<String> = (0..closes.len()).map(|i| format!("d{i}")).collect();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let options = ChartOptions {
+        scale,
+        ..Default::default()
+    };
+    generate_chart("TEST", &closes, &ema12, &ema26, &dates, &options).unwrap()
+}
+
+#[test]
+fn double_scale_doubles_the_pixels() {
+    let one = rendered(ChartScale::X1);
+    let two = rendered(ChartScale::X2);
+    assert_eq!(dimensions(&one), (CHART_WIDTH, CHART_HEIGHT));
+    assert_eq!(dimensions(&two), (CHART_WIDTH * 2, CHART_HEIGHT * 2));
+}
+
+#[test]
+fn double_scale_keeps_the_layout() {
+    // the line crosses the same spot of the picture at either scale
+    let one = image::load_from_memory(&rasterize(&chart_svg(), ChartScale::X1).unwrap())
+        .unwrap()
+        .to_rgba8();
+    let two = image::load_from_memory(&rasterize(&chart_svg(), ChartScale::X2).unwrap())
+        .unwrap()
+        .to_rgba8();
+    let background = image::Rgba([0x0b, 0x0c, 0x17, 0xff]);
+    assert_ne!(*one.get_pixel(640, 360), background);
+    assert_ne!(*two.get_pixel(1280, 720), background);
+    assert_eq!(*two.get_pixel(1280, 100), background);
+}
+
+/// Closes that fall, then rally into a Buy a few bars before the end.
+fn rally_closes() -> Vec<f64> {
+    let falling = (0..50).map(|i| 100.0 - i as f64 * 0.5);
+    let rally = (1..=12).map(|i| 75.5 + i as f64 * 2.0);
+    falling.chain(rally).collect()
+}
+
+fn annotated(closes: &[f64], annotate: bool) -> Value {
+    let dates: Vec<String> = (0..closes.len()).map(|i| format!("d{i}")).collect();
+    let (_, ema12, ema26) = calculate(closes, 0.0);
+    let options = ChartOptions {
+        annotate_crossover: annotate,
+        max_points: 0,
+        ..Default::default()
+    };
+    let chart = build_chart("TEST", closes, &ema12, &ema26, &dates, &options).unwrap();
+    serde_json::to_value(&chart).unwrap()
+}
+
+fn mark_lines(chart: &Value) -> Vec<&Value> {
+    chart["series"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|s| s.get("markLine"))
+        .collect()
+}
+
+#[test]
+fn crossover_annotation_sits_on_the_crossover() {
+    let closes = rally_closes();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let (index, signal) = last_crossover(&ema12, &ema26, 0.0).unwrap();
+    assert_eq!(signal, Signal::Buy);
+    assert!(index < closes.len() - 1, "crossover on the last bar");
+
+    let chart = annotated(&closes, true);
+    let marks = mark_lines(&chart);
+    assert_eq!(marks.len(), 1);
+    // the whole series fits in the window, so points are bar indices
+    assert_eq!(marks[0]["data"][0]["xAxis"], index as f64);
+    assert_eq!(marks[0]["label"]["formatter"], format!("Buy: d{index}"));
+}
+
+#[test]
+fn no_annotation_without_a_crossover_or_the_option() {
+    assert!(mark_lines(&annotated(&[100.0; 60], true)).is_empty());
+    assert!(mark_lines(&annotated(&rally_closes(), false)).is_empty());
+}
+
+fn with_events(bar_dates: Option<Vec<NaiveDate>>) -> Value {
+    let closes = vec![100.0; 30];
+    let dates: Vec<String> = (0..closes.len()).map(|i| format!("d{i}")).collect();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let first = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let options = ChartOptions {
+        max_points: 0,
+        annotations: vec![
+            (first + Duration::days(5), AnnotationKind::Earnings),
+            (first + Duration::days(20), AnnotationKind::ExDividend),
+            // after the last bar
+            (first + Duration::days(45), AnnotationKind::ExDividend),
+        ],
+        bar_dates,
+        ..Default::default()
+    };
+    let chart = build_chart("TEST", &closes, &ema12, &ema26, &dates, &options).unwrap();
+    serde_json::to_value(&chart).unwrap()
+}
+
+fn daily(n: i64) -> Vec<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    (0..n).map(|i| first + Duration::days(i)).collect()
+}
+
+#[test]
+fn events_get_a_labelled_line_each() {
+    let chart = with_events(Some(daily(30)));
+    assert_eq!(
+        series(&chart),
+        vec![
+            "Price (Bull)",
+            "Price (Bear)",
+            "Earnings",
+            "Ex-Dividend",
+            "EMA12",
+            "EMA26"
+        ]
+    );
+    let marks = mark_lines(&chart);
+    assert_eq!(marks.len(), 2);
+    assert_eq!(marks[0]["data"][0]["xAxis"], 5.0);
+    assert_eq!(marks[0]["data"][0]["label"]["formatter"], "E");
+    assert_eq!(marks[1]["data"].as_array().unwrap().len(), 1);
+    assert_eq!(marks[1]["data"][0]["xAxis"], 20.0);
+    assert_eq!(marks[1]["data"][0]["label"]["formatter"], "D");
+}
+
+#[test]
+fn events_that_cant_be_placed_are_dropped_not_fatal() {
+    for bar_dates in [None, Some(daily(12))] {
+        let chart = with_events(bar_dates);
+        assert!(mark_lines(&chart).is_empty());
+        assert_eq!(series(&chart).len(), 4);
+    }
+}
+
+#[test]
+fn annotations_are_part_of_the_fingerprint() {
+    let mut annotated = ChartJob::sample(ChartTheme::Dark);
+    annotated.options.annotations = vec![(
+        NaiveDate::from_ymd_opt(2024, 1, 5).unwrap(),
+        AnnotationKind::Earnings,
+    )];
+    assert_ne!(
+        ChartJob::sample(ChartTheme::Dark).fingerprint(),
+        annotated.fingerprint()
+    );
+}
+
+/// `closes` charted whole and cut to its last [`CHART_HISTORY`] bars, the
+/// cut one given the band's state where it starts when `carry` is set.
+fn full_and_cut(closes: &[f64], band_pct: f64, carry: bool) -> (Value, Value) {
+    let dates: Vec<String> = (0..closes.len()).map(|i| format!("d{i}")).collect();
+    let (_, ema12, ema26) = calculate(closes, band_pct);
+    let options = ChartOptions {
+        annotate_crossover: true,
+        band_pct,
+        indicators: IndicatorSet {
+            spread: true,
+            ..IndicatorSet::default()
+        },
+        ..Default::default()
+    };
+    let full = build_chart("TEST", closes, &ema12, &ema26, &dates, &options).unwrap();
+
+    let cut = closes.len() - CHART_HISTORY;
+    let options = ChartOptions {
+        trend_before: carry.then(|| trend(&ema12[..cut], &ema26[..cut], band_pct)[cut - 1]),
+        ..options
+    };
+    let trimmed = build_chart(
+        "TEST",
+        &closes[cut..],
+        &ema12[cut..],
+        &ema26[cut..],
+        &dates[cut..],
+        &options,
+    )
+    .unwrap();
+    (
+        serde_json::to_value(&full).unwrap(),
+        serde_json::to_value(&trimmed).unwrap(),
+    )
+}
+
+#[test]
+fn a_chart_drawn_from_its_recent_history_matches_the_full_one() {
+    // a year of a choppy trend, crossing back and forth through the band
+    let closes: Vec<f64> = (0..365)
+        .map(|i| {
+            let i = i as f64;
+            100.0 + 0.05 * i + 6.0 * (i / 11.0).sin() + 2.0 * (i / 3.0).cos()
+        })
+        .collect();
+    let (full, trimmed) = full_and_cut(&closes, 0.5, true);
+    assert_eq!(full, trimmed);
+}
+
+#[test]
+fn a_cut_chart_keeps_a_trend_held_inside_the_band() {
+    // a climb, then a drift down that keeps the fast average just under
+    // the slow one, inside the band, for the rest of the year
+    let closes: Vec<f64> = (0..365)
+        .map(|i| match i {
+            0..100 => 100.0 + 0.5 * i as f64,
+            i => 150.0 - 0.05 * (i - 100) as f64,
+        })
+        .collect();
+
+    let (full, trimmed) = full_and_cut(&closes, 1.0, true);
+    assert_eq!(full, trimmed);
+    // starting from the raw sign, the cut chart would turn bearish
+    let (full, guessed) = full_and_cut(&closes, 1.0, false);
+    assert_ne!(full, guessed);
+}
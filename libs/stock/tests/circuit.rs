@@ -0,0 +1,198 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Error, anyhow};
+use stock::circuit::{CircuitBreaker, CircuitState, StoreUnavailable};
+
+const COOL_DOWN: Duration = Duration::from_secs(30);
+
+/// Stand-in backend: answers unless `down` is set, and counts the calls
+/// that actually reached it.
+#[derive(Default)]
+struct Backend {
+    down: AtomicBool,
+    calls: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Outage;
+
+impl std::fmt::Display for Outage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "connection refused")
+    }
+}
+
+impl std::error::Error for Outage {}
+
+impl Backend {
+    async fn get(&self) -> Result<u32, Error> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.down.load(Ordering::SeqCst) {
+            Err(Outage.into())
+        } else {
+            Ok(7)
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+fn is_outage(e: &Error) -> bool {
+    e.downcast_ref::<Outage>().is_some()
+}
+
+#[test]
+fn opens_after_the_threshold_and_fails_fast_until_the_cool_down() {
+    let breaker = CircuitBreaker::new("test", 3, COOL_DOWN);
+    let start = Instant::now();
+
+    for _ in 0..2 {
+        breaker.try_acquire(start).unwrap();
+        breaker.record(start, false);
+        assert_eq!(breaker.state_at(start), CircuitState::Closed);
+    }
+    breaker.try_acquire(start).unwrap();
+    breaker.record(start, false);
+    assert_eq!(breaker.state_at(start), CircuitState::Open);
+
+    let later = start + Duration::from_secs(10);
+    assert_eq!(
+        breaker.try_acquire(later),
+        Err(StoreUnavailable {
+            circuit: "test",
+            retry_in: Duration::from_secs(20),
+        })
+    );
+    assert_eq!(breaker.state_at(start + COOL_DOWN), CircuitState::HalfOpen);
+}
+
+#[test]
+fn a_success_resets_the_failure_count() {
+    let breaker = CircuitBreaker::new("test", 3, COOL_DOWN);
+    let now = Instant::now();
+    for healthy in [false, false, true, false, false] {
+        breaker.try_acquire(now).unwrap();
+        breaker.record(now, healthy);
+    }
+    assert_eq!(breaker.state_at(now), CircuitState::Closed);
+}
+
+#[test]
+fn half_open_lets_one_probe_through() {
+    let breaker = CircuitBreaker::new("test", 1, COOL_DOWN);
+    let start = Instant::now();
+    breaker.record(start, false);
+
+    let probe = start + COOL_DOWN;
+    breaker.try_acquire(probe).unwrap();
+    assert_eq!(breaker.state_at(probe), CircuitState::HalfOpen);
+    // a second caller waits on the probe rather than piling on
+    assert!(breaker.try_acquire(probe).is_err());
+
+    // a failed probe opens it again, for twice as long
+    breaker.record(probe, false);
+    assert_eq!(breaker.state_at(probe), CircuitState::Open);
+    assert!(breaker.try_acquire(probe + COOL_DOWN).is_err());
+
+    // a good one closes it
+    let retry = probe + COOL_DOWN * 2;
+    breaker.try_acquire(retry).unwrap();
+    breaker.record(retry, true);
+    assert_eq!(breaker.state_at(retry), CircuitState::Closed);
+    breaker.try_acquire(retry).unwrap();
+}
+
+#[test]
+fn a_probe_that_never_reports_back_is_given_up_on() {
+    let breaker = CircuitBreaker::new("test", 1, COOL_DOWN);
+    let start = Instant::now();
+    breaker.record(start, false);
+
+    let probe = start + COOL_DOWN;
+    breaker.try_acquire(probe).unwrap();
+    assert!(breaker.try_acquire(probe + COOL_DOWN / 2).is_err());
+    assert!(breaker.try_acquire(probe + COOL_DOWN).is_ok());
+}
+
+#[test]
+fn the_cool_down_doubles_after_each_failed_probe_up_to_the_cap() {
+    let breaker = CircuitBreaker::new("test", 1, COOL_DOWN).with_max_cool_down(COOL_DOWN * 4);
+    let mut now = Instant::now();
+    breaker.record(now, false);
+
+    for cool_down in [COOL_DOWN, COOL_DOWN * 2, COOL_DOWN * 4, COOL_DOWN * 4] {
+        let early = now + cool_down - Duration::from_secs(1);
+        assert_eq!(
+            breaker.try_acquire(early),
+            Err(StoreUnavailable {
+                circuit: "test",
+                retry_in: Duration::from_secs(1),
+            })
+        );
+        now += cool_down;
+        breaker.try_acquire(now).unwrap();
+        breaker.record(now, false);
+    }
+
+    // closing starts the next outage from the first cool-down
+    now += COOL_DOWN * 4;
+    breaker.try_acquire(now).unwrap();
+    breaker.record(now, true);
+    breaker.record(now, false);
+    assert!(breaker.try_acquire(now + COOL_DOWN).is_ok());
+}
+
+#[tokio::test]
+async fn calls_drive_the_circuit_through_every_state() {
+    let cool_down = Duration::from_millis(50);
+    let breaker = CircuitBreaker::new("redis_read", 3, cool_down);
+    let backend = Backend::default();
+
+    assert_eq!(breaker.call(backend.get(), is_outage).await.unwrap(), 7);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    backend.down.store(true, Ordering::SeqCst);
+    for _ in 0..3 {
+        let e = breaker.call(backend.get(), is_outage).await.unwrap_err();
+        assert!(is_outage(&e));
+    }
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert_eq!(backend.calls(), 4);
+
+    // open: turned away without reaching the backend
+    let e = breaker.call(backend.get(), is_outage).await.unwrap_err();
+    let unavailable = e.downcast_ref::<StoreUnavailable>().unwrap();
+    assert_eq!(unavailable.circuit, "redis_read");
+    assert_eq!(backend.calls(), 4);
+
+    tokio::time::sleep(cool_down).await;
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    // the probe still fails, so back to open
+    breaker.call(backend.get(), is_outage).await.unwrap_err();
+    assert_eq!(backend.calls(), 5);
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    backend.down.store(false, Ordering::SeqCst);
+    tokio::time::sleep(cool_down * 2).await;
+    assert_eq!(breaker.call(backend.get(), is_outage).await.unwrap(), 7);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(backend.calls(), 6);
+}
+
+#[tokio::test]
+async fn errors_the_backend_answered_with_do_not_count() {
+    let breaker = CircuitBreaker::new("test", 1, COOL_DOWN);
+    for _ in 0..3 {
+        let result: Result<(), Error> = breaker
+            .call(async { Err(anyhow!("WRONGTYPE")) }, is_outage)
+            .await;
+        assert!(result.is_err());
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
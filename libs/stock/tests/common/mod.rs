@@ -0,0 +1,163 @@
+//! Shared fixtures for the stock integration tests.
+//!
+//! Alpaca is served by a `wiremock` server. Redis-backed tests need a real
+//! server: set `TEST_REDIS_URL` (e.g. `redis://127.0.0.1:6379`) to run them,
+//! otherwise they return early so plain `cargo test` passes offline.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, anyhow};
+use chrono::{Duration, TimeZone, Utc};
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+use stock::{Bar, PriceClient, PriceSource, Session, Snapshot, SymbolStore, Timeframe};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+/// A mock Alpaca server and a client pointed at it.
+pub async fn alpaca() -> (MockServer, PriceClient) {
+    let server = MockServer::start().await;
+    let client = PriceClient::new(server.uri(), "test-key".into(), "test-secret".into())
+        .expect("price client");
+    (server, client)
+}
+
+pub fn bars_path(symbol: &str) -> String {
+    format!("/v2/stocks/{symbol}/bars")
+}
+
+/// Daily bars in Alpaca's JSON shape, one per close starting 2024-01-01.
+pub fn bars(closes: &[f64]) -> Vec<Value> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 5, 0, 0).unwrap();
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            json!({
+                "t": (start + Duration::days(i as i64)).to_rfc3339(),
+                "o": c,
+                "h": c + 1.0,
+                "l": c - 1.0,
+                "c": c,
+                "v": 1000,
+            })
+        })
+        .collect()
+}
+
+/// [`bars`] already parsed into [`Bar`]s.
+pub fn daily_bars(closes: &[f64]) -> Vec<Bar> {
+    serde_json::from_value(Value::Array(bars(closes))).expect("bars parse")
+}
+
+/// An in-memory [`PriceSource`]. Symbols without bars fail like an unknown
+/// ticker would; snapshots are only returned for symbols given one.
+#[derive(Default)]
+pub struct MockSource {
+    pub bars: HashMap<String, Vec<Bar>>,
+    pub snapshots: HashMap<String, Snapshot>,
+}
+
+impl MockSource {
+    pub fn with_closes(mut self, symbol: &str, closes: &[f64]) -> Self {
+        self.bars.insert(symbol.to_string(), daily_bars(closes));
+        self
+    }
+}
+
+impl PriceSource for MockSource {
+    fn fetch_price<'a>(
+        &'a self,
+        symbol: &'a str,
+        _duration: Duration,
+        _timeframe: Timeframe,
+        limit: usize,
+        _bypass_cache: bool,
+        _session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            let bars = self
+                .bars
+                .get(symbol)
+                .ok_or_else(|| anyhow!("unknown symbol {symbol}"))?;
+            Ok(bars[bars.len().saturating_sub(limit)..].to_vec())
+        })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(async move {
+            Ok(symbols
+                .iter()
+                .filter_map(|s| Some((s.clone(), self.snapshots.get(s)?.clone())))
+                .collect())
+        })
+    }
+}
+
+/// Sixty falling closes followed by a sharp rally; EMA12 crosses above EMA26
+/// exactly on the last bar, so the series ends on a Buy.
+pub fn crossover_closes() -> Vec<f64> {
+    let falling = (0..60).map(|i| 100.0 - i as f64 * 0.5);
+    let rally = (1..=7).map(|i| 70.5 + i as f64 * 2.0);
+    falling.chain(rally).collect()
+}
+
+/// Constant closes; the EMAs never separate, so there is no signal.
+pub fn flat_closes() -> Vec<f64> {
+    vec![100.0; 60]
+}
+
+/// Serve `closes` as a single page for `symbol`.
+pub async fn mount_bars(server: &MockServer, symbol: &str, closes: &[f64]) {
+    Mock::given(method("GET"))
+        .and(path(bars_path(symbol)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(closes),
+            "symbol": symbol,
+            "next_page_token": null,
+        })))
+        .mount(server)
+        .await;
+}
+
+/// Make every bars request for `symbol` fail with `status`.
+pub async fn mount_error(server: &MockServer, symbol: &str, status: u16) {
+    Mock::given(method("GET"))
+        .and(path(bars_path(symbol)))
+        .respond_with(ResponseTemplate::new(status).set_body_json(json!({
+            "message": "invalid symbol",
+        })))
+        .mount(server)
+        .await;
+}
+
+/// A store on `TEST_REDIS_URL` under a key prefix unique to this call, or
+/// `None` when no test Redis is configured.
+pub async fn redis_store() -> Option<SymbolStore> {
+    let Ok(url) = std::env::var("TEST_REDIS_URL") else {
+        eprintln!("TEST_REDIS_URL not set, skipping");
+        return None;
+    };
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let prefix = format!("test:{}:{}", std::process::id(), nanos);
+
+    Some(
+        SymbolStore::new(&url, prefix)
+            .await
+            .expect("connect to TEST_REDIS_URL"),
+    )
+}
@@ -0,0 +1,92 @@
+use chrono::NaiveDate;
+use stock::corporate_actions::parse;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// A corporate actions response in Alpaca's shape, with the ids and other
+/// fields that aren't read left in.
+const ACTIONS: &str = r#"{
+  "corporate_actions": {
+    "cash_dividends": [
+      {"id": "b1", "symbol": "AAPL", "cusip": "037833100", "rate": 0.26,
+       "special": false, "foreign": false, "process_date": "2026-08-14",
+       "ex_date": "2026-08-11", "record_date": "2026-08-11",
+       "payable_date": "2026-08-14"},
+      {"id": "a1", "symbol": "AAPL", "cusip": "037833100", "rate": 0.25,
+       "special": false, "foreign": false, "process_date": "2026-05-15",
+       "ex_date": "2026-05-12", "record_date": "2026-05-12",
+       "payable_date": "2026-05-15"}
+    ],
+    "forward_splits": [
+      {"id": "c1", "symbol": "AAPL", "cusip": "037833100", "new_rate": 4,
+       "old_rate": 1, "process_date": "2026-08-31", "ex_date": "2026-08-31",
+       "record_date": "2026-08-24", "payable_date": "2026-08-28"}
+    ],
+    "reverse_splits": [
+      {"id": "d1", "symbol": "AAPL", "old_cusip": "037833100",
+       "new_cusip": "037833200", "new_rate": 1, "old_rate": 10,
+       "process_date": "2026-03-02", "ex_date": "2026-03-02",
+       "record_date": null, "payable_date": null}
+    ],
+    "mergers": [
+      {"id": "e1", "acquirer_symbol": "AAPL", "acquiree_symbol": "XYZ"}
+    ]
+  },
+  "next_page_token": null
+}"#;
+
+#[test]
+fn parse_reads_dividends_sorted_by_ex_date() {
+    let actions = parse(ACTIONS).unwrap();
+
+    let ex_dates: Vec<NaiveDate> = actions.dividends.iter().map(|d| d.ex_date).collect();
+    assert_eq!(ex_dates, vec![date(2026, 5, 12), date(2026, 8, 11)]);
+    let latest = &actions.dividends[1];
+    assert_eq!(latest.symbol, "AAPL");
+    assert_eq!(latest.rate, 0.26);
+    assert!(!latest.special);
+    assert_eq!(latest.payable_date, Some(date(2026, 8, 14)));
+}
+
+#[test]
+fn parse_merges_forward_and_reverse_splits() {
+    let actions = parse(ACTIONS).unwrap();
+
+    assert_eq!(actions.splits.len(), 2);
+    let reverse = &actions.splits[0];
+    assert!(reverse.is_reverse());
+    assert_eq!((reverse.new_rate, reverse.old_rate), (1.0, 10.0));
+    assert_eq!(reverse.payable_date, None);
+    let forward = &actions.splits[1];
+    assert!(!forward.is_reverse());
+    assert_eq!(forward.ex_date, date(2026, 8, 31));
+}
+
+#[test]
+fn missing_or_null_types_count_as_none() {
+    let only_dividends = parse(
+        r#"{"corporate_actions": {"cash_dividends": [
+            {"symbol": "KO", "rate": 0.51, "ex_date": "2026-09-15"}
+        ], "forward_splits": null}}"#,
+    )
+    .unwrap();
+    assert_eq!(only_dividends.dividends.len(), 1);
+    assert!(only_dividends.splits.is_empty());
+    assert_eq!(only_dividends.dividends[0].record_date, None);
+
+    assert!(parse(r#"{"corporate_actions": {}}"#).unwrap().is_empty());
+    assert!(
+        parse(r#"{"corporate_actions": {}, "next_page_token": null}"#)
+            .unwrap()
+            .is_empty()
+    );
+    assert!(parse("{}").unwrap().is_empty());
+}
+
+#[test]
+fn malformed_bodies_are_errors() {
+    assert!(parse("not json").is_err());
+    assert!(parse(r#"{"corporate_actions": {"cash_dividends": [{"symbol": "KO"}]}}"#).is_err());
+}
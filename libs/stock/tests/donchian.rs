@@ -0,0 +1,62 @@
+use stock::indicators::donchian::{Breakout, breakout, calculate};
+
+/// Bars ranging 99..101, then `last` as the final close with a matching range.
+fn ranging_then(last: f64) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let mut closes = vec![100.0; 30];
+    closes.push(last);
+    let highs = closes.iter().map(|c| c + 1.0).collect();
+    let lows = closes.iter().map(|c| c - 1.0).collect();
+    (highs, lows, closes)
+}
+
+#[test]
+fn channel_spans_the_window_high_and_low() {
+    let highs = [3.0, 5.0, 4.0, 2.0];
+    let lows = [1.0, 2.0, 0.5, 1.5];
+
+    let (upper, lower, middle) = calculate(&highs, &lows, 3);
+    assert!(upper[..2].iter().all(|v| v.is_nan()));
+    assert_eq!(upper[2..], [5.0, 5.0]);
+    assert_eq!(lower[2..], [0.5, 0.5]);
+    assert_eq!(middle[3], 2.75);
+}
+
+#[test]
+fn close_above_the_prior_upper_is_bullish() {
+    let (highs, lows, closes) = ranging_then(105.0);
+    let (upper, lower, _) = calculate(&highs, &lows, 20);
+
+    assert_eq!(breakout(&closes, &upper, &lower), Breakout::Bullish);
+}
+
+#[test]
+fn close_below_the_prior_lower_is_bearish() {
+    let (highs, lows, closes) = ranging_then(95.0);
+    let (upper, lower, _) = calculate(&highs, &lows, 20);
+
+    assert_eq!(breakout(&closes, &upper, &lower), Breakout::Bearish);
+}
+
+#[test]
+fn close_inside_the_channel_is_no_breakout() {
+    let (highs, lows, closes) = ranging_then(100.5);
+    let (upper, lower, _) = calculate(&highs, &lows, 20);
+
+    assert_eq!(breakout(&closes, &upper, &lower), Breakout::None);
+}
+
+#[test]
+fn series_shorter_than_the_period_has_no_channel() {
+    let (highs, lows, closes) = ranging_then(105.0);
+    let (upper, lower, middle) = calculate(&highs, &lows, 40);
+
+    assert_eq!(upper.len(), closes.len());
+    assert!(
+        upper
+            .iter()
+            .chain(&lower)
+            .chain(&middle)
+            .all(|v| v.is_nan())
+    );
+    assert_eq!(breakout(&closes, &upper, &lower), Breakout::None);
+}
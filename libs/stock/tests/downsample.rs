@@ -0,0 +1,75 @@
+use stock::indicators::downsample::{downsample, pick};
+
+/// A gentle sine wave with a one-bar spike at `spike`.
+fn wave(n: usize, spike: usize) -> Vec<f64> {
+    (0..n)
+        .map(|i| {
+            let base = 100.0 + 5.0 * (i as f64 / 50.0).sin();
+            if i == spike { base + 40.0 } else { base }
+        })
+        .collect()
+}
+
+#[test]
+fn short_series_keep_every_point() {
+    let values = wave(90, 10);
+    assert_eq!(downsample(&values, 400), (0..90).collect::<Vec<_>>());
+}
+
+#[test]
+fn tiny_limits_disable_downsampling() {
+    let values = wave(1000, 10);
+    assert_eq!(downsample(&values, 2).len(), 1000);
+}
+
+#[test]
+fn output_has_max_points_in_order() {
+    let values = wave(5000, 10);
+    let keep = downsample(&values, 400);
+
+    assert_eq!(keep.len(), 400);
+    assert!(keep.windows(2).all(|w| w[0] < w[1]), "{keep:?}");
+}
+
+#[test]
+fn endpoints_are_preserved() {
+    let values = wave(5000, 10);
+    let keep = downsample(&values, 400);
+
+    assert_eq!(keep.first(), Some(&0));
+    assert_eq!(keep.last(), Some(&4999));
+}
+
+#[test]
+fn peaks_survive() {
+    for spike in [7, 1234, 2501, 4990] {
+        let values = wave(5000, spike);
+        let keep = downsample(&values, 400);
+        assert!(keep.contains(&spike), "spike at {spike} flattened away");
+
+        let kept = pick(&values, &keep);
+        let max = kept.iter().cloned().fold(f64::MIN, f64::max);
+        assert_eq!(max, values[spike]);
+    }
+}
+
+#[test]
+fn gaps_stay_visible_without_hiding_peaks() {
+    let mut values = wave(1000, 300);
+    for v in &mut values[200..260] {
+        *v = f64::NAN;
+    }
+
+    let keep = downsample(&values, 100);
+    assert_eq!(keep.len(), 100);
+    assert!(keep.contains(&300), "{keep:?}");
+    assert!(pick(&values, &keep).iter().any(|v| v.is_nan()));
+}
+
+#[test]
+fn pick_aligns_every_series() {
+    let dates: Vec<String> = (0..10).map(|i| format!("d{i}")).collect();
+    let keep = [0, 3, 9];
+
+    assert_eq!(pick(&dates, &keep), vec!["d0", "d3", "d9"]);
+}
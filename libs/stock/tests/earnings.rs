@@ -0,0 +1,128 @@
+use chrono::NaiveDate;
+use serde_json::json;
+use stock::earnings::{EarningsDate, EarningsSource, Finnhub, parse_finnhub, reporting_within};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path, query_param},
+};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn earnings(symbol: &str, date: NaiveDate) -> EarningsDate {
+    EarningsDate {
+        symbol: symbol.into(),
+        date,
+    }
+}
+
+fn watchlist(symbols: &[&str]) -> Vec<String> {
+    symbols.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn reports_within_the_window_are_flagged_nearest_first() {
+    let today = date(2026, 10, 12);
+    let dates = [
+        earnings("MSFT", date(2026, 10, 15)),
+        earnings("AAPL", date(2026, 10, 14)),
+        earnings("NVDA", date(2026, 10, 12)),
+        earnings("TSLA", date(2026, 10, 16)),
+        earnings("AMZN", date(2026, 10, 9)),
+    ];
+    let symbols = watchlist(&["AAPL", "MSFT", "NVDA", "TSLA", "AMZN"]);
+
+    assert_eq!(
+        reporting_within(&dates, &symbols, today, 3),
+        [
+            ("NVDA".to_string(), 0),
+            ("AAPL".to_string(), 2),
+            ("MSFT".to_string(), 3),
+        ]
+    );
+}
+
+#[test]
+fn only_watched_symbols_are_flagged_at_their_nearest_report() {
+    let today = date(2026, 10, 12);
+    let dates = [
+        earnings("AAPL", date(2026, 10, 14)),
+        earnings("AAPL", date(2026, 10, 13)),
+        earnings("GOOG", date(2026, 10, 13)),
+    ];
+
+    assert_eq!(
+        reporting_within(&dates, &watchlist(&["aapl"]), today, 5),
+        [("AAPL".to_string(), 1)]
+    );
+    assert!(reporting_within(&dates, &watchlist(&["AAPL"]), today, 0).is_empty());
+}
+
+#[test]
+fn finnhub_calendars_are_parsed() {
+    let body = r#"{"earningsCalendar": [
+        {"date": "2026-10-29", "epsActual": null, "epsEstimate": 1.6,
+         "hour": "amc", "quarter": 4, "revenueActual": null,
+         "revenueEstimate": 94000000000, "symbol": "aapl", "year": 2026}
+    ]}"#;
+    assert_eq!(
+        parse_finnhub(body).unwrap(),
+        [earnings("AAPL", date(2026, 10, 29))]
+    );
+    assert!(
+        parse_finnhub(r#"{"earningsCalendar": null}"#)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn finnhub_fetches_the_range_and_keeps_watched_symbols() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/calendar/earnings"))
+        .and(query_param("from", "2026-10-12"))
+        .and(query_param("to", "2026-10-15"))
+        .and(query_param("token", "test-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "earningsCalendar": [
+                {"date": "2026-10-14", "symbol": "AAPL"},
+                {"date": "2026-10-13", "symbol": "GOOG"},
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let finnhub = Finnhub::new(server.uri(), "test-token").unwrap();
+    let got = finnhub
+        .upcoming(
+            &watchlist(&["AAPL"]),
+            date(2026, 10, 12),
+            date(2026, 10, 15),
+        )
+        .await
+        .unwrap();
+    assert_eq!(got, [earnings("AAPL", date(2026, 10, 14))]);
+}
+
+#[tokio::test]
+async fn finnhub_errors_are_reported() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid token"))
+        .mount(&server)
+        .await;
+
+    let finnhub = Finnhub::new(server.uri(), "bad").unwrap();
+    let err = finnhub
+        .upcoming(
+            &watchlist(&["AAPL"]),
+            date(2026, 10, 12),
+            date(2026, 10, 15),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("401"), "{err}");
+}
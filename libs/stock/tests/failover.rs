@@ -0,0 +1,197 @@
+mod common;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration as StdDuration,
+};
+
+use anyhow::{Result, anyhow};
+use chrono::Duration;
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use stock::{
+    Bar, InvalidSymbol, PriceSource, RequestFailed, Session, Snapshot, Timeframe,
+    failover::{FailoverSource, is_retryable},
+};
+
+use common::daily_bars;
+
+const COOL_DOWN: StdDuration = StdDuration::from_millis(50);
+
+/// How a scripted call goes.
+#[derive(Clone, Copy)]
+enum Step {
+    Answer,
+    Down,
+    RateLimited,
+    Unknown,
+}
+
+/// A source that plays `script` one call at a time and answers once it
+/// runs out, serving one bar at `close` so callers can tell sources apart.
+struct Scripted {
+    close: f64,
+    script: Mutex<VecDeque<Step>>,
+    calls: AtomicUsize,
+}
+
+impl Scripted {
+    fn new(close: f64, script: &[Step]) -> Arc<Self> {
+        Arc::new(Self {
+            close,
+            script: Mutex::new(script.iter().copied().collect()),
+            calls: AtomicUsize::new(0),
+        })
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+fn failed(status: StatusCode) -> anyhow::Error {
+    RequestFailed::new(status, format!("request failed with {status}")).into()
+}
+
+impl PriceSource for Scripted {
+    fn fetch_price<'a>(
+        &'a self,
+        symbol: &'a str,
+        _duration: Duration,
+        _timeframe: Timeframe,
+        _limit: usize,
+        _bypass_cache: bool,
+        _session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let step = self.script.lock().unwrap().pop_front();
+            match step.unwrap_or(Step::Answer) {
+                Step::Answer => Ok(daily_bars(&[self.close])),
+                Step::Down => Err(failed(StatusCode::SERVICE_UNAVAILABLE)),
+                Step::RateLimited => Err(failed(StatusCode::TOO_MANY_REQUESTS)),
+                Step::Unknown => Err(InvalidSymbol(symbol.to_string()).into()),
+            }
+        })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        _symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(async { Ok(HashMap::new()) })
+    }
+}
+
+fn failover(primary: &Arc<Scripted>, backup: &Arc<Scripted>) -> FailoverSource {
+    FailoverSource::new(vec![
+        ("alpaca", primary.clone() as Arc<dyn PriceSource>),
+        ("backup", backup.clone() as Arc<dyn PriceSource>),
+    ])
+    .unwrap()
+    .with_demotion(2, COOL_DOWN)
+}
+
+/// The latest close, with the fallback that served it.
+async fn served(source: &FailoverSource) -> Result<(f64, Option<String>)> {
+    let (bars, fallback) = source
+        .fetch_price_served(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            30,
+            false,
+            Session::Regular,
+        )
+        .await?;
+    Ok((bars.last().unwrap().close, fallback))
+}
+
+async fn close(source: &FailoverSource) -> Result<f64> {
+    Ok(served(source).await?.0)
+}
+
+#[tokio::test]
+async fn an_outage_fails_over_to_the_next_source() {
+    let primary = Scripted::new(1.0, &[Step::Down]);
+    let backup = Scripted::new(2.0, &[]);
+    let source = failover(&primary, &backup);
+
+    assert_eq!(
+        served(&source).await.unwrap(),
+        (2.0, Some("backup".to_string()))
+    );
+
+    // the primary is back
+    assert_eq!(served(&source).await.unwrap(), (1.0, None));
+}
+
+#[tokio::test]
+async fn rate_limits_fail_over_too() {
+    let primary = Scripted::new(1.0, &[Step::RateLimited]);
+    let backup = Scripted::new(2.0, &[]);
+    let source = failover(&primary, &backup);
+
+    assert_eq!(close(&source).await.unwrap(), 2.0);
+}
+
+#[tokio::test]
+async fn unknown_symbols_are_not_retried_elsewhere() {
+    let primary = Scripted::new(1.0, &[Step::Unknown]);
+    let backup = Scripted::new(2.0, &[]);
+    let source = failover(&primary, &backup);
+
+    let err = close(&source).await.unwrap_err();
+    assert!(err.is::<InvalidSymbol>(), "{err:?}");
+    assert_eq!(backup.calls(), 0);
+}
+
+#[tokio::test]
+async fn a_failing_source_is_demoted_then_recovers_after_its_cool_down() {
+    let primary = Scripted::new(1.0, &[Step::Down, Step::Down]);
+    let backup = Scripted::new(2.0, &[]);
+    let source = failover(&primary, &backup);
+
+    assert_eq!(close(&source).await.unwrap(), 2.0);
+    assert_eq!(close(&source).await.unwrap(), 2.0);
+    assert_eq!(source.demoted(), ["alpaca"]);
+
+    // skipped while demoted
+    assert_eq!(close(&source).await.unwrap(), 2.0);
+    assert_eq!(primary.calls(), 2);
+
+    tokio::time::sleep(COOL_DOWN + StdDuration::from_millis(20)).await;
+    assert_eq!(served(&source).await.unwrap(), (1.0, None));
+    assert_eq!(primary.calls(), 3);
+    assert!(source.demoted().is_empty());
+}
+
+#[tokio::test]
+async fn every_source_down_is_an_outage() {
+    let primary = Scripted::new(1.0, &[Step::Down]);
+    let backup = Scripted::new(2.0, &[Step::Down]);
+    let source = failover(&primary, &backup);
+
+    let err = close(&source).await.unwrap_err();
+    assert!(is_retryable(&err), "{err:?}");
+    assert_eq!((primary.calls(), backup.calls()), (1, 1));
+}
+
+#[test]
+fn only_outages_are_retryable() {
+    assert!(is_retryable(&failed(StatusCode::BAD_GATEWAY)));
+    assert!(is_retryable(&failed(StatusCode::TOO_MANY_REQUESTS)));
+    assert!(!is_retryable(&failed(StatusCode::NOT_FOUND)));
+    assert!(!is_retryable(&failed(StatusCode::UNPROCESSABLE_ENTITY)));
+    assert!(!is_retryable(&InvalidSymbol("??".into()).into()));
+    assert!(!is_retryable(&anyhow!("unknown symbol NOPE")));
+}
+
+#[test]
+fn failover_needs_a_source() {
+    assert!(FailoverSource::new(Vec::new()).is_err());
+}
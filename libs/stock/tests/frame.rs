@@ -0,0 +1,104 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use stock::{
+    ChartRenderer, Timeframe,
+    indicators::cdc::Signal,
+    scan::{ScanFrame, ScanOutcome, scan_frame},
+    strategy::Strategy,
+};
+
+use common::{MockSource, crossover_closes, flat_closes};
+
+fn renderer() -> Arc<ChartRenderer> {
+    Arc::new(
+        ChartRenderer::with_render_fn(1, Duration::from_secs(5), |job| {
+            Ok(job.symbol.clone().into_bytes())
+        })
+        .unwrap(),
+    )
+}
+
+async fn run(frame: ScanFrame) -> Vec<(String, ScanOutcome)> {
+    let source = MockSource::default()
+        .with_closes("UP", &crossover_closes())
+        .with_closes("FLAT", &flat_closes())
+        .with_closes("NEW", &[]);
+    let mut results: Vec<_> = scan_frame(
+        Arc::new(source),
+        renderer(),
+        vec!["UP".into(), "FLAT".into(), "NEW".into()],
+        frame,
+        Strategy::default(),
+        0.0,
+    )
+    .map(|(symbol, res)| (symbol, res.unwrap()))
+    .collect()
+    .await;
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+#[tokio::test]
+async fn every_symbol_charts_each_one_with_history() {
+    let results = run(ScanFrame::daily().every_symbol()).await;
+    let hits: Vec<_> = results.iter().filter_map(|(_, o)| o.hit.as_ref()).collect();
+
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].symbol, "FLAT");
+    assert!(!matches!(hits[0].signal, Signal::Buy | Signal::Sell));
+    assert_eq!(hits[0].chart, b"FLAT");
+    assert_eq!(hits[1].symbol, "UP");
+    assert_eq!(hits[1].signal, Signal::Buy);
+    assert_eq!(hits[1].chart, b"UP");
+    // nothing to chart without history
+    assert_eq!(results[1].0, "NEW");
+    assert!(results[1].1.hit.is_none() && results[1].1.reading.is_none());
+}
+
+#[tokio::test]
+async fn a_plain_scan_charts_only_crossovers() {
+    let results = run(ScanFrame::daily()).await;
+    let hits: Vec<_> = results.iter().filter_map(|(_, o)| o.hit.as_ref()).collect();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].symbol, "UP");
+}
+
+#[tokio::test]
+async fn short_histories_are_hits_without_a_chart() {
+    let bars = crossover_closes().len();
+    let results = run(ScanFrame::daily().every_symbol().min_chart_bars(bars + 1)).await;
+    let hits: Vec<_> = results.iter().filter_map(|(_, o)| o.hit.as_ref()).collect();
+
+    assert_eq!(hits.len(), 2);
+    // left out on purpose, not a render that failed
+    assert!(hits.iter().all(|h| h.chart.is_empty() && !h.chart_failed));
+    assert_eq!(hits[1].signal, Signal::Buy);
+
+    let results = run(ScanFrame::daily().every_symbol().min_chart_bars(bars)).await;
+    let up = results[2].1.hit.as_ref().unwrap();
+    assert_eq!(up.chart, b"UP");
+}
+
+#[tokio::test]
+async fn every_symbol_works_on_weekly_bars() {
+    let results = run(ScanFrame::at(Timeframe::Week1).every_symbol()).await;
+    let hits: Vec<_> = results.iter().filter_map(|(_, o)| o.hit.as_ref()).collect();
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().all(|h| h.timeframe == Timeframe::Week1));
+}
+
+#[test]
+fn frames_follow_the_timeframe() {
+    assert_eq!(ScanFrame::at(Timeframe::Day1), ScanFrame::daily());
+    assert_eq!(
+        ScanFrame::at(Timeframe::Minute30),
+        ScanFrame::intraday(Timeframe::Minute30, true)
+    );
+    let weekly = ScanFrame::at(Timeframe::Week1);
+    assert_eq!(weekly.lookback, Timeframe::Week1.lookback());
+    assert!(weekly.charts && !weekly.every_symbol);
+    assert!(ScanFrame::daily().every_symbol().every_symbol);
+}
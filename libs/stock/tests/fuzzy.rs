@@ -0,0 +1,64 @@
+use stock::fuzzy::{edit_distance, search, suggestion};
+
+fn watched() -> Vec<String> {
+    [
+        "AAPL", "AMD", "AMZN", "GOOG", "GOOGL", "META", "MSFT", "NVDA", "BRK.B",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+#[test]
+fn edit_distance_counts_each_kind_of_edit_once() {
+    assert_eq!(edit_distance("AAPL", "AAPL"), 0);
+    assert_eq!(edit_distance("AAPL", "APL"), 1);
+    assert_eq!(edit_distance("AAPL", "AAPLX"), 1);
+    assert_eq!(edit_distance("AAPL", "ABPL"), 1);
+    assert_eq!(edit_distance("AAPL", "APAL"), 1);
+    assert_eq!(edit_distance("MSFT", "NVDA"), 4);
+    assert_eq!(edit_distance("", "AMD"), 3);
+}
+
+#[test]
+fn prefixes_rank_before_typos_shortest_first() {
+    assert_eq!(search(&watched(), "goo", 5), vec!["GOOG", "GOOGL"]);
+    assert_eq!(search(&watched(), "A", 5), vec!["AMD", "AAPL", "AMZN"]);
+}
+
+#[test]
+fn the_exact_symbol_comes_first() {
+    assert_eq!(search(&watched(), "GOOG", 5), vec!["GOOG", "GOOGL"]);
+    assert_eq!(search(&watched(), " brk.b ", 5), vec!["BRK.B"]);
+}
+
+#[test]
+fn typos_find_the_watched_symbol() {
+    assert_eq!(search(&watched(), "APPL", 5), vec!["AAPL"]);
+    assert_eq!(search(&watched(), "GOGOL", 5), vec!["GOOGL", "GOOG"]);
+    assert_eq!(search(&watched(), "MSTF", 5), vec!["MSFT"]);
+    assert_eq!(search(&watched(), "NVIDA", 5), vec!["NVDA"]);
+}
+
+#[test]
+fn ambiguous_typos_rank_by_edits_then_alphabetically() {
+    // one edit from both AMD and AMZN, and a prefix of neither
+    assert_eq!(search(&watched(), "AMX", 5), vec!["AMD"]);
+    assert_eq!(search(&watched(), "AMZD", 5), vec!["AMD", "AMZN"]);
+    assert_eq!(search(&watched(), "AMZD", 1), vec!["AMD"]);
+}
+
+#[test]
+fn unrelated_and_blank_queries_match_nothing() {
+    assert!(search(&watched(), "XYZ", 5).is_empty());
+    assert!(search(&watched(), "  ", 5).is_empty());
+    assert!(search(&[], "AAPL", 5).is_empty());
+}
+
+#[test]
+fn suggestions_skip_symbols_already_watched() {
+    assert_eq!(suggestion(&watched(), "appl"), Some("AAPL".into()));
+    assert_eq!(suggestion(&watched(), "GOOGL"), None);
+    assert_eq!(suggestion(&watched(), "GOO"), Some("GOOG".into()));
+    assert_eq!(suggestion(&watched(), "XYZ"), None);
+}
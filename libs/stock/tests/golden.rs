@@ -0,0 +1,91 @@
+//! Charts drawn from the checked-in fixture, compared against the reference
+//! thumbnails in `tests/goldens/charts.txt`. After an intended change to
+//! how charts look, regenerate them with
+//! `cargo test -p stock --test golden -- --ignored regen_goldens` and look
+//! over the diff before committing it.
+
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use stock::{
+    ChartJob,
+    indicators::cdc::generate_chart,
+    testing::{DEFAULT_TOLERANCE, Thumbnail, format_goldens, parse_goldens, variants},
+};
+
+const GOLDENS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/goldens/charts.txt");
+
+fn render(job: &ChartJob) -> Vec<u8> {
+    generate_chart(
+        &job.symbol,
+        &job.closes,
+        &job.ema12,
+        &job.ema26,
+        &job.dates,
+        &job.options,
+    )
+    .unwrap_or_else(|e| panic!("{} failed to render: {e:?}", job.symbol))
+}
+
+#[test]
+fn charts_match_their_goldens() {
+    let goldens = parse_goldens(&fs::read_to_string(GOLDENS).unwrap()).unwrap();
+    let artifacts = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+
+    let mut drifted = Vec::new();
+    for (name, job) in variants() {
+        let Some(golden) = goldens.get(&name) else {
+            drifted.push(format!("{name}: no golden, run regen_goldens to add it"));
+            continue;
+        };
+        let png = render(&job);
+        let thumbnail = Thumbnail::from_png(&png).unwrap();
+        let distance = thumbnail.distance(golden);
+        if distance <= DEFAULT_TOLERANCE {
+            continue;
+        }
+
+        fs::create_dir_all(&artifacts).unwrap();
+        let actual = artifacts.join(format!("{name}.actual.png"));
+        let diff = artifacts.join(format!("{name}.diff.png"));
+        fs::write(&actual, &png).unwrap();
+        fs::write(&diff, thumbnail.diff_png(golden).unwrap()).unwrap();
+        drifted.push(format!(
+            "{name}: {distance:.2} > {DEFAULT_TOLERANCE}, see {} and {}",
+            actual.display(),
+            diff.display()
+        ));
+    }
+
+    assert!(
+        drifted.is_empty(),
+        "charts don't match their goldens:\n{}",
+        drifted.join("\n")
+    );
+}
+
+#[test]
+fn a_chart_renders_the_same_twice() {
+    let (name, job) = variants().swap_remove(0);
+    let first = Thumbnail::from_png(&render(&job)).unwrap();
+    let second = Thumbnail::from_png(&render(&job)).unwrap();
+    assert_eq!(first, second, "{name} is not deterministic");
+}
+
+#[test]
+fn goldens_survive_a_round_trip() {
+    let thumbnail = Thumbnail::from_png(&render(&variants()[0].1)).unwrap();
+    let goldens = BTreeMap::from([("dark-1x".to_string(), thumbnail)]);
+
+    assert_eq!(parse_goldens(&format_goldens(&goldens)).unwrap(), goldens);
+}
+
+#[test]
+#[ignore = "rewrites the reference thumbnails"]
+fn regen_goldens() {
+    let goldens: BTreeMap<_, _> = variants()
+        .into_iter()
+        .map(|(name, job)| (name, Thumbnail::from_png(&render(&job)).unwrap()))
+        .collect();
+    fs::write(GOLDENS, format_goldens(&goldens)).unwrap();
+    eprintln!("wrote {} goldens to {GOLDENS}", goldens.len());
+}
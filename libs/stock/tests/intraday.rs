@@ -0,0 +1,227 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{Duration as Span, TimeZone, Utc};
+use futures::StreamExt;
+use stock::{
+    Bar, ChartRenderer, DataSource, OhlcvSeries, Timeframe,
+    calendar::{closes_bar, in_market_hours, session_date},
+    indicators::cdc::Signal,
+    scan::{ScanFrame, ScanHit, ScanOutcome, ScanReading, is_new_spike, is_repeat, scan_frame},
+    strategy::Strategy,
+};
+
+use common::{MockSource, crossover_closes, daily_bars, flat_closes};
+
+fn renderer() -> Arc<ChartRenderer> {
+    Arc::new(
+        ChartRenderer::with_render_fn(1, Duration::from_secs(5), |job| {
+            Ok(job.dates.last().cloned().unwrap_or_default().into_bytes())
+        })
+        .unwrap(),
+    )
+}
+
+async fn scan_up(charts: bool) -> ScanHit {
+    let source = MockSource::default()
+        .with_closes("UP", &crossover_closes())
+        .with_closes("FLAT", &flat_closes());
+    let results: Vec<_> = scan_frame(
+        Arc::new(source),
+        renderer(),
+        vec!["UP".into(), "FLAT".into()],
+        ScanFrame::intraday(Timeframe::Hour1, charts),
+        Strategy::default(),
+        0.0,
+    )
+    .collect()
+    .await;
+
+    let mut hits: Vec<ScanHit> = results
+        .into_iter()
+        .filter_map(|(_, res)| res.unwrap().hit)
+        .collect();
+    assert_eq!(hits.len(), 1);
+    hits.remove(0)
+}
+
+#[tokio::test]
+async fn intraday_hits_are_text_only_without_charts() {
+    let hit = scan_up(false).await;
+    assert_eq!(hit.symbol, "UP");
+    assert_eq!(hit.signal, Signal::Buy);
+    assert_eq!(hit.timeframe, Timeframe::Hour1);
+    assert_eq!(hit.source, DataSource::Bars);
+    assert!(hit.chart.is_empty());
+}
+
+#[tokio::test]
+async fn intraday_charts_label_the_axis_with_times() {
+    let hit = scan_up(true).await;
+    // the stub renderer returns the last axis label
+    let label = String::from_utf8(hit.chart).unwrap();
+    assert!(label.contains(':'), "{label}");
+}
+
+/// `closes` as hourly bars, the last one starting `age` ago.
+fn hourly(closes: &[f64], age: Span) -> Vec<Bar> {
+    let mut bars = daily_bars(closes);
+    let last = Utc::now() - age;
+    let n = bars.len() as i32;
+    for (i, bar) in bars.iter_mut().enumerate() {
+        bar.timestamp = last - Span::hours((n - 1 - i as i32).into());
+    }
+    bars
+}
+
+async fn scan_hourly(bars: Vec<Bar>) -> ScanOutcome {
+    let mut source = MockSource::default();
+    source.bars.insert("UP".into(), bars);
+    let mut results: Vec<_> = scan_frame(
+        Arc::new(source),
+        renderer(),
+        vec!["UP".into()],
+        ScanFrame::intraday(Timeframe::Hour1, false),
+        Strategy::default(),
+        0.0,
+    )
+    .collect()
+    .await;
+    results.remove(0).1.unwrap()
+}
+
+#[tokio::test]
+async fn a_crossover_on_a_forming_bar_waits_for_its_close() {
+    let forming = scan_hourly(hourly(&crossover_closes(), Span::minutes(30))).await;
+    assert!(forming.hit.is_none());
+    let closed = scan_hourly(hourly(&crossover_closes(), Span::minutes(61))).await;
+    assert!(closed.hit.is_some());
+}
+
+#[tokio::test]
+async fn volume_spikes_are_judged_and_keyed_on_the_last_closed_bar() {
+    let mut bars = hourly(&flat_closes(), Span::minutes(30));
+    for bar in &mut bars {
+        bar.volume = 20_000.0;
+    }
+    let n = bars.len();
+    bars[n - 2].volume = 100_000.0;
+    // the forming bar's volume so far doesn't count
+    bars[n - 1].volume = 1_000_000.0;
+    let closed = bars[n - 2].timestamp;
+
+    let volume = scan_hourly(bars.clone()).await.volume.unwrap();
+    assert_eq!(volume.timestamp, closed);
+    assert_eq!(volume.ratio, 5.0);
+
+    let today = session_date(closed);
+    let seen = |timestamp| ScanReading {
+        signal: Signal::BearishZone,
+        close: 100.0,
+        ema12: 100.0,
+        ema26: 100.0,
+        timestamp,
+    };
+    assert!(is_new_spike(None, &volume, 3.0, today));
+    assert!(is_new_spike(
+        Some(&seen(bars[n - 3].timestamp)),
+        &volume,
+        3.0,
+        today
+    ));
+    assert!(!is_new_spike(Some(&seen(closed)), &volume, 3.0, today));
+    assert!(!is_new_spike(None, &volume, 6.0, today));
+}
+
+#[test]
+fn each_timeframe_runs_as_its_bars_close() {
+    // Monday 2024-07-15, EDT (UTC-4)
+    let at = |h, m| Utc.with_ymd_and_hms(2024, 7, 15, h, m, 0).unwrap();
+    assert!(closes_bar(at(13, 45), Timeframe::Minute15));
+    assert!(!closes_bar(at(13, 45), Timeframe::Minute30));
+    assert!(closes_bar(at(14, 30), Timeframe::Minute30));
+    assert!(!closes_bar(at(14, 30), Timeframe::Hour1));
+    assert!(closes_bar(at(14, 0), Timeframe::Hour1));
+    assert!(closes_bar(at(20, 0), Timeframe::Hour1));
+}
+
+#[test]
+fn intraday_frame_reads_a_month_of_bars() {
+    let frame = ScanFrame::intraday(Timeframe::Minute15, false);
+    assert_eq!(frame.lookback, chrono::Duration::days(30));
+    assert!(!frame.charts);
+    assert_eq!(ScanFrame::daily().timeframe, Timeframe::Day1);
+    assert!(ScanFrame::daily().charts);
+}
+
+#[test]
+fn intraday_axis_labels_carry_the_time() {
+    let series = OhlcvSeries::new(daily_bars(&[1.0, 2.0]));
+    assert_eq!(series.dates()[0], "2024-01-01");
+    assert_eq!(series.dates_at(Timeframe::Hour1)[0], "01-01 00:00");
+}
+
+fn hit(signal: Signal, hour: u32) -> ScanHit {
+    ScanHit {
+        symbol: "UP".into(),
+        signal,
+        close: 10.0,
+        timestamp: Utc.with_ymd_and_hms(2024, 7, 15, hour, 0, 0).unwrap(),
+        timeframe: Timeframe::Hour1,
+        source: DataSource::Bars,
+        chart: Vec::new(),
+        chart_failed: false,
+        fallback: None,
+    }
+}
+
+fn reading(signal: Signal, hour: u32) -> ScanReading {
+    ScanReading {
+        signal,
+        close: 10.0,
+        ema12: 10.0,
+        ema26: 10.0,
+        timestamp: Utc.with_ymd_and_hms(2024, 7, 15, hour, 0, 0).unwrap(),
+    }
+}
+
+#[test]
+fn the_same_crossover_on_the_same_bar_is_a_repeat() {
+    assert!(is_repeat(
+        Some(&reading(Signal::Buy, 14)),
+        &hit(Signal::Buy, 14)
+    ));
+}
+
+#[test]
+fn a_new_bar_or_signal_is_not_a_repeat() {
+    assert!(!is_repeat(None, &hit(Signal::Buy, 14)));
+    assert!(!is_repeat(
+        Some(&reading(Signal::Buy, 13)),
+        &hit(Signal::Buy, 14)
+    ));
+    assert!(!is_repeat(
+        Some(&reading(Signal::BullishZone, 14)),
+        &hit(Signal::Buy, 14)
+    ));
+    assert!(!is_repeat(
+        Some(&reading(Signal::Sell, 14)),
+        &hit(Signal::Buy, 14)
+    ));
+}
+
+#[test]
+fn market_hours_are_the_regular_session_on_weekdays() {
+    // Monday 2024-07-15, EDT (UTC-4)
+    let at = |h, m| Utc.with_ymd_and_hms(2024, 7, 15, h, m, 0).unwrap();
+    assert!(!in_market_hours(at(13, 0)));
+    assert!(in_market_hours(at(13, 30)));
+    assert!(in_market_hours(at(17, 0)));
+    assert!(in_market_hours(at(20, 0)));
+    assert!(!in_market_hours(at(20, 30)));
+    // Saturday
+    assert!(!in_market_hours(
+        Utc.with_ymd_and_hms(2024, 7, 13, 15, 0, 0).unwrap()
+    ));
+}
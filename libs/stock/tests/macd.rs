@@ -0,0 +1,48 @@
+use stock::indicators::macd::{
+    FAST_PERIOD, MacdState, SIGNAL_PERIOD, SLOW_PERIOD, calculate, state,
+};
+
+fn macd(closes: &[f64]) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    calculate(closes, FAST_PERIOD, SLOW_PERIOD, SIGNAL_PERIOD)
+}
+
+#[test]
+fn warms_up_over_slow_and_signal_periods() {
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+    let (line, signal, histogram) = macd(&closes);
+
+    let warmup = SLOW_PERIOD + SIGNAL_PERIOD - 2;
+    assert!(line[..warmup].iter().all(|v| v.is_nan()));
+    assert!(signal[warmup..].iter().all(|v| v.is_finite()));
+    assert!((histogram[59] - (line[59] - signal[59])).abs() < 1e-9);
+}
+
+#[test]
+fn short_history_has_no_state() {
+    let closes = vec![100.0; 20];
+    let (line, signal, _) = macd(&closes);
+    assert_eq!(state(&line, &signal), None);
+}
+
+#[test]
+fn reports_trend_and_crosses() {
+    let mut closes: Vec<f64> = (0..60).map(|i| 200.0 - i as f64).collect();
+    let (line, signal, _) = macd(&closes);
+    assert_eq!(state(&line, &signal), Some(MacdState::Bearish));
+
+    // a sharp reversal lifts MACD through its signal line
+    let mut crossed = None;
+    for i in 0..30 {
+        closes.push(141.0 + 4.0 * i as f64);
+        let (line, signal, _) = macd(&closes);
+        if let Some(MacdState::BullishCross) = state(&line, &signal) {
+            crossed = Some(closes.len());
+            break;
+        }
+    }
+    assert!(crossed.is_some());
+
+    closes.push(closes.last().unwrap() + 4.0);
+    let (line, signal, _) = macd(&closes);
+    assert_eq!(state(&line, &signal), Some(MacdState::Bullish));
+}
@@ -0,0 +1,17 @@
+use stock::position::{pnl_pct, valid_entry};
+
+#[test]
+fn pnl_is_the_move_from_the_entry_in_percent() {
+    assert_eq!(pnl_pct(100.0, 112.5), Some(12.5));
+    assert_eq!(pnl_pct(200.0, 150.0), Some(-25.0));
+    assert_eq!(pnl_pct(50.0, 50.0), Some(0.0));
+}
+
+#[test]
+fn entries_must_be_positive_and_finite() {
+    for entry in [0.0, -5.0, f64::NAN, f64::INFINITY] {
+        assert!(!valid_entry(entry), "{entry}");
+        assert_eq!(pnl_pct(entry, 10.0), None, "{entry}");
+    }
+    assert!(valid_entry(0.01));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{Duration as Span, Utc};
+use futures::StreamExt;
+use serde_json::{Value, json};
+use stock::ChartRenderer;
+use stock::indicators::cdc::DEFAULT_MIN_CHART_BARS;
+use stock::scan::{prewarm, scan};
+use stock::strategy::Strategy;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use common::{alpaca, crossover_closes, flat_closes};
+
+/// Daily bars ending now, so the scan finds them fresh and skips the
+/// snapshot.
+fn fresh_bars(closes: &[f64]) -> Vec<Value> {
+    let end = Utc::now();
+    let n = closes.len() as i64;
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            json!({
+                "t": (end - Span::days(n - 1 - i as i64)).to_rfc3339(),
+                "o": c,
+                "h": c + 1.0,
+                "l": c - 1.0,
+                "c": c,
+                "v": 1000,
+            })
+        })
+        .collect()
+}
+
+fn renderer() -> Arc<ChartRenderer> {
+    Arc::new(
+        ChartRenderer::with_render_fn(1, Duration::from_secs(5), |job| {
+            Ok(job.symbol.as_bytes().to_vec())
+        })
+        .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn scan_reads_prewarmed_symbols_without_http() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/bars"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": {
+                "UP": fresh_bars(&crossover_closes()),
+                "FLAT": fresh_bars(&flat_closes()),
+            },
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Arc::new(client);
+    let symbols = vec!["UP".to_string(), "FLAT".to_string()];
+    let warmed = prewarm(client.clone(), &symbols, Duration::from_secs(600)).await;
+    assert_eq!(warmed, 2);
+    let before = server.received_requests().await.unwrap().len();
+
+    let results: Vec<_> = scan(
+        client,
+        renderer(),
+        symbols,
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    )
+    .collect()
+    .await;
+
+    assert_eq!(server.received_requests().await.unwrap().len(), before);
+    assert_eq!(results.len(), 2);
+    for (symbol, res) in &results {
+        assert!(res.is_ok(), "{symbol}");
+    }
+    let hit = results
+        .iter()
+        .find_map(|(_, r)| r.as_ref().unwrap().hit.as_ref());
+    assert_eq!(hit.map(|h| h.symbol.as_str()), Some("UP"));
+}
+
+#[tokio::test]
+async fn a_failed_prewarm_leaves_the_scan_to_fetch() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/bars"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/UP/bars"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": fresh_bars(&crossover_closes()),
+            "symbol": "UP",
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Arc::new(client);
+    let symbols = vec!["UP".to_string()];
+    assert_eq!(
+        prewarm(client.clone(), &symbols, Duration::from_secs(600)).await,
+        0
+    );
+
+    let results: Vec<_> = scan(
+        client,
+        renderer(),
+        symbols,
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    )
+    .collect()
+    .await;
+    assert!(results[0].1.as_ref().unwrap().hit.is_some());
+}
@@ -0,0 +1,1083 @@
+mod common;
+
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use serde_json::json;
+use stock::{
+    Bar, DATA_API, Feed, InvalidSymbol, PaperOrLive, PriceClient, PriceSource, QuoteSource,
+    Session, Snapshot, Timeframe, normalize_data_api, validate_symbol,
+};
+use wiremock::{
+    Mock, ResponseTemplate,
+    matchers::{header, method, path, query_param, query_param_is_missing},
+};
+
+use common::{alpaca, bars, bars_path, mount_bars, mount_error};
+
+#[tokio::test]
+async fn fetch_price_parses_bars_and_sends_credentials() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(header("APCA-API-KEY-ID", "test-key"))
+        .and(header("APCA-API-SECRET-KEY", "test-secret"))
+        .and(query_param("timeframe", "1Day"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[1.0, 2.0, 3.0]),
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let got = client
+        .fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+
+    let closes: Vec<f64> = got.iter().map(|b| b.close).collect();
+    assert_eq!(closes, vec![1.0, 2.0, 3.0]);
+}
+
+#[tokio::test]
+async fn fetch_price_follows_next_page_token() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(query_param_is_missing("page_token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[1.0, 2.0]),
+            "next_page_token": "page-2",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(query_param("page_token", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[3.0]),
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let got = client
+        .fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(got.len(), 3);
+    assert_eq!(got[2].close, 3.0);
+}
+
+#[tokio::test]
+async fn fetch_price_stops_paging_at_limit() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        // newest first, so the bars kept are the latest ones
+        .and(query_param("sort", "desc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[1.0, 2.0, 3.0]),
+            "next_page_token": "more",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let got = client
+        .fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            2,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(got.len(), 2);
+}
+
+#[tokio::test]
+async fn fetch_price_maps_http_errors() {
+    let (server, client) = alpaca().await;
+    mount_error(&server, "NOPE", 422).await;
+
+    let err = client
+        .fetch_price(
+            "NOPE",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("NOPE"), "{err}");
+    assert!(err.contains("422"), "{err}");
+    assert!(err.contains("invalid symbol"), "{err}");
+}
+
+#[tokio::test]
+async fn fetch_price_handles_empty_history() {
+    let (server, client) = alpaca().await;
+    mount_bars(&server, "NEW", &[]).await;
+
+    let got = client
+        .fetch_price(
+            "NEW",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+
+    assert!(got.is_empty());
+}
+
+#[tokio::test]
+async fn fetch_price_serves_repeat_requests_from_cache() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[1.0, 2.0]),
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    for _ in 0..3 {
+        let got = client
+            .fetch_price(
+                "AAPL",
+                Duration::days(30),
+                Timeframe::Day1,
+                100,
+                false,
+                Session::Regular,
+            )
+            .await
+            .unwrap();
+        assert_eq!(got.len(), 2);
+    }
+}
+
+#[tokio::test]
+async fn flushing_the_cache_sends_the_next_request_to_alpaca() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[1.0, 2.0]),
+            "next_page_token": null,
+        })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let fetch = || {
+        client.fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+    };
+    fetch().await.unwrap();
+    fetch().await.unwrap();
+
+    let flushed = PriceSource::flush_cache(&client).await;
+    assert_eq!(flushed.bars, 1);
+    assert!(!flushed.assets);
+    fetch().await.unwrap();
+    assert_eq!(client.flush_cache().await.bars, 1);
+}
+
+#[tokio::test]
+async fn fetch_price_bypass_always_hits_network_and_refreshes_cache() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[1.0]),
+            "next_page_token": null,
+        })))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": bars(&[2.0]),
+            "next_page_token": null,
+        })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let fetch = |bypass| {
+        client.fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            bypass,
+            Session::Regular,
+        )
+    };
+
+    assert_eq!(fetch(false).await.unwrap()[0].close, 1.0);
+    // cached, but bypassed: goes to the network both times
+    assert_eq!(fetch(true).await.unwrap()[0].close, 2.0);
+    assert_eq!(fetch(true).await.unwrap()[0].close, 2.0);
+    // the live result replaced the cached one
+    assert_eq!(fetch(false).await.unwrap()[0].close, 2.0);
+}
+
+#[tokio::test]
+async fn expired_bars_are_extended_from_the_last_cached_one() {
+    let (server, client) = alpaca().await;
+    let client = client.with_cache_ttl(StdDuration::from_millis(1));
+    let day = |n: i64| {
+        Utc::now()
+            .date_naive()
+            .and_hms_opt(5, 0, 0)
+            .unwrap()
+            .and_utc()
+            - Duration::days(n)
+    };
+    let bar = |n: i64, c: f64| json!({"t": day(n).to_rfc3339(), "o": c, "h": c, "l": c, "c": c, "v": 1000});
+
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(query_param("start", day(2).to_rfc3339()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(2, 4.0), bar(1, 5.0)],
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(4, 1.0), bar(3, 2.0), bar(2, 3.0)],
+            "next_page_token": null,
+        })))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let fetch = || {
+        client.fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+    };
+    assert_eq!(fetch().await.unwrap().len(), 3);
+    tokio::time::sleep(StdDuration::from_millis(5)).await;
+
+    // only bars from the last cached one on were asked for, and it was
+    // replaced by its final version
+    let closes: Vec<f64> = fetch().await.unwrap().iter().map(|b| b.close).collect();
+    assert_eq!(closes, [1.0, 2.0, 4.0, 5.0]);
+}
+
+#[tokio::test]
+async fn a_delta_that_fills_the_limit_falls_back_to_a_full_fetch() {
+    let (server, client) = alpaca().await;
+    let client = client.with_cache_ttl(StdDuration::from_millis(1));
+    let day = |n: i64| {
+        Utc::now()
+            .date_naive()
+            .and_hms_opt(5, 0, 0)
+            .unwrap()
+            .and_utc()
+            - Duration::days(n)
+    };
+    let bar = |n: i64, c: f64| json!({"t": day(n).to_rfc3339(), "o": c, "h": c, "l": c, "c": c, "v": 1000});
+
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(query_param("start", day(5).to_rfc3339()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(2, 4.0), bar(1, 5.0)],
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(6, 1.0), bar(5, 2.0)],
+            "next_page_token": null,
+        })))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(2, 4.0), bar(1, 5.0)],
+            "next_page_token": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let fetch = || {
+        client.fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            2,
+            false,
+            Session::Regular,
+        )
+    };
+    assert_eq!(fetch().await.unwrap().len(), 2);
+    tokio::time::sleep(StdDuration::from_millis(5)).await;
+
+    let closes: Vec<f64> = fetch().await.unwrap().iter().map(|b| b.close).collect();
+    assert_eq!(closes, [4.0, 5.0]);
+}
+
+/// Closes of the newest three daily bars `client` has for AAPL.
+async fn newest_three(client: &PriceClient) -> Vec<f64> {
+    let bars = client
+        .fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            3,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+    bars.iter().map(|b| b.close).collect()
+}
+
+#[tokio::test]
+async fn cold_and_warm_fetches_keep_the_same_newest_bars() {
+    let day = |n: i64| {
+        Utc::now()
+            .date_naive()
+            .and_hms_opt(5, 0, 0)
+            .unwrap()
+            .and_utc()
+            - Duration::days(n)
+    };
+    let bar = |n: i64, c: f64| json!({"t": day(n).to_rfc3339(), "o": c, "h": c, "l": c, "c": c, "v": 1000});
+
+    // cold: the whole range at once, more bars than the limit
+    let (cold_server, cold) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(4, 1.0), bar(3, 2.0), bar(2, 4.0), bar(1, 5.0)],
+            "next_page_token": null,
+        })))
+        .mount(&cold_server)
+        .await;
+
+    // warm: the same bars, the newest arriving after the first fetch expired
+    let (warm_server, warm) = alpaca().await;
+    let warm = warm.with_cache_ttl(StdDuration::from_millis(1));
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(query_param("start", day(2).to_rfc3339()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(2, 4.0), bar(1, 5.0)],
+            "next_page_token": null,
+        })))
+        .mount(&warm_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "bars": [bar(4, 1.0), bar(3, 2.0), bar(2, 3.0)],
+            "next_page_token": null,
+        })))
+        .up_to_n_times(1)
+        .mount(&warm_server)
+        .await;
+    newest_three(&warm).await;
+    tokio::time::sleep(StdDuration::from_millis(5)).await;
+
+    let cold = newest_three(&cold).await;
+    assert_eq!(cold, [2.0, 4.0, 5.0]);
+    assert_eq!(newest_three(&warm).await, cold);
+}
+
+#[tokio::test]
+async fn fetch_snapshot_parses_daily_bar() {
+    let (server, client) = alpaca().await;
+    let daily = bars(&[42.0]).remove(0);
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/AAPL/snapshot"))
+        .and(query_param("feed", "iex"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "symbol": "AAPL",
+            "dailyBar": daily,
+            "prevDailyBar": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let snapshot = client.fetch_snapshot("AAPL").await.unwrap().unwrap();
+    assert_eq!(snapshot.daily_bar.unwrap().close, 42.0);
+    assert!(snapshot.prev_daily_bar.is_none());
+}
+
+#[test]
+fn snapshots_deserialize_from_alpacas_shape() {
+    // as documented, with the fields that aren't read left in
+    let body = r#"{
+      "symbol": "AAPL",
+      "latestTrade": {"t": "2024-01-05T20:59:59.5Z", "x": "V", "p": 181.18,
+                      "s": 100, "c": ["@"], "i": 52983525029461, "z": "C"},
+      "latestQuote": {"t": "2024-01-05T20:59:59.9Z", "ax": "V", "ap": 181.2,
+                      "as": 3, "bx": "V", "bp": 181.15, "bs": 2, "c": ["R"],
+                      "z": "C"},
+      "minuteBar": {"t": "2024-01-05T20:59:00Z", "o": 181.1, "h": 181.3,
+                    "l": 181.0, "c": 181.18, "v": 20512, "n": 310, "vw": 181.15},
+      "dailyBar": {"t": "2024-01-05T05:00:00Z", "o": 181.99, "h": 182.76,
+                   "l": 180.17, "c": 181.18, "v": 62303300, "n": 682716,
+                   "vw": 181.4},
+      "prevDailyBar": {"t": "2024-01-04T05:00:00Z", "o": 182.15, "h": 183.09,
+                       "l": 180.88, "c": 181.91, "v": 71983600, "n": 721412,
+                       "vw": 181.9}
+    }"#;
+    let snapshot: Snapshot = serde_json::from_str(body).unwrap();
+
+    let trade = snapshot.latest_trade.as_ref().unwrap();
+    assert_eq!(trade.price, 181.18);
+    let quote = snapshot.latest_quote.as_ref().unwrap();
+    assert_eq!((quote.bid_price, quote.bid_size), (181.15, 2.0));
+    assert_eq!((quote.ask_price, quote.ask_size), (181.2, 3.0));
+    assert!((quote.spread().unwrap() - 0.05).abs() < 1e-9);
+    let daily = snapshot.daily_bar.as_ref().unwrap();
+    assert_eq!(
+        (daily.open, daily.close, daily.volume),
+        (181.99, 181.18, 62303300.0)
+    );
+    assert_eq!(snapshot.prev_daily_bar.as_ref().unwrap().close, 181.91);
+    assert_eq!(snapshot.quote().unwrap().source, QuoteSource::Trade);
+
+    // an empty book on one side has no spread
+    let closed: Snapshot = serde_json::from_str(
+        r#"{"latestQuote": {"t": "2024-01-06T00:00:00Z", "ap": 0, "as": 0, "bp": 181.1, "bs": 1}}"#,
+    )
+    .unwrap();
+    assert!(closed.latest_quote.unwrap().spread().is_none());
+    assert!(closed.latest_trade.is_none() && closed.daily_bar.is_none());
+}
+
+#[tokio::test]
+async fn fetch_snapshot_not_found_is_none() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/NOPE/snapshot"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    assert!(client.fetch_snapshot("NOPE").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn fetch_snapshots_skips_symbols_without_data() {
+    let (server, client) = alpaca().await;
+    let daily = bars(&[42.0]).remove(0);
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/snapshots"))
+        .and(query_param("symbols", "AAPL,NOPE"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "AAPL": { "dailyBar": daily },
+            "NOPE": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let symbols = vec!["AAPL".to_string(), "NOPE".to_string()];
+    let snapshots = client.fetch_snapshots(&symbols).await.unwrap();
+
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots["AAPL"].price(), Some(42.0));
+}
+
+#[tokio::test]
+async fn urls_encode_the_symbol_as_one_path_segment() {
+    let (server, client) = alpaca().await;
+    let base = server.uri();
+
+    assert_eq!(
+        client.bars_url("AAPL").unwrap().as_str(),
+        format!("{base}/v2/stocks/AAPL/bars")
+    );
+    assert_eq!(
+        client.bars_url("BRK.B").unwrap().as_str(),
+        format!("{base}/v2/stocks/BRK.B/bars")
+    );
+    assert_eq!(
+        client.bars_url("BTC/USD").unwrap().as_str(),
+        format!("{base}/v2/stocks/BTC%2FUSD/bars")
+    );
+    assert_eq!(
+        client.snapshot_url("BF-B").unwrap().as_str(),
+        format!("{base}/v2/stocks/BF-B/snapshot")
+    );
+}
+
+#[tokio::test]
+async fn dotted_symbol_requests_its_own_bars() {
+    let (server, client) = alpaca().await;
+    mount_bars(&server, "BRK.B", &[1.0, 2.0]).await;
+
+    let bars = client
+        .fetch_price(
+            "BRK.B",
+            Duration::days(5),
+            Timeframe::Day1,
+            10,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+    assert_eq!(bars.len(), 2);
+}
+
+#[test]
+fn symbols_must_look_like_tickers() {
+    for ok in ["AAPL", "BRK.B", "BF-B", "BTC/USD", "SPY", "1INCH/USD"] {
+        assert_eq!(validate_symbol(ok), Ok(()), "{ok:?}");
+    }
+    for bad in [
+        "",
+        "AAPL/../x",
+        "A/B/C",
+        "/USD",
+        "BTC/",
+        "..",
+        ".B",
+        "AA PL",
+        "AAPL?x=1",
+        "AAPL#",
+        "AAPL\n",
+        "AA\u{0}PL",
+        "ÄAPL",
+        "ABCDEFGHIJKLMNOP",
+    ] {
+        assert_eq!(
+            validate_symbol(bad),
+            Err(InvalidSymbol(bad.to_string())),
+            "{bad:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn invalid_symbols_never_reach_the_network() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let err = client
+        .fetch_price(
+            "AA\tPL",
+            Duration::days(5),
+            Timeframe::Day1,
+            10,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.downcast_ref::<InvalidSymbol>().is_some());
+
+    let err = client.fetch_snapshot("x/../y").await.unwrap_err();
+    assert!(err.downcast_ref::<InvalidSymbol>().is_some());
+
+    let snapshots = client
+        .fetch_snapshots(&["bad symbol".to_string()])
+        .await
+        .unwrap();
+    assert!(snapshots.is_empty());
+}
+
+#[tokio::test]
+async fn requests_count_against_the_shared_budget() {
+    let (server, client) = alpaca().await;
+    mount_bars(&server, "AAPL", &[1.0, 2.0]).await;
+    let clone = client.clone();
+
+    for _ in 0..3 {
+        clone
+            .fetch_price(
+                "AAPL",
+                Duration::days(30),
+                Timeframe::Day1,
+                100,
+                true,
+                Session::Regular,
+            )
+            .await
+            .unwrap();
+    }
+
+    let usage = client.usage();
+    assert_eq!(usage.used, 3);
+    assert_eq!(usage.remaining, usage.limit - 3);
+}
+
+#[tokio::test]
+async fn rate_limit_header_corrects_the_budget() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-RateLimit-Remaining", "12")
+                .set_body_json(json!({ "bars": bars(&[1.0]), "next_page_token": null })),
+        )
+        .mount(&server)
+        .await;
+
+    client
+        .fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+
+    let usage = client.usage();
+    assert_eq!(usage.used, 1);
+    assert_eq!(usage.remaining, 12);
+}
+
+#[test]
+fn stock_bar_parses_with_integer_volume_and_extras() {
+    let bar: Bar = serde_json::from_value(json!({
+        "t": "2024-01-02T05:00:00Z",
+        "o": 187.15, "h": 188.44, "l": 183.89, "c": 185.64,
+        "v": 82488674, "n": 1009074, "vw": 185.8,
+    }))
+    .unwrap();
+
+    assert_eq!(bar.volume, 82_488_674.0);
+    assert_eq!(bar.trade_count, Some(1_009_074));
+    assert_eq!(bar.vwap, Some(185.8));
+}
+
+#[test]
+fn crypto_bar_parses_with_fractional_volume() {
+    let bar: Bar = serde_json::from_value(json!({
+        "t": "2024-01-02T00:00:00Z",
+        "o": 44187.3, "h": 45899.1, "l": 44176.9, "c": 45006.2,
+        "v": 1.2564732, "n": 451, "vw": 45002.53,
+    }))
+    .unwrap();
+
+    assert_eq!(bar.volume, 1.2564732);
+    assert_eq!(bar.trade_count, Some(451));
+}
+
+#[test]
+fn bar_parses_without_optional_fields() {
+    let bar: Bar = serde_json::from_value(json!({
+        "t": "2024-01-02T05:00:00Z",
+        "o": 10.0, "h": 11.0, "l": 9.0, "c": 10.5, "v": 0,
+    }))
+    .unwrap();
+
+    assert_eq!(bar.volume, 0.0);
+    assert_eq!(bar.trade_count, None);
+    assert_eq!(bar.vwap, None);
+}
+
+#[test]
+fn bar_without_volume_is_rejected() {
+    let res = serde_json::from_value::<Bar>(json!({
+        "t": "2024-01-02T05:00:00Z",
+        "o": 10.0, "h": 11.0, "l": 9.0, "c": 10.5, "v": "lots",
+    }));
+    assert!(res.is_err());
+}
+
+/// Minute bars across one winter day: pre-market, the open, the last
+/// regular minute, and after-hours.
+fn minute_bars() -> serde_json::Value {
+    let times = [
+        "2024-01-02T13:00:00Z",
+        "2024-01-02T14:30:00Z",
+        "2024-01-02T20:59:00Z",
+        "2024-01-02T21:00:00Z",
+    ];
+    let bars: Vec<_> = times
+        .iter()
+        .enumerate()
+        .map(|(i, t)| json!({ "t": t, "o": 1.0, "h": 1.0, "l": 1.0, "c": i as f64, "v": 100 }))
+        .collect();
+    json!({ "bars": bars, "next_page_token": null })
+}
+
+#[tokio::test]
+async fn regular_session_drops_extended_hours_minute_bars() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(query_param("timeframe", "1Min"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(minute_bars()))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let fetch = |session| {
+        client.fetch_price(
+            "AAPL",
+            Duration::days(1),
+            Timeframe::Minute1,
+            100,
+            false,
+            session,
+        )
+    };
+    let closes = |bars: Vec<Bar>| bars.iter().map(|b| b.close).collect::<Vec<_>>();
+
+    // sessions are cached apart, so each one is fetched
+    assert_eq!(
+        closes(fetch(Session::Regular).await.unwrap()),
+        vec![1.0, 2.0]
+    );
+    assert_eq!(
+        closes(fetch(Session::Extended).await.unwrap()),
+        vec![0.0, 1.0, 2.0, 3.0]
+    );
+}
+
+#[test]
+fn regular_session_keeps_the_hourly_bar_holding_the_open() {
+    // winter: 14:00Z is 09:00 ET, so the bar runs 09:00-10:00 and holds the
+    // 09:30 open; the 16:00 ET bar is after-hours
+    let at = |t: &str| t.parse().unwrap();
+    assert!(Session::Regular.includes(Timeframe::Hour1, at("2024-01-02T14:00:00Z")));
+    assert!(Session::Regular.includes(Timeframe::Hour1, at("2024-01-02T20:00:00Z")));
+    assert!(!Session::Regular.includes(Timeframe::Hour1, at("2024-01-02T21:00:00Z")));
+    assert!(!Session::Regular.includes(Timeframe::Hour1, at("2024-01-02T13:00:00Z")));
+    assert!(!Session::Regular.includes(Timeframe::Minute1, at("2024-01-02T14:29:00Z")));
+    assert!(Session::Regular.includes(Timeframe::Minute30, at("2024-01-02T14:30:00Z")));
+}
+
+#[tokio::test]
+async fn regular_session_keeps_every_daily_bar() {
+    let (server, client) = alpaca().await;
+    mount_bars(&server, "AAPL", &[1.0, 2.0, 3.0]).await;
+
+    let got = client
+        .fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+    assert_eq!(got.len(), 3);
+}
+
+#[tokio::test]
+async fn requests_use_the_configured_feed() {
+    let (server, client) = alpaca().await;
+    let client = client.with_feed(Feed::Sip);
+    Mock::given(method("GET"))
+        .and(path(bars_path("AAPL")))
+        .and(query_param("feed", "sip"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "bars": bars(&[1.0]), "next_page_token": null })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .fetch_price(
+            "AAPL",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+}
+
+#[test]
+fn feeds_parse_by_name() {
+    assert_eq!(Feed::parse("IEX"), Some(Feed::Iex));
+    assert_eq!(Feed::parse(" sip "), Some(Feed::Sip));
+    assert_eq!(Feed::parse("otc"), None);
+}
+
+#[test]
+fn trading_hosts_are_swapped_for_the_data_api() {
+    for raw in [
+        "https://paper-api.alpaca.markets",
+        "https://api.alpaca.markets/",
+        "https://paper-api.alpaca.markets/v2",
+    ] {
+        assert_eq!(normalize_data_api(raw).unwrap(), DATA_API, "{raw}");
+    }
+}
+
+#[test]
+fn data_api_loses_its_trailing_slash_and_version() {
+    assert_eq!(
+        normalize_data_api("https://data.alpaca.markets/").unwrap(),
+        DATA_API
+    );
+    assert_eq!(
+        normalize_data_api(" https://data.alpaca.markets/v2/ ").unwrap(),
+        DATA_API
+    );
+    assert_eq!(
+        normalize_data_api("http://127.0.0.1:8080/alpaca/v2").unwrap(),
+        "http://127.0.0.1:8080/alpaca"
+    );
+    assert!(normalize_data_api("data.alpaca.markets").is_err());
+}
+
+#[test]
+fn accounts_parse_by_name_and_trading_host() {
+    assert_eq!(PaperOrLive::parse(" Live "), Some(PaperOrLive::Live));
+    assert_eq!(PaperOrLive::parse("demo"), None);
+    assert_eq!(
+        PaperOrLive::of_url("https://api.alpaca.markets"),
+        Some(PaperOrLive::Live)
+    );
+    assert_eq!(
+        PaperOrLive::of_url("https://paper-api.alpaca.markets/v2"),
+        Some(PaperOrLive::Paper)
+    );
+    assert_eq!(PaperOrLive::of_url(DATA_API), None);
+}
+
+#[test]
+fn a_client_given_the_trading_api_requests_bars_from_the_data_api() {
+    let client = PriceClient::new(
+        "https://api.alpaca.markets".into(),
+        "test-key".into(),
+        "test-secret".into(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        client.bars_url("AAPL").unwrap().as_str(),
+        "https://data.alpaca.markets/v2/stocks/AAPL/bars"
+    );
+}
+
+#[tokio::test]
+async fn quotes_fall_back_to_the_last_close_without_a_snapshot_price() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/snapshots"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "AAPL": { "latestTrade": { "t": "2024-01-05T20:59:59Z", "p": 181.18 } },
+            "THIN": null,
+        })))
+        .mount(&server)
+        .await;
+    mount_bars(&server, "THIN", &[10.0, 11.0, 12.5]).await;
+
+    let symbols = vec!["AAPL".to_string(), "THIN".to_string()];
+    let quotes = client.fetch_quotes(&symbols).await.unwrap();
+
+    assert_eq!(quotes["AAPL"].price, 181.18);
+    assert_eq!(quotes["AAPL"].source, QuoteSource::Trade);
+    assert_eq!(quotes["THIN"].price, 12.5);
+    assert_eq!(quotes["THIN"].source, QuoteSource::LastClose);
+}
+
+#[tokio::test]
+async fn quotes_leave_out_symbols_with_no_price_at_all() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/stocks/snapshots"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .mount(&server)
+        .await;
+    mount_bars(&server, "NEW", &[]).await;
+    mount_error(&server, "GONE", 404).await;
+
+    let symbols = vec!["NEW".to_string(), "GONE".to_string()];
+    let quotes = client.fetch_quotes(&symbols).await.unwrap();
+    assert!(quotes.is_empty(), "{quotes:?}");
+}
+
+#[tokio::test]
+async fn assets_come_from_the_trading_api_and_are_cached() {
+    let (server, client) = alpaca().await;
+    let client = client.with_trading_api(format!("{}/trading", server.uri()));
+    Mock::given(method("GET"))
+        .and(path("/trading/v2/assets"))
+        .and(query_param("status", "active"))
+        .and(query_param("asset_class", "us_equity"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"symbol": "AAPL", "name": "Apple Inc. Common Stock", "exchange": "NASDAQ",
+             "status": "active", "tradable": true},
+            {"symbol": "MSFT", "name": "Microsoft Corporation Common Stock",
+             "exchange": "NASDAQ", "status": "active", "tradable": true},
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // the first search doesn't wait for the list, it starts fetching it
+    assert!(client.search_symbols("apple").await.unwrap().is_empty());
+    let found = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let found = client.search_symbols("apple").await.unwrap();
+            if !found.is_empty() {
+                return found;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("asset list warmed");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].symbol, "AAPL");
+    // later searches are served from the cached list
+    let found = PriceSource::search_symbols(&client, "msf").await.unwrap();
+    assert_eq!(found[0].symbol, "MSFT");
+
+    // the trading API has a budget of its own
+    assert_eq!(client.trading_usage().used, 1);
+    assert_eq!(client.usage().used, 0);
+}
+
+#[tokio::test]
+async fn assets_errors_are_reported() {
+    let (server, client) = alpaca().await;
+    let client = client.with_trading_api(server.uri());
+    Mock::given(method("GET"))
+        .and(path("/v2/assets"))
+        .respond_with(ResponseTemplate::new(403).set_body_string("forbidden"))
+        .mount(&server)
+        .await;
+
+    let err = client.list_assets().await.unwrap_err();
+    assert!(err.to_string().contains("403"), "{err}");
+}
+
+#[tokio::test]
+async fn corporate_actions_ask_for_dividends_and_splits() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/corporate-actions"))
+        .and(query_param("symbols", "AAPL"))
+        .and(query_param(
+            "types",
+            "cash_dividend,forward_split,reverse_split",
+        ))
+        .and(query_param("start", "2026-01-01"))
+        .and(query_param("end", "2026-12-31"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "corporate_actions": {
+                "cash_dividends": [
+                    {"symbol": "AAPL", "rate": 0.26, "ex_date": "2026-08-11"},
+                ],
+                "forward_splits": [
+                    {"symbol": "AAPL", "new_rate": 4, "old_rate": 1, "ex_date": "2026-08-31"},
+                ],
+            },
+            "next_page_token": null,
+        })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let start = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+    let actions = client
+        .fetch_corporate_actions("AAPL", start, end)
+        .await
+        .unwrap();
+    assert_eq!(actions.dividends.len(), 1);
+    assert_eq!(actions.splits.len(), 1);
+
+    // chart annotations read the same endpoint and keep the dividends
+    let events = client.fetch_events("AAPL", start, end).await.unwrap();
+    assert_eq!(events.len(), 1);
+}
+
+#[tokio::test]
+async fn crypto_has_no_corporate_actions() {
+    let (server, client) = alpaca().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let start = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+    let actions = client
+        .fetch_corporate_actions("BTC/USD", start, end)
+        .await
+        .unwrap();
+    assert!(actions.is_empty());
+}
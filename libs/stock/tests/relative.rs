@@ -0,0 +1,86 @@
+use chrono::{TimeZone, Utc};
+use stock::{
+    Bar,
+    indicators::relative::{BASE, align, rebase, spread},
+};
+
+#[test]
+fn rebase_starts_at_the_base() {
+    assert_eq!(rebase(&[50.0, 55.0, 45.0]), vec![100.0, 110.0, 90.0]);
+    assert_eq!(rebase(&[200.0])[0], BASE);
+}
+
+#[test]
+fn rebase_skips_leading_gaps_and_zeros() {
+    let out = rebase(&[f64::NAN, 0.0, 20.0, 30.0]);
+    assert!(out[0].is_nan());
+    assert_eq!(&out[1..], &[0.0, 100.0, 150.0]);
+}
+
+#[test]
+fn rebase_without_a_reference_is_all_gaps() {
+    assert!(rebase(&[0.0, f64::NAN]).iter().all(|v| v.is_nan()));
+    assert!(rebase(&[]).is_empty());
+}
+
+#[test]
+fn spread_is_the_gap_between_rebased_lines() {
+    let symbol = rebase(&[10.0, 12.0, 11.0]);
+    let benchmark = rebase(&[400.0, 404.0, 420.0]);
+    let got = spread(&symbol, &benchmark);
+    let want = [0.0, 19.0, 5.0];
+    for (g, w) in got.iter().zip(want) {
+        assert!((g - w).abs() < 1e-9, "got {got:?}");
+    }
+}
+
+/// Daily bars on the July 2024 `days`, each closing at its day.
+fn bars(days: &[u32]) -> Vec<Bar> {
+    days.iter()
+        .map(|&d| {
+            let close = f64::from(d);
+            Bar {
+                timestamp: Utc.with_ymd_and_hms(2024, 7, d, 4, 0, 0).unwrap(),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1000.0,
+                trade_count: None,
+                vwap: None,
+            }
+        })
+        .collect()
+}
+
+fn closes(pairs: &[(&Bar, &Bar)]) -> Vec<(f64, f64)> {
+    pairs.iter().map(|(a, b)| (a.close, b.close)).collect()
+}
+
+#[test]
+fn align_keeps_the_shared_recent_bars() {
+    let long = bars(&[1, 2, 3, 4, 5]);
+    let short = bars(&[3, 4, 5]);
+
+    let want = [(3.0, 3.0), (4.0, 4.0), (5.0, 5.0)];
+    assert_eq!(closes(&align(&long, &short)), want);
+    assert_eq!(closes(&align(&short, &long)), want);
+}
+
+#[test]
+fn align_joins_on_timestamps_across_gaps() {
+    // the symbol halted on the 3rd, the benchmark has no bar on the 5th
+    let symbol = bars(&[1, 2, 4, 5, 8]);
+    let benchmark = bars(&[2, 3, 4, 8, 9]);
+
+    assert_eq!(
+        closes(&align(&symbol, &benchmark)),
+        [(2.0, 2.0), (4.0, 4.0), (8.0, 8.0)]
+    );
+}
+
+#[test]
+fn align_with_an_empty_side_is_empty() {
+    assert!(align(&bars(&[1, 2]), &[]).is_empty());
+    assert!(align(&bars(&[1, 2]), &bars(&[3, 4])).is_empty());
+}
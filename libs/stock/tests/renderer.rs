@@ -0,0 +1,182 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use futures::future::join_all;
+use stock::{ChartJob, ChartRenderer, RenderTimeout, indicators::cdc::ChartOptions};
+
+fn job(symbol: &str) -> ChartJob {
+    ChartJob {
+        symbol: symbol.to_string(),
+        closes: [1.0].into(),
+        ema12: [1.0].into(),
+        ema26: [1.0].into(),
+        dates: ["2024-07-01".to_string()].into(),
+        options: ChartOptions::default(),
+    }
+}
+
+/// Renderer whose "chart" is the symbol's bytes, recording the order jobs
+/// start in. `SLOW` jobs take far longer than the rest.
+fn recording(workers: usize, timeout: Duration) -> (ChartRenderer, Arc<Mutex<Vec<String>>>) {
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let log = started.clone();
+    let renderer = ChartRenderer::with_render_fn(workers, timeout, move |job| {
+        log.lock().unwrap().push(job.symbol.clone());
+        let delay = if job.symbol == "SLOW" { 400 } else { 30 };
+        thread::sleep(Duration::from_millis(delay));
+        Ok(job.symbol.as_bytes().to_vec())
+    })
+    .unwrap();
+    (renderer, started)
+}
+
+#[tokio::test]
+async fn saturated_renderer_serves_jobs_in_order() {
+    let (renderer, started) = recording(2, Duration::from_secs(5));
+    let symbols: Vec<String> = (0..8).map(|n| format!("S{n}")).collect();
+
+    let results = join_all(symbols.iter().map(|s| renderer.render(job(s)))).await;
+
+    let charts: Vec<String> = results
+        .into_iter()
+        .map(|r| String::from_utf8(r.unwrap()).unwrap())
+        .collect();
+    assert_eq!(charts, symbols);
+
+    // two workers take jobs off one queue, so each pair starts together and
+    // no job overtakes one submitted more than a worker's width earlier
+    let started = started.lock().unwrap().clone();
+    assert_eq!(started.len(), symbols.len());
+    for (i, symbol) in started.iter().enumerate() {
+        let submitted = symbols.iter().position(|s| s == symbol).unwrap();
+        assert!(submitted.abs_diff(i) < 2, "{started:?}");
+    }
+}
+
+#[tokio::test]
+async fn timeouts_surface_as_errors_and_free_the_queue() {
+    let (renderer, started) = recording(1, Duration::from_millis(150));
+
+    let (slow, queued, fast) = tokio::join!(
+        renderer.render(job("SLOW")),
+        renderer.render(job("QUEUED")),
+        async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            renderer.render(job("FAST")).await
+        },
+    );
+
+    let err = slow.unwrap_err().to_string();
+    assert!(err.contains("timed out"), "{err}");
+    // stuck behind SLOW past its own deadline
+    assert!(queued.is_err());
+    assert!(fast.is_err());
+
+    // once SLOW finishes, the abandoned jobs are skipped rather than run, so
+    // the next job starts right away
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let after = renderer.render(job("AFTER")).await.unwrap();
+    assert_eq!(after, b"AFTER");
+    assert_eq!(*started.lock().unwrap(), vec!["SLOW", "AFTER"]);
+}
+
+#[tokio::test]
+async fn a_panicking_chart_fails_alone_and_keeps_its_worker() {
+    let renderer = ChartRenderer::with_render_fn(1, Duration::from_secs(5), |job| {
+        if job.symbol == "BOOM" {
+            panic!("bad series");
+        }
+        Ok(job.symbol.as_bytes().to_vec())
+    })
+    .unwrap();
+
+    let err = renderer.render(job("BOOM")).await.unwrap_err();
+    assert!(err.to_string().contains("panicked: bad series"), "{err}");
+    // the only worker survived to draw the next chart
+    assert_eq!(renderer.render(job("AAPL")).await.unwrap(), b"AAPL");
+}
+
+#[test]
+fn zero_workers_is_rejected() {
+    assert!(ChartRenderer::with_render_fn(0, Duration::from_secs(1), |_| Ok(vec![])).is_err());
+}
+
+#[tokio::test]
+async fn concurrent_identical_jobs_render_once() {
+    let (renderer, started) = recording(4, Duration::from_secs(5));
+    let renderer = Arc::new(renderer);
+
+    let tasks = (0..8).map(|_| {
+        let renderer = renderer.clone();
+        tokio::spawn(async move { renderer.render(job("NVDA")).await })
+    });
+    for res in join_all(tasks).await {
+        assert_eq!(res.unwrap().unwrap(), b"NVDA");
+    }
+    assert_eq!(*started.lock().unwrap(), vec!["NVDA"]);
+
+    // finished renders aren't kept; the next request draws again
+    renderer.render(job("NVDA")).await.unwrap();
+    assert_eq!(started.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn different_options_are_not_coalesced() {
+    let (renderer, started) = recording(2, Duration::from_secs(5));
+    let mut wide = job("NVDA");
+    wide.options.max_points = 10;
+
+    let (a, b) = tokio::join!(renderer.render(job("NVDA")), renderer.render(wide));
+    a.unwrap();
+    b.unwrap();
+    assert_eq!(started.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn waiters_share_a_timeout() {
+    let (renderer, started) = recording(1, Duration::from_millis(100));
+
+    let (a, b) = tokio::join!(renderer.render(job("SLOW")), renderer.render(job("SLOW")));
+    for res in [a, b] {
+        assert!(res.unwrap_err().is::<RenderTimeout>());
+    }
+    assert_eq!(*started.lock().unwrap(), vec!["SLOW"]);
+}
+
+#[test]
+fn fingerprint_follows_job_contents() {
+    assert_eq!(job("A").fingerprint(), job("A").fingerprint());
+    assert_ne!(job("A").fingerprint(), job("B").fingerprint());
+
+    let mut moved = job("A");
+    moved.closes = [1.5].into();
+    assert_ne!(job("A").fingerprint(), moved.fingerprint());
+}
+
+#[test]
+fn a_cloned_job_shares_its_series() {
+    let job = job("A");
+    let copy = job.clone();
+    assert!(Arc::ptr_eq(&job.closes, &copy.closes));
+    assert!(Arc::ptr_eq(&job.dates, &copy.dates));
+    assert_eq!(job.fingerprint(), copy.fingerprint());
+}
+
+#[tokio::test]
+async fn a_lone_render_hands_its_chart_over_uncopied() {
+    let drawn = Arc::new(Mutex::new(0usize));
+    let at = drawn.clone();
+    let renderer = ChartRenderer::with_render_fn(1, Duration::from_secs(5), move |job| {
+        let png = job.symbol.as_bytes().to_vec();
+        *at.lock().unwrap() = png.as_ptr() as usize;
+        Ok(png)
+    })
+    .unwrap();
+
+    let png = renderer.render(job("AAPL")).await.unwrap();
+    assert_eq!(png, b"AAPL");
+    assert_eq!(png.as_ptr() as usize, *drawn.lock().unwrap());
+}
@@ -0,0 +1,144 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use chrono::{NaiveDate, TimeZone, Utc};
+use stock::{
+    indicators::cdc::Signal,
+    report::{GuildRun, RunArchive, RunRecord, SCHEMA_VERSION, SymbolRecord},
+    scan::{ScanOutcome, ScanReading},
+    timing::RunTimings,
+};
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 7, 16).unwrap()
+}
+
+fn sample_run() -> RunRecord {
+    let started = Utc.with_ymd_and_hms(2024, 7, 16, 20, 30, 0).unwrap();
+    let reading = ScanReading {
+        signal: Signal::Buy,
+        close: 123.45,
+        ema12: 120.5,
+        ema26: 119.25,
+        timestamp: Utc.with_ymd_and_hms(2024, 7, 16, 4, 0, 0).unwrap(),
+    };
+
+    let mut run = RunRecord::new(date(), started);
+    run.finished_at = started + chrono::Duration::seconds(42);
+    run.guilds.push(GuildRun {
+        guild_id: 7,
+        symbols: vec![
+            SymbolRecord::from_result(
+                "aapl",
+                &Ok(ScanOutcome {
+                    reading: Some(reading),
+                    hit: None,
+                    volume: None,
+                }),
+            ),
+            SymbolRecord::from_result("NOPE", &Err(anyhow!("alpaca said 422"))),
+        ],
+        failed_batches: vec!["daily:2024-07-16:7:1".into()],
+    });
+    run
+}
+
+#[test]
+fn run_round_trips_through_json() {
+    let run = sample_run();
+    let json = serde_json::to_string(&run).unwrap();
+    let back: RunRecord = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back, run);
+    assert_eq!(back.version, SCHEMA_VERSION);
+}
+
+#[test]
+fn run_timings_are_archived_only_when_kept() {
+    let mut run = sample_run();
+    let json = serde_json::to_string(&run).unwrap();
+    assert!(!json.contains("timings"));
+
+    run.timings = Some(RunTimings {
+        fetch_ms: 9_000,
+        indicators_ms: 120,
+        render_ms: 4_500,
+        send_ms: 800,
+        wall_ms: 6_000,
+    });
+    let json = serde_json::to_string(&run).unwrap();
+    let back: RunRecord = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.timings, run.timings);
+}
+
+#[test]
+fn records_carry_reading_or_error() {
+    let run = sample_run();
+    let symbols = &run.guild(7).unwrap().symbols;
+
+    assert_eq!(symbols[0].symbol, "AAPL");
+    assert_eq!(symbols[0].signal, Some(Signal::Buy));
+    assert_eq!(symbols[0].ema26, Some(119.25));
+    assert_eq!(symbols[0].error, None);
+
+    assert_eq!(symbols[1].signal, None);
+    assert_eq!(symbols[1].error.as_deref(), Some("alpaca said 422"));
+    assert!(run.guild(8).is_none());
+}
+
+/// A version 1 document exactly as written by the first archiving release.
+/// If this stops parsing, old archives have become unreadable.
+#[test]
+fn version_1_archives_stay_readable() {
+    let v1 = r#"{
+        "version": 1,
+        "date": "2024-07-16",
+        "started_at": "2024-07-16T20:30:00Z",
+        "finished_at": "2024-07-16T20:30:42Z",
+        "guilds": [{
+            "guild_id": 7,
+            "symbols": [
+                {"symbol": "AAPL", "signal": "bullish_zone", "close": 1.5,
+                 "ema12": 1.0, "ema26": 0.5, "timestamp": "2024-07-16T04:00:00Z",
+                 "score": null, "error": null},
+                {"symbol": "NOPE", "error": "boom"}
+            ]
+        }]
+    }"#;
+
+    let run: RunRecord = serde_json::from_str(v1).unwrap();
+    assert_eq!(run.version, 1);
+    assert_eq!(run.date, date());
+
+    let symbols = &run.guilds[0].symbols;
+    assert_eq!(symbols[0].signal, Some(Signal::BullishZone));
+    assert_eq!(symbols[1].close, None);
+    assert_eq!(symbols[1].error.as_deref(), Some("boom"));
+    assert!(run.guilds[0].failed_batches.is_empty());
+}
+
+#[tokio::test]
+async fn file_archive_writes_reads_and_lists() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("runs-{}-{nanos}", std::process::id()));
+    let archive = RunArchive::File(dir.clone());
+
+    assert!(archive.list(10).await.unwrap().is_empty());
+    assert_eq!(archive.read(date()).await.unwrap(), None);
+
+    let run = sample_run();
+    archive.write(&run).await.unwrap();
+    let mut older = sample_run();
+    older.date = date().pred_opt().unwrap();
+    archive.write(&older).await.unwrap();
+
+    assert!(dir.join("run-2024-07-16.json").exists());
+    assert_eq!(archive.read(date()).await.unwrap(), Some(run));
+    assert_eq!(archive.list(10).await.unwrap(), vec![date(), older.date]);
+    assert_eq!(archive.list(1).await.unwrap(), vec![date()]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,25 @@
+use stock::indicators::rsi::{DEFAULT_PERIOD, calculate};
+
+#[test]
+fn warms_up_before_reading() {
+    let closes: Vec<f64> = (0..20).map(|i| 100.0 + i as f64).collect();
+    let rsi = calculate(&closes, DEFAULT_PERIOD);
+
+    assert_eq!(rsi.len(), closes.len());
+    assert!(rsi[..DEFAULT_PERIOD].iter().all(|v| v.is_nan()));
+    assert!(rsi[DEFAULT_PERIOD..].iter().all(|v| v.is_finite()));
+}
+
+#[test]
+fn steady_rally_is_overbought_and_selloff_oversold() {
+    let up: Vec<f64> = (0..40).map(|i| 100.0 + i as f64).collect();
+    let down: Vec<f64> = (0..40).map(|i| 100.0 - i as f64).collect();
+
+    assert!(*calculate(&up, DEFAULT_PERIOD).last().unwrap() > 90.0);
+    assert!(*calculate(&down, DEFAULT_PERIOD).last().unwrap() < 10.0);
+}
+
+#[test]
+fn zero_period_is_all_gaps() {
+    assert!(calculate(&[1.0, 2.0], 0).iter().all(|v| v.is_nan()));
+}
@@ -0,0 +1,394 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::Duration as Span;
+use futures::{StreamExt, future::BoxFuture};
+use stock::indicators::cdc::{DEFAULT_MIN_CHART_BARS, Signal};
+use stock::scan::{Decision, Verdict, check_symbol, scan};
+use stock::strategy::{AdxFilter, Strategy, TrendCheck};
+use stock::{Bar, ChartRenderer, PriceSource, Session, Snapshot, Timeframe};
+use tokio::sync::Notify;
+
+use common::{MockSource, alpaca, crossover_closes, flat_closes, mount_bars, mount_error};
+
+#[tokio::test]
+async fn scan_reports_only_the_crossover() {
+    let (server, client) = alpaca().await;
+    mount_bars(&server, "UP", &crossover_closes()).await;
+    mount_bars(&server, "FLAT", &flat_closes()).await;
+    mount_error(&server, "BAD", 500).await;
+
+    let symbols = vec!["UP".to_string(), "FLAT".to_string(), "BAD".to_string()];
+    let renderer = ChartRenderer::new(2, std::time::Duration::from_secs(30)).unwrap();
+    let results: Vec<_> = scan(
+        Arc::new(client),
+        Arc::new(renderer),
+        symbols,
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    )
+    .collect()
+    .await;
+    assert_eq!(results.len(), 3);
+
+    let mut hits = Vec::new();
+    let mut failed = Vec::new();
+    for (symbol, res) in results {
+        match res {
+            Ok(outcome) => hits.extend(outcome.hit),
+            Err(_) => failed.push(symbol),
+        }
+    }
+
+    assert_eq!(failed, vec!["BAD"]);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].symbol, "UP");
+    assert_eq!(hits[0].signal, Signal::Buy);
+    assert!(!hits[0].chart.is_empty());
+}
+
+#[tokio::test]
+async fn scan_runs_against_any_price_source() {
+    let source = MockSource::default()
+        .with_closes("UP", &crossover_closes())
+        .with_closes("FLAT", &flat_closes());
+
+    let symbols = vec!["UP".to_string(), "FLAT".to_string(), "GONE".to_string()];
+    let renderer = ChartRenderer::new(1, std::time::Duration::from_secs(30)).unwrap();
+    let mut results: Vec<_> = scan(
+        Arc::new(source),
+        Arc::new(renderer),
+        symbols,
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    )
+    .collect()
+    .await;
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(results.len(), 3);
+    let flat = results[0].1.as_ref().unwrap();
+    assert!(flat.hit.is_none(), "FLAT");
+    assert_eq!(flat.reading.unwrap().signal, Signal::BearishZone);
+    assert!(results[1].1.is_err(), "GONE");
+    let up = results[2].1.as_ref().unwrap();
+    let hit = up.hit.as_ref().expect("UP hit");
+    assert_eq!(up.reading.unwrap().close, hit.close);
+    assert_eq!(hit.signal, Signal::Buy);
+    assert!(!hit.chart.is_empty());
+}
+
+#[tokio::test]
+async fn render_timeout_posts_the_hit_without_a_chart() {
+    let source = MockSource::default()
+        .with_closes("STUCK", &crossover_closes())
+        .with_closes("UP", &crossover_closes());
+
+    // the first chart hangs well past the timeout; later ones are instant
+    let renderer = ChartRenderer::with_render_fn(2, Duration::from_millis(100), |job| {
+        if job.symbol == "STUCK" {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        Ok(job.symbol.as_bytes().to_vec())
+    })
+    .unwrap();
+
+    let symbols = vec!["STUCK".to_string(), "UP".to_string()];
+    let mut results: Vec<_> = scan(
+        Arc::new(source),
+        Arc::new(renderer),
+        symbols,
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    )
+    .collect()
+    .await;
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let stuck = results[0]
+        .1
+        .as_ref()
+        .expect("STUCK is degraded, not failed");
+    let hit = stuck.hit.as_ref().expect("STUCK hit");
+    assert!(hit.chart_failed && hit.chart.is_empty());
+    assert_eq!(stuck.reading.unwrap().signal, Signal::Buy);
+
+    let up = results[1].1.as_ref().unwrap();
+    assert_eq!(up.hit.as_ref().expect("UP hit").chart, b"UP");
+}
+
+#[tokio::test]
+async fn broken_charts_degrade_the_hit_to_text() {
+    let source = MockSource::default().with_closes("UP", &crossover_closes());
+    let renderer = ChartRenderer::with_render_fn(1, Duration::from_secs(5), |_| {
+        Err(anyhow::anyhow!("font missing"))
+    })
+    .unwrap();
+
+    let results: Vec<_> = scan(
+        Arc::new(source),
+        Arc::new(renderer),
+        vec!["UP".to_string()],
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    )
+    .collect()
+    .await;
+
+    let outcome = results[0].1.as_ref().expect("UP is degraded, not failed");
+    let hit = outcome.hit.as_ref().expect("the crossover is kept");
+    assert_eq!(hit.signal, Signal::Buy);
+    assert!(hit.chart_failed);
+    assert!(hit.chart.is_empty());
+}
+
+/// [`MockSource`] that panics partway through fetching `BOOM`, standing in
+/// for a bug in some symbol's indicator code.
+struct PanickingSource(MockSource);
+
+impl PriceSource for PanickingSource {
+    fn fetch_price<'a>(
+        &'a self,
+        symbol: &'a str,
+        duration: Span,
+        timeframe: Timeframe,
+        limit: usize,
+        bypass_cache: bool,
+        session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            tokio::task::yield_now().await;
+            let bars: Vec<Bar> = Vec::new();
+            if symbol == "BOOM" {
+                let _ = bars[3];
+            }
+            self.0
+                .fetch_price(symbol, duration, timeframe, limit, bypass_cache, session)
+                .await
+        })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        self.0.fetch_prices(symbols)
+    }
+}
+
+#[tokio::test]
+async fn a_panicking_symbol_fails_alone() {
+    let source = PanickingSource(
+        MockSource::default()
+            .with_closes("UP", &crossover_closes())
+            .with_closes("BOOM", &crossover_closes())
+            .with_closes("FLAT", &flat_closes()),
+    );
+    let renderer = ChartRenderer::with_render_fn(1, Duration::from_secs(5), |job| {
+        Ok(job.symbol.as_bytes().to_vec())
+    })
+    .unwrap();
+
+    let symbols = vec!["UP".to_string(), "BOOM".to_string(), "FLAT".to_string()];
+    let mut results: Vec<_> = scan(
+        Arc::new(source),
+        Arc::new(renderer),
+        symbols,
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    )
+    .collect()
+    .await;
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(results.len(), 3);
+    let boom = results[0].1.as_ref().expect_err("BOOM panicked");
+    assert!(boom.to_string().contains("index out of bounds"), "{boom}");
+    assert!(results[1].1.as_ref().unwrap().reading.is_some(), "FLAT");
+    let up = results[2].1.as_ref().unwrap();
+    assert_eq!(up.hit.as_ref().expect("UP hit").chart, b"UP");
+}
+
+/// Sets its flag when dropped.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A source whose fetches never finish, flagging when one is dropped.
+#[derive(Default)]
+struct HangingSource {
+    started: Arc<Notify>,
+    dropped: Arc<AtomicBool>,
+}
+
+impl PriceSource for HangingSource {
+    fn fetch_price<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _duration: Span,
+        _timeframe: Timeframe,
+        _limit: usize,
+        _bypass_cache: bool,
+        _session: Session,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            let _flag = DropFlag(self.dropped.clone());
+            self.started.notify_one();
+            std::future::pending().await
+        })
+    }
+
+    fn fetch_prices<'a>(
+        &'a self,
+        _symbols: &'a [String],
+    ) -> BoxFuture<'a, Result<HashMap<String, Snapshot>>> {
+        Box::pin(async { Ok(HashMap::new()) })
+    }
+}
+
+#[tokio::test]
+async fn dropping_the_scan_stops_its_fetches() {
+    let source = Arc::new(HangingSource::default());
+    let (started, dropped) = (source.started.clone(), source.dropped.clone());
+    let renderer =
+        ChartRenderer::with_render_fn(1, Duration::from_secs(5), |_| Ok(Vec::new())).unwrap();
+
+    let mut results = scan(
+        source,
+        Arc::new(renderer),
+        vec!["HANG".to_string()],
+        Strategy::default(),
+        0.0,
+        false,
+        DEFAULT_MIN_CHART_BARS,
+    );
+    tokio::select! {
+        _ = results.next() => panic!("the fetch never finishes"),
+        _ = started.notified() => {}
+    }
+    drop(results);
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !dropped.load(Ordering::SeqCst) {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("the fetch was left running");
+}
+
+#[tokio::test]
+async fn check_explains_a_crossover_the_scan_would_post() {
+    let source = MockSource::default()
+        .with_closes("UP", &crossover_closes())
+        .with_closes("FLAT", &flat_closes());
+
+    let up = check_symbol(&source, "UP", Strategy::default(), 0.0, true)
+        .await
+        .unwrap()
+        .expect("UP has history");
+    assert_eq!(up.decision.signal, Signal::Buy);
+    assert_eq!(up.decision.weekly, Some(Signal::Buy));
+    assert_eq!(up.decision.verdict(), Verdict::Posts);
+    assert_eq!(up.close, *crossover_closes().last().unwrap());
+
+    let flat = check_symbol(&source, "FLAT", Strategy::default(), 0.0, true)
+        .await
+        .unwrap()
+        .expect("FLAT has history");
+    assert_eq!(flat.decision.verdict(), Verdict::NoCrossover);
+    assert_eq!(flat.decision.weekly, None, "no crossover, no weekly fetch");
+}
+
+#[tokio::test]
+async fn check_names_the_adx_filter_that_dropped_a_crossover() {
+    let source = MockSource::default().with_closes("UP", &crossover_closes());
+    let strategy = Strategy {
+        adx: Some(AdxFilter {
+            period: 14,
+            min: 99.0,
+        }),
+        ..Strategy::default()
+    };
+
+    let up = check_symbol(&source, "UP", strategy, 0.0, false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(up.decision.raw, Signal::Buy);
+    assert_eq!(up.decision.signal, Signal::BullishZone);
+    let Verdict::WeakTrend(trend) = up.decision.verdict() else {
+        panic!("{:?}", up.decision);
+    };
+    assert_eq!(trend.min, 99.0);
+    assert!(!trend.passes());
+}
+
+#[tokio::test]
+async fn check_without_history_is_none() {
+    let source = MockSource::default().with_closes("NEW", &[]);
+    let checked = check_symbol(&source, "NEW", Strategy::default(), 0.0, false)
+        .await
+        .unwrap();
+    assert!(checked.is_none());
+}
+
+#[test]
+fn a_weekly_disagreement_is_the_verdict_only_after_the_adx_passes() {
+    let trend = TrendCheck {
+        strength: 30.0,
+        min: 25.0,
+    };
+    let dropped = Decision {
+        raw: Signal::Sell,
+        trend: Some(trend),
+        daily: Signal::Sell,
+        weekly: Some(Signal::BullishZone),
+        signal: Signal::BearishZone,
+    };
+    assert!(!dropped.posts());
+    assert_eq!(
+        dropped.verdict(),
+        Verdict::WeeklyDisagrees(Signal::BullishZone)
+    );
+
+    let weak = Decision {
+        trend: Some(TrendCheck {
+            strength: 10.0,
+            ..trend
+        }),
+        daily: Signal::BearishZone,
+        weekly: None,
+        ..dropped
+    };
+    assert_eq!(
+        weak.verdict(),
+        Verdict::WeakTrend(TrendCheck {
+            strength: 10.0,
+            min: 25.0
+        })
+    );
+}
@@ -0,0 +1,115 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use stock::{
+    Bar, DataSource, OhlcvSeries, Snapshot, Timeframe,
+    calendar::{latest_session, session_date},
+};
+
+fn day(d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 7, d).unwrap()
+}
+
+/// Daily bar stamped the way Alpaca does, at midnight New York time.
+fn bar(d: u32, close: f64) -> Bar {
+    Bar {
+        timestamp: Utc.with_ymd_and_hms(2024, 7, d, 4, 0, 0).unwrap(),
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 1000.0,
+        trade_count: None,
+        vwap: None,
+    }
+}
+
+fn snapshot(daily: Option<Bar>) -> Snapshot {
+    Snapshot {
+        latest_trade: None,
+        latest_quote: None,
+        daily_bar: daily,
+        prev_daily_bar: None,
+    }
+}
+
+#[test]
+fn fresh_bars_are_left_alone() {
+    let mut series = OhlcvSeries::new(vec![bar(15, 1.0), bar(16, 2.0)]);
+    let snap = snapshot(Some(bar(16, 9.0)));
+
+    assert_eq!(series.ensure_fresh(day(16), Some(&snap)), DataSource::Bars);
+    assert_eq!(series.closes(), vec![1.0, 2.0]);
+}
+
+#[test]
+fn stale_bars_take_todays_bar_from_snapshot() {
+    let mut series = OhlcvSeries::new(vec![bar(12, 1.0), bar(15, 2.0)]);
+    let snap = snapshot(Some(bar(16, 3.0)));
+
+    assert!(!series.is_fresh(day(16)));
+    assert_eq!(
+        series.ensure_fresh(day(16), Some(&snap)),
+        DataSource::Snapshot
+    );
+    assert_eq!(series.closes(), vec![1.0, 2.0, 3.0]);
+    assert_eq!(series.last_session(), Some(day(16)));
+}
+
+#[test]
+fn stale_bars_without_a_newer_snapshot_are_flagged() {
+    let mut series = OhlcvSeries::new(vec![bar(15, 2.0)]);
+
+    assert_eq!(
+        series.ensure_fresh(day(16), None),
+        DataSource::Stale { last: day(15) }
+    );
+
+    // a snapshot still on the previous session doesn't help either
+    let snap = snapshot(Some(bar(15, 9.0)));
+    assert_eq!(
+        series.ensure_fresh(day(16), Some(&snap)),
+        DataSource::Stale { last: day(15) }
+    );
+    assert_eq!(series.closes(), vec![2.0]);
+}
+
+#[test]
+fn latest_session_skips_weekends_and_pre_open() {
+    // Tue 2024-07-16 20:30 UTC, the afternoon daily run
+    let afternoon = Utc.with_ymd_and_hms(2024, 7, 16, 20, 30, 0).unwrap();
+    assert_eq!(latest_session(afternoon), day(16));
+
+    // Tue before the open still expects Monday's bar
+    let morning = Utc.with_ymd_and_hms(2024, 7, 16, 12, 0, 0).unwrap();
+    assert_eq!(latest_session(morning), day(15));
+
+    // Sun expects Friday
+    let sunday = Utc.with_ymd_and_hms(2024, 7, 14, 20, 0, 0).unwrap();
+    assert_eq!(latest_session(sunday), day(12));
+}
+
+#[test]
+fn daily_bar_timestamps_map_to_their_session() {
+    // midnight New York in summer and winter
+    assert_eq!(
+        session_date(Utc.with_ymd_and_hms(2024, 7, 16, 4, 0, 0).unwrap()),
+        day(16)
+    );
+    assert_eq!(
+        session_date(Utc.with_ymd_and_hms(2024, 1, 16, 5, 0, 0).unwrap()),
+        NaiveDate::from_ymd_opt(2024, 1, 16).unwrap()
+    );
+}
+
+#[test]
+fn tail_is_the_last_bars_closes_and_labels() {
+    let series = OhlcvSeries::new((1..=10).map(|d| bar(d, d as f64)).collect());
+    let dates = series.dates_at(Timeframe::Day1);
+
+    let (closes, labels) = series.tail_at(Timeframe::Day1, 3);
+    assert_eq!(closes, [8.0, 9.0, 10.0]);
+    assert_eq!(labels, dates[7..]);
+
+    let (closes, labels) = series.tail_at(Timeframe::Day1, 50);
+    assert_eq!(closes, series.closes());
+    assert_eq!(labels, dates);
+}
@@ -0,0 +1,236 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::{America::New_York, Asia::Bangkok};
+use std::collections::BTreeMap;
+
+use stock::{
+    Bar, GuildSettings, IntradaySettings, MAX_NOTE_LEN, PostedSignal, SymbolMeta, Timeframe,
+    calendar::parse_timezone,
+    indicators::{cdc::Signal, volume::DEFAULT_THRESHOLD},
+    normalize_note, normalize_tag, route_channels,
+};
+
+/// Daily bar stamped the way Alpaca does, at midnight New York time.
+fn bar(d: u32, close: f64) -> Bar {
+    Bar {
+        timestamp: Utc.with_ymd_and_hms(2024, 7, d, 4, 0, 0).unwrap(),
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 1000.0,
+        trade_count: None,
+        vwap: None,
+    }
+}
+
+#[test]
+fn legacy_symbol_backfills_from_earliest_bar_as_approx() {
+    let mut meta = SymbolMeta::default();
+
+    assert!(meta.backfill_added(&[bar(15, 10.0), bar(16, 11.0)]));
+    assert_eq!(meta.added_price, Some(10.0));
+    assert_eq!(meta.added_at, Some(bar(15, 0.0).timestamp));
+    assert!(meta.added_price_approx);
+}
+
+#[test]
+fn failed_fetch_backfills_from_the_added_session() {
+    let mut meta = SymbolMeta {
+        added_at: Some(Utc.with_ymd_and_hms(2024, 7, 16, 18, 0, 0).unwrap()),
+        ..Default::default()
+    };
+
+    assert!(meta.backfill_added(&[bar(15, 10.0), bar(16, 11.0), bar(17, 12.0)]));
+    assert_eq!(meta.added_price, Some(11.0));
+    assert!(!meta.added_price_approx);
+}
+
+#[test]
+fn recorded_price_is_never_overwritten() {
+    let mut meta = SymbolMeta {
+        added_price: Some(5.0),
+        ..Default::default()
+    };
+
+    assert!(!meta.backfill_added(&[bar(15, 10.0)]));
+    assert!(!SymbolMeta::default().backfill_added(&[]));
+    assert_eq!(meta.added_price, Some(5.0));
+}
+
+#[test]
+fn change_since_added_is_a_percentage() {
+    let meta = SymbolMeta {
+        added_price: Some(80.0),
+        ..Default::default()
+    };
+
+    assert_eq!(meta.change_since_added(100.0), Some(25.0));
+    assert_eq!(SymbolMeta::default().change_since_added(100.0), None);
+}
+
+#[test]
+fn daily_pause_ends_exactly_at_its_deadline() {
+    let until = Utc.with_ymd_and_hms(2024, 7, 16, 13, 30, 0).unwrap();
+    let settings = GuildSettings {
+        daily_paused_until: Some(until),
+        ..Default::default()
+    };
+    let before = until - chrono::Duration::seconds(1);
+
+    assert_eq!(settings.daily_paused_at(before), Some(until));
+    assert!(!settings.daily_pause_expired(before));
+
+    assert_eq!(settings.daily_paused_at(until), None);
+    assert!(settings.daily_pause_expired(until));
+}
+
+#[test]
+fn unpaused_guild_is_neither_paused_nor_resuming() {
+    let settings = GuildSettings::default();
+    let now = Utc::now();
+
+    assert_eq!(settings.daily_paused_at(now), None);
+    assert!(!settings.daily_pause_expired(now));
+}
+
+#[test]
+fn guild_timezone_defaults_to_new_york() {
+    assert_eq!(GuildSettings::default().timezone(), New_York);
+
+    let settings = GuildSettings {
+        display_timezone: Some("Asia/Bangkok".into()),
+        ..Default::default()
+    };
+    assert_eq!(settings.timezone(), Bangkok);
+
+    // a name that stopped parsing falls back instead of failing
+    let settings = GuildSettings {
+        display_timezone: Some("Mars/Olympus".into()),
+        ..Default::default()
+    };
+    assert_eq!(settings.timezone(), New_York);
+}
+
+#[test]
+fn timezone_names_are_iana() {
+    assert_eq!(parse_timezone(" Asia/Bangkok ").unwrap(), Bangkok);
+    for raw in ["", "EST5", "Bangkok", "UTC+7"] {
+        assert!(parse_timezone(raw).is_err(), "{raw:?} accepted");
+    }
+}
+
+#[test]
+fn intraday_scan_needs_a_channel_and_the_switch_on() {
+    let mut intraday = IntradaySettings {
+        channel: Some(42),
+        ..Default::default()
+    };
+    assert_eq!(intraday.target(), None);
+    intraday.enabled = true;
+    assert_eq!(intraday.target(), Some(42));
+    intraday.channel = None;
+    assert_eq!(intraday.target(), None);
+}
+
+#[test]
+fn intraday_timeframe_defaults_to_an_hour() {
+    let mut intraday = IntradaySettings::default();
+    assert_eq!(intraday.timeframe(), Timeframe::Hour1);
+    intraday.timeframe = Some(Timeframe::Minute15);
+    assert_eq!(intraday.timeframe(), Timeframe::Minute15);
+    // not one the scan offers
+    intraday.timeframe = Some(Timeframe::Minute1);
+    assert_eq!(intraday.timeframe(), Timeframe::Hour1);
+}
+
+#[test]
+fn settings_from_before_intraday_scans_still_load() {
+    let settings: GuildSettings = serde_json::from_str(r#"{"daily_channel": 7}"#).unwrap();
+    assert_eq!(settings.daily_channel, Some(7));
+    assert_eq!(settings.intraday, IntradaySettings::default());
+}
+
+#[test]
+fn tags_are_lowercase_without_a_hash() {
+    assert_eq!(normalize_tag(" #Tech "), Some("tech".into()));
+    assert_eq!(normalize_tag("oil_gas-2"), Some("oil_gas-2".into()));
+    assert_eq!(normalize_tag("#"), None);
+    assert_eq!(normalize_tag("big tech"), None);
+    assert_eq!(normalize_tag(&"x".repeat(33)), None);
+}
+
+#[test]
+fn notes_are_trimmed_onto_one_line() {
+    assert_eq!(
+        normalize_note("  earnings\n next  week "),
+        Some("earnings next week".into())
+    );
+    assert_eq!(normalize_note(" \n "), None);
+    assert!(normalize_note(&"x".repeat(MAX_NOTE_LEN)).is_some());
+    assert_eq!(normalize_note(&"x".repeat(MAX_NOTE_LEN + 1)), None);
+}
+
+fn tags(raw: &[&str]) -> Vec<String> {
+    raw.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn hits_follow_each_routed_tag_once() {
+    let routes = BTreeMap::from([
+        ("tech".to_string(), 10),
+        ("ai".to_string(), 10),
+        ("energy".to_string(), 20),
+    ]);
+
+    assert_eq!(route_channels(&tags(&["energy"]), &routes), [20]);
+    assert_eq!(
+        route_channels(&tags(&["ai", "energy", "tech"]), &routes),
+        [10, 20]
+    );
+    assert_eq!(route_channels(&tags(&["retail"]), &routes), [] as [u64; 0]);
+    assert_eq!(route_channels(&[], &routes), [] as [u64; 0]);
+}
+
+#[test]
+fn settings_stored_before_routes_have_none() {
+    let settings: GuildSettings = serde_json::from_str(r#"{"daily_channel": 5}"#).unwrap();
+    assert!(settings.tag_routes.is_empty());
+    let meta: SymbolMeta = serde_json::from_str(r#"{"added_price": 1.5}"#).unwrap();
+    assert!(meta.tags.is_empty());
+}
+
+#[test]
+fn only_an_opposite_crossover_soon_after_reverses_a_post() {
+    let day = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+    let post = PostedSignal {
+        signal: Signal::Buy,
+        session: day(17),
+        channel_id: 1,
+        message_id: 2,
+        embed_index: 0,
+    };
+
+    assert!(post.reversed_by(Signal::Sell, day(18), 5, Timeframe::Day1));
+    assert!(post.reversed_by(Signal::Sell, day(22), 5, Timeframe::Day1));
+    assert!(!post.reversed_by(Signal::Sell, day(23), 5, Timeframe::Day1));
+    assert!(!post.reversed_by(Signal::Sell, day(17), 5, Timeframe::Day1));
+    assert!(!post.reversed_by(Signal::Buy, day(18), 5, Timeframe::Day1));
+    assert!(!post.reversed_by(Signal::BearishZone, day(18), 5, Timeframe::Day1));
+    assert!(!post.reversed_by(Signal::Sell, day(18), 0, Timeframe::Day1));
+
+    // an intraday post is reversed within its own session too
+    assert!(post.reversed_by(Signal::Sell, day(17), 5, Timeframe::Hour1));
+    assert!(!post.reversed_by(Signal::Sell, day(17), 0, Timeframe::Hour1));
+    assert!(!post.reversed_by(Signal::Sell, day(16), 5, Timeframe::Hour1));
+}
+
+#[test]
+fn volume_spikes_are_off_until_turned_on() {
+    let mut settings: GuildSettings = serde_json::from_str(r#"{"daily_channel": 5}"#).unwrap();
+    assert_eq!(settings.volume_spikes(), None);
+
+    settings.volume_spike_alerts = true;
+    assert_eq!(settings.volume_spikes(), Some(DEFAULT_THRESHOLD));
+    settings.volume_spike_threshold = Some(4.5);
+    assert_eq!(settings.volume_spikes(), Some(4.5));
+}
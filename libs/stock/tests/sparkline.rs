@@ -0,0 +1,64 @@
+use stock::indicators::cdc::{SPARKLINE_SIZE, generate_sparkline, sparkline_unicode};
+use tiny_skia::Pixmap;
+
+fn rising() -> Vec<f64> {
+    (0..20).map(|i| 100.0 + i as f64).collect()
+}
+
+fn falling() -> Vec<f64> {
+    (0..20).map(|i| 100.0 - i as f64).collect()
+}
+
+/// Size and line color of a rendered sparkline, as `(width, height, rgb)`.
+fn drawn(closes: &[f64]) -> (u32, u32, [u8; 3]) {
+    let (width, height) = SPARKLINE_SIZE;
+    let png = generate_sparkline(closes, width, height).unwrap();
+    let pixmap = Pixmap::decode_png(&png).unwrap();
+    let line = pixmap
+        .pixels()
+        .iter()
+        .find(|p| p.alpha() == 255)
+        .expect("something is drawn");
+    (
+        pixmap.width(),
+        pixmap.height(),
+        [line.red(), line.green(), line.blue()],
+    )
+}
+
+#[test]
+fn rising_series_is_green() {
+    assert_eq!(drawn(&rising()), (240, 60, [0x00, 0xd0, 0x84]));
+}
+
+#[test]
+fn falling_series_is_red() {
+    assert_eq!(drawn(&falling()), (240, 60, [0xff, 0x4d, 0x4f]));
+}
+
+#[test]
+fn constant_series_is_grey() {
+    assert_eq!(drawn(&[100.0; 20]), (240, 60, [0xa0, 0xa0, 0xa0]));
+    assert_eq!(drawn(&[100.0]).2, [0xa0, 0xa0, 0xa0]);
+}
+
+#[test]
+fn nothing_to_draw_is_an_error() {
+    assert!(generate_sparkline(&[], 240, 60).is_err());
+    assert!(generate_sparkline(&[f64::NAN], 240, 60).is_err());
+    assert!(generate_sparkline(&rising(), 0, 60).is_err());
+}
+
+#[test]
+fn unicode_follows_the_series() {
+    assert_eq!(sparkline_unicode(&[1.0, 2.0, 3.0, 4.0, 5.0]), "▁▂▃▅▇");
+    assert_eq!(sparkline_unicode(&[5.0, 4.0, 3.0, 2.0, 1.0]), "▇▅▃▂▁");
+    assert_eq!(sparkline_unicode(&[7.0; 4]), "▃▃▃▃");
+    assert_eq!(sparkline_unicode(&[]), "");
+}
+
+#[test]
+fn unicode_skips_missing_closes() {
+    assert_eq!(sparkline_unicode(&[1.0, f64::NAN, 3.0]), "▁▇");
+    assert_eq!(sparkline_unicode(&rising()).chars().count(), 20);
+}
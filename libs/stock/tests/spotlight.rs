@@ -0,0 +1,66 @@
+mod common;
+
+use common::{crossover_closes, daily_bars, flat_closes};
+use stock::{
+    indicators::cdc::Signal,
+    spotlight::{MIN_BARS, MIN_SCORE, Setup, assess, rank},
+};
+
+fn setup(symbol: &str, gap_change: f64) -> Setup {
+    Setup {
+        symbol: symbol.to_string(),
+        signal: Signal::BullishZone,
+        close: 100.0,
+        gap_pct: 1.0,
+        gap_change,
+        volume_ratio: None,
+    }
+}
+
+#[test]
+fn fresh_crossover_scores_above_the_minimum() {
+    let setup = assess("aapl", &daily_bars(&crossover_closes()), 0.0).unwrap();
+
+    assert_eq!(setup.symbol, "AAPL");
+    assert_eq!(setup.signal, Signal::Buy);
+    assert!(setup.gap_pct > 0.0);
+    assert!(setup.gap_change > MIN_SCORE, "{setup:?}");
+}
+
+#[test]
+fn flat_history_scores_zero() {
+    let setup = assess("FLAT", &daily_bars(&flat_closes()), 0.0).unwrap();
+    assert_eq!(setup.score(), 0.0);
+    assert_eq!(rank([setup], MIN_SCORE), None);
+}
+
+#[test]
+fn short_history_is_not_assessed() {
+    let closes = vec![100.0; MIN_BARS - 1];
+    assert_eq!(assess("NEW", &daily_bars(&closes), 0.0), None);
+}
+
+#[test]
+fn rank_picks_the_largest_move_either_way() {
+    let top = rank(
+        [setup("AAPL", 0.4), setup("MSFT", -0.9), setup("NVDA", 0.6)],
+        MIN_SCORE,
+    )
+    .unwrap();
+    assert_eq!(top.symbol, "MSFT");
+}
+
+#[test]
+fn rank_skips_setups_below_the_threshold() {
+    assert_eq!(
+        rank([setup("AAPL", 0.1), setup("MSFT", -0.2)], MIN_SCORE),
+        None
+    );
+    assert_eq!(rank([], MIN_SCORE), None);
+}
+
+#[test]
+fn rank_breaks_ties_by_symbol() {
+    let top = rank([setup("MSFT", 0.5), setup("AAPL", -0.5)], MIN_SCORE).unwrap();
+    assert_eq!(top.symbol, "AAPL");
+}
@@ -0,0 +1,97 @@
+use stock::indicators::{
+    cdc::{Signal, calculate, last_crossover, trend},
+    spread::{self, split_by_trend},
+};
+
+#[test]
+fn spread_is_the_fast_average_less_the_slow() {
+    let got = spread::calculate(&[10.0, 12.0, 11.0], &[11.0, 11.5, 11.0]);
+    assert_eq!(got, vec![-1.0, 0.5, 0.0]);
+    assert!(spread::calculate(&[f64::NAN], &[1.0])[0].is_nan());
+}
+
+#[test]
+fn spread_follows_the_emas() {
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+    let (_, ema12, ema26) = calculate(&closes, 0.0);
+    let got = spread::calculate(&ema12, &ema26);
+
+    assert_eq!(got.len(), closes.len());
+    // a steady climb keeps the fast average above the slow one
+    assert!(got[1..].iter().all(|v| *v > 0.0), "{got:?}");
+    assert_eq!(got[59], ema12[59] - ema26[59]);
+}
+
+#[test]
+fn crossings_are_where_the_trend_flips() {
+    let slow = [100.0; 7];
+    let fast = [98.0, 99.0, 100.5, 101.0, 99.5, 99.0, 102.0];
+    assert_eq!(
+        spread::crossings(&fast, &slow, 0.0),
+        [(2, Signal::Buy), (4, Signal::Sell), (6, Signal::Buy)]
+    );
+}
+
+#[test]
+fn a_dip_inside_the_band_is_not_a_crossing() {
+    let slow = [100.0; 5];
+    // the spread turns positive at 1 and negative at 3, but only clears
+    // the 1% band at 2 and 4
+    let fast = [99.0, 100.5, 102.0, 99.5, 98.0];
+    assert_eq!(
+        spread::crossings(&fast, &slow, 1.0),
+        [(2, Signal::Buy), (4, Signal::Sell)]
+    );
+    assert!(spread::crossings(&[101.0, 99.5, 101.0], &[100.0; 3], 1.0).is_empty());
+    assert!(spread::crossings(&[], &[], 1.0).is_empty());
+}
+
+#[test]
+fn the_last_crossing_is_the_last_crossover() {
+    let closes: Vec<f64> = (0..120)
+        .map(|i| 100.0 + 10.0 * (i as f64 / 10.0).sin())
+        .collect();
+    for band_pct in [0.0, 0.5] {
+        let (_, ema12, ema26) = calculate(&closes, band_pct);
+        let crossings = spread::crossings(&ema12, &ema26, band_pct);
+
+        assert!(crossings.len() > 1, "{crossings:?}");
+        assert_eq!(
+            crossings.last().copied(),
+            last_crossover(&ema12, &ema26, band_pct)
+        );
+    }
+}
+
+#[test]
+fn split_keeps_each_side_and_joins_at_flips() {
+    let spread = [1.0, 2.0, -1.0, 0.5, 3.0];
+    // the trend holds bearish through the small positive spread at 3
+    let trend = [true, true, false, false, true];
+    let (bull, bear) = split_by_trend(&spread, &trend);
+    let shown = |line: &[f64]| -> Vec<Option<f64>> {
+        line.iter()
+            .map(|v| Some(*v).filter(|v| !v.is_nan()))
+            .collect()
+    };
+
+    assert_eq!(
+        shown(&bull),
+        [Some(1.0), Some(2.0), None, Some(0.5), Some(3.0)]
+    );
+    assert_eq!(shown(&bear), [None, Some(2.0), Some(-1.0), Some(0.5), None]);
+}
+
+#[test]
+fn split_follows_the_trend_not_the_sign() {
+    let slow = [100.0; 4];
+    let fast = [102.0, 99.5, 99.75, 98.0];
+    let values = spread::calculate(&fast, &slow);
+    let (bull, bear) = split_by_trend(&values, &trend(&fast, &slow, 1.0));
+
+    // negative, but still inside the band of a bullish trend
+    assert_eq!(bull[1], -0.5);
+    assert_eq!(bull[2], -0.25);
+    assert!(bear[..2].iter().all(|v| v.is_nan()));
+    assert_eq!(bear[3], -2.0);
+}
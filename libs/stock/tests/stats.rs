@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use stock::{
+    Scope,
+    stats::{DayUsage, RETENTION_DAYS, day_key, summarize, users_key, window},
+};
+
+const GUILD: Scope = Scope::Guild(1);
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn day(date: NaiveDate, commands: &[(&str, u64)], users: &[(u64, u64)]) -> DayUsage {
+    DayUsage {
+        date,
+        commands: commands
+            .iter()
+            .map(|(c, n)| (c.to_string(), *n))
+            .collect::<HashMap<_, _>>(),
+        users: users.iter().copied().collect(),
+    }
+}
+
+#[test]
+fn keys_are_zero_padded_dates_per_server() {
+    assert_eq!(day_key(GUILD, date(2025, 3, 7)), "usage:guild:1:20250307");
+    assert_eq!(
+        users_key(GUILD, date(2025, 3, 7)),
+        "usage:guild:1:20250307:users"
+    );
+    assert_eq!(
+        day_key(Scope::Guild(2), date(2024, 12, 31)),
+        "usage:guild:2:20241231"
+    );
+}
+
+#[test]
+fn window_ends_today_oldest_first() {
+    let today = date(2025, 3, 2);
+    assert_eq!(
+        window(today, 3),
+        vec![date(2025, 2, 28), date(2025, 3, 1), date(2025, 3, 2)]
+    );
+    assert_eq!(window(today, 1), vec![today]);
+}
+
+#[test]
+fn window_crosses_year_boundary() {
+    let keys: Vec<String> = window(date(2025, 1, 1), 2)
+        .into_iter()
+        .map(|date| day_key(GUILD, date))
+        .collect();
+    assert_eq!(
+        keys,
+        vec!["usage:guild:1:20241231", "usage:guild:1:20250101"]
+    );
+}
+
+#[test]
+fn window_is_clamped_to_retention() {
+    let today = date(2025, 6, 30);
+    assert_eq!(window(today, 0), vec![today]);
+    let capped = window(today, 365);
+    assert_eq!(capped.len(), RETENTION_DAYS as usize);
+    assert_eq!(capped.last(), Some(&today));
+}
+
+#[test]
+fn summarize_adds_up_commands_across_days() {
+    let days = [
+        day(
+            date(2025, 3, 1),
+            &[("stock watch", 3), ("stock graph", 5)],
+            &[],
+        ),
+        day(
+            date(2025, 3, 2),
+            &[("stock graph", 1), ("stock list", 4)],
+            &[],
+        ),
+    ];
+    let summary = summarize(&days);
+    assert_eq!(summary.total, 13);
+    assert_eq!(
+        summary.commands,
+        vec![
+            ("stock graph".to_string(), 6),
+            ("stock list".to_string(), 4),
+            ("stock watch".to_string(), 3),
+        ]
+    );
+}
+
+#[test]
+fn summarize_breaks_command_ties_by_name() {
+    let days = [day(date(2025, 3, 1), &[("b", 2), ("a", 2), ("c", 1)], &[])];
+    let names: Vec<String> = summarize(&days)
+        .commands
+        .into_iter()
+        .map(|(c, _)| c)
+        .collect();
+    assert_eq!(names, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn busiest_day_prefers_earliest_on_tie() {
+    let days = [
+        day(date(2025, 3, 1), &[("a", 2)], &[]),
+        day(date(2025, 3, 2), &[("a", 5)], &[]),
+        day(date(2025, 3, 3), &[("b", 5)], &[]),
+    ];
+    assert_eq!(summarize(&days).busiest_day, Some((date(2025, 3, 2), 5)));
+}
+
+#[test]
+fn empty_window_has_no_busiest_day() {
+    let days = [day(date(2025, 3, 1), &[], &[])];
+    let summary = summarize(&days);
+    assert_eq!(summary.total, 0);
+    assert!(summary.commands.is_empty());
+    assert_eq!(summary.busiest_day, None);
+}
+
+#[test]
+fn top_users_ranked_and_capped() {
+    let days = [
+        day(
+            date(2025, 3, 1),
+            &[("a", 30)],
+            &[(1, 4), (2, 9), (3, 1), (4, 2)],
+        ),
+        day(date(2025, 3, 2), &[("a", 10)], &[(1, 6), (5, 3), (6, 3)]),
+    ];
+    let summary = summarize(&days);
+    assert_eq!(
+        summary.top_users,
+        vec![(1, 10), (2, 9), (5, 3), (6, 3), (4, 2)]
+    );
+}
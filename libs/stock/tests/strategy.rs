@@ -0,0 +1,192 @@
+mod common;
+
+use stock::{
+    Timeframe,
+    indicators::cdc::{DEFAULT_BAND_PCT, Signal, calculate},
+    scan::ScanFrame,
+    strategy::{AdxFilter, MaKind, Strategy, require_trend},
+};
+
+use common::{crossover_closes, daily_bars};
+
+fn sma_adx() -> Strategy {
+    Strategy {
+        ma: MaKind::Sma,
+        fast: 5,
+        slow: 20,
+        adx: Some(AdxFilter {
+            period: 14,
+            min: 25.0,
+        }),
+    }
+}
+
+#[test]
+fn strategy_round_trips_through_json() {
+    for strategy in [Strategy::default(), sma_adx()] {
+        let json = serde_json::to_string(&strategy).unwrap();
+        assert_eq!(serde_json::from_str::<Strategy>(&json).unwrap(), strategy);
+    }
+    assert_eq!(
+        serde_json::to_value(sma_adx()).unwrap(),
+        serde_json::json!({
+            "ma": "sma",
+            "fast": 5,
+            "slow": 20,
+            "adx": { "period": 14, "min": 25.0 }
+        })
+    );
+}
+
+#[test]
+fn missing_fields_take_defaults() {
+    assert_eq!(
+        serde_json::from_str::<Strategy>("{}").unwrap(),
+        Strategy::default()
+    );
+    let partial: Strategy = serde_json::from_str(r#"{"ma": "sma"}"#).unwrap();
+    assert_eq!(partial.ma, MaKind::Sma);
+    assert_eq!((partial.fast, partial.slow, partial.adx), (12, 26, None));
+}
+
+#[test]
+fn displays_periods_and_filter() {
+    assert_eq!(Strategy::default().to_string(), "EMA 12/26");
+    assert_eq!(sma_adx().to_string(), "SMA 5/20, ADX 14 ≥ 25");
+    assert_eq!(
+        sma_adx().line_names(),
+        ("SMA5".to_string(), "SMA20".to_string())
+    );
+}
+
+#[test]
+fn validate_rejects_nonsense_periods() {
+    let bars = ScanFrame::daily().expected_bars();
+    assert!(Strategy::default().validate(bars).is_ok());
+    assert!(sma_adx().validate(bars).is_ok());
+
+    let bad = [
+        Strategy {
+            fast: 0,
+            ..Default::default()
+        },
+        Strategy {
+            fast: 26,
+            slow: 12,
+            ..Default::default()
+        },
+        Strategy {
+            fast: 20,
+            slow: 20,
+            ..Default::default()
+        },
+        Strategy {
+            fast: 50,
+            slow: bars,
+            ..Default::default()
+        },
+        Strategy {
+            adx: Some(AdxFilter {
+                period: 1,
+                min: 25.0,
+            }),
+            ..Default::default()
+        },
+        Strategy {
+            adx: Some(AdxFilter {
+                period: 14,
+                min: 101.0,
+            }),
+            ..Default::default()
+        },
+    ];
+    for strategy in bad {
+        assert!(strategy.validate(bars).is_err(), "{strategy:?} accepted");
+    }
+}
+
+#[test]
+fn periods_are_bounded_by_the_bars_a_scan_reads() {
+    let daily = ScanFrame::daily().expected_bars();
+    let hourly = ScanFrame::intraday(Timeframe::Hour1, false).expected_bars();
+    assert!((200..=210).contains(&daily), "{daily}");
+    assert!(hourly < daily, "{hourly}");
+
+    let long = Strategy {
+        fast: 50,
+        slow: 200,
+        ..Default::default()
+    };
+    assert!(long.validate(daily).is_ok());
+    assert!(long.validate(hourly).is_err());
+
+    let slow_adx = Strategy {
+        adx: Some(AdxFilter {
+            period: 100,
+            min: 25.0,
+        }),
+        ..Default::default()
+    };
+    assert!(slow_adx.validate(daily).is_ok());
+    assert!(slow_adx.validate(hourly).is_err());
+}
+
+#[test]
+fn default_strategy_matches_cdc() {
+    let closes = crossover_closes();
+    let bars = daily_bars(&closes);
+
+    let (signal, fast, slow) = Strategy::default().evaluate(&bars, DEFAULT_BAND_PCT);
+    let (cdc_signal, ema12, ema26) = calculate(&closes, DEFAULT_BAND_PCT);
+    assert_eq!(signal, cdc_signal);
+    assert_eq!(fast, ema12);
+    assert_eq!(slow, ema26);
+}
+
+#[test]
+fn sma_averages_the_window() {
+    let closes = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let sma = MaKind::Sma.calculate(&closes, 3);
+    assert_eq!(sma[5], 5.0);
+    assert_eq!(sma[2], 2.0);
+}
+
+#[test]
+fn weak_trend_drops_the_crossover() {
+    let bars = daily_bars(&crossover_closes());
+    let plain = Strategy::default();
+    assert_eq!(plain.evaluate(&bars, 0.0).0, Signal::Buy);
+
+    let strict = Strategy {
+        adx: Some(AdxFilter {
+            period: 14,
+            min: 99.0,
+        }),
+        ..plain
+    };
+    assert_eq!(strict.evaluate(&bars, 0.0).0, Signal::BullishZone);
+
+    let lenient = Strategy {
+        adx: Some(AdxFilter {
+            period: 14,
+            min: 0.0,
+        }),
+        ..plain
+    };
+    assert_eq!(lenient.evaluate(&bars, 0.0).0, Signal::Buy);
+}
+
+#[test]
+fn require_trend_only_touches_crossovers() {
+    assert_eq!(require_trend(Signal::Buy, 30.0, 25.0), Signal::Buy);
+    assert_eq!(require_trend(Signal::Buy, 20.0, 25.0), Signal::BullishZone);
+    assert_eq!(require_trend(Signal::Sell, 20.0, 25.0), Signal::BearishZone);
+    assert_eq!(
+        require_trend(Signal::Sell, f64::NAN, 25.0),
+        Signal::BearishZone
+    );
+    assert_eq!(
+        require_trend(Signal::BearishZone, 0.0, 25.0),
+        Signal::BearishZone
+    );
+}
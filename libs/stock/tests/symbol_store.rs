@@ -0,0 +1,1045 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use stock::{
+    BrowseState, DeleteOutcome, GuildSettings, PostedSignal, Scope, SymbolImpact, SymbolMeta,
+    Timeframe, UserPrefs, WatchlistCleanup,
+    alert::{Alert, Direction},
+    indicators::cdc::Signal,
+    key_category, mute_active, mute_score, mute_until,
+    report::RunRecord,
+    scan::ScanReading,
+    strategy::{MaKind, Strategy},
+    used_memory,
+};
+
+use common::redis_store;
+
+const GUILD: Scope = Scope::Guild(1);
+
+#[tokio::test]
+async fn add_remove_round_trip() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert!(store.is_empty(GUILD).await.unwrap());
+    assert!(store.add(GUILD, " aapl ").await.unwrap());
+    assert!(!store.add(GUILD, "AAPL").await.unwrap());
+    assert!(store.add(GUILD, "msft").await.unwrap());
+
+    let mut symbols = store.list(GUILD).await.unwrap();
+    symbols.sort();
+    assert_eq!(symbols, vec!["AAPL", "MSFT"]);
+    assert_eq!(store.len(GUILD).await.unwrap(), 2);
+
+    assert!(store.remove(GUILD, "aapl").await.unwrap());
+    assert!(!store.remove(GUILD, "aapl").await.unwrap());
+    assert!(store.remove(GUILD, "MSFT").await.unwrap());
+    assert!(store.is_empty(GUILD).await.unwrap());
+}
+
+#[tokio::test]
+async fn search_ranks_the_scope_watchlist() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    for symbol in ["GOOG", "GOOGL", "AAPL"] {
+        store.add(GUILD, symbol).await.unwrap();
+    }
+    store.add(Scope::User(7), "GOOGLX").await.unwrap();
+
+    assert_eq!(
+        store.search(GUILD, "goo", 5).await.unwrap(),
+        vec!["GOOG", "GOOGL"]
+    );
+    assert_eq!(store.search(GUILD, "APPL", 5).await.unwrap(), vec!["AAPL"]);
+    assert!(store.search(GUILD, "MSFT", 5).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn a_second_daily_run_of_the_same_day_is_refused() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+    assert!(store.try_daily_lock(today).await.unwrap());
+    assert!(!store.try_daily_lock(today).await.unwrap());
+    // a released lock can be claimed again, for retrying a failed run
+    store.release_daily_lock(today).await.unwrap();
+    assert!(store.try_daily_lock(today).await.unwrap());
+    assert!(
+        store
+            .try_daily_lock(today.succ_opt().unwrap())
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn a_cooldown_refuses_with_the_seconds_left() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert_eq!(store.try_cooldown("trigger", 7, 60).await.unwrap(), None);
+    let left = store.try_cooldown("trigger", 7, 60).await.unwrap();
+    assert!(matches!(left, Some(59..=60)), "{left:?}");
+
+    // each user and command has its own window
+    assert_eq!(store.try_cooldown("trigger", 8, 60).await.unwrap(), None);
+    assert_eq!(store.try_cooldown("benchmark", 7, 60).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn an_expired_cooldown_can_be_claimed_again() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert_eq!(store.try_cooldown("trigger", 7, 1).await.unwrap(), None);
+    assert!(store.try_cooldown("trigger", 7, 1).await.unwrap().is_some());
+
+    tokio::time::sleep(std::time::Duration::from_millis(1_100)).await;
+    assert_eq!(store.try_cooldown("trigger", 7, 1).await.unwrap(), None);
+}
+
+#[test]
+fn a_mute_lapses_at_its_expiry() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 16, 20, 0, 0).unwrap();
+    let until = now + Duration::days(3);
+
+    let score = mute_score(Some(until));
+    assert_eq!(mute_until(score), Some(until));
+    assert!(mute_active(score, now));
+    assert!(!mute_active(score, until));
+    assert!(!mute_active(score, until + Duration::seconds(1)));
+}
+
+#[test]
+fn an_open_ended_mute_never_lapses() {
+    let score = mute_score(None);
+    assert!(score.is_infinite());
+    assert_eq!(mute_until(score), None);
+    assert!(mute_active(score, Utc::now() + Duration::days(3650)));
+}
+
+#[tokio::test]
+async fn an_expired_mute_unmutes_itself() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    store.add(GUILD, "AAPL").await.unwrap();
+    store.add(GUILD, "MSFT").await.unwrap();
+
+    store
+        .mute(GUILD, "aapl", Some(Utc::now() - Duration::hours(1)))
+        .await
+        .unwrap();
+    assert!(!store.is_muted(GUILD, "AAPL").await.unwrap());
+    assert!(store.list_muted(GUILD).await.unwrap().is_empty());
+    assert!(!store.unmute(GUILD, "AAPL").await.unwrap());
+
+    let until = Utc::now() + Duration::days(2);
+    store.mute(GUILD, "msft", Some(until)).await.unwrap();
+    assert!(store.is_muted(GUILD, "MSFT").await.unwrap());
+    let muted = store.list_muted(GUILD).await.unwrap();
+    assert_eq!(muted.len(), 1);
+    assert_eq!(
+        muted["MSFT"].map(|t| t.timestamp()),
+        Some(until.timestamp())
+    );
+    assert!(store.unmute(GUILD, "MSFT").await.unwrap());
+    assert!(!store.is_muted(GUILD, "MSFT").await.unwrap());
+}
+
+#[tokio::test]
+async fn removing_a_symbol_drops_its_mute() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    store.add(GUILD, "AAPL").await.unwrap();
+    store.mute(GUILD, "AAPL", None).await.unwrap();
+    assert_eq!(store.list_muted(GUILD).await.unwrap()["AAPL"], None);
+
+    store.remove(GUILD, "AAPL").await.unwrap();
+    assert!(!store.is_muted(GUILD, "AAPL").await.unwrap());
+}
+
+#[tokio::test]
+async fn entry_prices_are_kept_until_cleared_or_removed() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    store.add(GUILD, "AAPL").await.unwrap();
+    store.add(GUILD, "MSFT").await.unwrap();
+    assert_eq!(store.get_entry(GUILD, "AAPL").await.unwrap(), None);
+
+    store.set_entry(GUILD, "aapl", 150.25).await.unwrap();
+    store.set_entry(GUILD, "MSFT", 400.0).await.unwrap();
+    assert_eq!(store.get_entry(GUILD, "AAPL").await.unwrap(), Some(150.25));
+    let entries = store.list_entries(GUILD).await.unwrap();
+    assert_eq!(
+        entries,
+        HashMap::from([("AAPL".into(), 150.25), ("MSFT".into(), 400.0)])
+    );
+
+    assert!(store.clear_entry(GUILD, "MSFT").await.unwrap());
+    assert!(!store.clear_entry(GUILD, "MSFT").await.unwrap());
+    store.remove(GUILD, "AAPL").await.unwrap();
+    assert!(store.list_entries(GUILD).await.unwrap().is_empty());
+}
+
+#[test]
+fn keys_are_categorised_by_record() {
+    assert_eq!(key_category("guild:1:watchlist"), "watchlist");
+    assert_eq!(key_category("user:5:alerts"), "alerts");
+    assert_eq!(key_category("guild:1:last_signal:1Hour"), "last_signal");
+    assert_eq!(key_category("pending_del:9-1700000000"), "pending_del");
+    assert_eq!(key_category("runs:2026-10-16"), "runs");
+    assert_eq!(key_category("usage:20261016:users"), "usage");
+    assert_eq!(key_category("guilds"), "guilds");
+    assert_eq!(key_category("watchlist"), "watchlist");
+}
+
+#[test]
+fn used_memory_is_read_from_info() {
+    let info = "# Memory\r\nused_memory:1048576\r\nused_memory_human:1.00M\r\n";
+    assert_eq!(used_memory(info), Some(1_048_576));
+    assert_eq!(used_memory("# Memory\r\nmaxmemory:0\r\n"), None);
+}
+
+#[tokio::test]
+async fn key_stats_count_keys_per_category() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    store.add(GUILD, "AAPL").await.unwrap();
+    store.add(Scope::User(7), "MSFT").await.unwrap();
+    store
+        .set_pending_delete("req".into(), vec!["AAPL".into()])
+        .await
+        .unwrap();
+
+    let stats = store.key_stats().await.unwrap();
+    assert_eq!(stats.categories.get("watchlist"), Some(&2));
+    assert_eq!(stats.categories.get("pending_del"), Some(&1));
+    assert_eq!(stats.keys(), stats.categories.values().sum::<usize>());
+}
+
+#[tokio::test]
+async fn pending_deletes_expire_after_the_configured_ttl() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let ttl = std::time::Duration::from_secs(42);
+    let store = store.with_pending_delete_ttl(ttl);
+    assert_eq!(store.pending_delete_window(), ttl);
+
+    store
+        .set_pending_delete("req".into(), vec!["AAPL".into()])
+        .await
+        .unwrap();
+    let left = store
+        .pending_delete_ttl("req".into())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(left <= ttl && left.as_secs() >= 40, "{left:?}");
+
+    assert!(store.refresh_pending_delete("req".into()).await.unwrap());
+    let left = store
+        .pending_delete_ttl("req".into())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(left <= ttl && left.as_secs() >= 40, "{left:?}");
+
+    // zero would expire at once, so the store keeps what it had
+    let store = store.with_pending_delete_ttl(std::time::Duration::ZERO);
+    assert_eq!(store.pending_delete_window(), ttl);
+}
+
+#[tokio::test]
+async fn pending_delete_round_trip() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert_eq!(store.get_pending_delete("req".into()).await.unwrap(), None);
+
+    let added = store
+        .set_pending_delete("req".into(), vec!["aapl".into(), "msft".into()])
+        .await
+        .unwrap();
+    assert_eq!(added, 2);
+
+    let mut pending = store
+        .get_pending_delete("req".into())
+        .await
+        .unwrap()
+        .unwrap();
+    pending.sort();
+    assert_eq!(pending, vec!["AAPL", "MSFT"]);
+
+    // setting again replaces rather than merges
+    store
+        .set_pending_delete("req".into(), vec!["tsla".into()])
+        .await
+        .unwrap();
+    assert_eq!(
+        store.get_pending_delete("req".into()).await.unwrap(),
+        Some(vec!["TSLA".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn refreshing_a_pending_delete_restarts_its_expiry() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert!(!store.refresh_pending_delete("req".into()).await.unwrap());
+
+    store
+        .set_pending_delete("req".into(), vec!["aapl".into()])
+        .await
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(2_100)).await;
+    let before = store
+        .pending_delete_ttl("req".into())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(before <= std::time::Duration::from_secs(298), "{before:?}");
+
+    assert!(store.refresh_pending_delete("req".into()).await.unwrap());
+    let after = store
+        .pending_delete_ttl("req".into())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(after > before, "{after:?} should exceed {before:?}");
+    assert!(after >= std::time::Duration::from_secs(299), "{after:?}");
+    assert_eq!(
+        store.get_pending_delete("req".into()).await.unwrap(),
+        Some(vec!["AAPL".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn concurrent_confirms_take_a_pending_delete_once() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    store
+        .set_pending_delete("req".into(), vec!["aapl".into(), "msft".into()])
+        .await
+        .unwrap();
+
+    let (first, second) = tokio::join!(
+        store.take_pending_delete("req".into()),
+        store.take_pending_delete("req".into())
+    );
+    let mut taken: Vec<_> = [first.unwrap(), second.unwrap()]
+        .into_iter()
+        .flatten()
+        .collect();
+    assert_eq!(taken.len(), 1, "both confirms got the symbols");
+    assert_eq!(taken.pop().unwrap(), vec!["AAPL", "MSFT"]);
+
+    // a later click finds it gone, like an expired request
+    assert_eq!(store.take_pending_delete("req".into()).await.unwrap(), None);
+    assert_eq!(store.get_pending_delete("req".into()).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn aliases_are_kept_per_guild() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert!(store.list_aliases(1).await.unwrap().is_empty());
+    assert!(store.set_alias(1, "fruit", "AAPL").await.unwrap());
+    assert!(!store.set_alias(1, "fruit", "APLE").await.unwrap());
+    assert!(store.set_alias(2, "fruit", "MSFT").await.unwrap());
+
+    let aliases = store.list_aliases(1).await.unwrap();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases["fruit"], "APLE");
+    assert_eq!(store.list_aliases(2).await.unwrap()["fruit"], "MSFT");
+
+    assert!(store.remove_alias(1, "fruit").await.unwrap());
+    assert!(!store.remove_alias(1, "fruit").await.unwrap());
+    assert!(store.list_aliases(1).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn browse_state_round_trips_per_message() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert_eq!(store.get_browse_state(1).await.unwrap(), None);
+
+    let state = BrowseState {
+        page: 2,
+        symbol: Some("AAPL".into()),
+    };
+    store.set_browse_state(1, &state).await.unwrap();
+    store
+        .set_browse_state(2, &BrowseState::default())
+        .await
+        .unwrap();
+
+    assert_eq!(store.get_browse_state(1).await.unwrap(), Some(state));
+    assert_eq!(
+        store.get_browse_state(2).await.unwrap(),
+        Some(BrowseState::default())
+    );
+}
+
+#[tokio::test]
+async fn pending_add_round_trip() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert_eq!(store.get_pending_add("req".into()).await.unwrap(), None);
+
+    store
+        .set_pending_add("req".into(), vec!["msft".into(), "aapl".into()])
+        .await
+        .unwrap();
+    assert_eq!(
+        store.get_pending_add("req".into()).await.unwrap(),
+        Some(vec!["AAPL".to_string(), "MSFT".to_string()])
+    );
+
+    // adds and deletes under the same id don't collide
+    assert_eq!(store.get_pending_delete("req".into()).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn add_many_reports_new_symbols() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    store.add(Scope::Guild(1), "AAPL").await.unwrap();
+    let added = store
+        .add_many(
+            Scope::Guild(1),
+            &["msft".into(), "AAPL".into(), "tsla".into()],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(added, vec!["MSFT", "TSLA"]);
+    assert_eq!(store.len(Scope::Guild(1)).await.unwrap(), 3);
+}
+
+fn reading(signal: Signal) -> ScanReading {
+    ScanReading {
+        signal,
+        close: 101.5,
+        ema12: 100.0,
+        ema26: 99.0,
+        timestamp: Utc.with_ymd_and_hms(2024, 3, 1, 5, 0, 0).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn list_with_last_signals_covers_every_symbol() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert!(
+        store
+            .list_with_last_signals(GUILD)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    for symbol in ["msft", "AAPL", "TSLA"] {
+        store.add(GUILD, symbol).await.unwrap();
+    }
+    store
+        .set_last_readings(
+            GUILD,
+            &[
+                ("aapl".into(), reading(Signal::Buy)),
+                ("MSFT".into(), reading(Signal::BearishZone)),
+            ],
+        )
+        .await
+        .unwrap();
+    store
+        .set_last_readings(GUILD, &[("msft".into(), reading(Signal::Sell))])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        store.list_with_last_signals(GUILD).await.unwrap(),
+        vec![
+            ("AAPL".to_string(), Some(Signal::Buy)),
+            ("MSFT".to_string(), Some(Signal::Sell)),
+            ("TSLA".to_string(), None),
+        ]
+    );
+
+    let readings = store.list_last_readings(GUILD).await.unwrap();
+    assert_eq!(readings[0].1, Some(reading(Signal::Buy)));
+
+    // removing a symbol drops its signal with it
+    store.remove(GUILD, "AAPL").await.unwrap();
+    store.add(GUILD, "AAPL").await.unwrap();
+    assert_eq!(
+        store.list_with_last_signals(GUILD).await.unwrap()[0],
+        ("AAPL".to_string(), None)
+    );
+}
+
+#[tokio::test]
+async fn guild_watchlists_are_isolated() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    store.add(Scope::Guild(1), "AAPL").await.unwrap();
+    store.add(Scope::Guild(2), "MSFT").await.unwrap();
+    store.add(Scope::User(1), "TSLA").await.unwrap();
+
+    assert_eq!(store.list(Scope::Guild(1)).await.unwrap(), vec!["AAPL"]);
+    assert_eq!(store.list(Scope::Guild(2)).await.unwrap(), vec!["MSFT"]);
+    assert_eq!(store.list(Scope::User(1)).await.unwrap(), vec!["TSLA"]);
+
+    assert!(!store.remove(Scope::Guild(2), "AAPL").await.unwrap());
+    assert_eq!(store.len(Scope::Guild(1)).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn guild_settings_register_guild() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert!(store.list_guilds().await.unwrap().is_empty());
+
+    let settings = GuildSettings {
+        daily_channel: Some(42),
+        ..Default::default()
+    };
+    store.set_guild_settings(7, &settings).await.unwrap();
+
+    assert_eq!(store.list_guilds().await.unwrap(), vec![7]);
+    assert_eq!(store.get_guild_settings(7).await.unwrap(), settings);
+}
+
+#[tokio::test]
+async fn strategy_round_trip_and_reset() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert_eq!(store.get_strategy(7).await.unwrap(), Strategy::default());
+
+    let strategy = Strategy {
+        ma: MaKind::Sma,
+        fast: 5,
+        slow: 20,
+        adx: None,
+    };
+    store.set_strategy(7, &strategy).await.unwrap();
+    assert_eq!(store.get_strategy(7).await.unwrap(), strategy);
+    assert_eq!(store.get_strategy(8).await.unwrap(), Strategy::default());
+
+    store.clear_strategy(7).await.unwrap();
+    assert_eq!(store.get_strategy(7).await.unwrap(), Strategy::default());
+}
+
+#[tokio::test]
+async fn quiet_period_round_trip_and_expiry() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let now = Utc::now();
+
+    store.add(GUILD, "NVDA").await.unwrap();
+    store
+        .set_quiet(GUILD, "nvda", now + Duration::days(7))
+        .await
+        .unwrap();
+    store
+        .set_quiet(GUILD, "AMD", now - Duration::days(1))
+        .await
+        .unwrap();
+
+    let meta = store.list_meta(GUILD).await.unwrap();
+    assert!(meta["NVDA"].quiet_at(now).is_some());
+    assert_eq!(meta["AMD"].quiet_at(now), None);
+
+    store.clear_quiet(GUILD, "NVDA").await.unwrap();
+    assert_eq!(
+        store.get_meta(GUILD, "NVDA").await.unwrap().quiet_until,
+        None
+    );
+
+    store
+        .set_quiet(GUILD, "NVDA", now + Duration::days(7))
+        .await
+        .unwrap();
+    store.remove(GUILD, "NVDA").await.unwrap();
+    assert!(!store.list_meta(GUILD).await.unwrap().contains_key("NVDA"));
+}
+
+#[tokio::test]
+async fn graph_timeframe_is_remembered_per_user_and_symbol() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert_eq!(store.get_graph_timeframe(1, "AAPL").await.unwrap(), None);
+
+    store
+        .set_graph_timeframe(1, "aapl", Timeframe::Hour1)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        store.get_graph_timeframe(1, "AAPL").await.unwrap(),
+        Some(Timeframe::Hour1)
+    );
+    assert_eq!(store.get_graph_timeframe(1, "MSFT").await.unwrap(), None);
+    assert_eq!(store.get_graph_timeframe(2, "AAPL").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn runs_round_trip_and_list_newest_first() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let day = |d| NaiveDate::from_ymd_opt(2024, 7, d).unwrap();
+
+    assert_eq!(store.get_run(day(16)).await.unwrap(), None);
+
+    for d in [15, 16, 12] {
+        store
+            .save_run(&RunRecord::new(day(d), Utc::now()))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(store.get_run(day(16)).await.unwrap().unwrap().date, day(16));
+    assert_eq!(store.list_runs(2).await.unwrap(), vec![day(16), day(15)]);
+    assert!(store.list_runs(0).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn digest_opt_in_toggles_membership() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    assert!(store.list_digest_users().await.unwrap().is_empty());
+
+    let mut prefs = UserPrefs {
+        daily_digest: true,
+        digest_guild: Some(7),
+        ..Default::default()
+    };
+    store.set_user_pref(1, &prefs).await.unwrap();
+    store.set_user_pref(2, &prefs).await.unwrap();
+    let mut users = store.list_digest_users().await.unwrap();
+    users.sort();
+    assert_eq!(users, vec![1, 2]);
+    assert_eq!(store.get_user_pref(1).await.unwrap(), prefs);
+
+    prefs.daily_digest = false;
+    store.set_user_pref(1, &prefs).await.unwrap();
+    assert_eq!(store.list_digest_users().await.unwrap(), vec![2]);
+    assert!(!store.get_user_pref(1).await.unwrap().daily_digest);
+}
+
+fn members(raw: &[&str]) -> Vec<String> {
+    raw.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn cleanup_collapses_case_and_whitespace_duplicates() {
+    let cleanup = WatchlistCleanup::plan(&members(&["aapl", "AAPL ", "AAPL"]));
+    assert_eq!(cleanup.symbols, vec!["AAPL"]);
+    assert!(cleanup.renamed.is_empty());
+    assert_eq!(cleanup.removed, members(&["AAPL ", "aapl"]));
+    assert!(!cleanup.is_clean());
+}
+
+#[test]
+fn cleanup_renames_when_no_member_is_normalized() {
+    let cleanup = WatchlistCleanup::plan(&members(&[" msft", "msft", "TSLA", "  "]));
+    assert_eq!(cleanup.symbols, vec!["MSFT", "TSLA"]);
+    assert_eq!(
+        cleanup.renamed,
+        vec![(" msft".to_string(), "MSFT".to_string())]
+    );
+    assert_eq!(cleanup.removed, members(&["  ", "msft"]));
+}
+
+#[test]
+fn cleanup_leaves_normalized_watchlist_alone() {
+    let cleanup = WatchlistCleanup::plan(&members(&["TSLA", "AAPL"]));
+    assert_eq!(cleanup.symbols, vec!["AAPL", "TSLA"]);
+    assert!(cleanup.is_clean());
+}
+
+#[tokio::test]
+async fn clean_keeps_normalized_watchlist_and_meta() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    store.add(GUILD, "aapl").await.unwrap();
+    store
+        .set_quiet(GUILD, "AAPL", Utc::now() + Duration::days(3))
+        .await
+        .unwrap();
+
+    let cleanup = store.clean(GUILD).await.unwrap();
+    assert!(cleanup.is_clean());
+    assert_eq!(cleanup.symbols, vec!["AAPL"]);
+    assert!(
+        store
+            .get_meta(GUILD, "AAPL")
+            .await
+            .unwrap()
+            .quiet_until
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn notes_are_kept_with_the_rest_of_the_meta() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    store.add(GUILD, "NVDA").await.unwrap();
+    store
+        .set_quiet(GUILD, "NVDA", Utc::now() + Duration::days(1))
+        .await
+        .unwrap();
+    store
+        .set_note(GUILD, "nvda", Some("watch the gap".into()))
+        .await
+        .unwrap();
+
+    let meta = store.get_meta(GUILD, "NVDA").await.unwrap();
+    assert_eq!(meta.note.as_deref(), Some("watch the gap"));
+    assert!(meta.quiet_until.is_some());
+
+    store.set_note(GUILD, "NVDA", None).await.unwrap();
+    assert_eq!(store.get_meta(GUILD, "NVDA").await.unwrap().note, None);
+}
+
+#[tokio::test]
+async fn stats_count_a_populated_store() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    let empty = store.stats().await.unwrap();
+    assert_eq!(empty.keys, 0);
+
+    store.add(GUILD, "AAPL").await.unwrap();
+    store.add(GUILD, "MSFT").await.unwrap();
+    store.add(Scope::User(9), "TSLA").await.unwrap();
+    store
+        .set_pending_delete("req".into(), vec!["AAPL".into()])
+        .await
+        .unwrap();
+    store
+        .set_pending_add("req".into(), vec!["NVDA".into()])
+        .await
+        .unwrap();
+    let now = Utc::now();
+    for (secs, price) in [(0, 100.0), (1, 200.0)] {
+        let alert = Alert::new(
+            "AAPL",
+            Direction::Above,
+            price,
+            now + Duration::seconds(secs),
+        );
+        store.save_alert(9, &alert).await.unwrap();
+    }
+
+    let stats = store.stats().await.unwrap();
+    assert_eq!(stats.watchlists, 2);
+    assert_eq!(stats.symbols, 3);
+    assert_eq!(stats.pending_sessions, 2);
+    assert_eq!(stats.alerts, 2);
+    // two watchlists, two pending sets, the alerts hash and its index
+    assert_eq!(stats.keys, 6);
+    if let Some(bytes) = stats.memory_bytes {
+        assert!(bytes > 0);
+    }
+}
+
+fn set(raw: &[&str]) -> HashSet<String> {
+    raw.iter().map(|s| s.to_string()).collect()
+}
+
+fn alert_on(symbol: &str, id: &str) -> Alert {
+    let mut alert = Alert::new(symbol, Direction::Above, 100.0, Utc::now());
+    alert.id = id.into();
+    alert
+}
+
+#[test]
+fn impact_gathers_each_symbols_records_in_order() {
+    let meta = HashMap::from([(
+        "TSLA".to_string(),
+        SymbolMeta {
+            added_at: Some(Utc::now()),
+            ..SymbolMeta::default()
+        },
+    )]);
+    let alerts = [
+        alert_on("TSLA", "a1"),
+        alert_on("AAPL", "a2"),
+        alert_on("tsla", "a3"),
+    ];
+
+    let impact = SymbolImpact::collect(
+        &members(&["tsla ", "AAPL", "GONE"]),
+        &set(&["TSLA", "AAPL"]),
+        &meta,
+        &set(&["AAPL"]),
+        &alerts,
+    );
+
+    let symbols: Vec<&str> = impact.iter().map(|i| i.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["TSLA", "AAPL", "GONE"]);
+    assert_eq!(impact[0].alert_ids, vec!["a1", "a3"]);
+    assert!(impact[0].meta.is_some() && !impact[0].has_reading);
+    assert_eq!(impact[0].records(), 3);
+    assert_eq!(impact[1].alert_ids, vec!["a2"]);
+    assert_eq!(impact[1].records(), 2);
+    assert!(!impact[2].watched);
+    assert_eq!(impact[2].records(), 0);
+}
+
+#[test]
+fn delete_outcome_reports_failures_without_counting_their_records() {
+    let impact = SymbolImpact::collect(
+        &members(&["TSLA", "AAPL"]),
+        &set(&["TSLA", "AAPL"]),
+        &HashMap::new(),
+        &set(&["TSLA", "AAPL"]),
+        &[alert_on("TSLA", "a1"), alert_on("AAPL", "a2")],
+    );
+
+    let mut outcome = DeleteOutcome::default();
+    outcome.record(&impact[0], Ok(()));
+    outcome.record(&impact[1], Err(anyhow!("connection reset")));
+
+    assert_eq!(outcome.removed, vec!["TSLA"]);
+    assert_eq!(outcome.cleaned, 2);
+    assert_eq!(
+        outcome.failed,
+        vec![("AAPL".to_string(), "connection reset".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn an_alert_run_never_restores_or_reverts_an_alert() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    for alert in [alert_on("AAPL", "a1"), alert_on("MSFT", "a2")] {
+        store.save_alert(7, &alert).await.unwrap();
+    }
+    let read = store.list_stored_alerts(7).await.unwrap();
+    let read_of = |id: &str| read.iter().find(|s| s.alert.id == id).unwrap();
+
+    // while the run evaluates them, the owner deletes one and re-sets the other
+    store.remove_alerts(7, &["a1".into()]).await.unwrap();
+    let mut reset = alert_on("MSFT", "a2");
+    reset.price = 120.0;
+    store.save_alert(7, &reset).await.unwrap();
+
+    let fired = alert_on("AAPL", "a1");
+    assert!(
+        !store
+            .settle_alert(7, read_of("a1"), Some(&fired))
+            .await
+            .unwrap()
+    );
+    let fired = alert_on("MSFT", "a2");
+    assert!(
+        !store
+            .settle_alert(7, read_of("a2"), Some(&fired))
+            .await
+            .unwrap()
+    );
+    assert_eq!(store.list_alerts(7).await.unwrap(), vec![reset]);
+
+    // an alert left as read is settled, and removing the last drops its owner
+    let read = store.list_stored_alerts(7).await.unwrap();
+    assert!(store.settle_alert(7, &read[0], None).await.unwrap());
+    assert!(store.list_alerts(7).await.unwrap().is_empty());
+    assert!(store.list_alert_users().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn removing_the_last_alert_drops_its_owner() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    for alert in [alert_on("AAPL", "a1"), alert_on("MSFT", "a2")] {
+        store.save_alert(7, &alert).await.unwrap();
+    }
+
+    let removed = store.remove_alerts(7, &["a1".into()]).await.unwrap();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(store.list_alert_users().await.unwrap(), vec![7]);
+
+    store.remove_alerts(7, &["a2".into()]).await.unwrap();
+    assert!(store.list_alert_users().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn cascade_delete_takes_meta_readings_and_owner_alerts() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let user = Scope::User(42);
+
+    store.add(user, "TSLA").await.unwrap();
+    store.add(user, "AAPL").await.unwrap();
+    store
+        .record_added(user, "TSLA", Utc::now() - Duration::days(84), Some(200.0))
+        .await
+        .unwrap();
+    store
+        .set_last_readings(user, &[("TSLA".into(), reading(Signal::Buy))])
+        .await
+        .unwrap();
+    for alert in [
+        alert_on("TSLA", "a1"),
+        alert_on("TSLA", "a2"),
+        alert_on("AAPL", "a3"),
+    ] {
+        store.save_alert(42, &alert).await.unwrap();
+    }
+
+    let impact = store
+        .impact_report(user, &members(&["tsla"]))
+        .await
+        .unwrap();
+    assert_eq!(impact.len(), 1);
+    assert!(impact[0].watched && impact[0].has_reading);
+    assert_eq!(impact[0].alert_ids, vec!["a1", "a2"]);
+    assert_eq!(impact[0].records(), 4);
+
+    let outcome = store.remove_cascade(user, &impact).await;
+    assert_eq!(outcome.removed, vec!["TSLA"]);
+    assert_eq!(outcome.cleaned, 4);
+    assert!(outcome.failed.is_empty());
+
+    assert_eq!(store.list(user).await.unwrap(), vec!["AAPL"]);
+    assert!(!store.list_meta(user).await.unwrap().contains_key("TSLA"));
+    let left: Vec<String> = store
+        .list_alerts(42)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    assert_eq!(left, vec!["a3"]);
+}
+
+#[tokio::test]
+async fn cascade_delete_from_a_server_leaves_members_alerts() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    store.add(GUILD, "TSLA").await.unwrap();
+    store.save_alert(42, &alert_on("TSLA", "a1")).await.unwrap();
+
+    let impact = store
+        .impact_report(GUILD, &members(&["TSLA"]))
+        .await
+        .unwrap();
+    assert!(impact[0].alert_ids.is_empty());
+
+    store.remove_cascade(GUILD, &impact).await;
+    assert!(store.is_empty(GUILD).await.unwrap());
+    assert_eq!(store.list_alerts(42).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn posted_signals_round_trip_per_scope() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+
+    let post = PostedSignal {
+        signal: Signal::Buy,
+        session: NaiveDate::from_ymd_opt(2024, 6, 17).unwrap(),
+        channel_id: 10,
+        message_id: 20,
+        embed_index: 3,
+    };
+    store
+        .set_posted(GUILD, &[("aapl".to_string(), post)])
+        .await
+        .unwrap();
+
+    let posted = store.posted(GUILD).await.unwrap();
+    assert_eq!(posted, HashMap::from([("AAPL".to_string(), post)]));
+    assert!(store.posted(Scope::Guild(2)).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn usage_is_kept_per_server_and_hides_users_who_opted_out() {
+    let Some(store) = redis_store().await else {
+        return;
+    };
+    let listed = UserPrefs {
+        show_in_stats: true,
+        ..Default::default()
+    };
+    store.set_user_pref(7, &listed).await.unwrap();
+    store.set_user_pref(8, &listed).await.unwrap();
+
+    store.incr_usage(GUILD, "stock graph", 7).await.unwrap();
+    store.incr_usage(GUILD, "stock graph", 8).await.unwrap();
+    store
+        .incr_usage(Scope::Guild(2), "stock watch", 7)
+        .await
+        .unwrap();
+    store.set_user_pref(8, &UserPrefs::default()).await.unwrap();
+
+    let today = [Utc::now().date_naive()];
+    let usage = store.usage_for(GUILD, &today).await.unwrap();
+    assert_eq!(
+        usage[0].commands,
+        HashMap::from([("stock graph".into(), 2)])
+    );
+    assert_eq!(usage[0].users, HashMap::from([(7, 1)]));
+    let other = store.usage_for(Scope::Guild(2), &today).await.unwrap();
+    assert_eq!(
+        other[0].commands,
+        HashMap::from([("stock watch".into(), 1)])
+    );
+}
@@ -0,0 +1,21 @@
+use stock::{
+    ChartJob,
+    indicators::cdc::{ChartTheme, generate_chart},
+};
+
+#[test]
+fn theme_previews_render_in_every_theme() {
+    for theme in ChartTheme::ALL {
+        let job = ChartJob::sample(theme);
+        let png = generate_chart(
+            &job.symbol,
+            &job.closes,
+            &job.ema12,
+            &job.ema26,
+            &job.dates,
+            &job.options,
+        )
+        .unwrap_or_else(|e| panic!("{theme:?} preview failed: {e:?}"));
+        assert!(png.starts_with(b"\x89PNG"), "{theme:?} is not a PNG");
+    }
+}
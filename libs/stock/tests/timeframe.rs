@@ -0,0 +1,42 @@
+use stock::{Timeframe, UserPrefs};
+
+#[test]
+fn requested_timeframe_wins() {
+    let tf = Timeframe::resolve(
+        Some(Timeframe::Minute5),
+        Some(Timeframe::Hour1),
+        Some(Timeframe::Week1),
+    );
+    assert_eq!(tf, Timeframe::Minute5);
+}
+
+#[test]
+fn remembered_timeframe_beats_the_user_default() {
+    let tf = Timeframe::resolve(None, Some(Timeframe::Hour1), Some(Timeframe::Week1));
+    assert_eq!(tf, Timeframe::Hour1);
+}
+
+#[test]
+fn user_default_applies_to_new_symbols() {
+    let tf = Timeframe::resolve(None, None, Some(Timeframe::Week1));
+    assert_eq!(tf, Timeframe::Week1);
+}
+
+#[test]
+fn falls_back_to_daily() {
+    assert_eq!(Timeframe::resolve(None, None, None), Timeframe::Day1);
+}
+
+#[test]
+fn timeframes_serialize_as_alpaca_names() {
+    assert_eq!(
+        serde_json::to_string(&Timeframe::Hour1).unwrap(),
+        "\"1Hour\""
+    );
+
+    let prefs: UserPrefs = serde_json::from_str(r#"{"ephemeral_replies":true}"#).unwrap();
+    assert_eq!(prefs.default_timeframe, None);
+
+    let prefs: UserPrefs = serde_json::from_str(r#"{"default_timeframe":"15Min"}"#).unwrap();
+    assert_eq!(prefs.default_timeframe, Some(Timeframe::Minute15));
+}
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use stock::timing::{
+    DEGRADATION_WINDOW, Recorder, RunTimings, ScanTimings, Stage, StageStats, TimingCollector,
+    degraded, median, percentile,
+};
+
+fn ms(values: &[u64]) -> Vec<Duration> {
+    values.iter().map(|v| Duration::from_millis(*v)).collect()
+}
+
+#[test]
+fn stats_average_and_tail_of_samples() {
+    // 1..=20 ms, shuffled: mean 10.5, p95 the 19th smallest
+    let samples = ms(&[
+        7, 15, 1, 20, 12, 3, 18, 9, 5, 14, 2, 11, 19, 6, 16, 10, 4, 13, 8, 17,
+    ]);
+    let stats = StageStats::from_samples(&samples).unwrap();
+    assert_eq!(stats.count, 20);
+    assert_eq!(stats.mean, Duration::from_micros(10_500));
+    assert_eq!(stats.p95, Duration::from_millis(19));
+    assert_eq!(stats.max, Duration::from_millis(20));
+}
+
+#[test]
+fn percentile_uses_nearest_rank() {
+    let sorted = ms(&[10, 20, 30, 40, 50]);
+    assert_eq!(percentile(&sorted, 0.0), Some(Duration::from_millis(10)));
+    assert_eq!(percentile(&sorted, 20.0), Some(Duration::from_millis(10)));
+    assert_eq!(percentile(&sorted, 50.0), Some(Duration::from_millis(30)));
+    assert_eq!(percentile(&sorted, 95.0), Some(Duration::from_millis(50)));
+    assert_eq!(percentile(&sorted, 100.0), Some(Duration::from_millis(50)));
+    assert_eq!(percentile(&[], 95.0), None);
+}
+
+#[test]
+fn single_sample_is_every_statistic() {
+    let stats = StageStats::from_samples(&ms(&[42])).unwrap();
+    assert_eq!(stats.count, 1);
+    assert_eq!(stats.mean, Duration::from_millis(42));
+    assert_eq!(stats.p95, Duration::from_millis(42));
+    assert_eq!(stats.max, Duration::from_millis(42));
+}
+
+#[test]
+fn no_samples_no_stats() {
+    assert_eq!(StageStats::from_samples(&[]), None);
+    assert_eq!(TimingCollector::default().stats(Stage::Fetch), None);
+}
+
+#[test]
+fn collector_keeps_stages_apart() {
+    let timings = TimingCollector::default();
+    timings.record(Stage::Fetch, Duration::from_millis(100));
+    timings.record(Stage::Fetch, Duration::from_millis(300));
+    timings.record(Stage::Render, Duration::from_millis(50));
+
+    let fetch = timings.stats(Stage::Fetch).unwrap();
+    assert_eq!(fetch.count, 2);
+    assert_eq!(fetch.mean, Duration::from_millis(200));
+    assert_eq!(timings.stats(Stage::Render).unwrap().count, 1);
+}
+
+#[test]
+fn collector_drops_oldest_past_capacity() {
+    let timings = TimingCollector::new(3);
+    for v in [1000, 10, 20, 30] {
+        timings.record(Stage::Render, Duration::from_millis(v));
+    }
+
+    let stats = timings.stats(Stage::Render).unwrap();
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.mean, Duration::from_millis(20));
+    assert_eq!(stats.max, Duration::from_millis(30));
+}
+
+#[test]
+fn median_of_odd_and_even_histories() {
+    assert_eq!(median(&ms(&[30, 10, 20])), Some(Duration::from_millis(20)));
+    assert_eq!(
+        median(&ms(&[40, 10, 30, 20])),
+        Some(Duration::from_millis(25))
+    );
+    assert_eq!(median(&ms(&[7])), Some(Duration::from_millis(7)));
+    assert_eq!(median(&[]), None);
+}
+
+#[test]
+fn run_is_degraded_past_twice_the_median() {
+    let history = ms(&[60_000, 55_000, 65_000, 58_000, 62_000]);
+    assert_eq!(degraded(Duration::from_millis(110_000), &history), None);
+    // exactly twice the usual still counts as in bounds
+    assert_eq!(degraded(Duration::from_millis(120_000), &history), None);
+    assert_eq!(
+        degraded(Duration::from_millis(120_001), &history),
+        Some(Duration::from_millis(60_000))
+    );
+}
+
+#[test]
+fn outlier_past_runs_barely_move_the_baseline() {
+    // one earlier run that hung doesn't make today look fine
+    let history = ms(&[60_000, 600_000, 60_000, 61_000, 59_000]);
+    assert_eq!(
+        degraded(Duration::from_millis(130_000), &history),
+        Some(Duration::from_millis(60_000))
+    );
+}
+
+#[test]
+fn too_little_history_is_never_degraded() {
+    assert_eq!(degraded(Duration::from_secs(3_600), &[]), None);
+    assert_eq!(
+        degraded(Duration::from_secs(3_600), &ms(&[1_000, 1_000])),
+        None
+    );
+    assert!(degraded(Duration::from_secs(3_600), &ms(&[1_000, 1_000, 1_000])).is_some());
+}
+
+#[test]
+fn only_the_most_recent_runs_count() {
+    // ten recent fast runs, then a long tail of slow ones from before a fix
+    let mut history = vec![Duration::from_secs(30); DEGRADATION_WINDOW];
+    history.extend(vec![Duration::from_secs(600); 20]);
+    assert_eq!(
+        degraded(Duration::from_secs(90), &history),
+        Some(Duration::from_secs(30))
+    );
+
+    // and the other way round: a recent slowdown becomes the new normal
+    let mut history = vec![Duration::from_secs(600); DEGRADATION_WINDOW];
+    history.extend(vec![Duration::from_secs(30); 20]);
+    assert_eq!(degraded(Duration::from_secs(900), &history), None);
+}
+
+#[test]
+fn scan_timings_total_each_stage() {
+    let timings = ScanTimings::default();
+    timings.record(Stage::Fetch, Duration::from_millis(300));
+    timings.record(Stage::Fetch, Duration::from_millis(200));
+    timings.record(Stage::Indicators, Duration::from_millis(5));
+    timings.record(Stage::Render, Duration::from_millis(900));
+    timings.record(Stage::Send, Duration::from_millis(40));
+
+    assert_eq!(timings.total(Stage::Fetch), Duration::from_millis(500));
+    assert_eq!(
+        timings.finish(Duration::from_millis(1_200)),
+        RunTimings {
+            fetch_ms: 500,
+            indicators_ms: 5,
+            render_ms: 900,
+            send_ms: 40,
+            wall_ms: 1_200,
+        }
+    );
+    assert_eq!(
+        timings.finish(Duration::from_secs(2)).wall(),
+        Duration::from_secs(2)
+    );
+}
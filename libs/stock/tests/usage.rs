@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use stock::{
+    scan::{CONCURRENCY, LOW_BUDGET, concurrency_for},
+    usage::{ApiUsage, UsageTracker, WINDOW},
+};
+
+fn burst(tracker: &UsageTracker, at: Instant, count: u32) {
+    for _ in 0..count {
+        tracker.record_at(at);
+    }
+}
+
+#[test]
+fn fresh_tracker_has_the_full_budget() {
+    let tracker = UsageTracker::new(200);
+    let usage = tracker.usage_at(Instant::now());
+    assert_eq!(
+        usage,
+        ApiUsage {
+            used: 0,
+            limit: 200,
+            remaining: 200
+        }
+    );
+}
+
+#[test]
+fn requests_leave_the_window_after_a_minute() {
+    let tracker = UsageTracker::new(200);
+    let start = Instant::now();
+    burst(&tracker, start, 150);
+    burst(&tracker, start + Duration::from_secs(30), 30);
+
+    assert_eq!(tracker.usage_at(start + Duration::from_secs(45)).used, 180);
+    let later = tracker.usage_at(start + WINDOW);
+    assert_eq!(later.used, 30);
+    assert_eq!(later.remaining, 170);
+    assert_eq!(tracker.usage_at(start + WINDOW * 2).used, 0);
+}
+
+#[test]
+fn budget_never_goes_negative() {
+    let tracker = UsageTracker::new(10);
+    let now = Instant::now();
+    burst(&tracker, now, 25);
+    let usage = tracker.usage_at(now);
+    assert_eq!(usage.used, 25);
+    assert_eq!(usage.remaining, 0);
+}
+
+#[test]
+fn reported_remaining_lowers_the_budget() {
+    let tracker = UsageTracker::new(200);
+    let start = Instant::now();
+    burst(&tracker, start, 10);
+    // another process sharing the key has used most of it
+    tracker.observe_remaining_at(start, 40);
+    burst(&tracker, start + Duration::from_secs(1), 5);
+
+    assert_eq!(
+        tracker.usage_at(start + Duration::from_secs(2)).remaining,
+        35
+    );
+}
+
+#[test]
+fn reported_remaining_above_our_count_is_ignored() {
+    let tracker = UsageTracker::new(200);
+    let now = Instant::now();
+    burst(&tracker, now, 50);
+    tracker.observe_remaining_at(now, 199);
+    assert_eq!(tracker.usage_at(now).remaining, 150);
+}
+
+#[test]
+fn stale_reports_expire_with_the_window() {
+    let tracker = UsageTracker::new(200);
+    let start = Instant::now();
+    tracker.observe_remaining_at(start, 5);
+    assert_eq!(tracker.usage_at(start).remaining, 5);
+    assert_eq!(tracker.usage_at(start + WINDOW).remaining, 200);
+}
+
+#[test]
+fn concurrency_is_full_without_a_budget_or_with_plenty_left() {
+    assert_eq!(concurrency_for(None), CONCURRENCY);
+
+    let tracker = UsageTracker::new(200);
+    let now = Instant::now();
+    burst(&tracker, now, 200 - LOW_BUDGET);
+    assert_eq!(concurrency_for(Some(tracker.usage_at(now))), CONCURRENCY);
+}
+
+#[test]
+fn concurrency_drops_after_a_burst_and_recovers() {
+    let tracker = UsageTracker::new(200);
+    let start = Instant::now();
+    burst(&tracker, start, 180);
+
+    let during = concurrency_for(Some(tracker.usage_at(start + Duration::from_secs(10))));
+    assert!(during < CONCURRENCY, "got {during}");
+    assert!(during >= 1);
+
+    let after = concurrency_for(Some(tracker.usage_at(start + WINDOW)));
+    assert_eq!(after, CONCURRENCY);
+}
+
+#[test]
+fn concurrency_scales_with_what_is_left() {
+    let at = |remaining| {
+        concurrency_for(Some(ApiUsage {
+            used: 200 - remaining,
+            limit: 200,
+            remaining,
+        }))
+    };
+    assert_eq!(
+        at(LOW_BUDGET - 1),
+        CONCURRENCY * (LOW_BUDGET as usize - 1) / LOW_BUDGET as usize
+    );
+    assert_eq!(at(LOW_BUDGET / 2), CONCURRENCY / 2);
+    assert_eq!(at(1), 1);
+    assert_eq!(at(0), 1);
+}
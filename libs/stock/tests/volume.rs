@@ -0,0 +1,81 @@
+use chrono::{Duration, TimeZone, Utc};
+use stock::{
+    Bar,
+    indicators::volume::{MIN_AVERAGE_VOLUME, relative, spike},
+};
+
+/// Daily bars with these volumes, oldest first.
+fn bars(volumes: &[f64]) -> Vec<Bar> {
+    let start = Utc.with_ymd_and_hms(2024, 7, 1, 4, 0, 0).unwrap();
+    volumes
+        .iter()
+        .enumerate()
+        .map(|(i, &volume)| Bar {
+            timestamp: start + Duration::days(i as i64),
+            open: 10.0,
+            high: 10.0,
+            low: 10.0,
+            close: 10.0 + i as f64,
+            volume,
+            trade_count: None,
+            vwap: None,
+        })
+        .collect()
+}
+
+#[test]
+fn the_latest_bar_is_left_out_of_its_own_average() {
+    let found = spike(
+        &bars(&[100_000.0, 200_000.0, 300_000.0, 1_000_000.0]),
+        3,
+        3.0,
+    )
+    .expect("5x the trailing average");
+
+    assert_eq!(found.average, 200_000.0);
+    assert_eq!(found.volume, 1_000_000.0);
+    assert_eq!(found.ratio, 5.0);
+    assert_eq!(found.close, 13.0);
+}
+
+#[test]
+fn only_the_lookback_bars_before_the_latest_count() {
+    // the first bar falls outside a two-bar lookback
+    let found = relative(&bars(&[9_000_000.0, 100_000.0, 100_000.0, 400_000.0]), 2).unwrap();
+    assert_eq!(found.average, 100_000.0);
+    assert_eq!(found.ratio, 4.0);
+}
+
+#[test]
+fn a_thin_average_is_not_a_spike() {
+    // a ratio of 100 off an average under the floor means nothing
+    let thin = bars(&[100.0, 100.0, 100.0, 10_000.0]);
+    assert_eq!(relative(&thin, 3), None);
+    assert_eq!(spike(&thin, 3, 3.0), None);
+
+    let floor = bars(&[MIN_AVERAGE_VOLUME; 4]);
+    assert_eq!(relative(&floor, 3).unwrap().ratio, 1.0);
+}
+
+#[test]
+fn below_the_threshold_or_short_of_history_is_none() {
+    let history = bars(&[100_000.0, 100_000.0, 100_000.0, 290_000.0]);
+    assert_eq!(spike(&history, 3, 3.0), None);
+    assert!(spike(&history, 3, 2.5).is_some());
+
+    assert_eq!(relative(&history, 4), None);
+    assert_eq!(relative(&history, 0), None);
+    assert_eq!(relative(&[], 3), None);
+}
+
+#[test]
+fn a_partial_session_is_judged_on_what_has_traded() {
+    // an hour into the session, the latest bar has a fraction of a day's volume
+    let early = bars(&[200_000.0, 200_000.0, 200_000.0, 50_000.0]);
+    assert_eq!(spike(&early, 3, 3.0), None);
+
+    // by midday it has already traded more than three full days' worth
+    let midday = bars(&[200_000.0, 200_000.0, 200_000.0, 650_000.0]);
+    let found = spike(&midday, 3, 3.0).unwrap();
+    assert_eq!(found.ratio, 3.25);
+}
@@ -0,0 +1,58 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use stock::{calendar::session_date, indicators::vwap::cumulative};
+
+fn day(d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 7, d).unwrap()
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+    }
+}
+
+#[test]
+fn cumulates_within_a_session() {
+    let prices = [10.0, 11.0, 12.0];
+    let volumes = [100.0, 300.0, 100.0];
+    let sessions = [day(1); 3];
+
+    // (1000) / 100, (1000 + 3300) / 400, (4300 + 1200) / 500
+    assert_close(
+        &cumulative(&prices, &volumes, &sessions),
+        &[10.0, 10.75, 11.0],
+    );
+}
+
+#[test]
+fn resets_at_each_session() {
+    let prices = [10.0, 20.0, 30.0, 40.0];
+    let volumes = [1.0, 1.0, 1.0, 3.0];
+    let sessions = [day(1), day(1), day(2), day(2)];
+
+    assert_close(
+        &cumulative(&prices, &volumes, &sessions),
+        &[10.0, 15.0, 30.0, 37.5],
+    );
+}
+
+#[test]
+fn zero_volume_bars() {
+    let prices = [10.0, 12.0, 14.0];
+    let volumes = [0.0, 2.0, 0.0];
+    let out = cumulative(&prices, &volumes, &[day(1); 3]);
+
+    assert!(out[0].is_nan());
+    assert_eq!(out[1], 12.0);
+    assert_eq!(out[2], 12.0);
+}
+
+#[test]
+fn after_hours_stay_in_their_session() {
+    // 19:59 EDT and 04:00 EDT the next morning
+    let late = Utc.with_ymd_and_hms(2024, 7, 1, 23, 59, 0).unwrap();
+    let early = Utc.with_ymd_and_hms(2024, 7, 2, 8, 0, 0).unwrap();
+    assert_eq!(session_date(late), day(1));
+    assert_eq!(session_date(early), day(2));
+}
@@ -0,0 +1,111 @@
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::{Value, json};
+use stock::{PriceSource, Session, Timeframe, failover::is_retryable, yahoo::YahooSource};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path, query_param},
+};
+
+async fn yahoo() -> (MockServer, YahooSource) {
+    let server = MockServer::start().await;
+    let source = YahooSource::new(server.uri()).expect("yahoo source");
+    (server, source)
+}
+
+/// A chart of three daily bars stamped at the open, the middle one empty.
+fn chart() -> Value {
+    let open = |day: u32| {
+        Utc.with_ymd_and_hms(2025, 1, day, 14, 30, 0)
+            .unwrap()
+            .timestamp()
+    };
+    json!({
+        "chart": {
+            "result": [{
+                "meta": { "regularMarketPrice": 103.5, "regularMarketTime": open(8) + 3600 },
+                "timestamp": [open(6), open(7), open(8)],
+                "indicators": { "quote": [{
+                    "open": [100.0, null, 102.0],
+                    "high": [101.0, null, 104.0],
+                    "low": [99.0, null, 101.0],
+                    "close": [100.5, null, 103.0],
+                    "volume": [1000, null, 1200]
+                }] }
+            }],
+            "error": null
+        }
+    })
+}
+
+#[tokio::test]
+async fn daily_bars_skip_empty_ones_and_are_stamped_at_midnight() {
+    let (server, source) = yahoo().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/BRK-B"))
+        .and(query_param("interval", "1d"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chart()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let bars = source
+        .fetch_price(
+            "BRK.B",
+            Duration::days(30),
+            Timeframe::Day1,
+            100,
+            false,
+            Session::Regular,
+        )
+        .await
+        .unwrap();
+
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    assert_eq!(closes, [100.5, 103.0]);
+    // midnight in New York, as Alpaca stamps its daily bars
+    assert_eq!(
+        bars[0].timestamp,
+        Utc.with_ymd_and_hms(2025, 1, 6, 5, 0, 0).unwrap()
+    );
+    assert_eq!(bars[1].volume, 1200.0);
+}
+
+#[tokio::test]
+async fn snapshots_come_from_the_chart_header() {
+    let (server, source) = yahoo().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chart()))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/NOPE"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "chart": { "result": null, "error": { "code": "Not Found", "description": "No data found" } }
+        })))
+        .mount(&server)
+        .await;
+
+    let snapshots = source
+        .fetch_prices(&["AAPL".to_string(), "NOPE".to_string()])
+        .await
+        .unwrap();
+
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots["AAPL"].price(), Some(103.5));
+}
+
+#[tokio::test]
+async fn an_outage_fails_the_request_for_the_next_source() {
+    let (server, source) = yahoo().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let err = source
+        .fetch_prices(&["AAPL".to_string()])
+        .await
+        .unwrap_err();
+    assert!(is_retryable(&err), "{err:?}");
+}